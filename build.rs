@@ -0,0 +1,348 @@
+//! Generates code from the output of the Zephyr build.
+//!
+//! The arms of `device_dt_get!` are generated from the devicetree header, which is
+//! `ZEPHYR_DEVICETREE_HEADER` if set, otherwise it is searched in the build directory
+//! `ZEPHYR_BINARY_DIR`. Without a header, the macro fails to compile for every node.
+//!
+//! The options of the Kconfig configuration `.config` (`ZEPHYR_DOTCONFIG` or in the build
+//! directory) become constants of the `kconfig` module, the enabled boolean options also
+//! `zephyr_config_*` cfg flags.
+//!
+//! The versions of the application and the kernel are read from `app_version.h` and `version.h`
+//! in the build directory for the `version` module, together with the time of the build
+//! (`SOURCE_DATE_EPOCH` for reproducible builds).
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Location of the Kconfig configuration within the build directory.
+const DOTCONFIG_PATH: &str = "zephyr/.config";
+
+/// Options the wrappers are compiled in for with `zephyr_config_*` cfg flags. Their flags are
+/// declared even if the configuration does not list them, e.g. for unmet dependencies.
+const CHECKED_OPTIONS: &[&str] = &[
+    "DYNAMIC_THREAD",
+    "NET_STATISTICS_ICMP",
+    "NET_STATISTICS_IPV4",
+    "NET_STATISTICS_IPV6",
+    "NET_STATISTICS_TCP",
+    "NET_STATISTICS_UDP",
+    "RTIO_CONSUME_SEM",
+];
+
+/// Locations of the generated headers within the build directory, for newer and older Zephyr
+/// versions.
+const GENERATED_DIRS: &[&str] = &[
+    "zephyr/include/generated/zephyr",
+    "zephyr/include/generated",
+];
+
+/// Path of the generated header `name` in the build directory.
+fn generated_header(name: &str) -> Option<PathBuf> {
+    let build_dir = PathBuf::from(env::var_os("ZEPHYR_BINARY_DIR")?);
+    GENERATED_DIRS
+        .iter()
+        .map(|dir| build_dir.join(dir).join(name))
+        .find(|path| path.exists())
+}
+
+fn header_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("ZEPHYR_DEVICETREE_HEADER") {
+        return Some(PathBuf::from(path));
+    }
+    generated_header("devicetree_generated.h")
+}
+
+/// Whether `name` can be matched as an identifier by the macro.
+fn is_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    let starts_ok = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_');
+    starts_ok && chars.all(|c| c.is_ascii_alphanumeric() || c == '_') && name != "_"
+}
+
+/// Writes an arm of the macro expanding to the device of the node with `ord`.
+fn arm(out: &mut String, pattern: &str, ord: u32) {
+    writeln!(
+        out,
+        "    ({}) => {{ $crate::__device_dts_ord!(\"__device_dts_ord_{}\") }};",
+        pattern, ord
+    )
+    .unwrap();
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=ZEPHYR_DEVICETREE_HEADER");
+    println!("cargo:rerun-if-env-changed=ZEPHYR_DOTCONFIG");
+    println!("cargo:rerun-if-env-changed=ZEPHYR_BINARY_DIR");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    devicetree(&out_dir);
+    kconfig(&out_dir);
+    version(&out_dir);
+}
+
+fn devicetree(out_dir: &Path) {
+    let mut ords = BTreeMap::new();
+    let mut node_labels = BTreeMap::new();
+    let mut aliases = BTreeMap::new();
+    let mut chosen = BTreeMap::new();
+
+    if let Some(path) = header_path() {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let header = fs::read_to_string(&path).expect("reading the devicetree header");
+        for line in header.lines() {
+            let mut parts = line.split_whitespace();
+            if parts.next() != Some("#define") {
+                continue;
+            }
+            let (name, value) = match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) => (name, value),
+                _ => continue,
+            };
+
+            if let Some(node) = name.strip_suffix("_ORD") {
+                if let Ok(ord) = value.parse::<u32>() {
+                    ords.insert(node.to_string(), ord);
+                }
+            } else if !value.starts_with("DT_N") {
+                continue;
+            } else if let Some(label) = name.strip_prefix("DT_N_NODELABEL_") {
+                node_labels.insert(label.to_string(), value.to_string());
+            } else if let Some(alias) = name.strip_prefix("DT_N_ALIAS_") {
+                aliases.insert(alias.to_string(), value.to_string());
+            } else if let Some(property) = name.strip_prefix("DT_CHOSEN_") {
+                chosen.insert(property.to_string(), value.to_string());
+            }
+        }
+    }
+
+    let mut arms = String::new();
+    for (label, node) in &node_labels {
+        if let Some(&ord) = ords.get(node) {
+            arm(&mut arms, &format!("{:?}", label), ord);
+            if is_ident(label) {
+                arm(&mut arms, &format!("nodelabel({})", label), ord);
+            }
+        }
+    }
+    for (kind, names) in [("alias", &aliases), ("chosen", &chosen)] {
+        for (name, node) in names {
+            if let Some(&ord) = ords.get(node) {
+                if is_ident(name) {
+                    arm(&mut arms, &format!("{}({})", kind, name), ord);
+                }
+            }
+        }
+    }
+
+    let out = out_dir.join("devicetree.rs");
+    let source = format!(
+        r#"/// Resolves a devicetree node to the `&'static Device` of its driver, the
+/// equivalent of `DEVICE_DT_GET`.
+///
+/// The node is named like in C with `DT_NODELABEL`, `DT_ALIAS` or `DT_CHOSEN`:
+/// `device_dt_get!(nodelabel(uart0))` (or short `device_dt_get!("uart0")`),
+/// `device_dt_get!(alias(led0))` and `device_dt_get!(chosen(zephyr_console))`. Unknown nodes fail
+/// to compile and nodes without a device (e.g. disabled ones) fail to link.
+#[macro_export]
+macro_rules! device_dt_get {{
+{}    ($($node:tt)*) => {{
+        compile_error!(concat!("no devicetree node ", stringify!($($node)*)))
+    }};
+}}
+"#,
+        arms
+    );
+    fs::write(out, source).expect("writing the devicetree macro");
+}
+
+fn dotconfig_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("ZEPHYR_DOTCONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let path = PathBuf::from(env::var_os("ZEPHYR_BINARY_DIR")?).join(DOTCONFIG_PATH);
+    Some(path).filter(|path| path.exists())
+}
+
+/// Value of a string option, which is quoted and escaped like a C string literal.
+fn unquote(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+    let mut unquoted = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            unquoted.extend(chars.next());
+        } else {
+            unquoted.push(c);
+        }
+    }
+    Some(unquoted)
+}
+
+/// Type and value of the constant of an option, `None` for options that are not set.
+fn constant(value: &str) -> Option<(&'static str, String)> {
+    if value == "y" {
+        Some(("bool", "true".to_string()))
+    } else if let Some(text) = unquote(value) {
+        Some(("&str", format!("{:?}", text)))
+    } else if let Some(hex) = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        u64::from_str_radix(hex, 16)
+            .ok()
+            .map(|number| ("u64", format!("0x{:x}", number)))
+    } else {
+        value
+            .parse::<i64>()
+            .ok()
+            .map(|number| ("i64", number.to_string()))
+    }
+}
+
+fn kconfig(out_dir: &Path) {
+    let mut constants = String::new();
+    for name in CHECKED_OPTIONS {
+        println!(
+            "cargo:rustc-check-cfg=cfg(zephyr_config_{})",
+            name.to_lowercase()
+        );
+    }
+
+    if let Some(path) = dotconfig_path() {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let dotconfig = fs::read_to_string(&path).expect("reading the Kconfig configuration");
+        for line in dotconfig.lines() {
+            // disabled boolean options are listed as `# CONFIG_FOO is not set`
+            if let Some(name) = line
+                .strip_prefix("# ")
+                .and_then(|line| line.strip_suffix(" is not set"))
+                .and_then(|name| name.strip_prefix("CONFIG_"))
+                .filter(|name| is_ident(name))
+            {
+                println!(
+                    "cargo:rustc-check-cfg=cfg(zephyr_config_{})",
+                    name.to_lowercase()
+                );
+                writeln!(constants, "pub const CONFIG_{}: bool = false;", name).unwrap();
+                continue;
+            }
+
+            let (name, value) = match line.strip_prefix("CONFIG_").and_then(|l| l.split_once('=')) {
+                Some((name, value)) if is_ident(name) => (name, value),
+                _ => continue,
+            };
+            let flag = format!("zephyr_config_{}", name.to_lowercase());
+            println!("cargo:rustc-check-cfg=cfg({})", flag);
+            if value == "y" {
+                println!("cargo:rustc-cfg={}", flag);
+            }
+            if let Some((ty, value)) = constant(value) {
+                writeln!(constants, "pub const CONFIG_{}: {} = {};", name, ty, value).unwrap();
+            }
+        }
+    }
+
+    fs::write(out_dir.join("kconfig.rs"), constants).expect("writing the Kconfig constants");
+}
+
+/// Values of the `#define`s of the generated header `name`, empty without the header.
+fn defines(name: &str) -> BTreeMap<String, String> {
+    let mut defines = BTreeMap::new();
+    if let Some(path) = generated_header(name) {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let header = fs::read_to_string(&path).expect("reading a generated header");
+        for line in header.lines() {
+            let line = match line.trim_start().strip_prefix("#define") {
+                Some(line) => line.trim(),
+                None => continue,
+            };
+            if let Some((name, value)) = line.split_once(char::is_whitespace) {
+                defines.insert(name.to_string(), value.trim().to_string());
+            }
+        }
+    }
+    defines
+}
+
+/// Writes the string constant `name` documented with `doc`, `None` if `value` is not defined.
+/// Quoted values are unquoted, others are taken as is.
+fn string_constant(out: &mut String, name: &str, doc: &str, value: Option<&String>) {
+    let value = value.map(|value| unquote(value).unwrap_or_else(|| value.clone()));
+    writeln!(
+        out,
+        "/// {}\npub const {}: Option<&str> = {:?};",
+        doc, name, value
+    )
+    .unwrap();
+}
+
+fn version(out_dir: &Path) {
+    let app = defines("app_version.h");
+    let kernel = defines("version.h");
+    let mut constants = String::new();
+
+    let number = |name: &str| app.get(name).and_then(|value| value.parse::<u8>().ok());
+    let app_version = match (
+        number("APP_VERSION_MAJOR"),
+        number("APP_VERSION_MINOR"),
+        number("APP_PATCHLEVEL"),
+    ) {
+        (Some(major), Some(minor), Some(patch)) => format!(
+            "Some(AppVersion {{ major: {}, minor: {}, patch: {}, tweak: {} }})",
+            major,
+            minor,
+            patch,
+            number("APP_TWEAK").unwrap_or(0)
+        ),
+        _ => "None".to_string(),
+    };
+    writeln!(
+        constants,
+        "/// Version of the application.\npub const APP_VERSION: Option<AppVersion> = {};",
+        app_version
+    )
+    .unwrap();
+    string_constant(
+        &mut constants,
+        "APP_VERSION_STRING",
+        "Version of the application as string, `APP_VERSION_STRING`.",
+        app.get("APP_VERSION_STRING"),
+    );
+    string_constant(
+        &mut constants,
+        "APP_BUILD_VERSION",
+        "Build version of the application, `APP_BUILD_VERSION`.",
+        app.get("APP_BUILD_VERSION"),
+    );
+    string_constant(
+        &mut constants,
+        "KERNEL_BUILD_VERSION",
+        "Build version of the kernel, `BUILD_VERSION`.",
+        kernel.get("BUILD_VERSION"),
+    );
+
+    let timestamp = match env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch
+            .parse()
+            .expect("SOURCE_DATE_EPOCH is a number of seconds"),
+        Err(_) => SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs()),
+    };
+    writeln!(
+        constants,
+        "/// Time of the build in seconds since the Unix epoch.\n\
+         pub const BUILD_TIMESTAMP: u64 = {};",
+        timestamp
+    )
+    .unwrap();
+
+    fs::write(out_dir.join("version.rs"), constants).expect("writing the version constants");
+}
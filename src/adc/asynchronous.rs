@@ -0,0 +1,257 @@
+//! Asynchronous sampling of the Zephyr ADC API, requires `CONFIG_ADC_ASYNC`.
+//!
+//! [Adc::read_async] starts a sequence and returns immediately, completion is signalled through a
+//! `k_poll_signal`. [ContinuousSampler] builds on it to sample a sequence over and over into two
+//! alternating buffers, so one buffer can be processed while the other one is filled.
+//!
+//! The buffers are owned by the pending read, so they cannot be freed while the driver is still
+//! writing to them.
+
+use super::{Adc, AdcSample, AdcSequence, Device, CONTEXT};
+use crate::time::Timeout;
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use zephyr_sys::raw::{
+    adc_sequence as ZAdcSequence, adc_sequence_options as ZAdcSequenceOptions, k_poll_event,
+    k_poll_signal,
+};
+
+/// Equivalent of `K_POLL_TYPE_SIGNAL`, the macro is not available in the bindings.
+const K_POLL_TYPE_SIGNAL: u32 = 1 << (zephyr_sys::raw::_poll_types_bits__POLL_TYPE_SIGNAL - 1);
+
+/// Wrapper to the `adc_read_async` syscall, raising `signal` when the sequence is done.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// The sequence, its options and buffer and the signal MUST stay valid until the signal is raised.
+pub unsafe fn read_async(
+    device: &Device,
+    sequence: &ZAdcSequence,
+    signal: *mut k_poll_signal,
+) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::adc_read_async(
+        device as *const Device,
+        sequence as *const ZAdcSequence,
+        signal,
+    );
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// A pending asynchronous read of an [AdcSequence].
+///
+/// Dropping a pending read blocks until the driver is done with the buffer.
+pub struct AdcRead<'a, T: AdcSample> {
+    // everything referenced by the driver is boxed so it does not move with the read
+    signal: Box<k_poll_signal>,
+    _options: Option<Box<ZAdcSequenceOptions>>,
+    _sequence: Box<ZAdcSequence>,
+    buffer: Option<Vec<T>>,
+    _adc: PhantomData<&'a mut Adc>,
+}
+
+impl<'a, T: AdcSample> AdcRead<'a, T> {
+    /// `device` MUST be an ADC device that is not used by anyone else for `'a`.
+    unsafe fn start(
+        device: &'static Device,
+        sequence: &AdcSequence,
+        mut buffer: Vec<T>,
+    ) -> ZephyrResult<Self> {
+        if buffer.len() < sequence.sample_count() {
+            return Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &CONTEXT,
+            ));
+        }
+
+        let mut signal: Box<k_poll_signal> = Box::new(core::mem::zeroed());
+        zephyr_sys::syscalls::any::k_poll_signal_init(&mut *signal as *mut _);
+        let options = sequence.options().map(Box::new);
+        let raw = Box::new(sequence.raw(options.as_deref(), &mut buffer[..]));
+        read_async(device, &raw, &mut *signal as *mut _)?;
+
+        Ok(AdcRead {
+            signal,
+            _options: options,
+            _sequence: raw,
+            buffer: Some(buffer),
+            _adc: PhantomData,
+        })
+    }
+
+    fn signal_ptr(&self) -> *mut k_poll_signal {
+        &*self.signal as *const k_poll_signal as *mut k_poll_signal
+    }
+
+    /// Result of the read if it is done. This is a wrapper to the `k_poll_signal_check` syscall.
+    pub fn result(&self) -> Option<ZephyrResult<()>> {
+        let mut signaled: core::ffi::c_uint = 0;
+        let mut result: core::ffi::c_int = 0;
+        unsafe {
+            zephyr_sys::syscalls::any::k_poll_signal_check(
+                self.signal_ptr(),
+                &mut signaled as *mut _,
+                &mut result as *mut _,
+            )
+        };
+
+        match (signaled, result) {
+            (0, _) => None,
+            (_, 0) => Some(Ok(())),
+            (_, errno) => Some(Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))),
+        }
+    }
+
+    /// Returns `true` if the driver is done with the sequence.
+    pub fn is_done(&self) -> bool {
+        self.result().is_some()
+    }
+
+    /// Wait up to `timeout` for the signal. This is a wrapper to the `k_poll` syscall.
+    fn poll(&self, timeout: Timeout) -> ZephyrResult<()> {
+        let mut event: k_poll_event = unsafe { core::mem::zeroed() };
+        let errno = unsafe {
+            zephyr_sys::raw::k_poll_event_init(
+                &mut event as *mut k_poll_event,
+                K_POLL_TYPE_SIGNAL,
+                zephyr_sys::raw::k_poll_modes_K_POLL_MODE_NOTIFY_ONLY as _,
+                self.signal_ptr() as *mut core::ffi::c_void,
+            );
+            zephyr_sys::syscalls::any::k_poll(&mut event as *mut k_poll_event, 1, timeout)
+        };
+
+        if errno == 0 {
+            Ok(())
+        } else {
+            Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+        }
+    }
+
+    /// Wait up to `timeout` for the read to finish and return the buffer with the samples.
+    ///
+    /// If the timeout expires the read is returned again, so waiting can be continued.
+    pub fn wait(mut self, timeout: Timeout) -> Result<ZephyrResult<Vec<T>>, Self> {
+        if self.poll(timeout).is_err() {
+            return Err(self);
+        }
+
+        let result = self.result().unwrap_or_else(|| {
+            Err(ZephyrError::new_with_context(ErrorNumber::Io, &CONTEXT))
+        });
+        let buffer = self.buffer.take().expect("buffer of a pending read");
+        Ok(result.map(|_| buffer))
+    }
+}
+
+impl<'a, T: AdcSample> Drop for AdcRead<'a, T> {
+    fn drop(&mut self) {
+        if self.buffer.is_some() {
+            let _ = self.poll(crate::time::FOREVER);
+        }
+    }
+}
+
+impl Adc {
+    /// Start sampling `sequence` into `buffer` without blocking.
+    ///
+    /// Fails with [ErrorNumber::InvalidArgument] if `buffer` cannot hold all samplings.
+    pub fn read_async<T: AdcSample>(
+        &mut self,
+        sequence: &AdcSequence,
+        buffer: Vec<T>,
+    ) -> ZephyrResult<AdcRead<T>> {
+        // device is required to be an ADC device in constructor, and is borrowed by the read
+        unsafe { AdcRead::start(self.device, sequence, buffer) }
+    }
+
+    /// Creates a [ContinuousSampler] sampling `sequence` over and over.
+    pub fn continuous<T: AdcSample>(
+        &mut self,
+        sequence: AdcSequence,
+    ) -> ContinuousSampler<T> {
+        ContinuousSampler::new(self, sequence)
+    }
+}
+
+/// Repeated sampling of a sequence into two alternating buffers.
+///
+/// While the samples of one buffer are handed out by [ContinuousSampler::next], the driver fills
+/// the other one. The sequence is restarted as soon as the previous one is collected, so the
+/// interval between two sequences is longer than [AdcSequence::interval_us].
+pub struct ContinuousSampler<'a, T: AdcSample> {
+    device: &'static Device,
+    sequence: AdcSequence,
+    pending: Option<AdcRead<'a, T>>,
+    ready: Vec<T>,
+    spare: Vec<T>,
+}
+
+impl<'a, T: AdcSample> ContinuousSampler<'a, T> {
+    fn new(adc: &'a mut Adc, sequence: AdcSequence) -> Self {
+        let samples = sequence.sample_count();
+        ContinuousSampler {
+            device: adc.device,
+            sequence,
+            pending: None,
+            ready: vec![T::default(); samples],
+            spare: vec![T::default(); samples],
+        }
+    }
+
+    fn start(&mut self) -> ZephyrResult<()> {
+        let mut buffer = core::mem::take(&mut self.spare);
+        if buffer.is_empty() {
+            // the buffer is lost if a sequence failed
+            buffer = vec![T::default(); self.sequence.sample_count()];
+        }
+
+        // the sampler mutably borrows the Adc for 'a, so it is used by no one else
+        let read = unsafe { AdcRead::start(self.device, &self.sequence, buffer) }?;
+        self.pending = Some(read);
+        Ok(())
+    }
+
+    /// Wait up to `timeout` for the next buffer of samples.
+    ///
+    /// The first call starts the sampling. Fails with the error of the driver on failed sequences
+    /// and with [ErrorNumber::TryAgain] if the timeout expired.
+    pub fn next(&mut self, timeout: Timeout) -> ZephyrResult<&[T]> {
+        if self.pending.is_none() {
+            self.start()?;
+        }
+
+        let read = self.pending.take().expect("sampling started");
+        let buffer = match read.wait(timeout) {
+            Ok(result) => result?,
+            Err(read) => {
+                self.pending = Some(read);
+                return Err(ZephyrError::new_with_context(
+                    ErrorNumber::TryAgain,
+                    &CONTEXT,
+                ));
+            }
+        };
+
+        // the buffer handed out last time is filled next
+        self.spare = core::mem::replace(&mut self.ready, buffer);
+        self.start()?;
+        Ok(&self.ready)
+    }
+
+    /// Stop the sampling, waiting for the pending sequence to finish.
+    pub fn stop(&mut self) {
+        if let Some(read) = self.pending.take() {
+            if let Ok(Ok(buffer)) = read.wait(crate::time::FOREVER) {
+                self.spare = buffer;
+            }
+        }
+    }
+}
@@ -0,0 +1,482 @@
+//! Syscalls and high level wrappers for the Zephyr ADC API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::adc::{Adc, AdcChannel, AdcSequence, Gain, Reference};
+//!# fn battery(adc: &mut Adc) {
+//! let channel = AdcChannel::new(0, Gain::OneSixth, Reference::Internal);
+//! adc.channel_setup(&channel).expect("setting up channel");
+//!
+//! let sequence = AdcSequence::new(channel.mask(), 12);
+//! let mut sample = [0_i16; 1];
+//! adc.read(&sequence, &mut sample).expect("sampling channel");
+//! let millivolts = adc.to_millivolts(&channel, 12, sample[0] as i32);
+//!# }
+//! ```
+
+use crate::device::{class, TypedDevice};
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use core::ffi::c_void;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::{
+    adc_channel_cfg as ZAdcChannelConfig, adc_driver_api as AdcDriverApi,
+    adc_sequence as ZAdcSequence, adc_sequence_options as ZAdcSequenceOptions,
+};
+
+#[cfg(feature = "adc-async")]
+pub mod asynchronous;
+
+const CONTEXT: AdcWrapperContext = AdcWrapperContext {};
+
+/// Gain applied to the input of an ADC channel.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Gain {
+    OneSixth,
+    OneFifth,
+    OneQuarter,
+    OneThird,
+    TwoFifths,
+    OneHalf,
+    TwoThirds,
+    FourFifths,
+    One,
+    Two,
+    Three,
+    Four,
+    Six,
+    Eight,
+    Twelve,
+    Sixteen,
+    TwentyFour,
+    ThirtyTwo,
+    SixtyFour,
+    OneHundredTwentyEight,
+}
+
+impl Gain {
+    /// Multiplier and divisor to undo the gain, like `adc_gain_invert`.
+    fn inverse(&self) -> (i64, i64) {
+        match self {
+            Gain::OneSixth => (6, 1),
+            Gain::OneFifth => (5, 1),
+            Gain::OneQuarter => (4, 1),
+            Gain::OneThird => (3, 1),
+            Gain::TwoFifths => (5, 2),
+            Gain::OneHalf => (2, 1),
+            Gain::TwoThirds => (3, 2),
+            Gain::FourFifths => (5, 4),
+            Gain::One => (1, 1),
+            Gain::Two => (1, 2),
+            Gain::Three => (1, 3),
+            Gain::Four => (1, 4),
+            Gain::Six => (1, 6),
+            Gain::Eight => (1, 8),
+            Gain::Twelve => (1, 12),
+            Gain::Sixteen => (1, 16),
+            Gain::TwentyFour => (1, 24),
+            Gain::ThirtyTwo => (1, 32),
+            Gain::SixtyFour => (1, 64),
+            Gain::OneHundredTwentyEight => (1, 128),
+        }
+    }
+
+    /// Undo the gain on `value`. This is the equivalent of `adc_gain_invert`.
+    pub fn invert(&self, value: i32) -> i32 {
+        let (multiplier, divisor) = self.inverse();
+        (value as i64 * multiplier / divisor) as i32
+    }
+}
+
+impl From<Gain> for zephyr_sys::raw::adc_gain {
+    fn from(other: Gain) -> Self {
+        match other {
+            Gain::OneSixth => zephyr_sys::raw::adc_gain_ADC_GAIN_1_6,
+            Gain::OneFifth => zephyr_sys::raw::adc_gain_ADC_GAIN_1_5,
+            Gain::OneQuarter => zephyr_sys::raw::adc_gain_ADC_GAIN_1_4,
+            Gain::OneThird => zephyr_sys::raw::adc_gain_ADC_GAIN_1_3,
+            Gain::TwoFifths => zephyr_sys::raw::adc_gain_ADC_GAIN_2_5,
+            Gain::OneHalf => zephyr_sys::raw::adc_gain_ADC_GAIN_1_2,
+            Gain::TwoThirds => zephyr_sys::raw::adc_gain_ADC_GAIN_2_3,
+            Gain::FourFifths => zephyr_sys::raw::adc_gain_ADC_GAIN_4_5,
+            Gain::One => zephyr_sys::raw::adc_gain_ADC_GAIN_1,
+            Gain::Two => zephyr_sys::raw::adc_gain_ADC_GAIN_2,
+            Gain::Three => zephyr_sys::raw::adc_gain_ADC_GAIN_3,
+            Gain::Four => zephyr_sys::raw::adc_gain_ADC_GAIN_4,
+            Gain::Six => zephyr_sys::raw::adc_gain_ADC_GAIN_6,
+            Gain::Eight => zephyr_sys::raw::adc_gain_ADC_GAIN_8,
+            Gain::Twelve => zephyr_sys::raw::adc_gain_ADC_GAIN_12,
+            Gain::Sixteen => zephyr_sys::raw::adc_gain_ADC_GAIN_16,
+            Gain::TwentyFour => zephyr_sys::raw::adc_gain_ADC_GAIN_24,
+            Gain::ThirtyTwo => zephyr_sys::raw::adc_gain_ADC_GAIN_32,
+            Gain::SixtyFour => zephyr_sys::raw::adc_gain_ADC_GAIN_64,
+            Gain::OneHundredTwentyEight => zephyr_sys::raw::adc_gain_ADC_GAIN_128,
+        }
+    }
+}
+
+/// Reference voltage of an ADC channel.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Reference {
+    Vdd,
+    VddHalf,
+    VddThird,
+    VddQuarter,
+    Internal,
+    External0,
+    External1,
+}
+
+impl From<Reference> for zephyr_sys::raw::adc_reference {
+    fn from(other: Reference) -> Self {
+        match other {
+            Reference::Vdd => zephyr_sys::raw::adc_reference_ADC_REF_VDD_1,
+            Reference::VddHalf => zephyr_sys::raw::adc_reference_ADC_REF_VDD_1_2,
+            Reference::VddThird => zephyr_sys::raw::adc_reference_ADC_REF_VDD_1_3,
+            Reference::VddQuarter => zephyr_sys::raw::adc_reference_ADC_REF_VDD_1_4,
+            Reference::Internal => zephyr_sys::raw::adc_reference_ADC_REF_INTERNAL,
+            Reference::External0 => zephyr_sys::raw::adc_reference_ADC_REF_EXTERNAL0,
+            Reference::External1 => zephyr_sys::raw::adc_reference_ADC_REF_EXTERNAL1,
+        }
+    }
+}
+
+/// Acquisition time of an ADC channel, values are limited to 14 bits.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AcquisitionTime {
+    /// The default acquisition time of the driver.
+    Default,
+    Ticks(u16),
+    Microseconds(u16),
+    Nanoseconds(u16),
+}
+
+impl From<AcquisitionTime> for u16 {
+    /// Equivalent of the `ADC_ACQ_TIME` macro.
+    fn from(other: AcquisitionTime) -> Self {
+        let (unit, value) = match other {
+            AcquisitionTime::Default => return zephyr_sys::raw::ADC_ACQ_TIME_DEFAULT as u16,
+            AcquisitionTime::Ticks(value) => (zephyr_sys::raw::ADC_ACQ_TIME_TICKS, value),
+            AcquisitionTime::Microseconds(value) => {
+                (zephyr_sys::raw::ADC_ACQ_TIME_MICROSECONDS, value)
+            }
+            AcquisitionTime::Nanoseconds(value) => {
+                (zephyr_sys::raw::ADC_ACQ_TIME_NANOSECONDS, value)
+            }
+        };
+        ((unit as u16) << 14) | (value & 0x3FFF)
+    }
+}
+
+/// Configuration of a single ADC channel.
+#[derive(Copy, Clone, Debug)]
+pub struct AdcChannel {
+    /// Identifier of the channel, between 0 and 31.
+    pub id: u8,
+    pub gain: Gain,
+    pub reference: Reference,
+    pub acquisition_time: AcquisitionTime,
+    pub differential: bool,
+    /// Positive input of the channel, requires `CONFIG_ADC_CONFIGURABLE_INPUTS`.
+    #[cfg(feature = "adc-configurable-inputs")]
+    pub input_positive: u8,
+    /// Negative input of a differential channel, requires `CONFIG_ADC_CONFIGURABLE_INPUTS`.
+    #[cfg(feature = "adc-configurable-inputs")]
+    pub input_negative: u8,
+    /// Voltage of the reference in millivolts, used for references other than
+    /// [Reference::Internal] when converting to millivolts.
+    pub vref_mv: Option<u16>,
+}
+
+impl AdcChannel {
+    /// Creates a single ended channel with the default acquisition time.
+    pub fn new(id: u8, gain: Gain, reference: Reference) -> Self {
+        AdcChannel {
+            id,
+            gain,
+            reference,
+            acquisition_time: AcquisitionTime::Default,
+            differential: false,
+            #[cfg(feature = "adc-configurable-inputs")]
+            input_positive: 0,
+            #[cfg(feature = "adc-configurable-inputs")]
+            input_negative: 0,
+            vref_mv: None,
+        }
+    }
+
+    /// Bit of the channel in [AdcSequence::channels], `0` for ids above 31, which
+    /// [channel_setup] rejects.
+    pub fn mask(&self) -> u32 {
+        1_u32.checked_shl(self.id as u32).unwrap_or(0)
+    }
+}
+
+impl From<&AdcChannel> for ZAdcChannelConfig {
+    fn from(other: &AdcChannel) -> Self {
+        // the configuration contains bitfields and optional fields, so start from all zeros
+        let mut config: ZAdcChannelConfig = unsafe { core::mem::zeroed() };
+        config.gain = other.gain.into();
+        config.reference = other.reference.into();
+        config.acquisition_time = other.acquisition_time.into();
+        config.set_channel_id(other.id as _);
+        config.set_differential(other.differential as _);
+        #[cfg(feature = "adc-configurable-inputs")]
+        {
+            config.input_positive = other.input_positive;
+            config.input_negative = other.input_negative;
+        }
+        config
+    }
+}
+
+/// Sequence of samplings of one or more channels.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AdcSequence {
+    /// Bit mask of the sampled channels, see [AdcChannel::mask].
+    pub channels: u32,
+    /// Resolution of the samples in bits.
+    pub resolution: u8,
+    /// Oversampling as power of two, e.g. `2` averages 4 samples.
+    pub oversampling: u8,
+    /// Calibrate the ADC before sampling, if supported.
+    pub calibrate: bool,
+    /// Number of samplings after the first one, the buffer must hold all samplings.
+    pub extra_samplings: u16,
+    /// Interval between the samplings in microseconds, `0` samples as fast as possible.
+    pub interval_us: u32,
+}
+
+impl AdcSequence {
+    /// Creates a sequence with a single sampling and no oversampling.
+    pub const fn new(channels: u32, resolution: u8) -> Self {
+        AdcSequence {
+            channels,
+            resolution,
+            oversampling: 0,
+            calibrate: false,
+            extra_samplings: 0,
+            interval_us: 0,
+        }
+    }
+
+    /// Number of samples of all samplings of the sequence.
+    pub fn sample_count(&self) -> usize {
+        self.channels.count_ones() as usize * (self.extra_samplings as usize + 1)
+    }
+
+    fn options(&self) -> Option<ZAdcSequenceOptions> {
+        if self.extra_samplings == 0 && self.interval_us == 0 {
+            return None;
+        }
+
+        Some(ZAdcSequenceOptions {
+            interval_us: self.interval_us,
+            callback: None,
+            user_data: core::ptr::null_mut(),
+            extra_samplings: self.extra_samplings,
+        })
+    }
+
+    /// Build the raw sequence sampling into `buffer`. `options` must outlive the returned sequence.
+    fn raw<T: AdcSample>(
+        &self,
+        options: Option<&ZAdcSequenceOptions>,
+        buffer: &mut [T],
+    ) -> ZAdcSequence {
+        ZAdcSequence {
+            options: options.map_or(core::ptr::null(), |options| options as *const _),
+            channels: self.channels,
+            buffer: buffer.as_mut_ptr() as *mut c_void,
+            buffer_size: core::mem::size_of_val(buffer),
+            resolution: self.resolution,
+            oversampling: self.oversampling,
+            calibrate: self.calibrate,
+        }
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for i16 {}
+    impl Sealed for u16 {}
+    impl Sealed for i32 {}
+    impl Sealed for u32 {}
+}
+
+/// Types the ADC drivers write samples as. Most drivers use 16 bit samples, resolutions above 16
+/// bits require 32 bit samples.
+pub trait AdcSample: Copy + Default + private::Sealed {}
+
+impl AdcSample for i16 {}
+impl AdcSample for u16 {}
+impl AdcSample for i32 {}
+impl AdcSample for u32 {}
+
+/// Wrapper to the `adc_channel_setup` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// Fails with [ErrorNumber::InvalidArgument] if the id of the channel is above 31.
+pub unsafe fn channel_setup(device: &Device, channel: &AdcChannel) -> ZephyrResult<()> {
+    if channel.id > 31 {
+        return Err(ZephyrError::new_with_context(
+            ErrorNumber::InvalidArgument,
+            &CONTEXT,
+        ));
+    }
+    let config: ZAdcChannelConfig = channel.into();
+    let errno = zephyr_sys::syscalls::any::adc_channel_setup(
+        device as *const Device,
+        &config as *const ZAdcChannelConfig,
+    );
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `adc_read` syscall, sampling `sequence` into `buffer`.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn read<T: AdcSample>(
+    device: &Device,
+    sequence: &AdcSequence,
+    buffer: &mut [T],
+) -> ZephyrResult<()> {
+    let options = sequence.options();
+    let sequence = sequence.raw(options.as_ref(), buffer);
+    let errno = zephyr_sys::syscalls::any::adc_read(
+        device as *const Device,
+        &sequence as *const ZAdcSequence,
+    );
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Equivalent of the `adc_ref_internal` API function, returns the internal reference voltage in
+/// millivolts or `None` if it is unknown.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+pub unsafe fn ref_internal(device: &Device) -> Option<u16> {
+    match (device.api as *const AdcDriverApi).as_ref() {
+        Some(api) if api.ref_internal != 0 => Some(api.ref_internal),
+        _ => None,
+    }
+}
+
+/// Convert the `raw` sample to millivolts. This is the equivalent of `adc_raw_to_millivolts`.
+///
+/// The product of the sample and the reference is calculated in [i64] and the result saturates.
+pub fn raw_to_millivolts(reference_mv: i32, gain: Gain, resolution: u8, raw: i32) -> i32 {
+    let (multiplier, divisor) = gain.inverse();
+    let millivolts = raw as i64 * reference_mv as i64 * multiplier / divisor;
+    // shifting by more than 63 bits gives the same result as by 63
+    let millivolts = millivolts >> resolution.min(63);
+    millivolts.max(i32::MIN as i64).min(i32::MAX as i64) as i32
+}
+
+/// High level wrapper for an ADC.
+pub struct Adc {
+    device: &'static Device,
+}
+
+impl Adc {
+    /// Creates a new [Adc] on the current interface.
+    ///
+    /// `device` MUST be an ADC device. If `device` is not an ADC device the behaviour when calling
+    /// any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Adc { device }
+    }
+
+    /// Creates a new [Adc] from a device checked to be an ADC device.
+    pub fn from_device(device: TypedDevice<class::Adc>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Adc::new(device.device()) }
+    }
+
+    /// Configure `channel` before using it in a sequence.
+    pub fn channel_setup(&mut self, channel: &AdcChannel) -> ZephyrResult<()> {
+        // device is required to be an ADC device in constructor
+        unsafe { channel_setup(self.device, channel) }
+    }
+
+    /// Sample `sequence` into `buffer`, blocking until all samplings are done.
+    ///
+    /// Fails with [ErrorNumber::InvalidArgument] if `buffer` cannot hold all samplings.
+    pub fn read<T: AdcSample>(
+        &mut self,
+        sequence: &AdcSequence,
+        buffer: &mut [T],
+    ) -> ZephyrResult<()> {
+        if buffer.len() < sequence.sample_count() {
+            return Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &CONTEXT,
+            ));
+        }
+
+        // device is required to be an ADC device in constructor
+        unsafe { read(self.device, sequence, buffer) }
+    }
+
+    /// Internal reference voltage in millivolts, if known.
+    pub fn reference_internal(&self) -> Option<u16> {
+        // device is required to be an ADC device in constructor
+        unsafe { ref_internal(self.device) }
+    }
+
+    /// Convert a `raw` sample of `channel` taken with `resolution` to millivolts. This is the
+    /// equivalent of `adc_raw_to_millivolts_dt`.
+    ///
+    /// Fails with [ErrorNumber::NotSupported] if the voltage of the channel reference is unknown
+    /// and with [ErrorNumber::InvalidArgument] if `resolution` is `0` for a differential channel.
+    pub fn to_millivolts(
+        &self,
+        channel: &AdcChannel,
+        resolution: u8,
+        raw: i32,
+    ) -> ZephyrResult<i32> {
+        let reference_mv = match channel.reference {
+            Reference::Internal => self.reference_internal(),
+            _ => channel.vref_mv,
+        }
+        .ok_or_else(|| ZephyrError::new_with_context(ErrorNumber::NotSupported, &CONTEXT))?;
+
+        // one bit of differential samples is the sign
+        let resolution = if channel.differential {
+            resolution.checked_sub(1).ok_or_else(|| {
+                ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT)
+            })?
+        } else {
+            resolution
+        };
+
+        Ok(raw_to_millivolts(
+            reference_mv as i32,
+            channel.gain,
+            resolution,
+            raw,
+        ))
+    }
+}
+
+#[derive(Debug)]
+struct AdcWrapperContext {}
+
+impl Context for AdcWrapperContext {
+    fn name(&self) -> &'static str {
+        "adc wrapper"
+    }
+}
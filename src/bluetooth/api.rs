@@ -8,13 +8,15 @@ use crate::bluetooth::le::{
 use crate::bluetooth::{CONTEXT, gatt};
 use crate::network::NetworkBufferSimple;
 use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::ffi::CString;
+use alloc::vec::Vec;
+use core::ffi::CStr;
+use core::marker::PhantomData;
+use core::mem::{replace, transmute};
+use core::ops::Deref;
+use core::ptr::{addr_of_mut, slice_from_raw_parts};
+use core::slice;
 use pretty_hex::simple_hex;
-use std::ffi::{CStr, CString};
-use std::marker::PhantomData;
-use std::mem::{replace, transmute};
-use std::ops::Deref;
-use std::ptr::slice_from_raw_parts;
-use std::slice;
 
 pub type BtReadyCallback = extern "C" fn(err: u32) -> ();
 
@@ -37,17 +39,17 @@ impl BtConnectionCallbacks {
         parameters_requested: Option<BtLeParametersRequestedCallback>,
         parameters_updated: Option<BtLeParametersUpdatedCallback>,
     ) -> Self {
-        let connected = unsafe { std::mem::transmute(connected) };
-        let disconnected = unsafe { std::mem::transmute(disconnected) };
-        let le_param_req = unsafe { std::mem::transmute(parameters_requested) };
-        let le_param_updated = unsafe { std::mem::transmute(parameters_updated) };
+        let connected = unsafe { core::mem::transmute(connected) };
+        let disconnected = unsafe { core::mem::transmute(disconnected) };
+        let le_param_req = unsafe { core::mem::transmute(parameters_requested) };
+        let le_param_updated = unsafe { core::mem::transmute(parameters_updated) };
 
         Self(zephyr_sys::raw::bt_conn_cb {
             connected,
             disconnected,
             le_param_req,
             le_param_updated,
-            _next: std::ptr::null_mut(),
+            _next: core::ptr::null_mut(),
         })
     }
 
@@ -75,7 +77,7 @@ pub struct Api;
 impl Api {
     pub fn register_service(service: &mut GattService) -> ZephyrResult<()> {
         let errno =
-            unsafe { zephyr_sys::raw::bt_gatt_service_register(std::mem::transmute(service)) };
+            unsafe { zephyr_sys::raw::bt_gatt_service_register(core::mem::transmute(service)) };
 
         if errno == 0 {
             Ok(())
@@ -85,7 +87,7 @@ impl Api {
     }
 
     pub fn enable() -> Result<Api, ZephyrError> {
-        let api = unsafe { API_CONTAINER.take_api() };
+        let api = unsafe { (*addr_of_mut!(API_CONTAINER)).take_api() };
         unsafe {
             enable(None)?;
         }
@@ -156,7 +158,7 @@ impl Api {
 }
 
 pub unsafe fn enable(callback: Option<BtReadyCallback>) -> Result<(), ZephyrError> {
-    let callback: zephyr_sys::raw::bt_ready_cb_t = std::mem::transmute(callback);
+    let callback: zephyr_sys::raw::bt_ready_cb_t = core::mem::transmute(callback);
     let errno = zephyr_sys::raw::bt_enable(callback);
 
     if errno != 0 {
@@ -265,7 +267,7 @@ impl RawAdvertisementHandle {
     fn ad_ptr(&self) -> *const zephyr_sys::raw::bt_data {
         match self.zraw_ad_data.as_ref() {
             Some(ad) => ad.as_ptr(),
-            _ => std::ptr::null(),
+            _ => core::ptr::null(),
         }
     }
 
@@ -279,7 +281,7 @@ impl RawAdvertisementHandle {
     fn sd_ptr(&self) -> *const zephyr_sys::raw::bt_data {
         match self.zraw_sd_data.as_ref() {
             Some(sd) => sd.as_ptr(),
-            _ => std::ptr::null(),
+            _ => core::ptr::null(),
         }
     }
 
@@ -297,7 +299,7 @@ pub unsafe fn start_scanning(
 ) -> ZephyrResult<()> {
     let bt_le_scan_param = zephyr_sys::raw::bt_le_scan_param::from(scan_parameters);
 
-    let errno = zephyr_sys::raw::bt_le_scan_start(&bt_le_scan_param, std::mem::transmute(callback));
+    let errno = zephyr_sys::raw::bt_le_scan_start(&bt_le_scan_param, core::mem::transmute(callback));
 
     if errno == 0 {
         Ok(())
@@ -321,7 +323,7 @@ pub unsafe fn create_connection(
     creation_parameters: &ConnectionCreationParameters,
     connection_parameters: &ConnectionParameters,
 ) -> ZephyrResult<BtConnection> {
-    let mut out_pointer: *mut zephyr_sys::raw::bt_conn = std::ptr::null_mut();
+    let mut out_pointer: *mut zephyr_sys::raw::bt_conn = core::ptr::null_mut();
     let errno = zephyr_sys::raw::bt_conn_le_create(
         transmute(address),
         transmute(creation_parameters),
@@ -1,5 +1,5 @@
 use crate::bluetooth::le::{AddressType, LeAddress};
-use std::mem::transmute;
+use core::mem::transmute;
 
 #[repr(transparent)]
 #[derive(Eq)]
@@ -1,7 +1,10 @@
 use crate::bluetooth::uuid::BtUuid;
 use crate::network::NetworkBufferSimple;
-use std::ops::DerefMut;
-use std::slice;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::DerefMut;
+use core::slice;
 use uuid::Uuid;
 pub use zephyr_sys::raw::bt_data as ZBtData;
 
@@ -136,15 +139,15 @@ impl DataParser<'_> {
     fn parse(&mut self) {
         unsafe {
             zephyr_sys::raw::bt_data_parse(
-                std::mem::transmute(self.net_buf as *mut _),
-                std::mem::transmute(
+                core::mem::transmute(self.net_buf as *mut _),
+                core::mem::transmute(
                     data_parser_callback
                         as *const fn(
                             data: &mut zephyr_sys::raw::bt_data,
                             parser: &mut DataParser<'_>,
                         ) -> bool,
                 ),
-                std::mem::transmute(self as *mut _),
+                core::mem::transmute(self as *mut _),
             )
         }
     }
@@ -2,9 +2,10 @@ use crate::bluetooth::connection::BtConnection;
 use crate::bluetooth::uuid::{BtUuid, BtUuid128, BtUuid16};
 use crate::bluetooth::CONTEXT;
 use crate::{ZephyrError, ZephyrResult};
-use std::ffi::c_void;
-use std::marker::PhantomData;
-use std::mem::transmute;
+use alloc::boxed::Box;
+use core::ffi::c_void;
+use core::marker::PhantomData;
+use core::mem::transmute;
 
 #[macro_export]
 macro_rules! gatt_attribute {
@@ -45,10 +46,10 @@ impl<'attr> GattService<'attr> {
     pub const fn new(attrs: &'attr mut [GattAttribute<'_, '_>]) -> Self {
         Self(
             zephyr_sys::raw::bt_gatt_service {
-                attrs: unsafe { std::mem::transmute(attrs.as_ptr()) },
+                attrs: unsafe { core::mem::transmute(attrs.as_ptr()) },
                 attr_count: attrs.len(),
                 node: zephyr_sys::raw::sys_snode_t {
-                    next: std::ptr::null_mut(),
+                    next: core::ptr::null_mut(),
                 },
             },
             PhantomData,
@@ -68,7 +69,7 @@ pub union AttributeReadCallback {
     pub c: unsafe extern "C" fn(
         conn: *mut zephyr_sys::raw::bt_conn,
         attr: *const zephyr_sys::raw::bt_gatt_attr,
-        buf: *mut std::ffi::c_void,
+        buf: *mut core::ffi::c_void,
         len: u16,
         offset: u16,
     ) -> isize,
@@ -87,7 +88,7 @@ pub union AttributeWriteCallback {
     pub c: unsafe extern "C" fn(
         conn: *mut zephyr_sys::raw::bt_conn,
         attr: *const zephyr_sys::raw::bt_gatt_attr,
-        buf: *const std::ffi::c_void,
+        buf: *const core::ffi::c_void,
         len: u16,
         offset: u16,
         flags: u8,
@@ -155,16 +156,16 @@ impl<'uuid, 'ud> GattAttribute<'uuid, 'ud> {
     {
         Self(
             zephyr_sys::raw::bt_gatt_attr {
-                uuid: unsafe { std::mem::transmute(uuid) },
+                uuid: unsafe { core::mem::transmute(uuid) },
                 read: match read {
                     None => None,
-                    Some(read) => Some(unsafe { std::mem::transmute(read) }),
+                    Some(read) => Some(unsafe { core::mem::transmute(read) }),
                 },
                 write: match write {
                     None => None,
-                    Some(write) => Some(unsafe { std::mem::transmute(write) }),
+                    Some(write) => Some(unsafe { core::mem::transmute(write) }),
                 },
-                user_data: unsafe { std::mem::transmute(user_data as *mut _) },
+                user_data: unsafe { core::mem::transmute(user_data as *mut _) },
                 handle,
                 perm,
             },
@@ -189,7 +190,7 @@ impl<'uuid, 'ud> GattAttribute<'uuid, 'ud> {
                 },
                 write: match write {
                     None => None,
-                    Some(write) => Some(unsafe { std::mem::transmute(write) }),
+                    Some(write) => Some(unsafe { core::mem::transmute(write) }),
                 },
                 user_data,
                 handle,
@@ -219,11 +220,11 @@ impl NotifyParams {
     pub fn by_uuid(attribute: &BtUuid128, data: &[u8]) -> Self {
         Self(zephyr_sys::raw::bt_gatt_notify_params {
             uuid: unsafe { transmute(attribute as *const _) },
-            attr: std::ptr::null(),
-            data: data.as_ptr() as *const std::ffi::c_void,
+            attr: core::ptr::null(),
+            data: data.as_ptr() as *const core::ffi::c_void,
             len: data.len() as u16,
             func: None,
-            user_data: std::ptr::null_mut(),
+            user_data: core::ptr::null_mut(),
         })
     }
 }
@@ -334,7 +335,7 @@ impl SubscribeParameters {
                 value: 0,
                 flags: [0],
                 node: zephyr_sys::raw::sys_snode_t {
-                    next: std::ptr::null_mut(),
+                    next: core::ptr::null_mut(),
                 }
             }
         )
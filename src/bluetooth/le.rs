@@ -1,5 +1,5 @@
 use bitflags::bitflags;
-use std::fmt::{write, Debug, Display, Formatter};
+use core::fmt::{write, Debug, Display, Formatter};
 bitflags! {
     pub struct AdvertisementFlags: u8 {
         const GeneralDiscoverable = zephyr_sys::raw::BT_LE_AD_GENERAL as u8;
@@ -90,7 +90,7 @@ impl From<u8> for AddressType {
 }
 
 impl Display for AddressType {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             AddressType::Public => write!(f, "public"),
             AddressType::Random => write!(f, "random"),
@@ -111,7 +111,7 @@ impl AddressWrapper {
 }
 
 impl Debug for AddressWrapper {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let address: &[u8; 6] = &self.0.a.val;
         write!(
             f,
@@ -139,7 +139,7 @@ impl LeAddress {
 }
 
 impl Debug for LeAddress {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let address: &[u8; 6] = &self.address;
         write!(
             f,
@@ -150,7 +150,7 @@ impl Debug for LeAddress {
 }
 
 impl Display for LeAddress {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let address: &[u8; 6] = &self.address;
         write!(
             f,
@@ -210,7 +210,7 @@ impl From<&AdvertisementParameters> for zephyr_sys::raw::bt_le_adv_param {
             options: options.bits(),
             interval_min: *interval_min,
             interval_max: *interval_max,
-            peer: unsafe { std::mem::transmute(peer.as_ref()) },
+            peer: unsafe { core::mem::transmute(peer.as_ref()) },
         }
     }
 }
@@ -1,5 +1,5 @@
 use crate::Context;
-use std::fmt::{Debug, Formatter};
+use core::fmt::{Debug, Formatter};
 
 pub mod api;
 pub mod connection;
@@ -11,7 +11,7 @@ pub(self) struct BluetoothContext {}
 pub(self) static CONTEXT: BluetoothContext = BluetoothContext {};
 
 impl Debug for BluetoothContext {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "bluetooth")
     }
 }
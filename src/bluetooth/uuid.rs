@@ -1,6 +1,6 @@
-use std::mem::transmute;
 use crate::bluetooth::gatt::UserData;
-use std::ops::Deref;
+use core::mem::transmute;
+use core::ops::Deref;
 use uuid::{Bytes, Uuid};
 pub use zephyr_sys::raw::{
     bt_uuid_128 as BtUuid128, bt_uuid_16 as BtUuid16, bt_uuid_32 as BtUuid32, bt_uuid,
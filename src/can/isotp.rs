@@ -0,0 +1,269 @@
+//! Wrappers for the ISO-TP (ISO 15765-2) transport protocol over CAN, requires `CONFIG_ISOTP`.
+//!
+//! ISO-TP segments payloads of up to 4095 bytes (more with CAN FD) into CAN frames with flow
+//! control, e.g. for UDS diagnostics. An [IsoTpReceiver] is bound to a pair of addresses and
+//! reassembles received messages, [send] transmits a message and waits for its completion.
+//!
+//! The ISO-TP functions report the network layer results of the standard instead of error
+//! numbers, which are represented by [IsoTpError].
+
+use super::{Can, CanId, Device};
+use crate::time::Timeout;
+use alloc::boxed::Box;
+use core::error::Error;
+use core::ffi::c_int;
+use core::fmt::{Display, Formatter};
+use zephyr_sys::raw::{
+    isotp_fc_opts as ZFlowControl, isotp_msg_id as ZMessageId, isotp_recv_ctx as ZRecvContext,
+    isotp_send_ctx as ZSendContext,
+};
+
+/// Results of the ISO-TP network layer other than success.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum IsoTpError {
+    /// Timeout waiting for the transmission of a frame (`N_As`/`N_Ar`).
+    TimeoutA,
+    /// Timeout waiting for a flow control frame (`N_Bs`).
+    TimeoutBs,
+    /// Timeout waiting for a consecutive frame (`N_Cr`).
+    TimeoutCr,
+    /// Consecutive frame with an unexpected sequence number.
+    WrongSequenceNumber,
+    /// Flow control frame with an invalid flow status.
+    InvalidFlowStatus,
+    /// Frame that is not expected in the current state.
+    UnexpectedPdu,
+    /// More wait flow control frames than allowed.
+    WaitFrameOverrun,
+    /// The receiver has no buffer for the message.
+    BufferOverflow,
+    /// General error of the network layer.
+    Error,
+    /// No free CAN filter to receive frames.
+    NoFreeFilter,
+    /// No free net buffer for the message.
+    NoNetBufferLeft,
+    /// No data left in the receive buffer.
+    NoBufferDataLeft,
+    /// No free context for the transmission.
+    NoContextLeft,
+    /// Timeout waiting for a received message.
+    ReceiveTimeout,
+    Other(i32),
+}
+
+impl From<c_int> for IsoTpError {
+    fn from(code: c_int) -> Self {
+        match code {
+            -1 => IsoTpError::TimeoutA,
+            -2 => IsoTpError::TimeoutBs,
+            -3 => IsoTpError::TimeoutCr,
+            -4 => IsoTpError::WrongSequenceNumber,
+            -5 => IsoTpError::InvalidFlowStatus,
+            -6 => IsoTpError::UnexpectedPdu,
+            -7 => IsoTpError::WaitFrameOverrun,
+            -8 => IsoTpError::BufferOverflow,
+            -9 => IsoTpError::Error,
+            -10 => IsoTpError::NoFreeFilter,
+            -11 => IsoTpError::NoNetBufferLeft,
+            -12 => IsoTpError::NoBufferDataLeft,
+            -13 => IsoTpError::NoContextLeft,
+            -14 => IsoTpError::ReceiveTimeout,
+            code => IsoTpError::Other(code),
+        }
+    }
+}
+
+impl Display for IsoTpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            IsoTpError::TimeoutA => write!(f, "-1: timeout transmitting a frame"),
+            IsoTpError::TimeoutBs => write!(f, "-2: timeout waiting for flow control"),
+            IsoTpError::TimeoutCr => write!(f, "-3: timeout waiting for a consecutive frame"),
+            IsoTpError::WrongSequenceNumber => write!(f, "-4: wrong sequence number"),
+            IsoTpError::InvalidFlowStatus => write!(f, "-5: invalid flow status"),
+            IsoTpError::UnexpectedPdu => write!(f, "-6: unexpected PDU"),
+            IsoTpError::WaitFrameOverrun => write!(f, "-7: too many wait frames"),
+            IsoTpError::BufferOverflow => write!(f, "-8: buffer overflow"),
+            IsoTpError::Error => write!(f, "-9: network layer error"),
+            IsoTpError::NoFreeFilter => write!(f, "-10: no free CAN filter"),
+            IsoTpError::NoNetBufferLeft => write!(f, "-11: no net buffer left"),
+            IsoTpError::NoBufferDataLeft => write!(f, "-12: no buffer data left"),
+            IsoTpError::NoContextLeft => write!(f, "-13: no context left"),
+            IsoTpError::ReceiveTimeout => write!(f, "-14: receive timeout"),
+            IsoTpError::Other(code) => write!(f, "{}: unknown ISO-TP error", code),
+        }
+    }
+}
+
+impl Error for IsoTpError {}
+
+/// Address of one side of an ISO-TP connection.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct IsoTpAddress {
+    pub id: CanId,
+    /// Extended addressing, the first byte of each frame is this address.
+    pub extended_address: Option<u8>,
+    /// Normal fixed addressing as specified by SAE J1939.
+    pub fixed: bool,
+    /// Send CAN FD frames.
+    pub fd: bool,
+    /// Use the data bitrate for CAN FD frames.
+    pub bitrate_switch: bool,
+    /// Data length code of the frames, `0` for the default of 8 bytes.
+    pub dl: u8,
+}
+
+impl IsoTpAddress {
+    /// Address with normal addressing and classic CAN frames.
+    pub const fn new(id: CanId) -> Self {
+        IsoTpAddress {
+            id,
+            extended_address: None,
+            fixed: false,
+            fd: false,
+            bitrate_switch: false,
+            dl: 0,
+        }
+    }
+}
+
+impl From<&IsoTpAddress> for ZMessageId {
+    fn from(other: &IsoTpAddress) -> Self {
+        let mut flags = 0;
+        if other.extended_address.is_some() {
+            flags |= zephyr_sys::raw::ISOTP_MSG_EXT_ADDR;
+        }
+        if other.fixed {
+            flags |= zephyr_sys::raw::ISOTP_MSG_FIXED_ADDR;
+        }
+        if matches!(other.id, CanId::Extended(_)) {
+            flags |= zephyr_sys::raw::ISOTP_MSG_IDE;
+        }
+        if other.fd {
+            flags |= zephyr_sys::raw::ISOTP_MSG_FDF;
+        }
+        if other.bitrate_switch {
+            flags |= zephyr_sys::raw::ISOTP_MSG_BRS;
+        }
+
+        let mut id: ZMessageId = unsafe { core::mem::zeroed() };
+        id.__bindgen_anon_1.ext_id = other.id.raw();
+        id.ext_addr = other.extended_address.unwrap_or(0);
+        id.dl = other.dl;
+        id.flags = flags as u8;
+        id
+    }
+}
+
+/// Flow control parameters the receiver sends to the transmitter.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct FlowControl {
+    /// Number of consecutive frames before the next flow control frame, `0` for none.
+    pub block_size: u8,
+    /// Minimal separation time between consecutive frames, encoded as in the standard.
+    pub st_min: u8,
+}
+
+fn result(code: c_int) -> Result<(), IsoTpError> {
+    if code == zephyr_sys::raw::ISOTP_N_OK as c_int {
+        Ok(())
+    } else {
+        Err(code.into())
+    }
+}
+
+/// Receiver of ISO-TP messages on a pair of addresses, unbound when dropped.
+pub struct IsoTpReceiver {
+    // boxed as the ISO-TP implementation keeps a pointer to the context
+    ctx: Box<ZRecvContext>,
+}
+
+impl IsoTpReceiver {
+    /// Bind a receiver for messages from `rx` with flow control frames sent to `tx`. This is a
+    /// wrapper to `isotp_bind`.
+    pub fn bind(
+        can: &Can,
+        rx: &IsoTpAddress,
+        tx: &IsoTpAddress,
+        flow_control: FlowControl,
+        timeout: Timeout,
+    ) -> Result<Self, IsoTpError> {
+        let mut ctx: Box<ZRecvContext> = Box::new(unsafe { core::mem::zeroed() });
+        let rx: ZMessageId = rx.into();
+        let tx: ZMessageId = tx.into();
+        let options = ZFlowControl {
+            bs: flow_control.block_size,
+            stmin: flow_control.st_min,
+        };
+
+        // device is required to be a CAN controller in constructor of the Can
+        result(unsafe {
+            zephyr_sys::raw::isotp_bind(
+                &mut *ctx as *mut ZRecvContext,
+                can.device as *const Device,
+                &rx as *const ZMessageId,
+                &tx as *const ZMessageId,
+                &options as *const ZFlowControl,
+                timeout,
+            )
+        })?;
+
+        Ok(IsoTpReceiver { ctx })
+    }
+
+    /// Receive up to `buffer.len()` bytes of a message, waiting up to `timeout`. Returns the
+    /// number of received bytes. This is a wrapper to `isotp_recv`.
+    ///
+    /// Messages longer than the buffer are returned in multiple calls.
+    pub fn recv(&mut self, buffer: &mut [u8], timeout: Timeout) -> Result<usize, IsoTpError> {
+        let received = unsafe {
+            zephyr_sys::raw::isotp_recv(
+                &mut *self.ctx as *mut ZRecvContext,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                timeout,
+            )
+        };
+
+        if received >= 0 {
+            Ok(received as usize)
+        } else {
+            Err(received.into())
+        }
+    }
+}
+
+impl Drop for IsoTpReceiver {
+    fn drop(&mut self) {
+        unsafe { zephyr_sys::raw::isotp_unbind(&mut *self.ctx as *mut ZRecvContext) };
+    }
+}
+
+/// Send `data` to `tx`, receiving flow control frames from `rx`, and block until the message is
+/// sent. This is a wrapper to `isotp_send`.
+pub fn send(
+    can: &Can,
+    data: &[u8],
+    tx: &IsoTpAddress,
+    rx: &IsoTpAddress,
+) -> Result<(), IsoTpError> {
+    // the context is only used until the blocking send returns
+    let mut ctx: Box<ZSendContext> = Box::new(unsafe { core::mem::zeroed() });
+    let tx: ZMessageId = tx.into();
+    let rx: ZMessageId = rx.into();
+
+    // device is required to be a CAN controller in constructor of the Can
+    result(unsafe {
+        zephyr_sys::raw::isotp_send(
+            &mut *ctx as *mut ZSendContext,
+            can.device as *const Device,
+            data.as_ptr(),
+            data.len(),
+            &tx as *const ZMessageId,
+            &rx as *const ZMessageId,
+            None,
+            core::ptr::null_mut(),
+        )
+    })
+}
@@ -0,0 +1,550 @@
+//! Syscalls and high level wrappers for the Zephyr CAN controller API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API. CAN FD bitrates require `CONFIG_CAN_FD_MODE` and the `can-fd`
+//! feature.
+//!
+//! Received frames are delivered either to a closure, which is called from the interrupt of the
+//! controller, or into a [MessageQueue] of raw frames that a thread reads from.
+//!
+//! The ISO-TP transport protocol is available in `isotp` with the `isotp` feature.
+
+use crate::device::{class, TypedDevice};
+use crate::handlers::Handlers;
+use crate::message_queue::MessageQueue;
+use crate::time::Timeout;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use core::convert::TryFrom;
+use core::ffi::{c_int, c_void};
+use core::ops::BitOr;
+use core::sync::atomic::{AtomicI32, Ordering};
+pub use zephyr::device::Device;
+pub use zephyr_sys::raw::can_frame as RawCanFrame;
+use zephyr_sys::raw::can_filter as ZCanFilter;
+
+#[cfg(feature = "isotp")]
+pub mod isotp;
+
+const CONTEXT: CanWrapperContext = CanWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+fn invalid_argument() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT)
+}
+
+/// Identifier of a CAN frame.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum CanId {
+    /// 11 bit identifier.
+    Standard(u16),
+    /// 29 bit identifier.
+    Extended(u32),
+}
+
+impl CanId {
+    /// The raw identifier.
+    pub fn raw(&self) -> u32 {
+        match self {
+            CanId::Standard(id) => *id as u32,
+            CanId::Extended(id) => *id,
+        }
+    }
+
+    /// Returns `true` if the identifier fits its format.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            CanId::Standard(id) => *id as u32 <= zephyr_sys::raw::CAN_STD_ID_MASK,
+            CanId::Extended(id) => *id <= zephyr_sys::raw::CAN_EXT_ID_MASK,
+        }
+    }
+
+    fn is_extended(&self) -> bool {
+        matches!(self, CanId::Extended(_))
+    }
+}
+
+/// Payload lengths of the data length codes of CAN FD. This is the table of `can_dlc_to_bytes`.
+const DLC_TO_BYTES: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64];
+
+/// Convert a data length code to the number of bytes. This is the equivalent of
+/// `can_dlc_to_bytes`.
+pub fn dlc_to_bytes(dlc: u8) -> usize {
+    DLC_TO_BYTES[(dlc as usize).min(15)] as usize
+}
+
+/// Convert a number of bytes to the smallest data length code holding them. This is the
+/// equivalent of `can_bytes_to_dlc`.
+pub fn bytes_to_dlc(bytes: usize) -> u8 {
+    DLC_TO_BYTES
+        .iter()
+        .position(|&len| len as usize >= bytes)
+        .unwrap_or(15) as u8
+}
+
+/// A classic or FD CAN frame.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CanFrame {
+    id: CanId,
+    rtr: bool,
+    fd: bool,
+    bitrate_switch: bool,
+    dlc: u8,
+    data: [u8; 64],
+}
+
+impl CanFrame {
+    /// Creates a classic data frame, fails with [ErrorNumber::InvalidArgument] if the identifier
+    /// is out of range or `data` is longer than 8 bytes.
+    pub fn new(id: CanId, data: &[u8]) -> ZephyrResult<Self> {
+        if data.len() > 8 {
+            return Err(invalid_argument());
+        }
+        Self::with_data(id, false, data)
+    }
+
+    /// Creates a CAN FD data frame, fails with [ErrorNumber::InvalidArgument] if the identifier
+    /// is out of range or `data` is longer than 64 bytes. The payload is padded with zeros to the
+    /// next length that can be encoded.
+    pub fn new_fd(id: CanId, data: &[u8], bitrate_switch: bool) -> ZephyrResult<Self> {
+        let mut frame = Self::with_data(id, true, data)?;
+        frame.bitrate_switch = bitrate_switch;
+        Ok(frame)
+    }
+
+    /// Creates a remote transmission request for `len` bytes.
+    pub fn remote(id: CanId, len: u8) -> ZephyrResult<Self> {
+        if !id.is_valid() || len > 8 {
+            return Err(invalid_argument());
+        }
+
+        Ok(CanFrame {
+            id,
+            rtr: true,
+            fd: false,
+            bitrate_switch: false,
+            dlc: len,
+            data: [0; 64],
+        })
+    }
+
+    fn with_data(id: CanId, fd: bool, payload: &[u8]) -> ZephyrResult<Self> {
+        if !id.is_valid() || payload.len() > 64 {
+            return Err(invalid_argument());
+        }
+
+        let mut data = [0; 64];
+        data[..payload.len()].copy_from_slice(payload);
+        Ok(CanFrame {
+            id,
+            rtr: false,
+            fd,
+            bitrate_switch: false,
+            dlc: bytes_to_dlc(payload.len()),
+            data,
+        })
+    }
+
+    pub fn id(&self) -> CanId {
+        self.id
+    }
+
+    /// Returns `true` for remote transmission requests.
+    pub fn is_remote(&self) -> bool {
+        self.rtr
+    }
+
+    /// Returns `true` for CAN FD frames.
+    pub fn is_fd(&self) -> bool {
+        self.fd
+    }
+
+    /// Returns `true` if the data phase of the CAN FD frame uses the data bitrate.
+    pub fn is_bitrate_switch(&self) -> bool {
+        self.bitrate_switch
+    }
+
+    pub fn dlc(&self) -> u8 {
+        self.dlc
+    }
+
+    /// The payload, empty for remote transmission requests.
+    pub fn data(&self) -> &[u8] {
+        if self.rtr {
+            &[]
+        } else {
+            &self.data[..dlc_to_bytes(self.dlc)]
+        }
+    }
+}
+
+impl From<&CanFrame> for RawCanFrame {
+    fn from(other: &CanFrame) -> Self {
+        let mut flags = 0;
+        if other.id.is_extended() {
+            flags |= zephyr_sys::raw::CAN_FRAME_IDE;
+        }
+        if other.rtr {
+            flags |= zephyr_sys::raw::CAN_FRAME_RTR;
+        }
+        if other.fd {
+            flags |= zephyr_sys::raw::CAN_FRAME_FDF;
+        }
+        if other.bitrate_switch {
+            flags |= zephyr_sys::raw::CAN_FRAME_BRS;
+        }
+
+        // the frame contains optional fields depending on the Kconfig, start from all zeros
+        let mut frame: RawCanFrame = unsafe { core::mem::zeroed() };
+        frame.id = other.id.raw();
+        frame.dlc = other.dlc;
+        frame.flags = flags as u8;
+        // the payload of the frame is 8 or 64 bytes long depending on CONFIG_CAN_FD_MODE
+        let data = unsafe { &mut frame.__bindgen_anon_1.data };
+        let len = data.len();
+        data.copy_from_slice(&other.data[..len]);
+        frame
+    }
+}
+
+impl TryFrom<&RawCanFrame> for CanFrame {
+    type Error = ZephyrError;
+
+    fn try_from(other: &RawCanFrame) -> Result<Self, Self::Error> {
+        let flags = other.flags as u32;
+        let id = if flags & zephyr_sys::raw::CAN_FRAME_IDE != 0 {
+            CanId::Extended(other.id)
+        } else {
+            CanId::Standard(other.id as u16)
+        };
+        if !id.is_valid() || other.dlc > 15 {
+            return Err(invalid_argument());
+        }
+
+        let mut data = [0; 64];
+        let raw_data = unsafe { &other.__bindgen_anon_1.data };
+        let len = raw_data.len().min(dlc_to_bytes(other.dlc));
+        data[..len].copy_from_slice(&raw_data[..len]);
+
+        Ok(CanFrame {
+            id,
+            rtr: flags & zephyr_sys::raw::CAN_FRAME_RTR != 0,
+            fd: flags & zephyr_sys::raw::CAN_FRAME_FDF != 0,
+            bitrate_switch: flags & zephyr_sys::raw::CAN_FRAME_BRS != 0,
+            dlc: other.dlc,
+            data,
+        })
+    }
+}
+
+/// Filter for received frames, a frame matches if its identifier equals the filter identifier in
+/// all bits set in the mask.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CanFilter {
+    pub id: CanId,
+    pub mask: u32,
+}
+
+impl CanFilter {
+    /// Filter matching exactly `id`.
+    pub fn exact(id: CanId) -> Self {
+        let mask = match id {
+            CanId::Standard(_) => zephyr_sys::raw::CAN_STD_ID_MASK,
+            CanId::Extended(_) => zephyr_sys::raw::CAN_EXT_ID_MASK,
+        };
+        CanFilter { id, mask }
+    }
+}
+
+impl From<&CanFilter> for ZCanFilter {
+    fn from(other: &CanFilter) -> Self {
+        let mut filter: ZCanFilter = unsafe { core::mem::zeroed() };
+        filter.id = other.id.raw();
+        filter.mask = other.mask;
+        if other.id.is_extended() {
+            filter.flags = zephyr_sys::raw::CAN_FILTER_IDE as u8;
+        }
+        filter
+    }
+}
+
+/// Operation mode of the controller, modes can be combined with `|`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CanMode(pub u32);
+
+impl CanMode {
+    pub const NORMAL: CanMode = CanMode(zephyr_sys::raw::CAN_MODE_NORMAL);
+    /// Received frames are sent back to the controller.
+    pub const LOOPBACK: CanMode = CanMode(zephyr_sys::raw::CAN_MODE_LOOPBACK);
+    /// The controller does not acknowledge frames or send.
+    pub const LISTEN_ONLY: CanMode = CanMode(zephyr_sys::raw::CAN_MODE_LISTENONLY);
+    pub const FD: CanMode = CanMode(zephyr_sys::raw::CAN_MODE_FD);
+    /// Frames are not retransmitted after errors.
+    pub const ONE_SHOT: CanMode = CanMode(zephyr_sys::raw::CAN_MODE_ONE_SHOT);
+    pub const TRIPLE_SAMPLING: CanMode = CanMode(zephyr_sys::raw::CAN_MODE_3_SAMPLES);
+}
+
+impl BitOr for CanMode {
+    type Output = CanMode;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        CanMode(self.0 | rhs.0)
+    }
+}
+
+/// Error state of the controller.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CanState {
+    ErrorActive,
+    ErrorWarning,
+    ErrorPassive,
+    BusOff,
+    Stopped,
+    Unknown(u32),
+}
+
+impl From<zephyr_sys::raw::can_state> for CanState {
+    fn from(other: zephyr_sys::raw::can_state) -> Self {
+        match other {
+            zephyr_sys::raw::can_state_CAN_STATE_ERROR_ACTIVE => CanState::ErrorActive,
+            zephyr_sys::raw::can_state_CAN_STATE_ERROR_WARNING => CanState::ErrorWarning,
+            zephyr_sys::raw::can_state_CAN_STATE_ERROR_PASSIVE => CanState::ErrorPassive,
+            zephyr_sys::raw::can_state_CAN_STATE_BUS_OFF => CanState::BusOff,
+            zephyr_sys::raw::can_state_CAN_STATE_STOPPED => CanState::Stopped,
+            state => CanState::Unknown(state as u32),
+        }
+    }
+}
+
+/// Error counters of the controller.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ErrorCounters {
+    pub tx: u8,
+    pub rx: u8,
+}
+
+/// Id of an installed receive filter.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FilterId(c_int);
+
+/// Wrapper to the `can_send` syscall, blocking until the frame is sent.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn send(device: &Device, frame: &RawCanFrame, timeout: Timeout) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::can_send(
+        device as *const Device,
+        frame as *const RawCanFrame,
+        timeout,
+        None,
+        core::ptr::null_mut(),
+    ))
+}
+
+/// High level wrapper for a CAN controller.
+pub struct Can {
+    device: &'static Device,
+}
+
+impl Can {
+    /// Creates a new [Can] on the current interface.
+    ///
+    /// `device` MUST be a CAN controller. If `device` is not a CAN controller the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Can { device }
+    }
+
+    /// Creates a new [Can] from a device checked to be a CAN controller.
+    pub fn from_device(device: TypedDevice<class::Can>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Can::new(device.device()) }
+    }
+
+    /// Start the controller, it must be configured while stopped.
+    pub fn start(&mut self) -> ZephyrResult<()> {
+        // device is required to be a CAN controller in constructor
+        result(unsafe { zephyr_sys::syscalls::any::can_start(self.device as *const Device) })
+    }
+
+    /// Stop the controller, aborting pending transmissions.
+    pub fn stop(&mut self) -> ZephyrResult<()> {
+        // device is required to be a CAN controller in constructor
+        result(unsafe { zephyr_sys::syscalls::any::can_stop(self.device as *const Device) })
+    }
+
+    /// Set the operation mode, the controller must be stopped.
+    pub fn set_mode(&mut self, mode: CanMode) -> ZephyrResult<()> {
+        // device is required to be a CAN controller in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::can_set_mode(self.device as *const Device, mode.0 as _)
+        })
+    }
+
+    /// Set the bitrate of the arbitration phase in bits per second, the controller must be
+    /// stopped.
+    pub fn set_bitrate(&mut self, bitrate: u32) -> ZephyrResult<()> {
+        // device is required to be a CAN controller in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::can_set_bitrate(self.device as *const Device, bitrate)
+        })
+    }
+
+    /// Set the bitrate of the data phase of CAN FD frames, the controller must be stopped.
+    #[cfg(feature = "can-fd")]
+    pub fn set_bitrate_data(&mut self, bitrate: u32) -> ZephyrResult<()> {
+        // device is required to be a CAN controller in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::can_set_bitrate_data(self.device as *const Device, bitrate)
+        })
+    }
+
+    /// Send `frame`, waiting up to `timeout` for a free transmit buffer and then until the frame
+    /// is sent.
+    pub fn send(&mut self, frame: &CanFrame, timeout: Timeout) -> ZephyrResult<()> {
+        // device is required to be a CAN controller in constructor
+        unsafe { send(self.device, &frame.into(), timeout) }
+    }
+
+    /// Current error state and counters. This is a wrapper to `can_get_state`.
+    pub fn state(&self) -> ZephyrResult<(CanState, ErrorCounters)> {
+        let mut state: zephyr_sys::raw::can_state = 0;
+        let mut counters: zephyr_sys::raw::can_bus_err_cnt = unsafe { core::mem::zeroed() };
+        // device is required to be a CAN controller in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::can_get_state(
+                self.device as *const Device,
+                &mut state as *mut _,
+                &mut counters as *mut _,
+            )
+        })?;
+
+        Ok((
+            state.into(),
+            ErrorCounters {
+                tx: counters.tx_err_cnt,
+                rx: counters.rx_err_cnt,
+            },
+        ))
+    }
+
+    /// Call `f` from the interrupt of the controller for every frame matching `filter`. This is a
+    /// wrapper to `can_add_rx_filter`.
+    pub fn add_rx_filter<F>(&mut self, filter: &CanFilter, f: F) -> ZephyrResult<FilterId>
+    where
+        F: FnMut(&CanFrame) + Send + 'static,
+    {
+        let raw: ZCanFilter = filter.into();
+        let device = self.device as *const Device as usize;
+        // the handler must be in place before the filter can match
+        let pushed = FILTER_HANDLERS.push(
+            FilterKey {
+                device,
+                filter: AtomicI32::new(-1),
+            },
+            Box::new(f),
+        );
+        // device is required to be a CAN controller in constructor
+        let id = unsafe {
+            zephyr_sys::raw::can_add_rx_filter(
+                self.device as *const Device,
+                Some(rx_trampoline),
+                pushed.id as *mut c_void,
+                &raw as *const ZCanFilter,
+            )
+        };
+
+        if id >= 0 {
+            // the key stays in the table until the filter is removed
+            unsafe { (*pushed.key).filter.store(id, Ordering::Relaxed) };
+            Ok(FilterId(id))
+        } else {
+            FILTER_HANDLERS.remove_id(pushed.id);
+            Err(ZephyrError::from_errno_with_context(id, &CONTEXT))
+        }
+    }
+
+    /// Put every frame matching `filter` into `queue`. This is a wrapper to
+    /// `can_add_rx_filter_msgq`.
+    pub fn add_rx_filter_msgq(
+        &mut self,
+        filter: &CanFilter,
+        queue: &'static MessageQueue<RawCanFrame>,
+    ) -> ZephyrResult<FilterId> {
+        let raw: ZCanFilter = filter.into();
+        // device is required to be a CAN controller in constructor
+        let id = unsafe {
+            zephyr_sys::syscalls::any::can_add_rx_filter_msgq(
+                self.device as *const Device,
+                queue.msgq_ptr(),
+                &raw as *const ZCanFilter,
+            )
+        };
+
+        if id >= 0 {
+            Ok(FilterId(id))
+        } else {
+            Err(ZephyrError::from_errno_with_context(id, &CONTEXT))
+        }
+    }
+
+    /// Remove the filter with `id`.
+    pub fn remove_rx_filter(&mut self, id: FilterId) {
+        // device is required to be a CAN controller in constructor
+        unsafe {
+            zephyr_sys::syscalls::any::can_remove_rx_filter(self.device as *const Device, id.0)
+        };
+        let device = self.device as *const Device as usize;
+        FILTER_HANDLERS.remove_where(|key| key.matches(device, id.0));
+    }
+}
+
+/// Filter of a device a closure is installed for with [Can::add_rx_filter].
+struct FilterKey {
+    device: usize,
+    /// Id of the filter assigned by the driver, `-1` until the filter has been added.
+    filter: AtomicI32,
+}
+
+impl FilterKey {
+    fn matches(&self, device: usize, filter: c_int) -> bool {
+        self.device == device && self.filter.load(Ordering::Relaxed) == filter
+    }
+}
+
+impl PartialEq for FilterKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.matches(other.device, other.filter.load(Ordering::Relaxed))
+    }
+}
+
+type FilterHandler = dyn FnMut(&CanFrame) + Send;
+
+static FILTER_HANDLERS: Handlers<FilterKey, FilterHandler> = Handlers::new();
+
+/// C callback that dispatches a received frame to the closure whose id is passed as user data.
+extern "C" fn rx_trampoline(
+    _dev: *const Device,
+    frame: *mut RawCanFrame,
+    user_data: *mut c_void,
+) {
+    if let Some(Ok(frame)) = unsafe { frame.as_ref() }.map(CanFrame::try_from) {
+        FILTER_HANDLERS.call_id(user_data as usize, |handler| handler(&frame));
+    }
+}
+
+#[derive(Debug)]
+struct CanWrapperContext {}
+
+impl Context for CanWrapperContext {
+    fn name(&self) -> &'static str {
+        "can wrapper"
+    }
+}
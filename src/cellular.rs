@@ -0,0 +1,331 @@
+//! Wrappers for the Zephyr cellular modem API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! The cellular API is inlined in the Zephyr headers, so this module calls the driver API
+//! directly. Modems like the ones of `modem_cellular` are powered with the power management of
+//! their device and provide the network through a PPP interface, which is usable once the
+//! connection manager reports connectivity:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::cellular::{Cellular, SignalType};
+//!# use zephyr_rust_wrappers::time::FOREVER;
+//!# fn example(device: &'static zephyr_rust_wrappers::cellular::Device) {
+//! let modem = unsafe { Cellular::new(device) };
+//! modem.set_apn("internet").unwrap();
+//! modem.connect(FOREVER).unwrap();
+//! println!("RSSI {} dBm", modem.signal(SignalType::Rssi).unwrap());
+//!# }
+//! ```
+
+use crate::device::{class, TypedDevice};
+use crate::network::conn_mgr;
+use crate::network::interface::NetworkInterface;
+use crate::time::Timeout;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::ffi::CString;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::ffi::c_int;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::{cellular_driver_api as CellularDriverApi, cellular_network as ZNetwork};
+
+const CONTEXT: CellularWrapperContext = CellularWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+fn not_implemented() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::NotImplemented, &CONTEXT)
+}
+
+/// Radio access technology, the equivalent of `enum cellular_access_technology`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AccessTechnology {
+    Gsm,
+    Gprs,
+    Umts,
+    Edge,
+    Lte,
+    LteCatM1,
+    LteCatM2,
+    NbIot,
+}
+
+impl AccessTechnology {
+    fn to_raw(self) -> zephyr_sys::raw::cellular_access_technology {
+        use zephyr_sys::raw::{
+            cellular_access_technology_CELLULAR_ACCESS_TECHNOLOGY_EDGE as EDGE,
+            cellular_access_technology_CELLULAR_ACCESS_TECHNOLOGY_GPRS as GPRS,
+            cellular_access_technology_CELLULAR_ACCESS_TECHNOLOGY_GSM as GSM,
+            cellular_access_technology_CELLULAR_ACCESS_TECHNOLOGY_LTE as LTE,
+            cellular_access_technology_CELLULAR_ACCESS_TECHNOLOGY_LTE_CAT_M1 as LTE_CAT_M1,
+            cellular_access_technology_CELLULAR_ACCESS_TECHNOLOGY_LTE_CAT_M2 as LTE_CAT_M2,
+            cellular_access_technology_CELLULAR_ACCESS_TECHNOLOGY_NB_IOT as NB_IOT,
+            cellular_access_technology_CELLULAR_ACCESS_TECHNOLOGY_UMTS as UMTS,
+        };
+        match self {
+            AccessTechnology::Gsm => GSM,
+            AccessTechnology::Gprs => GPRS,
+            AccessTechnology::Umts => UMTS,
+            AccessTechnology::Edge => EDGE,
+            AccessTechnology::Lte => LTE,
+            AccessTechnology::LteCatM1 => LTE_CAT_M1,
+            AccessTechnology::LteCatM2 => LTE_CAT_M2,
+            AccessTechnology::NbIot => NB_IOT,
+        }
+    }
+}
+
+/// Access technology and the bands the modem may use with it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Network {
+    pub technology: AccessTechnology,
+    /// Bands of the technology, all supported bands if empty.
+    pub bands: Vec<u16>,
+}
+
+/// Registration of the modem in the network, the equivalent of
+/// `enum cellular_registration_status`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RegistrationStatus {
+    NotRegistered,
+    RegisteredHome,
+    Searching,
+    Denied,
+    Unknown,
+    RegisteredRoaming,
+    Other(u32),
+}
+
+impl From<u32> for RegistrationStatus {
+    fn from(raw: u32) -> Self {
+        use zephyr_sys::raw::{
+            cellular_registration_status_CELLULAR_REGISTRATION_DENIED as DENIED,
+            cellular_registration_status_CELLULAR_REGISTRATION_NOT_REGISTERED as NOT_REGISTERED,
+            cellular_registration_status_CELLULAR_REGISTRATION_REGISTERED_HOME as HOME,
+            cellular_registration_status_CELLULAR_REGISTRATION_REGISTERED_ROAMING as ROAMING,
+            cellular_registration_status_CELLULAR_REGISTRATION_SEARCHING as SEARCHING,
+            cellular_registration_status_CELLULAR_REGISTRATION_UNKNOWN as UNKNOWN,
+        };
+        match raw {
+            NOT_REGISTERED => RegistrationStatus::NotRegistered,
+            HOME => RegistrationStatus::RegisteredHome,
+            SEARCHING => RegistrationStatus::Searching,
+            DENIED => RegistrationStatus::Denied,
+            UNKNOWN => RegistrationStatus::Unknown,
+            ROAMING => RegistrationStatus::RegisteredRoaming,
+            other => RegistrationStatus::Other(other),
+        }
+    }
+}
+
+impl RegistrationStatus {
+    /// Whether the modem is registered in its home network or roaming.
+    pub fn is_registered(&self) -> bool {
+        matches!(
+            self,
+            RegistrationStatus::RegisteredHome | RegistrationStatus::RegisteredRoaming
+        )
+    }
+}
+
+/// Signal quality value of [Cellular::signal].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SignalType {
+    /// Received signal strength in dBm.
+    Rssi,
+    /// Reference signal received power of LTE in dBm.
+    Rsrp,
+    /// Reference signal received quality of LTE in dB.
+    Rsrq,
+}
+
+/// Information of [Cellular::modem_info].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ModemInfo {
+    Imei,
+    ModelId,
+    Manufacturer,
+    FirmwareVersion,
+    SimImsi,
+    SimIccid,
+}
+
+/// Size of the buffer of [Cellular::modem_info], the information is truncated to fit.
+const MODEM_INFO_SIZE: usize = 64;
+
+/// Wrapper to the `net_if_get_first_by_type` function, returns the first PPP interface.
+pub fn ppp_interface() -> Option<NetworkInterface> {
+    unsafe {
+        NetworkInterface::from_raw(zephyr_sys::raw::net_if_get_first_by_type(
+            &zephyr_sys::raw::_net_l2_PPP,
+        ))
+    }
+}
+
+/// High level wrapper for a cellular modem.
+#[derive(Copy, Clone)]
+pub struct Cellular {
+    device: &'static Device,
+}
+
+impl Cellular {
+    /// Creates a new [Cellular] for `device`.
+    ///
+    /// `device` MUST be a cellular modem. If `device` is not a cellular modem the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Cellular { device }
+    }
+
+    /// Creates a new [Cellular] from a device checked to be a cellular modem.
+    pub fn from_device(device: TypedDevice<class::Cellular>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Cellular::new(device.device()) }
+    }
+
+    fn api(&self) -> &'static CellularDriverApi {
+        // device is required to be a cellular modem in constructor
+        unsafe { &*(self.device.api as *const CellularDriverApi) }
+    }
+
+    fn pm_action(&self, action: zephyr_sys::raw::pm_device_action) -> ZephyrResult<()> {
+        let errno = unsafe { zephyr_sys::raw::pm_device_action_run(self.device, action) };
+        // the modem already is in the requested state
+        if errno == -i32::from(ErrorNumber::Already) {
+            return Ok(());
+        }
+        result(errno)
+    }
+
+    /// Power on the modem by resuming its device, the modem then registers in the network and
+    /// brings up the PPP link.
+    pub fn power_on(&self) -> ZephyrResult<()> {
+        self.pm_action(zephyr_sys::raw::pm_device_action_PM_DEVICE_ACTION_RESUME)
+    }
+
+    /// Power off the modem by suspending its device.
+    pub fn power_off(&self) -> ZephyrResult<()> {
+        self.pm_action(zephyr_sys::raw::pm_device_action_PM_DEVICE_ACTION_SUSPEND)
+    }
+
+    /// Powers on the modem, takes the PPP interface up and waits up to `timeout` until the
+    /// network is usable, see [conn_mgr::wait_for_connectivity].
+    pub fn connect(&self, timeout: Timeout) -> ZephyrResult<()> {
+        self.power_on()?;
+        let iface = ppp_interface().ok_or_else(|| {
+            ZephyrError::new_with_context(ErrorNumber::NoDevice, &CONTEXT)
+        })?;
+        iface.up()?;
+        conn_mgr::wait_for_connectivity(timeout)
+    }
+
+    /// Takes the PPP interface down and powers off the modem.
+    pub fn disconnect(&self) -> ZephyrResult<()> {
+        if let Some(iface) = ppp_interface() {
+            iface.down()?;
+        }
+        self.power_off()
+    }
+
+    /// Restrict the modem to `networks`, the equivalent of `cellular_configure_networks`.
+    ///
+    /// Fails with [ErrorNumber::InvalidArgument] for more than 255 networks or more than 65535
+    /// bands of a network.
+    pub fn configure_networks(&self, networks: &[Network]) -> ZephyrResult<()> {
+        let configure = self.api().configure_networks.ok_or_else(not_implemented)?;
+        let invalid = || ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT);
+        let count = u8::try_from(networks.len()).map_err(|_| invalid())?;
+        let mut bands: Vec<Vec<u16>> = networks.iter().map(|net| net.bands.clone()).collect();
+        let raw = networks
+            .iter()
+            .zip(bands.iter_mut())
+            .map(|(network, bands)| {
+                Ok(ZNetwork {
+                    technology: network.technology.to_raw(),
+                    bands: bands.as_mut_ptr(),
+                    size: u16::try_from(bands.len()).map_err(|_| invalid())?,
+                })
+            })
+            .collect::<ZephyrResult<Vec<ZNetwork>>>()?;
+        result(unsafe { configure(self.device, raw.as_ptr(), count) })
+    }
+
+    /// Set the access point name of the packet data connection, the equivalent of
+    /// `cellular_set_apn`.
+    pub fn set_apn(&self, apn: &str) -> ZephyrResult<()> {
+        let set_apn = self.api().set_apn.ok_or_else(not_implemented)?;
+        let apn = CString::new(apn)
+            .map_err(|_| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))?;
+        result(unsafe { set_apn(self.device, apn.as_ptr()) })
+    }
+
+    /// Registration status for `technology`, the equivalent of
+    /// `cellular_get_registration_status`.
+    pub fn registration_status(
+        &self,
+        technology: AccessTechnology,
+    ) -> ZephyrResult<RegistrationStatus> {
+        let get_status = self.api().get_registration_status.ok_or_else(not_implemented)?;
+        let mut status = 0;
+        result(unsafe { get_status(self.device, technology.to_raw(), &mut status) })?;
+        Ok(RegistrationStatus::from(status as u32))
+    }
+
+    /// Signal quality of `signal_type`, the equivalent of `cellular_get_signal`.
+    pub fn signal(&self, signal_type: SignalType) -> ZephyrResult<i16> {
+        let get_signal = self.api().get_signal.ok_or_else(not_implemented)?;
+        let signal_type = match signal_type {
+            SignalType::Rssi => zephyr_sys::raw::cellular_signal_type_CELLULAR_SIGNAL_RSSI,
+            SignalType::Rsrp => zephyr_sys::raw::cellular_signal_type_CELLULAR_SIGNAL_RSRP,
+            SignalType::Rsrq => zephyr_sys::raw::cellular_signal_type_CELLULAR_SIGNAL_RSRQ,
+        };
+        let mut value = 0;
+        result(unsafe { get_signal(self.device, signal_type, &mut value) })?;
+        Ok(value)
+    }
+
+    /// Information about the modem or its SIM card, the equivalent of `cellular_get_modem_info`.
+    pub fn modem_info(&self, info: ModemInfo) -> ZephyrResult<String> {
+        let get_info = self.api().get_modem_info.ok_or_else(not_implemented)?;
+        let info = match info {
+            ModemInfo::Imei => zephyr_sys::raw::cellular_modem_info_type_CELLULAR_MODEM_INFO_IMEI,
+            ModemInfo::ModelId => {
+                zephyr_sys::raw::cellular_modem_info_type_CELLULAR_MODEM_INFO_MODEL_ID
+            }
+            ModemInfo::Manufacturer => {
+                zephyr_sys::raw::cellular_modem_info_type_CELLULAR_MODEM_INFO_MANUFACTURER
+            }
+            ModemInfo::FirmwareVersion => {
+                zephyr_sys::raw::cellular_modem_info_type_CELLULAR_MODEM_INFO_FW_VERSION
+            }
+            ModemInfo::SimImsi => {
+                zephyr_sys::raw::cellular_modem_info_type_CELLULAR_MODEM_INFO_SIM_IMSI
+            }
+            ModemInfo::SimIccid => {
+                zephyr_sys::raw::cellular_modem_info_type_CELLULAR_MODEM_INFO_SIM_ICCID
+            }
+        };
+        let mut buf = [0u8; MODEM_INFO_SIZE];
+        result(unsafe { get_info(self.device, info, buf.as_mut_ptr() as *mut _, buf.len()) })?;
+        let len = buf.iter().position(|c| *c == 0).unwrap_or(buf.len());
+        Ok(String::from_utf8_lossy(&buf[..len]).into_owned())
+    }
+}
+
+#[derive(Debug)]
+struct CellularWrapperContext {}
+
+impl Context for CellularWrapperContext {
+    fn name(&self) -> &'static str {
+        "cellular wrapper"
+    }
+}
@@ -0,0 +1,306 @@
+//! Syscalls and high level wrappers for the Zephyr battery charger API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! Chargers report the state of the charging process and of the power supply, the charge
+//! parameters can be set at runtime where the driver supports it. Together with a
+//! [crate::fuel_gauge::FuelGauge] this covers the battery status of most products.
+
+use crate::device::{class, TypedDevice};
+use crate::{Context, ZephyrError, ZephyrResult};
+use core::ffi::c_int;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::charger_propval as ZPropertyValue;
+
+const CONTEXT: ChargerWrapperContext = ChargerWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Properties of a charger.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Property {
+    Online,
+    Present,
+    Status,
+    Health,
+    ConstantChargeCurrent,
+    PrechargeCurrent,
+    ChargeTermCurrent,
+    ConstantChargeVoltage,
+    InputCurrentLimit,
+}
+
+impl From<Property> for zephyr_sys::raw::charger_prop_t {
+    fn from(other: Property) -> Self {
+        (match other {
+            Property::Online => zephyr_sys::raw::charger_property_CHARGER_PROP_ONLINE,
+            Property::Present => zephyr_sys::raw::charger_property_CHARGER_PROP_PRESENT,
+            Property::Status => zephyr_sys::raw::charger_property_CHARGER_PROP_STATUS,
+            Property::Health => zephyr_sys::raw::charger_property_CHARGER_PROP_HEALTH,
+            Property::ConstantChargeCurrent => {
+                zephyr_sys::raw::charger_property_CHARGER_PROP_CONSTANT_CHARGE_CURRENT_UA
+            }
+            Property::PrechargeCurrent => {
+                zephyr_sys::raw::charger_property_CHARGER_PROP_PRECHARGE_CURRENT_UA
+            }
+            Property::ChargeTermCurrent => {
+                zephyr_sys::raw::charger_property_CHARGER_PROP_CHARGE_TERM_CURRENT_UA
+            }
+            Property::ConstantChargeVoltage => {
+                zephyr_sys::raw::charger_property_CHARGER_PROP_CONSTANT_CHARGE_VOLTAGE_UV
+            }
+            Property::InputCurrentLimit => {
+                zephyr_sys::raw::charger_property_CHARGER_PROP_INPUT_REGULATION_CURRENT_UA
+            }
+        }) as zephyr_sys::raw::charger_prop_t
+    }
+}
+
+/// State of the charging process.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ChargerStatus {
+    Unknown,
+    Charging,
+    Discharging,
+    /// A supply is connected, but the battery is not charged.
+    NotCharging,
+    Full,
+}
+
+impl From<u32> for ChargerStatus {
+    fn from(other: u32) -> Self {
+        match other {
+            zephyr_sys::raw::charger_status_CHARGER_STATUS_CHARGING => ChargerStatus::Charging,
+            zephyr_sys::raw::charger_status_CHARGER_STATUS_DISCHARGING => {
+                ChargerStatus::Discharging
+            }
+            zephyr_sys::raw::charger_status_CHARGER_STATUS_NOT_CHARGING => {
+                ChargerStatus::NotCharging
+            }
+            zephyr_sys::raw::charger_status_CHARGER_STATUS_FULL => ChargerStatus::Full,
+            _ => ChargerStatus::Unknown,
+        }
+    }
+}
+
+/// Health of the battery as seen by the charger.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ChargerHealth {
+    Unknown,
+    Good,
+    Overheat,
+    Overvoltage,
+    UnspecifiedFailure,
+    Cold,
+    WatchdogTimerExpire,
+    SafetyTimerExpire,
+    Warm,
+    Cool,
+    Hot,
+    NoBattery,
+}
+
+impl From<u32> for ChargerHealth {
+    fn from(other: u32) -> Self {
+        match other {
+            zephyr_sys::raw::charger_health_CHARGER_HEALTH_GOOD => ChargerHealth::Good,
+            zephyr_sys::raw::charger_health_CHARGER_HEALTH_OVERHEAT => ChargerHealth::Overheat,
+            zephyr_sys::raw::charger_health_CHARGER_HEALTH_OVERVOLTAGE => {
+                ChargerHealth::Overvoltage
+            }
+            zephyr_sys::raw::charger_health_CHARGER_HEALTH_UNSPEC_FAILURE => {
+                ChargerHealth::UnspecifiedFailure
+            }
+            zephyr_sys::raw::charger_health_CHARGER_HEALTH_COLD => ChargerHealth::Cold,
+            zephyr_sys::raw::charger_health_CHARGER_HEALTH_WATCHDOG_TIMER_EXPIRE => {
+                ChargerHealth::WatchdogTimerExpire
+            }
+            zephyr_sys::raw::charger_health_CHARGER_HEALTH_SAFETY_TIMER_EXPIRE => {
+                ChargerHealth::SafetyTimerExpire
+            }
+            zephyr_sys::raw::charger_health_CHARGER_HEALTH_WARM => ChargerHealth::Warm,
+            zephyr_sys::raw::charger_health_CHARGER_HEALTH_COOL => ChargerHealth::Cool,
+            zephyr_sys::raw::charger_health_CHARGER_HEALTH_HOT => ChargerHealth::Hot,
+            zephyr_sys::raw::charger_health_CHARGER_HEALTH_NO_BATTERY => ChargerHealth::NoBattery,
+            _ => ChargerHealth::Unknown,
+        }
+    }
+}
+
+/// Wrapper to the `charger_get_prop` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// The field of the returned union depends on the property.
+pub unsafe fn get_prop(device: &Device, property: Property) -> ZephyrResult<ZPropertyValue> {
+    let mut value: ZPropertyValue = core::mem::zeroed();
+    result(zephyr_sys::syscalls::any::charger_get_prop(
+        device as *const Device,
+        property.into(),
+        &mut value as *mut ZPropertyValue,
+    ))?;
+    Ok(value)
+}
+
+/// Wrapper to the `charger_set_prop` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// The field set in `value` MUST match the property.
+pub unsafe fn set_prop(
+    device: &Device,
+    property: Property,
+    value: &ZPropertyValue,
+) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::charger_set_prop(
+        device as *const Device,
+        property.into(),
+        value as *const ZPropertyValue,
+    ))
+}
+
+/// Wrapper to the `charger_charge_enable` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn charge_enable(device: &Device, enable: bool) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::charger_charge_enable(
+        device as *const Device,
+        enable,
+    ))
+}
+
+/// High level wrapper for a battery charger.
+pub struct Charger {
+    device: &'static Device,
+}
+
+impl Charger {
+    /// Creates a new [Charger].
+    ///
+    /// `device` MUST be a charger device. If `device` is not a charger device the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Charger { device }
+    }
+
+    /// Creates a new [Charger] from a device checked to be a charger device.
+    pub fn from_device(device: TypedDevice<class::Charger>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Charger::new(device.device()) }
+    }
+
+    fn property(&self, property: Property) -> ZephyrResult<ZPropertyValue> {
+        // device is required to be a charger device in constructor
+        unsafe { get_prop(self.device, property) }
+    }
+
+    fn set_current(&mut self, property: Property, current_ua: u32) -> ZephyrResult<()> {
+        // zeroed as only the field of the property is set
+        let mut value: ZPropertyValue = unsafe { core::mem::zeroed() };
+        match property {
+            Property::ConstantChargeCurrent => value.const_charge_current_ua = current_ua,
+            Property::PrechargeCurrent => value.precharge_current_ua = current_ua,
+            Property::ChargeTermCurrent => value.charge_term_current_ua = current_ua,
+            Property::InputCurrentLimit => value.input_current_regulation_current_ua = current_ua,
+            _ => unreachable!("property is not a current"),
+        }
+        // device is required to be a charger device in constructor
+        unsafe { set_prop(self.device, property, &value) }
+    }
+
+    /// Enable or disable charging.
+    pub fn set_charging(&mut self, enable: bool) -> ZephyrResult<()> {
+        // device is required to be a charger device in constructor
+        unsafe { charge_enable(self.device, enable) }
+    }
+
+    /// Returns `true` if the charger is connected to a supply.
+    pub fn is_online(&self) -> ZephyrResult<bool> {
+        let value = self.property(Property::Online)?;
+        // the union field matches the property
+        Ok(unsafe { value.online } != zephyr_sys::raw::charger_online_CHARGER_ONLINE_OFFLINE)
+    }
+
+    /// Returns `true` if a battery is connected.
+    pub fn is_battery_present(&self) -> ZephyrResult<bool> {
+        let value = self.property(Property::Present)?;
+        // the union field matches the property
+        Ok(unsafe { value.present })
+    }
+
+    /// State of the charging process.
+    pub fn status(&self) -> ZephyrResult<ChargerStatus> {
+        let value = self.property(Property::Status)?;
+        // the union field matches the property
+        Ok((unsafe { value.status } as u32).into())
+    }
+
+    /// Health of the battery.
+    pub fn health(&self) -> ZephyrResult<ChargerHealth> {
+        let value = self.property(Property::Health)?;
+        // the union field matches the property
+        Ok((unsafe { value.health } as u32).into())
+    }
+
+    /// Current of the constant current phase in microamperes.
+    pub fn constant_charge_current_ua(&self) -> ZephyrResult<u32> {
+        let value = self.property(Property::ConstantChargeCurrent)?;
+        // the union field matches the property
+        Ok(unsafe { value.const_charge_current_ua })
+    }
+
+    /// Set the current of the constant current phase in microamperes.
+    pub fn set_constant_charge_current_ua(&mut self, current_ua: u32) -> ZephyrResult<()> {
+        self.set_current(Property::ConstantChargeCurrent, current_ua)
+    }
+
+    /// Set the current of the precharge phase of deeply discharged batteries in microamperes.
+    pub fn set_precharge_current_ua(&mut self, current_ua: u32) -> ZephyrResult<()> {
+        self.set_current(Property::PrechargeCurrent, current_ua)
+    }
+
+    /// Set the current below which charging is terminated in microamperes.
+    pub fn set_charge_term_current_ua(&mut self, current_ua: u32) -> ZephyrResult<()> {
+        self.set_current(Property::ChargeTermCurrent, current_ua)
+    }
+
+    /// Set the current drawn from the supply in microamperes.
+    pub fn set_input_current_limit_ua(&mut self, current_ua: u32) -> ZephyrResult<()> {
+        self.set_current(Property::InputCurrentLimit, current_ua)
+    }
+
+    /// Voltage of the constant voltage phase in microvolts.
+    pub fn constant_charge_voltage_uv(&self) -> ZephyrResult<u32> {
+        let value = self.property(Property::ConstantChargeVoltage)?;
+        // the union field matches the property
+        Ok(unsafe { value.const_charge_voltage_uv })
+    }
+
+    /// Set the voltage of the constant voltage phase in microvolts.
+    pub fn set_constant_charge_voltage_uv(&mut self, voltage_uv: u32) -> ZephyrResult<()> {
+        // zeroed as only the field of the property is set
+        let mut value: ZPropertyValue = unsafe { core::mem::zeroed() };
+        value.const_charge_voltage_uv = voltage_uv;
+        // device is required to be a charger device in constructor
+        unsafe { set_prop(self.device, Property::ConstantChargeVoltage, &value) }
+    }
+}
+
+#[derive(Debug)]
+struct ChargerWrapperContext {}
+
+impl Context for ChargerWrapperContext {
+    fn name(&self) -> &'static str {
+        "charger wrapper"
+    }
+}
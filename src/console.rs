@@ -0,0 +1,214 @@
+//! Output through `printk` and input from the console subsystem, for simple interactive tools that
+//! do without the shell.
+//!
+//! [Printk] is a [core::fmt::Write] writing to the console with `k_str_out`, the output of
+//! `printk`, which requires `CONFIG_PRINTK`. The [printk!](crate::printk) and
+//! [printkln!](crate::printkln) macros format with it:
+//!
+//! ```no_run
+//! use zephyr_rust_wrappers::{console, printk, printkln};
+//!
+//! console::init().unwrap();
+//! let mut editor = console::LineEditor::new(64);
+//! loop {
+//!     printk!("> ");
+//!     let line = editor.read_line().unwrap();
+//!     printkln!("echo: {}", line);
+//! }
+//! ```
+//!
+//! The input comes from the console subsystem (`CONFIG_CONSOLE_SUBSYS`), either byte by byte with
+//! [getchar] (`CONFIG_CONSOLE_GETCHAR`, after [init]) or by line with [getline]
+//! (`CONFIG_CONSOLE_GETLINE`, after [getline_init]), Zephyr supports only one of them at a time.
+//! [LineEditor] builds lines from the bytes of [getchar] with echo, backspace and erasing of the
+//! line.
+
+use crate::{Context, ZephyrError, ZephyrResult};
+use alloc::string::String;
+use core::ffi::{c_char, CStr};
+use core::fmt::{Arguments, Write};
+
+const CONTEXT: ConsoleWrapperContext = ConsoleWrapperContext {};
+
+/// Backspace, sent by some terminals for the backspace key.
+const BACKSPACE: u8 = 0x08;
+/// Delete, sent by most terminals for the backspace key.
+const DELETE: u8 = 0x7f;
+/// Ctrl-C, discards the line.
+const END_OF_TEXT: u8 = 0x03;
+/// Ctrl-U, erases the line.
+const NEGATIVE_ACKNOWLEDGE: u8 = 0x15;
+
+/// Writer to the console with `k_str_out`, the output of `printk`.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Printk;
+
+impl Write for Printk {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        // the syscall only reads the string, the pointer is mutable in the signature only
+        unsafe { zephyr_sys::syscalls::any::k_str_out(s.as_ptr() as *mut c_char, s.len()) };
+        Ok(())
+    }
+}
+
+/// Writes the message formatted from `args` to the console with [Printk].
+pub fn print(args: Arguments<'_>) {
+    // writing to the console never fails
+    let _ = Printk.write_fmt(args);
+}
+
+/// Prints to the console like `print!`, through `printk`.
+#[macro_export]
+macro_rules! printk {
+    ($($arg:tt)*) => {
+        $crate::console::print(format_args!($($arg)*))
+    };
+}
+
+/// Prints to the console with a newline like `println!`, through `printk`.
+#[macro_export]
+macro_rules! printkln {
+    () => {
+        $crate::console::print(format_args!("\n"))
+    };
+    ($($arg:tt)*) => {
+        $crate::console::print(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+fn result(errno: i32) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Error of the `errno` of the calling thread, the tty of the console returns -1 and sets it.
+fn errno_error() -> ZephyrError {
+    let errno = unsafe { *zephyr_sys::syscalls::any::z_errno() };
+    ZephyrError::from_errno_with_context(errno, &CONTEXT)
+}
+
+/// Wrapper to the `console_init` function, required before [getchar] and [putchar].
+pub fn init() -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::console_init() })
+}
+
+/// Wrapper to the `console_getchar` function.
+///
+/// Blocks until a byte is received.
+pub fn getchar() -> ZephyrResult<u8> {
+    let res = unsafe { zephyr_sys::raw::console_getchar() };
+    if res < 0 {
+        Err(errno_error())
+    } else {
+        Ok(res as u8)
+    }
+}
+
+/// Wrapper to the `console_putchar` function.
+pub fn putchar(byte: u8) -> ZephyrResult<()> {
+    let res = unsafe { zephyr_sys::raw::console_putchar(byte as c_char) };
+    if res < 0 {
+        Err(errno_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Wrapper to the `console_getline_init` function, required before [getline].
+pub fn getline_init() {
+    unsafe { zephyr_sys::raw::console_getline_init() };
+}
+
+/// Wrapper to the `console_getline` function.
+///
+/// Blocks until a line is received and returns it without the line ending. Zephyr edits the line
+/// and echoes it already, invalid UTF-8 is replaced.
+pub fn getline() -> String {
+    // the line is valid until the next call, so it is copied
+    let line = unsafe { CStr::from_ptr(zephyr_sys::raw::console_getline()) };
+    line.to_string_lossy().into_owned()
+}
+
+/// Editor of a line of input, which echoes the printable ASCII characters to the console.
+///
+/// Backspace (or delete) removes the last character, Ctrl-U erases the line and Ctrl-C discards it.
+/// A line ends with a carriage return or a line feed, both in a row count as one line ending.
+/// Characters beyond the maximum length of the line and other control characters are ignored.
+#[derive(Debug)]
+pub struct LineEditor {
+    line: String,
+    max_len: usize,
+    last_cr: bool,
+}
+
+impl LineEditor {
+    /// Creates an editor of lines with up to `max_len` characters.
+    pub fn new(max_len: usize) -> Self {
+        LineEditor {
+            line: String::with_capacity(max_len),
+            max_len,
+            last_cr: false,
+        }
+    }
+
+    /// The line being edited.
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    /// Edits the line with `byte` and returns the line once it ends.
+    pub fn feed(&mut self, byte: u8) -> Option<String> {
+        let last_cr = core::mem::replace(&mut self.last_cr, byte == b'\r');
+        match byte {
+            b'\n' if last_cr => {}
+            b'\r' | b'\n' => {
+                print(format_args!("\r\n"));
+                return Some(core::mem::take(&mut self.line));
+            }
+            BACKSPACE | DELETE => {
+                if self.line.pop().is_some() {
+                    print(format_args!("\x08 \x08"));
+                }
+            }
+            NEGATIVE_ACKNOWLEDGE => self.erase(),
+            END_OF_TEXT => {
+                self.line.clear();
+                print(format_args!("^C\r\n"));
+            }
+            0x20..=0x7e if self.line.len() < self.max_len => {
+                self.line.push(byte as char);
+                print(format_args!("{}", byte as char));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// Reads a line with [getchar], the console MUST be initialized with [init].
+    pub fn read_line(&mut self) -> ZephyrResult<String> {
+        loop {
+            if let Some(line) = self.feed(getchar()?) {
+                return Ok(line);
+            }
+        }
+    }
+
+    fn erase(&mut self) {
+        for _ in 0..self.line.len() {
+            print(format_args!("\x08 \x08"));
+        }
+        self.line.clear();
+    }
+}
+
+#[derive(Debug)]
+struct ConsoleWrapperContext {}
+
+impl Context for ConsoleWrapperContext {
+    fn name(&self) -> &'static str {
+        "console wrapper"
+    }
+}
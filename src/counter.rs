@@ -0,0 +1,369 @@
+//! Syscalls and high level wrappers for the Zephyr counter API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! Counters are hardware timers with alarm channels, so alarms can be scheduled with a resolution
+//! below the kernel tick. Alarm and top value callbacks are called from the interrupt of the
+//! counter.
+
+use crate::device::{class, TypedDevice};
+use crate::handlers::Handlers;
+use crate::{Context, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use core::ffi::c_void;
+use core::time::Duration;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::{counter_alarm_cfg as ZAlarmConfig, counter_top_cfg as ZTopConfig};
+
+const CONTEXT: CounterWrapperContext = CounterWrapperContext {};
+
+fn result(errno: core::ffi::c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Configuration of an alarm on a counter channel.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AlarmConfig {
+    pub ticks: u32,
+    /// `ticks` is an absolute counter value instead of relative to the current value.
+    pub absolute: bool,
+    /// Expire immediately if an absolute alarm is set to a value in the past.
+    pub expire_when_late: bool,
+}
+
+impl AlarmConfig {
+    /// Alarm `ticks` after the current value of the counter.
+    pub const fn relative(ticks: u32) -> Self {
+        AlarmConfig {
+            ticks,
+            absolute: false,
+            expire_when_late: false,
+        }
+    }
+
+    /// Alarm when the counter reaches `ticks`.
+    pub const fn absolute(ticks: u32) -> Self {
+        AlarmConfig {
+            ticks,
+            absolute: true,
+            expire_when_late: false,
+        }
+    }
+
+    fn flags(&self) -> u32 {
+        let mut flags = 0;
+        if self.absolute {
+            flags |= zephyr_sys::raw::COUNTER_ALARM_CFG_ABSOLUTE;
+        }
+        if self.expire_when_late {
+            flags |= zephyr_sys::raw::COUNTER_ALARM_CFG_EXPIRE_WHEN_LATE;
+        }
+        flags
+    }
+}
+
+/// Configuration of the top value the counter wraps at.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TopConfig {
+    pub ticks: u32,
+    /// Reset the counter when setting the top value.
+    pub reset: bool,
+    /// Reset the counter if it is already above the new top value, requires `reset` to be `false`.
+    pub reset_when_late: bool,
+}
+
+impl TopConfig {
+    /// Wrap at `ticks`, resetting the counter.
+    pub const fn new(ticks: u32) -> Self {
+        TopConfig {
+            ticks,
+            reset: true,
+            reset_when_late: false,
+        }
+    }
+
+    fn flags(&self) -> u32 {
+        let mut flags = 0;
+        if !self.reset {
+            flags |= zephyr_sys::raw::COUNTER_TOP_CFG_DONT_RESET;
+        }
+        if self.reset_when_late {
+            flags |= zephyr_sys::raw::COUNTER_TOP_CFG_RESET_WHEN_LATE;
+        }
+        flags
+    }
+}
+
+/// Wrapper to the `counter_start` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn start(device: &Device) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::counter_start(device as *const Device))
+}
+
+/// Wrapper to the `counter_stop` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn stop(device: &Device) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::counter_stop(device as *const Device))
+}
+
+/// Wrapper to the `counter_get_value` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn get_value(device: &Device) -> ZephyrResult<u32> {
+    let mut ticks = 0_u32;
+    result(zephyr_sys::syscalls::any::counter_get_value(
+        device as *const Device,
+        &mut ticks as *mut u32,
+    ))?;
+    Ok(ticks)
+}
+
+/// Wrapper to the `counter_set_channel_alarm` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn set_channel_alarm(
+    device: &Device,
+    channel: u8,
+    config: &ZAlarmConfig,
+) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::counter_set_channel_alarm(
+        device as *const Device,
+        channel,
+        config as *const ZAlarmConfig,
+    ))
+}
+
+/// Wrapper to the `counter_cancel_channel_alarm` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn cancel_channel_alarm(device: &Device, channel: u8) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::counter_cancel_channel_alarm(
+        device as *const Device,
+        channel,
+    ))
+}
+
+/// Wrapper to the `counter_set_top_value` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn set_top_value(device: &Device, config: &ZTopConfig) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::counter_set_top_value(
+        device as *const Device,
+        config as *const ZTopConfig,
+    ))
+}
+
+/// High level wrapper for a counter device.
+pub struct Counter {
+    device: &'static Device,
+}
+
+impl Counter {
+    /// Creates a new [Counter] on the current interface.
+    ///
+    /// `device` MUST be a counter device. If `device` is not a counter device the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Counter { device }
+    }
+
+    /// Creates a new [Counter] from a device checked to be a counter device.
+    pub fn from_device(device: TypedDevice<class::Counter>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Counter::new(device.device()) }
+    }
+
+    /// Start counting.
+    pub fn start(&mut self) -> ZephyrResult<()> {
+        // device is required to be a counter device in constructor
+        unsafe { start(self.device) }
+    }
+
+    /// Stop counting.
+    pub fn stop(&mut self) -> ZephyrResult<()> {
+        // device is required to be a counter device in constructor
+        unsafe { stop(self.device) }
+    }
+
+    /// Current value of the counter in ticks.
+    pub fn value(&self) -> ZephyrResult<u32> {
+        // device is required to be a counter device in constructor
+        unsafe { get_value(self.device) }
+    }
+
+    /// Frequency of the counter in Hertz. This is a wrapper to `counter_get_frequency`.
+    pub fn frequency(&self) -> u32 {
+        // device is required to be a counter device in constructor
+        unsafe { zephyr_sys::syscalls::any::counter_get_frequency(self.device as *const Device) }
+    }
+
+    /// Returns `true` if the counter counts up. This is a wrapper to `counter_is_counting_up`.
+    pub fn is_counting_up(&self) -> bool {
+        // device is required to be a counter device in constructor
+        unsafe { zephyr_sys::syscalls::any::counter_is_counting_up(self.device as *const Device) }
+    }
+
+    /// Number of alarm channels. This is a wrapper to `counter_get_num_of_channels`.
+    pub fn channel_count(&self) -> u8 {
+        // device is required to be a counter device in constructor
+        unsafe {
+            zephyr_sys::syscalls::any::counter_get_num_of_channels(self.device as *const Device)
+        }
+    }
+
+    /// Convert `duration` to ticks of the counter. This is a wrapper to `counter_us_to_ticks`.
+    pub fn duration_to_ticks(&self, duration: Duration) -> u32 {
+        // device is required to be a counter device in constructor
+        unsafe {
+            zephyr_sys::syscalls::any::counter_us_to_ticks(
+                self.device as *const Device,
+                duration.as_micros() as u64,
+            )
+        }
+    }
+
+    /// Convert `ticks` of the counter to a duration. This is a wrapper to `counter_ticks_to_us`.
+    pub fn ticks_to_duration(&self, ticks: u32) -> Duration {
+        // device is required to be a counter device in constructor
+        Duration::from_micros(unsafe {
+            zephyr_sys::syscalls::any::counter_ticks_to_us(self.device as *const Device, ticks)
+        })
+    }
+
+    /// Value the counter wraps at. This is a wrapper to `counter_get_top_value`.
+    pub fn top_value(&self) -> u32 {
+        // device is required to be a counter device in constructor
+        unsafe { zephyr_sys::syscalls::any::counter_get_top_value(self.device as *const Device) }
+    }
+
+    /// Set the value the counter wraps at. Fails if alarms are active.
+    pub fn set_top_value(&mut self, config: &TopConfig) -> ZephyrResult<()> {
+        let raw = ZTopConfig {
+            ticks: config.ticks,
+            callback: None,
+            user_data: core::ptr::null_mut(),
+            flags: config.flags(),
+        };
+        // device is required to be a counter device in constructor
+        unsafe { set_top_value(self.device, &raw) }
+    }
+
+    /// Set the value the counter wraps at, calling `f` every time it wraps.
+    pub fn set_top_value_with<F>(&mut self, config: &TopConfig, f: F) -> ZephyrResult<()>
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let device = self.device as *const Device as usize;
+        let pushed = TOP_HANDLERS.push(device, Box::new(f));
+        let raw = ZTopConfig {
+            ticks: config.ticks,
+            callback: Some(top_trampoline),
+            user_data: pushed.id as *mut c_void,
+            flags: config.flags(),
+        };
+        // device is required to be a counter device in constructor
+        let result = unsafe { set_top_value(self.device, &raw) };
+        if result.is_ok() {
+            // the previous closure of the device is no longer used by the driver
+            TOP_HANDLERS.remove_others(pushed.id, |key| *key == device);
+        } else {
+            TOP_HANDLERS.remove_id(pushed.id);
+        }
+        result
+    }
+
+    /// Set a one shot alarm on `channel`, calling `f` with the counter value when it expires.
+    ///
+    /// Fails if an alarm that did not expire yet is set on the channel, it must be cancelled
+    /// before.
+    pub fn set_alarm<F>(&mut self, channel: u8, config: &AlarmConfig, f: F) -> ZephyrResult<()>
+    where
+        F: FnMut(u32) + Send + 'static,
+    {
+        let key = AlarmKey {
+            device: self.device as *const Device as usize,
+            channel,
+        };
+        let pushed = ALARM_HANDLERS.push(key, Box::new(f));
+        let raw = ZAlarmConfig {
+            callback: Some(alarm_trampoline),
+            ticks: config.ticks,
+            user_data: pushed.id as *mut c_void,
+            flags: config.flags(),
+        };
+        // device is required to be a counter device in constructor
+        let result = unsafe { set_channel_alarm(self.device, channel, &raw) };
+        if result.is_ok() {
+            // closures of expired alarms on the channel are no longer used by the driver
+            ALARM_HANDLERS.remove_others(pushed.id, |other| *other == key);
+        } else {
+            // the driver refuses to replace an alarm that did not expire yet
+            ALARM_HANDLERS.remove_id(pushed.id);
+        }
+        result
+    }
+
+    /// Cancel the alarm on `channel`.
+    pub fn cancel_alarm(&mut self, channel: u8) -> ZephyrResult<()> {
+        // device is required to be a counter device in constructor
+        unsafe { cancel_channel_alarm(self.device, channel) }?;
+        let key = AlarmKey {
+            device: self.device as *const Device as usize,
+            channel,
+        };
+        ALARM_HANDLERS.remove_where(|other| *other == key);
+        Ok(())
+    }
+}
+
+/// Channel of a device a closure is installed for with [Counter::set_alarm].
+#[derive(Copy, Clone, PartialEq)]
+struct AlarmKey {
+    device: usize,
+    channel: u8,
+}
+
+type AlarmHandler = dyn FnMut(u32) + Send;
+type TopHandler = dyn FnMut() + Send;
+
+static ALARM_HANDLERS: Handlers<AlarmKey, AlarmHandler> = Handlers::new();
+/// Closures installed with [Counter::set_top_value_with], keyed by the address of the device.
+static TOP_HANDLERS: Handlers<usize, TopHandler> = Handlers::new();
+
+/// C callback that dispatches an alarm to the closure whose id is passed as user data.
+extern "C" fn alarm_trampoline(
+    _dev: *const Device,
+    _channel: u8,
+    ticks: u32,
+    user_data: *mut c_void,
+) {
+    ALARM_HANDLERS.call_id(user_data as usize, |handler| handler(ticks));
+}
+
+/// C callback that dispatches a wrap of the counter to the closure whose id is passed as user
+/// data.
+extern "C" fn top_trampoline(_dev: *const Device, user_data: *mut c_void) {
+    TOP_HANDLERS.call_id(user_data as usize, |handler| handler());
+}
+
+#[derive(Debug)]
+struct CounterWrapperContext {}
+
+impl Context for CounterWrapperContext {
+    fn name(&self) -> &'static str {
+        "counter wrapper"
+    }
+}
@@ -0,0 +1,292 @@
+//! AES cipher sessions of the crypto driver API.
+
+use super::{api, not_implemented, result, Device, CONTEXT};
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use core::marker::PhantomData;
+use zephyr_sys::raw::{
+    cipher_aead_pkt as ZAeadPacket, cipher_ctx as ZCipherContext, cipher_pkt as ZCipherPacket,
+};
+
+/// Size of an AES block in bytes.
+pub const AES_BLOCK_SIZE: usize = 16;
+
+/// Block cipher mode of a session.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CipherMode {
+    Ecb,
+    Cbc,
+    /// Counter mode with a counter of `counter_len` bits, the rest of the block is the IV.
+    Ctr { counter_len: u32 },
+    /// Counter with CBC-MAC, lengths in bytes.
+    Ccm { tag_len: u16, nonce_len: u16 },
+    /// Galois/counter mode, lengths in bytes.
+    Gcm { tag_len: u16, nonce_len: u16 },
+}
+
+impl From<CipherMode> for zephyr_sys::raw::cipher_mode {
+    fn from(other: CipherMode) -> Self {
+        match other {
+            CipherMode::Ecb => zephyr_sys::raw::cipher_mode_CRYPTO_CIPHER_MODE_ECB,
+            CipherMode::Cbc => zephyr_sys::raw::cipher_mode_CRYPTO_CIPHER_MODE_CBC,
+            CipherMode::Ctr { .. } => zephyr_sys::raw::cipher_mode_CRYPTO_CIPHER_MODE_CTR,
+            CipherMode::Ccm { .. } => zephyr_sys::raw::cipher_mode_CRYPTO_CIPHER_MODE_CCM,
+            CipherMode::Gcm { .. } => zephyr_sys::raw::cipher_mode_CRYPTO_CIPHER_MODE_GCM,
+        }
+    }
+}
+
+/// Direction of a cipher session.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CipherOperation {
+    Encrypt,
+    Decrypt,
+}
+
+impl From<CipherOperation> for zephyr_sys::raw::cipher_op {
+    fn from(other: CipherOperation) -> Self {
+        match other {
+            CipherOperation::Encrypt => zephyr_sys::raw::cipher_op_CRYPTO_CIPHER_OP_ENCRYPT,
+            CipherOperation::Decrypt => zephyr_sys::raw::cipher_op_CRYPTO_CIPHER_OP_DECRYPT,
+        }
+    }
+}
+
+fn invalid_argument() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT)
+}
+
+/// An open AES session using a key borrowed for `'k`, freed when dropped.
+///
+/// Each operation writes to `output` and returns the number of bytes written. If the driver does
+/// not support `CAP_NO_IV_PREFIX`, encrypting in CBC mode prefixes the output with the IV, so the
+/// output must be one block longer than the input.
+pub struct CipherSession<'k> {
+    // boxed as the driver keeps a pointer to the context
+    ctx: Box<ZCipherContext>,
+    mode: CipherMode,
+    _key: PhantomData<&'k [u8]>,
+}
+
+impl<'k> CipherSession<'k> {
+    /// Equivalent of the `cipher_begin_session` API function.
+    ///
+    /// `device` MUST be a crypto device.
+    pub(super) unsafe fn begin(
+        device: &'static Device,
+        key: &'k [u8],
+        mode: CipherMode,
+        operation: CipherOperation,
+        flags: u16,
+    ) -> ZephyrResult<Self> {
+        let begin_session = api(device)
+            .and_then(|api| api.cipher_begin_session)
+            .ok_or_else(not_implemented)?;
+        if key.len() > u16::MAX as usize {
+            return Err(invalid_argument());
+        }
+
+        let mut ctx: Box<ZCipherContext> = Box::new(core::mem::zeroed());
+        ctx.device = device as *const Device;
+        ctx.ops.cipher_mode = mode.into();
+        ctx.key.bit_stream = key.as_ptr();
+        ctx.keylen = key.len() as u16;
+        ctx.flags = flags;
+        match mode {
+            CipherMode::Ctr { counter_len } => ctx.mode_params.ctr_info.ctr_len = counter_len,
+            CipherMode::Ccm { tag_len, nonce_len } => {
+                ctx.mode_params.ccm_info.tag_len = tag_len;
+                ctx.mode_params.ccm_info.nonce_len = nonce_len;
+            }
+            CipherMode::Gcm { tag_len, nonce_len } => {
+                ctx.mode_params.gcm_info.tag_len = tag_len;
+                ctx.mode_params.gcm_info.nonce_len = nonce_len;
+            }
+            CipherMode::Ecb | CipherMode::Cbc => {}
+        }
+
+        result((begin_session)(
+            device as *const Device,
+            &mut *ctx as *mut ZCipherContext,
+            zephyr_sys::raw::cipher_algo_CRYPTO_CIPHER_ALGO_AES,
+            mode.into(),
+            operation.into(),
+        ))?;
+
+        Ok(CipherSession {
+            ctx,
+            mode,
+            _key: PhantomData,
+        })
+    }
+
+    /// Mode of the session.
+    pub fn mode(&self) -> CipherMode {
+        self.mode
+    }
+
+    fn check_mode(&self, matches: bool) -> ZephyrResult<()> {
+        if matches {
+            Ok(())
+        } else {
+            Err(invalid_argument())
+        }
+    }
+
+    /// Build the packet for `input` and `output`, copying the input to the output for drivers that
+    /// only support in place operations.
+    fn packet(&mut self, input: &[u8], output: &mut [u8]) -> ZephyrResult<ZCipherPacket> {
+        let in_place = self.ctx.flags & zephyr_sys::raw::CAP_INPLACE_OPS as u16 != 0;
+        if output.len() < input.len() || output.len() > i32::MAX as usize {
+            return Err(invalid_argument());
+        }
+
+        let in_buf = if in_place {
+            output[..input.len()].copy_from_slice(input);
+            output.as_mut_ptr()
+        } else {
+            // the input is never modified by the driver
+            input.as_ptr() as *mut u8
+        };
+
+        Ok(ZCipherPacket {
+            in_buf,
+            in_len: input.len() as _,
+            out_buf: output.as_mut_ptr(),
+            out_buf_max: output.len() as _,
+            out_len: 0,
+            ctx: &mut *self.ctx as *mut ZCipherContext,
+        })
+    }
+
+    /// Encrypt or decrypt a single block in ECB mode. This is the equivalent of `cipher_block_op`.
+    pub fn block(&mut self, input: &[u8], output: &mut [u8]) -> ZephyrResult<usize> {
+        self.check_mode(self.mode == CipherMode::Ecb)?;
+        let mut packet = self.packet(input, output)?;
+        // the session is open in ECB mode, so the block handler is set by the driver
+        let handler = unsafe { self.ctx.ops.__bindgen_anon_1.block_crypt_hndlr }
+            .ok_or_else(not_implemented)?;
+        result(unsafe { (handler)(packet.ctx, &mut packet as *mut _) })?;
+        Ok(packet.out_len as usize)
+    }
+
+    /// Encrypt or decrypt in CBC mode. This is the equivalent of `cipher_cbc_op`.
+    pub fn cbc(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+        iv: &[u8; AES_BLOCK_SIZE],
+    ) -> ZephyrResult<usize> {
+        self.check_mode(self.mode == CipherMode::Cbc)?;
+        let mut packet = self.packet(input, output)?;
+        let mut iv = *iv;
+        // the session is open in CBC mode, so the CBC handler is set by the driver
+        let handler = unsafe { self.ctx.ops.__bindgen_anon_1.cbc_crypt_hndlr }
+            .ok_or_else(not_implemented)?;
+        result(unsafe { (handler)(packet.ctx, &mut packet as *mut _, iv.as_mut_ptr()) })?;
+        Ok(packet.out_len as usize)
+    }
+
+    /// Encrypt or decrypt in CTR mode. This is the equivalent of `cipher_ctr_op`.
+    ///
+    /// `iv` is the part of the initial counter block that is not the counter.
+    pub fn ctr(&mut self, input: &[u8], output: &mut [u8], iv: &[u8]) -> ZephyrResult<usize> {
+        let counter_len = match self.mode {
+            CipherMode::Ctr { counter_len } => counter_len as usize / 8,
+            _ => return Err(invalid_argument()),
+        };
+        if iv.len() + counter_len != AES_BLOCK_SIZE {
+            return Err(invalid_argument());
+        }
+
+        let mut packet = self.packet(input, output)?;
+        let mut iv = iv.to_vec();
+        // the session is open in CTR mode, so the CTR handler is set by the driver
+        let handler = unsafe { self.ctx.ops.__bindgen_anon_1.ctr_crypt_hndlr }
+            .ok_or_else(not_implemented)?;
+        result(unsafe { (handler)(packet.ctx, &mut packet as *mut _, iv.as_mut_ptr()) })?;
+        Ok(packet.out_len as usize)
+    }
+
+    /// Encrypt or decrypt in an AEAD mode. The tag is written when encrypting and checked when
+    /// decrypting.
+    fn aead(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+        associated_data: &[u8],
+        tag: &mut [u8],
+        nonce: &[u8],
+    ) -> ZephyrResult<usize> {
+        let (tag_len, nonce_len, ccm) = match self.mode {
+            CipherMode::Ccm { tag_len, nonce_len } => (tag_len, nonce_len, true),
+            CipherMode::Gcm { tag_len, nonce_len } => (tag_len, nonce_len, false),
+            _ => return Err(invalid_argument()),
+        };
+        if tag.len() != tag_len as usize || nonce.len() != nonce_len as usize {
+            return Err(invalid_argument());
+        }
+
+        let mut packet = self.packet(input, output)?;
+        let mut nonce = nonce.to_vec();
+        let mut aead = ZAeadPacket {
+            pkt: &mut packet as *mut ZCipherPacket,
+            // the associated data is never modified by the driver
+            ad: associated_data.as_ptr() as *mut u8,
+            ad_len: associated_data.len() as u32,
+            tag: tag.as_mut_ptr(),
+        };
+
+        // the session is open in an AEAD mode, so its handler is set by the driver
+        let errno = unsafe {
+            let handler = if ccm {
+                self.ctx.ops.__bindgen_anon_1.ccm_crypt_hndlr
+            } else {
+                self.ctx.ops.__bindgen_anon_1.gcm_crypt_hndlr
+            }
+            .ok_or_else(not_implemented)?;
+            (handler)(packet.ctx, &mut aead as *mut _, nonce.as_mut_ptr())
+        };
+        result(errno)?;
+        Ok(packet.out_len as usize)
+    }
+
+    /// Encrypt or decrypt in CCM mode. This is the equivalent of `cipher_ccm_op`.
+    pub fn ccm(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+        associated_data: &[u8],
+        tag: &mut [u8],
+        nonce: &[u8],
+    ) -> ZephyrResult<usize> {
+        self.check_mode(matches!(self.mode, CipherMode::Ccm { .. }))?;
+        self.aead(input, output, associated_data, tag, nonce)
+    }
+
+    /// Encrypt or decrypt in GCM mode. This is the equivalent of `cipher_gcm_op`.
+    pub fn gcm(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+        associated_data: &[u8],
+        tag: &mut [u8],
+        nonce: &[u8],
+    ) -> ZephyrResult<usize> {
+        self.check_mode(matches!(self.mode, CipherMode::Gcm { .. }))?;
+        self.aead(input, output, associated_data, tag, nonce)
+    }
+}
+
+impl<'k> Drop for CipherSession<'k> {
+    /// Equivalent of the `cipher_free_session` API function.
+    fn drop(&mut self) {
+        let device = self.ctx.device;
+        // the session could only be opened on a crypto device
+        if let Some(device) = unsafe { device.as_ref() } {
+            if let Some(free_session) = api(device).and_then(|api| api.cipher_free_session) {
+                unsafe { (free_session)(device as *const Device, &mut *self.ctx as *mut _) };
+            }
+        }
+    }
+}
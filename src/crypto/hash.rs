@@ -0,0 +1,133 @@
+//! Hash sessions of the crypto driver API.
+
+use super::{api, not_implemented, result, Device, CONTEXT};
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use zephyr_sys::raw::{hash_ctx as ZHashContext, hash_pkt as ZHashPacket};
+
+/// Algorithm of a hash session.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum HashAlgorithm {
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// Length of the digest in bytes.
+    pub fn digest_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha224 => 28,
+            HashAlgorithm::Sha256 => 32,
+            HashAlgorithm::Sha384 => 48,
+            HashAlgorithm::Sha512 => 64,
+        }
+    }
+}
+
+impl From<HashAlgorithm> for zephyr_sys::raw::hash_algo {
+    fn from(other: HashAlgorithm) -> Self {
+        match other {
+            HashAlgorithm::Sha224 => zephyr_sys::raw::hash_algo_CRYPTO_HASH_ALGO_SHA224,
+            HashAlgorithm::Sha256 => zephyr_sys::raw::hash_algo_CRYPTO_HASH_ALGO_SHA256,
+            HashAlgorithm::Sha384 => zephyr_sys::raw::hash_algo_CRYPTO_HASH_ALGO_SHA384,
+            HashAlgorithm::Sha512 => zephyr_sys::raw::hash_algo_CRYPTO_HASH_ALGO_SHA512,
+        }
+    }
+}
+
+/// An open hash session, freed when dropped.
+pub struct HashSession {
+    // boxed as the driver keeps a pointer to the context
+    ctx: Box<ZHashContext>,
+    algorithm: HashAlgorithm,
+}
+
+impl HashSession {
+    /// Equivalent of the `hash_begin_session` API function.
+    ///
+    /// `device` MUST be a crypto device.
+    pub(super) unsafe fn begin(
+        device: &'static Device,
+        algorithm: HashAlgorithm,
+        flags: u16,
+    ) -> ZephyrResult<Self> {
+        let begin_session = api(device)
+            .and_then(|api| api.hash_begin_session)
+            .ok_or_else(not_implemented)?;
+
+        let mut ctx: Box<ZHashContext> = Box::new(core::mem::zeroed());
+        ctx.device = device as *const Device;
+        ctx.flags = flags;
+        result((begin_session)(
+            device as *const Device,
+            &mut *ctx as *mut ZHashContext,
+            algorithm.into(),
+        ))?;
+
+        Ok(HashSession { ctx, algorithm })
+    }
+
+    /// Algorithm of the session.
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    fn run(&mut self, data: &[u8], digest: Option<&mut [u8]>, finish: bool) -> ZephyrResult<()> {
+        let handler = self.ctx.hash_hndlr.ok_or_else(not_implemented)?;
+        let ctx = &mut *self.ctx as *mut ZHashContext;
+        let mut packet = ZHashPacket {
+            // the input is never modified by the driver
+            in_buf: data.as_ptr() as *mut u8,
+            in_len: data.len(),
+            out_buf: digest.map_or(core::ptr::null_mut(), |digest| digest.as_mut_ptr()),
+            ctx,
+        };
+        // the session is open, so the handler has been set by the driver
+        result(unsafe { (handler)(ctx, &mut packet as *mut ZHashPacket, finish) })
+    }
+
+    /// Add `data` to the digest. This is the equivalent of `hash_update`.
+    pub fn update(&mut self, data: &[u8]) -> ZephyrResult<()> {
+        self.run(data, None, false)
+    }
+
+    /// Add the last `data` and write the digest to `digest`. This is the equivalent of
+    /// `hash_compute`, the session can be used for the next digest afterwards.
+    ///
+    /// Fails with [ErrorNumber::InvalidArgument] if `digest` is shorter than
+    /// [HashAlgorithm::digest_len].
+    pub fn finish(&mut self, data: &[u8], digest: &mut [u8]) -> ZephyrResult<()> {
+        if digest.len() < self.algorithm.digest_len() {
+            return Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &CONTEXT,
+            ));
+        }
+
+        self.run(data, Some(digest), true)
+    }
+
+    /// Compute the digest of `data`.
+    pub fn digest(&mut self, data: &[u8]) -> ZephyrResult<Vec<u8>> {
+        let mut digest = vec![0; self.algorithm.digest_len()];
+        self.finish(data, &mut digest)?;
+        Ok(digest)
+    }
+}
+
+impl Drop for HashSession {
+    /// Equivalent of the `hash_free_session` API function.
+    fn drop(&mut self) {
+        let device = self.ctx.device;
+        // the session could only be opened on a crypto device
+        if let Some(device) = unsafe { device.as_ref() } {
+            if let Some(free_session) = api(device).and_then(|api| api.hash_free_session) {
+                unsafe { (free_session)(device as *const Device, &mut *self.ctx as *mut _) };
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Wrappers for the Zephyr crypto driver API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! The API of the crypto drivers is inlined in the Zephyr headers, so this module calls the driver
+//! API directly. Sessions are opened on a [Crypto] device and closed when dropped. Only synchronous
+//! operations with raw keys are supported.
+
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+pub use zephyr::device::Device;
+use zephyr_sys::raw::crypto_driver_api as CryptoDriverApi;
+
+pub mod cipher;
+pub mod hash;
+
+pub use cipher::{CipherMode, CipherOperation, CipherSession};
+pub use hash::{HashAlgorithm, HashSession};
+
+const CONTEXT: CryptoWrapperContext = CryptoWrapperContext {};
+
+/// Convert void pointer from C API to a crypto driver API Rust struct.
+fn api(device: &Device) -> Option<&CryptoDriverApi> {
+    // the API of a crypto device is always a crypto driver API
+    unsafe { (device.api as *const CryptoDriverApi).as_ref() }
+}
+
+fn not_implemented() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::NotImplemented, &CONTEXT)
+}
+
+fn result(errno: core::ffi::c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Equivalent of the `crypto_query_hwcaps` API function, returns the `CAP_*` flags of the driver.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+///
+/// This method fails with [ErrorNumber::NotImplemented] if the driver does not expose the call.
+pub unsafe fn query_hwcaps(device: &Device) -> ZephyrResult<u16> {
+    match api(device).and_then(|api| api.query_hw_caps) {
+        Some(query_hw_caps) => Ok((query_hw_caps)(device as *const Device) as u16),
+        None => Err(not_implemented()),
+    }
+}
+
+/// Session flags requested from the driver: raw keys, synchronous operations and separate buffers
+/// for input and output if supported, in place operations otherwise.
+fn session_flags(hwcaps: u16) -> u16 {
+    let mut flags = (zephyr_sys::raw::CAP_RAW_KEY | zephyr_sys::raw::CAP_SYNC_OPS) as u16;
+    if hwcaps & zephyr_sys::raw::CAP_SEPARATE_IO_BUFS as u16 != 0 {
+        flags |= zephyr_sys::raw::CAP_SEPARATE_IO_BUFS as u16;
+    } else {
+        flags |= zephyr_sys::raw::CAP_INPLACE_OPS as u16;
+    }
+    if hwcaps & zephyr_sys::raw::CAP_NO_IV_PREFIX as u16 != 0 {
+        flags |= zephyr_sys::raw::CAP_NO_IV_PREFIX as u16;
+    }
+    flags
+}
+
+/// High level wrapper for a crypto device.
+pub struct Crypto {
+    device: &'static Device,
+}
+
+impl Crypto {
+    /// Creates a new [Crypto] on the current interface.
+    ///
+    /// `device` MUST be a crypto device. If `device` is not a crypto device the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Crypto { device }
+    }
+
+    /// The `CAP_*` flags supported by the driver.
+    pub fn hwcaps(&self) -> ZephyrResult<u16> {
+        // device is required to be a crypto device in constructor
+        unsafe { query_hwcaps(self.device) }
+    }
+
+    /// Open an AES session using `key` for `operation` in `mode`.
+    ///
+    /// Fails with [ErrorNumber::NotSupported] if the driver does not support synchronous operations
+    /// with raw keys.
+    pub fn begin_cipher<'k>(
+        &mut self,
+        key: &'k [u8],
+        mode: CipherMode,
+        operation: CipherOperation,
+    ) -> ZephyrResult<CipherSession<'k>> {
+        let flags = self.checked_flags()?;
+        // device is required to be a crypto device in constructor
+        unsafe { CipherSession::begin(self.device, key, mode, operation, flags) }
+    }
+
+    /// Open a session computing digests with `algorithm`.
+    pub fn begin_hash(&mut self, algorithm: HashAlgorithm) -> ZephyrResult<HashSession> {
+        let flags = self.checked_flags()?;
+        // device is required to be a crypto device in constructor
+        unsafe { HashSession::begin(self.device, algorithm, flags) }
+    }
+
+    fn checked_flags(&self) -> ZephyrResult<u16> {
+        let hwcaps = self.hwcaps()?;
+        let flags = session_flags(hwcaps);
+        if hwcaps & flags == flags {
+            Ok(flags)
+        } else {
+            Err(ZephyrError::new_with_context(
+                ErrorNumber::NotSupported,
+                &CONTEXT,
+            ))
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CryptoWrapperContext {}
+
+impl Context for CryptoWrapperContext {
+    fn name(&self) -> &'static str {
+        "crypto wrapper"
+    }
+}
@@ -0,0 +1,143 @@
+//! Global logger of `defmt` shipping the encoded frames over a UART or RTT, requires the `defmt`
+//! feature. Crates instrumented with `defmt` then log through Zephyr with a fraction of the flash
+//! and bandwidth of formatted messages, the frames are decoded on the host with `defmt-print`.
+//!
+//! The transport is chosen at runtime with [init], frames logged before are dropped:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::defmt_logger::{self, Transport};
+//!# fn example(uart: &'static zephyr::device::Device) {
+//! unsafe { defmt_logger::init(Transport::Uart(uart)) };
+//! defmt::info!("booted after {=u32} ms", 42);
+//!# }
+//! ```
+//!
+//! [Transport::Uart] writes the frames with the polling API of the UART, e.g. of the
+//! `zephyr,console` UART if neither the console nor the logging of Zephyr use it.
+//! [Transport::Rtt] (feature `defmt-rtt`, `CONFIG_USE_SEGGER_RTT`) writes them into an up buffer of
+//! the RTT control block of Zephyr.
+//!
+//! Frames of threads are serialized by locking the scheduler. A frame logged from an interrupt
+//! while another frame is in progress is dropped, so the interrupted frame stays intact. The logger
+//! does not support SMP.
+
+use crate::sys::SpinLocked;
+use crate::uart::poll_out;
+#[cfg(feature = "defmt-rtt")]
+use core::ffi::{c_uint, c_void};
+use core::ptr::addr_of_mut;
+use zephyr::device::Device;
+
+/// Transport of the frames.
+#[derive(Copy, Clone)]
+pub enum Transport {
+    /// A UART written with `uart_poll_out`.
+    Uart(&'static Device),
+    /// The RTT up buffer with the index.
+    #[cfg(feature = "defmt-rtt")]
+    Rtt(u32),
+}
+
+#[cfg(feature = "defmt-rtt")]
+extern "C" {
+    fn SEGGER_RTT_WriteNoLock(buffer_index: c_uint, buffer: *const c_void, len: c_uint) -> c_uint;
+}
+
+static mut TRANSPORT: Option<Transport> = None;
+static mut ENCODER: defmt::Encoder = defmt::Encoder::new();
+
+#[derive(Copy, Clone)]
+struct Frame {
+    /// Set while a frame is in progress.
+    taken: bool,
+    /// Number of frames of interrupts nested in the frame in progress, which are dropped.
+    nested: usize,
+}
+
+/// Locked as not every target has compare and swap atomics.
+static FRAME: SpinLocked<Frame> = SpinLocked::new(Frame {
+    taken: false,
+    nested: 0,
+});
+
+/// Sets the transport of the frames.
+///
+/// The device of [Transport::Uart] MUST be a UART device. This function MUST be called before
+/// anything is logged, or from the thread that logs exclusively.
+pub unsafe fn init(transport: Transport) {
+    TRANSPORT = Some(transport);
+}
+
+fn write_transport(bytes: &[u8]) {
+    match unsafe { TRANSPORT } {
+        Some(Transport::Uart(device)) => {
+            for &byte in bytes {
+                // the device is required to be a UART device in init
+                unsafe { poll_out(device, byte) };
+            }
+        }
+        #[cfg(feature = "defmt-rtt")]
+        Some(Transport::Rtt(index)) => unsafe {
+            // the logger serializes the writes already
+            SEGGER_RTT_WriteNoLock(
+                index,
+                bytes.as_ptr() as *const c_void,
+                bytes.len() as c_uint,
+            );
+        },
+        None => {}
+    }
+}
+
+fn in_isr() -> bool {
+    unsafe { zephyr_sys::raw::k_is_in_isr() }
+}
+
+#[defmt::global_logger]
+struct ZephyrDefmtLogger;
+
+unsafe impl defmt::Logger for ZephyrDefmtLogger {
+    fn acquire() {
+        if !in_isr() {
+            unsafe { zephyr_sys::raw::k_sched_lock() };
+        }
+        let nested = FRAME.locked(|frame| {
+            // only an interrupt can start a frame while another one is in progress
+            if frame.taken {
+                frame.nested += 1;
+            }
+            core::mem::replace(&mut frame.taken, true)
+        });
+        if nested {
+            return;
+        }
+        unsafe { (*addr_of_mut!(ENCODER)).start_frame(write_transport) };
+    }
+
+    unsafe fn flush() {
+        // both transports write synchronously
+    }
+
+    unsafe fn release() {
+        let nested = FRAME.locked(|frame| {
+            let nested = frame.nested > 0;
+            if nested {
+                frame.nested -= 1;
+            }
+            nested
+        });
+        if !nested {
+            (*addr_of_mut!(ENCODER)).end_frame(write_transport);
+            FRAME.locked(|frame| frame.taken = false);
+        }
+        if !in_isr() {
+            zephyr_sys::raw::k_sched_unlock();
+        }
+    }
+
+    unsafe fn write(bytes: &[u8]) {
+        if FRAME.get().nested == 0 {
+            (*addr_of_mut!(ENCODER)).write(bytes, write_transport);
+        }
+    }
+}
@@ -0,0 +1,472 @@
+//! Lookup of the devices of Zephyr.
+//!
+//! [crate::device_dt_get] resolves a node of the devicetree to its device at build time, like
+//! `DEVICE_DT_GET` in C. Unlike a lookup with `device_get_binding`, a node that does not exist
+//! fails the build instead of returning `None` at runtime:
+//!
+//! ```ignore
+//! let console = zephyr_rust_wrappers::device_dt_get!(chosen(zephyr_console));
+//! let uart = unsafe { zephyr_rust_wrappers::uart::Uart::new(console) };
+//! ```
+//!
+//! The arms of the macro are generated from `devicetree_generated.h` of the build, which is set
+//! with the environment variable `ZEPHYR_DEVICETREE_HEADER` or found in the build directory
+//! `ZEPHYR_BINARY_DIR`.
+//!
+//! Devices whose driver failed to initialize are not ready. [not_ready] and [require_ready] check
+//! the devices at startup, [DeviceExt::init_status] tells the error of the driver.
+//!
+//! A [TypedDevice] is a device checked to implement the driver API of a [DeviceClass], from which
+//! the wrappers of the class are created without `unsafe`:
+//!
+//! ```ignore
+//! use zephyr_rust_wrappers::device::{class, TypedDevice};
+//! use zephyr_rust_wrappers::uart::Uart;
+//!
+//! let console = zephyr_rust_wrappers::device_dt_get!(chosen(zephyr_console));
+//! let uart = Uart::from_device(TypedDevice::<class::Uart>::new(console).unwrap());
+//! ```
+
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::ffi::CString;
+use alloc::format;
+use core::ffi::CStr;
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+pub use zephyr::device::Device;
+
+const CONTEXT: DeviceWrapperContext = DeviceWrapperContext {};
+
+include!(concat!(env!("OUT_DIR"), "/devicetree.rs"));
+
+/// Expands to a reference to the device object `symbol` defined by a driver, used by
+/// [crate::device_dt_get].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __device_dts_ord {
+    ($symbol:literal) => {{
+        extern "C" {
+            #[link_name = $symbol]
+            static DEVICE: $crate::device::Device;
+        }
+        // the device object is defined by the driver of the node and lives forever
+        unsafe { &DEVICE }
+    }};
+}
+
+/// Wrapper to the `device_is_ready` syscall.
+pub fn is_ready(device: &'static Device) -> bool {
+    unsafe { crate::sys::device_is_ready(device as *const Device) }
+}
+
+/// Result of the initialization of a device at boot.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InitStatus {
+    /// The device has not been initialized, e.g. it is initialized on demand with
+    /// `CONFIG_DEVICE_DEFERRED_INIT`.
+    NotInitialized,
+    /// The init function of the driver succeeded.
+    Ok,
+    /// The init function of the driver failed with the error number.
+    Failed(ErrorNumber),
+}
+
+/// State of a [Device], which is defined in the `zephyr` crate.
+pub trait DeviceExt {
+    /// Name of the device, usually the name of its devicetree node.
+    fn name(&self) -> &'static str;
+
+    /// Checks if the device is initialized successfully, see [is_ready].
+    fn is_ready(&'static self) -> bool;
+
+    /// Result of the initialization of the device.
+    fn init_status(&self) -> InitStatus;
+}
+
+impl DeviceExt for Device {
+    fn name(&self) -> &'static str {
+        if self.name.is_null() {
+            return "";
+        }
+        // the names of devices are string literals of the driver
+        let name = unsafe { CStr::from_ptr(self.name) };
+        name.to_str().unwrap_or("")
+    }
+
+    fn is_ready(&'static self) -> bool {
+        is_ready(self)
+    }
+
+    fn init_status(&self) -> InitStatus {
+        // the state is allocated by the device macros of the driver and lives forever
+        let state = match unsafe { self.state.as_ref() } {
+            Some(state) => state,
+            None => return InitStatus::NotInitialized,
+        };
+
+        if !state.initialized() {
+            InitStatus::NotInitialized
+        } else if state.init_res == 0 {
+            InitStatus::Ok
+        } else {
+            // the result is stored as positive error number
+            InitStatus::Failed(ErrorNumber::from(state.init_res as i32))
+        }
+    }
+}
+
+/// All devices that are defined statically, e.g. from the devicetree. Wrapper to the
+/// `z_device_get_all_static` function.
+pub fn all() -> &'static [Device] {
+    let mut devices: *const Device = core::ptr::null();
+    let len = unsafe { zephyr_sys::raw::z_device_get_all_static(&mut devices) };
+    if devices.is_null() {
+        return &[];
+    }
+    // the devices are in an iterable section which lives forever
+    unsafe { core::slice::from_raw_parts(devices, len as usize) }
+}
+
+/// Looks up the ready device `name`. Wrapper to the `device_get_binding` syscall.
+pub fn get_binding(name: &str) -> Option<&'static Device> {
+    let name = CString::new(name).ok()?;
+    unsafe { zephyr_sys::syscalls::any::device_get_binding(name.as_ptr()).as_ref() }
+}
+
+/// Looks up the device `name` among [all] devices, including the devices that are not ready.
+pub fn find(name: &str) -> Option<&'static Device> {
+    all().iter().find(|device| device.name() == name)
+}
+
+/// Devices that are not ready, e.g. because the init function of their driver failed.
+///
+/// ```no_run
+/// use zephyr_rust_wrappers::device::{self, DeviceExt};
+///
+/// for device in device::not_ready() {
+///     println!("{} is not ready: {:?}", device.name(), device.init_status());
+/// }
+/// ```
+pub fn not_ready() -> impl Iterator<Item = &'static Device> {
+    all().iter().filter(|device| !is_ready(*device))
+}
+
+/// Checks that all devices `names` are ready.
+///
+/// This function fails with [ErrorNumber::NoDevice] for the first device that does not exist or
+/// that is not ready, the error names the device and the error of its driver is the source.
+pub fn require_ready(names: &[&str]) -> ZephyrResult<()> {
+    for &name in names {
+        let device = find(name).ok_or_else(|| {
+            ZephyrError::new_with_context(ErrorNumber::NoDevice, &CONTEXT)
+                .with_operation(format!("find({})", name))
+        })?;
+        if is_ready(device) {
+            continue;
+        }
+
+        let error = ZephyrError::new_with_context(ErrorNumber::NoDevice, &CONTEXT)
+            .with_operation(format!("init({})", name));
+        return Err(match device.init_status() {
+            InitStatus::Failed(errno) => error.with_source(ZephyrError::new(errno)),
+            _ => error,
+        });
+    }
+    Ok(())
+}
+
+/// Class of devices sharing a driver API, e.g. all GPIO controllers.
+///
+/// Implementations MUST only accept devices whose driver API is the API of the class, the
+/// wrappers of the class rely on it.
+pub unsafe trait DeviceClass {
+    /// Name of the class, as in `DEVICE_API(name, ...)` of the drivers.
+    const NAME: &'static str;
+
+    /// Checks if the driver API of `device` is the API of the class. This is the equivalent of
+    /// the `DEVICE_API_IS` macro of the Zephyr API.
+    fn is_api(device: &'static Device) -> bool;
+}
+
+/// Handle of a device that is ready and implements the driver API of the class `C`.
+///
+/// The wrappers of the class are created from the handle without `unsafe`, e.g. with
+/// [crate::i2c::I2c::from_device] for [class::I2c].
+pub struct TypedDevice<C: DeviceClass> {
+    device: &'static Device,
+    class: PhantomData<C>,
+}
+
+impl<C: DeviceClass> TypedDevice<C> {
+    /// Creates a handle after checking that `device` is of the class `C`.
+    ///
+    /// This method fails with [ErrorNumber::NoDevice] if the device is not ready (see
+    /// `device_is_ready`) and with [ErrorNumber::NotSupported] if the driver API of the device is
+    /// not the API of the class.
+    pub fn new(device: &'static Device) -> ZephyrResult<Self> {
+        if !is_ready(device) {
+            return Err(ZephyrError::new_with_context(ErrorNumber::NoDevice, &CONTEXT));
+        }
+
+        if !C::is_api(device) {
+            return Err(ZephyrError::new_with_context(
+                ErrorNumber::NotSupported,
+                &CONTEXT,
+            ));
+        }
+
+        Ok(unsafe { Self::new_unchecked(device) })
+    }
+
+    /// Creates a handle without any checks.
+    ///
+    /// `device` MUST be a device of the class `C`. If it is not, the behaviour when calling any
+    /// method of a wrapper created from the handle is undefined!
+    pub const unsafe fn new_unchecked(device: &'static Device) -> Self {
+        TypedDevice {
+            device,
+            class: PhantomData,
+        }
+    }
+
+    /// The underlying device.
+    pub fn device(&self) -> &'static Device {
+        self.device
+    }
+}
+
+impl<C: DeviceClass> Clone for TypedDevice<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: DeviceClass> Copy for TypedDevice<C> {}
+
+impl<C: DeviceClass> Debug for TypedDevice<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TypedDevice")
+            .field("class", &C::NAME)
+            .field("device", &(self.device as *const Device))
+            .finish()
+    }
+}
+
+/// Declares a marker type of a [DeviceClass] whose driver APIs are in the iterable section with
+/// the bounds `start` and `end`, which the `DEVICE_API` macro of drivers places them in.
+macro_rules! device_class {
+    ($(#[$attr:meta])* $class:ident, $name:literal, $start:ident, $end:ident) => {
+        $(#[$attr])*
+        pub enum $class {}
+
+        #[cfg(not(feature = "mock-syscalls"))]
+        extern "C" {
+            // only the addresses of the bounds are used
+            static $start: u8;
+            static $end: u8;
+        }
+
+        unsafe impl $crate::device::DeviceClass for $class {
+            const NAME: &'static str = $name;
+
+            #[cfg(not(feature = "mock-syscalls"))]
+            fn is_api(device: &'static $crate::device::Device) -> bool {
+                let api = device.api as usize;
+                let (start, end) = unsafe {
+                    (
+                        core::ptr::addr_of!($start) as usize,
+                        core::ptr::addr_of!($end) as usize,
+                    )
+                };
+                api >= start && api < end
+            }
+
+            #[cfg(feature = "mock-syscalls")]
+            fn is_api(device: &'static $crate::device::Device) -> bool {
+                $crate::mock::device_api_is(device)
+            }
+        }
+    };
+}
+
+/// Marker types of the device classes with wrappers in this crate. There is no class of DMIC
+/// devices, their API `_dmic_ops` is not placed in an iterable section by `DEVICE_API`.
+pub mod class {
+    device_class!(
+        /// ADC devices, see [crate::adc].
+        Adc,
+        "adc",
+        _adc_driver_api_list_start,
+        _adc_driver_api_list_end
+    );
+    device_class!(
+        /// CAN controllers, see [crate::can].
+        Can,
+        "can",
+        _can_driver_api_list_start,
+        _can_driver_api_list_end
+    );
+    device_class!(
+        /// Cellular modems, see [crate::cellular].
+        Cellular,
+        "cellular",
+        _cellular_driver_api_list_start,
+        _cellular_driver_api_list_end
+    );
+    device_class!(
+        /// Battery chargers, see [crate::charger].
+        Charger,
+        "charger",
+        _charger_driver_api_list_start,
+        _charger_driver_api_list_end
+    );
+    device_class!(
+        /// Counter devices, see [crate::counter].
+        Counter,
+        "counter",
+        _counter_driver_api_list_start,
+        _counter_driver_api_list_end
+    );
+    device_class!(
+        /// Displays, see [crate::display].
+        Display,
+        "display",
+        _display_driver_api_list_start,
+        _display_driver_api_list_end
+    );
+    device_class!(
+        /// DMA controllers, see [crate::dma].
+        Dma,
+        "dma",
+        _dma_driver_api_list_start,
+        _dma_driver_api_list_end
+    );
+    device_class!(
+        /// Entropy sources, see [crate::entropy].
+        Entropy,
+        "entropy",
+        _entropy_driver_api_list_start,
+        _entropy_driver_api_list_end
+    );
+    device_class!(
+        /// Flash devices, see [crate::flash].
+        Flash,
+        "flash",
+        _flash_driver_api_list_start,
+        _flash_driver_api_list_end
+    );
+    device_class!(
+        /// Fuel gauges, see [crate::fuel_gauge].
+        FuelGauge,
+        "fuel_gauge",
+        _fuel_gauge_driver_api_list_start,
+        _fuel_gauge_driver_api_list_end
+    );
+    device_class!(
+        /// GPIO controllers, see [crate::gpio].
+        Gpio,
+        "gpio",
+        _gpio_driver_api_list_start,
+        _gpio_driver_api_list_end
+    );
+    device_class!(
+        /// I2C controllers, see [crate::i2c].
+        I2c,
+        "i2c",
+        _i2c_driver_api_list_start,
+        _i2c_driver_api_list_end
+    );
+    device_class!(
+        /// I2S controllers, see [crate::i2s].
+        I2s,
+        "i2s",
+        _i2s_driver_api_list_start,
+        _i2s_driver_api_list_end
+    );
+    device_class!(
+        /// LED controllers, see [crate::led].
+        Led,
+        "led",
+        _led_driver_api_list_start,
+        _led_driver_api_list_end
+    );
+    device_class!(
+        /// LoRa modems, see [crate::lora].
+        Lora,
+        "lora",
+        _lora_driver_api_list_start,
+        _lora_driver_api_list_end
+    );
+    device_class!(
+        /// PWM controllers, see [crate::pwm].
+        Pwm,
+        "pwm",
+        _pwm_driver_api_list_start,
+        _pwm_driver_api_list_end
+    );
+    device_class!(
+        /// Regulators, see [crate::regulator].
+        Regulator,
+        "regulator",
+        _regulator_driver_api_list_start,
+        _regulator_driver_api_list_end
+    );
+    device_class!(
+        /// Real-time clocks, see [crate::rtc].
+        Rtc,
+        "rtc",
+        _rtc_driver_api_list_start,
+        _rtc_driver_api_list_end
+    );
+    device_class!(
+        /// Sensors, see [crate::sensor].
+        Sensor,
+        "sensor",
+        _sensor_driver_api_list_start,
+        _sensor_driver_api_list_end
+    );
+    device_class!(
+        /// SMBus controllers, see [crate::smbus].
+        Smbus,
+        "smbus",
+        _smbus_driver_api_list_start,
+        _smbus_driver_api_list_end
+    );
+    device_class!(
+        /// SPI controllers, see [crate::spi].
+        Spi,
+        "spi",
+        _spi_driver_api_list_start,
+        _spi_driver_api_list_end
+    );
+    device_class!(
+        /// UART devices, see [crate::uart].
+        Uart,
+        "uart",
+        _uart_driver_api_list_start,
+        _uart_driver_api_list_end
+    );
+    device_class!(
+        /// Video devices, see [crate::video].
+        Video,
+        "video",
+        _video_driver_api_list_start,
+        _video_driver_api_list_end
+    );
+    device_class!(
+        /// Watchdogs, see [crate::watchdog].
+        Watchdog,
+        "wdt",
+        _wdt_driver_api_list_start,
+        _wdt_driver_api_list_end
+    );
+}
+
+#[derive(Debug)]
+struct DeviceWrapperContext {}
+
+impl Context for DeviceWrapperContext {
+    fn name(&self) -> &'static str {
+        "device wrapper"
+    }
+}
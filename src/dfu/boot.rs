@@ -0,0 +1,209 @@
+//! Image management of MCUboot, requires `CONFIG_MCUBOOT_IMG_MANAGER`.
+//!
+//! MCUboot boots the image in the primary slot and swaps in the image of the secondary slot on
+//! request. An upgrade requested with [UpgradeMode::Test] is reverted at the next reboot unless the
+//! new image confirms itself, so an image that fails before confirming rolls back to the previous
+//! one:
+//!
+//! ```no_run
+//! use zephyr_rust_wrappers::dfu::boot;
+//!
+//! if !boot::is_confirmed() {
+//!     // confirm only once the image has proven to work, e.g. after connecting to the server
+//!     boot::confirm().expect("confirming image");
+//! }
+//! ```
+//!
+//! After writing a new image into the secondary slot, e.g. with
+//! [super::flash_img::FirmwareWriter], the upgrade is requested with [request_upgrade] and done by
+//! MCUboot on the next reboot. The functions for the first image have variants with an image index
+//! for multi-image setups (`CONFIG_UPDATEABLE_IMAGE_NUMBER`).
+//!
+//! Slots are flash areas of [crate::flash::area], named by their id.
+
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use core::fmt::{Display, Formatter};
+use zephyr_sys::raw::mcuboot_img_header as ZImageHeader;
+
+const CONTEXT: BootWrapperContext = BootWrapperContext {};
+
+fn result(errno: i32) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Swap that MCUboot does at the next reboot, as reported by `mcuboot_swap_type`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SwapType {
+    /// The primary slot is booted as is.
+    None,
+    /// The image of the secondary slot is swapped in and reverted at the next reboot unless it is
+    /// confirmed.
+    Test,
+    /// The image of the secondary slot is swapped in permanently.
+    Permanent,
+    /// The unconfirmed image of the primary slot is swapped back.
+    Revert,
+    /// The swap failed as the image of the secondary slot is invalid.
+    Fail,
+}
+
+impl SwapType {
+    fn from_raw(swap_type: i32) -> ZephyrResult<Self> {
+        match swap_type as u32 {
+            zephyr_sys::raw::BOOT_SWAP_TYPE_NONE => Ok(SwapType::None),
+            zephyr_sys::raw::BOOT_SWAP_TYPE_TEST => Ok(SwapType::Test),
+            zephyr_sys::raw::BOOT_SWAP_TYPE_PERM => Ok(SwapType::Permanent),
+            zephyr_sys::raw::BOOT_SWAP_TYPE_REVERT => Ok(SwapType::Revert),
+            zephyr_sys::raw::BOOT_SWAP_TYPE_FAIL => Ok(SwapType::Fail),
+            _ => Err(ZephyrError::new_with_context(
+                ErrorNumber::NotSupported,
+                &CONTEXT,
+            )),
+        }
+    }
+}
+
+/// How the image of the secondary slot is upgraded to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum UpgradeMode {
+    /// The image is booted once and reverted unless it confirms itself, `BOOT_UPGRADE_TEST`.
+    Test,
+    /// The image replaces the current one without confirmation, `BOOT_UPGRADE_PERMANENT`.
+    Permanent,
+}
+
+impl From<UpgradeMode> for i32 {
+    fn from(other: UpgradeMode) -> Self {
+        match other {
+            UpgradeMode::Test => zephyr_sys::raw::BOOT_UPGRADE_TEST as i32,
+            UpgradeMode::Permanent => zephyr_sys::raw::BOOT_UPGRADE_PERMANENT as i32,
+        }
+    }
+}
+
+/// Semantic version of an image, ordered by all its parts including the build number.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ImageVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub revision: u16,
+    pub build_num: u32,
+}
+
+impl Display for ImageVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}+{}",
+            self.major, self.minor, self.revision, self.build_num
+        )
+    }
+}
+
+/// Header of the image in a slot.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ImageHeader {
+    pub version: ImageVersion,
+    /// Size of the image without header and trailer.
+    pub size: u32,
+}
+
+/// Wrapper to the `mcuboot_swap_type` function.
+pub fn swap_type() -> ZephyrResult<SwapType> {
+    let swap_type = unsafe { zephyr_sys::raw::mcuboot_swap_type() };
+    if swap_type < 0 {
+        return Err(ZephyrError::from_errno_with_context(swap_type, &CONTEXT));
+    }
+    SwapType::from_raw(swap_type)
+}
+
+/// Wrapper to the `mcuboot_swap_type_multi` function, the swap of the image with `image_index`.
+pub fn swap_type_of(image_index: i32) -> ZephyrResult<SwapType> {
+    let swap_type = unsafe { zephyr_sys::raw::mcuboot_swap_type_multi(image_index) };
+    if swap_type < 0 {
+        return Err(ZephyrError::from_errno_with_context(swap_type, &CONTEXT));
+    }
+    SwapType::from_raw(swap_type)
+}
+
+/// Wrapper to the `boot_request_upgrade` function, requests the upgrade to the image of the
+/// secondary slot at the next reboot.
+pub fn request_upgrade(mode: UpgradeMode) -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::boot_request_upgrade(mode.into()) })
+}
+
+/// Wrapper to the `boot_request_upgrade_multi` function, requests the upgrade of the image with
+/// `image_index`.
+pub fn request_upgrade_of(image_index: i32, mode: UpgradeMode) -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::boot_request_upgrade_multi(image_index, mode.into()) })
+}
+
+/// Wrapper to the `boot_is_img_confirmed` function, whether the running image is confirmed.
+pub fn is_confirmed() -> bool {
+    unsafe { zephyr_sys::raw::boot_is_img_confirmed() }
+}
+
+/// Wrapper to the `boot_write_img_confirmed` function, confirms the running image so it is not
+/// reverted at the next reboot.
+pub fn confirm() -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::boot_write_img_confirmed() })
+}
+
+/// Wrapper to the `boot_write_img_confirmed_multi` function, confirms the image with
+/// `image_index`.
+pub fn confirm_image(image_index: i32) -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::boot_write_img_confirmed_multi(image_index) })
+}
+
+/// Wrapper to the `boot_fetch_active_slot` function, the id of the flash area of the running
+/// image.
+pub fn active_slot() -> u8 {
+    unsafe { zephyr_sys::raw::boot_fetch_active_slot() }
+}
+
+/// Wrapper to the `boot_read_bank_header` function, reads the header of the image in the slot with
+/// the flash area id `area_id`.
+///
+/// This function fails if the slot does not hold a valid image, e.g. because it is erased.
+pub fn read_header(area_id: u8) -> ZephyrResult<ImageHeader> {
+    // zeroed as the header is plain data filled by the call
+    let mut header: ZImageHeader = unsafe { core::mem::zeroed() };
+    result(unsafe {
+        zephyr_sys::raw::boot_read_bank_header(
+            area_id,
+            &mut header as *mut ZImageHeader,
+            core::mem::size_of::<ZImageHeader>(),
+        )
+    })?;
+
+    // version 1 is the only header version
+    let header = unsafe { header.h.v1 };
+    Ok(ImageHeader {
+        version: ImageVersion {
+            major: header.sem_ver.major,
+            minor: header.sem_ver.minor,
+            revision: header.sem_ver.revision,
+            build_num: header.sem_ver.build_num,
+        },
+        size: header.image_size,
+    })
+}
+
+/// Wrapper to the `boot_erase_img_bank` function, erases the slot with the flash area id
+/// `area_id`, e.g. before writing a new image into it.
+pub fn erase_slot(area_id: u8) -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::boot_erase_img_bank(area_id) })
+}
+
+#[derive(Debug)]
+struct BootWrapperContext {}
+
+impl Context for BootWrapperContext {
+    fn name(&self) -> &'static str {
+        "boot wrapper"
+    }
+}
@@ -0,0 +1,253 @@
+//! Streaming writer of firmware images into a slot of MCUboot, requires `CONFIG_IMG_MANAGER` and
+//! `CONFIG_STREAM_FLASH`.
+//!
+//! A [FirmwareWriter] takes the image in chunks of any size as they arrive from the transport,
+//! e.g. Bluetooth, HTTP or MQTT, and writes them into the secondary slot in blocks of
+//! `CONFIG_IMG_BLOCK_BUF_SIZE` bytes. Once the whole image is written, [FirmwareWriter::verify]
+//! compares it with its SHA-256 hash (`CONFIG_IMG_ENABLE_IMAGE_CHECK`) and
+//! [FirmwareWriter::finish] requests the upgrade to it:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::ZephyrResult;
+//! use zephyr_rust_wrappers::dfu::boot::UpgradeMode;
+//! use zephyr_rust_wrappers::dfu::flash_img::FirmwareWriter;
+//!
+//!# fn example(chunks: &[&[u8]], size: usize, hash: &[u8; 32]) -> ZephyrResult<()> {
+//! let mut writer = FirmwareWriter::new()?;
+//! writer.set_total_size(size);
+//! for chunk in chunks {
+//!     writer.write(chunk)?;
+//!     println!("received {:?}%", writer.progress().percent());
+//! }
+//! writer.verify(hash)?;
+//! writer.finish(UpgradeMode::Test)?;
+//!# Ok(())
+//!# }
+//! ```
+
+use super::boot::{self, UpgradeMode};
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use zephyr_sys::raw::{flash_img_check as ZFlashImgCheck, flash_img_context as ZFlashImgContext};
+
+const CONTEXT: FlashImgWrapperContext = FlashImgWrapperContext {};
+
+/// Length of the SHA-256 hash of an image.
+pub const HASH_LEN: usize = 32;
+
+fn result(errno: i32) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Progress of a [FirmwareWriter].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Progress {
+    /// Bytes passed to the writer, including those that are still buffered.
+    pub received: usize,
+    /// Bytes written to the flash.
+    pub written: usize,
+    /// Size of the image, if set with [FirmwareWriter::set_total_size].
+    pub total: Option<usize>,
+}
+
+impl Progress {
+    /// Received bytes in percent of the size of the image, `None` if the size is unknown.
+    pub fn percent(&self) -> Option<u8> {
+        match self.total {
+            Some(0) => Some(100),
+            Some(total) => Some((self.received.min(total) * 100 / total) as u8),
+            None => None,
+        }
+    }
+}
+
+/// Writer of an image into a slot, a wrapper to the `flash_img_*` functions.
+pub struct FirmwareWriter {
+    // boxed as the stream of the context points into its own buffer
+    context: Box<ZFlashImgContext>,
+    area_id: u8,
+    received: usize,
+    total: Option<usize>,
+}
+
+impl FirmwareWriter {
+    /// Creates a writer into the secondary slot of the running image. Wrapper to the
+    /// `flash_img_init` function.
+    pub fn new() -> ZephyrResult<Self> {
+        let mut context = Self::new_context();
+        result(unsafe { zephyr_sys::raw::flash_img_init(&mut *context) })?;
+        // the flash area is opened by the call
+        let area_id = unsafe { (*context.flash_area).fa_id };
+        Ok(Self::from_context(context, area_id))
+    }
+
+    /// Creates a writer into the slot with the flash area id `area_id`. Wrapper to the
+    /// `flash_img_init_id` function.
+    pub fn for_area(area_id: u8) -> ZephyrResult<Self> {
+        let mut context = Self::new_context();
+        result(unsafe { zephyr_sys::raw::flash_img_init_id(&mut *context, area_id) })?;
+        Ok(Self::from_context(context, area_id))
+    }
+
+    fn new_context() -> Box<ZFlashImgContext> {
+        // zeroed as the context is initialized by flash_img_init
+        Box::new(unsafe { core::mem::zeroed() })
+    }
+
+    fn from_context(context: Box<ZFlashImgContext>, area_id: u8) -> Self {
+        FirmwareWriter {
+            context,
+            area_id,
+            received: 0,
+            total: None,
+        }
+    }
+
+    /// Flash area id of the slot the image is written to.
+    pub fn area_id(&self) -> u8 {
+        self.area_id
+    }
+
+    /// Sets the size of the image, e.g. from the header of the transport. With the size, writes
+    /// beyond it fail and [Progress::percent] is known.
+    pub fn set_total_size(&mut self, size: usize) {
+        self.total = Some(size);
+    }
+
+    /// Bytes written to the flash. Wrapper to the `flash_img_bytes_written` function.
+    pub fn bytes_written(&mut self) -> usize {
+        unsafe { zephyr_sys::raw::flash_img_bytes_written(&mut *self.context) }
+    }
+
+    /// Progress of the image.
+    pub fn progress(&mut self) -> Progress {
+        Progress {
+            received: self.received,
+            written: self.bytes_written(),
+            total: self.total,
+        }
+    }
+
+    /// Appends `chunk` to the image. Wrapper to the `flash_img_buffered_write` function.
+    ///
+    /// This method fails with [ErrorNumber::FileTooLarge] if the image grows beyond the size set
+    /// with [FirmwareWriter::set_total_size].
+    pub fn write(&mut self, chunk: &[u8]) -> ZephyrResult<()> {
+        if let Some(total) = self.total {
+            if self.received + chunk.len() > total {
+                return Err(ZephyrError::new_with_context(
+                    ErrorNumber::FileTooLarge,
+                    &CONTEXT,
+                ));
+            }
+        }
+
+        self.buffered_write(chunk, false)?;
+        self.received += chunk.len();
+        Ok(())
+    }
+
+    /// Writes the buffered bytes to the flash. The partial block is written at the end of the
+    /// image only, as the stream is not continued in the same block afterwards.
+    fn flush(&mut self) -> ZephyrResult<()> {
+        self.buffered_write(&[], true)
+    }
+
+    fn buffered_write(&mut self, data: &[u8], flush: bool) -> ZephyrResult<()> {
+        result(unsafe {
+            zephyr_sys::raw::flash_img_buffered_write(
+                &mut *self.context,
+                data.as_ptr(),
+                data.len(),
+                flush,
+            )
+        })
+    }
+
+    /// Flushes the image and compares the SHA-256 hash of the written bytes with `hash`. Wrapper
+    /// to the `flash_img_check` function.
+    ///
+    /// This method fails if the hashes differ or the image is incomplete, see
+    /// [FirmwareWriter::set_total_size].
+    pub fn verify(&mut self, hash: &[u8; HASH_LEN]) -> ZephyrResult<()> {
+        self.flush()?;
+        self.check_complete()?;
+
+        let check = ZFlashImgCheck {
+            match_: hash.as_ptr(),
+            clen: self.received,
+        };
+        result(unsafe {
+            zephyr_sys::raw::flash_img_check(&mut *self.context, &check, self.area_id)
+        })
+    }
+
+    /// Flushes the image and requests the upgrade to it with `mode`, see
+    /// [boot::request_upgrade]. The image is written into the secondary slot of the running image
+    /// for this, i.e. the writer was created with [FirmwareWriter::new].
+    ///
+    /// This method fails with [ErrorNumber::InvalidArgument] if the image is written into another
+    /// slot and with [ErrorNumber::NoData] if the image is incomplete.
+    pub fn finish(mut self, mode: UpgradeMode) -> ZephyrResult<()> {
+        if self.area_id != Self::secondary_area_id()? {
+            return Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &CONTEXT,
+            ));
+        }
+        self.flush()?;
+        self.check_complete()?;
+        boot::request_upgrade(mode)
+    }
+
+    /// Flash area id of the secondary slot of the running image, the slot `flash_img_init`
+    /// writes to.
+    fn secondary_area_id() -> ZephyrResult<u8> {
+        let mut context = Self::new_context();
+        result(unsafe { zephyr_sys::raw::flash_img_init(&mut *context) })?;
+        // the flash area is opened by the call, nothing is written before the first block
+        let area_id = unsafe { (*context.flash_area).fa_id };
+        unsafe { zephyr_sys::raw::flash_area_close(context.flash_area) };
+        Ok(area_id)
+    }
+
+    fn check_complete(&self) -> ZephyrResult<()> {
+        let complete = match self.total {
+            Some(total) => self.received == total,
+            None => self.received > 0,
+        };
+        if complete {
+            Ok(())
+        } else {
+            Err(ZephyrError::new_with_context(ErrorNumber::NoData, &CONTEXT))
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for FirmwareWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        FirmwareWriter::write(self, buf)
+            .map(|_| buf.len())
+            .map_err(|error| std::io::Error::from_raw_os_error(error.number().into()))
+    }
+
+    /// Does nothing, the buffered bytes are written by [FirmwareWriter::verify] and
+    /// [FirmwareWriter::finish] at the end of the image.
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct FlashImgWrapperContext {}
+
+impl Context for FlashImgWrapperContext {
+    fn name(&self) -> &'static str {
+        "flash image wrapper"
+    }
+}
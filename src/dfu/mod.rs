@@ -0,0 +1,8 @@
+//! Wrappers for the device firmware upgrade of Zephyr.
+//!
+//! [boot] manages the images of the bootloader MCUboot, e.g. to request the upgrade to a new image
+//! and to confirm the running image after an upgrade. [flash_img] writes a new image received in
+//! chunks into the slot of MCUboot.
+
+pub mod boot;
+pub mod flash_img;
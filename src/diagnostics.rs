@@ -0,0 +1,213 @@
+//! Runtime diagnostics of the threads and heaps, e.g. for a task that periodically reports the high
+//! water marks of the stacks and the usage of the heap.
+//!
+//! [threads] lists the threads of the system (`CONFIG_THREAD_MONITOR`) as [ThreadInfo] with the
+//! used part of their stacks, which requires `CONFIG_INIT_STACKS` and `CONFIG_THREAD_STACK_INFO`
+//! like the thread analyzer of Zephyr. [print_thread_analysis] prints the report of the analyzer
+//! itself (`CONFIG_THREAD_ANALYZER`).
+//!
+//! [heap_stats] and [system_heap_stats] return the statistics of a `sys_heap`
+//! (`CONFIG_SYS_HEAP_RUNTIME_STATS`). Zephyr has no measure of the fragmentation of a heap, but
+//! [print_heap_info] prints the free chunks by size.
+//!
+//! ```no_run
+//! use zephyr_rust_wrappers::diagnostics;
+//!
+//! for thread in diagnostics::threads() {
+//!     println!("{}", thread);
+//! }
+//! println!("heap: {}", diagnostics::system_heap_stats().unwrap());
+//! ```
+
+use crate::{Context, ZephyrError, ZephyrResult};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::{c_void, CStr};
+use core::fmt::{Display, Formatter};
+use zephyr_sys::raw::k_heap as ZKHeap;
+use zephyr_sys::raw::k_thread as ZThread;
+use zephyr_sys::raw::sys_heap as ZSysHeap;
+use zephyr_sys::raw::sys_memory_stats as ZSysMemoryStats;
+
+const CONTEXT: DiagnosticsWrapperContext = DiagnosticsWrapperContext {};
+
+extern "C" {
+    /// Heap of `k_malloc` with `CONFIG_HEAP_MEM_POOL_SIZE`, not declared in the public headers.
+    static mut _system_heap: ZKHeap;
+}
+
+fn result(errno: i32) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Information about a thread and the usage of its stack.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ThreadInfo {
+    /// Name of the thread, the address of the thread without `CONFIG_THREAD_NAME` or a name.
+    pub name: String,
+    /// Size of the stack in bytes.
+    pub stack_size: usize,
+    /// Most bytes of the stack that have been used, `None` if the stack could not be inspected
+    /// (e.g. of a user thread from a user thread).
+    pub stack_used: Option<usize>,
+}
+
+impl ThreadInfo {
+    /// Share of the stack that has been used in percent.
+    pub fn stack_usage_percent(&self) -> Option<u8> {
+        let used = self.stack_used?;
+        if self.stack_size == 0 {
+            return None;
+        }
+        Some((used * 100 / self.stack_size) as u8)
+    }
+}
+
+impl Display for ThreadInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match (self.stack_used, self.stack_usage_percent()) {
+            (Some(used), Some(percent)) => write!(
+                f,
+                "{}: stack {}/{} bytes ({}%)",
+                self.name, used, self.stack_size, percent
+            ),
+            _ => write!(f, "{}: stack ?/{} bytes", self.name, self.stack_size),
+        }
+    }
+}
+
+/// Information about the thread `thread`.
+///
+/// `thread` MUST point to a running thread.
+unsafe fn thread_info(thread: *const ZThread) -> ThreadInfo {
+    let name = zephyr_sys::raw::k_thread_name_get(thread as *mut ZThread);
+    let name = if name.is_null() || *name == 0 {
+        format!("{:p}", thread)
+    } else {
+        CStr::from_ptr(name).to_string_lossy().into_owned()
+    };
+
+    let stack_size = (*thread).stack_info.size;
+    let mut unused = 0;
+    let res = zephyr_sys::syscalls::any::k_thread_stack_space_get(thread, &mut unused);
+    ThreadInfo {
+        name,
+        stack_size,
+        stack_used: (res == 0).then(|| stack_size.saturating_sub(unused)),
+    }
+}
+
+unsafe extern "C" fn thread_callback(thread: *const ZThread, user_data: *mut c_void) {
+    let threads = &mut *(user_data as *mut Vec<ThreadInfo>);
+    threads.push(thread_info(thread));
+}
+
+/// Lists the threads of the system with `k_thread_foreach_unlocked`.
+///
+/// The list of threads is not locked while inspecting the threads, so allocating for the list is
+/// allowed. Threads that are created or aborted meanwhile may be missing.
+pub fn threads() -> Vec<ThreadInfo> {
+    let mut threads: Vec<ThreadInfo> = Vec::new();
+    unsafe {
+        zephyr_sys::raw::k_thread_foreach_unlocked(
+            Some(thread_callback),
+            &mut threads as *mut Vec<ThreadInfo> as *mut c_void,
+        )
+    };
+    threads
+}
+
+/// Wrapper to the `thread_analyzer_print` function, prints the stack usage (and with
+/// `CONFIG_THREAD_RUNTIME_STATS` the CPU usage) of all threads running on `cpu`.
+pub fn print_thread_analysis(cpu: u32) {
+    unsafe { zephyr_sys::raw::thread_analyzer_print(cpu) };
+}
+
+/// Statistics of a heap in bytes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct HeapStats {
+    pub free_bytes: usize,
+    pub allocated_bytes: usize,
+    /// Most bytes allocated at once since the start or the last [reset_heap_max].
+    pub max_allocated_bytes: usize,
+}
+
+impl HeapStats {
+    /// Size of the heap without the metadata of Zephyr.
+    pub fn size(&self) -> usize {
+        self.free_bytes + self.allocated_bytes
+    }
+
+    /// Share of the heap that is allocated in percent.
+    pub fn usage_percent(&self) -> u8 {
+        match self.size() {
+            0 => 0,
+            size => (self.allocated_bytes * 100 / size) as u8,
+        }
+    }
+}
+
+impl Display for HeapStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}/{} bytes allocated ({}%), at most {}",
+            self.allocated_bytes,
+            self.size(),
+            self.usage_percent(),
+            self.max_allocated_bytes
+        )
+    }
+}
+
+/// Wrapper to the `sys_heap_runtime_stats_get` function.
+///
+/// `heap` MUST point to an initialized heap, e.g. the `heap` of a `k_heap`.
+pub unsafe fn heap_stats(heap: *mut ZSysHeap) -> ZephyrResult<HeapStats> {
+    // zeroed as the statistics are filled by Zephyr
+    let mut stats: ZSysMemoryStats = core::mem::zeroed();
+    let res = zephyr_sys::raw::sys_heap_runtime_stats_get(heap, &mut stats);
+    result(res)?;
+    Ok(HeapStats {
+        free_bytes: stats.free_bytes,
+        allocated_bytes: stats.allocated_bytes,
+        max_allocated_bytes: stats.max_allocated_bytes,
+    })
+}
+
+/// Statistics of the heap of `k_malloc`, which requires `CONFIG_HEAP_MEM_POOL_SIZE` to be larger
+/// than 0.
+pub fn system_heap_stats() -> ZephyrResult<HeapStats> {
+    // the system heap is initialized before the application runs
+    unsafe { heap_stats(core::ptr::addr_of_mut!(_system_heap.heap)) }
+}
+
+/// Wrapper to the `sys_heap_runtime_stats_reset_max` function, resets the maximum of the
+/// allocated bytes to the bytes allocated now.
+///
+/// `heap` MUST point to an initialized heap.
+pub unsafe fn reset_heap_max(heap: *mut ZSysHeap) -> ZephyrResult<()> {
+    result(zephyr_sys::raw::sys_heap_runtime_stats_reset_max(heap))
+}
+
+/// Wrapper to the `sys_heap_print_info` function, prints the free chunks of the heap by size and
+/// with `dump_chunks` every chunk.
+///
+/// `heap` MUST point to an initialized heap.
+pub unsafe fn print_heap_info(heap: *mut ZSysHeap, dump_chunks: bool) {
+    zephyr_sys::raw::sys_heap_print_info(heap, dump_chunks);
+}
+
+#[derive(Debug)]
+struct DiagnosticsWrapperContext {}
+
+impl Context for DiagnosticsWrapperContext {
+    fn name(&self) -> &'static str {
+        "diagnostics wrapper"
+    }
+}
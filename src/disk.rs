@@ -0,0 +1,161 @@
+//! Wrappers for the Zephyr disk access API.
+//!
+//! Disks are block devices like SD cards (`SD`, `SDMMC`) or flash regions published with the
+//! `zephyr,flash-disk` devicetree binding, addressed by their name. They are the storage of FAT
+//! file systems and of the USB mass storage class.
+//!
+//! The USB mass storage class (`CONFIG_USB_MASS_STORAGE`) exports the disk named by
+//! `CONFIG_MASS_STORAGE_DISK_NAME` once the USB stack is enabled with [crate::usb::enable]. The
+//! host then owns the disk, so the application must not write to it and should [Disk::sync] and
+//! unmount file systems on it before enabling the stack.
+
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::ffi::CString;
+use core::ffi::{c_int, c_void};
+
+const CONTEXT: DiskWrapperContext = DiskWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Status of a disk as reported by `disk_access_status`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DiskStatus {
+    Ok,
+    Uninitialized,
+    NoMedia,
+    WriteProtected,
+    Other(i32),
+}
+
+impl From<c_int> for DiskStatus {
+    fn from(other: c_int) -> Self {
+        match other as u32 {
+            zephyr_sys::raw::DISK_STATUS_OK => DiskStatus::Ok,
+            zephyr_sys::raw::DISK_STATUS_UNINIT => DiskStatus::Uninitialized,
+            zephyr_sys::raw::DISK_STATUS_NOMEDIA => DiskStatus::NoMedia,
+            zephyr_sys::raw::DISK_STATUS_WR_PROTECT => DiskStatus::WriteProtected,
+            _ => DiskStatus::Other(other),
+        }
+    }
+}
+
+/// High level wrapper for a disk.
+pub struct Disk {
+    name: CString,
+}
+
+impl Disk {
+    /// Initializes the disk with the given name. This is the equivalent of `disk_access_init`.
+    pub fn open(name: &str) -> ZephyrResult<Self> {
+        let name = CString::new(name)
+            .map_err(|_| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))?;
+        result(unsafe { zephyr_sys::raw::disk_access_init(name.as_ptr()) })?;
+        Ok(Disk { name })
+    }
+
+    /// Name of the disk.
+    pub fn name(&self) -> &str {
+        // the name was created from a `&str`
+        self.name.to_str().unwrap_or("")
+    }
+
+    /// Status of the disk. This is the equivalent of `disk_access_status`.
+    pub fn status(&self) -> DiskStatus {
+        unsafe { zephyr_sys::raw::disk_access_status(self.name.as_ptr()) }.into()
+    }
+
+    fn ioctl_u32(&self, command: u32) -> ZephyrResult<u32> {
+        let mut value = 0u32;
+        result(unsafe {
+            zephyr_sys::raw::disk_access_ioctl(
+                self.name.as_ptr(),
+                command as u8,
+                &mut value as *mut u32 as *mut c_void,
+            )
+        })?;
+        Ok(value)
+    }
+
+    /// Number of sectors of the disk.
+    pub fn sector_count(&self) -> ZephyrResult<u32> {
+        self.ioctl_u32(zephyr_sys::raw::DISK_IOCTL_GET_SECTOR_COUNT)
+    }
+
+    /// Size of a sector in bytes.
+    pub fn sector_size(&self) -> ZephyrResult<u32> {
+        self.ioctl_u32(zephyr_sys::raw::DISK_IOCTL_GET_SECTOR_SIZE)
+    }
+
+    /// Size of the erase block in sectors.
+    pub fn erase_block_size(&self) -> ZephyrResult<u32> {
+        self.ioctl_u32(zephyr_sys::raw::DISK_IOCTL_GET_ERASE_BLOCK_SZ)
+    }
+
+    /// Capacity of the disk in bytes.
+    pub fn capacity(&self) -> ZephyrResult<u64> {
+        Ok(self.sector_count()? as u64 * self.sector_size()? as u64)
+    }
+
+    fn sectors(&self, len: usize) -> ZephyrResult<u32> {
+        let sector_size = self.sector_size()? as usize;
+        if sector_size == 0 || len % sector_size != 0 {
+            return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT));
+        }
+        Ok((len / sector_size) as u32)
+    }
+
+    /// Read whole sectors starting at `start_sector` into `buffer`, its length must be a multiple
+    /// of [Disk::sector_size]. This is the equivalent of `disk_access_read`.
+    pub fn read(&mut self, start_sector: u32, buffer: &mut [u8]) -> ZephyrResult<()> {
+        let count = self.sectors(buffer.len())?;
+        result(unsafe {
+            zephyr_sys::raw::disk_access_read(
+                self.name.as_ptr(),
+                buffer.as_mut_ptr(),
+                start_sector,
+                count,
+            )
+        })
+    }
+
+    /// Write whole sectors starting at `start_sector` from `data`, its length must be a multiple
+    /// of [Disk::sector_size]. This is the equivalent of `disk_access_write`.
+    pub fn write(&mut self, start_sector: u32, data: &[u8]) -> ZephyrResult<()> {
+        let count = self.sectors(data.len())?;
+        result(unsafe {
+            zephyr_sys::raw::disk_access_write(
+                self.name.as_ptr(),
+                data.as_ptr(),
+                start_sector,
+                count,
+            )
+        })
+    }
+
+    /// Write cached data to the medium. This is the equivalent of the `DISK_IOCTL_CTRL_SYNC`
+    /// ioctl.
+    pub fn sync(&mut self) -> ZephyrResult<()> {
+        result(unsafe {
+            zephyr_sys::raw::disk_access_ioctl(
+                self.name.as_ptr(),
+                zephyr_sys::raw::DISK_IOCTL_CTRL_SYNC as u8,
+                core::ptr::null_mut(),
+            )
+        })
+    }
+}
+
+#[derive(Debug)]
+struct DiskWrapperContext {}
+
+impl Context for DiskWrapperContext {
+    fn name(&self) -> &'static str {
+        "disk wrapper"
+    }
+}
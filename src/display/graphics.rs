@@ -0,0 +1,241 @@
+//! Rendering with [embedded_graphics] into a framebuffer in RAM that is flushed to a [Display].
+//!
+//! The framebuffer stores the pixels in the current pixel format of the display, so the color
+//! type of the framebuffer must match it: [Rgb565] for RGB 565, [Rgb888] for RGB 888 and ARGB 8888
+//! and [BinaryColor] for the monochrome formats.
+
+use super::{BufferDescriptor, Capabilities, Display, PixelFormat, CONTEXT};
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::marker::PhantomData;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{OriginDimensions, Size};
+use embedded_graphics::pixelcolor::{
+    BinaryColor, IntoStorage, PixelColor, Rgb565, Rgb888, RgbColor,
+};
+use embedded_graphics::Pixel;
+
+/// A pixel encoded in the pixel format of a display.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RawPixel {
+    /// The first `len` bytes of a color format.
+    Color { bytes: [u8; 4], len: usize },
+    /// A monochrome pixel, `true` if it is bright.
+    Mono(bool),
+}
+
+/// Colors that can be stored in the framebuffer of a display.
+pub trait FramebufferColor: PixelColor {
+    /// Returns `true` if colors of this type can be encoded in `format`.
+    fn supports(format: PixelFormat) -> bool;
+
+    /// Encode the color in `format`, which is supported.
+    fn encode(self, format: PixelFormat) -> RawPixel;
+}
+
+impl FramebufferColor for Rgb565 {
+    fn supports(format: PixelFormat) -> bool {
+        format == PixelFormat::Rgb565
+    }
+
+    /// Zephyr drivers expect RGB 565 pixels in big endian byte order.
+    fn encode(self, _format: PixelFormat) -> RawPixel {
+        let [high, low] = self.into_storage().to_be_bytes();
+        RawPixel::Color {
+            bytes: [high, low, 0, 0],
+            len: 2,
+        }
+    }
+}
+
+impl FramebufferColor for Rgb888 {
+    fn supports(format: PixelFormat) -> bool {
+        matches!(format, PixelFormat::Rgb888 | PixelFormat::Argb8888)
+    }
+
+    fn encode(self, format: PixelFormat) -> RawPixel {
+        match format {
+            // ARGB 8888 pixels are stored as native 32 bit words
+            PixelFormat::Argb8888 => RawPixel::Color {
+                bytes: (0xFF00_0000_u32 | self.into_storage()).to_ne_bytes(),
+                len: 4,
+            },
+            _ => RawPixel::Color {
+                bytes: [self.r(), self.g(), self.b(), 0],
+                len: 3,
+            },
+        }
+    }
+}
+
+impl FramebufferColor for BinaryColor {
+    fn supports(format: PixelFormat) -> bool {
+        format.is_mono()
+    }
+
+    fn encode(self, _format: PixelFormat) -> RawPixel {
+        RawPixel::Mono(self.is_on())
+    }
+}
+
+/// Framebuffer rendering colors of type `C` for a [Display].
+///
+/// Drawing only changes the framebuffer, [Framebuffer::flush] writes the rows changed since the
+/// last flush to the display.
+pub struct Framebuffer<C: FramebufferColor> {
+    display: Display,
+    capabilities: Capabilities,
+    format: PixelFormat,
+    buffer: Vec<u8>,
+    // first and last changed row
+    dirty: Option<(usize, usize)>,
+    _color: PhantomData<C>,
+}
+
+impl<C: FramebufferColor> Framebuffer<C> {
+    /// Creates a framebuffer for the whole `display`.
+    ///
+    /// Fails with [ErrorNumber::NotSupported] if `C` cannot be stored in the current pixel format
+    /// of the display.
+    pub fn new(display: Display) -> ZephyrResult<Self> {
+        let capabilities = display.capabilities()?;
+        let format = capabilities
+            .pixel_format
+            .filter(|format| C::supports(*format))
+            .ok_or_else(|| ZephyrError::new_with_context(ErrorNumber::NotSupported, &CONTEXT))?;
+
+        let mut framebuffer = Framebuffer {
+            display,
+            capabilities,
+            format,
+            buffer: Vec::new(),
+            dirty: None,
+            _color: PhantomData,
+        };
+        framebuffer.buffer = vec![0; framebuffer.row_offset(framebuffer.height())];
+        Ok(framebuffer)
+    }
+
+    fn width(&self) -> usize {
+        self.capabilities.width as usize
+    }
+
+    fn height(&self) -> usize {
+        self.capabilities.height as usize
+    }
+
+    /// Bytes per row of monochrome displays with horizontal bytes.
+    fn mono_stride(&self) -> usize {
+        (self.width() + 7) / 8
+    }
+
+    /// Offset of the first byte of row `y`. Rows of vertically tiled displays are rounded up to
+    /// the next tile, so this is the end of the previous rows.
+    fn row_offset(&self, y: usize) -> usize {
+        if !self.format.is_mono() {
+            y * self.width() * self.format.bytes_per_pixel()
+        } else if self.capabilities.is_mono_vtiled() {
+            (y + 7) / 8 * self.width()
+        } else {
+            y * self.mono_stride()
+        }
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, color: C) {
+        match color.encode(self.format) {
+            RawPixel::Color { bytes, len } => {
+                let offset = (y * self.width() + x) * len;
+                self.buffer[offset..offset + len].copy_from_slice(&bytes[..len]);
+            }
+            RawPixel::Mono(bright) => {
+                let (index, bit) = if self.capabilities.is_mono_vtiled() {
+                    (y / 8 * self.width() + x, y % 8)
+                } else {
+                    (y * self.mono_stride() + x / 8, x % 8)
+                };
+                let bit = if self.capabilities.is_mono_msb_first() {
+                    7 - bit
+                } else {
+                    bit
+                };
+                let set = bright == (self.format == PixelFormat::Mono01);
+                if set {
+                    self.buffer[index] |= 1 << bit;
+                } else {
+                    self.buffer[index] &= !(1 << bit);
+                }
+            }
+        }
+
+        self.dirty = Some(match self.dirty {
+            Some((first, last)) => (first.min(y), last.max(y)),
+            None => (y, y),
+        });
+    }
+
+    /// Write all rows changed since the last flush to the display.
+    pub fn flush(&mut self) -> ZephyrResult<()> {
+        let (mut first, mut last) = match self.dirty {
+            Some(dirty) => dirty,
+            None => return Ok(()),
+        };
+        if self.format.is_mono() && self.capabilities.is_mono_vtiled() {
+            // whole tiles of 8 rows are written, the last tile may be cut by the display height
+            first = first / 8 * 8;
+            last = (last / 8 * 8 + 7).min(self.capabilities.height as usize - 1);
+        }
+
+        let end = self.row_offset(last + 1).min(self.buffer.len());
+        let start = if self.format.is_mono() && self.capabilities.is_mono_vtiled() {
+            first / 8 * self.width()
+        } else {
+            self.row_offset(first)
+        };
+        let descriptor = BufferDescriptor {
+            width: self.capabilities.width,
+            height: (last - first + 1) as u16,
+            pitch: self.capabilities.width,
+        };
+
+        self.display
+            .write(0, first as u16, &descriptor, &self.buffer[start..end])?;
+        self.dirty = None;
+        Ok(())
+    }
+
+    /// The display the framebuffer is flushed to.
+    pub fn display(&mut self) -> &mut Display {
+        &mut self.display
+    }
+
+    /// Returns the display, discarding the framebuffer.
+    pub fn into_display(self) -> Display {
+        self.display
+    }
+}
+
+impl<C: FramebufferColor> OriginDimensions for Framebuffer<C> {
+    fn size(&self) -> Size {
+        Size::new(self.capabilities.width as u32, self.capabilities.height as u32)
+    }
+}
+
+impl<C: FramebufferColor> DrawTarget for Framebuffer<C> {
+    type Color = C;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let (width, height) = (self.width() as i32, self.height() as i32);
+        for Pixel(point, color) in pixels {
+            if point.x >= 0 && point.y >= 0 && point.x < width && point.y < height {
+                self.set_pixel(point.x as usize, point.y as usize, color);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,362 @@
+//! Wrappers for the Zephyr display driver API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! The display API is inlined in the Zephyr headers, so this module calls the driver API directly.
+//! With the `embedded-graphics` feature a [graphics::Framebuffer] renders
+//! [embedded_graphics](https://docs.rs/embedded-graphics) drawings and flushes them to a [Display].
+
+use crate::device::{class, TypedDevice};
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use core::ffi::{c_int, c_void};
+pub use zephyr::device::Device;
+use zephyr_sys::raw::{
+    display_buffer_descriptor as ZBufferDescriptor, display_capabilities as ZCapabilities,
+    display_driver_api as DisplayDriverApi,
+};
+
+#[cfg(feature = "embedded-graphics")]
+pub mod graphics;
+
+const CONTEXT: DisplayWrapperContext = DisplayWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+fn not_implemented() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::NotImplemented, &CONTEXT)
+}
+
+/// Pixel formats of the display API.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PixelFormat {
+    Rgb888,
+    /// Monochrome, a set bit is a bright pixel.
+    Mono01,
+    /// Monochrome, a set bit is a dark pixel.
+    Mono10,
+    Argb8888,
+    Rgb565,
+    Bgr565,
+}
+
+impl PixelFormat {
+    const ALL: [PixelFormat; 6] = [
+        PixelFormat::Rgb888,
+        PixelFormat::Mono01,
+        PixelFormat::Mono10,
+        PixelFormat::Argb8888,
+        PixelFormat::Rgb565,
+        PixelFormat::Bgr565,
+    ];
+
+    /// The `PIXEL_FORMAT_*` bit of the format.
+    pub fn bit(&self) -> u32 {
+        match self {
+            PixelFormat::Rgb888 => zephyr_sys::raw::display_pixel_format_PIXEL_FORMAT_RGB_888,
+            PixelFormat::Mono01 => zephyr_sys::raw::display_pixel_format_PIXEL_FORMAT_MONO01,
+            PixelFormat::Mono10 => zephyr_sys::raw::display_pixel_format_PIXEL_FORMAT_MONO10,
+            PixelFormat::Argb8888 => zephyr_sys::raw::display_pixel_format_PIXEL_FORMAT_ARGB_8888,
+            PixelFormat::Rgb565 => zephyr_sys::raw::display_pixel_format_PIXEL_FORMAT_RGB_565,
+            PixelFormat::Bgr565 => zephyr_sys::raw::display_pixel_format_PIXEL_FORMAT_BGR_565,
+        }
+    }
+
+    fn from_bit(bit: u32) -> Option<Self> {
+        PixelFormat::ALL.iter().copied().find(|format| format.bit() == bit)
+    }
+
+    /// Returns `true` for the monochrome formats, which pack 8 pixels in a byte.
+    pub fn is_mono(&self) -> bool {
+        matches!(self, PixelFormat::Mono01 | PixelFormat::Mono10)
+    }
+
+    /// Bytes per pixel of the color formats, `0` for the monochrome formats.
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            PixelFormat::Mono01 | PixelFormat::Mono10 => 0,
+            PixelFormat::Rgb565 | PixelFormat::Bgr565 => 2,
+            PixelFormat::Rgb888 => 3,
+            PixelFormat::Argb8888 => 4,
+        }
+    }
+}
+
+/// Orientation of the display.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Orientation {
+    Normal,
+    Rotated90,
+    Rotated180,
+    Rotated270,
+}
+
+impl Orientation {
+    fn from_raw(raw: zephyr_sys::raw::display_orientation) -> Self {
+        match raw {
+            zephyr_sys::raw::display_orientation_DISPLAY_ORIENTATION_ROTATED_90 => {
+                Orientation::Rotated90
+            }
+            zephyr_sys::raw::display_orientation_DISPLAY_ORIENTATION_ROTATED_180 => {
+                Orientation::Rotated180
+            }
+            zephyr_sys::raw::display_orientation_DISPLAY_ORIENTATION_ROTATED_270 => {
+                Orientation::Rotated270
+            }
+            _ => Orientation::Normal,
+        }
+    }
+}
+
+/// Capabilities of a display as reported by `display_get_capabilities`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Capabilities {
+    pub width: u16,
+    pub height: u16,
+    /// Bit mask of the supported [PixelFormat::bit]s.
+    pub supported_pixel_formats: u32,
+    /// The `SCREEN_INFO_*` flags of the display.
+    pub screen_info: u32,
+    pub pixel_format: Option<PixelFormat>,
+    pub orientation: Orientation,
+}
+
+impl Capabilities {
+    /// Returns `true` if the display supports `format`.
+    pub fn supports(&self, format: PixelFormat) -> bool {
+        self.supported_pixel_formats & format.bit() != 0
+    }
+
+    /// Returns `true` if monochrome pixels are packed in vertical bytes of 8 rows.
+    pub fn is_mono_vtiled(&self) -> bool {
+        self.screen_info & zephyr_sys::raw::SCREEN_INFO_MONO_VTILED != 0
+    }
+
+    /// Returns `true` if the first pixel of a monochrome byte is its most significant bit.
+    pub fn is_mono_msb_first(&self) -> bool {
+        self.screen_info & zephyr_sys::raw::SCREEN_INFO_MONO_MSB_FIRST != 0
+    }
+}
+
+impl From<&ZCapabilities> for Capabilities {
+    fn from(other: &ZCapabilities) -> Self {
+        Capabilities {
+            width: other.x_resolution,
+            height: other.y_resolution,
+            supported_pixel_formats: other.supported_pixel_formats,
+            screen_info: other.screen_info,
+            pixel_format: PixelFormat::from_bit(other.current_pixel_format),
+            orientation: Orientation::from_raw(other.current_orientation),
+        }
+    }
+}
+
+/// Layout of a buffer written to or read from a display.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BufferDescriptor {
+    /// Width of the area in pixels.
+    pub width: u16,
+    /// Height of the area in pixels.
+    pub height: u16,
+    /// Pixels per row in the buffer, at least `width`.
+    pub pitch: u16,
+}
+
+impl BufferDescriptor {
+    /// Bytes of a buffer of the area in the current pixel format of a display with
+    /// `capabilities`, `None` if the format is unknown.
+    pub fn buffer_len(&self, capabilities: &Capabilities) -> Option<usize> {
+        let format = capabilities.pixel_format?;
+        let (pitch, height) = (self.pitch as usize, self.height as usize);
+        Some(if !format.is_mono() {
+            pitch * height * format.bytes_per_pixel()
+        } else if capabilities.is_mono_vtiled() {
+            pitch * ((height + 7) / 8)
+        } else {
+            (pitch + 7) / 8 * height
+        })
+    }
+
+    fn raw(&self, buffer_len: usize) -> ZBufferDescriptor {
+        // the descriptor contains optional fields depending on the Zephyr version
+        let mut descriptor: ZBufferDescriptor = unsafe { core::mem::zeroed() };
+        descriptor.buf_size = buffer_len as u32;
+        descriptor.width = self.width;
+        descriptor.height = self.height;
+        descriptor.pitch = self.pitch;
+        descriptor
+    }
+}
+
+/// Checks that `buffer_len` bytes hold the area of `descriptor` on `display`, the driver accesses
+/// the whole area.
+fn check_buffer(
+    display: &Display,
+    descriptor: &BufferDescriptor,
+    buffer_len: usize,
+) -> ZephyrResult<()> {
+    let capabilities = display.capabilities()?;
+    match descriptor.buffer_len(&capabilities) {
+        Some(len) if descriptor.pitch >= descriptor.width && len <= buffer_len => Ok(()),
+        _ => Err(ZephyrError::new_with_context(
+            ErrorNumber::InvalidArgument,
+            &CONTEXT,
+        )),
+    }
+}
+
+/// High level wrapper for a display.
+pub struct Display {
+    device: &'static Device,
+}
+
+impl Display {
+    /// Creates a new [Display] on the current interface.
+    ///
+    /// `device` MUST be a display device. If `device` is not a display device the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Display { device }
+    }
+
+    /// Creates a new [Display] from a device checked to be a display device.
+    pub fn from_device(device: TypedDevice<class::Display>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Display::new(device.device()) }
+    }
+
+    fn api(&self) -> Option<&DisplayDriverApi> {
+        // device is required to be a display device in constructor
+        unsafe { (self.device.api as *const DisplayDriverApi).as_ref() }
+    }
+
+    /// Capabilities of the display. This is the equivalent of `display_get_capabilities`.
+    pub fn capabilities(&self) -> ZephyrResult<Capabilities> {
+        let get_capabilities = self
+            .api()
+            .and_then(|api| api.get_capabilities)
+            .ok_or_else(not_implemented)?;
+        let mut capabilities: ZCapabilities = unsafe { core::mem::zeroed() };
+        unsafe { (get_capabilities)(self.device as *const Device, &mut capabilities as *mut _) };
+        Ok((&capabilities).into())
+    }
+
+    /// Write `buffer` to the area at `x` and `y` described by `descriptor`. This is the equivalent
+    /// of `display_write`.
+    ///
+    /// Fails with [ErrorNumber::InvalidArgument] if `buffer` is shorter than the area in the
+    /// current pixel format (see [BufferDescriptor::buffer_len]), or the format is unknown.
+    pub fn write(
+        &mut self,
+        x: u16,
+        y: u16,
+        descriptor: &BufferDescriptor,
+        buffer: &[u8],
+    ) -> ZephyrResult<()> {
+        let write = self
+            .api()
+            .and_then(|api| api.write)
+            .ok_or_else(not_implemented)?;
+        check_buffer(self, descriptor, buffer.len())?;
+        let descriptor = descriptor.raw(buffer.len());
+        result(unsafe {
+            (write)(
+                self.device as *const Device,
+                x,
+                y,
+                &descriptor as *const ZBufferDescriptor,
+                buffer.as_ptr() as *const c_void,
+            )
+        })
+    }
+
+    /// Read the area at `x` and `y` described by `descriptor` into `buffer`. This is the
+    /// equivalent of `display_read`.
+    ///
+    /// Fails with [ErrorNumber::InvalidArgument] like [Display::write].
+    pub fn read(
+        &mut self,
+        x: u16,
+        y: u16,
+        descriptor: &BufferDescriptor,
+        buffer: &mut [u8],
+    ) -> ZephyrResult<()> {
+        let read = self
+            .api()
+            .and_then(|api| api.read)
+            .ok_or_else(not_implemented)?;
+        check_buffer(self, descriptor, buffer.len())?;
+        let descriptor = descriptor.raw(buffer.len());
+        result(unsafe {
+            (read)(
+                self.device as *const Device,
+                x,
+                y,
+                &descriptor as *const ZBufferDescriptor,
+                buffer.as_mut_ptr() as *mut c_void,
+            )
+        })
+    }
+
+    /// Turn the output off while the content is updated. This is the equivalent of
+    /// `display_blanking_on`.
+    pub fn blanking_on(&mut self) -> ZephyrResult<()> {
+        let blanking_on = self
+            .api()
+            .and_then(|api| api.blanking_on)
+            .ok_or_else(not_implemented)?;
+        result(unsafe { (blanking_on)(self.device as *const Device) })
+    }
+
+    /// Turn the output on. This is the equivalent of `display_blanking_off`.
+    pub fn blanking_off(&mut self) -> ZephyrResult<()> {
+        let blanking_off = self
+            .api()
+            .and_then(|api| api.blanking_off)
+            .ok_or_else(not_implemented)?;
+        result(unsafe { (blanking_off)(self.device as *const Device) })
+    }
+
+    /// Set the brightness of the display. This is the equivalent of `display_set_brightness`.
+    pub fn set_brightness(&mut self, brightness: u8) -> ZephyrResult<()> {
+        let set_brightness = self
+            .api()
+            .and_then(|api| api.set_brightness)
+            .ok_or_else(not_implemented)?;
+        result(unsafe { (set_brightness)(self.device as *const Device, brightness) })
+    }
+
+    /// Set the contrast of the display. This is the equivalent of `display_set_contrast`.
+    pub fn set_contrast(&mut self, contrast: u8) -> ZephyrResult<()> {
+        let set_contrast = self
+            .api()
+            .and_then(|api| api.set_contrast)
+            .ok_or_else(not_implemented)?;
+        result(unsafe { (set_contrast)(self.device as *const Device, contrast) })
+    }
+
+    /// Switch the pixel format of the display. This is the equivalent of
+    /// `display_set_pixel_format`.
+    pub fn set_pixel_format(&mut self, format: PixelFormat) -> ZephyrResult<()> {
+        let set_pixel_format = self
+            .api()
+            .and_then(|api| api.set_pixel_format)
+            .ok_or_else(not_implemented)?;
+        result(unsafe { (set_pixel_format)(self.device as *const Device, format.bit()) })
+    }
+}
+
+#[derive(Debug)]
+struct DisplayWrapperContext {}
+
+impl Context for DisplayWrapperContext {
+    fn name(&self) -> &'static str {
+        "display wrapper"
+    }
+}
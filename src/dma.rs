@@ -0,0 +1,471 @@
+//! Syscalls and high level wrappers for the Zephyr DMA API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! A transfer is described by a [DmaTransfer], which chains one or more [Block] descriptors, and
+//! is run on a [DmaChannel]. The addresses of the blocks are raw addresses, so creating a block is
+//! unsafe: the memory must stay valid until the transfer is done or stopped.
+
+use crate::device::{class, TypedDevice};
+use crate::handlers::Handlers;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ffi::{c_int, c_void};
+pub use zephyr::device::Device;
+use zephyr_sys::raw::{
+    dma_addr_t, dma_block_config as ZBlockConfig, dma_config as ZDmaConfig,
+    dma_driver_api as DmaDriverApi,
+};
+
+const CONTEXT: DmaWrapperContext = DmaWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Direction of a DMA transfer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Direction {
+    MemoryToMemory,
+    MemoryToPeripheral,
+    PeripheralToMemory,
+    PeripheralToPeripheral,
+}
+
+impl From<Direction> for u32 {
+    fn from(other: Direction) -> Self {
+        match other {
+            Direction::MemoryToMemory => zephyr_sys::raw::dma_channel_direction_MEMORY_TO_MEMORY,
+            Direction::MemoryToPeripheral => {
+                zephyr_sys::raw::dma_channel_direction_MEMORY_TO_PERIPHERAL
+            }
+            Direction::PeripheralToMemory => {
+                zephyr_sys::raw::dma_channel_direction_PERIPHERAL_TO_MEMORY
+            }
+            Direction::PeripheralToPeripheral => {
+                zephyr_sys::raw::dma_channel_direction_PERIPHERAL_TO_PERIPHERAL
+            }
+        }
+    }
+}
+
+/// How an address changes after each transferred data unit.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AddressAdjustment {
+    Increment,
+    Decrement,
+    /// The address stays the same, e.g. for the data register of a peripheral.
+    NoChange,
+}
+
+impl From<AddressAdjustment> for u32 {
+    fn from(other: AddressAdjustment) -> Self {
+        match other {
+            AddressAdjustment::Increment => zephyr_sys::raw::DMA_ADDR_ADJ_INCREMENT,
+            AddressAdjustment::Decrement => zephyr_sys::raw::DMA_ADDR_ADJ_DECREMENT,
+            AddressAdjustment::NoChange => zephyr_sys::raw::DMA_ADDR_ADJ_NO_CHANGE,
+        }
+    }
+}
+
+/// Descriptor of a single block of a transfer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Block {
+    source: usize,
+    dest: usize,
+    size: u32,
+    source_adjustment: AddressAdjustment,
+    dest_adjustment: AddressAdjustment,
+}
+
+impl Block {
+    /// Creates a block transferring `size` bytes from `source` to `dest`, incrementing both
+    /// addresses.
+    ///
+    /// `source` and `dest` MUST be valid for `size` bytes until the transfer is done or stopped.
+    pub unsafe fn new(source: *const u8, dest: *mut u8, size: u32) -> Self {
+        Block {
+            source: source as usize,
+            dest: dest as usize,
+            size,
+            source_adjustment: AddressAdjustment::Increment,
+            dest_adjustment: AddressAdjustment::Increment,
+        }
+    }
+
+    /// Set the adjustment of the source address.
+    pub fn source_adjustment(mut self, adjustment: AddressAdjustment) -> Self {
+        self.source_adjustment = adjustment;
+        self
+    }
+
+    /// Set the adjustment of the destination address.
+    pub fn dest_adjustment(mut self, adjustment: AddressAdjustment) -> Self {
+        self.dest_adjustment = adjustment;
+        self
+    }
+
+    fn raw(&self) -> ZBlockConfig {
+        // the descriptor contains bitfields and optional fields, so start from all zeros
+        let mut block: ZBlockConfig = unsafe { core::mem::zeroed() };
+        block.source_address = self.source as dma_addr_t;
+        block.dest_address = self.dest as dma_addr_t;
+        block.block_size = self.size;
+        block.set_source_addr_adj(self.source_adjustment.into());
+        block.set_dest_addr_adj(self.dest_adjustment.into());
+        block
+    }
+}
+
+/// Builder for the configuration of a DMA transfer.
+#[derive(Clone, Debug)]
+pub struct DmaTransfer {
+    direction: Direction,
+    slot: u32,
+    priority: u32,
+    source_data_size: u32,
+    dest_data_size: u32,
+    source_burst_length: u32,
+    dest_burst_length: u32,
+    cyclic: bool,
+    blocks: Vec<Block>,
+}
+
+impl DmaTransfer {
+    /// Creates a transfer in `direction` with byte sized data units and no blocks.
+    pub fn new(direction: Direction) -> Self {
+        DmaTransfer {
+            direction,
+            slot: 0,
+            priority: 0,
+            source_data_size: 1,
+            dest_data_size: 1,
+            source_burst_length: 1,
+            dest_burst_length: 1,
+            cyclic: false,
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Set the slot of the peripheral request line.
+    pub fn slot(mut self, slot: u8) -> Self {
+        self.slot = slot as u32;
+        self
+    }
+
+    /// Set the priority of the channel.
+    pub fn priority(mut self, priority: u8) -> Self {
+        self.priority = priority as u32;
+        self
+    }
+
+    /// Set the width of the data units in bytes.
+    pub fn data_size(mut self, source: u16, dest: u16) -> Self {
+        self.source_data_size = source as u32;
+        self.dest_data_size = dest as u32;
+        self
+    }
+
+    /// Set the number of data units per burst.
+    pub fn burst_length(mut self, source: u16, dest: u16) -> Self {
+        self.source_burst_length = source as u32;
+        self.dest_burst_length = dest as u32;
+        self
+    }
+
+    /// Restart with the first block after the last one, e.g. for ring buffers.
+    pub fn cyclic(mut self, cyclic: bool) -> Self {
+        self.cyclic = cyclic;
+        self
+    }
+
+    /// Append `block` to the chain of blocks.
+    pub fn block(mut self, block: Block) -> Self {
+        self.blocks.push(block);
+        self
+    }
+}
+
+/// Event passed to the callback of a [DmaChannel].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DmaEvent {
+    /// The whole transfer is done.
+    Complete,
+    /// A block of the transfer is done.
+    Block,
+}
+
+/// Configuration that must stay valid while the channel runs.
+struct Configured {
+    config: ZDmaConfig,
+    _blocks: Vec<ZBlockConfig>,
+}
+
+/// Channel of a controller a closure is installed for with [DmaChannel::configure_with].
+#[derive(Copy, Clone, Eq, PartialEq)]
+struct CallbackKey {
+    device: usize,
+    channel: u32,
+}
+
+type DmaCallback = dyn FnMut(Result<DmaEvent, ZephyrError>) + Send;
+
+static CALLBACKS: Handlers<CallbackKey, DmaCallback> = Handlers::new();
+
+/// Equivalent of the `dma_config` API function.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+///
+/// This method fails with [ErrorNumber::NotImplemented] if the driver does not expose this call.
+pub unsafe fn config(device: &Device, channel: u32, config: &mut ZDmaConfig) -> ZephyrResult<()> {
+    // convert void pointer from C API to a DMA driver API Rust struct
+    let api = (device.api as *const DmaDriverApi).as_ref();
+
+    match api.and_then(|api| api.config) {
+        Some(config_fn) => result((config_fn)(
+            device as *const Device,
+            channel,
+            config as *mut ZDmaConfig,
+        )),
+        None => Err(ZephyrError::new_with_context(
+            ErrorNumber::NotImplemented,
+            &CONTEXT,
+        )),
+    }
+}
+
+/// Wrapper to the `dma_start` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn start(device: &Device, channel: u32) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::dma_start(device as *const Device, channel))
+}
+
+/// Wrapper to the `dma_stop` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn stop(device: &Device, channel: u32) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::dma_stop(device as *const Device, channel))
+}
+
+/// High level wrapper for a channel of a DMA controller.
+///
+/// The channel is stopped when dropped, and released if it was requested with
+/// [DmaChannel::request].
+pub struct DmaChannel {
+    device: &'static Device,
+    channel: u32,
+    requested: bool,
+    // boxed as the driver keeps pointers to the configuration and the callback
+    configured: Option<Box<Configured>>,
+}
+
+impl DmaChannel {
+    /// Creates a new [DmaChannel] for `channel` of the controller.
+    ///
+    /// `device` MUST be a DMA device. If `device` is not a DMA device the behaviour when calling
+    /// any method is undefined!
+    pub unsafe fn new(device: &'static Device, channel: u32) -> Self {
+        DmaChannel {
+            device,
+            channel,
+            requested: false,
+            configured: None,
+        }
+    }
+
+    /// Creates a new [DmaChannel] from a device checked to be a DMA device.
+    pub fn from_device(device: TypedDevice<class::Dma>, channel: u32) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { DmaChannel::new(device.device(), channel) }
+    }
+
+    /// Request a free channel of the controller. This is a wrapper to `dma_request_channel`.
+    ///
+    /// `device` MUST be a DMA device. If `device` is not a DMA device the behaviour when calling
+    /// any method is undefined!
+    pub unsafe fn request(device: &'static Device) -> ZephyrResult<Self> {
+        let channel = zephyr_sys::syscalls::any::dma_request_channel(
+            device as *const Device,
+            core::ptr::null_mut(),
+        );
+
+        if channel >= 0 {
+            let mut dma_channel = Self::new(device, channel as u32);
+            dma_channel.requested = true;
+            Ok(dma_channel)
+        } else {
+            Err(ZephyrError::from_errno_with_context(channel, &CONTEXT))
+        }
+    }
+
+    /// Number of the channel on the controller.
+    pub fn channel(&self) -> u32 {
+        self.channel
+    }
+
+    /// Configure the channel for `transfer`.
+    ///
+    /// Fails with [ErrorNumber::InvalidArgument] if the transfer has no blocks.
+    pub fn configure(&mut self, transfer: &DmaTransfer) -> ZephyrResult<()> {
+        self.configure_raw(transfer, None)
+    }
+
+    /// Configure the channel for `transfer`, calling `f` from the interrupt of the controller when
+    /// the transfer is done or failed.
+    pub fn configure_with<F>(&mut self, transfer: &DmaTransfer, f: F) -> ZephyrResult<()>
+    where
+        F: FnMut(Result<DmaEvent, ZephyrError>) + Send + 'static,
+    {
+        self.configure_raw(transfer, Some(Box::new(f)))
+    }
+
+    fn configure_raw(
+        &mut self,
+        transfer: &DmaTransfer,
+        callback: Option<Box<DmaCallback>>,
+    ) -> ZephyrResult<()> {
+        if transfer.blocks.is_empty() {
+            return Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &CONTEXT,
+            ));
+        }
+
+        // the configuration is reused by the driver, so it must not change while running
+        self.stop()?;
+
+        let mut blocks: Vec<ZBlockConfig> = transfer.blocks.iter().map(Block::raw).collect();
+        let block_pointers: Vec<*mut ZBlockConfig> =
+            blocks.iter_mut().map(|block| block as *mut _).collect();
+        for (block, next) in blocks.iter_mut().zip(block_pointers.iter().skip(1)) {
+            block.next_block = *next;
+        }
+
+        // the configuration contains bitfields and optional fields, so start from all zeros
+        let mut raw: ZDmaConfig = unsafe { core::mem::zeroed() };
+        raw.set_dma_slot(transfer.slot);
+        raw.set_channel_direction(transfer.direction.into());
+        raw.set_channel_priority(transfer.priority);
+        raw.set_cyclic(transfer.cyclic as u32);
+        raw.set_source_data_size(transfer.source_data_size);
+        raw.set_dest_data_size(transfer.dest_data_size);
+        raw.set_source_burst_length(transfer.source_burst_length);
+        raw.set_dest_burst_length(transfer.dest_burst_length);
+        raw.block_count = blocks.len() as u32;
+        raw.head_block = blocks.as_mut_ptr();
+
+        let mut configured = Box::new(Configured {
+            config: raw,
+            _blocks: blocks,
+        });
+        let key = self.key();
+        let inserted = callback.map(|callback| CALLBACKS.insert(key, callback));
+        if let Some(inserted) = inserted.as_ref() {
+            configured.config.dma_callback = Some(dma_trampoline);
+            configured.config.user_data = inserted.id as *mut c_void;
+        }
+
+        // device is required to be a DMA device in constructor
+        let result = unsafe { config(self.device, self.channel, &mut configured.config) };
+        match inserted {
+            Some(inserted) if result.is_err() => {
+                if inserted.added {
+                    CALLBACKS.remove_id(inserted.id);
+                } else {
+                    CALLBACKS.restore(inserted.id, inserted.replaced);
+                }
+            }
+            // the channel is stopped, so the closure of the previous configuration is not called
+            None if result.is_ok() => {
+                CALLBACKS.remove(&key);
+            }
+            _ => {}
+        }
+        result?;
+        self.configured = Some(configured);
+        Ok(())
+    }
+
+    fn key(&self) -> CallbackKey {
+        CallbackKey {
+            device: self.device as *const Device as usize,
+            channel: self.channel,
+        }
+    }
+
+    /// Start the configured transfer.
+    pub fn start(&mut self) -> ZephyrResult<()> {
+        if self.configured.is_none() {
+            return Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &CONTEXT,
+            ));
+        }
+
+        // device is required to be a DMA device in constructor
+        unsafe { start(self.device, self.channel) }
+    }
+
+    /// Stop a running transfer.
+    pub fn stop(&mut self) -> ZephyrResult<()> {
+        if self.configured.is_none() {
+            return Ok(());
+        }
+
+        // device is required to be a DMA device in constructor
+        unsafe { stop(self.device, self.channel) }
+    }
+}
+
+impl Drop for DmaChannel {
+    fn drop(&mut self) {
+        let _ = self.stop();
+        // a closure still called from the interrupt is dropped by the callback after the call
+        CALLBACKS.remove(&self.key());
+        if self.requested {
+            // device is required to be a DMA device in constructor
+            unsafe {
+                zephyr_sys::syscalls::any::dma_release_channel(
+                    self.device as *const Device,
+                    self.channel,
+                )
+            };
+        }
+    }
+}
+
+/// C callback that dispatches the status of a transfer to the closure whose id is passed as user
+/// data.
+extern "C" fn dma_trampoline(
+    _dev: *const Device,
+    user_data: *mut c_void,
+    _channel: u32,
+    status: c_int,
+) {
+    let event = match status {
+        status if status == zephyr_sys::raw::DMA_STATUS_COMPLETE as c_int => {
+            Ok(DmaEvent::Complete)
+        }
+        status if status == zephyr_sys::raw::DMA_STATUS_BLOCK as c_int => Ok(DmaEvent::Block),
+        errno => Err(ZephyrError::from_errno_with_context(errno, &CONTEXT)),
+    };
+    CALLBACKS.call_id(user_data as usize, |callback| callback(event));
+}
+
+#[derive(Debug)]
+struct DmaWrapperContext {}
+
+impl Context for DmaWrapperContext {
+    fn name(&self) -> &'static str {
+        "dma wrapper"
+    }
+}
@@ -0,0 +1,289 @@
+//! Wrappers for the Zephyr digital microphone (DMIC) API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! The DMIC API is inlined in the Zephyr headers, so this module calls the driver API directly.
+//! The driver converts the PDM signal of the microphones to PCM and fills blocks allocated from
+//! a [MemorySlab], which are returned by [Dmic::read] as [SlabBlock] and freed when dropped.
+//! Samples of the channels are interleaved in a block.
+
+use crate::memory_slab::{MemorySlab, SlabBlock};
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::vec::Vec;
+use core::ffi::{c_int, c_void};
+use core::time::Duration;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::{
+    _dmic_ops as DmicDriverApi, dmic_cfg as ZDmicConfig, pcm_stream_cfg as ZPcmStreamConfig,
+};
+
+const CONTEXT: DmicWrapperContext = DmicWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+fn not_implemented() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::NotImplemented, &CONTEXT)
+}
+
+/// Side of a PDM controller a microphone is connected to. Two microphones share a data line,
+/// one samples on the rising and one on the falling clock edge.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// Microphone that provides a PCM channel.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PdmChannel {
+    /// Index of the PDM controller.
+    pub pdm: u8,
+    pub side: Side,
+}
+
+impl PdmChannel {
+    /// Nibble of the channel map for PCM channel `channel`, this is the equivalent of
+    /// `dmic_build_channel_map`.
+    fn map(&self, channel: usize) -> u64 {
+        let side = match self.side {
+            Side::Left => zephyr_sys::raw::pdm_lr_PDM_CHAN_LEFT,
+            Side::Right => zephyr_sys::raw::pdm_lr_PDM_CHAN_RIGHT,
+        } as u64;
+        (((self.pdm as u64) << 1) | side) << (channel * 4)
+    }
+}
+
+/// Configuration of a DMIC capture.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DmicConfig {
+    /// Range of the PDM clock in Hz, the driver selects a frequency that fits the PCM rate.
+    pub min_pdm_clock_hz: u32,
+    pub max_pdm_clock_hz: u32,
+    /// Range of the duty cycle of the PDM clock in percent.
+    pub min_pdm_clock_duty_cycle: u8,
+    pub max_pdm_clock_duty_cycle: u8,
+    /// Sample rate in Hz, e.g. 16000.
+    pub pcm_rate: u32,
+    /// Bits per sample, e.g. 16.
+    pub pcm_width: u8,
+    /// Size of a block in bytes, at most the block size of the slab.
+    pub block_size: u16,
+    /// Microphones of the PCM channels, in the order of the samples in a block.
+    pub channels: Vec<PdmChannel>,
+}
+
+impl DmicConfig {
+    /// Capture of 16 bit samples at `pcm_rate` from `channels`, with the clock range of common
+    /// PDM microphones.
+    pub fn new(pcm_rate: u32, block_size: u16, channels: Vec<PdmChannel>) -> Self {
+        DmicConfig {
+            min_pdm_clock_hz: 1_000_000,
+            max_pdm_clock_hz: 3_500_000,
+            min_pdm_clock_duty_cycle: 40,
+            max_pdm_clock_duty_cycle: 60,
+            pcm_rate,
+            pcm_width: 16,
+            block_size,
+            channels,
+        }
+    }
+
+    fn channel_map(&self) -> u64 {
+        self.channels
+            .iter()
+            .enumerate()
+            .fold(0, |map, (index, channel)| map | channel.map(index))
+    }
+}
+
+/// Channels the driver activated for a capture, as returned by [Dmic::configure].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ActiveChannels {
+    pub count: u8,
+    /// Channel map in the format of the `req_chan_map` of the configuration.
+    pub map: u64,
+}
+
+/// High level wrapper for a digital microphone interface.
+pub struct Dmic {
+    device: &'static Device,
+    slab: Option<&'static MemorySlab>,
+}
+
+impl Dmic {
+    /// Creates a new [Dmic].
+    ///
+    /// `device` MUST be a DMIC device. If `device` is not a DMIC device the behaviour when calling
+    /// any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Dmic { device, slab: None }
+    }
+
+    fn api(&self) -> Option<&DmicDriverApi> {
+        // device is required to be a DMIC device in constructor
+        unsafe { (self.device.api as *const DmicDriverApi).as_ref() }
+    }
+
+    /// Configure a capture, its blocks are allocated from `slab`. This is the equivalent of
+    /// `dmic_configure`.
+    pub fn configure(
+        &mut self,
+        config: &DmicConfig,
+        slab: &'static MemorySlab,
+    ) -> ZephyrResult<ActiveChannels> {
+        if config.block_size as usize > slab.block_size() || config.channels.is_empty() {
+            return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT));
+        }
+        let configure = self
+            .api()
+            .and_then(|api| api.configure)
+            .ok_or_else(not_implemented)?;
+
+        let mut stream: ZPcmStreamConfig = unsafe { core::mem::zeroed() };
+        stream.pcm_rate = config.pcm_rate;
+        stream.pcm_width = config.pcm_width;
+        stream.block_size = config.block_size;
+        stream.mem_slab = slab.slab_ptr();
+
+        let map = config.channel_map();
+        // zeroed as the driver reports the active channels in the same struct
+        let mut raw: ZDmicConfig = unsafe { core::mem::zeroed() };
+        raw.io.min_pdm_clk_freq = config.min_pdm_clock_hz;
+        raw.io.max_pdm_clk_freq = config.max_pdm_clock_hz;
+        raw.io.min_pdm_clk_dc = config.min_pdm_clock_duty_cycle;
+        raw.io.max_pdm_clk_dc = config.max_pdm_clock_duty_cycle;
+        raw.streams = &mut stream as *mut ZPcmStreamConfig;
+        raw.channel.req_num_streams = 1;
+        raw.channel.req_num_chan = config.channels.len() as u8;
+        raw.channel.req_chan_map_lo = map as u32;
+        raw.channel.req_chan_map_hi = (map >> 32) as u32;
+
+        result(unsafe { (configure)(self.device as *const Device, &mut raw as *mut ZDmicConfig) })?;
+        self.slab = Some(slab);
+        Ok(ActiveChannels {
+            count: raw.channel.act_num_chan,
+            map: ((raw.channel.act_chan_map_hi as u64) << 32) | raw.channel.act_chan_map_lo as u64,
+        })
+    }
+
+    fn trigger(&mut self, command: zephyr_sys::raw::dmic_trigger) -> ZephyrResult<()> {
+        let trigger = self
+            .api()
+            .and_then(|api| api.trigger)
+            .ok_or_else(not_implemented)?;
+        result(unsafe { (trigger)(self.device as *const Device, command) })
+    }
+
+    /// Start the capture. This is the equivalent of `dmic_trigger` with `DMIC_TRIGGER_START`.
+    pub fn start(&mut self) -> ZephyrResult<()> {
+        self.trigger(zephyr_sys::raw::dmic_trigger_DMIC_TRIGGER_START)
+    }
+
+    /// Stop the capture, it can be started again without configuring it.
+    pub fn stop(&mut self) -> ZephyrResult<()> {
+        self.trigger(zephyr_sys::raw::dmic_trigger_DMIC_TRIGGER_STOP)
+    }
+
+    /// Pause the capture while keeping the PDM clock running.
+    pub fn pause(&mut self) -> ZephyrResult<()> {
+        self.trigger(zephyr_sys::raw::dmic_trigger_DMIC_TRIGGER_PAUSE)
+    }
+
+    /// Continue a paused capture.
+    pub fn release(&mut self) -> ZephyrResult<()> {
+        self.trigger(zephyr_sys::raw::dmic_trigger_DMIC_TRIGGER_RELEASE)
+    }
+
+    /// Stop the capture and return to the unconfigured state.
+    pub fn reset(&mut self) -> ZephyrResult<()> {
+        self.trigger(zephyr_sys::raw::dmic_trigger_DMIC_TRIGGER_RESET)?;
+        self.slab = None;
+        Ok(())
+    }
+
+    /// Wait up to `timeout` for the next block of samples. This is the equivalent of
+    /// `dmic_read`.
+    pub fn read(&mut self, timeout: Option<Duration>) -> ZephyrResult<SlabBlock<'static>> {
+        let slab = self
+            .slab
+            .ok_or_else(|| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))?;
+        let read = self
+            .api()
+            .and_then(|api| api.read)
+            .ok_or_else(not_implemented)?;
+
+        let mut block: *mut c_void = core::ptr::null_mut();
+        let mut size = 0usize;
+        let timeout = timeout
+            .map(|timeout| timeout.as_millis() as i32)
+            .unwrap_or(zephyr_sys::raw::SYS_FOREVER_MS);
+        result(unsafe {
+            (read)(
+                self.device as *const Device,
+                0,
+                &mut block as *mut *mut c_void,
+                &mut size as *mut usize,
+                timeout,
+            )
+        })?;
+        // the driver hands the ownership of the block to the caller
+        Ok(unsafe { SlabBlock::from_raw(slab, block as *mut u8, size) })
+    }
+}
+
+/// Interprets a block of 16 bit samples, the length of `block` must be even.
+pub fn samples_i16(block: &[u8]) -> impl Iterator<Item = i16> + '_ {
+    block
+        .chunks_exact(2)
+        .map(|sample| i16::from_ne_bytes([sample[0], sample[1]]))
+}
+
+/// Root mean square of 16 bit samples, e.g. for level metering.
+pub fn rms(samples: impl Iterator<Item = i16>) -> u16 {
+    let (sum, count) = samples.fold((0u64, 0u64), |(sum, count), sample| {
+        let sample = sample as i64;
+        (sum + (sample * sample) as u64, count + 1)
+    });
+    if count == 0 {
+        0
+    } else {
+        // the mean square is at most 2^30, so the root fits
+        isqrt(sum / count) as u16
+    }
+}
+
+/// Integer square root rounded down, bit by bit as `f64::sqrt` is not available in core.
+fn isqrt(value: u64) -> u64 {
+    let mut rest = value;
+    let mut root = 0;
+    let mut bit = 1 << 62;
+    while bit > value {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if rest >= root + bit {
+            rest -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root
+}
+
+#[derive(Debug)]
+struct DmicWrapperContext {}
+
+impl Context for DmicWrapperContext {
+    fn name(&self) -> &'static str {
+        "dmic wrapper"
+    }
+}
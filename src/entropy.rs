@@ -0,0 +1,148 @@
+//! Syscalls and high level wrappers for the Zephyr entropy API and the cryptographically secure
+//! random number generator of the kernel.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API. With the `rand_core` feature [Entropy] and [CsRand] implement
+//! [rand_core::RngCore] and [rand_core::CryptoRng].
+
+use crate::device::{class, TypedDevice};
+use crate::{Context, ZephyrError, ZephyrResult};
+pub use zephyr::device::Device;
+
+const CONTEXT: EntropyWrapperContext = EntropyWrapperContext {};
+
+/// Wrapper to the `entropy_get_entropy` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// The syscall takes at most [u16::MAX] bytes, larger buffers are filled in multiple calls.
+pub unsafe fn get_entropy(device: &Device, buffer: &mut [u8]) -> ZephyrResult<()> {
+    for chunk in buffer.chunks_mut(u16::MAX as usize) {
+        let errno = zephyr_sys::syscalls::any::entropy_get_entropy(
+            device as *const Device,
+            chunk.as_mut_ptr(),
+            chunk.len() as u16,
+        );
+
+        if errno != 0 {
+            return Err(ZephyrError::from_errno_with_context(errno, &CONTEXT));
+        }
+    }
+
+    Ok(())
+}
+
+/// Wrapper to the `sys_csrand_get` syscall, filling `buffer` with cryptographically secure random
+/// bytes.
+pub fn csrand_get(buffer: &mut [u8]) -> ZephyrResult<()> {
+    let errno = unsafe {
+        zephyr_sys::syscalls::any::sys_csrand_get(
+            buffer.as_mut_ptr() as *mut core::ffi::c_void,
+            buffer.len(),
+        )
+    };
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// High level wrapper for an entropy device, usually the hardware true random number generator.
+#[derive(Copy, Clone)]
+pub struct Entropy {
+    device: &'static Device,
+}
+
+impl Entropy {
+    /// Creates a new [Entropy] on the current interface.
+    ///
+    /// `device` MUST be an entropy device. If `device` is not an entropy device the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Entropy { device }
+    }
+
+    /// Creates a new [Entropy] from a device checked to be an entropy device.
+    pub fn from_device(device: TypedDevice<class::Entropy>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Entropy::new(device.device()) }
+    }
+
+    /// Fill `buffer` with entropy, blocking until enough entropy is available.
+    pub fn fill(&mut self, buffer: &mut [u8]) -> ZephyrResult<()> {
+        // device is required to be an entropy device in constructor
+        unsafe { get_entropy(self.device, buffer) }
+    }
+}
+
+/// The cryptographically secure random number generator of the kernel,
+/// `CONFIG_CSPRNG_ENABLED` must be set.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CsRand;
+
+impl CsRand {
+    /// Fill `buffer` with random bytes.
+    pub fn fill(&mut self, buffer: &mut [u8]) -> ZephyrResult<()> {
+        csrand_get(buffer)
+    }
+}
+
+#[cfg(feature = "rand_core")]
+mod rng {
+    use super::{CsRand, Entropy};
+    use crate::ZephyrError;
+    use rand_core::{CryptoRng, Error, RngCore};
+    use core::num::NonZeroU32;
+
+    /// Convert to a custom error code of [rand_core] holding the error number.
+    fn rand_error(error: ZephyrError) -> Error {
+        let code = Error::CUSTOM_START + i32::from(error.number()) as u32;
+        NonZeroU32::new(code)
+            .expect("custom error codes are not zero")
+            .into()
+    }
+
+    macro_rules! impl_rng {
+        ($rng:ty) => {
+            impl RngCore for $rng {
+                fn next_u32(&mut self) -> u32 {
+                    let mut bytes = [0_u8; 4];
+                    self.fill_bytes(&mut bytes);
+                    u32::from_ne_bytes(bytes)
+                }
+
+                fn next_u64(&mut self) -> u64 {
+                    let mut bytes = [0_u8; 8];
+                    self.fill_bytes(&mut bytes);
+                    u64::from_ne_bytes(bytes)
+                }
+
+                /// Panics if the random source fails, use [RngCore::try_fill_bytes] to handle it.
+                fn fill_bytes(&mut self, dest: &mut [u8]) {
+                    self.fill(dest).expect("reading random bytes")
+                }
+
+                fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+                    self.fill(dest).map_err(rand_error)
+                }
+            }
+
+            impl CryptoRng for $rng {}
+        };
+    }
+
+    impl_rng!(Entropy);
+    impl_rng!(CsRand);
+}
+
+#[derive(Debug)]
+struct EntropyWrapperContext {}
+
+impl Context for EntropyWrapperContext {
+    fn name(&self) -> &'static str {
+        "entropy wrapper"
+    }
+}
@@ -0,0 +1,271 @@
+//! Small executor for the futures of the wrappers, requires the `executor` feature.
+//!
+//! The asynchronous variants of the wrappers, e.g. of the sockets, the UARTs, the sensors and the
+//! resolver, only rely on the wakers of `core::task`, so they run on any executor. [Executor] is a
+//! minimal one built on the kernel primitives of Zephyr, so they share one runtime without
+//! additional dependencies.
+//!
+//! The tasks run on the thread calling [Executor::run] or [Executor::block_on]. Waking a task marks
+//! it as ready and raises a `k_poll_signal` of the executor, which may be done from any thread
+//! and from interrupts, e.g. from the callbacks of the drivers. While no task is ready the thread
+//! of the executor waits for the signal with `k_poll`, so it does not take CPU time from other
+//! threads.
+//!
+//! ```no_run
+//! use zephyr_rust_wrappers::executor::Executor;
+//!
+//! let mut executor = Executor::new();
+//! let spawner = executor.spawner();
+//! spawner.spawn(async {
+//!     // e.g. wait for an AsyncTcpStream
+//! });
+//! executor.run();
+//! ```
+
+use crate::sys::SpinLocked;
+use crate::time::{Timeout, FOREVER};
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use alloc::task::Wake;
+use alloc::vec::Vec;
+use core::cell::{RefCell, UnsafeCell};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use zephyr_sys::raw::{k_poll_event, k_poll_signal};
+
+/// Equivalent of `K_POLL_TYPE_SIGNAL`, the macro is not available in the bindings.
+const K_POLL_TYPE_SIGNAL: u32 = 1 << (zephyr_sys::raw::_poll_types_bits__POLL_TYPE_SIGNAL - 1);
+
+type LocalFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+/// The `k_poll_signal` raised by the wakers of an executor.
+struct Signal(UnsafeCell<k_poll_signal>);
+
+// the signal is a kernel object, raising and resetting it are syscalls
+unsafe impl Send for Signal {}
+unsafe impl Sync for Signal {}
+
+impl Signal {
+    /// Allocates the signal first, the kernel object must not move after its initialization.
+    fn new() -> Arc<Self> {
+        // zeroed as the signal is initialized below
+        let signal = Arc::new(Signal(UnsafeCell::new(unsafe { core::mem::zeroed() })));
+        unsafe { zephyr_sys::syscalls::any::k_poll_signal_init(signal.0.get()) };
+        signal
+    }
+
+    /// Wrapper to the `k_poll_signal_raise` syscall.
+    fn raise(&self) {
+        unsafe { zephyr_sys::syscalls::any::k_poll_signal_raise(self.0.get(), 0) };
+    }
+
+    /// Wrapper to the `k_poll_signal_reset` syscall.
+    fn reset(&self) {
+        unsafe { zephyr_sys::syscalls::any::k_poll_signal_reset(self.0.get()) };
+    }
+
+    /// Waits up to `timeout` for the signal. This is a wrapper to the `k_poll` syscall.
+    fn wait(&self, timeout: Timeout) {
+        let mut event: k_poll_event = unsafe { core::mem::zeroed() };
+        unsafe {
+            zephyr_sys::raw::k_poll_event_init(
+                &mut event as *mut k_poll_event,
+                K_POLL_TYPE_SIGNAL,
+                zephyr_sys::raw::k_poll_modes_K_POLL_MODE_NOTIFY_ONLY as _,
+                self.0.get() as *mut core::ffi::c_void,
+            );
+            // an expired timeout only means that no task has been woken
+            zephyr_sys::syscalls::any::k_poll(&mut event as *mut k_poll_event, 1, timeout);
+        }
+    }
+}
+
+/// State of a task shared with its wakers.
+struct TaskHeader {
+    /// Locked as not every target has compare and swap atomics.
+    ready: SpinLocked<bool>,
+    signal: Arc<Signal>,
+}
+
+impl TaskHeader {
+    fn new(signal: Arc<Signal>) -> Arc<Self> {
+        // tasks are polled once after they have been spawned
+        Arc::new(TaskHeader {
+            ready: SpinLocked::new(true),
+            signal,
+        })
+    }
+
+    /// Clears the ready flag, returns whether the task has to be polled.
+    fn take_ready(&self) -> bool {
+        self.ready.replace(false)
+    }
+}
+
+impl Wake for TaskHeader {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.ready.set(true);
+        self.signal.raise();
+    }
+}
+
+struct Task {
+    header: Arc<TaskHeader>,
+    waker: Waker,
+    future: LocalFuture,
+}
+
+impl Task {
+    fn new(signal: Arc<Signal>, future: LocalFuture) -> Self {
+        let header = TaskHeader::new(signal);
+        Task {
+            waker: Waker::from(header.clone()),
+            header,
+            future,
+        }
+    }
+
+    /// Polls the future if the task has been woken, returns `None` if the task did not run.
+    fn poll_ready(&mut self) -> Option<Poll<()>> {
+        if !self.header.take_ready() {
+            return None;
+        }
+        let mut cx = Context::from_waker(&self.waker);
+        Some(self.future.as_mut().poll(&mut cx))
+    }
+}
+
+/// Single threaded executor whose wakers are backed by a `k_poll_signal`.
+///
+/// The tasks are polled on the thread of the executor only, so they do not need to be `Send`.
+pub struct Executor {
+    signal: Arc<Signal>,
+    tasks: Vec<Task>,
+    spawned: Rc<RefCell<Vec<LocalFuture>>>,
+}
+
+impl Executor {
+    pub fn new() -> Self {
+        Executor {
+            signal: Signal::new(),
+            tasks: Vec::new(),
+            spawned: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// Handle to spawn tasks on this executor, e.g. from within another task.
+    pub fn spawner(&self) -> Spawner {
+        Spawner {
+            spawned: self.spawned.clone(),
+        }
+    }
+
+    /// Spawns a task running `future` to completion.
+    pub fn spawn<F: Future<Output = ()> + 'static>(&self, future: F) {
+        self.spawned.borrow_mut().push(Box::pin(future));
+    }
+
+    /// Number of tasks that did not complete yet.
+    pub fn len(&self) -> usize {
+        self.tasks.len() + self.spawned.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Moves the spawned futures into the list of tasks.
+    fn adopt_spawned(&mut self) {
+        let spawned = core::mem::take(&mut *self.spawned.borrow_mut());
+        let signal = &self.signal;
+        self.tasks.extend(
+            spawned
+                .into_iter()
+                .map(|future| Task::new(signal.clone(), future)),
+        );
+    }
+
+    /// Polls all tasks that have been woken, returns whether any task ran.
+    fn poll_tasks(&mut self) -> bool {
+        self.adopt_spawned();
+        let mut ran = false;
+        self.tasks.retain_mut(|task| match task.poll_ready() {
+            Some(poll) => {
+                ran = true;
+                poll.is_pending()
+            }
+            None => true,
+        });
+        // tasks spawned while polling are polled right away
+        ran || !self.spawned.borrow().is_empty()
+    }
+
+    /// Runs the tasks until all of them completed, the thread sleeps while no task is ready.
+    pub fn run(&mut self) {
+        while !self.is_empty() {
+            self.run_once(FOREVER);
+        }
+    }
+
+    /// Polls the tasks that are ready, waits up to `timeout` for a task to be woken if none is
+    /// ready. Returns whether any task ran, e.g. to run the executor within a loop doing other
+    /// work.
+    pub fn run_once(&mut self, timeout: Timeout) -> bool {
+        // reset before polling, so wakes during the polls are not lost
+        self.signal.reset();
+        if self.poll_tasks() {
+            return true;
+        }
+        self.signal.wait(timeout);
+        self.poll_tasks()
+    }
+
+    /// Runs `future` to completion on the current thread and returns its output. The spawned tasks
+    /// run while `future` is pending.
+    pub fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+        let mut future = core::pin::pin!(future);
+        let header = TaskHeader::new(self.signal.clone());
+        let waker = Waker::from(header.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            self.signal.reset();
+            let mut ran = false;
+            if header.take_ready() {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return output;
+                }
+                ran = true;
+            }
+            ran |= self.poll_tasks();
+            if !ran {
+                self.signal.wait(FOREVER);
+            }
+        }
+    }
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        Executor::new()
+    }
+}
+
+/// Handle of an [Executor] to spawn tasks, obtained with [Executor::spawner].
+#[derive(Clone)]
+pub struct Spawner {
+    spawned: Rc<RefCell<Vec<LocalFuture>>>,
+}
+
+impl Spawner {
+    /// Spawns a task running `future` to completion, it is polled the next time the executor runs.
+    pub fn spawn<F: Future<Output = ()> + 'static>(&self, future: F) {
+        self.spawned.borrow_mut().push(Box::pin(future));
+    }
+}
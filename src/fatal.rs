@@ -0,0 +1,217 @@
+//! Handling of Rust panics through the fatal error handling of Zephyr.
+//!
+//! [report] logs the message of a fatal error through [crate::logging] after switching the logging
+//! subsystem into panic mode with `log_panic`, so the message is output right away even in deferred
+//! mode. The following features choose what happens on panics and fatal errors:
+//!
+//! * `panic-handler` (without `std`) makes this module the panic handler of the application. A
+//!   panic is reported with its message and location and raises a kernel panic like `k_panic`,
+//!   which ends in `k_sys_fatal_error_handler`. With `std`, [install_hook] reports panics before
+//!   the panic runtime of `std` continues.
+//! * `fatal-error-handler` implements `k_sys_fatal_error_handler`, which replaces the default
+//!   handler of Zephyr. Fatal errors that are not panics are reported with their reason, then the
+//!   system is halted with `k_fatal_halt`.
+//! * `panic-reboot` reboots the system with `sys_reboot` instead of raising the kernel panic or
+//!   halting the system.
+//! * `panic-retained` keeps the last reported message in RAM that is not initialized at boot
+//!   (`.noinit`), so [last_panic] returns it after a warm reboot.
+
+#[cfg(feature = "std")]
+use alloc::boxed::Box;
+#[cfg(feature = "panic-retained")]
+use alloc::string::String;
+#[cfg(feature = "fatal-error-handler")]
+use core::ffi::{c_uint, c_void};
+use core::fmt::Arguments;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once a panic has been reported, so the fatal error it raises is not reported again.
+static PANICKING: AtomicBool = AtomicBool::new(false);
+
+/// Logs `message` with the error level after switching the logging into panic mode. With the
+/// `panic-retained` feature, the message is also kept for [last_panic], unless another message is
+/// being retained at the same time.
+pub fn report(message: Arguments<'_>) {
+    #[cfg(feature = "panic-retained")]
+    retain(message);
+    unsafe { zephyr_sys::raw::log_panic() };
+    crate::logging::log_args(
+        core::ptr::null(),
+        zephyr_sys::raw::LOG_LEVEL_ERR as u8,
+        message,
+    );
+}
+
+/// Installs a panic hook of `std` which reports panics with [report].
+#[cfg(feature = "std")]
+pub fn install_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        PANICKING.store(true, Ordering::Relaxed);
+        report(format_args!("{}", info));
+    }));
+}
+
+#[cfg(all(feature = "panic-handler", not(feature = "std")))]
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo<'_>) -> ! {
+    PANICKING.store(true, Ordering::Relaxed);
+    report(format_args!("{}", info));
+    raise_panic()
+}
+
+/// Reboots the system after a panic with the `panic-reboot` feature.
+#[cfg(all(
+    feature = "panic-handler",
+    feature = "panic-reboot",
+    not(feature = "std")
+))]
+fn raise_panic() -> ! {
+    reboot()
+}
+
+/// Raises a kernel panic, the equivalent of `k_panic` which raises it with an exception of the
+/// architecture. `z_fatal_error` returns if the fatal error handler returns, like `k_panic` the
+/// system is then halted.
+#[cfg(all(
+    feature = "panic-handler",
+    not(feature = "panic-reboot"),
+    not(feature = "std")
+))]
+fn raise_panic() -> ! {
+    let reason = zephyr_sys::raw::k_fatal_error_reason_K_ERR_KERNEL_PANIC;
+    unsafe {
+        zephyr_sys::raw::z_fatal_error(reason as _, core::ptr::null());
+        zephyr_sys::raw::k_fatal_halt(reason as _)
+    }
+}
+
+/// Wrapper to the `sys_reboot` function with a cold reboot.
+#[cfg(feature = "panic-reboot")]
+fn reboot() -> ! {
+    unsafe { zephyr_sys::raw::sys_reboot(zephyr_sys::raw::SYS_REBOOT_COLD as _) }
+}
+
+/// Description of the reason of a fatal error, the `K_ERR_*` constants.
+#[cfg(feature = "fatal-error-handler")]
+fn reason_description(reason: c_uint) -> &'static str {
+    match reason {
+        zephyr_sys::raw::k_fatal_error_reason_K_ERR_CPU_EXCEPTION => "CPU exception",
+        zephyr_sys::raw::k_fatal_error_reason_K_ERR_SPURIOUS_IRQ => "Unhandled interrupt",
+        zephyr_sys::raw::k_fatal_error_reason_K_ERR_STACK_CHK_FAIL => "Stack overflow",
+        zephyr_sys::raw::k_fatal_error_reason_K_ERR_KERNEL_OOPS => "Kernel oops",
+        zephyr_sys::raw::k_fatal_error_reason_K_ERR_KERNEL_PANIC => "Kernel panic",
+        _ => "Unknown error",
+    }
+}
+
+/// Handler of fatal errors called by the kernel, replaces the weak default handler of Zephyr.
+#[cfg(feature = "fatal-error-handler")]
+#[no_mangle]
+pub extern "C" fn k_sys_fatal_error_handler(reason: c_uint, _esf: *const c_void) -> ! {
+    if !PANICKING.load(Ordering::Relaxed) {
+        report(format_args!(
+            "fatal error {}: {}",
+            reason,
+            reason_description(reason)
+        ));
+    }
+    halt(reason)
+}
+
+#[cfg(all(feature = "fatal-error-handler", feature = "panic-reboot"))]
+fn halt(_reason: c_uint) -> ! {
+    reboot()
+}
+
+/// Wrapper to the `k_fatal_halt` function, like the default handler of Zephyr.
+#[cfg(all(feature = "fatal-error-handler", not(feature = "panic-reboot")))]
+fn halt(reason: c_uint) -> ! {
+    unsafe { zephyr_sys::raw::k_fatal_halt(reason) }
+}
+
+/// Capacity of the retained message in bytes.
+#[cfg(feature = "panic-retained")]
+pub const RETAINED_LEN: usize = 256;
+
+/// Marks a valid message in [RETAINED].
+#[cfg(feature = "panic-retained")]
+const RETAINED_MAGIC: u32 = 0x5041_4e43;
+
+#[cfg(feature = "panic-retained")]
+#[repr(C)]
+struct RetainedPanic {
+    magic: u32,
+    len: u32,
+    message: [u8; RETAINED_LEN],
+}
+
+/// Message of the last panic, the `.noinit` sections of Zephyr are not initialized at boot.
+#[cfg(feature = "panic-retained")]
+#[link_section = ".noinit.rust_panic"]
+static mut RETAINED: RetainedPanic = RetainedPanic {
+    magic: 0,
+    len: 0,
+    message: [0; RETAINED_LEN],
+};
+
+/// Claims [RETAINED] for one access, a spinlock guards the flag as not every target has compare
+/// and swap atomics.
+#[cfg(feature = "panic-retained")]
+static RETAINED_TAKEN: crate::sys::SpinLocked<bool> = crate::sys::SpinLocked::new(false);
+
+/// Calls `f` with [RETAINED], `None` if it is accessed already, e.g. by a nested panic or an
+/// interrupt.
+#[cfg(feature = "panic-retained")]
+fn with_retained<R>(f: impl FnOnce(&mut RetainedPanic) -> R) -> Option<R> {
+    if RETAINED_TAKEN.replace(true) {
+        return None;
+    }
+    // claimed above, so nothing else accesses the message
+    let result = f(unsafe { &mut *core::ptr::addr_of_mut!(RETAINED) });
+    RETAINED_TAKEN.set(false);
+    Some(result)
+}
+
+#[cfg(feature = "panic-retained")]
+impl core::fmt::Write for RetainedPanic {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let start = self.len as usize;
+        let len = s.len().min(RETAINED_LEN - start);
+        self.message[start..start + len].copy_from_slice(&s.as_bytes()[..len]);
+        self.len += len as u32;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "panic-retained")]
+fn retain(message: Arguments<'_>) {
+    // the message is not retained if another one is being retained
+    with_retained(|retained| {
+        retained.len = 0;
+        // the message is cut if it does not fit
+        let _ = core::fmt::Write::write_fmt(retained, message);
+        unsafe { core::ptr::write_volatile(&mut retained.magic, RETAINED_MAGIC) };
+    });
+}
+
+/// Message of the last reported fatal error, which survives warm reboots. Messages longer than
+/// [RETAINED_LEN] bytes are cut, and `None` is returned while a message is being retained.
+#[cfg(feature = "panic-retained")]
+pub fn last_panic() -> Option<String> {
+    with_retained(|retained| {
+        // the memory is not initialized after a cold boot
+        let magic = unsafe { core::ptr::read_volatile(&retained.magic) };
+        if magic != RETAINED_MAGIC || retained.len as usize > RETAINED_LEN {
+            return None;
+        }
+        let message = &retained.message[..retained.len as usize];
+        Some(String::from_utf8_lossy(message).into_owned())
+    })
+    .flatten()
+}
+
+/// Forgets the message of the last reported fatal error.
+#[cfg(feature = "panic-retained")]
+pub fn clear_last_panic() {
+    with_retained(|retained| unsafe { core::ptr::write_volatile(&mut retained.magic, 0) });
+}
@@ -0,0 +1,187 @@
+//! Wrappers for the Zephyr flash map, which addresses the fixed partitions of the devicetree.
+//!
+//! Partitions are opened by their id, by enumerating all of them with [partitions] or, with
+//! `CONFIG_FLASH_MAP_LABELS` and the `flash-map-labels` feature, by their devicetree label.
+//! Offsets passed to a [FlashArea] are relative to the start of the partition.
+
+use super::{Flash, CONTEXT};
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+#[cfg(feature = "flash-map-labels")]
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::c_void;
+use zephyr_sys::raw::{flash_area as ZFlashArea, off_t};
+
+/// Information about a fixed partition of the flash map.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PartitionInfo {
+    pub id: u8,
+    /// Offset of the partition on its flash device.
+    pub offset: usize,
+    pub size: usize,
+    #[cfg(feature = "flash-map-labels")]
+    pub label: Option<String>,
+}
+
+impl From<&ZFlashArea> for PartitionInfo {
+    fn from(other: &ZFlashArea) -> Self {
+        PartitionInfo {
+            id: other.fa_id,
+            offset: other.fa_off as usize,
+            size: other.fa_size,
+            #[cfg(feature = "flash-map-labels")]
+            label: unsafe { other.label.as_ref() }.map(|label| {
+                unsafe { core::ffi::CStr::from_ptr(label) }
+                    .to_string_lossy()
+                    .into_owned()
+            }),
+        }
+    }
+}
+
+/// Enumerate all partitions of the flash map. This is a wrapper to `flash_area_foreach`.
+pub fn partitions() -> Vec<PartitionInfo> {
+    extern "C" fn collect(area: *const ZFlashArea, user_data: *mut c_void) {
+        let partitions = unsafe { &mut *(user_data as *mut Vec<PartitionInfo>) };
+        if let Some(area) = unsafe { area.as_ref() } {
+            partitions.push(area.into());
+        }
+    }
+
+    let mut partitions: Vec<PartitionInfo> = Vec::new();
+    unsafe {
+        zephyr_sys::raw::flash_area_foreach(
+            Some(collect),
+            &mut partitions as *mut Vec<PartitionInfo> as *mut c_void,
+        )
+    };
+    partitions
+}
+
+/// An open partition of the flash map, closed when dropped.
+pub struct FlashArea {
+    area: *const ZFlashArea,
+}
+
+impl FlashArea {
+    /// Open the partition with `id`. This is a wrapper to `flash_area_open`.
+    ///
+    /// The id is the `FIXED_PARTITION_ID` of the partition in the devicetree.
+    pub fn open(id: u8) -> ZephyrResult<Self> {
+        let mut area: *const ZFlashArea = core::ptr::null();
+        let errno = unsafe { zephyr_sys::raw::flash_area_open(id, &mut area as *mut _) };
+
+        if errno == 0 {
+            Ok(FlashArea { area })
+        } else {
+            Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+        }
+    }
+
+    /// Open the partition with the devicetree `label`, e.g. `"storage_partition"`.
+    ///
+    /// Fails with [ErrorNumber::NoDevice] if there is no partition with this label.
+    #[cfg(feature = "flash-map-labels")]
+    pub fn open_by_label(label: &str) -> ZephyrResult<Self> {
+        let id = partitions()
+            .into_iter()
+            .find(|partition| partition.label.as_deref() == Some(label))
+            .map(|partition| partition.id)
+            .ok_or_else(|| ZephyrError::new_with_context(ErrorNumber::NoDevice, &CONTEXT))?;
+        Self::open(id)
+    }
+
+    fn area(&self) -> &ZFlashArea {
+        // the pointer is set by flash_area_open and points into the static flash map
+        unsafe { &*self.area }
+    }
+
+    /// Information about the partition.
+    pub fn info(&self) -> PartitionInfo {
+        self.area().into()
+    }
+
+    /// Size of the partition in bytes.
+    pub fn size(&self) -> usize {
+        self.area().fa_size
+    }
+
+    /// The flash device the partition is located on.
+    pub fn device(&self) -> ZephyrResult<Flash> {
+        match unsafe { self.area().fa_dev.as_ref() } {
+            // the device of a partition is always a flash device
+            Some(device) => Ok(unsafe { Flash::new(device) }),
+            None => Err(ZephyrError::new_with_context(
+                ErrorNumber::NoDevice,
+                &CONTEXT,
+            )),
+        }
+    }
+
+    /// Minimal alignment of writes. This is a wrapper to `flash_area_align`.
+    pub fn align(&self) -> u32 {
+        unsafe { zephyr_sys::raw::flash_area_align(self.area) }
+    }
+
+    /// Read `buffer.len()` bytes at `offset` of the partition. This is a wrapper to
+    /// `flash_area_read`.
+    pub fn read(&self, offset: usize, buffer: &mut [u8]) -> ZephyrResult<()> {
+        let errno = unsafe {
+            zephyr_sys::raw::flash_area_read(
+                self.area,
+                offset as off_t,
+                buffer.as_mut_ptr() as *mut c_void,
+                buffer.len(),
+            )
+        };
+
+        if errno == 0 {
+            Ok(())
+        } else {
+            Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+        }
+    }
+
+    /// Write `data` at `offset` of the partition. This is a wrapper to `flash_area_write`.
+    ///
+    /// Offset and length must be aligned to [FlashArea::align] and the area must be erased.
+    pub fn write(&mut self, offset: usize, data: &[u8]) -> ZephyrResult<()> {
+        let errno = unsafe {
+            zephyr_sys::raw::flash_area_write(
+                self.area,
+                offset as off_t,
+                data.as_ptr() as *const c_void,
+                data.len(),
+            )
+        };
+
+        if errno == 0 {
+            Ok(())
+        } else {
+            Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+        }
+    }
+
+    /// Erase `size` bytes at `offset` of the partition. This is a wrapper to `flash_area_erase`.
+    pub fn erase(&mut self, offset: usize, size: usize) -> ZephyrResult<()> {
+        let errno =
+            unsafe { zephyr_sys::raw::flash_area_erase(self.area, offset as off_t, size) };
+
+        if errno == 0 {
+            Ok(())
+        } else {
+            Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+        }
+    }
+
+    /// Erase the whole partition.
+    pub fn erase_all(&mut self) -> ZephyrResult<()> {
+        self.erase(0, self.size())
+    }
+}
+
+impl Drop for FlashArea {
+    fn drop(&mut self) {
+        unsafe { zephyr_sys::raw::flash_area_close(self.area) };
+    }
+}
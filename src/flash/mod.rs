@@ -0,0 +1,276 @@
+//! Syscalls and high level wrappers for the Zephyr flash API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API. Page information requires `CONFIG_FLASH_PAGE_LAYOUT` and the
+//! `flash-page-layout` feature.
+//!
+//! Partitions of the flash map are available in [area].
+
+use crate::device::{class, TypedDevice};
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use core::ffi::c_void;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::off_t;
+
+pub mod area;
+
+const CONTEXT: FlashWrapperContext = FlashWrapperContext {};
+const ALIGNMENT_CONTEXT: FlashAlignmentContext = FlashAlignmentContext {};
+#[cfg(feature = "flash-page-layout")]
+const PAGE_CONTEXT: FlashPageContext = FlashPageContext {};
+
+/// Wrapper to the `flash_read` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn read(device: &Device, offset: usize, buffer: &mut [u8]) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::flash_read(
+        device as *const Device,
+        offset as off_t,
+        buffer.as_mut_ptr() as *mut c_void,
+        buffer.len(),
+    );
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `flash_write` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn write(device: &Device, offset: usize, data: &[u8]) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::flash_write(
+        device as *const Device,
+        offset as off_t,
+        data.as_ptr() as *const c_void,
+        data.len(),
+    );
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `flash_erase` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn erase(device: &Device, offset: usize, size: usize) -> ZephyrResult<()> {
+    let errno =
+        zephyr_sys::syscalls::any::flash_erase(device as *const Device, offset as off_t, size);
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `flash_get_write_block_size` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+pub unsafe fn get_write_block_size(device: &Device) -> usize {
+    zephyr_sys::syscalls::any::flash_get_write_block_size(device as *const Device)
+}
+
+/// Wrapper to the `flash_get_parameters` syscall, returns the value of erased bytes.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+///
+/// This method fails with [ErrorNumber::NotSupported] if the driver provides no parameters.
+pub unsafe fn get_erase_value(device: &Device) -> ZephyrResult<u8> {
+    let parameters = zephyr_sys::syscalls::any::flash_get_parameters(device as *const Device);
+    match parameters.as_ref() {
+        Some(parameters) => Ok(parameters.erase_value),
+        None => Err(ZephyrError::new_with_context(
+            ErrorNumber::NotSupported,
+            &CONTEXT,
+        )),
+    }
+}
+
+/// Layout of a flash page.
+#[cfg(feature = "flash-page-layout")]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PageInfo {
+    /// Offset of the first byte of the page.
+    pub start_offset: usize,
+    pub size: usize,
+    pub index: u32,
+}
+
+#[cfg(feature = "flash-page-layout")]
+impl PageInfo {
+    /// Offset after the last byte of the page.
+    pub fn end_offset(&self) -> usize {
+        self.start_offset + self.size
+    }
+}
+
+/// Wrapper to the `flash_get_page_info_by_offs` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+#[cfg(feature = "flash-page-layout")]
+pub unsafe fn get_page_info_by_offset(device: &Device, offset: usize) -> ZephyrResult<PageInfo> {
+    let mut info: zephyr_sys::raw::flash_pages_info = core::mem::zeroed();
+    let errno = zephyr_sys::syscalls::any::flash_get_page_info_by_offs(
+        device as *const Device,
+        offset as off_t,
+        &mut info as *mut _,
+    );
+
+    if errno == 0 {
+        Ok(PageInfo {
+            start_offset: info.start_offset as usize,
+            size: info.size,
+            index: info.index,
+        })
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `flash_get_page_count` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+#[cfg(feature = "flash-page-layout")]
+pub unsafe fn get_page_count(device: &Device) -> usize {
+    zephyr_sys::syscalls::any::flash_get_page_count(device as *const Device)
+}
+
+/// High level wrapper for a flash device.
+///
+/// Writes are checked against the write block size of the device and fail with
+/// [ErrorNumber::InvalidArgument] in the flash alignment context if they are not aligned. With the
+/// `flash-page-layout` feature erases are checked to cover whole pages in the same way.
+pub struct Flash {
+    device: &'static Device,
+}
+
+impl Flash {
+    /// Creates a new [Flash] on the current interface.
+    ///
+    /// `device` MUST be a flash device. If `device` is not a flash device the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Flash { device }
+    }
+
+    /// Creates a new [Flash] from a device checked to be a flash device.
+    pub fn from_device(device: TypedDevice<class::Flash>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Flash::new(device.device()) }
+    }
+
+    /// Minimal number of bytes a write must be aligned to.
+    pub fn write_block_size(&self) -> usize {
+        // device is required to be a flash device in constructor
+        unsafe { get_write_block_size(self.device) }
+    }
+
+    /// Value of the bytes of erased flash, see [get_erase_value].
+    pub fn erase_value(&self) -> ZephyrResult<u8> {
+        // device is required to be a flash device in constructor
+        unsafe { get_erase_value(self.device) }
+    }
+
+    /// Read `buffer.len()` bytes starting at `offset`.
+    pub fn read(&self, offset: usize, buffer: &mut [u8]) -> ZephyrResult<()> {
+        // device is required to be a flash device in constructor
+        unsafe { read(self.device, offset, buffer) }
+    }
+
+    /// Write `data` at `offset`. Offset and length must be multiples of the write block size and
+    /// the area must be erased before.
+    pub fn write(&mut self, offset: usize, data: &[u8]) -> ZephyrResult<()> {
+        let block_size = self.write_block_size();
+        if block_size > 1 && (offset % block_size != 0 || data.len() % block_size != 0) {
+            return Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &ALIGNMENT_CONTEXT,
+            ));
+        }
+
+        // device is required to be a flash device in constructor
+        unsafe { write(self.device, offset, data) }
+    }
+
+    /// Erase `size` bytes starting at `offset`. The area must start and end at page boundaries.
+    pub fn erase(&mut self, offset: usize, size: usize) -> ZephyrResult<()> {
+        #[cfg(feature = "flash-page-layout")]
+        self.check_page_boundaries(offset, size)?;
+
+        // device is required to be a flash device in constructor
+        unsafe { erase(self.device, offset, size) }
+    }
+
+    #[cfg(feature = "flash-page-layout")]
+    fn check_page_boundaries(&self, offset: usize, size: usize) -> ZephyrResult<()> {
+        let first = self.page_info(offset)?;
+        let last = match size {
+            0 => first,
+            size => self.page_info(offset + size - 1)?,
+        };
+
+        if first.start_offset == offset && last.end_offset() == offset + size {
+            Ok(())
+        } else {
+            Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &PAGE_CONTEXT,
+            ))
+        }
+    }
+
+    /// Layout of the page containing `offset`.
+    #[cfg(feature = "flash-page-layout")]
+    pub fn page_info(&self, offset: usize) -> ZephyrResult<PageInfo> {
+        // device is required to be a flash device in constructor
+        unsafe { get_page_info_by_offset(self.device, offset) }
+    }
+
+    /// Number of pages of the device.
+    #[cfg(feature = "flash-page-layout")]
+    pub fn page_count(&self) -> usize {
+        // device is required to be a flash device in constructor
+        unsafe { get_page_count(self.device) }
+    }
+}
+
+#[derive(Debug)]
+struct FlashWrapperContext {}
+
+impl Context for FlashWrapperContext {
+    fn name(&self) -> &'static str {
+        "flash wrapper"
+    }
+}
+
+/// Context of writes that are not aligned to the write block size.
+#[derive(Debug)]
+struct FlashAlignmentContext {}
+
+impl Context for FlashAlignmentContext {
+    fn name(&self) -> &'static str {
+        "flash wrapper (write not aligned to write block size)"
+    }
+}
+
+/// Context of erases that do not cover whole pages.
+#[cfg(feature = "flash-page-layout")]
+#[derive(Debug)]
+struct FlashPageContext {}
+
+#[cfg(feature = "flash-page-layout")]
+impl Context for FlashPageContext {
+    fn name(&self) -> &'static str {
+        "flash wrapper (erase not aligned to page boundaries)"
+    }
+}
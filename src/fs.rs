@@ -0,0 +1,532 @@
+//! Wrappers for the Zephyr file system API.
+//!
+//! File systems are mounted at a mount point like `/lfs` either by the devicetree (`zephyr,fstab`
+//! with the `automount` property) or at runtime with a [Mount]. Paths are absolute and start with
+//! the mount point. [File] and [Dir] close their handle when they are dropped.
+//!
+//! Files and directories on a [Mount] are opened with [Mount::open] and [Mount::open_dir], they
+//! borrow the mount so it cannot be unmounted while they are open. [File::open] and [Dir::open]
+//! are for file systems which stay mounted, like those of the devicetree, and are `unsafe`.
+//!
+//! Errors of the file system drivers are reported with the usual error numbers, e.g.
+//! [ErrorNumber::NoEntry] for missing files, [ErrorNumber::Exists] if a directory that should be
+//! created exists and [ErrorNumber::NoSpace] if the file system is full.
+
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::string::String;
+use bitflags::bitflags;
+use core::ffi::{c_int, c_void, CStr};
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+pub use std::io::SeekFrom;
+use zephyr_sys::raw::{
+    fs_dir_t as ZDir, fs_dirent as ZDirEntry, fs_file_t as ZFile, fs_mount_t as ZMount,
+    fs_statvfs as ZStatVfs, off_t,
+};
+
+/// Position of [File::seek], the equivalent of `std::io::SeekFrom` without the `std` feature.
+#[cfg(not(feature = "std"))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SeekFrom {
+    /// Offset from the start of the file.
+    Start(u64),
+    /// Offset from the end of the file.
+    End(i64),
+    /// Offset from the current position.
+    Current(i64),
+}
+
+const CONTEXT: FsWrapperContext = FsWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+fn c_path(path: &str) -> ZephyrResult<CString> {
+    CString::new(path)
+        .map_err(|_| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))
+}
+
+bitflags! {
+    /// Flags for opening a [File].
+    pub struct OpenFlags: u8 {
+        const READ = zephyr_sys::raw::FS_O_READ as u8;
+        const WRITE = zephyr_sys::raw::FS_O_WRITE as u8;
+        const READ_WRITE = zephyr_sys::raw::FS_O_RDWR as u8;
+        /// Create the file if it does not exist.
+        const CREATE = zephyr_sys::raw::FS_O_CREATE as u8;
+        /// Move to the end of the file before each write.
+        const APPEND = zephyr_sys::raw::FS_O_APPEND as u8;
+        /// Truncate the file to zero length when it is opened.
+        const TRUNCATE = zephyr_sys::raw::FS_O_TRUNC as u8;
+    }
+}
+
+bitflags! {
+    /// Flags for mounting a file system with a [Mount].
+    pub struct MountFlags: u8 {
+        /// Do not format the storage if it does not contain a file system.
+        const NO_FORMAT = zephyr_sys::raw::FS_MOUNT_FLAG_NO_FORMAT as u8;
+        const READ_ONLY = zephyr_sys::raw::FS_MOUNT_FLAG_READ_ONLY as u8;
+        /// The storage is a disk of the disk access API instead of a flash area.
+        const USE_DISK_ACCESS = zephyr_sys::raw::FS_MOUNT_FLAG_USE_DISK_ACCESS as u8;
+    }
+}
+
+/// File system types of Zephyr.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FileSystemType {
+    FatFs,
+    LittleFs,
+    Ext2,
+}
+
+impl From<FileSystemType> for c_int {
+    fn from(other: FileSystemType) -> Self {
+        (match other {
+            FileSystemType::FatFs => zephyr_sys::raw::FS_FATFS,
+            FileSystemType::LittleFs => zephyr_sys::raw::FS_LITTLEFS,
+            FileSystemType::Ext2 => zephyr_sys::raw::FS_EXT2,
+        }) as c_int
+    }
+}
+
+/// Kind of a directory entry.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum EntryType {
+    File,
+    Directory,
+}
+
+/// Entry of a directory, as returned by [Dir] and [stat].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DirEntry {
+    pub name: String,
+    pub entry_type: EntryType,
+    /// Size in bytes, 0 for directories.
+    pub size: usize,
+}
+
+impl From<&ZDirEntry> for DirEntry {
+    fn from(other: &ZDirEntry) -> Self {
+        // the name is a null terminated string in a fixed size array
+        let name = unsafe { CStr::from_ptr(other.name.as_ptr()) };
+        DirEntry {
+            name: name.to_string_lossy().into_owned(),
+            entry_type: if other.type_ == zephyr_sys::raw::fs_dir_entry_type_FS_DIR_ENTRY_DIR {
+                EntryType::Directory
+            } else {
+                EntryType::File
+            },
+            size: other.size,
+        }
+    }
+}
+
+/// Usage of a mounted file system, as returned by [statvfs].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FsStats {
+    /// Size of a block in bytes, which is the allocation unit.
+    pub block_size: usize,
+    /// Size of the file system in blocks.
+    pub blocks: usize,
+    /// Number of free blocks.
+    pub free_blocks: usize,
+}
+
+impl FsStats {
+    /// Size of the file system in bytes.
+    pub fn size(&self) -> usize {
+        self.block_size * self.blocks
+    }
+
+    /// Free space in bytes.
+    pub fn free(&self) -> usize {
+        self.block_size * self.free_blocks
+    }
+}
+
+/// Wrapper to the `fs_mkdir` function.
+pub fn mkdir(path: &str) -> ZephyrResult<()> {
+    let path = c_path(path)?;
+    result(unsafe { zephyr_sys::raw::fs_mkdir(path.as_ptr()) })
+}
+
+/// Wrapper to the `fs_unlink` function, removes a file or an empty directory.
+pub fn unlink(path: &str) -> ZephyrResult<()> {
+    let path = c_path(path)?;
+    result(unsafe { zephyr_sys::raw::fs_unlink(path.as_ptr()) })
+}
+
+/// Wrapper to the `fs_rename` function, both paths must be on the same file system.
+pub fn rename(from: &str, to: &str) -> ZephyrResult<()> {
+    let from = c_path(from)?;
+    let to = c_path(to)?;
+    result(unsafe { zephyr_sys::raw::fs_rename(from.as_ptr(), to.as_ptr()) })
+}
+
+/// Wrapper to the `fs_stat` function.
+pub fn stat(path: &str) -> ZephyrResult<DirEntry> {
+    let path = c_path(path)?;
+    let mut entry: ZDirEntry = unsafe { core::mem::zeroed() };
+    result(unsafe { zephyr_sys::raw::fs_stat(path.as_ptr(), &mut entry as *mut ZDirEntry) })?;
+    Ok((&entry).into())
+}
+
+/// Returns `true` if a file or directory exists at `path`.
+pub fn exists(path: &str) -> bool {
+    stat(path).is_ok()
+}
+
+/// Wrapper to the `fs_statvfs` function, `path` is the mount point or a path on the file system.
+pub fn statvfs(path: &str) -> ZephyrResult<FsStats> {
+    let path = c_path(path)?;
+    let mut stats: ZStatVfs = unsafe { core::mem::zeroed() };
+    result(unsafe { zephyr_sys::raw::fs_statvfs(path.as_ptr(), &mut stats as *mut ZStatVfs) })?;
+    Ok(FsStats {
+        block_size: stats.f_frsize as usize,
+        blocks: stats.f_blocks as usize,
+        free_blocks: stats.f_bfree as usize,
+    })
+}
+
+/// A file system mounted at runtime, it is unmounted when dropped.
+pub struct Mount {
+    // boxed as the file system keeps a pointer to the mount while it is mounted
+    mount: Box<ZMount>,
+    mount_point: CString,
+}
+
+impl Mount {
+    /// Mounts a file system of type `fs_type` at `mount_point`. This is the equivalent of
+    /// `fs_mount`.
+    ///
+    /// `fs_data` and `storage_dev` MUST be valid for the file system type, e.g. a `struct
+    /// fs_littlefs` and the ID of a flash area for LittleFS. Both must stay valid while the file
+    /// system is mounted.
+    pub unsafe fn new(
+        fs_type: FileSystemType,
+        mount_point: &str,
+        fs_data: *mut c_void,
+        storage_dev: *mut c_void,
+        flags: MountFlags,
+    ) -> ZephyrResult<Self> {
+        let mount_point = c_path(mount_point)?;
+        // zeroed as the list node is set when mounting
+        let mut mount: Box<ZMount> = Box::new(core::mem::zeroed());
+        mount.type_ = fs_type.into();
+        mount.mnt_point = mount_point.as_ptr();
+        mount.fs_data = fs_data;
+        mount.storage_dev = storage_dev;
+        mount.flags = flags.bits();
+
+        result(zephyr_sys::raw::fs_mount(&mut *mount as *mut ZMount))?;
+        Ok(Mount { mount, mount_point })
+    }
+
+    /// Mounts LittleFS on the flash area with `area_id` at `mount_point`.
+    ///
+    /// `data` is the state of the file system, declared in C with
+    /// `FS_LITTLEFS_DECLARE_DEFAULT_CONFIG`. The flash area is formatted if it does not contain a
+    /// file system, unless [MountFlags::NO_FORMAT] is set.
+    #[cfg(feature = "fs-littlefs")]
+    pub fn littlefs(
+        mount_point: &str,
+        data: &'static mut zephyr_sys::raw::fs_littlefs,
+        area_id: u8,
+        flags: MountFlags,
+    ) -> ZephyrResult<Self> {
+        unsafe {
+            // LittleFS expects the ID of the flash area in place of the storage pointer
+            Self::new(
+                FileSystemType::LittleFs,
+                mount_point,
+                data as *mut _ as *mut c_void,
+                area_id as usize as *mut c_void,
+                flags,
+            )
+        }
+    }
+
+    /// Mount point of the file system.
+    pub fn mount_point(&self) -> &str {
+        // the mount point was created from a `&str`
+        self.mount_point.to_str().unwrap_or("")
+    }
+
+    /// Usage of the file system.
+    pub fn stats(&self) -> ZephyrResult<FsStats> {
+        statvfs(self.mount_point())
+    }
+
+    fn is_mount(&self, mount: *const ZMount) -> bool {
+        core::ptr::eq(mount, &*self.mount)
+    }
+
+    /// Opens the file at `path` on this file system, like [File::open]. Fails with
+    /// [ErrorNumber::InvalidArgument] if `path` is on another file system.
+    pub fn open(&self, path: &str, flags: OpenFlags) -> ZephyrResult<File<'_>> {
+        // the file is closed again if it is on another file system
+        let file = unsafe { File::open(path, flags)? };
+        if self.is_mount(file.file.mp) {
+            Ok(file)
+        } else {
+            Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &CONTEXT,
+            ))
+        }
+    }
+
+    /// Creates the file at `path` on this file system, like [File::create].
+    pub fn create(&self, path: &str) -> ZephyrResult<File<'_>> {
+        self.open(
+            path,
+            OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::TRUNCATE,
+        )
+    }
+
+    /// Opens the directory at `path` on this file system, like [Dir::open]. Fails with
+    /// [ErrorNumber::InvalidArgument] if `path` is on another file system.
+    pub fn open_dir(&self, path: &str) -> ZephyrResult<Dir<'_>> {
+        let dir = unsafe { Dir::open(path)? };
+        if self.is_mount(dir.dir.mp) {
+            Ok(dir)
+        } else {
+            Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &CONTEXT,
+            ))
+        }
+    }
+}
+
+impl Drop for Mount {
+    fn drop(&mut self) {
+        // errors cannot be reported here, the mount is freed either way
+        unsafe { zephyr_sys::raw::fs_unmount(&mut *self.mount as *mut ZMount) };
+    }
+}
+
+/// An open file on a file system which stays mounted for `'m`, it is closed when dropped.
+///
+/// [File] implements [std::io::Read], [std::io::Write] and [std::io::Seek], so it can be used
+/// with buffered readers and writers of the standard library.
+pub struct File<'m> {
+    // boxed as the file system might keep a pointer to the handle while the file is open
+    file: Box<ZFile>,
+    _mount: PhantomData<&'m Mount>,
+}
+
+impl<'m> File<'m> {
+    /// Opens the file at `path`. This is the equivalent of `fs_open`.
+    ///
+    /// The file system of `path` MUST stay mounted for `'m`, e.g. it is mounted by the devicetree.
+    /// Files on a [Mount] are opened with [Mount::open].
+    pub unsafe fn open(path: &str, flags: OpenFlags) -> ZephyrResult<Self> {
+        let path = c_path(path)?;
+        // zeroed is the equivalent of `fs_file_t_init`
+        let mut file: Box<ZFile> = Box::new(core::mem::zeroed());
+        result(zephyr_sys::raw::fs_open(
+            &mut *file as *mut ZFile,
+            path.as_ptr(),
+            flags.bits(),
+        ))?;
+        Ok(File {
+            file,
+            _mount: PhantomData,
+        })
+    }
+
+    /// Creates the file at `path` or truncates it if it exists, and opens it for writing.
+    ///
+    /// The file system of `path` MUST stay mounted for `'m`, like for [File::open].
+    pub unsafe fn create(path: &str) -> ZephyrResult<Self> {
+        Self::open(path, OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::TRUNCATE)
+    }
+
+    fn file_ptr(&mut self) -> *mut ZFile {
+        &mut *self.file as *mut ZFile
+    }
+
+    /// Read up to `buffer.len()` bytes, returns the number of bytes read which is 0 at the end of
+    /// the file. This is the equivalent of `fs_read`.
+    pub fn read(&mut self, buffer: &mut [u8]) -> ZephyrResult<usize> {
+        let read = unsafe {
+            zephyr_sys::raw::fs_read(
+                self.file_ptr(),
+                buffer.as_mut_ptr() as *mut c_void,
+                buffer.len(),
+            )
+        };
+        if read < 0 {
+            Err(ZephyrError::from_errno_with_context(read as i32, &CONTEXT))
+        } else {
+            Ok(read as usize)
+        }
+    }
+
+    /// Write `data`, returns the number of bytes written which is less than `data.len()` if the
+    /// file system is full. This is the equivalent of `fs_write`.
+    pub fn write(&mut self, data: &[u8]) -> ZephyrResult<usize> {
+        let written = unsafe {
+            zephyr_sys::raw::fs_write(
+                self.file_ptr(),
+                data.as_ptr() as *const c_void,
+                data.len(),
+            )
+        };
+        if written < 0 {
+            Err(ZephyrError::from_errno_with_context(written as i32, &CONTEXT))
+        } else {
+            Ok(written as usize)
+        }
+    }
+
+    /// Move the position in the file, returns the new position from the start of the file. This
+    /// is the equivalent of `fs_seek` followed by `fs_tell`.
+    pub fn seek(&mut self, position: SeekFrom) -> ZephyrResult<u64> {
+        let (offset, whence) = match position {
+            SeekFrom::Start(offset) => (offset as off_t, zephyr_sys::raw::FS_SEEK_SET),
+            SeekFrom::Current(offset) => (offset as off_t, zephyr_sys::raw::FS_SEEK_CUR),
+            SeekFrom::End(offset) => (offset as off_t, zephyr_sys::raw::FS_SEEK_END),
+        };
+        result(unsafe { zephyr_sys::raw::fs_seek(self.file_ptr(), offset, whence as c_int) })?;
+        self.position()
+    }
+
+    /// Position in the file. This is the equivalent of `fs_tell`.
+    pub fn position(&mut self) -> ZephyrResult<u64> {
+        let position = unsafe { zephyr_sys::raw::fs_tell(self.file_ptr()) };
+        if position < 0 {
+            Err(ZephyrError::from_errno_with_context(position as i32, &CONTEXT))
+        } else {
+            Ok(position as u64)
+        }
+    }
+
+    /// Truncate or extend the file to `length` bytes. This is the equivalent of `fs_truncate`.
+    pub fn truncate(&mut self, length: u64) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::fs_truncate(self.file_ptr(), length as off_t) })
+    }
+
+    /// Write cached data to the storage. This is the equivalent of `fs_sync`.
+    pub fn sync(&mut self) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::fs_sync(self.file_ptr()) })
+    }
+}
+
+#[cfg(feature = "std")]
+fn io_error(error: ZephyrError) -> std::io::Error {
+    std::io::Error::from_raw_os_error(error.number().into())
+}
+
+#[cfg(feature = "std")]
+impl std::io::Read for File<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        File::read(self, buf).map_err(io_error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for File<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        File::write(self, buf).map_err(io_error)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.sync().map_err(io_error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Seek for File<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        File::seek(self, pos).map_err(io_error)
+    }
+}
+
+impl Drop for File<'_> {
+    fn drop(&mut self) {
+        // closing flushes the file, errors cannot be reported here
+        unsafe { zephyr_sys::raw::fs_close(self.file_ptr()) };
+    }
+}
+
+/// An open directory on a file system which stays mounted for `'m`, it is closed when dropped.
+///
+/// Iterating a [Dir] returns its entries, an entry fails if the directory could not be read.
+pub struct Dir<'m> {
+    // boxed as the file system might keep a pointer to the handle while the directory is open
+    dir: Box<ZDir>,
+    done: bool,
+    _mount: PhantomData<&'m Mount>,
+}
+
+impl<'m> Dir<'m> {
+    /// Opens the directory at `path`. This is the equivalent of `fs_opendir`.
+    ///
+    /// The file system of `path` MUST stay mounted for `'m`, like for [File::open]. Directories
+    /// on a [Mount] are opened with [Mount::open_dir].
+    pub unsafe fn open(path: &str) -> ZephyrResult<Self> {
+        let path = c_path(path)?;
+        // zeroed is the equivalent of `fs_dir_t_init`
+        let mut dir: Box<ZDir> = Box::new(core::mem::zeroed());
+        result(zephyr_sys::raw::fs_opendir(
+            &mut *dir as *mut ZDir,
+            path.as_ptr(),
+        ))?;
+        Ok(Dir {
+            dir,
+            done: false,
+            _mount: PhantomData,
+        })
+    }
+}
+
+impl Iterator for Dir<'_> {
+    type Item = ZephyrResult<DirEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut entry: ZDirEntry = unsafe { core::mem::zeroed() };
+        let errno = unsafe {
+            zephyr_sys::raw::fs_readdir(&mut *self.dir as *mut ZDir, &mut entry as *mut ZDirEntry)
+        };
+        if errno != 0 {
+            self.done = true;
+            return Some(Err(ZephyrError::from_errno_with_context(errno, &CONTEXT)));
+        }
+        // an empty name marks the end of the directory
+        if entry.name[0] == 0 {
+            self.done = true;
+            return None;
+        }
+        Some(Ok((&entry).into()))
+    }
+}
+
+impl Drop for Dir<'_> {
+    fn drop(&mut self) {
+        // errors cannot be reported here
+        unsafe { zephyr_sys::raw::fs_closedir(&mut *self.dir as *mut ZDir) };
+    }
+}
+
+#[derive(Debug)]
+struct FsWrapperContext {}
+
+impl Context for FsWrapperContext {
+    fn name(&self) -> &'static str {
+        "fs wrapper"
+    }
+}
@@ -0,0 +1,223 @@
+//! Syscalls and high level wrappers for the Zephyr fuel gauge API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! Fuel gauges report properties of a battery, the supported properties depend on the driver.
+//! Unsupported properties fail with [crate::ErrorNumber::NotSupported].
+
+use crate::device::{class, TypedDevice};
+use crate::{Context, ZephyrError, ZephyrResult};
+use core::time::Duration;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::fuel_gauge_prop_val as ZPropertyValue;
+
+const CONTEXT: FuelGaugeWrapperContext = FuelGaugeWrapperContext {};
+
+/// Properties of a fuel gauge.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Property {
+    RelativeStateOfCharge,
+    AbsoluteStateOfCharge,
+    Voltage,
+    Current,
+    AverageCurrent,
+    RemainingCapacity,
+    FullChargeCapacity,
+    RuntimeToEmpty,
+    RuntimeToFull,
+    Temperature,
+    CycleCount,
+}
+
+impl From<Property> for zephyr_sys::raw::fuel_gauge_prop_t {
+    fn from(other: Property) -> Self {
+        (match other {
+            Property::RelativeStateOfCharge => {
+                zephyr_sys::raw::fuel_gauge_prop_type_FUEL_GAUGE_RELATIVE_STATE_OF_CHARGE
+            }
+            Property::AbsoluteStateOfCharge => {
+                zephyr_sys::raw::fuel_gauge_prop_type_FUEL_GAUGE_ABSOLUTE_STATE_OF_CHARGE
+            }
+            Property::Voltage => zephyr_sys::raw::fuel_gauge_prop_type_FUEL_GAUGE_VOLTAGE,
+            Property::Current => zephyr_sys::raw::fuel_gauge_prop_type_FUEL_GAUGE_CURRENT,
+            Property::AverageCurrent => {
+                zephyr_sys::raw::fuel_gauge_prop_type_FUEL_GAUGE_AVG_CURRENT
+            }
+            Property::RemainingCapacity => {
+                zephyr_sys::raw::fuel_gauge_prop_type_FUEL_GAUGE_REMAINING_CAPACITY
+            }
+            Property::FullChargeCapacity => {
+                zephyr_sys::raw::fuel_gauge_prop_type_FUEL_GAUGE_FULL_CHARGE_CAPACITY
+            }
+            Property::RuntimeToEmpty => {
+                zephyr_sys::raw::fuel_gauge_prop_type_FUEL_GAUGE_RUNTIME_TO_EMPTY
+            }
+            Property::RuntimeToFull => {
+                zephyr_sys::raw::fuel_gauge_prop_type_FUEL_GAUGE_RUNTIME_TO_FULL
+            }
+            Property::Temperature => zephyr_sys::raw::fuel_gauge_prop_type_FUEL_GAUGE_TEMPERATURE,
+            Property::CycleCount => zephyr_sys::raw::fuel_gauge_prop_type_FUEL_GAUGE_CYCLE_COUNT,
+        }) as zephyr_sys::raw::fuel_gauge_prop_t
+    }
+}
+
+/// Wrapper to the `fuel_gauge_get_prop` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// The field of the returned union depends on the property.
+pub unsafe fn get_prop(device: &Device, property: Property) -> ZephyrResult<ZPropertyValue> {
+    let mut value: ZPropertyValue = core::mem::zeroed();
+    let errno = zephyr_sys::syscalls::any::fuel_gauge_get_prop(
+        device as *const Device,
+        property.into(),
+        &mut value as *mut ZPropertyValue,
+    );
+
+    if errno == 0 {
+        Ok(value)
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Snapshot of the state of a battery, properties the fuel gauge does not support are `None`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct BatteryStatus {
+    /// Relative state of charge in percent.
+    pub state_of_charge: Option<u8>,
+    /// Voltage in microvolts.
+    pub voltage_uv: Option<i32>,
+    /// Current in microamperes, negative while discharging.
+    pub current_ua: Option<i32>,
+    /// Temperature in degrees Celsius.
+    pub temperature: Option<f32>,
+    pub runtime_to_empty: Option<Duration>,
+    pub runtime_to_full: Option<Duration>,
+}
+
+/// High level wrapper for a fuel gauge.
+pub struct FuelGauge {
+    device: &'static Device,
+}
+
+impl FuelGauge {
+    /// Creates a new [FuelGauge].
+    ///
+    /// `device` MUST be a fuel gauge device. If `device` is not a fuel gauge device the behaviour
+    /// when calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        FuelGauge { device }
+    }
+
+    /// Creates a new [FuelGauge] from a device checked to be a fuel gauge device.
+    pub fn from_device(device: TypedDevice<class::FuelGauge>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { FuelGauge::new(device.device()) }
+    }
+
+    fn property(&self, property: Property) -> ZephyrResult<ZPropertyValue> {
+        // device is required to be a fuel gauge device in constructor
+        unsafe { get_prop(self.device, property) }
+    }
+
+    /// Remaining capacity in percent of the full charge capacity.
+    pub fn state_of_charge(&self) -> ZephyrResult<u8> {
+        let value = self.property(Property::RelativeStateOfCharge)?;
+        // the union field matches the property
+        Ok(unsafe { value.relative_state_of_charge })
+    }
+
+    /// Remaining capacity in percent of the design capacity.
+    pub fn absolute_state_of_charge(&self) -> ZephyrResult<u8> {
+        let value = self.property(Property::AbsoluteStateOfCharge)?;
+        // the union field matches the property
+        Ok(unsafe { value.absolute_state_of_charge })
+    }
+
+    /// Voltage of the battery in microvolts.
+    pub fn voltage_uv(&self) -> ZephyrResult<i32> {
+        let value = self.property(Property::Voltage)?;
+        // the union field matches the property
+        Ok(unsafe { value.voltage })
+    }
+
+    /// Current of the battery in microamperes, negative while discharging.
+    pub fn current_ua(&self) -> ZephyrResult<i32> {
+        let value = self.property(Property::Current)?;
+        // the union field matches the property
+        Ok(unsafe { value.current })
+    }
+
+    /// Average current of the battery in microamperes, negative while discharging.
+    pub fn average_current_ua(&self) -> ZephyrResult<i32> {
+        let value = self.property(Property::AverageCurrent)?;
+        // the union field matches the property
+        Ok(unsafe { value.avg_current })
+    }
+
+    /// Remaining capacity in microampere hours.
+    pub fn remaining_capacity_uah(&self) -> ZephyrResult<u32> {
+        let value = self.property(Property::RemainingCapacity)?;
+        // the union field matches the property
+        Ok(unsafe { value.remaining_capacity })
+    }
+
+    /// Capacity of the fully charged battery in microampere hours.
+    pub fn full_charge_capacity_uah(&self) -> ZephyrResult<u32> {
+        let value = self.property(Property::FullChargeCapacity)?;
+        // the union field matches the property
+        Ok(unsafe { value.full_charge_capacity })
+    }
+
+    /// Estimated time until the battery is empty at the current load.
+    pub fn runtime_to_empty(&self) -> ZephyrResult<Duration> {
+        let value = self.property(Property::RuntimeToEmpty)?;
+        // the union field matches the property and is in minutes
+        Ok(Duration::from_secs(unsafe { value.runtime_to_empty } as u64 * 60))
+    }
+
+    /// Estimated time until the battery is fully charged.
+    pub fn runtime_to_full(&self) -> ZephyrResult<Duration> {
+        let value = self.property(Property::RuntimeToFull)?;
+        // the union field matches the property and is in minutes
+        Ok(Duration::from_secs(unsafe { value.runtime_to_full } as u64 * 60))
+    }
+
+    /// Temperature of the battery in degrees Celsius.
+    pub fn temperature(&self) -> ZephyrResult<f32> {
+        let value = self.property(Property::Temperature)?;
+        // the union field matches the property and is in 0.1 K
+        Ok(unsafe { value.temperature } as f32 / 10.0 - 273.15)
+    }
+
+    /// Number of charge cycles in 1/100 cycles.
+    pub fn cycle_count(&self) -> ZephyrResult<u32> {
+        let value = self.property(Property::CycleCount)?;
+        // the union field matches the property
+        Ok(unsafe { value.cycle_count })
+    }
+
+    /// Reads the commonly supported properties, unsupported ones are `None`.
+    pub fn status(&self) -> BatteryStatus {
+        BatteryStatus {
+            state_of_charge: self.state_of_charge().ok(),
+            voltage_uv: self.voltage_uv().ok(),
+            current_ua: self.current_ua().ok(),
+            temperature: self.temperature().ok(),
+            runtime_to_empty: self.runtime_to_empty().ok(),
+            runtime_to_full: self.runtime_to_full().ok(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct FuelGaugeWrapperContext {}
+
+impl Context for FuelGaugeWrapperContext {
+    fn name(&self) -> &'static str {
+        "fuel gauge wrapper"
+    }
+}
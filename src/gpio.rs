@@ -3,6 +3,7 @@
 //! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
 //! performed to offer a safe API.
 
+use crate::device::{class, TypedDevice};
 use crate::{Context, ZephyrError};
 pub use zephyr_sys::raw::device as Device;
 pub use zephyr_sys::raw::{gpio_flags_t as GpioFlags, gpio_pin_t as GpioPinNumber};
@@ -20,7 +21,7 @@ pub unsafe fn pin_configure(
     pin: GpioPinNumber,
     flags: GpioFlags,
 ) -> Result<(), ZephyrError> {
-    let errno = zephyr_sys::syscalls::any::gpio_pin_configure(port as *const Device, pin, flags);
+    let errno = crate::sys::gpio_pin_configure(port as *const Device, pin, flags);
 
     if errno == 0 {
         Ok(())
@@ -41,9 +42,9 @@ pub unsafe fn pin_set_raw(
     value: bool,
 ) -> Result<(), ZephyrError> {
     let errno = if value {
-        zephyr_sys::syscalls::any::gpio_port_set_bits_raw(port as *const Device, 1 << pin)
+        crate::sys::gpio_port_set_bits_raw(port as *const Device, 1 << pin)
     } else {
-        zephyr_sys::syscalls::any::gpio_port_clear_bits_raw(port as *const Device, 1 << pin)
+        crate::sys::gpio_port_clear_bits_raw(port as *const Device, 1 << pin)
     };
 
     if errno == 0 {
@@ -62,6 +63,40 @@ impl Context for GpioWrapperContext {
     }
 }
 
+/// Wrapper to the `gpio_emul_input_set` function, drives the input `pin` of an emulated GPIO
+/// controller (`CONFIG_GPIO_EMUL`) from a test.
+///
+/// `port` MUST be an emulated GPIO controller. Fails if the pin is not configured as input.
+#[cfg(feature = "test-emul")]
+pub unsafe fn emul_input_set(
+    port: &Device,
+    pin: GpioPinNumber,
+    value: bool,
+) -> Result<(), ZephyrError> {
+    let errno = zephyr_sys::raw::gpio_emul_input_set(port as *const Device, pin, value as i32);
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `gpio_emul_output_get` function, reads the output `pin` of an emulated GPIO
+/// controller (`CONFIG_GPIO_EMUL`) from a test.
+///
+/// `port` MUST be an emulated GPIO controller. Fails if the pin is not configured as output.
+#[cfg(feature = "test-emul")]
+pub unsafe fn emul_output_get(port: &Device, pin: GpioPinNumber) -> Result<bool, ZephyrError> {
+    let value = zephyr_sys::raw::gpio_emul_output_get(port as *const Device, pin);
+
+    if value < 0 {
+        Err(ZephyrError::from_errno_with_context(value, &CONTEXT))
+    } else {
+        Ok(value != 0)
+    }
+}
+
 /// High level wrapper for a GPIO pin.
 pub struct GpioPin {
     device: &'static Device,
@@ -82,6 +117,16 @@ impl GpioPin {
         Ok(GpioPin { device, pin_number })
     }
 
+    /// Creates a new [GpioPin] on a device checked to be a gpio device.
+    pub fn from_device(
+        device: TypedDevice<class::Gpio>,
+        pin_number: GpioPinNumber,
+        flags: GpioFlags,
+    ) -> Result<Self, ZephyrError> {
+        // the class of the device is checked by the handle
+        unsafe { GpioPin::new(device.device(), pin_number, flags) }
+    }
+
     /// Set the state of the GPIO pin.
     pub fn set_value(&mut self, value: bool) -> Result<(), ZephyrError> {
         // device MUST BE a gpio device as per the constructor
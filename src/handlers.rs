@@ -0,0 +1,246 @@
+//! Tables of the closures behind the C callbacks of the wrappers.
+//!
+//! The callbacks run in interrupts, work queues or the threads of subsystems, while the closures
+//! are installed and removed from application threads. A [Handlers] table is locked with a
+//! `k_spinlock`, but only to look up and change its entries: nothing is allocated, freed or called
+//! with the lock held. A callback takes the closure out of its entry and puts it back after the
+//! call, a closure replaced or removed meanwhile is dropped by the callback instead, so a closure
+//! is never freed while it runs. Callbacks happening while the closure of the entry is taken, i.e.
+//! nested or on another CPU, are skipped.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use zephyr_sys::raw::k_spinlock as ZSpinlock;
+
+/// Entry of a table, boxed so its key stays at the same address while it is in the table.
+struct Entry<K, F: ?Sized> {
+    id: usize,
+    key: K,
+    /// `None` while a callback calls the closure.
+    handler: Option<Box<F>>,
+}
+
+struct State<K, F: ?Sized> {
+    entries: Vec<Box<Entry<K, F>>>,
+    next_id: usize,
+}
+
+/// Table of closures `F` installed for keys `K`, e.g. a device and a channel.
+///
+/// Each entry has an id, which C APIs with user data are passed to find the entry in the callback.
+pub(crate) struct Handlers<K, F: ?Sized> {
+    lock: UnsafeCell<ZSpinlock>,
+    state: UnsafeCell<State<K, F>>,
+}
+
+// the state is only accessed with the lock held, the closures are sent to the callbacks
+unsafe impl<K: Send, F: ?Sized + Send> Sync for Handlers<K, F> {}
+
+/// Result of [Handlers::insert].
+pub(crate) struct Inserted<K, F: ?Sized> {
+    /// Id of the entry.
+    pub(crate) id: usize,
+    /// Key in the entry, which stays at this address until the entry is removed.
+    pub(crate) key: *const K,
+    /// Whether the entry has been added, otherwise the closure of an existing entry was replaced.
+    pub(crate) added: bool,
+    /// The replaced closure, `None` if it is called right now.
+    pub(crate) replaced: Option<Box<F>>,
+}
+
+impl<K: PartialEq, F: ?Sized> Handlers<K, F> {
+    pub(crate) const fn new() -> Self {
+        Handlers {
+            // zeroed as an unlocked spinlock is all zeros, like K_SPINLOCK_DEFINE
+            lock: UnsafeCell::new(unsafe { core::mem::zeroed() }),
+            state: UnsafeCell::new(State {
+                entries: Vec::new(),
+                next_id: 0,
+            }),
+        }
+    }
+
+    fn locked<R>(&self, f: impl FnOnce(&mut State<K, F>) -> R) -> R {
+        let key = unsafe { crate::sys::k_spin_lock(self.lock.get()) };
+        // the lock is held, so nothing else accesses the state
+        let result = f(unsafe { &mut *self.state.get() });
+        unsafe { crate::sys::k_spin_unlock(self.lock.get(), key) };
+        result
+    }
+
+    /// Installs `handler` for `key`, replacing the closure of the entry with an equal key.
+    pub(crate) fn insert(&self, key: K, handler: Box<F>) -> Inserted<K, F> {
+        self.add(key, handler, true)
+    }
+
+    /// Adds an entry for `key` next to the entries with an equal key, e.g. while the driver may
+    /// still call the closure installed before until it accepted the new one.
+    pub(crate) fn push(&self, key: K, handler: Box<F>) -> Inserted<K, F> {
+        self.add(key, handler, false)
+    }
+
+    fn add(&self, key: K, handler: Box<F>, replace: bool) -> Inserted<K, F> {
+        let mut entry = Some(Box::new(Entry {
+            id: 0,
+            key,
+            handler: Some(handler),
+        }));
+        // the table grows into a vector allocated without the lock held
+        let mut spare: Vec<Box<Entry<K, F>>> = Vec::new();
+        loop {
+            let result = self.locked(|state| {
+                let new = entry.as_mut().unwrap();
+                let mut entries = state.entries.iter_mut();
+                if let Some(existing) = entries.find(|e| replace && e.key == new.key) {
+                    return Ok(Inserted {
+                        id: existing.id,
+                        key: &existing.key,
+                        added: false,
+                        replaced: core::mem::replace(&mut existing.handler, new.handler.take()),
+                    });
+                }
+                if state.entries.len() == state.entries.capacity() {
+                    if spare.capacity() <= state.entries.len() {
+                        return Err(state.entries.len());
+                    }
+                    spare.append(&mut state.entries);
+                    core::mem::swap(&mut spare, &mut state.entries);
+                }
+                let mut new = entry.take().unwrap();
+                // ids keep the order of the entries, 32 bits are plenty for the installed closures
+                new.id = state.next_id;
+                state.next_id += 1;
+                let inserted = Inserted {
+                    id: new.id,
+                    key: &new.key,
+                    added: true,
+                    replaced: None,
+                };
+                state.entries.push(new);
+                Ok(inserted)
+            });
+            match result {
+                Ok(inserted) => return inserted,
+                Err(len) => spare = Vec::with_capacity((len * 2).max(4)),
+            }
+        }
+    }
+
+    /// Sets the closure of the entry `id` back to `handler`, e.g. the one returned by
+    /// [Handlers::insert] after installing the new closure failed. Returns the closure it
+    /// replaces, which is dropped without the lock held.
+    pub(crate) fn restore(&self, id: usize, handler: Option<Box<F>>) -> Option<Box<F>> {
+        self.locked(|state| {
+            let entry = state.entries.iter_mut().find(|entry| entry.id == id)?;
+            core::mem::replace(&mut entry.handler, handler)
+        })
+    }
+
+    /// Removes the entry `id`.
+    pub(crate) fn remove_id(&self, id: usize) -> bool {
+        self.remove_first(|entry| entry.id == id)
+    }
+
+    /// Removes the entry with `key`.
+    pub(crate) fn remove(&self, key: &K) -> bool {
+        self.remove_first(|entry| entry.key == *key)
+    }
+
+    /// Removes all entries whose key matches `matches`.
+    pub(crate) fn remove_where(&self, mut matches: impl FnMut(&K) -> bool) {
+        while self.remove_first(|entry| matches(&entry.key)) {}
+    }
+
+    /// Removes all entries other than `id` whose key matches `matches`, e.g. the ones replaced by
+    /// an entry added with [Handlers::push].
+    pub(crate) fn remove_others(&self, id: usize, mut matches: impl FnMut(&K) -> bool) {
+        while self.remove_first(|entry| entry.id != id && matches(&entry.key)) {}
+    }
+
+    fn remove_first(&self, mut matches: impl FnMut(&Entry<K, F>) -> bool) -> bool {
+        let removed = self.locked(|state| {
+            let index = state.entries.iter().position(|entry| matches(entry))?;
+            Some(state.entries.remove(index))
+        });
+        // the entry and its closure are dropped here, without the lock held
+        removed.is_some()
+    }
+
+    /// Id of the entry with `key`.
+    pub(crate) fn id(&self, key: &K) -> Option<usize> {
+        self.locked(|state| {
+            let entry = state.entries.iter().find(|entry| entry.key == *key)?;
+            Some(entry.id)
+        })
+    }
+
+    /// Calls `f` with the key of the entry `id` with the lock held, e.g. to get the C callback in
+    /// it before the entry is removed.
+    pub(crate) fn with_key<R>(&self, id: usize, f: impl FnOnce(&K) -> R) -> Option<R> {
+        self.locked(|state| {
+            let entry = state.entries.iter().find(|entry| entry.id == id)?;
+            Some(f(&entry.key))
+        })
+    }
+
+    /// Calls `f` with the closure of the entry `id`, returns `None` if there is no such entry or
+    /// its closure is called already.
+    pub(crate) fn call_id<R>(&self, id: usize, f: impl FnOnce(&mut F) -> R) -> Option<R> {
+        self.call_first(|entry| entry.id == id, f)
+    }
+
+    /// Calls `f` with the closure of the first entry whose key matches `matches`, like
+    /// [Handlers::call_id].
+    pub(crate) fn call<R>(
+        &self,
+        mut matches: impl FnMut(&K) -> bool,
+        f: impl FnOnce(&mut F) -> R,
+    ) -> Option<R> {
+        self.call_first(|entry| matches(&entry.key), f)
+    }
+
+    /// Calls `f` with the closures of all entries whose key matches `matches`.
+    pub(crate) fn call_all(&self, mut matches: impl FnMut(&K) -> bool, mut f: impl FnMut(&mut F)) {
+        // the entries are ordered by id, the closures may add and remove entries meanwhile
+        let mut next_id = 0;
+        loop {
+            let id = self.locked(|state| {
+                let mut entries = state.entries.iter();
+                let entry = entries.find(|entry| entry.id >= next_id && matches(&entry.key))?;
+                Some(entry.id)
+            });
+            match id {
+                Some(id) => {
+                    self.call_id(id, &mut f);
+                    next_id = id + 1;
+                }
+                None => return,
+            }
+        }
+    }
+
+    fn call_first<R>(
+        &self,
+        mut matches: impl FnMut(&Entry<K, F>) -> bool,
+        f: impl FnOnce(&mut F) -> R,
+    ) -> Option<R> {
+        let (id, mut handler) = self.locked(|state| {
+            let entry = state.entries.iter_mut().find(|entry| matches(entry))?;
+            Some((entry.id, entry.handler.take()?))
+        })?;
+        let result = f(&mut handler);
+        let dropped = self.locked(|state| {
+            match state.entries.iter_mut().find(|entry| entry.id == id) {
+                Some(entry) if entry.handler.is_none() => {
+                    entry.handler = Some(handler);
+                    None
+                }
+                // the entry was removed or its closure replaced during the call
+                _ => Some(handler),
+            }
+        });
+        drop(dropped);
+        Some(result)
+    }
+}
@@ -0,0 +1,121 @@
+//! Syscalls for the Zephyr hardware info API.
+//!
+//! The device ID is a unique, read only value of the SoC that can be used as serial number. The
+//! reset cause tells why the device was reset, Zephyr accumulates the causes until they are
+//! cleared with [clear_reset_cause].
+
+use crate::{Context, ZephyrError, ZephyrResult};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use bitflags::bitflags;
+use core::fmt::Write;
+
+const CONTEXT: HwinfoWrapperContext = HwinfoWrapperContext {};
+
+/// Length of the buffer passed to `hwinfo_get_device_id`, large enough for all SoCs supported by
+/// Zephyr.
+const DEVICE_ID_MAX_LENGTH: usize = 32;
+
+bitflags! {
+    /// Causes of a reset, as reported by `hwinfo_get_reset_cause`.
+    pub struct ResetCause: u32 {
+        const PIN = zephyr_sys::raw::RESET_PIN;
+        const SOFTWARE = zephyr_sys::raw::RESET_SOFTWARE;
+        const BROWNOUT = zephyr_sys::raw::RESET_BROWNOUT;
+        const POWER_ON = zephyr_sys::raw::RESET_POR;
+        const WATCHDOG = zephyr_sys::raw::RESET_WATCHDOG;
+        const DEBUG = zephyr_sys::raw::RESET_DEBUG;
+        const SECURITY = zephyr_sys::raw::RESET_SECURITY;
+        const LOW_POWER_WAKE = zephyr_sys::raw::RESET_LOW_POWER_WAKE;
+        const CPU_LOCKUP = zephyr_sys::raw::RESET_CPU_LOCKUP;
+        const PARITY = zephyr_sys::raw::RESET_PARITY;
+        const PLL = zephyr_sys::raw::RESET_PLL;
+        const CLOCK = zephyr_sys::raw::RESET_CLOCK;
+        const HARDWARE = zephyr_sys::raw::RESET_HARDWARE;
+        const USER = zephyr_sys::raw::RESET_USER;
+        const TEMPERATURE = zephyr_sys::raw::RESET_TEMPERATURE;
+    }
+}
+
+/// Wrapper to the `hwinfo_get_device_id` syscall.
+///
+/// Returns the bytes of the device ID, their count depends on the SoC.
+pub fn device_id() -> ZephyrResult<Vec<u8>> {
+    let mut buffer = vec![0u8; DEVICE_ID_MAX_LENGTH];
+    let length = unsafe {
+        zephyr_sys::syscalls::any::hwinfo_get_device_id(buffer.as_mut_ptr(), buffer.len())
+    };
+
+    if length < 0 {
+        Err(ZephyrError::from_errno_with_context(length as i32, &CONTEXT))
+    } else {
+        buffer.truncate(length as usize);
+        Ok(buffer)
+    }
+}
+
+/// Device ID formatted as lower case hex string, e.g. to be used as serial number.
+pub fn device_id_hex() -> ZephyrResult<String> {
+    let id = device_id()?;
+    let mut hex = String::with_capacity(id.len() * 2);
+    for byte in id {
+        // writing to a string cannot fail
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    Ok(hex)
+}
+
+/// Wrapper to the `hwinfo_get_reset_cause` syscall.
+///
+/// Causes that are unknown to this wrapper are dropped.
+pub fn reset_cause() -> ZephyrResult<ResetCause> {
+    let mut cause = 0u32;
+    let errno =
+        unsafe { zephyr_sys::syscalls::any::hwinfo_get_reset_cause(&mut cause as *mut u32) };
+
+    if errno == 0 {
+        Ok(ResetCause::from_bits_truncate(cause))
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `hwinfo_get_supported_reset_cause` syscall.
+///
+/// Returns the causes the SoC is able to report.
+pub fn supported_reset_cause() -> ZephyrResult<ResetCause> {
+    let mut cause = 0u32;
+    let errno = unsafe {
+        zephyr_sys::syscalls::any::hwinfo_get_supported_reset_cause(&mut cause as *mut u32)
+    };
+
+    if errno == 0 {
+        Ok(ResetCause::from_bits_truncate(cause))
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `hwinfo_clear_reset_cause` syscall.
+///
+/// Call this after the reset cause was reported, otherwise causes of later resets are added to
+/// the current ones.
+pub fn clear_reset_cause() -> ZephyrResult<()> {
+    let errno = unsafe { zephyr_sys::syscalls::any::hwinfo_clear_reset_cause() };
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+#[derive(Debug)]
+struct HwinfoWrapperContext {}
+
+impl Context for HwinfoWrapperContext {
+    fn name(&self) -> &'static str {
+        "hwinfo wrapper"
+    }
+}
@@ -0,0 +1,54 @@
+//! Implementation of the [embedded_hal] I2C traits for [I2c], so device driver crates of the
+//! embedded ecosystem can use the I2C controllers of Zephyr.
+
+use super::{I2c, I2cAddress, I2cMessage};
+use crate::{ErrorNumber, ZephyrError};
+use alloc::vec::Vec;
+use embedded_hal::i2c::{
+    Error, ErrorKind, ErrorType, I2c as HalI2c, NoAcknowledgeSource, Operation, SevenBitAddress,
+    TenBitAddress,
+};
+
+impl Error for ZephyrError {
+    fn kind(&self) -> ErrorKind {
+        match self.number() {
+            // the drivers of Zephyr report a missing acknowledge as I/O error
+            ErrorNumber::Io => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown),
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl ErrorType for I2c {
+    type Error = ZephyrError;
+}
+
+fn messages<'a, 'b>(operations: &'a mut [Operation<'b>]) -> Vec<I2cMessage<'a>> {
+    operations
+        .iter_mut()
+        .map(|operation| match operation {
+            Operation::Read(buffer) => I2cMessage::Read(buffer),
+            Operation::Write(data) => I2cMessage::Write(data),
+        })
+        .collect()
+}
+
+impl HalI2c<SevenBitAddress> for I2c {
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.transfer(I2cAddress::SevenBit(address), &mut messages(operations))
+    }
+}
+
+impl HalI2c<TenBitAddress> for I2c {
+    fn transaction(
+        &mut self,
+        address: TenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.transfer(I2cAddress::TenBit(address), &mut messages(operations))
+    }
+}
@@ -0,0 +1,365 @@
+//! Syscalls and high level wrappers for the Zephyr I2C controller API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::i2c::{I2c, I2cAddress};
+//!# fn read_id(i2c: &mut I2c) {
+//! let mut id = [0_u8; 1];
+//! i2c.write_read(I2cAddress::SevenBit(0x68), &[0x75], &mut id)
+//!     .expect("reading WHO_AM_I register");
+//!# }
+//! ```
+
+use crate::device::{class, TypedDevice};
+use crate::{Context, ErrorNumber, ResultExt, ZephyrError, ZephyrResult};
+use alloc::format;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::i2c_msg as ZI2cMessage;
+
+#[cfg(feature = "embedded-hal")]
+pub mod hal;
+
+const CONTEXT: I2cWrapperContext = I2cWrapperContext {};
+
+/// Address of an I2C target.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum I2cAddress {
+    SevenBit(u8),
+    TenBit(u16),
+}
+
+impl I2cAddress {
+    fn address(&self) -> u16 {
+        match self {
+            I2cAddress::SevenBit(address) => *address as u16,
+            I2cAddress::TenBit(address) => *address,
+        }
+    }
+
+    /// Returns `true` if the address fits into its 7 or 10 bits.
+    fn is_valid(&self) -> bool {
+        match self {
+            I2cAddress::SevenBit(address) => *address <= 0x7f,
+            I2cAddress::TenBit(address) => *address <= 0x3ff,
+        }
+    }
+
+    fn message_flags(&self) -> u8 {
+        match self {
+            I2cAddress::SevenBit(_) => 0,
+            I2cAddress::TenBit(_) => zephyr_sys::raw::I2C_MSG_ADDR_10_BITS as u8,
+        }
+    }
+}
+
+/// Bus speed of an I2C controller. The list uses the values from Zephyr header files.
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BusSpeed {
+    /// 100 kHz
+    Standard = zephyr_sys::raw::I2C_SPEED_STANDARD,
+    /// 400 kHz
+    Fast = zephyr_sys::raw::I2C_SPEED_FAST,
+    /// 1 MHz
+    FastPlus = zephyr_sys::raw::I2C_SPEED_FAST_PLUS,
+    /// 3.4 MHz
+    High = zephyr_sys::raw::I2C_SPEED_HIGH,
+    /// 5 MHz
+    Ultra = zephyr_sys::raw::I2C_SPEED_ULTRA,
+}
+
+impl BusSpeed {
+    /// Returns the device configuration for a controller with this speed and 7-bit addressing.
+    /// This is the equivalent of `I2C_SPEED_SET(speed) | I2C_MODE_CONTROLLER`.
+    pub fn controller_config(&self) -> u32 {
+        ((*self as u32) << zephyr_sys::raw::I2C_SPEED_SHIFT) & zephyr_sys::raw::I2C_SPEED_MASK
+            | zephyr_sys::raw::I2C_MODE_CONTROLLER
+    }
+}
+
+/// Single message of an I2C transfer.
+pub enum I2cMessage<'a> {
+    Read(&'a mut [u8]),
+    Write(&'a [u8]),
+}
+
+impl I2cMessage<'_> {
+    fn is_read(&self) -> bool {
+        matches!(self, I2cMessage::Read(_))
+    }
+}
+
+/// Converts `messages` to the messages of the C API.
+///
+/// The flags are set like the helper functions of the Zephyr API do: a restart condition is
+/// generated when the direction changes and a stop condition after the last message.
+fn raw_messages(address: I2cAddress, messages: &mut [I2cMessage<'_>]) -> Vec<ZI2cMessage> {
+    let count = messages.len();
+    let mut previous_read: Option<bool> = None;
+
+    messages
+        .iter_mut()
+        .enumerate()
+        .map(|(index, message)| {
+            let is_read = message.is_read();
+            let mut flags = address.message_flags();
+            if is_read {
+                flags |= zephyr_sys::raw::I2C_MSG_READ as u8;
+            }
+            if previous_read.map(|previous| previous != is_read) == Some(true) {
+                flags |= zephyr_sys::raw::I2C_MSG_RESTART as u8;
+            }
+            if index == count - 1 {
+                flags |= zephyr_sys::raw::I2C_MSG_STOP as u8;
+            }
+            previous_read = Some(is_read);
+
+            let (buf, len) = match message {
+                I2cMessage::Read(buffer) => (buffer.as_mut_ptr(), buffer.len()),
+                // written buffers are never modified by the driver
+                I2cMessage::Write(data) => (data.as_ptr() as *mut u8, data.len()),
+            };
+            ZI2cMessage {
+                buf,
+                len: len as u32,
+                flags,
+            }
+        })
+        .collect()
+}
+
+/// Wrapper to the `i2c_configure` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn configure(device: &Device, dev_config: u32) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::i2c_configure(device as *const Device, dev_config);
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `i2c_get_config` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn get_config(device: &Device) -> ZephyrResult<u32> {
+    let mut dev_config = 0_u32;
+    let errno = zephyr_sys::syscalls::any::i2c_get_config(
+        device as *const Device,
+        &mut dev_config as *mut u32,
+    );
+
+    if errno == 0 {
+        Ok(dev_config)
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `i2c_transfer` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// This method fails with [ErrorNumber::InvalidArgument] if there are more than 255 messages.
+pub unsafe fn transfer_raw(
+    device: &Device,
+    messages: &mut [ZI2cMessage],
+    address: u16,
+) -> ZephyrResult<()> {
+    let count = u8::try_from(messages.len())
+        .map_err(|_| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))?;
+    let errno = zephyr_sys::syscalls::any::i2c_transfer(
+        device as *const Device,
+        messages.as_mut_ptr(),
+        count,
+        address,
+    );
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Performs the transfer of `messages` to the target `address`.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+///
+/// This method fails with [ErrorNumber::InvalidArgument] if `address` does not fit into its 7 or
+/// 10 bits or there are more than 255 messages.
+pub unsafe fn transfer(
+    device: &Device,
+    address: I2cAddress,
+    messages: &mut [I2cMessage<'_>],
+) -> ZephyrResult<()> {
+    if !address.is_valid() {
+        return Err(ZephyrError::new_with_context(
+            ErrorNumber::InvalidArgument,
+            &CONTEXT,
+        ));
+    }
+    if messages.is_empty() {
+        return Ok(());
+    }
+    let mut raw = raw_messages(address, messages);
+    transfer_raw(device, &mut raw, address.address())
+}
+
+/// High level wrapper for an I2C controller.
+///
+/// This essentially wraps the static reference of the underlying device and offers methods that use
+/// the safe syscall wrappers for an object oriented programming interface.
+pub struct I2c {
+    device: &'static Device,
+}
+
+impl I2c {
+    /// Creates a new [I2c] on the current interface.
+    ///
+    /// `device` MUST be an I2C controller. If `device` is not an I2C controller the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        I2c { device }
+    }
+
+    /// Creates a new [I2c] from a device checked to be an I2C controller.
+    pub fn from_device(device: TypedDevice<class::I2c>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { I2c::new(device.device()) }
+    }
+
+    /// Configure the controller to use `speed`. This is the equivalent of `i2c_configure` with
+    /// `I2C_SPEED_SET(speed) | I2C_MODE_CONTROLLER`.
+    pub fn set_speed(&mut self, speed: BusSpeed) -> ZephyrResult<()> {
+        // device is required to be an I2C controller in constructor
+        unsafe { configure(self.device, speed.controller_config()) }
+    }
+
+    /// Returns the current device configuration of the controller.
+    pub fn config(&self) -> ZephyrResult<u32> {
+        // device is required to be an I2C controller in constructor
+        unsafe { get_config(self.device) }
+    }
+
+    /// Performs the transfer of `messages` to the target `address`, see [transfer].
+    pub fn transfer(
+        &mut self,
+        address: I2cAddress,
+        messages: &mut [I2cMessage<'_>],
+    ) -> ZephyrResult<()> {
+        // device is required to be an I2C controller in constructor
+        unsafe { transfer(self.device, address, messages) }
+    }
+
+    /// Write `data` to the target `address`. This is the equivalent of `i2c_write`.
+    pub fn write(&mut self, address: I2cAddress, data: &[u8]) -> ZephyrResult<()> {
+        self.transfer(address, &mut [I2cMessage::Write(data)])
+    }
+
+    /// Read `buffer.len()` bytes from the target `address`. This is the equivalent of `i2c_read`.
+    pub fn read(&mut self, address: I2cAddress, buffer: &mut [u8]) -> ZephyrResult<()> {
+        self.transfer(address, &mut [I2cMessage::Read(buffer)])
+    }
+
+    /// Write `data` and read `buffer.len()` bytes from the target `address` after a restart
+    /// condition. This is the equivalent of `i2c_write_read`.
+    pub fn write_read(
+        &mut self,
+        address: I2cAddress,
+        data: &[u8],
+        buffer: &mut [u8],
+    ) -> ZephyrResult<()> {
+        self.transfer(
+            address,
+            &mut [I2cMessage::Write(data), I2cMessage::Read(buffer)],
+        )
+    }
+}
+
+/// Register access helpers for the common register layout of I2C peripherals, where the register
+/// address is written first and the register values are read or written afterwards.
+impl I2c {
+    /// Read the register `register` of the target `address`. This is the equivalent of
+    /// `i2c_reg_read_byte`.
+    pub fn read_reg(&mut self, address: I2cAddress, register: u8) -> ZephyrResult<u8> {
+        let mut value = [0_u8; 1];
+        self.write_read(address, &[register], &mut value)
+            .with_operation(|| format!("read_reg(0x{:02X})", register))?;
+        Ok(value[0])
+    }
+
+    /// Write `value` to the register `register` of the target `address`. This is the equivalent of
+    /// `i2c_reg_write_byte`.
+    pub fn write_reg(&mut self, address: I2cAddress, register: u8, value: u8) -> ZephyrResult<()> {
+        self.write(address, &[register, value])
+            .with_operation(|| format!("write_reg(0x{:02X})", register))
+    }
+
+    /// Set the bits of `mask` in the register `register` of the target `address` to the bits of
+    /// `value`. This is the equivalent of `i2c_reg_update_byte`.
+    pub fn update_reg(
+        &mut self,
+        address: I2cAddress,
+        register: u8,
+        mask: u8,
+        value: u8,
+    ) -> ZephyrResult<()> {
+        let old = self.read_reg(address, register)?;
+        let new = (old & !mask) | (value & mask);
+        if new == old {
+            Ok(())
+        } else {
+            self.write_reg(address, register, new)
+        }
+    }
+
+    /// Read `buffer.len()` consecutive registers starting at `start_register` of the target
+    /// `address`. This is the equivalent of `i2c_burst_read`.
+    pub fn burst_read(
+        &mut self,
+        address: I2cAddress,
+        start_register: u8,
+        buffer: &mut [u8],
+    ) -> ZephyrResult<()> {
+        self.write_read(address, &[start_register], buffer)
+            .with_operation(|| format!("burst_read(0x{:02X})", start_register))
+    }
+
+    /// Write `data` to consecutive registers starting at `start_register` of the target `address`.
+    /// This is the equivalent of `i2c_burst_write`.
+    pub fn burst_write(
+        &mut self,
+        address: I2cAddress,
+        start_register: u8,
+        data: &[u8],
+    ) -> ZephyrResult<()> {
+        // both messages are writes, so the data directly follows the register address on the bus
+        self.transfer(
+            address,
+            &mut [
+                I2cMessage::Write(&[start_register]),
+                I2cMessage::Write(data),
+            ],
+        )
+        .with_operation(|| format!("burst_write(0x{:02X})", start_register))
+    }
+}
+
+#[derive(Debug)]
+struct I2cWrapperContext {}
+
+impl Context for I2cWrapperContext {
+    fn name(&self) -> &'static str {
+        "i2c wrapper"
+    }
+}
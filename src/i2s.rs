@@ -0,0 +1,412 @@
+//! Syscalls and high level wrappers for the Zephyr I2S API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! I2S drivers stream audio in blocks allocated from a [MemorySlab]. Received blocks are returned
+//! as [SlabBlock] and freed when they are dropped, blocks to transmit are allocated from the slab
+//! of the TX stream and freed by the driver once they have been sent. A stream is started with
+//! [I2s::start] after enough blocks have been queued to bridge the time until the next write.
+
+use crate::device::{class, TypedDevice};
+use crate::memory_slab::{MemorySlab, SlabBlock};
+use crate::time::Timeout;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use core::ffi::{c_int, c_void};
+use core::time::Duration;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::i2s_config as ZI2sConfig;
+
+const CONTEXT: I2sWrapperContext = I2sWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Direction of an I2S stream.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Direction {
+    Rx,
+    Tx,
+    /// Both streams, only supported by some drivers for configuration and triggers.
+    Both,
+}
+
+impl From<Direction> for zephyr_sys::raw::i2s_dir {
+    fn from(other: Direction) -> Self {
+        match other {
+            Direction::Rx => zephyr_sys::raw::i2s_dir_I2S_DIR_RX,
+            Direction::Tx => zephyr_sys::raw::i2s_dir_I2S_DIR_TX,
+            Direction::Both => zephyr_sys::raw::i2s_dir_I2S_DIR_BOTH,
+        }
+    }
+}
+
+/// Data format of the serial interface.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DataFormat {
+    /// Standard I2S, data is delayed by one bit clock after the frame clock edge.
+    I2s,
+    LeftJustified,
+    RightJustified,
+    /// PCM with a frame clock pulse of one bit clock.
+    PcmShort,
+    /// PCM with a frame clock pulse of one word.
+    PcmLong,
+}
+
+impl From<DataFormat> for u8 {
+    fn from(other: DataFormat) -> Self {
+        (match other {
+            DataFormat::I2s => zephyr_sys::raw::I2S_FMT_DATA_FORMAT_I2S,
+            DataFormat::LeftJustified => zephyr_sys::raw::I2S_FMT_DATA_FORMAT_LEFT_JUSTIFIED,
+            DataFormat::RightJustified => zephyr_sys::raw::I2S_FMT_DATA_FORMAT_RIGHT_JUSTIFIED,
+            DataFormat::PcmShort => zephyr_sys::raw::I2S_FMT_DATA_FORMAT_PCM_SHORT,
+            DataFormat::PcmLong => zephyr_sys::raw::I2S_FMT_DATA_FORMAT_PCM_LONG,
+        }) as u8
+    }
+}
+
+/// Commands for [trigger].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    /// Start the stream, for TX the queue must hold at least one block.
+    Start,
+    /// Stop the stream after the current block.
+    Stop,
+    /// Stop the stream after all queued blocks have been transmitted.
+    Drain,
+    /// Stop the stream immediately and discard all queued blocks.
+    Drop,
+    /// Recover from an error state, discarding all queued blocks.
+    Prepare,
+}
+
+impl From<Command> for zephyr_sys::raw::i2s_trigger_cmd {
+    fn from(other: Command) -> Self {
+        match other {
+            Command::Start => zephyr_sys::raw::i2s_trigger_cmd_I2S_TRIGGER_START,
+            Command::Stop => zephyr_sys::raw::i2s_trigger_cmd_I2S_TRIGGER_STOP,
+            Command::Drain => zephyr_sys::raw::i2s_trigger_cmd_I2S_TRIGGER_DRAIN,
+            Command::Drop => zephyr_sys::raw::i2s_trigger_cmd_I2S_TRIGGER_DROP,
+            Command::Prepare => zephyr_sys::raw::i2s_trigger_cmd_I2S_TRIGGER_PREPARE,
+        }
+    }
+}
+
+/// Configuration of an I2S stream.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct StreamConfig {
+    /// Number of bits of a sample of a channel, e.g. 16.
+    pub word_size: u8,
+    /// Number of channels in a frame, e.g. 2 for stereo.
+    pub channels: u8,
+    pub format: DataFormat,
+    /// Transmit the least significant bit first.
+    pub lsb_first: bool,
+    pub invert_bit_clock: bool,
+    pub invert_frame_clock: bool,
+    /// Frame clock (sample rate) in Hz, e.g. 48000.
+    pub frame_clock_hz: u32,
+    /// Generate the bit clock, otherwise it is an input.
+    pub bit_clock_controller: bool,
+    /// Generate the frame clock, otherwise it is an input.
+    pub frame_clock_controller: bool,
+    /// Only run the bit clock while data is transferred.
+    pub bit_clock_gated: bool,
+    /// Connect TX to RX internally.
+    pub loopback: bool,
+    /// Size of a block in bytes, at most the block size of the slab.
+    pub block_size: usize,
+    /// Time to wait for free space in the TX queue or for a received block.
+    pub timeout: Option<Duration>,
+}
+
+impl StreamConfig {
+    /// Standard I2S stream in controller mode with the given sample format and rate.
+    pub const fn new(word_size: u8, channels: u8, frame_clock_hz: u32, block_size: usize) -> Self {
+        StreamConfig {
+            word_size,
+            channels,
+            format: DataFormat::I2s,
+            lsb_first: false,
+            invert_bit_clock: false,
+            invert_frame_clock: false,
+            frame_clock_hz,
+            bit_clock_controller: true,
+            frame_clock_controller: true,
+            bit_clock_gated: false,
+            loopback: false,
+            block_size,
+            timeout: None,
+        }
+    }
+
+    /// Size of a frame, i.e. one sample of every channel, in bytes.
+    pub fn frame_size(&self) -> usize {
+        let word_bytes = (self.word_size as usize + 7) / 8;
+        word_bytes * self.channels as usize
+    }
+
+    fn format(&self) -> u8 {
+        let mut format: u8 = self.format.into();
+        if self.lsb_first {
+            format |= zephyr_sys::raw::I2S_FMT_DATA_ORDER_LSB as u8;
+        }
+        if self.invert_bit_clock {
+            format |= zephyr_sys::raw::I2S_FMT_BIT_CLK_INV as u8;
+        }
+        if self.invert_frame_clock {
+            format |= zephyr_sys::raw::I2S_FMT_FRAME_CLK_INV as u8;
+        }
+        format
+    }
+
+    fn options(&self) -> u8 {
+        let mut options = 0;
+        if !self.bit_clock_controller {
+            options |= zephyr_sys::raw::I2S_OPT_BIT_CLK_SLAVE as u8;
+        }
+        if !self.frame_clock_controller {
+            options |= zephyr_sys::raw::I2S_OPT_FRAME_CLK_SLAVE as u8;
+        }
+        if self.bit_clock_gated {
+            options |= zephyr_sys::raw::I2S_OPT_BIT_CLK_GATED as u8;
+        }
+        if self.loopback {
+            options |= zephyr_sys::raw::I2S_OPT_LOOPBACK as u8;
+        }
+        options
+    }
+
+    fn raw(&self, slab: &MemorySlab) -> ZI2sConfig {
+        ZI2sConfig {
+            word_size: self.word_size,
+            channels: self.channels,
+            format: self.format(),
+            options: self.options(),
+            frame_clk_freq: self.frame_clock_hz,
+            mem_slab: slab.slab_ptr(),
+            block_size: self.block_size,
+            timeout: self
+                .timeout
+                .map(|timeout| timeout.as_millis() as i32)
+                .unwrap_or(zephyr_sys::raw::SYS_FOREVER_MS),
+        }
+    }
+}
+
+/// Wrapper to the `i2s_configure` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// `slab` MUST stay valid as long as the stream is configured.
+pub unsafe fn configure(
+    device: &Device,
+    direction: Direction,
+    config: &StreamConfig,
+    slab: &MemorySlab,
+) -> ZephyrResult<()> {
+    let raw = config.raw(slab);
+    result(zephyr_sys::syscalls::any::i2s_configure(
+        device as *const Device,
+        direction.into(),
+        &raw as *const ZI2sConfig,
+    ))
+}
+
+/// Wrapper to the `i2s_trigger` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn trigger(device: &Device, direction: Direction, command: Command) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::i2s_trigger(
+        device as *const Device,
+        direction.into(),
+        command.into(),
+    ))
+}
+
+/// Wrapper to the `i2s_buf_write` syscall, copies `data` into a block of the TX slab.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn buf_write(device: &Device, data: &[u8]) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::i2s_buf_write(
+        device as *const Device,
+        data.as_ptr() as *mut c_void,
+        data.len(),
+    ))
+}
+
+/// Wrapper to the `i2s_buf_read` syscall, copies a received block into `buffer`.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// `buffer` MUST hold a whole block of the RX stream, the driver copies the block without checking
+/// the size of `buffer`. Returns the number of bytes read.
+pub unsafe fn buf_read(device: &Device, buffer: &mut [u8]) -> ZephyrResult<usize> {
+    let mut size = buffer.len();
+    result(zephyr_sys::syscalls::any::i2s_buf_read(
+        device as *const Device,
+        buffer.as_mut_ptr() as *mut c_void,
+        &mut size as *mut usize,
+    ))?;
+    Ok(size)
+}
+
+/// High level wrapper for an I2S interface.
+pub struct I2s {
+    device: &'static Device,
+    rx_slab: Option<&'static MemorySlab>,
+    tx_slab: Option<&'static MemorySlab>,
+}
+
+impl I2s {
+    /// Creates a new [I2s].
+    ///
+    /// `device` MUST be an I2S device. If `device` is not an I2S device the behaviour when calling
+    /// any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        I2s {
+            device,
+            rx_slab: None,
+            tx_slab: None,
+        }
+    }
+
+    /// Creates a new [I2s] from a device checked to be an I2S device.
+    pub fn from_device(device: TypedDevice<class::I2s>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { I2s::new(device.device()) }
+    }
+
+    /// Configure the stream of `direction`, its blocks are allocated from `slab`.
+    pub fn configure(
+        &mut self,
+        direction: Direction,
+        config: &StreamConfig,
+        slab: &'static MemorySlab,
+    ) -> ZephyrResult<()> {
+        if config.block_size > slab.block_size() {
+            return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT));
+        }
+        // device is required to be an I2S device in constructor
+        unsafe { configure(self.device, direction, config, slab)? };
+        if direction != Direction::Tx {
+            self.rx_slab = Some(slab);
+        }
+        if direction != Direction::Rx {
+            self.tx_slab = Some(slab);
+        }
+        Ok(())
+    }
+
+    /// Send a trigger command to the stream of `direction`.
+    pub fn trigger(&mut self, direction: Direction, command: Command) -> ZephyrResult<()> {
+        // device is required to be an I2S device in constructor
+        unsafe { trigger(self.device, direction, command) }
+    }
+
+    /// Start the stream of `direction`.
+    pub fn start(&mut self, direction: Direction) -> ZephyrResult<()> {
+        self.trigger(direction, Command::Start)
+    }
+
+    /// Stop the stream of `direction` after all queued blocks have been transmitted.
+    pub fn drain(&mut self, direction: Direction) -> ZephyrResult<()> {
+        self.trigger(direction, Command::Drain)
+    }
+
+    /// Stop the stream of `direction` immediately and discard queued blocks.
+    pub fn drop_stream(&mut self, direction: Direction) -> ZephyrResult<()> {
+        self.trigger(direction, Command::Drop)
+    }
+
+    /// Allocate a block to be filled and queued with [I2s::write_block]. Waits up to `timeout`
+    /// for a free block.
+    pub fn alloc_block(&self, timeout: Timeout) -> ZephyrResult<SlabBlock<'static>> {
+        self.tx_slab
+            .ok_or_else(|| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))?
+            .alloc(timeout)
+    }
+
+    /// Queue a block for transmission without copying it. This is the equivalent of `i2s_write`.
+    ///
+    /// The block MUST be allocated from the slab the TX stream is configured with, e.g. with
+    /// [I2s::alloc_block], the driver frees it after transmission.
+    pub fn write_block(&mut self, block: SlabBlock<'static>) -> ZephyrResult<()> {
+        let tx_slab = self
+            .tx_slab
+            .ok_or_else(|| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))?;
+        if !core::ptr::eq(block.slab(), tx_slab) {
+            return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT));
+        }
+
+        let len = block.len();
+        let slab = block.slab();
+        let raw = block.into_raw();
+        // device is required to be an I2S device in constructor
+        let errno =
+            unsafe { zephyr_sys::raw::i2s_write(self.device as *const Device, raw as *mut _, len) };
+        if errno != 0 {
+            // the driver did not take the block, so it is still owned here
+            drop(unsafe { SlabBlock::from_raw(slab, raw, len) });
+            return Err(ZephyrError::from_errno_with_context(errno, &CONTEXT));
+        }
+        Ok(())
+    }
+
+    /// Copy `data` into a block and queue it for transmission.
+    pub fn write(&mut self, data: &[u8]) -> ZephyrResult<()> {
+        // device is required to be an I2S device in constructor
+        unsafe { buf_write(self.device, data) }
+    }
+
+    /// Returns the next received block without copying it. This is the equivalent of `i2s_read`.
+    pub fn read_block(&mut self) -> ZephyrResult<SlabBlock<'static>> {
+        let rx_slab = self
+            .rx_slab
+            .ok_or_else(|| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))?;
+
+        let mut block: *mut c_void = core::ptr::null_mut();
+        let mut size = 0usize;
+        // device is required to be an I2S device in constructor
+        result(unsafe {
+            zephyr_sys::raw::i2s_read(
+                self.device as *const Device,
+                &mut block as *mut *mut c_void,
+                &mut size as *mut usize,
+            )
+        })?;
+        // the driver hands the ownership of the block to the caller
+        Ok(unsafe { SlabBlock::from_raw(rx_slab, block as *mut u8, size) })
+    }
+
+    /// Copy the next received block into `buffer`, returns the number of bytes read. Fails with
+    /// [ErrorNumber::InvalidArgument] if `buffer` is shorter than a block of the RX slab.
+    pub fn read(&mut self, buffer: &mut [u8]) -> ZephyrResult<usize> {
+        match self.rx_slab {
+            // the blocks of the stream are at most as large as the blocks of its slab
+            Some(slab) if buffer.len() >= slab.block_size() => {}
+            _ => return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT)),
+        }
+        // device is required to be an I2S device in constructor
+        unsafe { buf_read(self.device, buffer) }
+    }
+}
+
+#[derive(Debug)]
+struct I2sWrapperContext {}
+
+impl Context for I2sWrapperContext {
+    fn name(&self) -> &'static str {
+        "i2s wrapper"
+    }
+}
@@ -0,0 +1,213 @@
+//! Wrappers for the Zephyr input subsystem.
+//!
+//! Input drivers like buttons (`gpio-keys`), keyboard matrices, touch controllers and encoders
+//! report [InputEvent]s, which are dispatched to the callbacks added with [add_callback]. An event
+//! with `sync` set completes a report, e.g. the x and y coordinate of a touch, [TouchDecoder]
+//! combines such reports of touchscreens.
+//!
+//! Zephyr collects input callbacks at link time, this module registers a single callback in the
+//! iterable section of the subsystem and dispatches the events to the Rust callbacks. Callbacks
+//! are called from the input thread with `CONFIG_INPUT_MODE_THREAD`, otherwise from the context of
+//! the reporting driver. The module requires the `input` feature, which must only be enabled
+//! together with `CONFIG_INPUT`.
+
+use crate::handlers::Handlers;
+use crate::{Context, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use core::ffi::c_void;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::{input_callback as ZInputCallback, input_event as ZInputEvent};
+
+const CONTEXT: InputWrapperContext = InputWrapperContext {};
+
+/// Key code of a touch on a touchscreen.
+pub const BTN_TOUCH: u16 = zephyr_sys::raw::INPUT_BTN_TOUCH as u16;
+/// Absolute x axis.
+pub const ABS_X: u16 = zephyr_sys::raw::INPUT_ABS_X as u16;
+/// Absolute y axis.
+pub const ABS_Y: u16 = zephyr_sys::raw::INPUT_ABS_Y as u16;
+/// Relative x axis, e.g. of an encoder.
+pub const REL_X: u16 = zephyr_sys::raw::INPUT_REL_X as u16;
+/// Relative y axis.
+pub const REL_Y: u16 = zephyr_sys::raw::INPUT_REL_Y as u16;
+/// Relative wheel axis.
+pub const REL_WHEEL: u16 = zephyr_sys::raw::INPUT_REL_WHEEL as u16;
+
+/// Decoded value of an [InputEvent].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum EventKind {
+    /// A key or button with one of the `INPUT_KEY_*` or `INPUT_BTN_*` codes changed its state.
+    Key { code: u16, pressed: bool },
+    /// A relative axis like [REL_X] moved by `value`.
+    Relative { axis: u16, value: i32 },
+    /// An absolute axis like [ABS_X] has the position `value`.
+    Absolute { axis: u16, value: i32 },
+    /// Miscellaneous event, e.g. a scan code of a keyboard matrix.
+    Misc { code: u16, value: i32 },
+    /// Event of a type unknown to this wrapper, e.g. a vendor specific one.
+    Other { event_type: u8, code: u16, value: i32 },
+}
+
+/// Event reported by an input device.
+#[derive(Copy, Clone, Debug)]
+pub struct InputEvent {
+    /// Device that reported the event, `None` for events reported without a device.
+    pub device: Option<&'static Device>,
+    /// The event completes a report of several events.
+    pub sync: bool,
+    pub kind: EventKind,
+}
+
+impl From<&ZInputEvent> for InputEvent {
+    fn from(other: &ZInputEvent) -> Self {
+        let code = other.code;
+        let value = other.value;
+        let kind = match other.type_ as u32 {
+            zephyr_sys::raw::INPUT_EV_KEY => EventKind::Key {
+                code,
+                pressed: value != 0,
+            },
+            zephyr_sys::raw::INPUT_EV_REL => EventKind::Relative { axis: code, value },
+            zephyr_sys::raw::INPUT_EV_ABS => EventKind::Absolute { axis: code, value },
+            zephyr_sys::raw::INPUT_EV_MSC => EventKind::Misc { code, value },
+            _ => EventKind::Other {
+                event_type: other.type_,
+                code,
+                value,
+            },
+        };
+
+        InputEvent {
+            // devices are static
+            device: unsafe { (other.dev as *const Device).as_ref() },
+            sync: other.sync() != 0,
+            kind,
+        }
+    }
+}
+
+/// State of a touchscreen, as decoded by a [TouchDecoder].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TouchEvent {
+    pub x: i32,
+    pub y: i32,
+    pub pressed: bool,
+}
+
+/// Combines the events of a touch controller to [TouchEvent]s.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TouchDecoder {
+    state: TouchEvent,
+}
+
+impl TouchDecoder {
+    /// Creates a new decoder for a released touchscreen.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the state with `event`, returns the state when the report is complete.
+    pub fn decode(&mut self, event: &InputEvent) -> Option<TouchEvent> {
+        match event.kind {
+            EventKind::Absolute { axis: ABS_X, value } => self.state.x = value,
+            EventKind::Absolute { axis: ABS_Y, value } => self.state.y = value,
+            EventKind::Key {
+                code: BTN_TOUCH,
+                pressed,
+            } => self.state.pressed = pressed,
+            _ => {}
+        }
+
+        if event.sync {
+            Some(self.state)
+        } else {
+            None
+        }
+    }
+}
+
+/// Identifies a callback added with [add_callback].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CallbackId(usize);
+
+type Callback = dyn FnMut(&InputEvent) + Send;
+
+/// Callbacks by the device they are added for, `None` for all devices.
+static CALLBACKS: Handlers<Option<usize>, Callback> = Handlers::new();
+
+/// Call `f` for every event of `device`, or of all devices if `device` is `None`.
+pub fn add_callback<F>(device: Option<&'static Device>, f: F) -> CallbackId
+where
+    F: FnMut(&InputEvent) + Send + 'static,
+{
+    let device = device.map(|device| device as *const Device as usize);
+    CallbackId(CALLBACKS.push(device, Box::new(f)).id)
+}
+
+/// Remove a callback added with [add_callback].
+pub fn remove_callback(id: CallbackId) {
+    CALLBACKS.remove_id(id.0);
+}
+
+/// Wrapper to the `input_report` function, reports an event for `device`.
+///
+/// This allows applications to inject events, e.g. of a virtual keyboard. Waits up to `timeout`
+/// for space in the event queue with `CONFIG_INPUT_MODE_THREAD`.
+pub fn report(
+    device: Option<&'static Device>,
+    event_type: u8,
+    code: u16,
+    value: i32,
+    sync: bool,
+    timeout: crate::time::Timeout,
+) -> ZephyrResult<()> {
+    let device = device.map_or(core::ptr::null(), |device| device as *const Device);
+    let errno =
+        unsafe { zephyr_sys::raw::input_report(device, event_type, code, value, sync, timeout) };
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// C callback of the input subsystem that dispatches events to the Rust callbacks.
+extern "C" fn input_trampoline(event: *mut ZInputEvent, _user_data: *mut c_void) {
+    // the event is valid for the duration of the callback
+    let event: InputEvent = match unsafe { event.as_ref() } {
+        Some(event) => event.into(),
+        None => return,
+    };
+    let device = event.device.map(|device| device as *const Device as usize);
+
+    CALLBACKS.call_all(
+        |key| key.is_none() || *key == device,
+        |handler| handler(&event),
+    );
+}
+
+#[repr(transparent)]
+struct StaticInputCallback(ZInputCallback);
+
+// the callback is only read by the input subsystem
+unsafe impl Sync for StaticInputCallback {}
+
+/// Callback in the iterable section of the input subsystem, the equivalent of
+/// `INPUT_CALLBACK_DEFINE(NULL, input_trampoline, NULL)`.
+#[used]
+#[link_section = "._input_callback.static.zephyr_rust_wrappers_input"]
+static INPUT_CALLBACK: StaticInputCallback = StaticInputCallback(ZInputCallback {
+    dev: core::ptr::null(),
+    callback: Some(input_trampoline),
+    user_data: core::ptr::null_mut(),
+});
+
+#[derive(Debug)]
+struct InputWrapperContext {}
+
+impl Context for InputWrapperContext {
+    fn name(&self) -> &'static str {
+        "input wrapper"
+    }
+}
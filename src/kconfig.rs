@@ -0,0 +1,22 @@
+//! Options of the Kconfig configuration of the Zephyr build.
+//!
+//! The constants are generated from the `.config` of the build, which is set with the environment
+//! variable `ZEPHYR_DOTCONFIG` or found in the build directory `ZEPHYR_BINARY_DIR`. Each option
+//! becomes a constant named like in C: boolean options are `bool` (also the disabled ones),
+//! integers `i64`, hexadecimal numbers `u64` and strings `&str`. Without a configuration, the
+//! module is empty.
+//!
+//! ```no_run
+//! use zephyr_rust_wrappers::kconfig;
+//!
+//! let connections = [0u8; kconfig::CONFIG_BT_MAX_CONN as usize];
+//! if kconfig::CONFIG_NET_IPV6 {
+//!     println!("IPv6 is enabled");
+//! }
+//! ```
+//!
+//! The enabled boolean options are also cfg flags of this crate named `zephyr_config_` and the
+//! lower case name without `CONFIG_`, e.g. `#[cfg(zephyr_config_net_ipv6)]`, so wrappers are only
+//! compiled in if Zephyr provides the functions they call.
+
+include!(concat!(env!("OUT_DIR"), "/kconfig.rs"));
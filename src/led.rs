@@ -0,0 +1,161 @@
+//! Syscalls and high level wrappers for the Zephyr LED API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! LED controllers like the PCA9633 drive several LEDs, which are the child nodes of the controller
+//! in the devicetree. A [Led] addresses one of them by the index of its child node.
+
+use crate::device::{class, TypedDevice};
+use crate::{Context, ZephyrError, ZephyrResult};
+use core::ffi::c_int;
+use core::time::Duration;
+pub use zephyr::device::Device;
+
+const CONTEXT: LedWrapperContext = LedWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `led_on` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn on(device: &Device, led: u32) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::led_on(device as *const Device, led))
+}
+
+/// Wrapper to the `led_off` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn off(device: &Device, led: u32) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::led_off(device as *const Device, led))
+}
+
+/// Wrapper to the `led_set_brightness` syscall, `value` is a percentage between 0 and 100.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn set_brightness(device: &Device, led: u32, value: u8) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::led_set_brightness(
+        device as *const Device,
+        led,
+        value,
+    ))
+}
+
+/// Wrapper to the `led_blink` syscall, with the on and off times in milliseconds.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn blink(device: &Device, led: u32, delay_on: u32, delay_off: u32) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::led_blink(
+        device as *const Device,
+        led,
+        delay_on,
+        delay_off,
+    ))
+}
+
+/// Wrapper to the `led_set_color` syscall, with one value per color of the LED.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn set_color(device: &Device, led: u32, colors: &[u8]) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::led_set_color(
+        device as *const Device,
+        led,
+        colors.len() as u8,
+        colors.as_ptr(),
+    ))
+}
+
+/// High level wrapper for a single LED of a LED controller.
+#[derive(Copy, Clone)]
+pub struct Led {
+    device: &'static Device,
+    index: u32,
+}
+
+impl Led {
+    /// Creates a new [Led] for the child node with `index` of the controller.
+    ///
+    /// `device` MUST be a LED controller. If `device` is not a LED controller the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device, index: u32) -> Self {
+        Led { device, index }
+    }
+
+    /// Creates a new [Led] from a device checked to be a LED controller.
+    pub fn from_device(device: TypedDevice<class::Led>, index: u32) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Led::new(device.device(), index) }
+    }
+
+    /// Index of the LED on its controller.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Turn the LED on with full brightness.
+    pub fn on(&mut self) -> ZephyrResult<()> {
+        // device is required to be a LED controller in constructor
+        unsafe { on(self.device, self.index) }
+    }
+
+    /// Turn the LED off.
+    pub fn off(&mut self) -> ZephyrResult<()> {
+        // device is required to be a LED controller in constructor
+        unsafe { off(self.device, self.index) }
+    }
+
+    /// Turn the LED on or off.
+    pub fn set(&mut self, on: bool) -> ZephyrResult<()> {
+        if on {
+            self.on()
+        } else {
+            self.off()
+        }
+    }
+
+    /// Set the brightness as a percentage, values above 100 are clamped.
+    pub fn set_brightness(&mut self, percent: u8) -> ZephyrResult<()> {
+        // device is required to be a LED controller in constructor
+        unsafe { set_brightness(self.device, self.index, percent.min(100)) }
+    }
+
+    /// Let the controller blink the LED with the given on and off times.
+    pub fn blink(&mut self, on: Duration, off: Duration) -> ZephyrResult<()> {
+        // device is required to be a LED controller in constructor
+        unsafe {
+            blink(
+                self.device,
+                self.index,
+                on.as_millis() as u32,
+                off.as_millis() as u32,
+            )
+        }
+    }
+
+    /// Set the brightness of each color of a multi color LED, in the order of its
+    /// `color-mapping` in the devicetree.
+    pub fn set_color(&mut self, colors: &[u8]) -> ZephyrResult<()> {
+        // device is required to be a LED controller in constructor
+        unsafe { set_color(self.device, self.index, colors) }
+    }
+}
+
+#[derive(Debug)]
+struct LedWrapperContext {}
+
+impl Context for LedWrapperContext {
+    fn name(&self) -> &'static str {
+        "led wrapper"
+    }
+}
@@ -1,64 +1,522 @@
-#![feature(arbitrary_enum_discriminant)]
-#![feature(const_fn_transmute)]
-#![feature(const_fn_fn_ptr_basics)]
-#![feature(const_mut_refs)]
-#![feature(const_fn)]
-#![feature(const_ptr_offset)]
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
 extern crate zephyr_sys;
 
-use std::error::Error;
-use std::fmt::{Debug, Display, Formatter, write};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::error::Error;
+use core::fmt::{Debug, Display, Formatter, write};
 
+pub mod adc;
 #[cfg(feature = "bluetooth")]
 pub mod bluetooth;
+pub mod can;
+pub mod cellular;
+pub mod charger;
+pub mod console;
+pub mod counter;
+pub mod crypto;
+#[cfg(feature = "defmt")]
+pub mod defmt_logger;
+pub mod device;
+pub mod dfu;
+pub mod diagnostics;
+pub mod disk;
+pub mod display;
+pub mod dmic;
+pub mod dma;
+pub mod entropy;
+#[cfg(feature = "executor")]
+pub mod executor;
+pub mod fatal;
+pub mod flash;
+pub mod fs;
+pub mod fuel_gauge;
 pub mod gpio;
+mod handlers;
+pub mod hwinfo;
+pub mod i2c;
+pub mod i2s;
+#[cfg(feature = "input")]
+pub mod input;
+pub mod kconfig;
+pub mod led;
+pub mod logging;
+pub mod lora;
+pub mod lorawan;
+pub mod memory_slab;
+pub mod message_queue;
+#[cfg(feature = "mock-syscalls")]
+pub mod mock;
+#[cfg(feature = "native-sim")]
+pub mod native_sim;
 pub mod network;
+pub mod power;
+pub mod pwm;
+pub mod random;
+pub mod regulator;
+pub mod rtc;
 pub mod sensor;
+pub mod settings;
+pub mod smbus;
+pub mod spi;
+mod sys;
+pub mod time;
+pub mod timing;
+pub mod uart;
+pub mod usb;
+pub mod version;
+pub mod video;
+pub mod watchdog;
+#[cfg(feature = "ztest")]
+pub mod ztest;
 
 /// Trait for a context in which an error can occur.
-pub trait Context: Debug {
+pub trait Context: Debug + Sync {
     fn name(&self) -> &'static str;
 }
 
+/// Context of a [ZephyrError], usually the wrapper module the error occurred in.
+#[derive(Clone, Debug)]
+pub enum ErrorContext {
+    /// Static context of a wrapper module.
+    Wrapper(&'static dyn Context),
+    /// Context named at runtime, e.g. by an application on top of the wrappers.
+    Named(Cow<'static, str>),
+}
+
+impl ErrorContext {
+    pub fn name(&self) -> &str {
+        match self {
+            ErrorContext::Wrapper(context) => context.name(),
+            ErrorContext::Named(name) => name.as_ref(),
+        }
+    }
+}
+
+impl<C: Context> From<&'static C> for ErrorContext {
+    fn from(context: &'static C) -> Self {
+        ErrorContext::Wrapper(context)
+    }
+}
+
+impl From<&'static dyn Context> for ErrorContext {
+    fn from(context: &'static dyn Context) -> Self {
+        ErrorContext::Wrapper(context)
+    }
+}
+
+impl From<&'static str> for ErrorContext {
+    fn from(name: &'static str) -> Self {
+        ErrorContext::Named(Cow::Borrowed(name))
+    }
+}
+
+impl From<String> for ErrorContext {
+    fn from(name: String) -> Self {
+        ErrorContext::Named(Cow::Owned(name))
+    }
+}
+
+impl Display for ErrorContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 /// List of the error numbers used in the Zephyr APIs.
 ///
+/// The variants follow the numbering of the errno table of Zephyr
+/// (`lib/libc/minimal/include/errno.h`), the comment of each variant names its C constant.
+/// `EWOULDBLOCK` is the same number as `EAGAIN` and `ENOTSUP` is distinct from `EOPNOTSUPP` in
+/// Zephyr. Numbers missing in the table are kept in [ErrorNumber::Other]. With the `native-libc`
+/// feature, the numbers converted from and to `i32` are those of Linux, see [native_sim].
+///
 /// Zephyr also uses negative numbers for error numbers. That is why some error numbers occur positive
 /// and negative. Negative error numbers are prefixed with a capital `N`.
 #[repr(i32)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum ErrorNumber {
+    /// `EPERM`
     Permission = 1,
+    /// `ENOENT`
+    NoEntry = 2,
+    /// `ESRCH`
+    NoContext = 3,
+    /// `EINTR`
+    Interrupted = 4,
+    /// `EIO`
+    Io = 5,
+    /// `ENXIO`
+    NoDeviceOrAddress = 6,
+    /// `E2BIG`
+    ArgumentListTooLong = 7,
+    /// `ENOEXEC`
+    ExecFormat = 8,
+    /// `EBADF`
+    BadFileNumber = 9,
+    /// `ECHILD`
+    NoChildren = 10,
+    /// `EAGAIN`
+    TryAgain = 11,
+    /// `ENOMEM`
+    NoMemory = 12,
+    /// `EACCES`
+    AccessDenied = 13,
+    /// `EFAULT`
+    BadAddress = 14,
+    /// `ENOTBLK`
+    NotBlockDevice = 15,
+    /// `EBUSY`
+    Busy = 16,
+    /// `EEXIST`
+    Exists = 17,
+    /// `EXDEV`
+    CrossDeviceLink = 18,
+    /// `ENODEV`
+    NoDevice = 19,
+    /// `ENOTDIR`
+    NotDirectory = 20,
+    /// `EISDIR`
+    IsDirectory = 21,
+    /// `EINVAL`
+    InvalidArgument = 22,
+    /// `ENFILE`
+    FileTableOverflow = 23,
+    /// `EMFILE`
+    TooManyOpenFiles = 24,
+    /// `ENOTTY`
+    NotTerminal = 25,
+    /// `ETXTBSY`
+    TextFileBusy = 26,
+    /// `EFBIG`
+    FileTooLarge = 27,
+    /// `ENOSPC`
+    NoSpace = 28,
+    /// `ESPIPE`
+    IllegalSeek = 29,
+    /// `EROFS`
+    ReadOnly = 30,
+    /// `EMLINK`
+    TooManyLinks = 31,
+    /// `EPIPE`
+    BrokenPipe = 32,
+    /// `EDOM`
+    Domain = 33,
+    /// `ERANGE`
+    Range = 34,
+    /// `ENOMSG`
+    NoMessage = 35,
+    /// `EDEADLK`
+    Deadlock = 45,
+    /// `ENOLCK`
+    NoLocks = 46,
+    /// `ENOSTR`
+    NotStream = 60,
+    /// `ENODATA`
+    NoData = 61,
+    /// `ETIME`
+    StreamTimeout = 62,
+    /// `ENOSR`
+    NoStreamResources = 63,
+    /// `EPROTO`
+    Protocol = 71,
+    /// `EBADMSG`
+    BadMessage = 77,
+    /// `ENOSYS`
     NotImplemented = 88,
+    /// `ENOTEMPTY`
+    NotEmpty = 90,
+    /// `ENAMETOOLONG`
+    NameTooLong = 91,
+    /// `ELOOP`
+    SymlinkLoop = 92,
+    /// `EOPNOTSUPP`
+    OperationNotSupported = 95,
+    /// `EPFNOSUPPORT`
+    ProtocolFamilyNotSupported = 96,
+    /// `ECONNRESET`
+    ConnectionReset = 104,
+    /// `ENOBUFS`
+    NoBuffers = 105,
+    /// `EAFNOSUPPORT`
+    AddressFamilyNotSupported = 106,
+    /// `EPROTOTYPE`
+    WrongProtocolType = 107,
+    /// `ENOTSOCK`
+    NotSocket = 108,
+    /// `ENOPROTOOPT`
+    ProtocolNotAvailable = 109,
+    /// `ESHUTDOWN`
+    Shutdown = 110,
+    /// `ECONNREFUSED`
+    ConnectionRefused = 111,
+    /// `EADDRINUSE`
+    AddressInUse = 112,
+    /// `ECONNABORTED`
+    ConnectionAborted = 113,
+    /// `ENETUNREACH`
+    NetworkUnreachable = 114,
+    /// `ENETDOWN`
+    NetworkDown = 115,
+    /// `ETIMEDOUT`
+    TimedOut = 116,
+    /// `EHOSTDOWN`
+    HostDown = 117,
+    /// `EHOSTUNREACH`
+    HostUnreachable = 118,
+    /// `EINPROGRESS`
+    InProgress = 119,
+    /// `EALREADY`
+    Already = 120,
+    /// `EDESTADDRREQ`
+    DestinationAddressRequired = 121,
+    /// `EMSGSIZE`
+    MessageSize = 122,
+    /// `EPROTONOSUPPORT`
+    ProtocolNotSupported = 123,
+    /// `ESOCKTNOSUPPORT`
+    SocketTypeNotSupported = 124,
+    /// `EADDRNOTAVAIL`
+    AddressNotAvailable = 125,
+    /// `ENETRESET`
+    NetworkReset = 126,
+    /// `EISCONN`
+    IsConnected = 127,
+    /// `ENOTCONN`
     NotConnected = 128,
+    /// `ETOOMANYREFS`
+    TooManyReferences = 129,
+    /// `ENOTSUP`
+    NotSupported = 134,
+    /// `EILSEQ`
+    IllegalByteSequence = 138,
+    /// `EOVERFLOW`
+    Overflow = 139,
+    /// `ECANCELED`
+    Canceled = 140,
     Other(i32),
 }
 
 impl From<i32> for ErrorNumber {
     fn from(errno: i32) -> Self {
+        #[cfg(feature = "native-libc")]
+        let errno = match native_sim::errno_from_libc(errno) {
+            Some(errno) => errno,
+            None => return ErrorNumber::Other(errno.abs()),
+        };
+
         match errno {
-            1 => ErrorNumber::Permission,
+            1 | -1 => ErrorNumber::Permission,
+            2 | -2 => ErrorNumber::NoEntry,
+            3 | -3 => ErrorNumber::NoContext,
+            4 | -4 => ErrorNumber::Interrupted,
+            5 | -5 => ErrorNumber::Io,
+            6 | -6 => ErrorNumber::NoDeviceOrAddress,
+            7 | -7 => ErrorNumber::ArgumentListTooLong,
+            8 | -8 => ErrorNumber::ExecFormat,
+            9 | -9 => ErrorNumber::BadFileNumber,
+            10 | -10 => ErrorNumber::NoChildren,
+            11 | -11 => ErrorNumber::TryAgain,
+            12 | -12 => ErrorNumber::NoMemory,
+            13 | -13 => ErrorNumber::AccessDenied,
+            14 | -14 => ErrorNumber::BadAddress,
+            15 | -15 => ErrorNumber::NotBlockDevice,
+            16 | -16 => ErrorNumber::Busy,
+            17 | -17 => ErrorNumber::Exists,
+            18 | -18 => ErrorNumber::CrossDeviceLink,
+            19 | -19 => ErrorNumber::NoDevice,
+            20 | -20 => ErrorNumber::NotDirectory,
+            21 | -21 => ErrorNumber::IsDirectory,
+            22 | -22 => ErrorNumber::InvalidArgument,
+            23 | -23 => ErrorNumber::FileTableOverflow,
+            24 | -24 => ErrorNumber::TooManyOpenFiles,
+            25 | -25 => ErrorNumber::NotTerminal,
+            26 | -26 => ErrorNumber::TextFileBusy,
+            27 | -27 => ErrorNumber::FileTooLarge,
+            28 | -28 => ErrorNumber::NoSpace,
+            29 | -29 => ErrorNumber::IllegalSeek,
+            30 | -30 => ErrorNumber::ReadOnly,
+            31 | -31 => ErrorNumber::TooManyLinks,
+            32 | -32 => ErrorNumber::BrokenPipe,
+            33 | -33 => ErrorNumber::Domain,
+            34 | -34 => ErrorNumber::Range,
+            35 | -35 => ErrorNumber::NoMessage,
+            45 | -45 => ErrorNumber::Deadlock,
+            46 | -46 => ErrorNumber::NoLocks,
+            60 | -60 => ErrorNumber::NotStream,
+            61 | -61 => ErrorNumber::NoData,
+            62 | -62 => ErrorNumber::StreamTimeout,
+            63 | -63 => ErrorNumber::NoStreamResources,
+            71 | -71 => ErrorNumber::Protocol,
+            77 | -77 => ErrorNumber::BadMessage,
             88 | -88 => ErrorNumber::NotImplemented,
+            90 | -90 => ErrorNumber::NotEmpty,
+            91 | -91 => ErrorNumber::NameTooLong,
+            92 | -92 => ErrorNumber::SymlinkLoop,
+            95 | -95 => ErrorNumber::OperationNotSupported,
+            96 | -96 => ErrorNumber::ProtocolFamilyNotSupported,
+            104 | -104 => ErrorNumber::ConnectionReset,
+            105 | -105 => ErrorNumber::NoBuffers,
+            106 | -106 => ErrorNumber::AddressFamilyNotSupported,
+            107 | -107 => ErrorNumber::WrongProtocolType,
+            108 | -108 => ErrorNumber::NotSocket,
+            109 | -109 => ErrorNumber::ProtocolNotAvailable,
+            110 | -110 => ErrorNumber::Shutdown,
+            111 | -111 => ErrorNumber::ConnectionRefused,
+            112 | -112 => ErrorNumber::AddressInUse,
+            113 | -113 => ErrorNumber::ConnectionAborted,
+            114 | -114 => ErrorNumber::NetworkUnreachable,
+            115 | -115 => ErrorNumber::NetworkDown,
+            116 | -116 => ErrorNumber::TimedOut,
+            117 | -117 => ErrorNumber::HostDown,
+            118 | -118 => ErrorNumber::HostUnreachable,
+            119 | -119 => ErrorNumber::InProgress,
+            120 | -120 => ErrorNumber::Already,
+            121 | -121 => ErrorNumber::DestinationAddressRequired,
+            122 | -122 => ErrorNumber::MessageSize,
+            123 | -123 => ErrorNumber::ProtocolNotSupported,
+            124 | -124 => ErrorNumber::SocketTypeNotSupported,
+            125 | -125 => ErrorNumber::AddressNotAvailable,
+            126 | -126 => ErrorNumber::NetworkReset,
+            127 | -127 => ErrorNumber::IsConnected,
             128 | -128 => ErrorNumber::NotConnected,
+            129 | -129 => ErrorNumber::TooManyReferences,
+            134 | -134 => ErrorNumber::NotSupported,
+            138 | -138 => ErrorNumber::IllegalByteSequence,
+            139 | -139 => ErrorNumber::Overflow,
+            140 | -140 => ErrorNumber::Canceled,
             errno => ErrorNumber::Other(errno.abs()),
         }
     }
 }
+impl From<ErrorNumber> for i32 {
+    /// Converts to the positive error number, negate it for return values of C callbacks.
+    fn from(errno: ErrorNumber) -> Self {
+        match errno {
+            ErrorNumber::Other(errno) => errno.abs(),
+            // the enum is `repr(i32)`, so the discriminant is stored in the first `i32`
+            #[cfg(not(feature = "native-libc"))]
+            errno => unsafe { *(&errno as *const ErrorNumber as *const i32) },
+            #[cfg(feature = "native-libc")]
+            errno => {
+                native_sim::errno_to_libc(unsafe { *(&errno as *const ErrorNumber as *const i32) })
+            }
+        }
+    }
+}
+
+impl ErrorNumber {
+    /// Description of the error number as returned by `strerror` of Zephyr, `None` for
+    /// [ErrorNumber::Other].
+    pub fn description(&self) -> Option<&'static str> {
+        let description = match self {
+            ErrorNumber::Permission => "Not owner",
+            ErrorNumber::NoEntry => "No such file or directory",
+            ErrorNumber::NoContext => "No such context",
+            ErrorNumber::Interrupted => "Interrupted system call",
+            ErrorNumber::Io => "I/O error",
+            ErrorNumber::NoDeviceOrAddress => "No such device or address",
+            ErrorNumber::ArgumentListTooLong => "Arg list too long",
+            ErrorNumber::ExecFormat => "Exec format error",
+            ErrorNumber::BadFileNumber => "Bad file number",
+            ErrorNumber::NoChildren => "No children",
+            ErrorNumber::TryAgain => "No more contexts",
+            ErrorNumber::NoMemory => "Not enough core",
+            ErrorNumber::AccessDenied => "Permission denied",
+            ErrorNumber::BadAddress => "Bad address",
+            ErrorNumber::NotBlockDevice => "Block device required",
+            ErrorNumber::Busy => "Mount device busy",
+            ErrorNumber::Exists => "File exists",
+            ErrorNumber::CrossDeviceLink => "Cross-device link",
+            ErrorNumber::NoDevice => "No such device",
+            ErrorNumber::NotDirectory => "Not a directory",
+            ErrorNumber::IsDirectory => "Is a directory",
+            ErrorNumber::InvalidArgument => "Invalid argument",
+            ErrorNumber::FileTableOverflow => "File table overflow",
+            ErrorNumber::TooManyOpenFiles => "Too many open files",
+            ErrorNumber::NotTerminal => "Not a typewriter",
+            ErrorNumber::TextFileBusy => "Text file busy",
+            ErrorNumber::FileTooLarge => "File too large",
+            ErrorNumber::NoSpace => "No space left on device",
+            ErrorNumber::IllegalSeek => "Illegal seek",
+            ErrorNumber::ReadOnly => "Read-only file system",
+            ErrorNumber::TooManyLinks => "Too many links",
+            ErrorNumber::BrokenPipe => "Broken pipe",
+            ErrorNumber::Domain => "Argument too large",
+            ErrorNumber::Range => "Result too large",
+            ErrorNumber::NoMessage => "Unexpected message type",
+            ErrorNumber::Deadlock => "Resource deadlock avoided",
+            ErrorNumber::NoLocks => "No locks available",
+            ErrorNumber::NotStream => "STREAMS device required",
+            ErrorNumber::NoData => "Missing expected message data",
+            ErrorNumber::StreamTimeout => "STREAMS timeout occurred",
+            ErrorNumber::NoStreamResources => "Insufficient memory",
+            ErrorNumber::Protocol => "Generic STREAMS error",
+            ErrorNumber::BadMessage => "Invalid STREAMS message",
+            ErrorNumber::NotImplemented => "Function not implemented",
+            ErrorNumber::NotEmpty => "Directory not empty",
+            ErrorNumber::NameTooLong => "File name too long",
+            ErrorNumber::SymlinkLoop => "Too many levels of symbolic links",
+            ErrorNumber::OperationNotSupported => "Operation not supported on socket",
+            ErrorNumber::ProtocolFamilyNotSupported => "Protocol family not supported",
+            ErrorNumber::ConnectionReset => "Connection reset by peer",
+            ErrorNumber::NoBuffers => "No buffer space available",
+            ErrorNumber::AddressFamilyNotSupported => "Addr family not supported",
+            ErrorNumber::WrongProtocolType => "Protocol wrong type for socket",
+            ErrorNumber::NotSocket => "Socket operation on non-socket",
+            ErrorNumber::ProtocolNotAvailable => "Protocol not available",
+            ErrorNumber::Shutdown => "Can't send after socket shutdown",
+            ErrorNumber::ConnectionRefused => "Connection refused",
+            ErrorNumber::AddressInUse => "Address already in use",
+            ErrorNumber::ConnectionAborted => "Software caused connection abort",
+            ErrorNumber::NetworkUnreachable => "Network is unreachable",
+            ErrorNumber::NetworkDown => "Network is down",
+            ErrorNumber::TimedOut => "Connection timed out",
+            ErrorNumber::HostDown => "Host is down",
+            ErrorNumber::HostUnreachable => "No route to host",
+            ErrorNumber::InProgress => "Operation now in progress",
+            ErrorNumber::Already => "Operation already in progress",
+            ErrorNumber::DestinationAddressRequired => "Destination address required",
+            ErrorNumber::MessageSize => "Message size",
+            ErrorNumber::ProtocolNotSupported => "Protocol not supported",
+            ErrorNumber::SocketTypeNotSupported => "Socket type not supported",
+            ErrorNumber::AddressNotAvailable => "Can't assign requested address",
+            ErrorNumber::NetworkReset => "Network dropped connection on reset",
+            ErrorNumber::IsConnected => "Socket is already connected",
+            ErrorNumber::NotConnected => "Socket is not connected",
+            ErrorNumber::TooManyReferences => "Too many references: can't splice",
+            ErrorNumber::NotSupported => "Unsupported value",
+            ErrorNumber::IllegalByteSequence => "Illegal byte sequence",
+            ErrorNumber::Overflow => "Value overflow",
+            ErrorNumber::Canceled => "Operation canceled",
+            ErrorNumber::Other(_) => return None,
+        };
+        Some(description)
+    }
+
+    /// Whether an operation failed as it would have blocked, i.e. `EAGAIN` (`EWOULDBLOCK`).
+    ///
+    /// Non-blocking sockets and the kernel objects polled with [time::NO_WAIT] fail this way.
+    pub fn is_would_block(&self) -> bool {
+        *self == ErrorNumber::TryAgain
+    }
+
+    /// Whether an operation failed as its timeout expired, i.e. `ETIMEDOUT` or `ETIME`.
+    ///
+    /// The kernel objects report an expired timeout with `EAGAIN`, use
+    /// [ErrorNumber::is_would_block] for them.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, ErrorNumber::TimedOut | ErrorNumber::StreamTimeout)
+    }
+}
 
 impl Display for ErrorNumber {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ErrorNumber::Permission => {
-                write!(f, "1: Not owner")
-            }
-            ErrorNumber::Other(errno) => {
-                write!(f, "Unknown error number: {}", errno)
-            }
-            ErrorNumber::NotImplemented => {
-                write!(f, "88: Function not implemented")
-            }
-            ErrorNumber::NotConnected => {
-                write!(f, "128: Not connected")
-            }
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.description() {
+            Some(description) => write!(f, "{}: {}", i32::from(*self), description),
+            None => write!(f, "Unknown error number: {}", i32::from(*self)),
         }
     }
 }
@@ -66,10 +524,17 @@ impl Display for ErrorNumber {
 /// Error that might occur in the Zephyr API. Such errors might also occur in the wrapper implementations.
 ///
 /// Errors generated by the wrapper functions will never have a negative error number.
+///
+/// Besides the error number, an error records the [ErrorContext] it occurred in, the operation that
+/// failed and the error that caused it, which is returned by [Error::source]. The error displays
+/// as e.g. `i2c wrapper: write_reg(0x6B) failed: 5: I/O error`, the alternate form (`{:#}`) also
+/// appends the chain of sources.
 #[derive(Debug)]
 pub struct ZephyrError {
     errno: ErrorNumber,
-    context: Option<&'static dyn Context>,
+    context: Option<ErrorContext>,
+    operation: Option<Cow<'static, str>>,
+    source: Option<Box<dyn Error + Send + Sync>>,
 }
 
 impl ZephyrError {
@@ -78,47 +543,143 @@ impl ZephyrError {
         Self {
             errno,
             context: None,
+            operation: None,
+            source: None,
         }
     }
 
     /// Create a new error with the given context
-    pub fn new_with_context(errno: ErrorNumber, context: &'static dyn Context) -> Self {
-        Self {
-            errno,
-            context: Some(context),
-        }
+    pub fn new_with_context(errno: ErrorNumber, context: impl Into<ErrorContext>) -> Self {
+        Self::new(errno).with_context(context)
     }
 
     /// Convert `errno` into a [ErrorNumber] variant and construct a new error
     pub fn from_errno(errno: i32) -> Self {
-        Self {
-            errno: errno.into(),
-            context: None,
-        }
+        Self::new(errno.into())
     }
 
     /// Convert `errno` into a [ErrorNumber] variant and construct a new error with the given context
-    pub fn from_errno_with_context(errno: i32, context: &'static dyn Context) -> Self {
-        Self {
-            errno: errno.into(),
-            context: Some(context),
-        }
+    pub fn from_errno_with_context(errno: i32, context: impl Into<ErrorContext>) -> Self {
+        Self::new(errno.into()).with_context(context)
+    }
+
+    /// Replaces the context of the error.
+    pub fn with_context(mut self, context: impl Into<ErrorContext>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// Records the operation that failed, e.g. the called function and its arguments.
+    pub fn with_operation(mut self, operation: impl Into<Cow<'static, str>>) -> Self {
+        self.operation = Some(operation.into());
+        self
+    }
+
+    /// Records the error that caused this error.
+    pub fn with_source(mut self, source: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        self.source = Some(source.into());
+        self
     }
 
     pub fn number(&self) -> ErrorNumber {
         self.errno
     }
+
+    pub fn context(&self) -> Option<&ErrorContext> {
+        self.context.as_ref()
+    }
+
+    pub fn operation(&self) -> Option<&str> {
+        self.operation.as_deref()
+    }
+
+    /// Shorthand for [ErrorNumber::is_would_block] of the error number.
+    pub fn is_would_block(&self) -> bool {
+        self.errno.is_would_block()
+    }
+
+    /// Shorthand for [ErrorNumber::is_timeout] of the error number.
+    pub fn is_timeout(&self) -> bool {
+        self.errno.is_timeout()
+    }
 }
 
 impl Display for ZephyrError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if let Some(context) = self.context {
-            write!(f, "[{}]: ", context.name())?;
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if let Some(context) = &self.context {
+            write!(f, "{}: ", context)?;
+        }
+        if let Some(operation) = &self.operation {
+            write!(f, "{} failed: ", operation)?;
         }
-        write!(f, "{}", self.errno)
+        write!(f, "{}", self.errno)?;
+        if f.alternate() {
+            let mut source = self.source();
+            while let Some(error) = source {
+                write!(f, ": {}", error)?;
+                source = error.source();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Error for ZephyrError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| source.as_ref() as &(dyn Error + 'static))
     }
 }
 
-impl Error for ZephyrError {}
+/// Kinds of the errors for the [embedded_io] traits, e.g. of [uart::Uart].
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Error for ZephyrError {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        use embedded_io::ErrorKind;
+
+        match self.number() {
+            errno if errno.is_timeout() => ErrorKind::TimedOut,
+            ErrorNumber::Permission | ErrorNumber::AccessDenied => ErrorKind::PermissionDenied,
+            ErrorNumber::NoEntry => ErrorKind::NotFound,
+            ErrorNumber::Exists => ErrorKind::AlreadyExists,
+            ErrorNumber::Interrupted => ErrorKind::Interrupted,
+            ErrorNumber::NoMemory => ErrorKind::OutOfMemory,
+            ErrorNumber::InvalidArgument => ErrorKind::InvalidInput,
+            ErrorNumber::BadMessage | ErrorNumber::IllegalByteSequence => ErrorKind::InvalidData,
+            ErrorNumber::NotImplemented
+            | ErrorNumber::NotSupported
+            | ErrorNumber::OperationNotSupported => ErrorKind::Unsupported,
+            ErrorNumber::NotConnected => ErrorKind::NotConnected,
+            ErrorNumber::ConnectionRefused => ErrorKind::ConnectionRefused,
+            ErrorNumber::ConnectionReset => ErrorKind::ConnectionReset,
+            ErrorNumber::ConnectionAborted => ErrorKind::ConnectionAborted,
+            ErrorNumber::AddressInUse => ErrorKind::AddrInUse,
+            ErrorNumber::AddressNotAvailable => ErrorKind::AddrNotAvailable,
+            ErrorNumber::BrokenPipe => ErrorKind::BrokenPipe,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+/// Extension of [ZephyrResult] to record the failed operation in its error.
+pub trait ResultExt<T> {
+    /// Records the operation returned by `operation` if the result is an error. `operation` is only
+    /// called on errors, so it may format its arguments.
+    fn with_operation<O, F>(self, operation: F) -> ZephyrResult<T>
+    where
+        O: Into<Cow<'static, str>>,
+        F: FnOnce() -> O;
+}
+
+impl<T> ResultExt<T> for ZephyrResult<T> {
+    fn with_operation<O, F>(self, operation: F) -> ZephyrResult<T>
+    where
+        O: Into<Cow<'static, str>>,
+        F: FnOnce() -> O,
+    {
+        self.map_err(|error| error.with_operation(operation()))
+    }
+}
 
 pub type ZephyrResult<T> = Result<T, ZephyrError>;
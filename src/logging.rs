@@ -0,0 +1,194 @@
+//! Messages for the logging subsystem of Zephyr, requires `CONFIG_LOG` without
+//! `CONFIG_LOG_MODE_MINIMAL`.
+//!
+//! With the `log` feature, this module is the backend of the `log` crate:
+//!
+//! ```no_run
+//! zephyr_rust_wrappers::logging::init(log::LevelFilter::Info).unwrap();
+//! log::info!("booted");
+//! ```
+//!
+//! Records are formatted in Rust and logged as the `%s` argument of a message. Zephyr copies the
+//! string into the message, so the backend is also safe in deferred mode
+//! (`CONFIG_LOG_MODE_DEFERRED`) and from interrupts. Messages longer than [MAX_MESSAGE_LEN] bytes
+//! are truncated.
+//!
+//! The levels `Error`, `Warn`, `Info` and `Debug` map to the levels of the same name of Zephyr,
+//! `Trace` is logged as `Debug`. Without a source, the target of the record (usually its module
+//! path) prefixes the message. [ZephyrLogger::with_source] creates a logger for a module registered
+//! in C with `LOG_MODULE_REGISTER`, whose messages are shown and filtered like those of the module.
+
+use core::ffi::{c_char, c_void};
+use core::fmt::{Arguments, Write};
+#[cfg(feature = "log")]
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use zephyr_sys::raw::log_msg_desc as ZLogMsgDesc;
+
+/// Longest message in bytes, including the prefix with the target.
+pub const MAX_MESSAGE_LEN: usize = 255;
+
+/// Equivalent of `CBPRINTF_PACKAGE_ADD_RW_STR_POS`, the macro is not available in the bindings.
+const CBPRINTF_PACKAGE_ADD_RW_STR_POS: u32 = 1 << 2;
+
+/// Format of all messages, the message is formatted in Rust already.
+const FORMAT: &[u8] = b"%s\0";
+
+/// Package of the format and its argument, aligned for `cbprintf_package`.
+type Package = [u64; 8];
+
+/// Logger forwarding the records of the `log` crate to Zephyr.
+#[cfg(feature = "log")]
+pub struct ZephyrLogger {
+    source: *const c_void,
+}
+
+// the source is a constant of the logging subsystem
+#[cfg(feature = "log")]
+unsafe impl Send for ZephyrLogger {}
+#[cfg(feature = "log")]
+unsafe impl Sync for ZephyrLogger {}
+
+#[cfg(feature = "log")]
+static LOGGER: ZephyrLogger = ZephyrLogger::new();
+
+#[cfg(feature = "log")]
+impl ZephyrLogger {
+    /// Creates a logger without a source, the target of a record prefixes its message.
+    pub const fn new() -> Self {
+        ZephyrLogger {
+            source: core::ptr::null(),
+        }
+    }
+
+    /// Creates a logger whose messages belong to the module `name` registered in C with
+    /// `LOG_MODULE_REGISTER(name)`.
+    ///
+    /// `source` MUST point to the `log_const_<name>` constant of the module, or to
+    /// `log_dynamic_<name>` with `CONFIG_LOG_RUNTIME_FILTERING`. Both are declared in Rust with
+    /// `extern "C"` and obtained with `core::ptr::addr_of!`.
+    pub const unsafe fn with_source(source: *const c_void) -> Self {
+        ZephyrLogger { source }
+    }
+}
+
+#[cfg(feature = "log")]
+impl Default for ZephyrLogger {
+    fn default() -> Self {
+        ZephyrLogger::new()
+    }
+}
+
+#[cfg(feature = "log")]
+impl Log for ZephyrLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let level = level(record.level());
+        if self.source.is_null() {
+            log_args(
+                self.source,
+                level,
+                format_args!("{}: {}", record.target(), record.args()),
+            );
+        } else {
+            log_args(self.source, level, *record.args());
+        }
+    }
+
+    fn flush(&self) {
+        // messages are processed by the logging thread of Zephyr
+    }
+}
+
+/// Installs a [ZephyrLogger] without a source as the logger of the `log` crate and sets the
+/// maximum level of the records.
+#[cfg(feature = "log")]
+pub fn init(max_level: LevelFilter) -> Result<(), SetLoggerError> {
+    log::set_logger(&LOGGER)?;
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+#[cfg(feature = "log")]
+fn level(level: Level) -> u8 {
+    match level {
+        Level::Error => zephyr_sys::raw::LOG_LEVEL_ERR as u8,
+        Level::Warn => zephyr_sys::raw::LOG_LEVEL_WRN as u8,
+        Level::Info => zephyr_sys::raw::LOG_LEVEL_INF as u8,
+        Level::Debug | Level::Trace => zephyr_sys::raw::LOG_LEVEL_DBG as u8,
+    }
+}
+
+/// Logs the message formatted from `args` with `level`, one of the `LOG_LEVEL_*` constants, for
+/// `source` (or no source if null).
+pub(crate) fn log_args(source: *const c_void, level: u8, args: Arguments<'_>) {
+    let mut message = MessageBuffer::new();
+    // a truncated message is still logged, the buffer never fails
+    let _ = message.write_fmt(args);
+    unsafe { log_string(source, level, message.as_ptr()) };
+}
+
+/// Creates a message of `level` logging `message` with the `%s` format, the equivalent of the
+/// `Z_LOG_MSG_CREATE` macro behind `LOG_ERR` and friends.
+///
+/// `message` MUST be nul terminated, it is only read during the call.
+unsafe fn log_string(source: *const c_void, level: u8, message: *const c_char) {
+    let mut package: Package = [0; 8];
+    // the position of the message is recorded, so the message string is copied when it is created
+    let len = zephyr_sys::raw::cbprintf_package(
+        package.as_mut_ptr() as *mut c_void,
+        core::mem::size_of::<Package>(),
+        CBPRINTF_PACKAGE_ADD_RW_STR_POS,
+        FORMAT.as_ptr() as *const c_char,
+        message,
+    );
+    if len < 0 {
+        return;
+    }
+
+    // zeroed as the descriptor is a bit field, only the set fields are used
+    let mut desc: ZLogMsgDesc = core::mem::zeroed();
+    desc.set_level(level as u32);
+    desc.set_package_len(len as u32);
+    zephyr_sys::syscalls::any::z_log_msg_static_create(
+        source,
+        desc,
+        package.as_mut_ptr() as *mut u8,
+        core::ptr::null(),
+    );
+}
+
+/// Nul terminated buffer of a message that cuts what does not fit.
+struct MessageBuffer {
+    buffer: [u8; MAX_MESSAGE_LEN + 1],
+    len: usize,
+}
+
+impl MessageBuffer {
+    fn new() -> Self {
+        MessageBuffer {
+            buffer: [0; MAX_MESSAGE_LEN + 1],
+            len: 0,
+        }
+    }
+
+    fn as_ptr(&self) -> *const c_char {
+        // the last byte is never written, so the message is always terminated
+        self.buffer.as_ptr() as *const c_char
+    }
+}
+
+impl Write for MessageBuffer {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let len = s.len().min(MAX_MESSAGE_LEN - self.len);
+        self.buffer[self.len..self.len + len].copy_from_slice(&s.as_bytes()[..len]);
+        self.len += len;
+        Ok(())
+    }
+}
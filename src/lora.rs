@@ -0,0 +1,317 @@
+//! Wrappers for the Zephyr LoRa API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! The LoRa API is inlined in the Zephyr headers, so this module calls the driver API directly.
+//! A modem is either configured for transmitting or for receiving, [Lora::send] and
+//! [Lora::recv] require the matching [Lora::configure] first:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::lora::{Bandwidth, CodingRate, Lora, ModemConfig, SpreadingFactor};
+//!# use zephyr_rust_wrappers::time::FOREVER;
+//!# fn example(device: &'static zephyr_rust_wrappers::lora::Device) {
+//! let mut lora = unsafe { Lora::new(device) };
+//! let config = ModemConfig::new(868_100_000, Bandwidth::Khz125, SpreadingFactor::Sf7);
+//! lora.configure(&config, false).unwrap();
+//! let mut buf = [0u8; 255];
+//! let packet = lora.recv(&mut buf, FOREVER).unwrap();
+//! println!("{:?} rssi {} snr {}", &buf[..packet.len], packet.rssi, packet.snr);
+//!# }
+//! ```
+
+use crate::device::{class, TypedDevice};
+use crate::handlers::Handlers;
+use crate::time::Timeout;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use core::ffi::{c_int, c_void};
+pub use zephyr::device::Device;
+use zephyr_sys::raw::{lora_driver_api as LoraDriverApi, lora_modem_config as ZModemConfig};
+
+const CONTEXT: LoraWrapperContext = LoraWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+fn not_implemented() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::NotImplemented, &CONTEXT)
+}
+
+/// Bandwidth of the signal.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Bandwidth {
+    Khz125,
+    Khz250,
+    Khz500,
+}
+
+impl Bandwidth {
+    fn to_raw(self) -> zephyr_sys::raw::lora_signal_bandwidth {
+        match self {
+            Bandwidth::Khz125 => zephyr_sys::raw::lora_signal_bandwidth_BW_125_KHZ,
+            Bandwidth::Khz250 => zephyr_sys::raw::lora_signal_bandwidth_BW_250_KHZ,
+            Bandwidth::Khz500 => zephyr_sys::raw::lora_signal_bandwidth_BW_500_KHZ,
+        }
+    }
+}
+
+/// Spreading factor, the data rate of the modem.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SpreadingFactor {
+    Sf6,
+    Sf7,
+    Sf8,
+    Sf9,
+    Sf10,
+    Sf11,
+    Sf12,
+}
+
+impl SpreadingFactor {
+    fn to_raw(self) -> zephyr_sys::raw::lora_datarate {
+        match self {
+            SpreadingFactor::Sf6 => zephyr_sys::raw::lora_datarate_SF_6,
+            SpreadingFactor::Sf7 => zephyr_sys::raw::lora_datarate_SF_7,
+            SpreadingFactor::Sf8 => zephyr_sys::raw::lora_datarate_SF_8,
+            SpreadingFactor::Sf9 => zephyr_sys::raw::lora_datarate_SF_9,
+            SpreadingFactor::Sf10 => zephyr_sys::raw::lora_datarate_SF_10,
+            SpreadingFactor::Sf11 => zephyr_sys::raw::lora_datarate_SF_11,
+            SpreadingFactor::Sf12 => zephyr_sys::raw::lora_datarate_SF_12,
+        }
+    }
+}
+
+/// Coding rate of the forward error correction.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CodingRate {
+    Cr4_5,
+    Cr4_6,
+    Cr4_7,
+    Cr4_8,
+}
+
+impl CodingRate {
+    fn to_raw(self) -> zephyr_sys::raw::lora_coding_rate {
+        match self {
+            CodingRate::Cr4_5 => zephyr_sys::raw::lora_coding_rate_CR_4_5,
+            CodingRate::Cr4_6 => zephyr_sys::raw::lora_coding_rate_CR_4_6,
+            CodingRate::Cr4_7 => zephyr_sys::raw::lora_coding_rate_CR_4_7,
+            CodingRate::Cr4_8 => zephyr_sys::raw::lora_coding_rate_CR_4_8,
+        }
+    }
+}
+
+/// Configuration of the modem, the equivalent of `struct lora_modem_config`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ModemConfig {
+    /// Frequency in Hz.
+    pub frequency: u32,
+    pub bandwidth: Bandwidth,
+    pub spreading_factor: SpreadingFactor,
+    pub coding_rate: CodingRate,
+    /// Length of the preamble in symbols.
+    pub preamble_len: u16,
+    /// Transmit power in dBm.
+    pub tx_power: i8,
+    /// Invert the I and Q signals, e.g. for downlinks of LoRaWAN gateways.
+    pub iq_inverted: bool,
+    /// Use the sync word of public (LoRaWAN) networks instead of the private one.
+    pub public_network: bool,
+}
+
+impl ModemConfig {
+    /// Configuration with coding rate 4/5, a preamble of 8 symbols, 14 dBm and the private sync
+    /// word.
+    pub fn new(frequency: u32, bandwidth: Bandwidth, spreading_factor: SpreadingFactor) -> Self {
+        ModemConfig {
+            frequency,
+            bandwidth,
+            spreading_factor,
+            coding_rate: CodingRate::Cr4_5,
+            preamble_len: 8,
+            tx_power: 14,
+            iq_inverted: false,
+            public_network: false,
+        }
+    }
+
+    fn to_raw(&self, tx: bool) -> ZModemConfig {
+        // zeroed for the fields of newer Zephyr versions, which are disabled when zero
+        let mut raw: ZModemConfig = unsafe { core::mem::zeroed() };
+        raw.frequency = self.frequency;
+        raw.bandwidth = self.bandwidth.to_raw();
+        raw.datarate = self.spreading_factor.to_raw();
+        raw.coding_rate = self.coding_rate.to_raw();
+        raw.preamble_len = self.preamble_len;
+        raw.tx_power = self.tx_power;
+        raw.tx = tx;
+        raw.iq_inverted = self.iq_inverted;
+        raw.public_network = self.public_network;
+        raw
+    }
+}
+
+/// Metadata of a received packet.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Received {
+    /// Length of the packet in the buffer.
+    pub len: usize,
+    /// Received signal strength in dBm.
+    pub rssi: i16,
+    /// Signal to noise ratio in dB.
+    pub snr: i8,
+}
+
+/// Handler of [Lora::recv_async], called with the packet, the RSSI and the SNR.
+type RecvHandler = dyn FnMut(&[u8], i16, i8) + Send;
+
+/// Handlers of [Lora::recv_async] by the address of the modem.
+static RECV_HANDLERS: Handlers<usize, RecvHandler> = Handlers::new();
+
+/// C callback that dispatches a received packet to the closure of the entry in the user data.
+extern "C" fn recv_trampoline(
+    _dev: *const Device,
+    data: *mut u8,
+    size: u16,
+    rssi: i16,
+    snr: i8,
+    user_data: *mut c_void,
+) {
+    let data = if data.is_null() {
+        &[][..]
+    } else {
+        unsafe { core::slice::from_raw_parts(data, size as usize) }
+    };
+    RECV_HANDLERS.call_id(user_data as usize, |handler| handler(data, rssi, snr));
+}
+
+/// High level wrapper for a LoRa modem. Asynchronous reception is stopped when dropped.
+pub struct Lora {
+    device: &'static Device,
+    receiving: bool,
+}
+
+impl Lora {
+    /// Creates a new [Lora] for `device`.
+    ///
+    /// `device` MUST be a LoRa modem. If `device` is not a LoRa modem the behaviour when calling
+    /// any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Lora {
+            device,
+            receiving: false,
+        }
+    }
+
+    /// Creates a new [Lora] from a device checked to be a LoRa modem.
+    pub fn from_device(device: TypedDevice<class::Lora>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Lora::new(device.device()) }
+    }
+
+    fn api(&self) -> &'static LoraDriverApi {
+        // device is required to be a LoRa modem in constructor
+        unsafe { &*(self.device.api as *const LoraDriverApi) }
+    }
+
+    /// Configure the modem for transmitting if `tx` is set or for receiving otherwise. This is
+    /// the equivalent of `lora_config`.
+    pub fn configure(&mut self, config: &ModemConfig, tx: bool) -> ZephyrResult<()> {
+        let configure = self.api().config.ok_or_else(not_implemented)?;
+        let mut raw = config.to_raw(tx);
+        result(unsafe { configure(self.device, &mut raw) })
+    }
+
+    /// Send `data` and wait until it is sent, the modem MUST be configured for transmitting. This
+    /// is the equivalent of `lora_send`.
+    pub fn send(&mut self, data: &[u8]) -> ZephyrResult<()> {
+        let send = self.api().send.ok_or_else(not_implemented)?;
+        result(unsafe { send(self.device, data.as_ptr() as *mut u8, data.len() as u32) })
+    }
+
+    /// Receive a packet into `buf`, waiting up to `timeout`. The modem MUST be configured for
+    /// receiving. This is the equivalent of `lora_recv`.
+    ///
+    /// Packets larger than `buf` are truncated, LoRa packets have at most 255 bytes.
+    pub fn recv(&mut self, buf: &mut [u8], timeout: Timeout) -> ZephyrResult<Received> {
+        let recv = self.api().recv.ok_or_else(not_implemented)?;
+        let size = buf.len().min(u8::MAX as usize) as u8;
+        let mut rssi = 0;
+        let mut snr = 0;
+        let len =
+            unsafe { recv(self.device, buf.as_mut_ptr(), size, timeout, &mut rssi, &mut snr) };
+        if len < 0 {
+            return Err(ZephyrError::from_errno_with_context(len, &CONTEXT));
+        }
+        Ok(Received {
+            len: len as usize,
+            rssi,
+            snr,
+        })
+    }
+
+    /// Call `f` from the driver for every received packet until [Lora::stop_recv_async] or drop.
+    /// The modem MUST be configured for receiving. This is the equivalent of `lora_recv_async`.
+    pub fn recv_async<F>(&mut self, f: F) -> ZephyrResult<()>
+    where
+        F: FnMut(&[u8], i16, i8) + Send + 'static,
+    {
+        let recv_async = self.api().recv_async.ok_or_else(not_implemented)?;
+        let key = self.key();
+        // the driver may call the previous handler until it accepted the new one
+        let id = RECV_HANDLERS.push(key, Box::new(f)).id;
+        let res =
+            result(unsafe { recv_async(self.device, Some(recv_trampoline), id as *mut c_void) });
+        match res {
+            Ok(()) => RECV_HANDLERS.remove_others(id, |modem| *modem == key),
+            Err(_) => {
+                RECV_HANDLERS.remove_id(id);
+            }
+        }
+        res?;
+        self.receiving = true;
+        Ok(())
+    }
+
+    /// Stop the reception started with [Lora::recv_async].
+    ///
+    /// The handler is only dropped once the driver stopped calling it, it stays installed if
+    /// stopping fails.
+    pub fn stop_recv_async(&mut self) -> ZephyrResult<()> {
+        if !self.receiving {
+            return Ok(());
+        }
+        let recv_async = self.api().recv_async.ok_or_else(not_implemented)?;
+        result(unsafe { recv_async(self.device, None, core::ptr::null_mut()) })?;
+        let key = self.key();
+        RECV_HANDLERS.remove_where(|modem| *modem == key);
+        self.receiving = false;
+        Ok(())
+    }
+
+    fn key(&self) -> usize {
+        self.device as *const Device as usize
+    }
+}
+
+impl Drop for Lora {
+    fn drop(&mut self) {
+        // if stopping fails the handler is leaked in the table, as the driver may still call it
+        let _ = self.stop_recv_async();
+    }
+}
+
+#[derive(Debug)]
+struct LoraWrapperContext {}
+
+impl Context for LoraWrapperContext {
+    fn name(&self) -> &'static str {
+        "lora wrapper"
+    }
+}
@@ -0,0 +1,327 @@
+//! Wrappers for the LoRaWAN stack of Zephyr (`CONFIG_LORAWAN`).
+//!
+//! The stack is a single instance using the LoRa modem chosen in the devicetree, so this module
+//! offers free functions. A class A node selects its region, starts the stack, joins the network
+//! and sends uplinks, downlinks are delivered to the handlers of [on_downlink]:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::lorawan::{self, Eui, Join, Key, MessageType, Region};
+//! lorawan::set_region(Region::Eu868).unwrap();
+//! lorawan::start().unwrap();
+//! lorawan::on_downlink(None, |downlink| println!("port {}: {:?}", downlink.port, downlink.data));
+//! lorawan::join(&Join::Otaa {
+//!     dev_eui: Eui([0x00, 0x80, 0xe1, 0x15, 0x00, 0x0a, 0x99, 0x99]),
+//!     join_eui: Eui([0; 8]),
+//!     app_key: Key([0x2b; 16]),
+//!     nwk_key: Key([0x2b; 16]),
+//!     dev_nonce: 0,
+//! }).unwrap();
+//! lorawan::send(2, b"hello", MessageType::Unconfirmed).unwrap();
+//! ```
+
+use crate::handlers::Handlers;
+use crate::sys::SpinLocked;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use core::cell::UnsafeCell;
+use core::ffi::c_int;
+use zephyr_sys::raw::{lorawan_downlink_cb as ZDownlinkCallback, lorawan_join_config as ZJoinConfig};
+
+const CONTEXT: LorawanWrapperContext = LorawanWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Extended unique identifier, e.g. the DevEUI, in big endian byte order.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Eui(pub [u8; 8]);
+
+/// AES-128 root or session key.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct Key(pub [u8; 16]);
+
+impl core::fmt::Debug for Key {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // keys are secrets and not printed
+        f.write_str("Key(..)")
+    }
+}
+
+/// Activation of the device in the network.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Join {
+    /// Over-the-air activation, the session keys are derived from the root keys while joining.
+    Otaa {
+        dev_eui: Eui,
+        join_eui: Eui,
+        app_key: Key,
+        /// Equal to `app_key` for LoRaWAN 1.0.x networks.
+        nwk_key: Key,
+        /// Nonce of the first join request, must increase with each join of LoRaWAN 1.1.
+        dev_nonce: u16,
+    },
+    /// Activation by personalization with the session keys of the network server.
+    Abp {
+        dev_eui: Eui,
+        dev_addr: u32,
+        app_skey: Key,
+        nwk_skey: Key,
+        app_eui: Eui,
+    },
+}
+
+/// Region of the channel plan, the equivalent of `enum lorawan_region`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Region {
+    As923,
+    Au915,
+    Cn470,
+    Cn779,
+    Eu433,
+    Eu868,
+    Kr920,
+    In865,
+    Us915,
+    Ru864,
+}
+
+impl Region {
+    fn to_raw(self) -> zephyr_sys::raw::lorawan_region {
+        match self {
+            Region::As923 => zephyr_sys::raw::lorawan_region_LORAWAN_REGION_AS923,
+            Region::Au915 => zephyr_sys::raw::lorawan_region_LORAWAN_REGION_AU915,
+            Region::Cn470 => zephyr_sys::raw::lorawan_region_LORAWAN_REGION_CN470,
+            Region::Cn779 => zephyr_sys::raw::lorawan_region_LORAWAN_REGION_CN779,
+            Region::Eu433 => zephyr_sys::raw::lorawan_region_LORAWAN_REGION_EU433,
+            Region::Eu868 => zephyr_sys::raw::lorawan_region_LORAWAN_REGION_EU868,
+            Region::Kr920 => zephyr_sys::raw::lorawan_region_LORAWAN_REGION_KR920,
+            Region::In865 => zephyr_sys::raw::lorawan_region_LORAWAN_REGION_IN865,
+            Region::Us915 => zephyr_sys::raw::lorawan_region_LORAWAN_REGION_US915,
+            Region::Ru864 => zephyr_sys::raw::lorawan_region_LORAWAN_REGION_RU864,
+        }
+    }
+}
+
+/// Device class, the equivalent of `enum lorawan_class`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Class {
+    /// Receive windows only after uplinks.
+    A,
+    /// Additional scheduled receive windows.
+    B,
+    /// Continuously receiving.
+    C,
+}
+
+/// Whether the network server acknowledges an uplink.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MessageType {
+    Unconfirmed,
+    /// Retransmitted until acknowledged, see [set_confirmed_tries].
+    Confirmed,
+}
+
+/// Wrapper to the `lorawan_set_region` function, MUST be called before [start].
+pub fn set_region(region: Region) -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::lorawan_set_region(region.to_raw()) })
+}
+
+/// Wrapper to the `lorawan_start` function.
+pub fn start() -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::lorawan_start() })
+}
+
+/// Wrapper to the `lorawan_join` function, blocks until the device joined the network.
+pub fn join(join: &Join) -> ZephyrResult<()> {
+    // the join config points to the keys, copied to live until the function returns
+    let mut join = *join;
+    // zeroed as only the member of the union of the activation is used
+    let mut config: ZJoinConfig = unsafe { core::mem::zeroed() };
+    match &mut join {
+        Join::Otaa {
+            dev_eui,
+            join_eui,
+            app_key,
+            nwk_key,
+            dev_nonce,
+        } => {
+            config.dev_eui = dev_eui.0.as_mut_ptr();
+            config.mode = zephyr_sys::raw::lorawan_act_type_LORAWAN_ACT_OTAA;
+            // writing a member of a union is safe, the activation mode selects the same member
+            let otaa = unsafe { &mut config.__bindgen_anon_1.otaa };
+            otaa.join_eui = join_eui.0.as_mut_ptr();
+            otaa.app_key = app_key.0.as_mut_ptr();
+            otaa.nwk_key = nwk_key.0.as_mut_ptr();
+            otaa.dev_nonce = *dev_nonce;
+        }
+        Join::Abp {
+            dev_eui,
+            dev_addr,
+            app_skey,
+            nwk_skey,
+            app_eui,
+        } => {
+            config.dev_eui = dev_eui.0.as_mut_ptr();
+            config.mode = zephyr_sys::raw::lorawan_act_type_LORAWAN_ACT_ABP;
+            // writing a member of a union is safe, the activation mode selects the same member
+            let abp = unsafe { &mut config.__bindgen_anon_1.abp };
+            abp.dev_addr = *dev_addr;
+            abp.app_skey = app_skey.0.as_mut_ptr();
+            abp.nwk_skey = nwk_skey.0.as_mut_ptr();
+            abp.app_eui = app_eui.0.as_mut_ptr();
+        }
+    }
+    result(unsafe { zephyr_sys::raw::lorawan_join(&config) })
+}
+
+/// Wrapper to the `lorawan_send` function, sends `data` on `port` (1 to 223) and blocks until it
+/// was sent, or acknowledged for [MessageType::Confirmed].
+///
+/// Fails with [ErrorNumber::InvalidArgument] if `data` is larger than the payload size of the
+/// current data rate, see [payload_sizes].
+pub fn send(port: u8, data: &[u8], message_type: MessageType) -> ZephyrResult<()> {
+    if data.len() > u8::MAX as usize {
+        return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT));
+    }
+    let message_type = match message_type {
+        MessageType::Unconfirmed => zephyr_sys::raw::lorawan_message_type_LORAWAN_MSG_UNCONFIRMED,
+        MessageType::Confirmed => zephyr_sys::raw::lorawan_message_type_LORAWAN_MSG_CONFIRMED,
+    };
+    let len = data.len() as u8;
+    result(unsafe {
+        zephyr_sys::raw::lorawan_send(port, data.as_ptr() as *mut u8, len, message_type)
+    })
+}
+
+/// Wrapper to the `lorawan_set_class` function.
+pub fn set_class(class: Class) -> ZephyrResult<()> {
+    let class = match class {
+        Class::A => zephyr_sys::raw::lorawan_class_LORAWAN_CLASS_A,
+        Class::B => zephyr_sys::raw::lorawan_class_LORAWAN_CLASS_B,
+        Class::C => zephyr_sys::raw::lorawan_class_LORAWAN_CLASS_C,
+    };
+    result(unsafe { zephyr_sys::raw::lorawan_set_class(class) })
+}
+
+/// Wrapper to the `lorawan_set_datarate` function, the data rate `DR0` to `DR15` of the region
+/// used while ADR is disabled.
+pub fn set_datarate(datarate: u8) -> ZephyrResult<()> {
+    if datarate > 15 {
+        return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT));
+    }
+    result(unsafe { zephyr_sys::raw::lorawan_set_datarate(datarate as _) })
+}
+
+/// Wrapper to the `lorawan_get_min_datarate` function.
+pub fn min_datarate() -> u8 {
+    unsafe { zephyr_sys::raw::lorawan_get_min_datarate() as u8 }
+}
+
+/// Wrapper to the `lorawan_enable_adr` function, lets the network server adapt the data rate.
+pub fn enable_adr(enable: bool) {
+    unsafe { zephyr_sys::raw::lorawan_enable_adr(enable) }
+}
+
+/// Wrapper to the `lorawan_set_conf_msg_tries` function, the number of transmissions of
+/// [MessageType::Confirmed] uplinks.
+pub fn set_confirmed_tries(tries: u8) -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::lorawan_set_conf_msg_tries(tries) })
+}
+
+/// Wrapper to the `lorawan_get_payload_sizes` function, returns the maximal payload size of the
+/// next uplink, which also carries pending MAC commands, and of the current data rate.
+pub fn payload_sizes() -> (u8, u8) {
+    let mut next = 0;
+    let mut max = 0;
+    unsafe { zephyr_sys::raw::lorawan_get_payload_sizes(&mut next, &mut max) };
+    (next, max)
+}
+
+/// Downlink received from the network server.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Downlink<'a> {
+    pub port: u8,
+    /// The network server has further downlinks queued, send an uplink to receive them.
+    pub data_pending: bool,
+    /// Received signal strength in dBm.
+    pub rssi: i16,
+    /// Signal to noise ratio in dB.
+    pub snr: i8,
+    pub data: &'a [u8],
+}
+
+/// Handlers of [on_downlink] by the port they are registered for, `None` for all ports.
+static DOWNLINK_HANDLERS: Handlers<Option<u8>, dyn FnMut(&Downlink) + Send> = Handlers::new();
+
+/// Callback registered for all ports with the first handler, callbacks cannot be unregistered.
+struct DownlinkCallback(UnsafeCell<ZDownlinkCallback>);
+
+// the callback is only initialized before it is registered, then it belongs to the stack
+unsafe impl Sync for DownlinkCallback {}
+
+// zeroed as the node is initialized when the callback is registered
+static DOWNLINK_CALLBACK: DownlinkCallback =
+    DownlinkCallback(UnsafeCell::new(unsafe { core::mem::zeroed() }));
+/// Locked as not every target has compare and swap atomics.
+static DOWNLINK_REGISTERED: SpinLocked<bool> = SpinLocked::new(false);
+
+/// C callback that dispatches a downlink to the handlers of its port.
+extern "C" fn downlink_trampoline(
+    port: u8,
+    flags: u8,
+    rssi: i16,
+    snr: i8,
+    len: u8,
+    data: *const u8,
+) {
+    let data = if data.is_null() {
+        &[][..]
+    } else {
+        unsafe { core::slice::from_raw_parts(data, len as usize) }
+    };
+    let downlink = Downlink {
+        port,
+        data_pending: flags & zephyr_sys::raw::LORAWAN_DATA_PENDING as u8 != 0,
+        rssi,
+        snr,
+        data,
+    };
+    DOWNLINK_HANDLERS.call_all(
+        |key| key.map_or(true, |key_port| key_port == port),
+        |handler| handler(&downlink),
+    );
+}
+
+/// Wrapper to the `lorawan_register_downlink_callback` function, calls `f` for every downlink on
+/// `port`, or on all ports for `None`.
+///
+/// Handlers are called from the thread of the stack and cannot be removed.
+pub fn on_downlink<F>(port: Option<u8>, f: F)
+where
+    F: FnMut(&Downlink) + Send + 'static,
+{
+    DOWNLINK_HANDLERS.push(port, Box::new(f));
+
+    if !DOWNLINK_REGISTERED.replace(true) {
+        let raw = DOWNLINK_CALLBACK.0.get();
+        unsafe {
+            (*raw).port = zephyr_sys::raw::LW_RECV_PORT_ANY as u16;
+            (*raw).cb = Some(downlink_trampoline);
+            zephyr_sys::raw::lorawan_register_downlink_callback(raw);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LorawanWrapperContext {}
+
+impl Context for LorawanWrapperContext {
+    fn name(&self) -> &'static str {
+        "lorawan wrapper"
+    }
+}
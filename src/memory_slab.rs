@@ -0,0 +1,195 @@
+//! Wrapper for the Zephyr memory slab (`k_mem_slab`) kernel object.
+//!
+//! A [MemorySlab] hands out blocks of a fixed size from a buffer allocated once. Drivers that
+//! stream data in blocks, like I2S or DMIC, allocate and free their buffers from a slab passed in
+//! their configuration.
+
+use crate::time::Timeout;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::alloc::Layout;
+use alloc::boxed::Box;
+use core::convert::TryFrom;
+use core::ffi::c_void;
+use core::ops::{Deref, DerefMut};
+use zephyr_sys::raw::k_mem_slab;
+
+const CONTEXT: MemorySlabContext = MemorySlabContext {};
+
+/// Memory slab with `count` blocks of `block_size` bytes.
+pub struct MemorySlab {
+    // boxed as the kernel object must not move after being initialized
+    slab: Box<k_mem_slab>,
+    buffer: *mut u8,
+    layout: Layout,
+    block_size: usize,
+}
+
+// the kernel object is synchronized by the kernel
+unsafe impl Sync for MemorySlab {}
+unsafe impl Send for MemorySlab {}
+
+impl MemorySlab {
+    /// Creates a new memory slab with `count` blocks of at least `block_size` bytes.
+    ///
+    /// The block size is rounded up to a multiple of the pointer size, as required by the kernel.
+    pub fn new(block_size: usize, count: usize) -> ZephyrResult<Self> {
+        Self::new_aligned(block_size, count, core::mem::align_of::<usize>())
+    }
+
+    /// Creates a new memory slab like [MemorySlab::new], with every block aligned to `align` bytes,
+    /// e.g. for DMA transfers with alignment constraints. `align` must be a power of two.
+    pub fn new_aligned(block_size: usize, count: usize, align: usize) -> ZephyrResult<Self> {
+        let invalid = || ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT);
+        let align = align.max(core::mem::align_of::<usize>());
+        if block_size == 0 || count == 0 || !align.is_power_of_two() {
+            return Err(invalid());
+        }
+        let block_size = block_size.checked_add(align - 1).ok_or_else(invalid)? & !(align - 1);
+        let size = block_size.checked_mul(count).ok_or_else(invalid)?;
+        let num_blocks = u32::try_from(count).map_err(|_| invalid())?;
+        let layout = Layout::from_size_align(size, align).map_err(|_| invalid())?;
+
+        let buffer = unsafe { alloc::alloc::alloc(layout) };
+        if buffer.is_null() {
+            return Err(ZephyrError::new_with_context(ErrorNumber::NoSpace, &CONTEXT));
+        }
+        let mut slab: Box<k_mem_slab> = Box::new(unsafe { core::mem::zeroed() });
+        let errno = unsafe {
+            zephyr_sys::raw::k_mem_slab_init(
+                &mut *slab as *mut k_mem_slab,
+                buffer as *mut c_void,
+                block_size,
+                num_blocks,
+            )
+        };
+        if errno != 0 {
+            unsafe { alloc::alloc::dealloc(buffer, layout) };
+            return Err(ZephyrError::from_errno_with_context(errno, &CONTEXT));
+        }
+
+        Ok(Self {
+            slab,
+            buffer,
+            layout,
+            block_size,
+        })
+    }
+
+    pub(crate) fn slab_ptr(&self) -> *mut k_mem_slab {
+        &*self.slab as *const k_mem_slab as *mut k_mem_slab
+    }
+
+    /// Size of a block in bytes.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Number of blocks that are not allocated.
+    pub fn num_free(&self) -> usize {
+        (self.slab.info.num_blocks - self.slab.info.num_used) as usize
+    }
+
+    /// Number of allocated blocks.
+    pub fn num_used(&self) -> usize {
+        self.slab.info.num_used as usize
+    }
+
+    /// Wrapper to the `k_mem_slab_alloc` function.
+    ///
+    /// Waits up to `timeout` for a free block. Must be called with [crate::time::NO_WAIT] from
+    /// interrupts.
+    pub fn alloc(&self, timeout: Timeout) -> ZephyrResult<SlabBlock<'_>> {
+        let mut block: *mut c_void = core::ptr::null_mut();
+        let errno = unsafe {
+            zephyr_sys::raw::k_mem_slab_alloc(
+                self.slab_ptr(),
+                &mut block as *mut *mut c_void,
+                timeout,
+            )
+        };
+
+        if errno == 0 {
+            // the kernel handed out a block of the slab
+            Ok(unsafe { SlabBlock::from_raw(self, block as *mut u8, self.block_size) })
+        } else {
+            Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+        }
+    }
+}
+
+impl Drop for MemorySlab {
+    fn drop(&mut self) {
+        // blocks borrow the slab, so none of them is alive anymore
+        unsafe { alloc::alloc::dealloc(self.buffer, self.layout) };
+    }
+}
+
+/// Block allocated from a [MemorySlab], it is freed when dropped.
+///
+/// The block dereferences to its first `len` bytes, which is the whole block when allocated and
+/// the received data when returned by a driver.
+pub struct SlabBlock<'a> {
+    slab: &'a MemorySlab,
+    block: *mut u8,
+    len: usize,
+}
+
+unsafe impl<'a> Send for SlabBlock<'a> {}
+
+impl<'a> SlabBlock<'a> {
+    /// Creates a block from a pointer returned by the kernel or a driver.
+    ///
+    /// `block` MUST be a block allocated from `slab` that is not owned by anything else, `len`
+    /// MUST not exceed the block size of the slab.
+    pub unsafe fn from_raw(slab: &'a MemorySlab, block: *mut u8, len: usize) -> Self {
+        SlabBlock { slab, block, len }
+    }
+
+    /// Releases the block without freeing it, e.g. to hand it to a driver that frees it.
+    pub fn into_raw(self) -> *mut u8 {
+        let block = self.block;
+        core::mem::forget(self);
+        block
+    }
+
+    /// Shortens the block to `len` bytes, if `len` is less than the current length.
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    /// Slab the block was allocated from.
+    pub fn slab(&self) -> &'a MemorySlab {
+        self.slab
+    }
+}
+
+impl<'a> Deref for SlabBlock<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        // the block is owned and at least `len` bytes long
+        unsafe { core::slice::from_raw_parts(self.block, self.len) }
+    }
+}
+
+impl<'a> DerefMut for SlabBlock<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // the block is owned and at least `len` bytes long
+        unsafe { core::slice::from_raw_parts_mut(self.block, self.len) }
+    }
+}
+
+impl<'a> Drop for SlabBlock<'a> {
+    fn drop(&mut self) {
+        unsafe { zephyr_sys::raw::k_mem_slab_free(self.slab.slab_ptr(), self.block as *mut c_void) }
+    }
+}
+
+#[derive(Debug)]
+struct MemorySlabContext {}
+
+impl Context for MemorySlabContext {
+    fn name(&self) -> &'static str {
+        "memory slab wrapper"
+    }
+}
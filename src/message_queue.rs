@@ -0,0 +1,125 @@
+//! Wrapper for the Zephyr message queue (`k_msgq`) kernel object.
+//!
+//! A [MessageQueue] transfers copies of messages of a fixed type between threads (or from an
+//! interrupt to a thread). Messages are copied byte by byte, so only [Copy] types can be sent.
+
+use crate::time::Timeout;
+use crate::{Context, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use zephyr_sys::raw::k_msgq;
+
+const CONTEXT: MessageQueueContext = MessageQueueContext {};
+
+/// Message queue with a fixed capacity of messages of type `T`.
+pub struct MessageQueue<T: Copy + Send> {
+    // boxed as the kernel object must not move after being initialized
+    msgq: Box<k_msgq>,
+    _buffer: Vec<MaybeUninit<T>>,
+    _message: PhantomData<T>,
+}
+
+// the kernel object is synchronized by the kernel
+unsafe impl<T: Copy + Send> Sync for MessageQueue<T> {}
+unsafe impl<T: Copy + Send> Send for MessageQueue<T> {}
+
+impl<T: Copy + Send> MessageQueue<T> {
+    /// Creates a new message queue that can hold up to `capacity` messages.
+    pub fn new(capacity: usize) -> Self {
+        let mut buffer: Vec<MaybeUninit<T>> = Vec::with_capacity(capacity);
+        let mut msgq: Box<k_msgq> = Box::new(unsafe { core::mem::zeroed() });
+        unsafe {
+            buffer.set_len(capacity);
+            zephyr_sys::raw::k_msgq_init(
+                &mut *msgq as *mut k_msgq,
+                buffer.as_mut_ptr() as *mut core::ffi::c_char,
+                core::mem::size_of::<T>(),
+                capacity as u32,
+            );
+        }
+
+        Self {
+            msgq,
+            _buffer: buffer,
+            _message: PhantomData,
+        }
+    }
+
+    pub(crate) fn msgq_ptr(&self) -> *mut k_msgq {
+        &*self.msgq as *const k_msgq as *mut k_msgq
+    }
+
+    /// Wrapper to the `k_msgq_put` syscall.
+    ///
+    /// Waits up to `timeout` for free space in the queue. Must be called with
+    /// [crate::time::NO_WAIT] from interrupts.
+    pub fn put(&self, message: &T, timeout: Timeout) -> ZephyrResult<()> {
+        let errno = unsafe {
+            zephyr_sys::syscalls::any::k_msgq_put(
+                self.msgq_ptr(),
+                message as *const T as *const core::ffi::c_void,
+                timeout,
+            )
+        };
+
+        if errno == 0 {
+            Ok(())
+        } else {
+            Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+        }
+    }
+
+    /// Wrapper to the `k_msgq_get` syscall.
+    ///
+    /// Waits up to `timeout` for a message.
+    pub fn get(&self, timeout: Timeout) -> ZephyrResult<T> {
+        let mut message = MaybeUninit::<T>::uninit();
+        let errno = unsafe {
+            zephyr_sys::syscalls::any::k_msgq_get(
+                self.msgq_ptr(),
+                message.as_mut_ptr() as *mut core::ffi::c_void,
+                timeout,
+            )
+        };
+
+        if errno == 0 {
+            // the kernel copied a complete message into the buffer
+            Ok(unsafe { message.assume_init() })
+        } else {
+            Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+        }
+    }
+
+    /// Wrapper to the `k_msgq_num_used_get` syscall.
+    pub fn len(&self) -> usize {
+        unsafe { zephyr_sys::syscalls::any::k_msgq_num_used_get(self.msgq_ptr()) as usize }
+    }
+
+    /// Returns `true` if there are no messages in the queue.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Wrapper to the `k_msgq_purge` syscall. Discards all messages in the queue.
+    pub fn purge(&self) {
+        unsafe { zephyr_sys::syscalls::any::k_msgq_purge(self.msgq_ptr()) }
+    }
+}
+
+impl<T: Copy + Send> Drop for MessageQueue<T> {
+    fn drop(&mut self) {
+        // wake up threads that still wait for the queue before the buffer is freed
+        self.purge();
+    }
+}
+
+#[derive(Debug)]
+struct MessageQueueContext {}
+
+impl Context for MessageQueueContext {
+    fn name(&self) -> &'static str {
+        "message queue wrapper"
+    }
+}
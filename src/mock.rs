@@ -0,0 +1,255 @@
+//! Mocked syscalls for unit tests on the host, requires the `mock-syscalls` feature.
+//!
+//! With the feature, the wrappers of [crate::gpio], [crate::sensor] and the checks of
+//! [crate::device] call the syscalls of this module instead of Zephyr, so application logic built
+//! on them is tested without linking a Zephyr image. A test installs a [Mock] for its thread,
+//! queues the return values of the syscalls it cares about and checks the recorded calls:
+//!
+//! ```no_run
+//! use zephyr_rust_wrappers::device::{class, TypedDevice};
+//! use zephyr_rust_wrappers::gpio::GpioPin;
+//! use zephyr_rust_wrappers::mock::{self, Mock};
+//!
+//! let mock = Mock::install();
+//! let port = TypedDevice::<class::Gpio>::new(mock::device("gpio0")).unwrap();
+//! let mut led = GpioPin::from_device(port, 3, 0).unwrap();
+//!
+//! mock.returns("gpio_port_set_bits_raw", -5);
+//! assert!(led.set_value(true).is_err());
+//! assert_eq!(
+//!     mock.call_names(),
+//!     ["device_is_ready", "device_api_is", "gpio_pin_configure", "gpio_port_set_bits_raw"]
+//! );
+//! ```
+//!
+//! Syscalls without a queued return value succeed, [crate::device::is_ready] and the class checks
+//! of [crate::device::TypedDevice] then return `true`. Only the syscalls of this module are
+//! mocked, calling other functions of Zephyr fails to link.
+
+use crate::sensor::{SensorChannel, SensorValue};
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::ffi::CString;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use std::collections::HashMap;
+use zephyr::device::Device;
+use zephyr_sys::raw::sensor_value as ZSensorValue;
+
+/// A recorded syscall.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Call {
+    /// Name of the syscall, e.g. `"gpio_pin_configure"`.
+    pub name: &'static str,
+    /// Address of the device the syscall was called for.
+    pub device: usize,
+    /// The other arguments of the syscall cast to `i64`, pointers are left out.
+    pub args: Vec<i64>,
+}
+
+#[derive(Default)]
+struct State {
+    calls: Vec<Call>,
+    returns: HashMap<&'static str, VecDeque<i32>>,
+    channel_values: HashMap<u32, Vec<ZSensorValue>>,
+}
+
+thread_local! {
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+}
+
+fn with_state<T>(f: impl FnOnce(&mut State) -> T) -> T {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let state = state
+            .as_mut()
+            .expect("syscall called without a mock installed for the thread");
+        f(state)
+    })
+}
+
+/// Records the call and returns the queued return value, `default` if there is none.
+fn call(name: &'static str, device: *const Device, args: &[i64], default: i32) -> i32 {
+    with_state(|state| {
+        state.calls.push(Call {
+            name,
+            device: device as usize,
+            args: args.to_vec(),
+        });
+        state
+            .returns
+            .get_mut(name)
+            .and_then(|returns| returns.pop_front())
+            .unwrap_or(default)
+    })
+}
+
+/// Mocked syscalls of the thread, uninstalled when dropped.
+pub struct Mock {
+    // the state is thread local, so the mock must not be sent to another thread
+    _not_send: core::marker::PhantomData<*const ()>,
+}
+
+impl Mock {
+    /// Installs the mock for the current thread, replacing a mock installed before.
+    pub fn install() -> Self {
+        STATE.with(|state| *state.borrow_mut() = Some(State::default()));
+        Mock {
+            _not_send: core::marker::PhantomData,
+        }
+    }
+
+    /// Queues `value` as the return value of the next call of the syscall `name`. For syscalls
+    /// returning `bool`, `0` is `false`.
+    ///
+    /// The class checks of [crate::device::TypedDevice] are mocked as the syscall
+    /// `"device_api_is"`.
+    pub fn returns(&self, name: &'static str, value: i32) {
+        with_state(|state| state.returns.entry(name).or_default().push_back(value));
+    }
+
+    /// Sets the values `sensor_channel_get` returns for `channel`, three values for XYZ channels.
+    /// Values beyond the room the wrappers pass for the channel are left out.
+    pub fn set_channel_values(&self, channel: SensorChannel, values: &[SensorValue]) {
+        let values = values.iter().map(|&value| value.into()).collect();
+        with_state(|state| state.channel_values.insert(channel as u32, values));
+    }
+
+    /// The syscalls called since the mock was installed.
+    pub fn calls(&self) -> Vec<Call> {
+        with_state(|state| state.calls.clone())
+    }
+
+    /// Names of the syscalls called since the mock was installed.
+    pub fn call_names(&self) -> Vec<&'static str> {
+        with_state(|state| state.calls.iter().map(|call| call.name).collect())
+    }
+
+    /// Forgets the recorded calls.
+    pub fn clear_calls(&self) {
+        with_state(|state| state.calls.clear());
+    }
+}
+
+impl Drop for Mock {
+    fn drop(&mut self) {
+        STATE.with(|state| *state.borrow_mut() = None);
+    }
+}
+
+/// Creates a device named `name` for the mocked syscalls. The device is leaked, which is fine for
+/// tests.
+pub fn device(name: &str) -> &'static Device {
+    let name = CString::new(name).expect("device name without NUL");
+    // zeroed as the mocked syscalls never access the driver of the device
+    let mut device: Device = unsafe { core::mem::zeroed() };
+    device.name = CString::into_raw(name);
+    Box::leak(Box::new(device))
+}
+
+/// Class check of [crate::device::DeviceClass] of mocked devices.
+pub(crate) fn device_api_is(device: &'static Device) -> bool {
+    call("device_api_is", device, &[], 1) != 0
+}
+
+/// The mocked syscalls, with the signatures of `zephyr_sys::syscalls::any`.
+pub(crate) mod syscalls {
+    use super::{call, with_state};
+    use crate::sensor::SensorChannel;
+    use core::sync::atomic::{AtomicBool, Ordering};
+    use zephyr::device::Device;
+    use zephyr_sys::raw::{
+        gpio_flags_t, gpio_pin_t, gpio_port_pins_t, k_spinlock, k_spinlock_key_t, sensor_attribute,
+        sensor_channel, sensor_value as ZSensorValue,
+    };
+
+    /// Lock of all mocked spinlocks, the tables never hold more than one at a time.
+    static LOCKED: AtomicBool = AtomicBool::new(false);
+
+    pub unsafe fn k_spin_lock(_lock: *mut k_spinlock) -> k_spinlock_key_t {
+        while LOCKED.swap(true, Ordering::Acquire) {
+            std::thread::yield_now();
+        }
+        // zeroed as the key is not used by the mocked unlock
+        core::mem::zeroed()
+    }
+
+    pub unsafe fn k_spin_unlock(_lock: *mut k_spinlock, _key: k_spinlock_key_t) {
+        LOCKED.store(false, Ordering::Release);
+    }
+
+    pub unsafe fn device_is_ready(device: *const Device) -> bool {
+        call("device_is_ready", device, &[], 1) != 0
+    }
+
+    pub unsafe fn gpio_pin_configure(
+        port: *const Device,
+        pin: gpio_pin_t,
+        flags: gpio_flags_t,
+    ) -> i32 {
+        call("gpio_pin_configure", port, &[pin as i64, flags as i64], 0)
+    }
+
+    pub unsafe fn gpio_port_set_bits_raw(port: *const Device, pins: gpio_port_pins_t) -> i32 {
+        call("gpio_port_set_bits_raw", port, &[pins as i64], 0)
+    }
+
+    pub unsafe fn gpio_port_clear_bits_raw(port: *const Device, pins: gpio_port_pins_t) -> i32 {
+        call("gpio_port_clear_bits_raw", port, &[pins as i64], 0)
+    }
+
+    pub unsafe fn sensor_sample_fetch(device: *const Device) -> i32 {
+        call("sensor_sample_fetch", device, &[], 0)
+    }
+
+    pub unsafe fn sensor_sample_fetch_chan(device: *const Device, channel: sensor_channel) -> i32 {
+        call("sensor_sample_fetch_chan", device, &[channel as i64], 0)
+    }
+
+    pub unsafe fn sensor_channel_get(
+        device: *const Device,
+        channel: sensor_channel,
+        value: *mut ZSensorValue,
+    ) -> i32 {
+        let errno = call("sensor_channel_get", device, &[channel as i64], 0);
+        if errno == 0 {
+            let values = with_state(|state| state.channel_values.get(&channel).cloned());
+            // the wrappers pass room for three values for XYZ channels, one otherwise
+            let room = if is_xyz(channel) { 3 } else { 1 };
+            for (index, &channel_value) in values.iter().flatten().take(room).enumerate() {
+                *value.add(index) = channel_value;
+            }
+        }
+        errno
+    }
+
+    fn is_xyz(channel: sensor_channel) -> bool {
+        [
+            SensorChannel::AccelXYZ,
+            SensorChannel::GyroXYZ,
+            SensorChannel::MagnXYZ,
+        ]
+        .iter()
+        .any(|xyz| *xyz as sensor_channel == channel)
+    }
+
+    pub unsafe fn sensor_attr_set(
+        device: *const Device,
+        channel: sensor_channel,
+        attribute: sensor_attribute,
+        value: *const ZSensorValue,
+    ) -> i32 {
+        let value = &*value;
+        call(
+            "sensor_attr_set",
+            device,
+            &[
+                channel as i64,
+                attribute as i64,
+                value.val1 as i64,
+                value.val2 as i64,
+            ],
+            0,
+        )
+    }
+}
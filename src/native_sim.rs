@@ -0,0 +1,122 @@
+//! Support for running the application on the host with the `native_sim` board, requires the
+//! `native-sim` feature.
+//!
+//! `native_sim` builds Zephyr and the application into a Linux executable, so application logic
+//! is tested in CI without hardware. The board is 32 bit by default and 64 bit as
+//! `native_sim/native/64`, the wrappers use the C types of the bindings and work with both.
+//! Emulated drivers stand in for the hardware: [crate::sensor::emul] sets the readings of emulated
+//! sensors and [crate::gpio::emul_input_set] drives the inputs of the emulated GPIO controller
+//! (both with the `test-emul` feature). The tests of [crate::ztest] run on `native_sim` as well.
+//!
+//! With `CONFIG_EXTERNAL_LIBC`, Zephyr uses the C library of the host, whose error numbers differ
+//! from those of Zephyr. The `native-libc` feature translates the error numbers of Linux from and
+//! to [crate::ErrorNumber], so the variants mean the same as on the target.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::{c_char, c_int, CStr};
+
+extern "C" {
+    fn nsi_exit(exit_code: c_int) -> !;
+    fn native_get_test_cmd_line_args(argc: *mut c_int, argv: *mut *mut *mut c_char);
+}
+
+/// Ends the executable with `code`, e.g. to report the result of a test run to CI. Wrapper to the
+/// `nsi_exit` function.
+pub fn exit(code: i32) -> ! {
+    unsafe { nsi_exit(code) }
+}
+
+/// Arguments passed to the executable after `--testargs`. Wrapper to the
+/// `native_get_test_cmd_line_args` function.
+pub fn test_args() -> Vec<String> {
+    let mut argc: c_int = 0;
+    let mut argv: *mut *mut c_char = core::ptr::null_mut();
+    unsafe { native_get_test_cmd_line_args(&mut argc, &mut argv) };
+    if argv.is_null() {
+        return Vec::new();
+    }
+
+    // the arguments are the ones of main, which live until the executable ends
+    let args = unsafe { core::slice::from_raw_parts(argv, argc as usize) };
+    args.iter()
+        .map(|&arg| {
+            unsafe { CStr::from_ptr(arg) }
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect()
+}
+
+/// Error numbers of Linux and Zephyr that differ as `(linux, zephyr)`, the others are the same in
+/// both.
+#[cfg(feature = "native-libc")]
+const DIFFERENT_ERRNO: &[(i32, i32)] = &[
+    (35, 45),   // EDEADLK
+    (36, 91),   // ENAMETOOLONG
+    (37, 46),   // ENOLCK
+    (38, 88),   // ENOSYS
+    (39, 90),   // ENOTEMPTY
+    (40, 92),   // ELOOP
+    (42, 35),   // ENOMSG
+    (74, 77),   // EBADMSG
+    (75, 139),  // EOVERFLOW
+    (84, 138),  // EILSEQ
+    (88, 108),  // ENOTSOCK
+    (89, 121),  // EDESTADDRREQ
+    (90, 122),  // EMSGSIZE
+    (91, 107),  // EPROTOTYPE
+    (92, 109),  // ENOPROTOOPT
+    (93, 123),  // EPROTONOSUPPORT
+    (94, 124),  // ESOCKTNOSUPPORT
+    (97, 106),  // EAFNOSUPPORT
+    (98, 112),  // EADDRINUSE
+    (99, 125),  // EADDRNOTAVAIL
+    (100, 115), // ENETDOWN
+    (101, 114), // ENETUNREACH
+    (102, 126), // ENETRESET
+    (103, 113), // ECONNABORTED
+    (106, 127), // EISCONN
+    (107, 128), // ENOTCONN
+    (108, 110), // ESHUTDOWN
+    (109, 129), // ETOOMANYREFS
+    (110, 116), // ETIMEDOUT
+    (112, 117), // EHOSTDOWN
+    (113, 118), // EHOSTUNREACH
+    (114, 120), // EALREADY
+    (115, 119), // EINPROGRESS
+    (125, 140), // ECANCELED
+];
+
+/// Whether the error number is the same in Linux and Zephyr.
+#[cfg(feature = "native-libc")]
+fn is_same_errno(errno: i32) -> bool {
+    matches!(errno, 1..=34 | 60..=63 | 71 | 95 | 96 | 104 | 105 | 111)
+}
+
+/// Converts an error number of Linux into the numbering of Zephyr, keeping the sign. Returns
+/// `None` for numbers without an equivalent in Zephyr.
+#[cfg(feature = "native-libc")]
+pub(crate) fn errno_from_libc(errno: i32) -> Option<i32> {
+    let number = errno.abs();
+    let zephyr = if is_same_errno(number) {
+        number
+    } else {
+        let (_, zephyr) = DIFFERENT_ERRNO.iter().find(|(linux, _)| *linux == number)?;
+        *zephyr
+    };
+    Some(if errno < 0 { -zephyr } else { zephyr })
+}
+
+/// Converts a positive error number of Zephyr into the numbering of Linux. `ENOTSUP` is the same
+/// number as `EOPNOTSUPP` in Linux.
+#[cfg(feature = "native-libc")]
+pub(crate) fn errno_to_libc(errno: i32) -> i32 {
+    if errno == 134 {
+        return 95;
+    }
+    DIFFERENT_ERRNO
+        .iter()
+        .find(|(_, zephyr)| *zephyr == errno)
+        .map_or(errno, |(linux, _)| *linux)
+}
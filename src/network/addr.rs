@@ -0,0 +1,158 @@
+//! Conversions between the address types of `std::net` (which are the types of `core::net`) and
+//! the address structs of the Zephyr networking stack.
+//!
+//! The structs of Zephyr store addresses and ports in network byte order, the conversions take
+//! care of the byte order so the rest of the crate only uses the types of `std::net`.
+
+use core::mem::size_of;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+pub use zephyr_sys::raw::{
+    in6_addr as ZIn6Addr, in_addr as ZInAddr, sockaddr as ZSockAddr, sockaddr_in as ZSockAddrIn,
+    sockaddr_in6 as ZSockAddrIn6, socklen_t,
+};
+
+/// Converts `addr` into an `in_addr`.
+pub fn to_in_addr(addr: &Ipv4Addr) -> ZInAddr {
+    // zeroed as the address is set below
+    let mut raw: ZInAddr = unsafe { core::mem::zeroed() };
+    raw.__bindgen_anon_1.s4_addr = addr.octets();
+    raw
+}
+
+/// Converts an `in_addr` into an [Ipv4Addr].
+pub fn from_in_addr(raw: &ZInAddr) -> Ipv4Addr {
+    // the union always holds the bytes of the address
+    Ipv4Addr::from(unsafe { raw.__bindgen_anon_1.s4_addr })
+}
+
+/// Converts `addr` into an `in6_addr`.
+pub fn to_in6_addr(addr: &Ipv6Addr) -> ZIn6Addr {
+    // zeroed as the address is set below
+    let mut raw: ZIn6Addr = unsafe { core::mem::zeroed() };
+    raw.__bindgen_anon_1.s6_addr = addr.octets();
+    raw
+}
+
+/// Converts an `in6_addr` into an [Ipv6Addr].
+pub fn from_in6_addr(raw: &ZIn6Addr) -> Ipv6Addr {
+    // the union always holds the bytes of the address
+    Ipv6Addr::from(unsafe { raw.__bindgen_anon_1.s6_addr })
+}
+
+/// Address family (`AF_INET` or `AF_INET6`) of `addr`.
+pub fn family(addr: &IpAddr) -> u32 {
+    match addr {
+        IpAddr::V4(_) => zephyr_sys::raw::AF_INET,
+        IpAddr::V6(_) => zephyr_sys::raw::AF_INET6,
+    }
+}
+
+/// `sockaddr` large enough for addresses of all families, together with the length of the address
+/// it holds.
+#[derive(Copy, Clone)]
+pub struct RawSocketAddr {
+    raw: ZSockAddr,
+    len: socklen_t,
+}
+
+impl RawSocketAddr {
+    /// Empty address to be filled by the socket layer, e.g. by `zsock_accept`.
+    pub fn new() -> Self {
+        RawSocketAddr {
+            // zeroed as the socket layer only sets the used part of the address
+            raw: unsafe { core::mem::zeroed() },
+            len: size_of::<ZSockAddr>() as socklen_t,
+        }
+    }
+
+    /// Pointer to the address for the Zephyr API.
+    pub fn as_ptr(&self) -> *const ZSockAddr {
+        &self.raw
+    }
+
+    /// Mutable pointer to the address for the Zephyr API.
+    pub fn as_mut_ptr(&mut self) -> *mut ZSockAddr {
+        &mut self.raw
+    }
+
+    /// Length of the address.
+    pub fn socklen(&self) -> socklen_t {
+        self.len
+    }
+
+    /// Mutable length for functions which take the capacity and return the length of the address.
+    pub fn socklen_mut(&mut self) -> &mut socklen_t {
+        &mut self.len
+    }
+
+    /// The `sockaddr` of the address.
+    pub fn as_sockaddr(&self) -> &ZSockAddr {
+        &self.raw
+    }
+
+    /// Converts the address into a [SocketAddr], returns `None` for address families other than
+    /// IPv4 and IPv6.
+    pub fn to_socket_addr(&self) -> Option<SocketAddr> {
+        from_sockaddr(&self.raw)
+    }
+}
+
+impl Default for RawSocketAddr {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<&SocketAddr> for RawSocketAddr {
+    fn from(addr: &SocketAddr) -> Self {
+        let mut raw = RawSocketAddr::new();
+        let len = match addr {
+            SocketAddr::V4(addr) => {
+                // sockaddr is large enough for all address families
+                let raw = unsafe { &mut *(raw.as_mut_ptr() as *mut ZSockAddrIn) };
+                raw.sin_family = zephyr_sys::raw::AF_INET as _;
+                raw.sin_port = addr.port().to_be();
+                raw.sin_addr = to_in_addr(addr.ip());
+                size_of::<ZSockAddrIn>()
+            }
+            SocketAddr::V6(addr) => {
+                // sockaddr is large enough for all address families
+                let raw = unsafe { &mut *(raw.as_mut_ptr() as *mut ZSockAddrIn6) };
+                raw.sin6_family = zephyr_sys::raw::AF_INET6 as _;
+                raw.sin6_port = addr.port().to_be();
+                raw.sin6_addr = to_in6_addr(addr.ip());
+                raw.sin6_scope_id = addr.scope_id() as _;
+                size_of::<ZSockAddrIn6>()
+            }
+        };
+        raw.len = len as socklen_t;
+        raw
+    }
+}
+
+impl From<SocketAddr> for RawSocketAddr {
+    fn from(addr: SocketAddr) -> Self {
+        (&addr).into()
+    }
+}
+
+/// Converts a `sockaddr` into a [SocketAddr], returns `None` for address families other than
+/// IPv4 and IPv6.
+pub fn from_sockaddr(raw: &ZSockAddr) -> Option<SocketAddr> {
+    match raw.sa_family as u32 {
+        zephyr_sys::raw::AF_INET => {
+            // the family identifies the type of the address
+            let raw = unsafe { &*(raw as *const ZSockAddr as *const ZSockAddrIn) };
+            let ip = from_in_addr(&raw.sin_addr);
+            Some(SocketAddrV4::new(ip, u16::from_be(raw.sin_port)).into())
+        }
+        zephyr_sys::raw::AF_INET6 => {
+            // the family identifies the type of the address
+            let raw = unsafe { &*(raw as *const ZSockAddr as *const ZSockAddrIn6) };
+            let ip = from_in6_addr(&raw.sin6_addr);
+            let port = u16::from_be(raw.sin6_port);
+            Some(SocketAddrV6::new(ip, port, 0, raw.sin6_scope_id as u32).into())
+        }
+        _ => None,
+    }
+}
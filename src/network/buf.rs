@@ -0,0 +1,464 @@
+//! Wrappers for the network buffers of Zephyr (`net_buf_simple` and `net_buf`).
+//!
+//! A [NetworkBufferSimple] is a view of a `net_buf_simple` that is owned elsewhere, e.g. the
+//! advertising data passed to a Bluetooth scan callback. [SimpleBuffer] creates a `net_buf_simple`
+//! on top of memory of the caller, which may be on the stack or in a static, and reserves
+//! headroom for headers pushed in front of the data later:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::buf::SimpleBuffer;
+//! let mut storage = [0u8; 64];
+//! let mut buf = SimpleBuffer::new(&mut storage, 4);
+//! buf.add_mem(b"payload").unwrap();
+//! buf.push_le16(7).unwrap();
+//! assert_eq!(buf.pull_le16(), Some(7));
+//! ```
+//!
+//! A [NetBuf] is a reference counted buffer allocated from a [NetBufPool], the reference is
+//! released when it is dropped.
+
+use crate::time::Timeout;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use core::ffi::c_void;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::ptr::NonNull;
+use core::slice;
+use zephyr_sys::raw::{
+    net_buf as ZNetBuf, net_buf_pool as ZNetBufPool, net_buf_simple as ZNetBufSimple,
+};
+
+const CONTEXT: BufWrapperContext = BufWrapperContext {};
+
+fn no_space() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::NoSpace, &CONTEXT)
+}
+
+/// View of a `net_buf_simple`, the data starts after the headroom and is followed by the
+/// tailroom.
+#[repr(transparent)]
+pub struct NetworkBufferSimple(ZNetBufSimple);
+
+impl NetworkBufferSimple {
+    /// View of the `net_buf_simple` at `raw`.
+    ///
+    /// `raw` MUST point to an initialized buffer that outlives the view and is not accessed
+    /// otherwise while the view is alive.
+    pub unsafe fn from_raw<'a>(raw: *mut ZNetBufSimple) -> &'a mut Self {
+        &mut *(raw as *mut Self)
+    }
+
+    /// Pointer to the buffer for the Zephyr API.
+    pub fn as_raw(&mut self) -> *mut ZNetBufSimple {
+        &mut self.0
+    }
+
+    /// The data of the buffer.
+    pub fn data(&self) -> &[u8] {
+        if self.0.len == 0 {
+            return &[];
+        }
+        unsafe { slice::from_raw_parts(self.0.data, self.0.len as usize) }
+    }
+
+    /// The data of the buffer.
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        if self.0.len == 0 {
+            return &mut [];
+        }
+        unsafe { slice::from_raw_parts_mut(self.0.data, self.0.len as usize) }
+    }
+
+    /// Length of the data.
+    pub fn len(&self) -> usize {
+        self.0.len as usize
+    }
+
+    /// Whether the buffer contains no data.
+    pub fn is_empty(&self) -> bool {
+        self.0.len == 0
+    }
+
+    /// Size of the memory of the buffer, including the headroom and the tailroom.
+    pub fn size(&self) -> usize {
+        self.0.size as usize
+    }
+
+    /// Wrapper to the `net_buf_simple_headroom` function, the number of bytes that can be
+    /// pushed.
+    pub fn headroom(&self) -> usize {
+        unsafe { zephyr_sys::raw::net_buf_simple_headroom(&self.0) }
+    }
+
+    /// Wrapper to the `net_buf_simple_tailroom` function, the number of bytes that can be added.
+    pub fn tailroom(&self) -> usize {
+        unsafe { zephyr_sys::raw::net_buf_simple_tailroom(&self.0) }
+    }
+
+    /// Discards the data and the headroom, the equivalent of the inlined
+    /// `net_buf_simple_reset`.
+    pub fn reset(&mut self) {
+        self.0.len = 0;
+        self.0.data = self.0.__buf;
+    }
+
+    /// Reserves `headroom` bytes in front of the data of an empty buffer, the equivalent of the
+    /// inlined `net_buf_simple_reserve`.
+    ///
+    /// Fails with [ErrorNumber::NoSpace] if the buffer is not empty or too small.
+    pub fn reserve(&mut self, headroom: usize) -> ZephyrResult<()> {
+        if self.0.len != 0 || headroom > self.size() {
+            return Err(no_space());
+        }
+        self.0.data = unsafe { self.0.__buf.add(headroom) };
+        Ok(())
+    }
+
+    fn check_tailroom(&self, len: usize) -> ZephyrResult<()> {
+        if len > self.tailroom() {
+            Err(no_space())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_headroom(&self, len: usize) -> ZephyrResult<()> {
+        if len > self.headroom() {
+            Err(no_space())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Wrapper to the `net_buf_simple_add` function, appends `len` bytes and returns them.
+    pub fn add(&mut self, len: usize) -> ZephyrResult<&mut [u8]> {
+        self.check_tailroom(len)?;
+        let data = unsafe { zephyr_sys::raw::net_buf_simple_add(&mut self.0, len) } as *mut u8;
+        Ok(unsafe { slice::from_raw_parts_mut(data, len) })
+    }
+
+    /// Wrapper to the `net_buf_simple_add_mem` function.
+    pub fn add_mem(&mut self, mem: &[u8]) -> ZephyrResult<()> {
+        self.check_tailroom(mem.len())?;
+        unsafe {
+            zephyr_sys::raw::net_buf_simple_add_mem(
+                &mut self.0,
+                mem.as_ptr() as *const c_void,
+                mem.len(),
+            )
+        };
+        Ok(())
+    }
+
+    /// Wrapper to the `net_buf_simple_add_u8` function.
+    pub fn add_u8(&mut self, value: u8) -> ZephyrResult<()> {
+        self.check_tailroom(1)?;
+        unsafe { zephyr_sys::raw::net_buf_simple_add_u8(&mut self.0, value) };
+        Ok(())
+    }
+
+    /// Wrapper to the `net_buf_simple_add_le16` function.
+    pub fn add_le16(&mut self, value: u16) -> ZephyrResult<()> {
+        self.check_tailroom(2)?;
+        unsafe { zephyr_sys::raw::net_buf_simple_add_le16(&mut self.0, value) };
+        Ok(())
+    }
+
+    /// Wrapper to the `net_buf_simple_add_be16` function.
+    pub fn add_be16(&mut self, value: u16) -> ZephyrResult<()> {
+        self.check_tailroom(2)?;
+        unsafe { zephyr_sys::raw::net_buf_simple_add_be16(&mut self.0, value) };
+        Ok(())
+    }
+
+    /// Wrapper to the `net_buf_simple_add_le32` function.
+    pub fn add_le32(&mut self, value: u32) -> ZephyrResult<()> {
+        self.check_tailroom(4)?;
+        unsafe { zephyr_sys::raw::net_buf_simple_add_le32(&mut self.0, value) };
+        Ok(())
+    }
+
+    /// Wrapper to the `net_buf_simple_add_be32` function.
+    pub fn add_be32(&mut self, value: u32) -> ZephyrResult<()> {
+        self.check_tailroom(4)?;
+        unsafe { zephyr_sys::raw::net_buf_simple_add_be32(&mut self.0, value) };
+        Ok(())
+    }
+
+    /// Wrapper to the `net_buf_simple_push` function, prepends `len` bytes of the headroom and
+    /// returns them.
+    pub fn push(&mut self, len: usize) -> ZephyrResult<&mut [u8]> {
+        self.check_headroom(len)?;
+        let data = unsafe { zephyr_sys::raw::net_buf_simple_push(&mut self.0, len) } as *mut u8;
+        Ok(unsafe { slice::from_raw_parts_mut(data, len) })
+    }
+
+    /// Prepends `mem`, the equivalent of `net_buf_simple_push_mem`.
+    pub fn push_mem(&mut self, mem: &[u8]) -> ZephyrResult<()> {
+        self.push(mem.len())?.copy_from_slice(mem);
+        Ok(())
+    }
+
+    /// Wrapper to the `net_buf_simple_push_u8` function.
+    pub fn push_u8(&mut self, value: u8) -> ZephyrResult<()> {
+        self.check_headroom(1)?;
+        unsafe { zephyr_sys::raw::net_buf_simple_push_u8(&mut self.0, value) };
+        Ok(())
+    }
+
+    /// Wrapper to the `net_buf_simple_push_le16` function.
+    pub fn push_le16(&mut self, value: u16) -> ZephyrResult<()> {
+        self.check_headroom(2)?;
+        unsafe { zephyr_sys::raw::net_buf_simple_push_le16(&mut self.0, value) };
+        Ok(())
+    }
+
+    /// Wrapper to the `net_buf_simple_push_be16` function.
+    pub fn push_be16(&mut self, value: u16) -> ZephyrResult<()> {
+        self.check_headroom(2)?;
+        unsafe { zephyr_sys::raw::net_buf_simple_push_be16(&mut self.0, value) };
+        Ok(())
+    }
+
+    /// Wrapper to the `net_buf_simple_pull_mem` function, removes `len` bytes from the start of
+    /// the data and returns them, `None` if the buffer contains less data.
+    ///
+    /// The returned bytes stay valid until they are overwritten by a push.
+    pub fn pull(&mut self, len: usize) -> Option<&[u8]> {
+        if len > self.len() {
+            return None;
+        }
+        let data = unsafe { zephyr_sys::raw::net_buf_simple_pull_mem(&mut self.0, len) };
+        Some(unsafe { slice::from_raw_parts(data as *const u8, len) })
+    }
+
+    /// Wrapper to the `net_buf_simple_pull_u8` function.
+    pub fn pull_u8(&mut self) -> Option<u8> {
+        if self.len() < 1 {
+            return None;
+        }
+        Some(unsafe { zephyr_sys::raw::net_buf_simple_pull_u8(&mut self.0) })
+    }
+
+    /// Wrapper to the `net_buf_simple_pull_le16` function.
+    pub fn pull_le16(&mut self) -> Option<u16> {
+        if self.len() < 2 {
+            return None;
+        }
+        Some(unsafe { zephyr_sys::raw::net_buf_simple_pull_le16(&mut self.0) })
+    }
+
+    /// Wrapper to the `net_buf_simple_pull_be16` function.
+    pub fn pull_be16(&mut self) -> Option<u16> {
+        if self.len() < 2 {
+            return None;
+        }
+        Some(unsafe { zephyr_sys::raw::net_buf_simple_pull_be16(&mut self.0) })
+    }
+
+    /// Wrapper to the `net_buf_simple_pull_le32` function.
+    pub fn pull_le32(&mut self) -> Option<u32> {
+        if self.len() < 4 {
+            return None;
+        }
+        Some(unsafe { zephyr_sys::raw::net_buf_simple_pull_le32(&mut self.0) })
+    }
+
+    /// Wrapper to the `net_buf_simple_pull_be32` function.
+    pub fn pull_be32(&mut self) -> Option<u32> {
+        if self.len() < 4 {
+            return None;
+        }
+        Some(unsafe { zephyr_sys::raw::net_buf_simple_pull_be32(&mut self.0) })
+    }
+}
+
+/// A `net_buf_simple` using memory borrowed from the caller.
+pub struct SimpleBuffer<'a> {
+    buf: NetworkBufferSimple,
+    _storage: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> SimpleBuffer<'a> {
+    /// Empty buffer in `storage` with `headroom` bytes reserved for pushing, the equivalent of
+    /// `net_buf_simple_init_with_data` followed by `net_buf_simple_reserve`.
+    ///
+    /// The headroom is limited to the size of `storage`, which is limited to `u16::MAX` bytes.
+    pub fn new(storage: &'a mut [u8], headroom: usize) -> Self {
+        let mut buffer = Self::with_data(storage, 0);
+        let headroom = headroom.min(buffer.size());
+        // the buffer is empty and the headroom fits
+        let _ = buffer.reserve(headroom);
+        buffer
+    }
+
+    /// Buffer containing the first `len` bytes of `storage` without headroom, e.g. to parse
+    /// them with the pull functions.
+    pub fn with_data(storage: &'a mut [u8], len: usize) -> Self {
+        let size = storage.len().min(u16::MAX as usize);
+        let ptr = storage.as_mut_ptr();
+        SimpleBuffer {
+            buf: NetworkBufferSimple(ZNetBufSimple {
+                data: ptr,
+                len: len.min(size) as u16,
+                size: size as u16,
+                __buf: ptr,
+            }),
+            _storage: PhantomData,
+        }
+    }
+}
+
+impl Deref for SimpleBuffer<'_> {
+    type Target = NetworkBufferSimple;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buf
+    }
+}
+
+impl DerefMut for SimpleBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buf
+    }
+}
+
+/// Pool of `net_buf` buffers defined with `NET_BUF_POOL_DEFINE` or one of its variants.
+#[derive(Copy, Clone)]
+pub struct NetBufPool(&'static ZNetBufPool);
+
+// the pool is protected by its lock
+unsafe impl Send for NetBufPool {}
+unsafe impl Sync for NetBufPool {}
+
+impl NetBufPool {
+    /// Wraps the pool `raw`, e.g. a pool defined in C and declared in an `extern "C"` block.
+    ///
+    /// `raw` MUST be a pool defined with one of the `NET_BUF_POOL_*_DEFINE` macros.
+    pub unsafe fn from_raw(raw: &'static ZNetBufPool) -> Self {
+        NetBufPool(raw)
+    }
+
+    fn as_raw(&self) -> *mut ZNetBufPool {
+        self.0 as *const ZNetBufPool as *mut ZNetBufPool
+    }
+
+    /// Wrapper to the `net_buf_pool_get` function, the pool with the id `id`.
+    pub fn get(id: i32) -> Option<Self> {
+        let raw = unsafe { zephyr_sys::raw::net_buf_pool_get(id) };
+        // pools returned by Zephyr are static
+        unsafe { raw.as_ref() }.map(NetBufPool)
+    }
+
+    /// Wrapper to the `net_buf_alloc_fixed` function, allocates a buffer with the data size of
+    /// a pool of fixed size buffers. Waits up to `timeout` for a free buffer.
+    ///
+    /// Fails with [ErrorNumber::NoSpace] if no buffer was freed in time.
+    pub fn alloc(&self, timeout: Timeout) -> ZephyrResult<NetBuf> {
+        let raw = unsafe { zephyr_sys::raw::net_buf_alloc_fixed(self.as_raw(), timeout) };
+        NonNull::new(raw).map(NetBuf).ok_or_else(no_space)
+    }
+
+    /// Wrapper to the `net_buf_alloc_len` function, allocates a buffer of `size` bytes from a
+    /// pool of variable size buffers. Waits up to `timeout` for free memory.
+    pub fn alloc_len(&self, size: usize, timeout: Timeout) -> ZephyrResult<NetBuf> {
+        let raw = unsafe { zephyr_sys::raw::net_buf_alloc_len(self.as_raw(), size, timeout) };
+        NonNull::new(raw).map(NetBuf).ok_or_else(no_space)
+    }
+}
+
+/// Reference to a `net_buf`, the reference is released with `net_buf_unref` when dropped.
+///
+/// A [NetBuf] gives mutable access to the data, so it is not [Clone]: further references are taken
+/// with the `unsafe` [NetBuf::clone_ref].
+pub struct NetBuf(NonNull<ZNetBuf>);
+
+// buffers are reference counted and may be sent to other threads, like through a FIFO in C
+unsafe impl Send for NetBuf {}
+
+impl NetBuf {
+    /// Takes over the reference of the caller to `raw`, returns `None` if `raw` is null.
+    ///
+    /// `raw` MUST be a buffer allocated from a pool. If the buffer has other references, they MUST
+    /// NOT access it while this one modifies it, like for [NetBuf::clone_ref].
+    pub unsafe fn from_raw(raw: *mut ZNetBuf) -> Option<Self> {
+        NonNull::new(raw).map(NetBuf)
+    }
+
+    /// Releases the ownership of the reference, which the caller has to release with
+    /// `net_buf_unref`.
+    pub fn into_raw(self) -> *mut ZNetBuf {
+        let raw = self.0.as_ptr();
+        core::mem::forget(self);
+        raw
+    }
+
+    /// Pointer to the buffer for the Zephyr API.
+    pub fn as_raw(&self) -> *mut ZNetBuf {
+        self.0.as_ptr()
+    }
+
+    /// Wrapper to the `net_buf_ref` function, takes another reference to the buffer.
+    ///
+    /// The data is shared by both references, so while one of them modifies the buffer the other
+    /// MUST NOT access it, from this or any other thread.
+    pub unsafe fn clone_ref(&self) -> Self {
+        let raw = zephyr_sys::raw::net_buf_ref(self.as_raw());
+        NetBuf(NonNull::new_unchecked(raw))
+    }
+
+    /// The number of references to the buffer.
+    pub fn ref_count(&self) -> u8 {
+        unsafe { self.0.as_ref().ref_ }
+    }
+
+    /// The user data of the buffer, its size is set by the pool.
+    pub fn user_data(&mut self) -> &mut [u8] {
+        let buf = unsafe { self.0.as_mut() };
+        let len = buf.user_data_size as usize;
+        unsafe { slice::from_raw_parts_mut(buf.user_data.as_mut_ptr(), len) }
+    }
+
+    /// Wrapper to the `net_buf_frag_add` function, appends `fragment` to the fragments of this
+    /// buffer, which takes over the reference.
+    pub fn append_fragment(&mut self, fragment: NetBuf) {
+        unsafe { zephyr_sys::raw::net_buf_frag_add(self.as_raw(), fragment.into_raw()) };
+    }
+
+    /// Wrapper to the `net_buf_frags_len` function, the length of the data of this buffer and
+    /// its fragments.
+    pub fn total_len(&self) -> usize {
+        unsafe { zephyr_sys::raw::net_buf_frags_len(self.as_raw()) }
+    }
+}
+
+impl Deref for NetBuf {
+    type Target = NetworkBufferSimple;
+
+    fn deref(&self) -> &Self::Target {
+        // the data fields of a net_buf overlay a net_buf_simple
+        unsafe { &*(&self.0.as_ref().__bindgen_anon_1.b as *const _ as *const NetworkBufferSimple) }
+    }
+}
+
+impl DerefMut for NetBuf {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // the data fields of a net_buf overlay a net_buf_simple
+        unsafe { NetworkBufferSimple::from_raw(&mut self.0.as_mut().__bindgen_anon_1.b) }
+    }
+}
+
+impl Drop for NetBuf {
+    fn drop(&mut self) {
+        unsafe { zephyr_sys::raw::net_buf_unref(self.as_raw()) }
+    }
+}
+
+#[derive(Debug)]
+struct BufWrapperContext {}
+
+impl Context for BufWrapperContext {
+    fn name(&self) -> &'static str {
+        "net_buf wrapper"
+    }
+}
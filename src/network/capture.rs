@@ -0,0 +1,232 @@
+//! Wrappers for the promiscuous mode (`CONFIG_NET_PROMISCUOUS_MODE`) and the packet capture
+//! (`CONFIG_NET_CAPTURE`) of the network stack.
+//!
+//! In promiscuous mode, an interface receives all frames on its link, and the network stack
+//! queues a copy of each of them for [wait_packet]. [PcapWriter] writes these frames in the pcap
+//! format, e.g. into a file or a socket for Wireshark:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::capture::{self, PcapWriter, LINKTYPE_ETHERNET};
+//!# use zephyr_rust_wrappers::network::interface::NetworkInterface;
+//!# use zephyr_rust_wrappers::time::FOREVER;
+//!# fn example(iface: NetworkInterface, out: impl std::io::Write) {
+//! capture::promiscuous_on(iface).unwrap();
+//! let mut pcap = PcapWriter::new(out, LINKTYPE_ETHERNET).unwrap();
+//! while let Some(pkt) = capture::wait_packet(FOREVER) {
+//!     pcap.write_packet(&pkt).unwrap();
+//! }
+//!# }
+//! ```
+//!
+//! [Capture] instead lets the network stack send the traffic of an interface through an IP tunnel
+//! to a remote host.
+
+use super::interface::NetworkInterface;
+use super::pkt::NetPkt;
+use crate::time::Timeout;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::ffi::CString;
+use alloc::string::ToString;
+use core::ffi::c_int;
+use core::net::IpAddr;
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::io;
+use zephyr::device::Device;
+use zephyr_sys::raw::net_capture_interface_api as CaptureApi;
+
+const CONTEXT: CaptureWrapperContext = CaptureWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+fn not_implemented() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::NotImplemented, &CONTEXT)
+}
+
+/// Turns on the promiscuous mode of `iface`, the equivalent of the inlined
+/// `net_promisc_mode_on`.
+///
+/// Fails with [ErrorNumber::Already] if the mode is already on and with
+/// [ErrorNumber::NotSupported] if the driver has no promiscuous mode.
+pub fn promiscuous_on(iface: NetworkInterface) -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::net_if_set_promisc(iface.as_raw()) })
+}
+
+/// Turns off the promiscuous mode of `iface`, the equivalent of the inlined
+/// `net_promisc_mode_off`.
+pub fn promiscuous_off(iface: NetworkInterface) {
+    unsafe { zephyr_sys::raw::net_if_unset_promisc(iface.as_raw()) };
+}
+
+/// Whether the promiscuous mode of `iface` is on.
+pub fn is_promiscuous(iface: NetworkInterface) -> bool {
+    unsafe { zephyr_sys::raw::net_if_is_promisc(iface.as_raw()) }
+}
+
+/// Wrapper to the `net_promisc_mode_wait_data` function, waits up to `timeout` for the next
+/// frame received by an interface in promiscuous mode. The frame starts with the link layer
+/// header.
+pub fn wait_packet(timeout: Timeout) -> Option<NetPkt> {
+    unsafe { NetPkt::from_raw(zephyr_sys::raw::net_promisc_mode_wait_data(timeout)) }
+}
+
+/// Link type of Ethernet frames in [PcapWriter].
+pub const LINKTYPE_ETHERNET: u32 = 1;
+/// Link type of IEEE 802.15.4 frames without the frame check sequence in [PcapWriter].
+pub const LINKTYPE_IEEE802_15_4_NOFCS: u32 = 230;
+
+/// Magic number of pcap files with timestamps in microseconds.
+#[cfg(feature = "std")]
+const PCAP_MAGIC: u32 = 0xa1b2_c3d4;
+/// Largest length of the frames in the file, longer frames are cut.
+#[cfg(feature = "std")]
+const PCAP_SNAPLEN: u32 = 65535;
+
+/// Writer of frames in the pcap format (version 2.4, native byte order), requires the `std`
+/// feature.
+///
+/// Frames are recorded with the time of the wall clock of [crate::time] if it is set and the
+/// uptime otherwise.
+#[cfg(feature = "std")]
+pub struct PcapWriter<W: io::Write> {
+    writer: W,
+}
+
+#[cfg(feature = "std")]
+impl<W: io::Write> PcapWriter<W> {
+    /// Writes the file header with the link type of the frames to `writer`.
+    pub fn new(mut writer: W, link_type: u32) -> io::Result<Self> {
+        let mut header = [0u8; 24];
+        header[0..4].copy_from_slice(&PCAP_MAGIC.to_ne_bytes());
+        header[4..6].copy_from_slice(&2u16.to_ne_bytes());
+        header[6..8].copy_from_slice(&4u16.to_ne_bytes());
+        // the time zone and the accuracy of the timestamps stay zero
+        header[16..20].copy_from_slice(&PCAP_SNAPLEN.to_ne_bytes());
+        header[20..24].copy_from_slice(&link_type.to_ne_bytes());
+        writer.write_all(&header)?;
+        Ok(PcapWriter { writer })
+    }
+
+    fn write_header(&mut self, timestamp: Duration, len: usize) -> io::Result<()> {
+        let captured = (len as u32).min(PCAP_SNAPLEN);
+        let mut header = [0u8; 16];
+        header[0..4].copy_from_slice(&(timestamp.as_secs() as u32).to_ne_bytes());
+        header[4..8].copy_from_slice(&timestamp.subsec_micros().to_ne_bytes());
+        header[8..12].copy_from_slice(&captured.to_ne_bytes());
+        header[12..16].copy_from_slice(&(len as u32).to_ne_bytes());
+        self.writer.write_all(&header)
+    }
+
+    /// Writes the record of `frame` received or sent at `timestamp`, the time since the Unix
+    /// epoch.
+    pub fn write_frame(&mut self, timestamp: Duration, frame: &[u8]) -> io::Result<()> {
+        self.write_header(timestamp, frame.len())?;
+        let captured = frame.len().min(PCAP_SNAPLEN as usize);
+        self.writer.write_all(&frame[..captured])
+    }
+
+    /// Writes the record of the data of all fragments of `pkt` at the current time.
+    pub fn write_packet(&mut self, pkt: &NetPkt) -> io::Result<()> {
+        let now = crate::time::wall_clock_since_epoch().unwrap_or_else(crate::time::uptime);
+        self.write_header(now, pkt.len())?;
+        let mut remaining = PCAP_SNAPLEN as usize;
+        for fragment in pkt.fragments() {
+            let len = fragment.len().min(remaining);
+            self.writer.write_all(&fragment[..len])?;
+            remaining -= len;
+        }
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Packet capture of Zephyr, which sends the frames of an interface to a remote host through an
+/// IP tunnel. The capture is cleaned up when dropped.
+pub struct Capture {
+    device: &'static Device,
+}
+
+impl Capture {
+    /// Wrapper to the `net_capture_setup` function, creates the tunnel from `local` to `remote`,
+    /// `peer` is the address of the tunnel interface on the remote host.
+    pub fn setup(remote: IpAddr, local: IpAddr, peer: IpAddr) -> ZephyrResult<Capture> {
+        // the addresses of std::net never contain a nul byte
+        let remote = CString::new(remote.to_string()).unwrap();
+        let local = CString::new(local.to_string()).unwrap();
+        let peer = CString::new(peer.to_string()).unwrap();
+        let mut device: *const Device = core::ptr::null();
+        result(unsafe {
+            zephyr_sys::raw::net_capture_setup(
+                remote.as_ptr(),
+                local.as_ptr(),
+                peer.as_ptr(),
+                &mut device,
+            )
+        })?;
+        // the device is valid after a successful setup
+        let device = unsafe { device.as_ref() }
+            .ok_or_else(|| ZephyrError::new_with_context(ErrorNumber::NoDevice, &CONTEXT))?;
+        Ok(Capture { device })
+    }
+
+    fn api(&self) -> &'static CaptureApi {
+        // the device is a capture device created by net_capture_setup
+        unsafe { &*(self.device.api as *const CaptureApi) }
+    }
+
+    /// Starts sending the traffic of `iface` through the tunnel, the equivalent of the inlined
+    /// `net_capture_enable`.
+    pub fn enable(&self, iface: NetworkInterface) -> ZephyrResult<()> {
+        let enable = self.api().enable.ok_or_else(not_implemented)?;
+        result(unsafe { enable(self.device as *const Device, iface.as_raw()) })
+    }
+
+    /// Stops sending traffic, the equivalent of the inlined `net_capture_disable`.
+    pub fn disable(&self) -> ZephyrResult<()> {
+        let disable = self.api().disable.ok_or_else(not_implemented)?;
+        result(unsafe { disable(self.device as *const Device) })
+    }
+
+    /// Whether traffic is sent through the tunnel, the equivalent of the inlined
+    /// `net_capture_is_enabled`.
+    pub fn is_enabled(&self) -> bool {
+        match self.api().is_enabled {
+            Some(is_enabled) => unsafe { is_enabled(self.device as *const Device) },
+            None => false,
+        }
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        // the equivalent of the inlined net_capture_cleanup
+        if let Some(cleanup) = self.api().cleanup {
+            unsafe { cleanup(self.device as *const Device) };
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CaptureWrapperContext {}
+
+impl Context for CaptureWrapperContext {
+    fn name(&self) -> &'static str {
+        "net_capture wrapper"
+    }
+}
@@ -0,0 +1,253 @@
+//! CoAP client over a UDP socket.
+//!
+//! Confirmable requests are retransmitted with an exponential back-off until they are acknowledged
+//! (RFC 7252, section 4.2), separate responses are acknowledged by the client. Large resources are
+//! fetched with block-wise transfers (RFC 7959) using the block context of Zephyr.
+
+use super::{CoapPacket, Code, MessageType, CONTEXT, DEFAULT_PACKET_SIZE};
+use crate::network::socket::UdpSocket;
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use core::time::Duration;
+use zephyr_sys::raw::coap_block_context as ZBlockContext;
+
+/// Size of the blocks of a block-wise transfer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BlockSize {
+    Block16,
+    Block32,
+    Block64,
+    Block128,
+    Block256,
+    Block512,
+    Block1024,
+}
+
+impl BlockSize {
+    fn to_raw(self) -> zephyr_sys::raw::coap_block_size {
+        match self {
+            BlockSize::Block16 => zephyr_sys::raw::coap_block_size_COAP_BLOCK_16,
+            BlockSize::Block32 => zephyr_sys::raw::coap_block_size_COAP_BLOCK_32,
+            BlockSize::Block64 => zephyr_sys::raw::coap_block_size_COAP_BLOCK_64,
+            BlockSize::Block128 => zephyr_sys::raw::coap_block_size_COAP_BLOCK_128,
+            BlockSize::Block256 => zephyr_sys::raw::coap_block_size_COAP_BLOCK_256,
+            BlockSize::Block512 => zephyr_sys::raw::coap_block_size_COAP_BLOCK_512,
+            BlockSize::Block1024 => zephyr_sys::raw::coap_block_size_COAP_BLOCK_1024,
+        }
+    }
+
+    /// Size of a block in bytes.
+    pub fn bytes(self) -> usize {
+        16 << (self.to_raw() as usize)
+    }
+}
+
+/// Client sending requests to a single CoAP server.
+pub struct CoapClient {
+    socket: UdpSocket,
+    ack_timeout: Duration,
+    max_retransmit: u32,
+    block_size: BlockSize,
+}
+
+impl CoapClient {
+    /// Initial timeout of a confirmable request.
+    pub const ACK_TIMEOUT: Duration = Duration::from_secs(2);
+    /// Retransmissions of a confirmable request before it fails.
+    pub const MAX_RETRANSMIT: u32 = 4;
+
+    /// Creates a client for the server at `server`, the socket is bound to a port chosen by the
+    /// network stack.
+    pub fn new(server: SocketAddr) -> ZephyrResult<Self> {
+        let local: SocketAddr = match server {
+            SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+            SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.connect(server)?;
+        Ok(CoapClient {
+            socket,
+            ack_timeout: Self::ACK_TIMEOUT,
+            max_retransmit: Self::MAX_RETRANSMIT,
+            block_size: BlockSize::Block256,
+        })
+    }
+
+    /// Set the initial timeout of confirmable requests, which doubles with each retransmission.
+    pub fn set_ack_timeout(&mut self, timeout: Duration) {
+        self.ack_timeout = timeout;
+    }
+
+    /// Set the number of retransmissions of confirmable requests.
+    pub fn set_max_retransmit(&mut self, max_retransmit: u32) {
+        self.max_retransmit = max_retransmit;
+    }
+
+    /// Set the size of the blocks requested by [CoapClient::get_blockwise].
+    pub fn set_block_size(&mut self, block_size: BlockSize) {
+        self.block_size = block_size;
+    }
+
+    /// Sends `request` and returns the response.
+    ///
+    /// Confirmable requests are retransmitted until they are acknowledged, a separate response
+    /// is awaited for as long as the last retransmission. Responses with error codes are returned
+    /// as well, use [Code::is_success] to check them.
+    pub fn request(&self, request: &CoapPacket) -> ZephyrResult<CoapPacket> {
+        let confirmable = request.message_type() == MessageType::Confirmable;
+        let attempts = if confirmable { self.max_retransmit + 1 } else { 1 };
+        let id = request.id();
+        let token = request.token();
+        let mut timeout = self.ack_timeout;
+        let mut acknowledged = false;
+
+        for _ in 0..attempts {
+            if !acknowledged {
+                self.socket.send(request.as_bytes())?;
+            }
+            self.socket.set_read_timeout(Some(timeout))?;
+
+            loop {
+                let response = match self.receive() {
+                    Ok(response) => response,
+                    Err(e) if e.is_would_block() => break,
+                    Err(e) => return Err(e),
+                };
+
+                match response.message_type() {
+                    MessageType::Acknowledgement if response.id() == id => {
+                        if response.code() == Code::EMPTY {
+                            // the response follows in a separate message
+                            acknowledged = true;
+                            continue;
+                        }
+                        return Ok(response);
+                    }
+                    MessageType::Reset if response.id() == id => {
+                        return Err(ZephyrError::new_with_context(
+                            ErrorNumber::NotConnected,
+                            &CONTEXT,
+                        ));
+                    }
+                    MessageType::Confirmable | MessageType::NonConfirmable
+                        if response.token() == token =>
+                    {
+                        if response.message_type() == MessageType::Confirmable {
+                            self.acknowledge(&response)?;
+                        }
+                        return Ok(response);
+                    }
+                    // messages of other exchanges are ignored
+                    _ => {}
+                }
+            }
+            timeout = timeout.saturating_mul(2);
+        }
+
+        Err(ZephyrError::new_with_context(ErrorNumber::TryAgain, &CONTEXT))
+    }
+
+    fn receive(&self) -> ZephyrResult<CoapPacket> {
+        let mut buffer = vec![0u8; DEFAULT_PACKET_SIZE.max(self.block_size.bytes() + 64)];
+        let count = self.socket.recv(&mut buffer)?;
+        buffer.truncate(count);
+        CoapPacket::parse(buffer)
+    }
+
+    fn acknowledge(&self, response: &CoapPacket) -> ZephyrResult<()> {
+        let ack = CoapPacket::new(
+            DEFAULT_PACKET_SIZE,
+            MessageType::Acknowledgement,
+            Code::EMPTY,
+            response.id(),
+            &[],
+        )?;
+        self.socket.send(ack.as_bytes())?;
+        Ok(())
+    }
+
+    /// Sends a GET request for `path`.
+    pub fn get(&self, path: &str) -> ZephyrResult<CoapPacket> {
+        self.request(&CoapPacket::request(Code::GET, path)?)
+    }
+
+    /// Sends a DELETE request for `path`.
+    pub fn delete(&self, path: &str) -> ZephyrResult<CoapPacket> {
+        self.request(&CoapPacket::request(Code::DELETE, path)?)
+    }
+
+    /// Sends a PUT request with `payload` of `content_format` for `path`.
+    pub fn put(&self, path: &str, payload: &[u8], content_format: u16) -> ZephyrResult<CoapPacket> {
+        self.request_with_payload(Code::PUT, path, payload, content_format)
+    }
+
+    /// Sends a POST request with `payload` of `content_format` for `path`.
+    pub fn post(
+        &self,
+        path: &str,
+        payload: &[u8],
+        content_format: u16,
+    ) -> ZephyrResult<CoapPacket> {
+        self.request_with_payload(Code::POST, path, payload, content_format)
+    }
+
+    fn request_with_payload(
+        &self,
+        method: Code,
+        path: &str,
+        payload: &[u8],
+        content_format: u16,
+    ) -> ZephyrResult<CoapPacket> {
+        let mut request = CoapPacket::new(
+            DEFAULT_PACKET_SIZE.max(payload.len() + 64),
+            MessageType::Confirmable,
+            method,
+            super::next_id(),
+            &super::next_token(),
+        )?;
+        request.append_path(path)?;
+        request.append_content_format(content_format)?;
+        request.append_payload(payload)?;
+        self.request(&request)
+    }
+
+    /// Fetches the resource `path` with a block-wise transfer of `Block2` options, returns the
+    /// whole payload.
+    ///
+    /// Fails with [ErrorNumber::Io] if the server responds with an error code.
+    pub fn get_blockwise(&self, path: &str) -> ZephyrResult<Vec<u8>> {
+        // zeroed as the context is initialized below
+        let mut context: ZBlockContext = unsafe { core::mem::zeroed() };
+        super::result(unsafe {
+            zephyr_sys::raw::coap_block_transfer_init(&mut context, self.block_size.to_raw(), 0)
+        })?;
+
+        let mut payload = Vec::new();
+        loop {
+            let mut request = CoapPacket::request(Code::GET, path)?;
+            super::result(unsafe {
+                zephyr_sys::raw::coap_append_block2_option(request.as_raw(), &mut context)
+            })?;
+            let mut response = self.request(&request)?;
+            if !response.code().is_success() {
+                return Err(ZephyrError::new_with_context(ErrorNumber::Io, &CONTEXT));
+            }
+            payload.extend_from_slice(response.payload());
+
+            super::result(unsafe {
+                zephyr_sys::raw::coap_update_from_block(response.as_raw(), &mut context)
+            })?;
+            let next = unsafe { zephyr_sys::raw::coap_next_block(response.as_raw(), &mut context) };
+            if next == 0 {
+                return Ok(payload);
+            }
+        }
+    }
+
+    /// The socket of the client, e.g. to [crate::network::poll::poll] it.
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+}
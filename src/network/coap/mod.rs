@@ -0,0 +1,409 @@
+//! Wrappers for the CoAP library of Zephyr (`CONFIG_COAP`).
+//!
+//! A [CoapPacket] builds or parses a CoAP message in an owned buffer, [client::CoapClient] sends
+//! requests over a [crate::network::socket::UdpSocket] and [server::CoapServer] dispatches
+//! requests to Rust handlers of resources:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::coap::client::CoapClient;
+//! let client = CoapClient::new(([192, 0, 2, 1], 5683).into()).unwrap();
+//! let response = client.get("sensors/temperature").unwrap();
+//! println!("{}: {:?}", response.code(), response.payload());
+//! ```
+
+pub mod client;
+pub mod server;
+
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::c_int;
+use core::fmt::{Display, Formatter};
+use zephyr_sys::raw::{coap_option as ZCoapOption, coap_packet as ZCoapPacket};
+
+const CONTEXT: CoapWrapperContext = CoapWrapperContext {};
+
+/// The CoAP functions return a negative error number on errors.
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno >= 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Default size of the buffer of a packet, which fits the default block size of 256 bytes.
+pub const DEFAULT_PACKET_SIZE: usize = 320;
+
+/// Maximum length of a token.
+pub const TOKEN_MAX_LEN: usize = zephyr_sys::raw::COAP_TOKEN_MAX_LEN as usize;
+
+/// Type of a CoAP message.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MessageType {
+    /// Message that is retransmitted until it is acknowledged.
+    Confirmable,
+    NonConfirmable,
+    Acknowledgement,
+    Reset,
+}
+
+impl From<MessageType> for u8 {
+    fn from(other: MessageType) -> Self {
+        (match other {
+            MessageType::Confirmable => zephyr_sys::raw::coap_msgtype_COAP_TYPE_CON,
+            MessageType::NonConfirmable => zephyr_sys::raw::coap_msgtype_COAP_TYPE_NON_CON,
+            MessageType::Acknowledgement => zephyr_sys::raw::coap_msgtype_COAP_TYPE_ACK,
+            MessageType::Reset => zephyr_sys::raw::coap_msgtype_COAP_TYPE_RESET,
+        }) as u8
+    }
+}
+
+impl From<u8> for MessageType {
+    fn from(other: u8) -> Self {
+        match other as u32 {
+            zephyr_sys::raw::coap_msgtype_COAP_TYPE_CON => MessageType::Confirmable,
+            zephyr_sys::raw::coap_msgtype_COAP_TYPE_NON_CON => MessageType::NonConfirmable,
+            zephyr_sys::raw::coap_msgtype_COAP_TYPE_ACK => MessageType::Acknowledgement,
+            _ => MessageType::Reset,
+        }
+    }
+}
+
+/// Code of a CoAP message, the method of requests or the response code of responses.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Code(pub u8);
+
+impl Code {
+    /// Code of empty messages, e.g. acknowledgements of separate responses.
+    pub const EMPTY: Code = Code(0);
+    pub const GET: Code = Code(zephyr_sys::raw::coap_method_COAP_METHOD_GET as u8);
+    pub const POST: Code = Code(zephyr_sys::raw::coap_method_COAP_METHOD_POST as u8);
+    pub const PUT: Code = Code(zephyr_sys::raw::coap_method_COAP_METHOD_PUT as u8);
+    pub const DELETE: Code = Code(zephyr_sys::raw::coap_method_COAP_METHOD_DELETE as u8);
+    pub const CREATED: Code = Code(Self::response(2, 1));
+    pub const DELETED: Code = Code(Self::response(2, 2));
+    pub const VALID: Code = Code(Self::response(2, 3));
+    pub const CHANGED: Code = Code(Self::response(2, 4));
+    pub const CONTENT: Code = Code(Self::response(2, 5));
+    pub const CONTINUE: Code = Code(Self::response(2, 31));
+    pub const BAD_REQUEST: Code = Code(Self::response(4, 0));
+    pub const UNAUTHORIZED: Code = Code(Self::response(4, 1));
+    pub const FORBIDDEN: Code = Code(Self::response(4, 3));
+    pub const NOT_FOUND: Code = Code(Self::response(4, 4));
+    pub const METHOD_NOT_ALLOWED: Code = Code(Self::response(4, 5));
+    pub const REQUEST_ENTITY_INCOMPLETE: Code = Code(Self::response(4, 8));
+    pub const REQUEST_TOO_LARGE: Code = Code(Self::response(4, 13));
+    pub const UNSUPPORTED_CONTENT_FORMAT: Code = Code(Self::response(4, 15));
+    pub const INTERNAL_ERROR: Code = Code(Self::response(5, 0));
+    pub const NOT_IMPLEMENTED: Code = Code(Self::response(5, 1));
+    pub const SERVICE_UNAVAILABLE: Code = Code(Self::response(5, 3));
+
+    const fn response(class: u8, detail: u8) -> u8 {
+        (class << 5) | detail
+    }
+
+    /// Class of the code, `0` for requests, `2` for success, `4` for client and `5` for server
+    /// errors.
+    pub fn class(&self) -> u8 {
+        self.0 >> 5
+    }
+
+    /// Detail of the code.
+    pub fn detail(&self) -> u8 {
+        self.0 & 0x1f
+    }
+
+    /// Returns `true` for the response codes of class `2`.
+    pub fn is_success(&self) -> bool {
+        self.class() == 2
+    }
+}
+
+impl Display for Code {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{:02}", self.class(), self.detail())
+    }
+}
+
+/// Number of a CoAP option.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct OptionNumber(pub u16);
+
+impl OptionNumber {
+    pub const IF_MATCH: OptionNumber = OptionNumber(1);
+    pub const URI_HOST: OptionNumber = OptionNumber(3);
+    pub const ETAG: OptionNumber = OptionNumber(4);
+    pub const OBSERVE: OptionNumber = OptionNumber(6);
+    pub const URI_PORT: OptionNumber = OptionNumber(7);
+    pub const URI_PATH: OptionNumber = OptionNumber(11);
+    pub const CONTENT_FORMAT: OptionNumber = OptionNumber(12);
+    pub const MAX_AGE: OptionNumber = OptionNumber(14);
+    pub const URI_QUERY: OptionNumber = OptionNumber(15);
+    pub const ACCEPT: OptionNumber = OptionNumber(17);
+    pub const BLOCK2: OptionNumber = OptionNumber(23);
+    pub const BLOCK1: OptionNumber = OptionNumber(27);
+    pub const SIZE2: OptionNumber = OptionNumber(28);
+    pub const SIZE1: OptionNumber = OptionNumber(60);
+}
+
+/// Content formats of the `Content-Format` and `Accept` options.
+pub mod content_format {
+    pub const TEXT_PLAIN: u16 = 0;
+    pub const LINK_FORMAT: u16 = 40;
+    pub const XML: u16 = 41;
+    pub const OCTET_STREAM: u16 = 42;
+    pub const JSON: u16 = 50;
+    pub const CBOR: u16 = 60;
+}
+
+/// CoAP message in an owned buffer, the equivalent of `struct coap_packet`.
+///
+/// The raw packet is the first field, so pointers passed to the handlers of the
+/// [server::CoapServer] can be cast back to the packet.
+#[repr(C)]
+pub struct CoapPacket {
+    raw: ZCoapPacket,
+    // the raw packet points into the heap allocation of the buffer, which does not move
+    buffer: Vec<u8>,
+}
+
+impl CoapPacket {
+    /// Wrapper to the `coap_packet_init` function.
+    ///
+    /// Creates a message with a buffer of `capacity` bytes, options and the payload are appended
+    /// with the other methods in the order of their option numbers.
+    pub fn new(
+        capacity: usize,
+        message_type: MessageType,
+        code: Code,
+        id: u16,
+        token: &[u8],
+    ) -> ZephyrResult<Self> {
+        if token.len() > TOKEN_MAX_LEN || capacity > u16::MAX as usize {
+            return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT));
+        }
+        let mut packet = CoapPacket {
+            // zeroed as the packet is initialized below
+            raw: unsafe { core::mem::zeroed() },
+            buffer: vec![0; capacity],
+        };
+        result(unsafe {
+            zephyr_sys::raw::coap_packet_init(
+                &mut packet.raw,
+                packet.buffer.as_mut_ptr(),
+                capacity as u16,
+                zephyr_sys::raw::COAP_VERSION_1 as u8,
+                message_type.into(),
+                token.len() as u8,
+                token.as_ptr(),
+                code.0,
+                id,
+            )
+        })?;
+        Ok(packet)
+    }
+
+    /// Creates a confirmable request for `path` (segments separated by `/`) with a new message id
+    /// and token.
+    pub fn request(method: Code, path: &str) -> ZephyrResult<Self> {
+        let mut packet = Self::new(
+            DEFAULT_PACKET_SIZE,
+            MessageType::Confirmable,
+            method,
+            next_id(),
+            &next_token(),
+        )?;
+        packet.append_path(path)?;
+        Ok(packet)
+    }
+
+    /// Creates the piggybacked acknowledgement of `request` with `code`, or a non-confirmable
+    /// response with the token of `request` if `request` is not confirmable.
+    pub fn response_to(request: &CoapPacket, code: Code) -> ZephyrResult<Self> {
+        let (message_type, id) = match request.message_type() {
+            MessageType::Confirmable => (MessageType::Acknowledgement, request.id()),
+            _ => (MessageType::NonConfirmable, next_id()),
+        };
+        Self::new(DEFAULT_PACKET_SIZE, message_type, code, id, &request.token())
+    }
+
+    /// Wrapper to the `coap_packet_parse` function, parses the message `data`.
+    pub fn parse(mut data: Vec<u8>) -> ZephyrResult<Self> {
+        if data.len() > u16::MAX as usize {
+            return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT));
+        }
+        let mut packet = CoapPacket {
+            // zeroed as the packet is initialized below
+            raw: unsafe { core::mem::zeroed() },
+            buffer: Vec::new(),
+        };
+        result(unsafe {
+            zephyr_sys::raw::coap_packet_parse(
+                &mut packet.raw,
+                data.as_mut_ptr(),
+                data.len() as u16,
+                core::ptr::null_mut(),
+                0,
+            )
+        })?;
+        packet.buffer = data;
+        Ok(packet)
+    }
+
+    /// Wrapper to the `coap_packet_append_option` function.
+    pub fn append_option(&mut self, number: OptionNumber, value: &[u8]) -> ZephyrResult<()> {
+        result(unsafe {
+            zephyr_sys::raw::coap_packet_append_option(
+                &mut self.raw,
+                number.0,
+                value.as_ptr(),
+                value.len() as u16,
+            )
+        })
+    }
+
+    /// Wrapper to the `coap_append_option_int` function, appends an option with an integer value.
+    pub fn append_uint_option(&mut self, number: OptionNumber, value: u32) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::coap_append_option_int(&mut self.raw, number.0, value) })
+    }
+
+    /// Appends a `Uri-Path` option for each segment of `path`.
+    pub fn append_path(&mut self, path: &str) -> ZephyrResult<()> {
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            self.append_option(OptionNumber::URI_PATH, segment.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Appends the `Content-Format` option.
+    pub fn append_content_format(&mut self, format: u16) -> ZephyrResult<()> {
+        self.append_uint_option(OptionNumber::CONTENT_FORMAT, format as u32)
+    }
+
+    /// Wrapper to the `coap_packet_append_payload_marker` and `coap_packet_append_payload`
+    /// functions, must be called after all options have been appended.
+    pub fn append_payload(&mut self, payload: &[u8]) -> ZephyrResult<()> {
+        if payload.is_empty() {
+            return Ok(());
+        }
+        result(unsafe { zephyr_sys::raw::coap_packet_append_payload_marker(&mut self.raw) })?;
+        result(unsafe {
+            zephyr_sys::raw::coap_packet_append_payload(
+                &mut self.raw,
+                payload.as_ptr(),
+                payload.len() as u16,
+            )
+        })
+    }
+
+    /// Type of the message.
+    pub fn message_type(&self) -> MessageType {
+        unsafe { zephyr_sys::raw::coap_header_get_type(&self.raw) }.into()
+    }
+
+    /// Code of the message.
+    pub fn code(&self) -> Code {
+        Code(unsafe { zephyr_sys::raw::coap_header_get_code(&self.raw) })
+    }
+
+    /// Message id, used to match acknowledgements.
+    pub fn id(&self) -> u16 {
+        unsafe { zephyr_sys::raw::coap_header_get_id(&self.raw) }
+    }
+
+    /// Token of the message, used to match responses to requests.
+    pub fn token(&self) -> Vec<u8> {
+        let mut token = [0u8; TOKEN_MAX_LEN];
+        let len = unsafe { zephyr_sys::raw::coap_header_get_token(&self.raw, token.as_mut_ptr()) };
+        token[..len as usize].to_vec()
+    }
+
+    /// Wrapper to the `coap_packet_get_payload` function.
+    pub fn payload(&self) -> &[u8] {
+        let mut len: u16 = 0;
+        let payload = unsafe { zephyr_sys::raw::coap_packet_get_payload(&self.raw, &mut len) };
+        if payload.is_null() {
+            &[]
+        } else {
+            // the payload is part of the buffer
+            unsafe { core::slice::from_raw_parts(payload, len as usize) }
+        }
+    }
+
+    /// Wrapper to the `coap_find_options` function, returns the values of all options `number`.
+    ///
+    /// Values are truncated to the value size of `struct coap_option`.
+    pub fn options(&self, number: OptionNumber) -> Vec<Vec<u8>> {
+        const MAX_OPTIONS: usize = 16;
+        // zeroed as the options are written by the function
+        let mut options: [ZCoapOption; MAX_OPTIONS] = unsafe { core::mem::zeroed() };
+        let count = unsafe {
+            zephyr_sys::raw::coap_find_options(
+                &self.raw,
+                number.0,
+                options.as_mut_ptr(),
+                MAX_OPTIONS as u16,
+            )
+        };
+        options[..count.max(0) as usize]
+            .iter()
+            .map(|option| option.value[..option.len as usize].to_vec())
+            .collect()
+    }
+
+    /// Value of the first option `number`.
+    pub fn option(&self, number: OptionNumber) -> Option<Vec<u8>> {
+        self.options(number).into_iter().next()
+    }
+
+    /// Integer value of the first option `number`, options are encoded in network byte order
+    /// without leading zeros.
+    pub fn uint_option(&self, number: OptionNumber) -> Option<u32> {
+        self.option(number)
+            .map(|value| value.iter().fold(0, |acc, byte| (acc << 8) | *byte as u32))
+    }
+
+    /// Path of a request, the `Uri-Path` options joined with `/`.
+    pub fn path(&self) -> String {
+        self.options(OptionNumber::URI_PATH)
+            .iter()
+            .map(|segment| String::from_utf8_lossy(segment))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Encoded message.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer[..self.raw.offset as usize]
+    }
+
+    /// Pointer to the packet for the Zephyr API.
+    pub fn as_raw(&mut self) -> *mut ZCoapPacket {
+        &mut self.raw
+    }
+}
+
+/// Wrapper to the `coap_next_id` function, returns a new message id.
+pub fn next_id() -> u16 {
+    unsafe { zephyr_sys::raw::coap_next_id() }
+}
+
+/// Wrapper to the `coap_next_token` function, returns a new random token.
+pub fn next_token() -> [u8; TOKEN_MAX_LEN] {
+    let mut token = [0u8; TOKEN_MAX_LEN];
+    let next = unsafe { zephyr_sys::raw::coap_next_token() };
+    // the function returns a static buffer of TOKEN_MAX_LEN bytes
+    token.copy_from_slice(unsafe { core::slice::from_raw_parts(next, TOKEN_MAX_LEN) });
+    token
+}
+
+#[derive(Debug)]
+struct CoapWrapperContext {}
+
+impl Context for CoapWrapperContext {
+    fn name(&self) -> &'static str {
+        "coap wrapper"
+    }
+}
@@ -0,0 +1,511 @@
+//! CoAP server dispatching requests to Rust handlers of resources.
+//!
+//! Resources are registered with their path and a handler per method, requests are matched to
+//! them by `coap_handle_request` of Zephyr. The services of `CONFIG_COAP_SERVER`
+//! (`COAP_SERVICE_DEFINE`) are collected in iterable sections at link time, so the server uses the
+//! resource and observer API of the CoAP library on a [UdpSocket] instead:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::coap::{content_format, Code};
+//!# use zephyr_rust_wrappers::network::coap::server::{CoapServer, Response};
+//! let mut server = CoapServer::bind(([0, 0, 0, 0], 5683).into()).unwrap();
+//! server
+//!     .resource("sensors/temperature", Code::GET, |_| {
+//!         Response::content(content_format::TEXT_PLAIN, b"21.5".to_vec())
+//!     })
+//!     .unwrap();
+//! server.observable("sensors/temperature").unwrap();
+//! server.run().unwrap();
+//! ```
+
+use super::{CoapPacket, Code, MessageType, OptionNumber, CONTEXT, DEFAULT_PACKET_SIZE};
+use crate::network::addr::{from_sockaddr, socklen_t, RawSocketAddr, ZSockAddr};
+use crate::network::socket::UdpSocket;
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::{c_char, c_int};
+use core::net::SocketAddr;
+use core::time::Duration;
+use zephyr_sys::raw::{
+    coap_observer as ZCoapObserver, coap_option as ZCoapOption, coap_packet as ZCoapPacket,
+    coap_resource as ZCoapResource,
+};
+
+/// Size of the buffer requests are received into.
+pub const RECEIVE_BUFFER_SIZE: usize = 1024;
+
+/// Maximum number of segments of the path of a resource.
+const MAX_PATH_SEGMENTS: usize = 16;
+
+/// Methods with a handler, in the order of the handlers of a [Resource].
+const METHODS: [Code; 4] = [Code::GET, Code::POST, Code::PUT, Code::DELETE];
+
+/// Handler of a method of a resource.
+type Handler = dyn FnMut(&Request<'_>) -> Response + Send;
+
+/// Request passed to a handler.
+pub struct Request<'a> {
+    packet: Option<&'a CoapPacket>,
+    peer: SocketAddr,
+}
+
+impl<'a> Request<'a> {
+    /// The received request, `None` if the `GET` handler is called for a notification of an
+    /// observer.
+    pub fn packet(&self) -> Option<&'a CoapPacket> {
+        self.packet
+    }
+
+    /// Address of the client.
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// Payload of the request, empty for notifications.
+    pub fn payload(&self) -> &'a [u8] {
+        self.packet.map(|packet| packet.payload()).unwrap_or(&[])
+    }
+
+    /// Returns `true` if the handler is called for a notification of an observer.
+    pub fn is_notification(&self) -> bool {
+        self.packet.is_none()
+    }
+}
+
+/// Response returned by a handler.
+#[derive(Clone, Debug)]
+pub struct Response {
+    pub code: Code,
+    pub content_format: Option<u16>,
+    pub payload: Vec<u8>,
+}
+
+impl Response {
+    /// Response with `code` and without payload.
+    pub fn new(code: Code) -> Self {
+        Response {
+            code,
+            content_format: None,
+            payload: Vec::new(),
+        }
+    }
+
+    /// `2.05 Content` response with `payload` of `content_format`.
+    pub fn content(content_format: u16, payload: Vec<u8>) -> Self {
+        Response {
+            code: Code::CONTENT,
+            content_format: Some(content_format),
+            payload,
+        }
+    }
+}
+
+/// Resource registered with the server, `user_data` of the raw resource points to it.
+struct Resource {
+    path: String,
+    // the raw resource points to the pointers, which point to the segments
+    _segments: Vec<CString>,
+    segment_ptrs: Vec<*const c_char>,
+    handlers: [Option<Box<Handler>>; 4],
+    observable: bool,
+    observers: Vec<Box<ZCoapObserver>>,
+    // messages built by the trampolines, sent by the server once Zephyr returns
+    outbox: Vec<(SocketAddr, CoapPacket)>,
+}
+
+impl Resource {
+    fn new(path: &str) -> ZephyrResult<Self> {
+        let segments = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(CString::new)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))?;
+        if segments.len() > MAX_PATH_SEGMENTS {
+            return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT));
+        }
+        let mut segment_ptrs: Vec<*const c_char> = segments.iter().map(|s| s.as_ptr()).collect();
+        segment_ptrs.push(core::ptr::null());
+        Ok(Resource {
+            path: segments
+                .iter()
+                .map(|s| s.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            _segments: segments,
+            segment_ptrs,
+            handlers: [None, None, None, None],
+            observable: false,
+            observers: Vec::new(),
+            outbox: Vec::new(),
+        })
+    }
+
+    fn to_raw(&mut self) -> ZCoapResource {
+        // zeroed as the unused methods are None and the list of observers is empty
+        let mut raw: ZCoapResource = unsafe { core::mem::zeroed() };
+        raw.get = self.handlers[0].as_ref().map(|_| get_trampoline as _);
+        raw.post = self.handlers[1].as_ref().map(|_| post_trampoline as _);
+        raw.put = self.handlers[2].as_ref().map(|_| put_trampoline as _);
+        raw.del = self.handlers[3].as_ref().map(|_| delete_trampoline as _);
+        if self.observable {
+            raw.notify = Some(notify_trampoline);
+        }
+        raw.path = self.segment_ptrs.as_ptr();
+        raw.user_data = self as *mut Resource as *mut _;
+        raw
+    }
+
+    /// Calls the handler of `method` and queues the response to `peer`, a reply to `request` or a
+    /// notification with `token` if there is no request.
+    fn respond(
+        &mut self,
+        method: usize,
+        request: Option<&CoapPacket>,
+        peer: SocketAddr,
+        token: &[u8],
+        observe: Option<u32>,
+    ) -> ZephyrResult<()> {
+        let handler = match self.handlers[method].as_mut() {
+            Some(handler) => handler,
+            None => return Err(ZephyrError::new_with_context(ErrorNumber::Permission, &CONTEXT)),
+        };
+        let response = handler(&Request {
+            packet: request,
+            peer,
+        });
+
+        let (message_type, id) = match request {
+            Some(request) if request.message_type() == MessageType::Confirmable => {
+                (MessageType::Acknowledgement, request.id())
+            }
+            _ => (MessageType::NonConfirmable, super::next_id()),
+        };
+        let capacity = DEFAULT_PACKET_SIZE.max(response.payload.len() + 64);
+        let mut message = CoapPacket::new(capacity, message_type, response.code, id, token)?;
+        if let Some(observe) = observe {
+            message.append_uint_option(OptionNumber::OBSERVE, observe)?;
+        }
+        if let Some(format) = response.content_format {
+            message.append_content_format(format)?;
+        }
+        message.append_payload(&response.payload)?;
+        self.outbox.push((peer, message));
+        Ok(())
+    }
+
+    /// Handles the `Observe` option of a `GET` request, returns the value of the option of the
+    /// response if `peer` is registered as observer.
+    unsafe fn update_observers(
+        &mut self,
+        resource: *mut ZCoapResource,
+        request: &CoapPacket,
+        addr: *mut ZSockAddr,
+        peer: SocketAddr,
+    ) -> Option<u32> {
+        if !self.observable {
+            return None;
+        }
+        let observe = request.uint_option(OptionNumber::OBSERVE)?;
+        let token = request.token();
+        // a registration with the same token replaces the previous one
+        if let Some(index) = self.observers.iter().position(|observer| {
+            observer_peer(observer) == Some(peer)
+                && observer.token[..observer.tkl as usize] == token[..]
+        }) {
+            zephyr_sys::raw::coap_remove_observer(resource, &mut *self.observers[index]);
+            self.observers.remove(index);
+        }
+        if observe != 0 {
+            // deregistration
+            return None;
+        }
+
+        // zeroed as the observer is initialized below
+        let mut observer: Box<ZCoapObserver> = Box::new(core::mem::zeroed());
+        zephyr_sys::raw::coap_observer_init(&mut *observer, &request.raw, addr);
+        zephyr_sys::raw::coap_register_observer(resource, &mut *observer);
+        self.observers.push(observer);
+        Some((*resource).age as u32)
+    }
+}
+
+fn observer_peer(observer: &ZCoapObserver) -> Option<SocketAddr> {
+    from_sockaddr(&observer.addr)
+}
+
+unsafe fn dispatch(
+    method: usize,
+    resource: *mut ZCoapResource,
+    request: *mut ZCoapPacket,
+    addr: *mut ZSockAddr,
+) -> c_int {
+    let entry = &mut *((*resource).user_data as *mut Resource);
+    // the server only passes the raw packets of its packets, which are their first field
+    let packet = &*(request as *const CoapPacket);
+    let peer = match from_sockaddr(&*addr) {
+        Some(peer) => peer,
+        None => return -i32::from(ErrorNumber::InvalidArgument),
+    };
+    let observe = if method == 0 {
+        entry.update_observers(resource, packet, addr, peer)
+    } else {
+        None
+    };
+    match entry.respond(method, Some(packet), peer, &packet.token(), observe) {
+        Ok(()) => 0,
+        Err(e) => -i32::from(e.number()),
+    }
+}
+
+unsafe extern "C" fn get_trampoline(
+    resource: *mut ZCoapResource,
+    request: *mut ZCoapPacket,
+    addr: *mut ZSockAddr,
+    _addr_len: socklen_t,
+) -> c_int {
+    dispatch(0, resource, request, addr)
+}
+
+unsafe extern "C" fn post_trampoline(
+    resource: *mut ZCoapResource,
+    request: *mut ZCoapPacket,
+    addr: *mut ZSockAddr,
+    _addr_len: socklen_t,
+) -> c_int {
+    dispatch(1, resource, request, addr)
+}
+
+unsafe extern "C" fn put_trampoline(
+    resource: *mut ZCoapResource,
+    request: *mut ZCoapPacket,
+    addr: *mut ZSockAddr,
+    _addr_len: socklen_t,
+) -> c_int {
+    dispatch(2, resource, request, addr)
+}
+
+unsafe extern "C" fn delete_trampoline(
+    resource: *mut ZCoapResource,
+    request: *mut ZCoapPacket,
+    addr: *mut ZSockAddr,
+    _addr_len: socklen_t,
+) -> c_int {
+    dispatch(3, resource, request, addr)
+}
+
+unsafe extern "C" fn notify_trampoline(
+    resource: *mut ZCoapResource,
+    observer: *mut ZCoapObserver,
+) {
+    let entry = &mut *((*resource).user_data as *mut Resource);
+    let observer = &*observer;
+    if let Some(peer) = observer_peer(observer) {
+        let token = &observer.token[..observer.tkl as usize];
+        // errors of a single notification do not affect the other observers
+        let _ = entry.respond(0, None, peer, token, Some((*resource).age as u32));
+    }
+}
+
+/// CoAP server over a UDP socket.
+///
+/// All resources must be registered before the first request is handled, since Zephyr matches
+/// requests against a contiguous array of resources.
+pub struct CoapServer {
+    socket: UdpSocket,
+    resources: Vec<Box<Resource>>,
+    // empty until the first request is handled, terminated by a resource without path
+    raw: Vec<ZCoapResource>,
+}
+
+impl CoapServer {
+    /// Creates a server bound to `addr`, usually port 5683 of the unspecified address.
+    pub fn bind(addr: SocketAddr) -> ZephyrResult<Self> {
+        Ok(CoapServer {
+            socket: UdpSocket::bind(addr)?,
+            resources: Vec::new(),
+            raw: Vec::new(),
+        })
+    }
+
+    fn resource_mut(&mut self, path: &str) -> ZephyrResult<&mut Resource> {
+        if !self.raw.is_empty() {
+            return Err(ZephyrError::new_with_context(ErrorNumber::Busy, &CONTEXT));
+        }
+        let resource = Resource::new(path)?;
+        let index = match self.resources.iter().position(|r| r.path == resource.path) {
+            Some(index) => index,
+            None => {
+                self.resources.push(Box::new(resource));
+                self.resources.len() - 1
+            }
+        };
+        Ok(&mut self.resources[index])
+    }
+
+    /// Registers `handler` for requests of `method` (`GET`, `POST`, `PUT` or `DELETE`) to `path`,
+    /// replacing a previous handler.
+    ///
+    /// Requests to unknown paths are answered with `4.04 Not Found` and requests of methods
+    /// without handler with `4.05 Method Not Allowed`. Fails with [ErrorNumber::Busy] once the
+    /// server handles requests.
+    pub fn resource<F>(&mut self, path: &str, method: Code, handler: F) -> ZephyrResult<()>
+    where
+        F: FnMut(&Request<'_>) -> Response + Send + 'static,
+    {
+        let index = match METHODS.iter().position(|m| *m == method) {
+            Some(index) => index,
+            None => {
+                return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))
+            }
+        };
+        self.resource_mut(path)?.handlers[index] = Some(Box::new(handler));
+        Ok(())
+    }
+
+    /// Allows clients to observe `path` (RFC 7641) with the `Observe` option of `GET` requests.
+    ///
+    /// Notifications sent by [CoapServer::notify] are built by the `GET` handler of the resource.
+    pub fn observable(&mut self, path: &str) -> ZephyrResult<()> {
+        self.resource_mut(path)?.observable = true;
+        Ok(())
+    }
+
+    fn start(&mut self) {
+        if self.raw.is_empty() {
+            self.raw = self.resources.iter_mut().map(|r| r.to_raw()).collect();
+            // zeroed as the array is terminated by a resource without path
+            self.raw.push(unsafe { core::mem::zeroed() });
+        }
+    }
+
+    fn flush(&mut self) -> ZephyrResult<()> {
+        for resource in self.resources.iter_mut() {
+            for (peer, message) in resource.outbox.drain(..) {
+                self.socket.send_to(message.as_bytes(), peer)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn reply(&self, request: &CoapPacket, peer: SocketAddr, code: Code) -> ZephyrResult<()> {
+        let response = CoapPacket::response_to(request, code)?;
+        self.socket.send_to(response.as_bytes(), peer)?;
+        Ok(())
+    }
+
+    /// Receives and handles one request, waits up to `timeout` or forever for `None`.
+    ///
+    /// Returns `false` if no message was received in time. Malformed messages, acknowledgements
+    /// and resets are ignored.
+    pub fn handle_one(&mut self, timeout: Option<Duration>) -> ZephyrResult<bool> {
+        self.start();
+        self.socket.set_read_timeout(timeout)?;
+        let mut buffer = vec![0u8; RECEIVE_BUFFER_SIZE];
+        let (count, peer) = match self.socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(e) if e.is_would_block() => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        buffer.truncate(count);
+        let mut request = match CoapPacket::parse(buffer) {
+            Ok(request) => request,
+            Err(_) => return Ok(true),
+        };
+
+        match request.message_type() {
+            MessageType::Confirmable if request.code() == Code::EMPTY => {
+                // CoAP ping, answered with a reset
+                let reset = CoapPacket::new(
+                    DEFAULT_PACKET_SIZE,
+                    MessageType::Reset,
+                    Code::EMPTY,
+                    request.id(),
+                    &[],
+                )?;
+                self.socket.send_to(reset.as_bytes(), peer)?;
+                return Ok(true);
+            }
+            MessageType::Confirmable | MessageType::NonConfirmable
+                if request.code().class() == 0 => {}
+            _ => return Ok(true),
+        }
+
+        // zeroed as the options are written by the function
+        let mut options: [ZCoapOption; MAX_PATH_SEGMENTS] = unsafe { core::mem::zeroed() };
+        let count = unsafe {
+            zephyr_sys::raw::coap_find_options(
+                request.as_raw(),
+                OptionNumber::URI_PATH.0,
+                options.as_mut_ptr(),
+                MAX_PATH_SEGMENTS as u16,
+            )
+        };
+        let mut addr = RawSocketAddr::from(&peer);
+        let status = super::result(unsafe {
+            zephyr_sys::raw::coap_handle_request(
+                request.as_raw(),
+                self.raw.as_mut_ptr(),
+                options.as_mut_ptr(),
+                count.max(0) as u8,
+                addr.as_mut_ptr(),
+                addr.socklen(),
+            )
+        });
+        self.flush()?;
+
+        match status {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                let code = match e.number() {
+                    ErrorNumber::NoEntry => Code::NOT_FOUND,
+                    ErrorNumber::Permission => Code::METHOD_NOT_ALLOWED,
+                    ErrorNumber::InvalidArgument => Code::BAD_REQUEST,
+                    _ => Code::INTERNAL_ERROR,
+                };
+                self.reply(&request, peer, code)?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Handles requests until an error occurs.
+    pub fn run(&mut self) -> ZephyrResult<()> {
+        loop {
+            self.handle_one(None)?;
+        }
+    }
+
+    /// Wrapper to the `coap_resource_notify` function, sends a notification built by the `GET`
+    /// handler to every observer of `path`.
+    ///
+    /// Fails with [ErrorNumber::NoEntry] if `path` is not an observable resource.
+    pub fn notify(&mut self, path: &str) -> ZephyrResult<()> {
+        self.start();
+        let path = Resource::new(path)?.path;
+        let index = self
+            .resources
+            .iter()
+            .position(|r| r.observable && r.path == path)
+            .ok_or_else(|| ZephyrError::new_with_context(ErrorNumber::NoEntry, &CONTEXT))?;
+        super::result(unsafe { zephyr_sys::raw::coap_resource_notify(&mut self.raw[index]) })?;
+        self.flush()
+    }
+
+    /// Number of observers of `path`.
+    pub fn observers(&self, path: &str) -> usize {
+        self.resources
+            .iter()
+            .find(|r| Resource::new(path).map_or(false, |path| r.path == path.path))
+            .map_or(0, |r| r.observers.len())
+    }
+
+    /// The socket of the server, e.g. to [crate::network::poll::poll] it.
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+}
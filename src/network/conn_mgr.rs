@@ -0,0 +1,144 @@
+//! Wrappers for the connection manager of Zephyr (`CONFIG_NET_CONNECTION_MANAGER`).
+//!
+//! The connection manager monitors the interfaces and raises the `NET_EVENT_L4_*` events once an
+//! interface is up and has an address, which is when the network is actually usable. With
+//! connectivity bindings (e.g. for Wi-Fi or LTE) it also connects interfaces to their networks:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::conn_mgr;
+//!# use zephyr_rust_wrappers::time::FOREVER;
+//! conn_mgr::all_if_connect(true).unwrap();
+//! conn_mgr::wait_for_connectivity(FOREVER).unwrap();
+//! ```
+
+use super::events::{mask, subscribe, subscribe_raw, unsubscribe, NetEvent, SubscriptionId};
+use super::interface::NetworkInterface;
+use crate::time::Timeout;
+use crate::{Context, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use core::ffi::c_int;
+use core::time::Duration;
+use zephyr_sys::raw::k_sem;
+
+const CONTEXT: ConnMgrWrapperContext = ConnMgrWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `conn_mgr_if_connect` function, connects `iface` with its connectivity
+/// binding.
+pub fn if_connect(iface: NetworkInterface) -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::conn_mgr_if_connect(iface.as_raw()) })
+}
+
+/// Wrapper to the `conn_mgr_if_disconnect` function.
+pub fn if_disconnect(iface: NetworkInterface) -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::conn_mgr_if_disconnect(iface.as_raw()) })
+}
+
+/// Wrapper to the `conn_mgr_if_is_bound` function, returns `true` if `iface` has a connectivity
+/// binding.
+pub fn if_is_bound(iface: NetworkInterface) -> bool {
+    unsafe { zephyr_sys::raw::conn_mgr_if_is_bound(iface.as_raw()) }
+}
+
+/// Wrapper to the `conn_mgr_if_set_timeout` function, the time a connection attempt of `iface`
+/// may take, forever for `None`.
+pub fn if_set_timeout(iface: NetworkInterface, timeout: Option<Duration>) -> ZephyrResult<()> {
+    let seconds = timeout.map_or(zephyr_sys::raw::CONN_MGR_IF_NO_TIMEOUT as c_int, |timeout| {
+        timeout.as_secs().min(c_int::MAX as u64) as c_int
+    });
+    result(unsafe { zephyr_sys::raw::conn_mgr_if_set_timeout(iface.as_raw(), seconds) })
+}
+
+/// Set whether `iface` reconnects after it lost the connection, the `CONN_MGR_IF_PERSISTENT`
+/// flag.
+pub fn if_set_persistent(iface: NetworkInterface, persistent: bool) -> ZephyrResult<()> {
+    result(unsafe {
+        zephyr_sys::raw::conn_mgr_if_set_flag(
+            iface.as_raw(),
+            zephyr_sys::raw::conn_mgr_if_flag_CONN_MGR_IF_PERSISTENT,
+            persistent,
+        )
+    })
+}
+
+/// Wrapper to the `conn_mgr_all_if_up` function, takes all interfaces up, except the ignored ones
+/// if `skip_ignored` is set.
+pub fn all_if_up(skip_ignored: bool) -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::conn_mgr_all_if_up(skip_ignored) })
+}
+
+/// Wrapper to the `conn_mgr_all_if_connect` function, takes all interfaces with a connectivity
+/// binding up and connects them.
+pub fn all_if_connect(skip_ignored: bool) -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::conn_mgr_all_if_connect(skip_ignored) })
+}
+
+/// Wrapper to the `conn_mgr_all_if_disconnect` function.
+pub fn all_if_disconnect(skip_ignored: bool) -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::conn_mgr_all_if_disconnect(skip_ignored) })
+}
+
+/// Wrapper to the `conn_mgr_ignore_iface` function, the connectivity of `iface` is not reported.
+pub fn ignore_iface(iface: NetworkInterface) {
+    unsafe { zephyr_sys::raw::conn_mgr_ignore_iface(iface.as_raw()) }
+}
+
+/// Wrapper to the `conn_mgr_watch_iface` function, reverts [ignore_iface].
+pub fn watch_iface(iface: NetworkInterface) {
+    unsafe { zephyr_sys::raw::conn_mgr_watch_iface(iface.as_raw()) }
+}
+
+/// Calls `f` with `true` when the network becomes usable and with `false` when the last usable
+/// interface lost its connectivity, together with the interface of the change. Returns the id to
+/// unsubscribe with [crate::network::events::unsubscribe].
+pub fn on_connectivity_changed<F>(mut f: F) -> SubscriptionId
+where
+    F: FnMut(bool, Option<NetworkInterface>) + Send + 'static,
+{
+    subscribe(mask::L4, move |event, iface| match event {
+        NetEvent::L4Connected => f(true, iface),
+        NetEvent::L4Disconnected => f(false, iface),
+        _ => {}
+    })
+}
+
+/// Semaphore given by the event handler of [wait_for_connectivity].
+struct SemaphorePtr(*mut k_sem);
+
+unsafe impl Send for SemaphorePtr {}
+
+/// Blocks until the network is usable or `timeout` expired, returns immediately if it already is.
+///
+/// Fails with [crate::ErrorNumber::TryAgain] if `timeout` expired.
+pub fn wait_for_connectivity(timeout: Timeout) -> ZephyrResult<()> {
+    // zeroed as the semaphore is initialized below
+    let mut connected: Box<k_sem> = Box::new(unsafe { core::mem::zeroed() });
+    result(unsafe { zephyr_sys::syscalls::any::k_sem_init(&mut *connected, 0, 1) })?;
+
+    let semaphore = SemaphorePtr(&mut *connected);
+    let subscription = subscribe_raw(zephyr_sys::raw::NET_EVENT_L4_CONNECTED, move |_, _, _| {
+        // the semaphore is only dropped after the subscription was removed
+        unsafe { zephyr_sys::syscalls::any::k_sem_give(semaphore.0) }
+    });
+    // raises NET_EVENT_L4_CONNECTED again if the network already is usable
+    unsafe { zephyr_sys::raw::conn_mgr_mon_resend_status() };
+    let taken = result(unsafe { zephyr_sys::syscalls::any::k_sem_take(&mut *connected, timeout) });
+    unsubscribe(subscription);
+    taken
+}
+
+#[derive(Debug)]
+struct ConnMgrWrapperContext {}
+
+impl Context for ConnMgrWrapperContext {
+    fn name(&self) -> &'static str {
+        "conn_mgr wrapper"
+    }
+}
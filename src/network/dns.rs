@@ -0,0 +1,393 @@
+//! Wrappers for the DNS resolver of Zephyr (`CONFIG_DNS_RESOLVER`).
+//!
+//! [resolve] wraps `zsock_getaddrinfo` and blocks until the query finished. With the `executor`
+//! feature, [resolve_async] uses the callback API of the resolver and does not block the executor.
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::dns;
+//!# use zephyr_rust_wrappers::network::socket::TcpStream;
+//! let addrs = dns::resolve("example.com", Some("80")).unwrap();
+//! let stream = TcpStream::connect(addrs[0]).unwrap();
+//! ```
+
+use super::addr::from_sockaddr;
+use crate::{Context, ErrorNumber, ZephyrError};
+#[cfg(feature = "executor")]
+use {
+    crate::sys::SpinLocked,
+    core::ffi::c_void,
+    core::future::Future,
+    core::pin::Pin,
+    core::sync::atomic::{AtomicBool, Ordering},
+    core::task::{self, Poll, Waker},
+    zephyr_sys::raw::dns_addrinfo as ZDnsAddrInfo,
+};
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::ffi::c_int;
+use core::fmt::{Display, Formatter};
+use core::net::SocketAddr;
+use zephyr_sys::raw::zsock_addrinfo as ZAddrInfo;
+
+const CONTEXT: DnsWrapperContext = DnsWrapperContext {};
+
+/// Error of a DNS query, mirrors the `DNS_EAI_*` codes of the resolver.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ResolveError {
+    /// The name is unknown, or has no address of the requested family.
+    NotFound,
+    /// The name server is temporarily not available.
+    TryAgain,
+    /// The name server failed permanently.
+    Failed,
+    /// The requested address family is not supported.
+    Family,
+    /// The service is not a number, service names are not supported.
+    Service,
+    /// The resolver ran out of memory or query slots.
+    Memory,
+    /// The query was canceled.
+    Canceled,
+    /// The resolver failed with the system error.
+    System(ErrorNumber),
+    /// Error code unknown to this wrapper.
+    Other(i32),
+}
+
+impl ResolveError {
+    fn from_code(code: c_int) -> Self {
+        match code {
+            zephyr_sys::raw::dns_resolve_status_DNS_EAI_NONAME
+            | zephyr_sys::raw::dns_resolve_status_DNS_EAI_NODATA
+            | zephyr_sys::raw::dns_resolve_status_DNS_EAI_ADDRFAMILY => ResolveError::NotFound,
+            zephyr_sys::raw::dns_resolve_status_DNS_EAI_AGAIN => ResolveError::TryAgain,
+            zephyr_sys::raw::dns_resolve_status_DNS_EAI_FAIL => ResolveError::Failed,
+            zephyr_sys::raw::dns_resolve_status_DNS_EAI_FAMILY => ResolveError::Family,
+            zephyr_sys::raw::dns_resolve_status_DNS_EAI_SERVICE => ResolveError::Service,
+            zephyr_sys::raw::dns_resolve_status_DNS_EAI_MEMORY => ResolveError::Memory,
+            zephyr_sys::raw::dns_resolve_status_DNS_EAI_CANCELED => ResolveError::Canceled,
+            zephyr_sys::raw::dns_resolve_status_DNS_EAI_SYSTEM => {
+                let errno = unsafe { *zephyr_sys::syscalls::any::z_errno() };
+                ResolveError::System(errno.into())
+            }
+            code => ResolveError::Other(code),
+        }
+    }
+}
+
+impl Display for ResolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ResolveError::NotFound => write!(f, "name not found"),
+            ResolveError::TryAgain => write!(f, "name server temporarily not available"),
+            ResolveError::Failed => write!(f, "name server failed"),
+            ResolveError::Family => write!(f, "address family not supported"),
+            ResolveError::Service => write!(f, "service not supported"),
+            ResolveError::Memory => write!(f, "out of memory"),
+            ResolveError::Canceled => write!(f, "query canceled"),
+            ResolveError::System(errno) => write!(f, "system error {}", errno),
+            ResolveError::Other(code) => write!(f, "resolver error {}", code),
+        }
+    }
+}
+
+impl Error for ResolveError {}
+
+impl From<ResolveError> for ZephyrError {
+    fn from(other: ResolveError) -> Self {
+        let errno = match other {
+            ResolveError::NotFound => ErrorNumber::NoEntry,
+            ResolveError::TryAgain => ErrorNumber::TryAgain,
+            ResolveError::Family | ResolveError::Service => ErrorNumber::NotSupported,
+            ResolveError::Memory => ErrorNumber::NoSpace,
+            ResolveError::System(errno) => errno,
+            _ => ErrorNumber::Io,
+        };
+        ZephyrError::new_with_context(errno, &CONTEXT).with_source(other)
+    }
+}
+
+pub type ResolveResult<T> = Result<T, ResolveError>;
+
+/// Address family of a query.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Family {
+    /// IPv4 and IPv6 addresses.
+    Any,
+    Ipv4,
+    Ipv6,
+}
+
+impl Default for Family {
+    fn default() -> Self {
+        Family::Any
+    }
+}
+
+/// Transport protocol of the returned addresses, the resolver returns an entry per protocol for
+/// [Protocol::Any].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Protocol {
+    Any,
+    Tcp,
+    Udp,
+}
+
+impl Default for Protocol {
+    fn default() -> Self {
+        Protocol::Any
+    }
+}
+
+/// Hints restricting the addresses returned by [resolve_with].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Hints {
+    pub family: Family,
+    pub protocol: Protocol,
+    /// Only parse numeric addresses of `host` instead of querying a name server (`AI_NUMERICHOST`).
+    pub numeric_host: bool,
+}
+
+impl Hints {
+    fn to_raw(&self) -> ZAddrInfo {
+        // zeroed as unset fields must be zero
+        let mut hints: ZAddrInfo = unsafe { core::mem::zeroed() };
+        hints.ai_family = (match self.family {
+            Family::Any => zephyr_sys::raw::AF_UNSPEC,
+            Family::Ipv4 => zephyr_sys::raw::AF_INET,
+            Family::Ipv6 => zephyr_sys::raw::AF_INET6,
+        }) as c_int;
+        let (socktype, protocol) = match self.protocol {
+            Protocol::Any => (0, 0),
+            Protocol::Tcp => (
+                zephyr_sys::raw::net_sock_type_SOCK_STREAM,
+                zephyr_sys::raw::net_ip_protocol_IPPROTO_TCP,
+            ),
+            Protocol::Udp => (
+                zephyr_sys::raw::net_sock_type_SOCK_DGRAM,
+                zephyr_sys::raw::net_ip_protocol_IPPROTO_UDP,
+            ),
+        };
+        hints.ai_socktype = socktype as c_int;
+        hints.ai_protocol = protocol as c_int;
+        if self.numeric_host {
+            hints.ai_flags = zephyr_sys::raw::AI_NUMERICHOST as c_int;
+        }
+        hints
+    }
+}
+
+/// Resolves `host` into the addresses of IPv4 and IPv6 and both transport protocols, the port of
+/// the addresses is `service` (a port number) or `0`.
+pub fn resolve(host: &str, service: Option<&str>) -> ResolveResult<Vec<SocketAddr>> {
+    resolve_with(host, service, &Hints::default())
+}
+
+/// Wrapper to the `zsock_getaddrinfo` function.
+///
+/// Resolves `host` into the addresses matching `hints`, the port of the addresses is `service` (a
+/// port number) or `0`. Addresses returned for several protocols are only included once.
+pub fn resolve_with(
+    host: &str,
+    service: Option<&str>,
+    hints: &Hints,
+) -> ResolveResult<Vec<SocketAddr>> {
+    // names with NUL characters cannot be resolved
+    let host = CString::new(host).map_err(|_| ResolveError::NotFound)?;
+    let service = service
+        .map(CString::new)
+        .transpose()
+        .map_err(|_| ResolveError::Service)?;
+    let raw_hints = hints.to_raw();
+
+    let mut list: *mut ZAddrInfo = core::ptr::null_mut();
+    let code = unsafe {
+        zephyr_sys::raw::zsock_getaddrinfo(
+            host.as_ptr(),
+            service.as_ref().map_or(core::ptr::null(), |service| service.as_ptr()),
+            &raw_hints,
+            &mut list,
+        )
+    };
+    if code != 0 {
+        return Err(ResolveError::from_code(code));
+    }
+
+    let mut addrs = Vec::new();
+    let mut entry = list;
+    // the list is valid until it is freed below
+    while let Some(info) = unsafe { entry.as_ref() } {
+        if let Some(addr) = unsafe { info.ai_addr.as_ref() }.and_then(from_sockaddr) {
+            if !addrs.contains(&addr) {
+                addrs.push(addr);
+            }
+        }
+        entry = info.ai_next;
+    }
+    unsafe { zephyr_sys::raw::zsock_freeaddrinfo(list) };
+    Ok(addrs)
+}
+
+/// Resolves `host` with the callback API of the resolver, the addresses use `port`.
+///
+/// `family` selects an A ([Family::Ipv4]) or AAAA ([Family::Ipv6]) query, [Family::Any] queries
+/// A records. The query is canceled if it does not finish within `timeout_ms` or the future is
+/// dropped.
+#[cfg(feature = "executor")]
+pub fn resolve_async(host: &str, port: u16, family: Family, timeout_ms: i32) -> ResolveFuture {
+    let host = match CString::new(host) {
+        Ok(host) => host,
+        Err(_) => {
+            return ResolveFuture {
+                state: None,
+                id: 0,
+                submitted: Err(ResolveError::NotFound),
+            }
+        }
+    };
+    let state = Box::new(QueryState {
+        done: AtomicBool::new(false),
+        waker: SpinLocked::new(None),
+        addrs: Vec::new(),
+        error: None,
+        port,
+        host,
+    });
+    let mut future = ResolveFuture {
+        state: Some(state),
+        id: 0,
+        submitted: Ok(()),
+    };
+
+    let query_type = match family {
+        Family::Any | Family::Ipv4 => zephyr_sys::raw::dns_query_type_DNS_QUERY_TYPE_A,
+        Family::Ipv6 => zephyr_sys::raw::dns_query_type_DNS_QUERY_TYPE_AAAA,
+    };
+    let state = future.state.as_mut().unwrap();
+    let code = unsafe {
+        zephyr_sys::raw::dns_get_addr_info(
+            state.host.as_ptr(),
+            query_type,
+            &mut future.id,
+            Some(resolve_callback),
+            state.as_mut() as *mut QueryState as *mut c_void,
+            timeout_ms,
+        )
+    };
+    if code < 0 {
+        future.submitted = Err(ResolveError::System(code.into()));
+    }
+    future
+}
+
+/// State shared between a [ResolveFuture] and the callback of its query.
+#[cfg(feature = "executor")]
+struct QueryState {
+    done: AtomicBool,
+    /// Locked as not every target has compare and swap atomics.
+    waker: SpinLocked<Option<Waker>>,
+    addrs: Vec<SocketAddr>,
+    error: Option<ResolveError>,
+    port: u16,
+    /// The name of the query, which the resolver might access until the query is done.
+    host: CString,
+}
+
+
+#[cfg(feature = "executor")]
+unsafe extern "C" fn resolve_callback(
+    status: zephyr_sys::raw::dns_resolve_status,
+    info: *mut ZDnsAddrInfo,
+    user_data: *mut c_void,
+) {
+    let state = &mut *(user_data as *mut QueryState);
+    match status {
+        zephyr_sys::raw::dns_resolve_status_DNS_EAI_INPROGRESS => {
+            if let Some(info) = info.as_ref() {
+                if let Some(addr) = from_sockaddr(&info.ai_addr) {
+                    state.addrs.push(SocketAddr::new(addr.ip(), state.port));
+                }
+            }
+            return;
+        }
+        zephyr_sys::raw::dns_resolve_status_DNS_EAI_ALLDONE => {}
+        status => state.error = Some(ResolveError::from_code(status as c_int)),
+    }
+
+    state.done.store(true, Ordering::Release);
+    if let Some(waker) = state.waker.locked(Option::take) {
+        waker.wake();
+    }
+}
+
+/// Future of a query started with [resolve_async].
+#[cfg(feature = "executor")]
+pub struct ResolveFuture {
+    state: Option<Box<QueryState>>,
+    id: u16,
+    submitted: ResolveResult<()>,
+}
+
+#[cfg(feature = "executor")]
+impl Future for ResolveFuture {
+    type Output = ResolveResult<Vec<SocketAddr>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if let Err(e) = core::mem::replace(&mut this.submitted, Ok(())) {
+            this.state = None;
+            return Poll::Ready(Err(e));
+        }
+        let state = match &mut this.state {
+            Some(state) => state,
+            None => panic!("future polled after completion"),
+        };
+
+        if !state.done.load(Ordering::Acquire) {
+            let waker = cx.waker().clone();
+            // the previous waker is dropped without the lock held
+            drop(state.waker.locked(|previous| previous.replace(waker)));
+            // the callback might have run before the waker was stored
+            if !state.done.load(Ordering::Acquire) {
+                return Poll::Pending;
+            }
+        }
+
+        // the callback does not access the state after the query is done
+        let mut state = this.state.take().unwrap();
+        match state.error {
+            Some(e) => Poll::Ready(Err(e)),
+            None if state.addrs.is_empty() => Poll::Ready(Err(ResolveError::NotFound)),
+            None => Poll::Ready(Ok(core::mem::take(&mut state.addrs))),
+        }
+    }
+}
+
+#[cfg(feature = "executor")]
+impl Drop for ResolveFuture {
+    fn drop(&mut self) {
+        let state = match self.state.take() {
+            Some(state) => state,
+            None => return,
+        };
+        if self.submitted.is_ok() && !state.done.load(Ordering::Acquire) {
+            // the resolver calls the callback with DNS_EAI_CANCELED before returning
+            unsafe { zephyr_sys::raw::dns_cancel_addr_info(self.id) };
+            if !state.done.load(Ordering::Acquire) {
+                // the query could not be canceled, the callback still owns the state
+                Box::leak(state);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct DnsWrapperContext {}
+
+impl Context for DnsWrapperContext {
+    fn name(&self) -> &'static str {
+        "dns wrapper"
+    }
+}
@@ -0,0 +1,319 @@
+//! Wrappers for the Ethernet L2 and the Ethernet PHYs of Zephyr (`CONFIG_NET_L2_ETHERNET`).
+//!
+//! The link of an interface is configured with the `NET_REQUEST_ETHERNET_*` requests of the
+//! network management API, its state is read from the PHY of the driver. `NET_REQUEST_ETHERNET_*`
+//! requests require `CONFIG_NET_L2_ETHERNET_MGMT` and are only accepted by drivers that report
+//! the matching [Capabilities]:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::ethernet::Ethernet;
+//!# use zephyr_rust_wrappers::network::interface::NetworkInterface;
+//! let ethernet = Ethernet::new(NetworkInterface::get_default().unwrap()).unwrap();
+//! let link = ethernet.link_state().unwrap();
+//! println!("{:02x?} up: {} {:?}", ethernet.mac_address(), link.is_up, link.speed);
+//! ```
+//!
+//! VLAN tags are added with [Ethernet::enable_vlan], the tagged traffic of each tag is sent and
+//! received on a virtual interface of its own.
+
+use super::events::{subscribe_raw, SubscriptionId};
+use super::interface::NetworkInterface;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use bitflags::bitflags;
+use core::ffi::c_int;
+use zephyr::device::Device;
+use zephyr_sys::raw::{
+    ethernet_api as ZEthernetApi, ethernet_req_params as ZRequestParams,
+    ethphy_driver_api as ZPhyApi, phy_link_state as ZLinkState,
+};
+
+const CONTEXT: EthernetWrapperContext = EthernetWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+fn not_implemented() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::NotImplemented, &CONTEXT)
+}
+
+bitflags! {
+    /// Hardware capabilities of an Ethernet driver, the equivalent of `enum ethernet_hw_caps`.
+    pub struct Capabilities: u32 {
+        const TX_CHECKSUM_OFFLOAD = zephyr_sys::raw::ethernet_hw_caps_ETHERNET_HW_TX_CHKSUM_OFFLOAD;
+        const RX_CHECKSUM_OFFLOAD = zephyr_sys::raw::ethernet_hw_caps_ETHERNET_HW_RX_CHKSUM_OFFLOAD;
+        const VLAN = zephyr_sys::raw::ethernet_hw_caps_ETHERNET_HW_VLAN;
+        const AUTO_NEGOTIATION = zephyr_sys::raw::ethernet_hw_caps_ETHERNET_AUTO_NEGOTIATION_SET;
+        const LINK_10BASE_T = zephyr_sys::raw::ethernet_hw_caps_ETHERNET_LINK_10BASE_T;
+        const LINK_100BASE_T = zephyr_sys::raw::ethernet_hw_caps_ETHERNET_LINK_100BASE_T;
+        const LINK_1000BASE_T = zephyr_sys::raw::ethernet_hw_caps_ETHERNET_LINK_1000BASE_T;
+        const DUPLEX = zephyr_sys::raw::ethernet_hw_caps_ETHERNET_DUPLEX_SET;
+        const PROMISCUOUS = zephyr_sys::raw::ethernet_hw_caps_ETHERNET_PROMISC_MODE;
+    }
+}
+
+/// Speed of a link.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Speed {
+    Mbps10,
+    Mbps100,
+    Mbps1000,
+}
+
+/// State of the link reported by the PHY.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LinkState {
+    pub is_up: bool,
+    /// Negotiated speed, `None` while the link is down.
+    pub speed: Option<Speed>,
+    pub full_duplex: bool,
+}
+
+impl From<&ZLinkState> for LinkState {
+    fn from(raw: &ZLinkState) -> Self {
+        let (speed, full_duplex) = match raw.speed as u32 {
+            zephyr_sys::raw::phy_link_speed_LINK_HALF_10BASE_T => (Some(Speed::Mbps10), false),
+            zephyr_sys::raw::phy_link_speed_LINK_FULL_10BASE_T => (Some(Speed::Mbps10), true),
+            zephyr_sys::raw::phy_link_speed_LINK_HALF_100BASE_T => (Some(Speed::Mbps100), false),
+            zephyr_sys::raw::phy_link_speed_LINK_FULL_100BASE_T => (Some(Speed::Mbps100), true),
+            zephyr_sys::raw::phy_link_speed_LINK_HALF_1000BASE_T => (Some(Speed::Mbps1000), false),
+            zephyr_sys::raw::phy_link_speed_LINK_FULL_1000BASE_T => (Some(Speed::Mbps1000), true),
+            _ => (None, false),
+        };
+        LinkState {
+            is_up: raw.is_up,
+            speed: if raw.is_up { speed } else { None },
+            full_duplex,
+        }
+    }
+}
+
+/// Ethernet functions of a network interface.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Ethernet {
+    iface: NetworkInterface,
+}
+
+impl Ethernet {
+    /// Ethernet functions of `iface`, `None` if it is not the interface of an Ethernet driver.
+    pub fn new(iface: NetworkInterface) -> Option<Self> {
+        // the equivalent of the inlined `net_if_l2`, interfaces are valid
+        let l2 = unsafe { (*(*iface.as_raw()).if_dev).l2 };
+        if core::ptr::eq(l2, unsafe { &zephyr_sys::raw::_net_l2_ETHERNET }) {
+            Some(Ethernet { iface })
+        } else {
+            None
+        }
+    }
+
+    /// The network interface.
+    pub fn interface(&self) -> NetworkInterface {
+        self.iface
+    }
+
+    /// Device of the Ethernet driver, the equivalent of the inlined `net_if_get_device`.
+    fn device(&self) -> &'static Device {
+        // interfaces are valid and point to the static device of their driver
+        unsafe { &*(*(*self.iface.as_raw()).if_dev).dev }
+    }
+
+    fn api(&self) -> &'static ZEthernetApi {
+        // the interface is checked to be an Ethernet interface in the constructor
+        unsafe { &*(self.device().api as *const ZEthernetApi) }
+    }
+
+    /// Device of the PHY, the equivalent of the inlined `net_eth_get_phy`.
+    fn phy(&self) -> ZephyrResult<&'static Device> {
+        let get_phy = self.api().get_phy.ok_or_else(not_implemented)?;
+        let phy = unsafe { get_phy(self.device()) };
+        // the PHY is a static device if there is one
+        unsafe { phy.as_ref() }.ok_or_else(|| {
+            ZephyrError::new_with_context(ErrorNumber::NoDevice, &CONTEXT)
+        })
+    }
+
+    fn phy_api(&self) -> ZephyrResult<(&'static Device, &'static ZPhyApi)> {
+        let phy = self.phy()?;
+        // the device returned by the driver is a PHY
+        Ok((phy, unsafe { &*(phy.api as *const ZPhyApi) }))
+    }
+
+    fn request(
+        &self,
+        handler: super::interface::RequestHandler,
+        code: u32,
+        params: &mut ZRequestParams,
+    ) -> ZephyrResult<()> {
+        result(self.iface.net_mgmt(handler, code, Some(params)))
+    }
+
+    fn params() -> ZRequestParams {
+        // zeroed as the requests only read their member of the union
+        unsafe { core::mem::zeroed() }
+    }
+
+    /// Capabilities of the driver, the equivalent of the inlined `net_eth_get_hw_capabilities`.
+    pub fn capabilities(&self) -> Capabilities {
+        match self.api().get_capabilities {
+            Some(get_capabilities) => {
+                Capabilities::from_bits_truncate(unsafe { get_capabilities(self.device()) } as u32)
+            }
+            None => Capabilities::empty(),
+        }
+    }
+
+    /// MAC address of the interface, its link address.
+    pub fn mac_address(&self) -> [u8; 6] {
+        let mut mac = [0u8; 6];
+        // interfaces are valid and the link address is set by the driver at initialization
+        let link_addr = unsafe { &(*(*self.iface.as_raw()).if_dev).link_addr };
+        if !link_addr.addr.is_null() {
+            let len = (link_addr.len as usize).min(mac.len());
+            let addr = unsafe { core::slice::from_raw_parts(link_addr.addr, len) };
+            mac[..len].copy_from_slice(addr);
+        }
+        mac
+    }
+
+    /// Sends `NET_REQUEST_ETHERNET_SET_MAC_ADDRESS`, the interface MUST be down.
+    pub fn set_mac_address(&self, mac: [u8; 6]) -> ZephyrResult<()> {
+        let mut params = Self::params();
+        params.__bindgen_anon_1.mac_address.addr = mac;
+        self.request(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_ETHERNET_SET_MAC_ADDRESS,
+            zephyr_sys::raw::NET_REQUEST_ETHERNET_SET_MAC_ADDRESS,
+            &mut params,
+        )
+    }
+
+    /// Sends `NET_REQUEST_ETHERNET_SET_LINK`, the speeds advertised by the PHY.
+    pub fn set_link_speeds(&self, speeds: &[Speed]) -> ZephyrResult<()> {
+        let mut params = Self::params();
+        // writing a member of a union is safe, the request reads the same member
+        let link = unsafe { &mut params.__bindgen_anon_1.l };
+        link.link_10bt = speeds.contains(&Speed::Mbps10);
+        link.link_100bt = speeds.contains(&Speed::Mbps100);
+        link.link_1000bt = speeds.contains(&Speed::Mbps1000);
+        self.request(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_ETHERNET_SET_LINK,
+            zephyr_sys::raw::NET_REQUEST_ETHERNET_SET_LINK,
+            &mut params,
+        )
+    }
+
+    /// Sends `NET_REQUEST_ETHERNET_SET_DUPLEX`.
+    pub fn set_full_duplex(&self, full_duplex: bool) -> ZephyrResult<()> {
+        let mut params = Self::params();
+        params.__bindgen_anon_1.full_duplex = full_duplex;
+        self.request(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_ETHERNET_SET_DUPLEX,
+            zephyr_sys::raw::NET_REQUEST_ETHERNET_SET_DUPLEX,
+            &mut params,
+        )
+    }
+
+    /// Sends `NET_REQUEST_ETHERNET_SET_AUTO_NEGOTIATION`.
+    pub fn set_auto_negotiation(&self, enable: bool) -> ZephyrResult<()> {
+        let mut params = Self::params();
+        params.__bindgen_anon_1.auto_negotiation = enable;
+        self.request(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_ETHERNET_SET_AUTO_NEGOTIATION,
+            zephyr_sys::raw::NET_REQUEST_ETHERNET_SET_AUTO_NEGOTIATION,
+            &mut params,
+        )
+    }
+
+    /// State of the link, the equivalent of the inlined `phy_get_link_state`.
+    pub fn link_state(&self) -> ZephyrResult<LinkState> {
+        let (phy, api) = self.phy_api()?;
+        let get_link = api.get_link.ok_or_else(not_implemented)?;
+        // zeroed as the state is written by the driver
+        let mut state: ZLinkState = unsafe { core::mem::zeroed() };
+        result(unsafe { get_link(phy, &mut state) })?;
+        Ok((&state).into())
+    }
+
+    /// Reads the PHY register `register`, the equivalent of the inlined `phy_read`.
+    pub fn phy_read(&self, register: u16) -> ZephyrResult<u32> {
+        let (phy, api) = self.phy_api()?;
+        let read = api.read.ok_or_else(not_implemented)?;
+        let mut value = 0;
+        result(unsafe { read(phy, register, &mut value) })?;
+        Ok(value)
+    }
+
+    /// Writes `value` to the PHY register `register`, the equivalent of the inlined `phy_write`.
+    pub fn phy_write(&self, register: u16, value: u32) -> ZephyrResult<()> {
+        let (phy, api) = self.phy_api()?;
+        let write = api.write.ok_or_else(not_implemented)?;
+        result(unsafe { write(phy, register, value) })
+    }
+
+    /// Wrapper to the `net_eth_vlan_enable` function, adds the VLAN `tag` to the interface. The
+    /// tagged traffic gets its own virtual interface, see [Ethernet::vlan_interface].
+    ///
+    /// Requires `CONFIG_NET_VLAN` and drivers reporting [Capabilities::VLAN], fails with
+    /// [ErrorNumber::Already] if the tag is already enabled.
+    pub fn enable_vlan(&self, tag: u16) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::net_eth_vlan_enable(self.iface.as_raw(), tag) })
+    }
+
+    /// Wrapper to the `net_eth_vlan_disable` function, removes the VLAN `tag`.
+    pub fn disable_vlan(&self, tag: u16) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::net_eth_vlan_disable(self.iface.as_raw(), tag) })
+    }
+
+    /// Wrapper to the `net_eth_get_vlan_tag` function, the VLAN tag of this interface or `None`
+    /// if it is untagged.
+    pub fn vlan_tag(&self) -> Option<u16> {
+        match unsafe { zephyr_sys::raw::net_eth_get_vlan_tag(self.iface.as_raw()) } {
+            tag if tag as u32 == zephyr_sys::raw::NET_VLAN_TAG_UNSPEC => None,
+            tag => Some(tag),
+        }
+    }
+
+    /// Wrapper to the `net_eth_get_vlan_iface` function, the interface of the traffic tagged with
+    /// `tag`.
+    pub fn vlan_interface(&self, tag: u16) -> Option<NetworkInterface> {
+        unsafe {
+            NetworkInterface::from_raw(zephyr_sys::raw::net_eth_get_vlan_iface(
+                self.iface.as_raw(),
+                tag,
+            ))
+        }
+    }
+
+    /// Calls `f` with `true` when the carrier of this interface is detected and with `false` when
+    /// it is lost, returns the id to unsubscribe with [crate::network::events::unsubscribe].
+    pub fn on_carrier_changed<F>(&self, mut f: F) -> SubscriptionId
+    where
+        F: FnMut(bool) + Send + 'static,
+    {
+        let this = self.iface;
+        let mask = zephyr_sys::raw::NET_EVENT_ETHERNET_CARRIER_ON
+            | zephyr_sys::raw::NET_EVENT_ETHERNET_CARRIER_OFF;
+        subscribe_raw(mask, move |event, iface, _| {
+            if iface != Some(this) {
+                return;
+            }
+            match event {
+                zephyr_sys::raw::NET_EVENT_ETHERNET_CARRIER_ON => f(true),
+                zephyr_sys::raw::NET_EVENT_ETHERNET_CARRIER_OFF => f(false),
+                _ => {}
+            }
+        })
+    }
+}
+
+#[derive(Debug)]
+struct EthernetWrapperContext {}
+
+impl Context for EthernetWrapperContext {
+    fn name(&self) -> &'static str {
+        "ethernet wrapper"
+    }
+}
@@ -0,0 +1,204 @@
+//! Wrappers for the event notifications of the network management API (`CONFIG_NET_MGMT_EVENT`).
+//!
+//! Events are identified by the `NET_EVENT_*` codes of Zephyr, which combine the layer, the
+//! command and flags. A subscription masks them with `net_mgmt_event_callback.event_mask`, so a
+//! mask of several events of the same layer receives all of them. The masks of [mask] combine
+//! the common events of a layer, events of different layers need separate subscriptions:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::events::{mask, subscribe, NetEvent};
+//! subscribe(mask::L4, |event, iface| {
+//!     if event == NetEvent::L4Connected {
+//!         println!("connected on {:?}", iface.map(|iface| iface.index()));
+//!     }
+//! });
+//! ```
+
+use super::addr::{from_in6_addr, from_in_addr, from_sockaddr, ZIn6Addr, ZInAddr, ZSockAddr};
+use super::interface::NetworkInterface;
+use crate::handlers::Handlers;
+use alloc::boxed::Box;
+use core::cell::UnsafeCell;
+use core::mem::size_of;
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use zephyr_sys::raw::{net_if as ZNetIf, net_mgmt_event_callback as ZEventCallback};
+
+/// Handler of a subscription, called with the raised event, the interface and the event info.
+type Handler = dyn FnMut(u32, Option<NetworkInterface>, Option<&[u8]>) + Send;
+
+/// C callback of a subscription, which the network management API links into its list.
+struct SubscriptionKey {
+    callback: UnsafeCell<ZEventCallback>,
+}
+
+// the C callback only points to the trampoline and to the other callbacks of the API
+unsafe impl Send for SubscriptionKey {}
+
+/// Every subscription is a key of its own.
+impl PartialEq for SubscriptionKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.callback.get() == other.callback.get()
+    }
+}
+
+static SUBSCRIPTIONS: Handlers<SubscriptionKey, Handler> = Handlers::new();
+
+/// Identifies a subscription added with [subscribe] or [subscribe_raw].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SubscriptionId(usize);
+
+unsafe extern "C" fn event_trampoline(
+    callback: *mut ZEventCallback,
+    event: u32,
+    iface: *mut ZNetIf,
+) {
+    // the info is set in the callback for the duration of the call
+    let info = if (*callback).info.is_null() {
+        None
+    } else {
+        Some(core::slice::from_raw_parts(
+            (*callback).info as *const u8,
+            (*callback).info_length,
+        ))
+    };
+    let iface = NetworkInterface::from_raw(iface);
+    SUBSCRIPTIONS.call(
+        |key| key.callback.get() == callback,
+        |handler| handler(event, iface, info),
+    );
+}
+
+/// Wrapper to the `net_mgmt_init_event_callback` and `net_mgmt_add_event_callback` functions.
+///
+/// Calls `f` for every event of `mask` with the raised `NET_EVENT_*` code, the interface and the
+/// info of the event, which is only available with `CONFIG_NET_MGMT_EVENT_INFO`. Handlers are
+/// called from the network management thread and should not block.
+pub fn subscribe_raw<F>(mask: u32, f: F) -> SubscriptionId
+where
+    F: FnMut(u32, Option<NetworkInterface>, Option<&[u8]>) + Send + 'static,
+{
+    // zeroed as the callback is initialized below
+    let mut callback: ZEventCallback = unsafe { core::mem::zeroed() };
+    // equivalent of the inlined net_mgmt_init_event_callback
+    callback.__bindgen_anon_1.handler = Some(event_trampoline);
+    callback.__bindgen_anon_2.event_mask = mask;
+
+    let pushed = SUBSCRIPTIONS.push(
+        SubscriptionKey {
+            callback: UnsafeCell::new(callback),
+        },
+        Box::new(f),
+    );
+    // the key and the callback in it stay in the table until the subscription is removed
+    unsafe { zephyr_sys::raw::net_mgmt_add_event_callback((*pushed.key).callback.get()) };
+    SubscriptionId(pushed.id)
+}
+
+/// Wrapper to the `net_mgmt_del_event_callback` function, removes a subscription.
+pub fn unsubscribe(id: SubscriptionId) {
+    if let Some(callback) = SUBSCRIPTIONS.with_key(id.0, |key| key.callback.get()) {
+        unsafe { zephyr_sys::raw::net_mgmt_del_event_callback(callback) };
+        SUBSCRIPTIONS.remove_id(id.0);
+    }
+}
+
+/// Masks of the common events of a layer for [subscribe].
+pub mod mask {
+    /// State changes of interfaces.
+    pub const IF: u32 = zephyr_sys::raw::NET_EVENT_IF_UP
+        | zephyr_sys::raw::NET_EVENT_IF_DOWN
+        | zephyr_sys::raw::NET_EVENT_IF_ADMIN_UP
+        | zephyr_sys::raw::NET_EVENT_IF_ADMIN_DOWN;
+    /// Addresses and the DHCPv4 client.
+    pub const IPV4: u32 = zephyr_sys::raw::NET_EVENT_IPV4_ADDR_ADD
+        | zephyr_sys::raw::NET_EVENT_IPV4_ADDR_DEL
+        | zephyr_sys::raw::NET_EVENT_IPV4_DHCP_START
+        | zephyr_sys::raw::NET_EVENT_IPV4_DHCP_BOUND
+        | zephyr_sys::raw::NET_EVENT_IPV4_DHCP_STOP;
+    /// Addresses.
+    pub const IPV6: u32 =
+        zephyr_sys::raw::NET_EVENT_IPV6_ADDR_ADD | zephyr_sys::raw::NET_EVENT_IPV6_ADDR_DEL;
+    /// Connectivity and DNS servers.
+    pub const L4: u32 = zephyr_sys::raw::NET_EVENT_L4_CONNECTED
+        | zephyr_sys::raw::NET_EVENT_L4_DISCONNECTED
+        | zephyr_sys::raw::NET_EVENT_DNS_SERVER_ADD
+        | zephyr_sys::raw::NET_EVENT_DNS_SERVER_DEL;
+    /// Connection results and scans.
+    pub const WIFI: u32 = zephyr_sys::raw::NET_EVENT_WIFI_CONNECT_RESULT
+        | zephyr_sys::raw::NET_EVENT_WIFI_DISCONNECT_RESULT
+        | zephyr_sys::raw::NET_EVENT_WIFI_SCAN_DONE;
+}
+
+/// Event of the network management API.
+///
+/// Addresses are only known with `CONFIG_NET_MGMT_EVENT_INFO`, which adds them to the events.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NetEvent {
+    IfUp,
+    IfDown,
+    IfAdminUp,
+    IfAdminDown,
+    Ipv4AddrAdd(Option<Ipv4Addr>),
+    Ipv4AddrDel(Option<Ipv4Addr>),
+    Ipv6AddrAdd(Option<Ipv6Addr>),
+    Ipv6AddrDel(Option<Ipv6Addr>),
+    Dhcpv4Start,
+    Dhcpv4Bound,
+    Dhcpv4Stop,
+    /// An interface is connected to a network with an address.
+    L4Connected,
+    L4Disconnected,
+    DnsServerAdd(Option<SocketAddr>),
+    DnsServerDel(Option<SocketAddr>),
+    WifiConnectResult,
+    WifiDisconnectResult,
+    WifiScanDone,
+    /// Event without a variant, with its `NET_EVENT_*` code.
+    Other(u32),
+}
+
+/// Reads the info of an event as `T`, if it is large enough.
+fn info_as<T: Copy>(info: Option<&[u8]>) -> Option<T> {
+    info.filter(|info| info.len() >= size_of::<T>())
+        // the info is a copy of the struct raised with the event, which may be unaligned
+        .map(|info| unsafe { core::ptr::read_unaligned(info.as_ptr() as *const T) })
+}
+
+impl NetEvent {
+    /// Parses the `NET_EVENT_*` code `event` and its info.
+    pub fn from_raw(event: u32, info: Option<&[u8]>) -> Self {
+        let ipv4 = || info_as::<ZInAddr>(info).map(|addr| from_in_addr(&addr));
+        let ipv6 = || info_as::<ZIn6Addr>(info).map(|addr| from_in6_addr(&addr));
+        let server = || info_as::<ZSockAddr>(info).and_then(|addr| from_sockaddr(&addr));
+        match event {
+            zephyr_sys::raw::NET_EVENT_IF_UP => NetEvent::IfUp,
+            zephyr_sys::raw::NET_EVENT_IF_DOWN => NetEvent::IfDown,
+            zephyr_sys::raw::NET_EVENT_IF_ADMIN_UP => NetEvent::IfAdminUp,
+            zephyr_sys::raw::NET_EVENT_IF_ADMIN_DOWN => NetEvent::IfAdminDown,
+            zephyr_sys::raw::NET_EVENT_IPV4_ADDR_ADD => NetEvent::Ipv4AddrAdd(ipv4()),
+            zephyr_sys::raw::NET_EVENT_IPV4_ADDR_DEL => NetEvent::Ipv4AddrDel(ipv4()),
+            zephyr_sys::raw::NET_EVENT_IPV6_ADDR_ADD => NetEvent::Ipv6AddrAdd(ipv6()),
+            zephyr_sys::raw::NET_EVENT_IPV6_ADDR_DEL => NetEvent::Ipv6AddrDel(ipv6()),
+            zephyr_sys::raw::NET_EVENT_IPV4_DHCP_START => NetEvent::Dhcpv4Start,
+            zephyr_sys::raw::NET_EVENT_IPV4_DHCP_BOUND => NetEvent::Dhcpv4Bound,
+            zephyr_sys::raw::NET_EVENT_IPV4_DHCP_STOP => NetEvent::Dhcpv4Stop,
+            zephyr_sys::raw::NET_EVENT_L4_CONNECTED => NetEvent::L4Connected,
+            zephyr_sys::raw::NET_EVENT_L4_DISCONNECTED => NetEvent::L4Disconnected,
+            zephyr_sys::raw::NET_EVENT_DNS_SERVER_ADD => NetEvent::DnsServerAdd(server()),
+            zephyr_sys::raw::NET_EVENT_DNS_SERVER_DEL => NetEvent::DnsServerDel(server()),
+            zephyr_sys::raw::NET_EVENT_WIFI_CONNECT_RESULT => NetEvent::WifiConnectResult,
+            zephyr_sys::raw::NET_EVENT_WIFI_DISCONNECT_RESULT => NetEvent::WifiDisconnectResult,
+            zephyr_sys::raw::NET_EVENT_WIFI_SCAN_DONE => NetEvent::WifiScanDone,
+            event => NetEvent::Other(event),
+        }
+    }
+}
+
+/// Calls `f` for every event of `mask` (e.g. of [mask]) with the typed event and the interface
+/// that raised it, see [subscribe_raw].
+pub fn subscribe<F>(mask: u32, mut f: F) -> SubscriptionId
+where
+    F: FnMut(NetEvent, Option<NetworkInterface>) + Send + 'static,
+{
+    subscribe_raw(mask, move |event, iface, info| f(NetEvent::from_raw(event, info), iface))
+}
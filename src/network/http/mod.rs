@@ -0,0 +1,68 @@
+//! Wrappers for the HTTP library of Zephyr.
+//!
+//! [server] wraps the HTTP server (`CONFIG_HTTP_SERVER`), whose services and resources are
+//! registered at link time like in C.
+
+pub mod server;
+
+use crate::Context;
+
+const CONTEXT: HttpWrapperContext = HttpWrapperContext {};
+
+/// Method of an HTTP request, the equivalent of `enum http_method`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Method {
+    Delete,
+    Get,
+    Head,
+    Post,
+    Put,
+    Options,
+    Patch,
+    Other(u32),
+}
+
+impl Method {
+    /// Raw `enum http_method` value.
+    pub const fn to_raw(self) -> u32 {
+        match self {
+            Method::Delete => zephyr_sys::raw::http_method_HTTP_DELETE,
+            Method::Get => zephyr_sys::raw::http_method_HTTP_GET,
+            Method::Head => zephyr_sys::raw::http_method_HTTP_HEAD,
+            Method::Post => zephyr_sys::raw::http_method_HTTP_POST,
+            Method::Put => zephyr_sys::raw::http_method_HTTP_PUT,
+            Method::Options => zephyr_sys::raw::http_method_HTTP_OPTIONS,
+            Method::Patch => zephyr_sys::raw::http_method_HTTP_PATCH,
+            Method::Other(raw) => raw,
+        }
+    }
+
+    /// Bit of the method in `bitmask_of_supported_http_methods`.
+    pub const fn bit(self) -> u32 {
+        1 << self.to_raw()
+    }
+}
+
+impl From<u32> for Method {
+    fn from(raw: u32) -> Self {
+        match raw {
+            zephyr_sys::raw::http_method_HTTP_DELETE => Method::Delete,
+            zephyr_sys::raw::http_method_HTTP_GET => Method::Get,
+            zephyr_sys::raw::http_method_HTTP_HEAD => Method::Head,
+            zephyr_sys::raw::http_method_HTTP_POST => Method::Post,
+            zephyr_sys::raw::http_method_HTTP_PUT => Method::Put,
+            zephyr_sys::raw::http_method_HTTP_OPTIONS => Method::Options,
+            zephyr_sys::raw::http_method_HTTP_PATCH => Method::Patch,
+            raw => Method::Other(raw),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct HttpWrapperContext {}
+
+impl Context for HttpWrapperContext {
+    fn name(&self) -> &'static str {
+        "http wrapper"
+    }
+}
@@ -0,0 +1,467 @@
+//! Wrappers for the HTTP server of Zephyr (`CONFIG_HTTP_SERVER`).
+//!
+//! Like in C, services and their resources are registered at link time: resources are defined
+//! with [crate::http_static_resource] and [crate::http_dynamic_resource] and grouped into a
+//! service listening on a port with [crate::http_service]. The handlers of dynamic resources are
+//! Rust closures, set before the server is started:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::http::server::{self, HttpResponse};
+//! zephyr_rust_wrappers::http_static_resource!(INDEX = "text/html", b"<h1>Settings</h1>");
+//! zephyr_rust_wrappers::http_dynamic_resource!(CONFIG = "application/json", [Get, Post]);
+//! zephyr_rust_wrappers::http_service!(UI = 80, ["/" => INDEX, "/api/config" => CONFIG]);
+//!
+//! CONFIG.set_handler(|request| HttpResponse::ok(request.body().to_vec()));
+//! server::start().unwrap();
+//! ```
+
+use super::{Method, CONTEXT};
+use crate::handlers::Handlers;
+use crate::{ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use core::ffi::{c_char, c_int, c_void};
+use core::sync::atomic::{AtomicI32, AtomicU16, Ordering};
+use zephyr_sys::raw::{
+    http_client_ctx as ZHttpClient, http_header as ZHttpHeader,
+    http_request_ctx as ZHttpRequestCtx, http_resource_desc as ZHttpResourceDesc,
+    http_resource_detail as ZHttpResourceDetail,
+    http_resource_detail_dynamic as ZHttpDynamicDetail,
+    http_resource_detail_static as ZHttpStaticDetail, http_response_ctx as ZHttpResponseCtx,
+    http_service_desc as ZHttpServiceDesc,
+};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `http_server_start` function, starts listening on the ports of all services.
+pub fn start() -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::http_server_start() })
+}
+
+/// Wrapper to the `http_server_stop` function.
+pub fn stop() -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::http_server_stop() })
+}
+
+/// Request passed to the handler of a dynamic resource.
+pub struct HttpRequest<'a> {
+    method: Method,
+    body: &'a [u8],
+}
+
+impl<'a> HttpRequest<'a> {
+    /// Method of the request.
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    /// Complete body of the request, empty for e.g. `GET` requests.
+    pub fn body(&self) -> &'a [u8] {
+        self.body
+    }
+}
+
+/// Response returned by the handler of a dynamic resource.
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Response with `status` and without body.
+    pub fn new(status: u16) -> Self {
+        HttpResponse {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// `200 OK` response with `body`, of the content type of the resource.
+    pub fn ok(body: Vec<u8>) -> Self {
+        HttpResponse {
+            body,
+            ..Self::new(200)
+        }
+    }
+
+    /// Adds the header `name`, e.g. `Location` for redirects.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Handler of a dynamic resource.
+type Handler = dyn FnMut(&HttpRequest<'_>) -> HttpResponse + Send;
+
+/// Handlers of the dynamic resources by the address of the resource.
+static HANDLERS: Handlers<usize, Handler> = Handlers::new();
+
+/// Response of a dynamic resource that is sent by the server after the handler returned.
+struct PendingResponse {
+    // the headers point to the strings
+    _strings: Vec<(CString, CString)>,
+    headers: Vec<ZHttpHeader>,
+    body: Vec<u8>,
+}
+
+impl PendingResponse {
+    fn new(response: HttpResponse) -> Self {
+        // headers with NUL characters in the name or the value are left out
+        let strings: Vec<(CString, CString)> = response
+            .headers
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    CString::new(name.as_str()).ok()?,
+                    CString::new(value.as_str()).ok()?,
+                ))
+            })
+            .collect();
+        let headers = strings
+            .iter()
+            .map(|(name, value)| ZHttpHeader {
+                name: name.as_ptr(),
+                value: value.as_ptr(),
+            })
+            .collect();
+        PendingResponse {
+            _strings: strings,
+            headers,
+            body: response.body,
+        }
+    }
+}
+
+struct DynamicState {
+    body: Vec<u8>,
+    response: Option<PendingResponse>,
+}
+
+/// Resource with static content, defined with [crate::http_static_resource].
+///
+/// The layout matches `struct http_resource_detail_static`.
+#[repr(transparent)]
+pub struct HttpStaticResource(ZHttpStaticDetail);
+
+// the detail is only read by the server
+unsafe impl Sync for HttpStaticResource {}
+
+impl HttpStaticResource {
+    /// Used by [crate::http_static_resource], the strings MUST be NUL terminated.
+    #[doc(hidden)]
+    pub const fn new(
+        content_type: &'static str,
+        content_encoding: Option<&'static str>,
+        data: &'static [u8],
+    ) -> Self {
+        HttpStaticResource(ZHttpStaticDetail {
+            common: detail(
+                Method::Get.bit(),
+                zephyr_sys::raw::http_resource_type_HTTP_RESOURCE_TYPE_STATIC,
+                content_type,
+                content_encoding,
+            ),
+            static_data: data.as_ptr() as *const c_void,
+            static_data_len: data.len(),
+        })
+    }
+
+    /// Used by [crate::http_service].
+    #[doc(hidden)]
+    pub const fn detail(&'static self) -> *mut c_void {
+        &self.0 as *const ZHttpStaticDetail as *mut c_void
+    }
+}
+
+/// Resource whose responses are built by a Rust handler, defined with
+/// [crate::http_dynamic_resource].
+///
+/// The user data of the detail points to the resource, the detail itself is written by the server.
+#[repr(C)]
+pub struct HttpDynamicResource {
+    detail: UnsafeCell<ZHttpDynamicDetail>,
+    state: UnsafeCell<DynamicState>,
+}
+
+// the server handles a dynamic resource for one client at a time, the handler is in HANDLERS
+unsafe impl Sync for HttpDynamicResource {}
+
+impl HttpDynamicResource {
+    /// Used by [crate::http_dynamic_resource], the string MUST be NUL terminated and `this` MUST
+    /// point to the resource itself.
+    #[doc(hidden)]
+    pub const fn new(
+        methods: u32,
+        content_type: &'static str,
+        this: *const HttpDynamicResource,
+    ) -> Self {
+        HttpDynamicResource {
+            detail: UnsafeCell::new(ZHttpDynamicDetail {
+                common: detail(
+                    methods,
+                    zephyr_sys::raw::http_resource_type_HTTP_RESOURCE_TYPE_DYNAMIC,
+                    content_type,
+                    None,
+                ),
+                cb: Some(dynamic_trampoline),
+                holder: core::ptr::null_mut(),
+                user_data: this as *mut c_void,
+            }),
+            state: UnsafeCell::new(DynamicState {
+                body: Vec::new(),
+                response: None,
+            }),
+        }
+    }
+
+    /// Used by [crate::http_service].
+    #[doc(hidden)]
+    pub const fn detail(&'static self) -> *mut c_void {
+        self.detail.get() as *mut c_void
+    }
+
+    fn key(&self) -> usize {
+        self as *const HttpDynamicResource as usize
+    }
+
+    /// Sets the handler which is called with the complete request, replacing a previous handler.
+    ///
+    /// Requests to resources without handler, e.g. before the handler is set, are answered with
+    /// `503 Service Unavailable`.
+    pub fn set_handler<F>(&'static self, handler: F)
+    where
+        F: FnMut(&HttpRequest<'_>) -> HttpResponse + Send + 'static,
+    {
+        // the replaced handler is dropped here, or by the server if it is called right now
+        drop(HANDLERS.insert(self.key(), Box::new(handler)).replaced);
+    }
+}
+
+const fn detail(
+    methods: u32,
+    resource_type: zephyr_sys::raw::http_resource_type,
+    content_type: &'static str,
+    content_encoding: Option<&'static str>,
+) -> ZHttpResourceDetail {
+    ZHttpResourceDetail {
+        bitmask_of_supported_http_methods: methods,
+        type_: resource_type,
+        path_len: 0,
+        content_encoding: match content_encoding {
+            Some(encoding) => encoding.as_ptr() as *const c_char,
+            None => core::ptr::null(),
+        },
+        content_type: content_type.as_ptr() as *const c_char,
+    }
+}
+
+unsafe extern "C" fn dynamic_trampoline(
+    client: *mut ZHttpClient,
+    status: zephyr_sys::raw::http_data_status,
+    request: *const ZHttpRequestCtx,
+    response: *mut ZHttpResponseCtx,
+    user_data: *mut c_void,
+) -> c_int {
+    let resource = &*(user_data as *const HttpDynamicResource);
+    let state = &mut *resource.state.get();
+
+    if status == zephyr_sys::raw::http_data_status_HTTP_SERVER_DATA_ABORTED {
+        state.body.clear();
+        state.response = None;
+        return 0;
+    }
+    let request = &*request;
+    if request.data_len > 0 {
+        // the body is received in chunks
+        let data = core::slice::from_raw_parts(request.data, request.data_len);
+        state.body.extend_from_slice(data);
+    }
+    if status != zephyr_sys::raw::http_data_status_HTTP_SERVER_DATA_FINAL {
+        return 0;
+    }
+
+    let body = core::mem::take(&mut state.body);
+    let http_request = HttpRequest {
+        method: Method::from((*client).method as u32),
+        body: &body,
+    };
+    let key = resource.key();
+    let reply = HANDLERS
+        .call(
+            |resource| *resource == key,
+            |handler| handler(&http_request),
+        )
+        .unwrap_or_else(|| HttpResponse::new(503));
+    let status = reply.status;
+    let pending = PendingResponse::new(reply);
+
+    let response = &mut *response;
+    response.status = status as _;
+    response.headers = pending.headers.as_ptr();
+    response.header_count = pending.headers.len();
+    response.body = pending.body.as_ptr();
+    response.body_len = pending.body.len();
+    response.final_chunk = true;
+    // the server sends the response after the callback returned, so it is kept in the state
+    state.response = Some(pending);
+    0
+}
+
+/// Entry of the resource table of a service, the equivalent of `struct http_resource_desc`.
+#[repr(transparent)]
+pub struct HttpResourceDesc(ZHttpResourceDesc);
+
+// the entry is immutable
+unsafe impl Sync for HttpResourceDesc {}
+
+impl HttpResourceDesc {
+    /// Used by [crate::http_service], the path MUST be NUL terminated.
+    #[doc(hidden)]
+    pub const fn new(path: &'static str, detail: *mut c_void) -> Self {
+        HttpResourceDesc(ZHttpResourceDesc {
+            resource: path.as_ptr() as *const c_char,
+            detail,
+        })
+    }
+}
+
+/// Service listening on a port, defined with [crate::http_service].
+///
+/// The layout matches `struct http_service_desc`, the service is read by the server.
+#[repr(transparent)]
+pub struct HttpService(ZHttpServiceDesc);
+
+// the service is immutable, the port and socket are changed through atomics
+unsafe impl Sync for HttpService {}
+
+impl HttpService {
+    /// Used by [crate::http_service], `begin` and `end` MUST delimit the resource table.
+    #[doc(hidden)]
+    pub const fn new(
+        port: &'static AtomicU16,
+        fd: &'static AtomicI32,
+        concurrent: usize,
+        backlog: usize,
+        begin: *const HttpResourceDesc,
+        end: *const HttpResourceDesc,
+    ) -> Self {
+        HttpService(ZHttpServiceDesc {
+            host: core::ptr::null(),
+            port: port as *const AtomicU16 as *mut u16,
+            fd: fd as *const AtomicI32 as *mut c_int,
+            detail: core::ptr::null_mut(),
+            concurrent,
+            backlog,
+            res_begin: begin as *mut ZHttpResourceDesc,
+            res_end: end as *mut ZHttpResourceDesc,
+            res_fallback: core::ptr::null_mut(),
+        })
+    }
+
+    /// Port of the service, chosen by the network stack once started if it was defined with port
+    /// `0`.
+    pub fn port(&self) -> u16 {
+        // the port of services defined with the macro points to an atomic
+        unsafe { &*(self.0.port as *const AtomicU16) }.load(Ordering::Relaxed)
+    }
+}
+
+/// Defines a resource with static content for [crate::http_service], the equivalent of a
+/// `struct http_resource_detail_static`.
+///
+/// Parameters are the name of the static, the content type, optionally the content encoding
+/// (e.g. `"gzip"`) and the content, usually from `include_bytes!`. The resource only accepts
+/// `GET` requests.
+#[macro_export]
+macro_rules! http_static_resource {
+    ($vis:vis $name:ident = $content_type:literal, $data:expr) => {
+        $vis static $name: $crate::network::http::server::HttpStaticResource =
+            $crate::network::http::server::HttpStaticResource::new(
+                concat!($content_type, "\0"),
+                None,
+                $data,
+            );
+    };
+    ($vis:vis $name:ident = $content_type:literal, $encoding:literal, $data:expr) => {
+        $vis static $name: $crate::network::http::server::HttpStaticResource =
+            $crate::network::http::server::HttpStaticResource::new(
+                concat!($content_type, "\0"),
+                Some(concat!($encoding, "\0")),
+                $data,
+            );
+    };
+}
+
+/// Defines a resource handled by a Rust closure for [crate::http_service], the equivalent of a
+/// `struct http_resource_detail_dynamic`.
+///
+/// Parameters are the name of the static, the content type of the responses and the accepted
+/// [crate::network::http::Method]s. The handler is set with
+/// [crate::network::http::server::HttpDynamicResource::set_handler].
+#[macro_export]
+macro_rules! http_dynamic_resource {
+    ($vis:vis $name:ident = $content_type:literal, [$($method:ident),+ $(,)?]) => {
+        $vis static $name: $crate::network::http::server::HttpDynamicResource =
+            $crate::network::http::server::HttpDynamicResource::new(
+                0 $(| $crate::network::http::Method::$method.bit())+,
+                concat!($content_type, "\0"),
+                &$name,
+            );
+    };
+}
+
+/// Registers an HTTP service on `port` with resources defined by [crate::http_static_resource]
+/// and [crate::http_dynamic_resource], the equivalent of `HTTP_SERVICE_DEFINE` and
+/// `HTTP_RESOURCE_DEFINE`.
+///
+/// The service listens on all addresses and handles up to 4 clients at once.
+#[macro_export]
+macro_rules! http_service {
+    ($vis:vis $name:ident = $port:expr, [$($path:literal => $resource:path),+ $(,)?]) => {
+        #[used]
+        #[link_section = "._http_service_desc.static.zephyr_rust_wrappers"]
+        $vis static $name: $crate::network::http::server::HttpService = {
+            const COUNT: usize = [$($path),+].len();
+
+            // the end of the table delimits the resources of the service
+            #[repr(C)]
+            struct Table {
+                resources: [$crate::network::http::server::HttpResourceDesc; COUNT],
+                end: [$crate::network::http::server::HttpResourceDesc; 0],
+            }
+
+            static TABLE: Table = Table {
+                resources: [$($crate::network::http::server::HttpResourceDesc::new(
+                    concat!($path, "\0"),
+                    $resource.detail(),
+                )),+],
+                end: [],
+            };
+            static PORT: ::core::sync::atomic::AtomicU16 =
+                ::core::sync::atomic::AtomicU16::new($port as u16);
+            static FD: ::core::sync::atomic::AtomicI32 = ::core::sync::atomic::AtomicI32::new(-1);
+
+            $crate::network::http::server::HttpService::new(
+                &PORT,
+                &FD,
+                4,
+                4,
+                &TABLE.resources as *const _ as *const _,
+                &TABLE.end as *const _ as *const _,
+            )
+        };
+    };
+}
@@ -0,0 +1,185 @@
+//! Wrappers for the IEEE 802.15.4 L2 of Zephyr (`CONFIG_NET_L2_IEEE802154_MGMT`).
+//!
+//! The radio parameters are configured with the `NET_REQUEST_IEEE802154_*` requests of the
+//! network management API. [Ieee802154::raw_socket] sends and receives MAC frames directly, which
+//! bypasses IP (and 6LoWPAN) entirely, e.g. for sniffers or custom MAC protocols:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::ieee802154::Ieee802154;
+//!# use zephyr_rust_wrappers::network::interface::NetworkInterface;
+//! let radio = Ieee802154::new(NetworkInterface::get_default().unwrap());
+//! radio.set_channel(26).unwrap();
+//! let socket = radio.raw_socket().unwrap();
+//! let mut frame = [0u8; 127];
+//! let len = socket.recv(&mut frame).unwrap();
+//! println!("{:02x?}", &frame[..len]);
+//! ```
+
+use super::interface::{NetworkInterface, RequestHandler};
+use super::socket::packet::{protocol, PacketSocket};
+use crate::{Context, ZephyrError, ZephyrResult};
+use core::ffi::c_int;
+
+const CONTEXT: Ieee802154WrapperContext = Ieee802154WrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// The short address of devices without a short address.
+pub const NO_SHORT_ADDRESS: u16 = zephyr_sys::raw::IEEE802154_NO_SHORT_ADDRESS as u16;
+
+/// The broadcast PAN ID and short address.
+pub const BROADCAST: u16 = zephyr_sys::raw::IEEE802154_BROADCAST_ADDRESS as u16;
+
+/// IEEE 802.15.4 functions of a network interface.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Ieee802154 {
+    iface: NetworkInterface,
+}
+
+impl Ieee802154 {
+    /// IEEE 802.15.4 functions of `iface`, which MUST be the interface of an IEEE 802.15.4 radio.
+    pub fn new(iface: NetworkInterface) -> Self {
+        Ieee802154 { iface }
+    }
+
+    /// The network interface.
+    pub fn interface(&self) -> NetworkInterface {
+        self.iface
+    }
+
+    fn set<T>(&self, handler: RequestHandler, code: u32, mut value: T) -> ZephyrResult<()> {
+        result(self.iface.net_mgmt(handler, code, Some(&mut value)))
+    }
+
+    fn get<T: Default>(&self, handler: RequestHandler, code: u32) -> ZephyrResult<T> {
+        let mut value = T::default();
+        result(self.iface.net_mgmt(handler, code, Some(&mut value)))?;
+        Ok(value)
+    }
+
+    /// Sends `NET_REQUEST_IEEE802154_SET_CHANNEL`.
+    pub fn set_channel(&self, channel: u16) -> ZephyrResult<()> {
+        self.set(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_IEEE802154_SET_CHANNEL,
+            zephyr_sys::raw::NET_REQUEST_IEEE802154_SET_CHANNEL,
+            channel,
+        )
+    }
+
+    /// Sends `NET_REQUEST_IEEE802154_GET_CHANNEL`.
+    pub fn channel(&self) -> ZephyrResult<u16> {
+        self.get(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_IEEE802154_GET_CHANNEL,
+            zephyr_sys::raw::NET_REQUEST_IEEE802154_GET_CHANNEL,
+        )
+    }
+
+    /// Sends `NET_REQUEST_IEEE802154_SET_PAN_ID`.
+    pub fn set_pan_id(&self, pan_id: u16) -> ZephyrResult<()> {
+        self.set(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_IEEE802154_SET_PAN_ID,
+            zephyr_sys::raw::NET_REQUEST_IEEE802154_SET_PAN_ID,
+            pan_id,
+        )
+    }
+
+    /// Sends `NET_REQUEST_IEEE802154_GET_PAN_ID`.
+    pub fn pan_id(&self) -> ZephyrResult<u16> {
+        self.get(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_IEEE802154_GET_PAN_ID,
+            zephyr_sys::raw::NET_REQUEST_IEEE802154_GET_PAN_ID,
+        )
+    }
+
+    /// Sends `NET_REQUEST_IEEE802154_SET_SHORT_ADDR`, [NO_SHORT_ADDRESS] removes the short
+    /// address.
+    pub fn set_short_address(&self, address: u16) -> ZephyrResult<()> {
+        self.set(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_IEEE802154_SET_SHORT_ADDR,
+            zephyr_sys::raw::NET_REQUEST_IEEE802154_SET_SHORT_ADDR,
+            address,
+        )
+    }
+
+    /// Sends `NET_REQUEST_IEEE802154_GET_SHORT_ADDR`.
+    pub fn short_address(&self) -> ZephyrResult<u16> {
+        self.get(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_IEEE802154_GET_SHORT_ADDR,
+            zephyr_sys::raw::NET_REQUEST_IEEE802154_GET_SHORT_ADDR,
+        )
+    }
+
+    /// Sends `NET_REQUEST_IEEE802154_SET_EXT_ADDR`, the address in big endian byte order.
+    pub fn set_extended_address(&self, address: [u8; 8]) -> ZephyrResult<()> {
+        self.set(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_IEEE802154_SET_EXT_ADDR,
+            zephyr_sys::raw::NET_REQUEST_IEEE802154_SET_EXT_ADDR,
+            address,
+        )
+    }
+
+    /// Sends `NET_REQUEST_IEEE802154_GET_EXT_ADDR`.
+    pub fn extended_address(&self) -> ZephyrResult<[u8; 8]> {
+        self.get(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_IEEE802154_GET_EXT_ADDR,
+            zephyr_sys::raw::NET_REQUEST_IEEE802154_GET_EXT_ADDR,
+        )
+    }
+
+    /// Sends `NET_REQUEST_IEEE802154_SET_TX_POWER`, the power in dBm.
+    pub fn set_tx_power(&self, dbm: i16) -> ZephyrResult<()> {
+        self.set(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_IEEE802154_SET_TX_POWER,
+            zephyr_sys::raw::NET_REQUEST_IEEE802154_SET_TX_POWER,
+            dbm,
+        )
+    }
+
+    /// Sends `NET_REQUEST_IEEE802154_GET_TX_POWER`.
+    pub fn tx_power(&self) -> ZephyrResult<i16> {
+        self.get(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_IEEE802154_GET_TX_POWER,
+            zephyr_sys::raw::NET_REQUEST_IEEE802154_GET_TX_POWER,
+        )
+    }
+
+    /// Sends `NET_REQUEST_IEEE802154_SET_ACK` or `NET_REQUEST_IEEE802154_UNSET_ACK`, whether
+    /// acknowledgments are requested for sent frames.
+    pub fn set_ack(&self, ack: bool) -> ZephyrResult<()> {
+        let (handler, code) = if ack {
+            (
+                zephyr_sys::raw::net_mgmt_NET_REQUEST_IEEE802154_SET_ACK as RequestHandler,
+                zephyr_sys::raw::NET_REQUEST_IEEE802154_SET_ACK,
+            )
+        } else {
+            (
+                zephyr_sys::raw::net_mgmt_NET_REQUEST_IEEE802154_UNSET_ACK as RequestHandler,
+                zephyr_sys::raw::NET_REQUEST_IEEE802154_UNSET_ACK,
+            )
+        };
+        result(self.iface.net_mgmt::<()>(handler, code, None))
+    }
+
+    /// Packet socket of the interface sending and receiving MAC frames without their FCS.
+    ///
+    /// Received frames are also processed by the stack, use a dedicated interface or a radio in
+    /// promiscuous mode to capture all traffic of a channel.
+    pub fn raw_socket(&self) -> ZephyrResult<PacketSocket> {
+        PacketSocket::bind(self.iface, protocol::IEEE802154)
+    }
+}
+
+#[derive(Debug)]
+struct Ieee802154WrapperContext {}
+
+impl Context for Ieee802154WrapperContext {
+    fn name(&self) -> &'static str {
+        "ieee802154 wrapper"
+    }
+}
@@ -0,0 +1,405 @@
+//! Wrappers for the network interfaces of Zephyr.
+//!
+//! Interfaces are created by the drivers and the network stack at boot, a [NetworkInterface] is
+//! a copyable handle to one of them.
+
+use super::addr::{from_in_addr, to_in6_addr, to_in_addr, ZIn6Addr, ZInAddr};
+use super::events::{subscribe_raw, SubscriptionId};
+use crate::time::Timeout;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use core::ffi::{c_int, c_void};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use core::time::Duration;
+use zephyr_sys::raw::{net_if as ZNetIf, net_if_dhcpv4 as ZDhcpv4};
+
+const CONTEXT: InterfaceWrapperContext = InterfaceWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+fn no_space() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::NoSpace, &CONTEXT)
+}
+
+fn not_found() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::NoEntry, &CONTEXT)
+}
+
+/// Lifetime of addresses and prefixes that do not expire.
+pub const INFINITE_LIFETIME: u32 = u32::MAX;
+
+/// Lifetime of an IPv6 address or prefix in seconds, [INFINITE_LIFETIME] never expires.
+fn lifetime(lifetime: Option<Duration>) -> u32 {
+    lifetime.map_or(INFINITE_LIFETIME, |lifetime| lifetime.as_secs() as u32)
+}
+
+/// Lease of the DHCPv4 client, reported with [Dhcpv4Event::Bound].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Dhcpv4Lease {
+    pub address: Ipv4Addr,
+    /// Lease time in seconds.
+    pub lease_time: u32,
+    /// Time in seconds until the client renews the lease.
+    pub renewal_time: u32,
+    /// Time in seconds until the client rebinds the lease with any server.
+    pub rebinding_time: u32,
+}
+
+impl From<&ZDhcpv4> for Dhcpv4Lease {
+    fn from(other: &ZDhcpv4) -> Self {
+        Dhcpv4Lease {
+            address: from_in_addr(&other.requested_ip),
+            lease_time: other.lease_time,
+            renewal_time: other.renewal_time,
+            rebinding_time: other.rebinding_time,
+        }
+    }
+}
+
+/// Event of the DHCPv4 client of an interface.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Dhcpv4Event {
+    /// The client was started.
+    Start,
+    /// A lease was acquired or renewed, the lease is only reported with
+    /// `CONFIG_NET_MGMT_EVENT_INFO`.
+    Bound(Option<Dhcpv4Lease>),
+    /// The client was stopped, the address has been removed.
+    Stop,
+}
+
+/// Handler function of a network management request, e.g. `net_mgmt_NET_REQUEST_WIFI_SCAN`.
+pub(crate) type RequestHandler =
+    unsafe extern "C" fn(u32, *mut ZNetIf, *mut c_void, usize) -> c_int;
+
+/// Handle of a network interface.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct NetworkInterface {
+    iface: *mut ZNetIf,
+}
+
+// interfaces are static and the functions of the network stack lock them
+unsafe impl Send for NetworkInterface {}
+unsafe impl Sync for NetworkInterface {}
+
+impl NetworkInterface {
+    /// Wrapper to the `net_if_get_default` function, returns the default interface.
+    pub fn get_default() -> Option<Self> {
+        unsafe { Self::from_raw(zephyr_sys::raw::net_if_get_default()) }
+    }
+
+    /// Wrapper to the `net_if_get_by_index` function, interfaces are numbered from `1`.
+    pub fn by_index(index: i32) -> Option<Self> {
+        unsafe { Self::from_raw(zephyr_sys::raw::net_if_get_by_index(index)) }
+    }
+
+    /// Creates a handle of `iface`, returns `None` if `iface` is null.
+    ///
+    /// `iface` MUST be an interface obtained from the Zephyr API.
+    pub unsafe fn from_raw(iface: *mut ZNetIf) -> Option<Self> {
+        if iface.is_null() {
+            None
+        } else {
+            Some(NetworkInterface { iface })
+        }
+    }
+
+    /// Pointer to the interface for the Zephyr API.
+    pub fn as_raw(&self) -> *mut ZNetIf {
+        self.iface
+    }
+
+    /// Wrapper to the `net_if_get_by_iface` function, returns the index of the interface.
+    pub fn index(&self) -> i32 {
+        unsafe { zephyr_sys::raw::net_if_get_by_iface(self.iface) }
+    }
+
+    /// Wrapper to the `net_if_up` function, brings the interface up.
+    pub fn up(&self) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::net_if_up(self.iface) })
+    }
+
+    /// Wrapper to the `net_if_down` function, brings the interface down.
+    pub fn down(&self) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::net_if_down(self.iface) })
+    }
+
+    /// Sends the network management request `code` with `data` to the interface, `handler` is the
+    /// function the `net_mgmt` macro expands to. Returns the error number of the handler.
+    pub(crate) fn net_mgmt<T>(
+        &self,
+        handler: RequestHandler,
+        code: u32,
+        data: Option<&mut T>,
+    ) -> c_int {
+        let (data, len) = match data {
+            Some(data) => (data as *mut T as *mut c_void, core::mem::size_of::<T>()),
+            None => (core::ptr::null_mut(), 0),
+        };
+        unsafe { handler(code, self.iface, data, len) }
+    }
+
+    /// Wrapper to the `net_dhcpv4_start` function, starts the DHCPv4 client of the interface.
+    pub fn dhcpv4_start(&self) {
+        unsafe { zephyr_sys::raw::net_dhcpv4_start(self.iface) }
+    }
+
+    /// Wrapper to the `net_dhcpv4_stop` function, stops the client and releases the address.
+    pub fn dhcpv4_stop(&self) {
+        unsafe { zephyr_sys::raw::net_dhcpv4_stop(self.iface) }
+    }
+
+    /// Wrapper to the `net_dhcpv4_restart` function, restarts the client, e.g. after the link
+    /// changed to another network.
+    pub fn dhcpv4_restart(&self) {
+        unsafe { zephyr_sys::raw::net_dhcpv4_restart(self.iface) }
+    }
+
+    /// Wrapper to the `net_if_ipv4_addr_add` function, adds the manually configured `addr`.
+    pub fn add_ipv4_addr(&self, addr: Ipv4Addr) -> ZephyrResult<()> {
+        let raw = to_in_addr(&addr);
+        let entry = unsafe {
+            zephyr_sys::raw::net_if_ipv4_addr_add(
+                self.iface,
+                &raw as *const ZInAddr as *mut ZInAddr,
+                zephyr_sys::raw::net_addr_type_NET_ADDR_MANUAL,
+                0,
+            )
+        };
+        if entry.is_null() {
+            Err(no_space())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Wrapper to the `net_if_ipv4_addr_rm` function, removes `addr` from the interface.
+    pub fn remove_ipv4_addr(&self, addr: Ipv4Addr) -> ZephyrResult<()> {
+        let raw = to_in_addr(&addr);
+        if unsafe { zephyr_sys::raw::net_if_ipv4_addr_rm(self.iface, &raw) } {
+            Ok(())
+        } else {
+            Err(not_found())
+        }
+    }
+
+    /// Wrapper to the `net_if_ipv4_set_netmask_by_addr` function, sets the netmask of `addr`.
+    pub fn set_ipv4_netmask(&self, addr: Ipv4Addr, netmask: Ipv4Addr) -> ZephyrResult<()> {
+        let raw_addr = to_in_addr(&addr);
+        let raw_netmask = to_in_addr(&netmask);
+        let set = unsafe {
+            zephyr_sys::raw::net_if_ipv4_set_netmask_by_addr(self.iface, &raw_addr, &raw_netmask)
+        };
+        if set {
+            Ok(())
+        } else {
+            Err(not_found())
+        }
+    }
+
+    /// Wrapper to the `net_if_ipv4_set_gw` function, sets the IPv4 gateway of the interface.
+    pub fn set_ipv4_gateway(&self, gateway: Ipv4Addr) {
+        let raw = to_in_addr(&gateway);
+        unsafe { zephyr_sys::raw::net_if_ipv4_set_gw(self.iface, &raw) }
+    }
+
+    /// Wrapper to the `net_if_ipv6_addr_add` function, adds the manually configured `addr`,
+    /// which is valid for `lifetime` or forever if `None`.
+    pub fn add_ipv6_addr(&self, addr: Ipv6Addr, lifetime: Option<Duration>) -> ZephyrResult<()> {
+        let raw = to_in6_addr(&addr);
+        let entry = unsafe {
+            zephyr_sys::raw::net_if_ipv6_addr_add(
+                self.iface,
+                &raw as *const ZIn6Addr as *mut ZIn6Addr,
+                zephyr_sys::raw::net_addr_type_NET_ADDR_MANUAL,
+                self::lifetime(lifetime),
+            )
+        };
+        if entry.is_null() {
+            Err(no_space())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Wrapper to the `net_if_ipv6_addr_rm` function, removes `addr` from the interface.
+    pub fn remove_ipv6_addr(&self, addr: Ipv6Addr) -> ZephyrResult<()> {
+        let raw = to_in6_addr(&addr);
+        if unsafe { zephyr_sys::raw::net_if_ipv6_addr_rm(self.iface, &raw) } {
+            Ok(())
+        } else {
+            Err(not_found())
+        }
+    }
+
+    /// Wrapper to the `net_if_ipv6_prefix_add` function, adds the on-link prefix `prefix/len`,
+    /// which is valid for `lifetime` or forever if `None`.
+    pub fn add_ipv6_prefix(
+        &self,
+        prefix: Ipv6Addr,
+        len: u8,
+        lifetime: Option<Duration>,
+    ) -> ZephyrResult<()> {
+        let raw = to_in6_addr(&prefix);
+        let entry = unsafe {
+            zephyr_sys::raw::net_if_ipv6_prefix_add(
+                self.iface,
+                &raw as *const ZIn6Addr as *mut ZIn6Addr,
+                len,
+                self::lifetime(lifetime),
+            )
+        };
+        if entry.is_null() {
+            Err(no_space())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Wrapper to the `net_if_ipv6_prefix_rm` function, removes the prefix `prefix/len`.
+    pub fn remove_ipv6_prefix(&self, prefix: Ipv6Addr, len: u8) -> ZephyrResult<()> {
+        let raw = to_in6_addr(&prefix);
+        let removed = unsafe {
+            zephyr_sys::raw::net_if_ipv6_prefix_rm(
+                self.iface,
+                &raw as *const ZIn6Addr as *mut ZIn6Addr,
+                len,
+            )
+        };
+        if removed {
+            Ok(())
+        } else {
+            Err(not_found())
+        }
+    }
+
+    /// Adds the manually configured `addr` of either family, IPv6 addresses do not expire.
+    pub fn add_addr(&self, addr: IpAddr) -> ZephyrResult<()> {
+        match addr {
+            IpAddr::V4(addr) => self.add_ipv4_addr(addr),
+            IpAddr::V6(addr) => self.add_ipv6_addr(addr, None),
+        }
+    }
+
+    /// Removes `addr` of either family from the interface.
+    pub fn remove_addr(&self, addr: IpAddr) -> ZephyrResult<()> {
+        match addr {
+            IpAddr::V4(addr) => self.remove_ipv4_addr(addr),
+            IpAddr::V6(addr) => self.remove_ipv6_addr(addr),
+        }
+    }
+
+    /// Wrapper to the `net_ipv4_igmp_join` function, joins the multicast group `addr` and
+    /// announces it with IGMP (`CONFIG_NET_IPV4_IGMP`).
+    ///
+    /// Fails with [ErrorNumber::Already] if the interface already is a member of the group.
+    pub fn join_multicast_v4(&self, addr: Ipv4Addr) -> ZephyrResult<()> {
+        let raw = to_in_addr(&addr);
+        result(unsafe { zephyr_sys::raw::net_ipv4_igmp_join(self.iface, &raw, core::ptr::null()) })
+    }
+
+    /// Wrapper to the `net_ipv4_igmp_leave` function, leaves the multicast group `addr`.
+    pub fn leave_multicast_v4(&self, addr: Ipv4Addr) -> ZephyrResult<()> {
+        let raw = to_in_addr(&addr);
+        result(unsafe { zephyr_sys::raw::net_ipv4_igmp_leave(self.iface, &raw) })
+    }
+
+    /// Wrapper to the `net_ipv6_mld_join` function, joins the multicast group `addr` and
+    /// announces it with MLD (`CONFIG_NET_IPV6_MLD`).
+    ///
+    /// Fails with [ErrorNumber::Already] if the interface already is a member of the group.
+    pub fn join_multicast_v6(&self, addr: Ipv6Addr) -> ZephyrResult<()> {
+        let raw = to_in6_addr(&addr);
+        result(unsafe { zephyr_sys::raw::net_ipv6_mld_join(self.iface, &raw) })
+    }
+
+    /// Wrapper to the `net_ipv6_mld_leave` function, leaves the multicast group `addr`.
+    pub fn leave_multicast_v6(&self, addr: Ipv6Addr) -> ZephyrResult<()> {
+        let raw = to_in6_addr(&addr);
+        result(unsafe { zephyr_sys::raw::net_ipv6_mld_leave(self.iface, &raw) })
+    }
+
+    /// Joins the multicast group `addr` of either family.
+    pub fn join_multicast(&self, addr: IpAddr) -> ZephyrResult<()> {
+        match addr {
+            IpAddr::V4(addr) => self.join_multicast_v4(addr),
+            IpAddr::V6(addr) => self.join_multicast_v6(addr),
+        }
+    }
+
+    /// Leaves the multicast group `addr` of either family.
+    pub fn leave_multicast(&self, addr: IpAddr) -> ZephyrResult<()> {
+        match addr {
+            IpAddr::V4(addr) => self.leave_multicast_v4(addr),
+            IpAddr::V6(addr) => self.leave_multicast_v6(addr),
+        }
+    }
+
+    /// Returns `true` if the DHCPv4 client of the interface holds a lease.
+    pub fn is_dhcpv4_bound(&self) -> bool {
+        // interfaces are valid and the state is only written by the DHCPv4 client
+        let state = unsafe { (*self.iface).config.dhcpv4.state };
+        state == zephyr_sys::raw::net_dhcpv4_state_NET_DHCPV4_BOUND
+    }
+
+    /// Calls `f` for the DHCPv4 events of this interface, returns the id to unsubscribe with
+    /// [crate::network::events::unsubscribe].
+    pub fn on_dhcpv4_event<F>(&self, mut f: F) -> SubscriptionId
+    where
+        F: FnMut(Dhcpv4Event) + Send + 'static,
+    {
+        let this = *self;
+        let mask = zephyr_sys::raw::NET_EVENT_IPV4_DHCP_START
+            | zephyr_sys::raw::NET_EVENT_IPV4_DHCP_BOUND
+            | zephyr_sys::raw::NET_EVENT_IPV4_DHCP_STOP;
+        subscribe_raw(mask, move |event, iface, info| {
+            if iface != Some(this) {
+                return;
+            }
+            match event {
+                zephyr_sys::raw::NET_EVENT_IPV4_DHCP_START => f(Dhcpv4Event::Start),
+                zephyr_sys::raw::NET_EVENT_IPV4_DHCP_BOUND => {
+                    let lease = info
+                        .filter(|info| info.len() >= core::mem::size_of::<ZDhcpv4>())
+                        // the info of the event is the DHCPv4 state of the interface
+                        .map(|info| unsafe { &*(info.as_ptr() as *const ZDhcpv4) }.into());
+                    f(Dhcpv4Event::Bound(lease))
+                }
+                zephyr_sys::raw::NET_EVENT_IPV4_DHCP_STOP => f(Dhcpv4Event::Stop),
+                _ => {}
+            }
+        })
+    }
+
+    /// Wrapper to the `net_mgmt_event_wait_on_iface` function, blocks until the DHCPv4 client of
+    /// the interface holds a lease or `timeout` expired.
+    pub fn wait_for_dhcpv4_bound(&self, timeout: Timeout) -> ZephyrResult<()> {
+        if self.is_dhcpv4_bound() {
+            return Ok(());
+        }
+        result(unsafe {
+            zephyr_sys::raw::net_mgmt_event_wait_on_iface(
+                self.iface,
+                zephyr_sys::raw::NET_EVENT_IPV4_DHCP_BOUND,
+                core::ptr::null_mut(),
+                core::ptr::null_mut(),
+                core::ptr::null_mut(),
+                timeout,
+            )
+        })
+    }
+}
+
+#[derive(Debug)]
+struct InterfaceWrapperContext {}
+
+impl Context for InterfaceWrapperContext {
+    fn name(&self) -> &'static str {
+        "interface wrapper"
+    }
+}
@@ -0,0 +1,217 @@
+//! Wrappers for the mDNS responder and DNS Service Discovery (DNS-SD) of Zephyr.
+//!
+//! The responder (`CONFIG_MDNS_RESPONDER`) answers queries for the hostname of the device, which
+//! can be changed at runtime with `CONFIG_NET_HOSTNAME_DYNAMIC`. With
+//! `CONFIG_MDNS_RESPONDER_DNS_SD` it also announces the services registered with
+//! [crate::dns_sd_service]:
+//!
+//! ```no_run
+//! zephyr_rust_wrappers::dns_sd_service!(pub HTTP_SERVICE = "sensor", "_http", "_tcp", 80, b"");
+//!
+//! HTTP_SERVICE.set_port(8080);
+//! ```
+//!
+//! Like in C, services are registered at link time, the records are collected in the iterable
+//! section of the responder. [browse] looks up the instances of a service type on the LAN,
+//! which requires `CONFIG_MDNS_RESOLVER`.
+
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::{c_char, c_int, c_void, CStr};
+use core::sync::atomic::{AtomicU16, Ordering};
+use core::time::Duration;
+use zephyr_sys::raw::{dns_addrinfo as ZDnsAddrInfo, dns_sd_rec as ZDnsSdRec, k_sem};
+
+const CONTEXT: MdnsWrapperContext = MdnsWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `net_hostname_set` function, requires `CONFIG_NET_HOSTNAME_DYNAMIC`.
+pub fn set_hostname(hostname: &str) -> ZephyrResult<()> {
+    result(unsafe {
+        zephyr_sys::raw::net_hostname_set(hostname.as_ptr() as *mut c_char, hostname.len())
+    })
+}
+
+/// Wrapper to the `net_hostname_get` function.
+pub fn hostname() -> String {
+    // the hostname is a static NUL terminated string
+    unsafe { CStr::from_ptr(zephyr_sys::raw::net_hostname_get()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Service announced with DNS-SD, defined with [crate::dns_sd_service].
+///
+/// The layout matches `struct dns_sd_rec`, the record is read by the responder.
+#[repr(transparent)]
+pub struct DnsSdService(ZDnsSdRec);
+
+// the record is immutable, only the port is changed through an atomic
+unsafe impl Sync for DnsSdService {}
+
+impl DnsSdService {
+    /// Used by [crate::dns_sd_service].
+    #[doc(hidden)]
+    pub const fn new(
+        instance: &'static CStr,
+        service: &'static CStr,
+        proto: &'static CStr,
+        text: &'static [u8],
+        port: &'static AtomicU16,
+    ) -> Self {
+        DnsSdService(ZDnsSdRec {
+            instance: instance.as_ptr(),
+            service: service.as_ptr(),
+            proto: proto.as_ptr(),
+            domain: "local\0".as_ptr() as *const c_char,
+            text: text.as_ptr() as *const c_char,
+            text_size: text.len(),
+            port: port as *const AtomicU16 as *const u16,
+        })
+    }
+
+    /// Used by [crate::dns_sd_service], fails to compile for strings with NUL characters.
+    #[doc(hidden)]
+    pub const fn c_str(bytes: &'static [u8]) -> &'static CStr {
+        match CStr::from_bytes_with_nul(bytes) {
+            Ok(string) => string,
+            Err(_) => panic!("DNS-SD names must not contain NUL characters"),
+        }
+    }
+
+    fn port_cell(&self) -> &AtomicU16 {
+        // the port of records defined with the macro points to an atomic
+        unsafe { &*(self.0.port as *const AtomicU16) }
+    }
+
+    /// Port of the service.
+    pub fn port(&self) -> u16 {
+        u16::from_be(self.port_cell().load(Ordering::Relaxed))
+    }
+
+    /// Change the port of the service, e.g. after binding a socket to port `0`.
+    pub fn set_port(&self, port: u16) {
+        self.port_cell().store(port.to_be(), Ordering::Relaxed);
+    }
+
+    /// Instance name of the service.
+    pub fn instance(&self) -> &'static str {
+        // the strings of records defined with the macro are static NUL terminated UTF-8 strings
+        unsafe { CStr::from_ptr(self.0.instance) }.to_str().unwrap_or("")
+    }
+}
+
+/// Registers a DNS-SD service in the `local` domain, the equivalent of `DNS_SD_REGISTER_SERVICE`.
+///
+/// Parameters are the name of the static, the instance name, the service type (e.g. `"_http"`),
+/// the protocol (`"_tcp"` or `"_udp"`), the initial port and the TXT record. The TXT record is a
+/// byte string in the DNS format, each `key=value` entry prefixed with its length, e.g.
+/// `b"\x0apath=/api"`.
+#[macro_export]
+macro_rules! dns_sd_service {
+    ($vis:vis $name:ident = $instance:literal, $service:literal, $proto:literal, $port:expr,
+        $text:expr) => {
+        #[used]
+        #[link_section = "._dns_sd_rec.static.zephyr_rust_wrappers"]
+        $vis static $name: $crate::network::mdns::DnsSdService = {
+            static PORT: ::core::sync::atomic::AtomicU16 =
+                ::core::sync::atomic::AtomicU16::new(($port as u16).to_be());
+            $crate::network::mdns::DnsSdService::new(
+                $crate::network::mdns::DnsSdService::c_str(concat!($instance, "\0").as_bytes()),
+                $crate::network::mdns::DnsSdService::c_str(concat!($service, "\0").as_bytes()),
+                $crate::network::mdns::DnsSdService::c_str(concat!($proto, "\0").as_bytes()),
+                $text,
+                &PORT,
+            )
+        };
+    };
+}
+
+/// State of a [browse], shared with the resolver callback.
+struct BrowseState {
+    instances: Vec<String>,
+    error: Option<c_int>,
+    done: k_sem,
+}
+
+unsafe extern "C" fn browse_callback(
+    status: zephyr_sys::raw::dns_resolve_status,
+    info: *mut ZDnsAddrInfo,
+    user_data: *mut c_void,
+) {
+    let state = &mut *(user_data as *mut BrowseState);
+    match status {
+        zephyr_sys::raw::dns_resolve_status_DNS_EAI_INPROGRESS => {
+            // PTR answers carry the instance name instead of an address
+            if let Some(info) = info.as_ref() {
+                if info.ai_family as u32 == zephyr_sys::raw::AF_LOCAL {
+                    let name = &info.ai_canonname[..info.ai_addrlen as usize];
+                    let name: Vec<u8> = name.iter().map(|c| *c as u8).collect();
+                    state.instances.push(String::from_utf8_lossy(&name).into_owned());
+                }
+            }
+            return;
+        }
+        zephyr_sys::raw::dns_resolve_status_DNS_EAI_ALLDONE => {}
+        status => state.error = Some(status as c_int),
+    }
+    zephyr_sys::syscalls::any::k_sem_give(&mut state.done);
+}
+
+/// Wrapper to the `dns_resolve_service` function, returns the instances of the service type
+/// `service` (e.g. `"_http._tcp.local"`) that answered within `duration`.
+///
+/// Blocks until the query finished, instances answering after the first response might be missed
+/// depending on the resolver configuration.
+pub fn browse(service: &str, duration: Duration) -> ZephyrResult<Vec<String>> {
+    let query = CString::new(service)
+        .map_err(|_| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))?;
+    let mut state = Box::new(BrowseState {
+        instances: Vec::new(),
+        error: None,
+        // zeroed as the semaphore is initialized below
+        done: unsafe { core::mem::zeroed() },
+    });
+    result(unsafe { zephyr_sys::syscalls::any::k_sem_init(&mut state.done, 0, 1) })?;
+
+    let mut id: u16 = 0;
+    let errno = unsafe {
+        zephyr_sys::raw::dns_resolve_service(
+            zephyr_sys::raw::dns_resolve_get_default(),
+            query.as_ptr(),
+            &mut id,
+            Some(browse_callback),
+            state.as_mut() as *mut BrowseState as *mut c_void,
+            duration.as_millis() as i32,
+        )
+    };
+    result(errno)?;
+
+    // the resolver calls the callback with DNS_EAI_CANCELED when the query times out
+    unsafe { zephyr_sys::syscalls::any::k_sem_take(&mut state.done, crate::time::FOREVER) };
+    match state.error {
+        None | Some(zephyr_sys::raw::dns_resolve_status_DNS_EAI_CANCELED) => {
+            Ok(core::mem::take(&mut state.instances))
+        }
+        Some(_) => Err(ZephyrError::new_with_context(ErrorNumber::Io, &CONTEXT)),
+    }
+}
+
+#[derive(Debug)]
+struct MdnsWrapperContext {}
+
+impl Context for MdnsWrapperContext {
+    fn name(&self) -> &'static str {
+        "mdns wrapper"
+    }
+}
@@ -0,0 +1,42 @@
+//! Wrappers for the Zephyr networking stack.
+//!
+//! [socket] offers types mirroring `std::net` on top of the BSD socket layer of Zephyr, [poll]
+//! waits for events on several of them and [dns] resolves host names into their addresses.
+//! [mdns] announces the device and its services on the local network.
+//!
+//! [interface] controls the network interfaces, e.g. their DHCPv4 client, and [events] reports the
+//! events of the network management API. [wifi] scans for and connects to Wi-Fi networks,
+//! [ethernet] configures Ethernet links, [ieee802154] IEEE 802.15.4 radios, `openthread` (feature
+//! `openthread`) joins Thread networks and [conn_mgr] reports when the network is usable.
+//! [addr] converts between the address types of `std::net` and the address structs of Zephyr,
+//! [buf] wraps the network buffers and [pkt] the network packets made of them, [stats] reads the
+//! counters of the network stack and [capture] mirrors the traffic of an interface.
+//!
+//! [coap] implements the application protocol CoAP on top of the sockets, [http] wraps the HTTP
+//! server of Zephyr and [websocket] its WebSocket client. `sntp` (feature `std`) synchronizes the
+//! wall clock of [crate::time] with a time server.
+
+pub mod addr;
+pub mod buf;
+pub mod capture;
+pub mod coap;
+pub mod conn_mgr;
+pub mod dns;
+pub mod ethernet;
+pub mod events;
+pub mod http;
+pub mod ieee802154;
+pub mod interface;
+pub mod mdns;
+#[cfg(feature = "openthread")]
+pub mod openthread;
+pub mod pkt;
+pub mod poll;
+#[cfg(feature = "std")]
+pub mod sntp;
+pub mod socket;
+pub mod stats;
+pub mod websocket;
+pub mod wifi;
+
+pub use buf::NetworkBufferSimple;
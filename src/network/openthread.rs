@@ -0,0 +1,391 @@
+//! Wrappers for the OpenThread L2 of Zephyr (`CONFIG_NET_L2_OPENTHREAD`).
+//!
+//! The Thread network is a regular IPv6 interface of Zephyr, so UDP over Thread uses the sockets
+//! of [crate::network::socket] once the device is attached:
+//!
+//! ```no_run
+//!# use std::net::{Ipv6Addr, SocketAddr};
+//!# use zephyr_rust_wrappers::network::openthread::{Dataset, OpenThread, Role};
+//!# use zephyr_rust_wrappers::network::socket::UdpSocket;
+//! let thread = OpenThread::get().unwrap();
+//! thread.set_active_dataset(&Dataset {
+//!     network_name: "rusty".into(),
+//!     pan_id: 0x1234,
+//!     extended_pan_id: [0xde, 0xad, 0x00, 0xbe, 0xef, 0x00, 0xca, 0xfe],
+//!     channel: 15,
+//!     network_key: [0x11; 16],
+//!     mesh_local_prefix: None,
+//! }).unwrap();
+//! thread.on_state_changed(|_, role| println!("role: {:?}", role));
+//! thread.start().unwrap();
+//!
+//! let socket = UdpSocket::bind(SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), 5683)).unwrap();
+//! let all_nodes = SocketAddr::new("ff03::1".parse::<Ipv6Addr>().unwrap().into(), 5683);
+//! socket.send_to(b"hello", all_nodes).unwrap();
+//! ```
+//!
+//! The OpenThread functions are called with the API lock of the OpenThread context held, like
+//! the `openthread_api_mutex_lock` documentation of Zephyr requires.
+
+use super::interface::NetworkInterface;
+use crate::handlers::Handlers;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use bitflags::bitflags;
+use core::cell::UnsafeCell;
+use core::ffi::{c_int, c_void};
+use zephyr_sys::raw::{
+    openthread_context as ZOpenThreadContext,
+    openthread_state_changed_cb as ZStateChangedCallback, otError,
+    otInstance as ZInstance, otOperationalDataset as ZDataset,
+    otOperationalDatasetTlvs as ZDatasetTlvs,
+};
+
+const CONTEXT: OpenThreadWrapperContext = OpenThreadWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// The OpenThread functions return an `otError` instead of an error number.
+fn ot_result(error: otError) -> ZephyrResult<()> {
+    let errno = match error {
+        zephyr_sys::raw::otError_OT_ERROR_NONE => return Ok(()),
+        zephyr_sys::raw::otError_OT_ERROR_NO_BUFS => ErrorNumber::NoSpace,
+        zephyr_sys::raw::otError_OT_ERROR_BUSY => ErrorNumber::Busy,
+        zephyr_sys::raw::otError_OT_ERROR_INVALID_ARGS => ErrorNumber::InvalidArgument,
+        zephyr_sys::raw::otError_OT_ERROR_NOT_IMPLEMENTED => ErrorNumber::NotImplemented,
+        zephyr_sys::raw::otError_OT_ERROR_INVALID_STATE => ErrorNumber::InProgress,
+        zephyr_sys::raw::otError_OT_ERROR_NOT_FOUND => ErrorNumber::NoEntry,
+        zephyr_sys::raw::otError_OT_ERROR_ALREADY => ErrorNumber::Exists,
+        _ => ErrorNumber::Io,
+    };
+    Err(ZephyrError::new_with_context(errno, &CONTEXT))
+}
+
+fn invalid_argument() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT)
+}
+
+/// Maximal length of a network name in bytes.
+pub const NETWORK_NAME_MAX_LENGTH: usize = zephyr_sys::raw::OT_NETWORK_NAME_MAX_SIZE as usize;
+
+bitflags! {
+    /// Changes reported to the handlers of [OpenThread::on_state_changed], the `OT_CHANGED_*`
+    /// flags.
+    pub struct Changes: u32 {
+        const IP6_ADDRESS_ADDED = zephyr_sys::raw::OT_CHANGED_IP6_ADDRESS_ADDED;
+        const IP6_ADDRESS_REMOVED = zephyr_sys::raw::OT_CHANGED_IP6_ADDRESS_REMOVED;
+        const ROLE = zephyr_sys::raw::OT_CHANGED_THREAD_ROLE;
+        const PARTITION_ID = zephyr_sys::raw::OT_CHANGED_THREAD_PARTITION_ID;
+        const CHANNEL = zephyr_sys::raw::OT_CHANGED_THREAD_CHANNEL;
+        const PAN_ID = zephyr_sys::raw::OT_CHANGED_THREAD_PANID;
+        const NETWORK_NAME = zephyr_sys::raw::OT_CHANGED_THREAD_NETWORK_NAME;
+        const ACTIVE_DATASET = zephyr_sys::raw::OT_CHANGED_ACTIVE_DATASET;
+        const PENDING_DATASET = zephyr_sys::raw::OT_CHANGED_PENDING_DATASET;
+    }
+}
+
+/// Role of the device in the Thread network.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Role {
+    /// The Thread stack is disabled.
+    Disabled,
+    /// Not attached to a Thread network.
+    Detached,
+    Child,
+    Router,
+    Leader,
+    Other(u32),
+}
+
+impl From<u32> for Role {
+    fn from(raw: u32) -> Self {
+        match raw {
+            zephyr_sys::raw::otDeviceRole_OT_DEVICE_ROLE_DISABLED => Role::Disabled,
+            zephyr_sys::raw::otDeviceRole_OT_DEVICE_ROLE_DETACHED => Role::Detached,
+            zephyr_sys::raw::otDeviceRole_OT_DEVICE_ROLE_CHILD => Role::Child,
+            zephyr_sys::raw::otDeviceRole_OT_DEVICE_ROLE_ROUTER => Role::Router,
+            zephyr_sys::raw::otDeviceRole_OT_DEVICE_ROLE_LEADER => Role::Leader,
+            other => Role::Other(other),
+        }
+    }
+}
+
+impl Role {
+    /// Whether the device is attached to a Thread network.
+    pub fn is_attached(&self) -> bool {
+        matches!(self, Role::Child | Role::Router | Role::Leader)
+    }
+}
+
+/// The commonly configured parameters of an operational dataset.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dataset {
+    /// Name of the network, at most [NETWORK_NAME_MAX_LENGTH] bytes.
+    pub network_name: String,
+    pub pan_id: u16,
+    pub extended_pan_id: [u8; 8],
+    /// IEEE 802.15.4 channel, 11 to 26.
+    pub channel: u16,
+    pub network_key: [u8; 16],
+    /// The /64 prefix of the mesh local addresses, OpenThread derives it if `None`.
+    pub mesh_local_prefix: Option<[u8; 8]>,
+}
+
+impl Dataset {
+    fn to_raw(&self) -> ZephyrResult<ZDataset> {
+        let name = self.network_name.as_bytes();
+        if name.len() > NETWORK_NAME_MAX_LENGTH {
+            return Err(invalid_argument());
+        }
+        // zeroed as the components not set below are marked as absent
+        let mut raw: ZDataset = unsafe { core::mem::zeroed() };
+        for (dst, src) in raw.mNetworkName.m8.iter_mut().zip(name) {
+            *dst = *src as _;
+        }
+        raw.mPanId = self.pan_id;
+        raw.mExtendedPanId.m8 = self.extended_pan_id;
+        raw.mChannel = self.channel;
+        raw.mNetworkKey.m8 = self.network_key;
+        // a dataset is only accepted with an active timestamp
+        raw.mActiveTimestamp.mSeconds = 1;
+
+        let components = &mut raw.mComponents;
+        components.mIsActiveTimestampPresent = true;
+        components.mIsNetworkNamePresent = true;
+        components.mIsPanIdPresent = true;
+        components.mIsExtendedPanIdPresent = true;
+        components.mIsChannelPresent = true;
+        components.mIsNetworkKeyPresent = true;
+        if let Some(prefix) = self.mesh_local_prefix {
+            raw.mMeshLocalPrefix.m8 = prefix;
+            raw.mComponents.mIsMeshLocalPrefixPresent = true;
+        }
+        Ok(raw)
+    }
+}
+
+impl From<&ZDataset> for Dataset {
+    fn from(raw: &ZDataset) -> Self {
+        let name = &raw.mNetworkName.m8;
+        let len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+        let name: Vec<u8> = name[..len].iter().map(|c| *c as u8).collect();
+        let components = &raw.mComponents;
+        Dataset {
+            network_name: String::from_utf8_lossy(&name).into_owned(),
+            pan_id: raw.mPanId,
+            extended_pan_id: raw.mExtendedPanId.m8,
+            channel: raw.mChannel,
+            network_key: raw.mNetworkKey.m8,
+            mesh_local_prefix: if components.mIsMeshLocalPrefixPresent {
+                Some(raw.mMeshLocalPrefix.m8)
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// Handler of [OpenThread::on_state_changed].
+type Handler = dyn FnMut(Changes, Role) + Send;
+
+/// C callback of a handler, which the OpenThread context links into its list.
+struct SubscriptionKey {
+    callback: UnsafeCell<ZStateChangedCallback>,
+}
+
+// the C callback only points to the trampoline and to the other callbacks of the context
+unsafe impl Send for SubscriptionKey {}
+
+/// Every handler is a key of its own.
+impl PartialEq for SubscriptionKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.callback.get() == other.callback.get()
+    }
+}
+
+static SUBSCRIPTIONS: Handlers<SubscriptionKey, Handler> = Handlers::new();
+
+/// Identifies a handler added with [OpenThread::on_state_changed].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SubscriptionId(usize);
+
+unsafe extern "C" fn state_changed_trampoline(
+    flags: u32,
+    context: *mut ZOpenThreadContext,
+    user_data: *mut c_void,
+) {
+    // the API lock is held by the caller
+    let role = zephyr_sys::raw::otThreadGetDeviceRole((*context).instance);
+    let changes = Changes::from_bits_truncate(flags);
+    SUBSCRIPTIONS.call_id(user_data as usize, |handler| {
+        handler(changes, Role::from(role as u32))
+    });
+}
+
+/// Holds the API lock of the OpenThread context while alive.
+struct ApiLock {
+    context: *mut ZOpenThreadContext,
+}
+
+impl Drop for ApiLock {
+    fn drop(&mut self) {
+        unsafe { zephyr_sys::raw::openthread_api_mutex_unlock(self.context) }
+    }
+}
+
+/// Handle of the OpenThread context of Zephyr.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct OpenThread {
+    context: *mut ZOpenThreadContext,
+}
+
+// the OpenThread API is guarded by the API lock of the context
+unsafe impl Send for OpenThread {}
+unsafe impl Sync for OpenThread {}
+
+impl OpenThread {
+    /// Wrapper to the `openthread_get_default_context` function, returns `None` if the
+    /// OpenThread L2 is not initialized.
+    pub fn get() -> Option<Self> {
+        let context = unsafe { zephyr_sys::raw::openthread_get_default_context() };
+        if context.is_null() {
+            None
+        } else {
+            Some(OpenThread { context })
+        }
+    }
+
+    fn lock(&self) -> (ApiLock, *mut ZInstance) {
+        unsafe { zephyr_sys::raw::openthread_api_mutex_lock(self.context) };
+        let lock = ApiLock {
+            context: self.context,
+        };
+        // the instance of an initialized context does not change
+        (lock, unsafe { (*self.context).instance })
+    }
+
+    /// The network interface of the Thread network.
+    pub fn interface(&self) -> NetworkInterface {
+        // the interface is set when the context is initialized
+        unsafe { NetworkInterface::from_raw((*self.context).iface) }.unwrap()
+    }
+
+    /// Wrapper to the `otDatasetSetActive` function, the dataset used by [OpenThread::start].
+    pub fn set_active_dataset(&self, dataset: &Dataset) -> ZephyrResult<()> {
+        let raw = dataset.to_raw()?;
+        let (_lock, instance) = self.lock();
+        ot_result(unsafe { zephyr_sys::raw::otDatasetSetActive(instance, &raw) })
+    }
+
+    /// Wrapper to the `otDatasetSetActiveTlvs` function, sets the active dataset from its TLV
+    /// encoding, e.g. as received from a commissioner.
+    pub fn set_active_dataset_tlvs(&self, tlvs: &[u8]) -> ZephyrResult<()> {
+        // zeroed as the TLVs are copied below
+        let mut raw: ZDatasetTlvs = unsafe { core::mem::zeroed() };
+        if tlvs.len() > raw.mTlvs.len() {
+            return Err(invalid_argument());
+        }
+        raw.mTlvs[..tlvs.len()].copy_from_slice(tlvs);
+        raw.mLength = tlvs.len() as u8;
+        let (_lock, instance) = self.lock();
+        ot_result(unsafe { zephyr_sys::raw::otDatasetSetActiveTlvs(instance, &raw) })
+    }
+
+    /// Wrapper to the `otDatasetGetActive` function, fails with [ErrorNumber::NoEntry] if no
+    /// dataset is configured.
+    pub fn active_dataset(&self) -> ZephyrResult<Dataset> {
+        // zeroed as the dataset is written by the function
+        let mut raw: ZDataset = unsafe { core::mem::zeroed() };
+        let (_lock, instance) = self.lock();
+        ot_result(unsafe { zephyr_sys::raw::otDatasetGetActive(instance, &mut raw) })?;
+        Ok((&raw).into())
+    }
+
+    /// Wrapper to the `openthread_start` function, enables IPv6 and Thread, which attaches to the
+    /// network of the active dataset.
+    pub fn start(&self) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::openthread_start(self.context) })
+    }
+
+    /// Wrapper to the `openthread_stop` function, detaches from the network.
+    pub fn stop(&self) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::openthread_stop(self.context) })
+    }
+
+    /// Wrapper to the `otThreadGetDeviceRole` function.
+    pub fn role(&self) -> Role {
+        let (_lock, instance) = self.lock();
+        Role::from(unsafe { zephyr_sys::raw::otThreadGetDeviceRole(instance) } as u32)
+    }
+
+    /// Wrapper to the `otLinkGetChannel` function.
+    pub fn channel(&self) -> u8 {
+        let (_lock, instance) = self.lock();
+        unsafe { zephyr_sys::raw::otLinkGetChannel(instance) }
+    }
+
+    /// Wrapper to the `otLinkGetPanId` function.
+    pub fn pan_id(&self) -> u16 {
+        let (_lock, instance) = self.lock();
+        unsafe { zephyr_sys::raw::otLinkGetPanId(instance) }
+    }
+
+    /// Wrapper to the `openthread_state_changed_cb_register` function, calls `f` with the changes
+    /// and the current role whenever the state of the stack changes.
+    ///
+    /// Handlers are called from the OpenThread thread with the API lock held and should not block.
+    pub fn on_state_changed<F>(&self, f: F) -> ZephyrResult<SubscriptionId>
+    where
+        F: FnMut(Changes, Role) + Send + 'static,
+    {
+        // zeroed as the callback is initialized below
+        let mut callback: ZStateChangedCallback = unsafe { core::mem::zeroed() };
+        callback.state_changed_cb = Some(state_changed_trampoline);
+        let pushed = SUBSCRIPTIONS.push(
+            SubscriptionKey {
+                callback: UnsafeCell::new(callback),
+            },
+            Box::new(f),
+        );
+        // the key and the callback in it stay in the table until the handler is removed, the
+        // callback is only read by the context once it is registered
+        let callback = unsafe { (*pushed.key).callback.get() };
+        unsafe { (*callback).user_data = pushed.id as *mut c_void };
+
+        let registered = result(unsafe {
+            zephyr_sys::raw::openthread_state_changed_cb_register(self.context, callback)
+        });
+        if let Err(e) = registered {
+            SUBSCRIPTIONS.remove_id(pushed.id);
+            return Err(e);
+        }
+        Ok(SubscriptionId(pushed.id))
+    }
+
+    /// Wrapper to the `openthread_state_changed_cb_unregister` function, removes a handler.
+    pub fn unsubscribe(&self, id: SubscriptionId) {
+        if let Some(callback) = SUBSCRIPTIONS.with_key(id.0, |key| key.callback.get()) {
+            unsafe {
+                zephyr_sys::raw::openthread_state_changed_cb_unregister(self.context, callback)
+            };
+            SUBSCRIPTIONS.remove_id(id.0);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct OpenThreadWrapperContext {}
+
+impl Context for OpenThreadWrapperContext {
+    fn name(&self) -> &'static str {
+        "openthread wrapper"
+    }
+}
@@ -0,0 +1,307 @@
+//! Wrappers for the network packets of Zephyr (`net_pkt`).
+//!
+//! A packet stores its data in a chain of [crate::network::buf::NetBuf] fragments and keeps a
+//! cursor for reading and writing across them. [NetPkt::fragments] and [NetPkt::contiguous]
+//! access the data in place, the read functions copy it and advance the cursor:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::pkt::NetPkt;
+//!# fn example(mut pkt: NetPkt) {
+//! pkt.rewind();
+//! pkt.skip(12).unwrap();
+//! let ethertype = pkt.read_be16().unwrap();
+//! println!("{:04x}, {} bytes on {:?}", ethertype, pkt.len(), pkt.interface());
+//!# }
+//! ```
+//!
+//! The packet is released with `net_pkt_unref` when dropped.
+
+use super::interface::NetworkInterface;
+use crate::time::Timeout;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use core::ffi::{c_int, c_void};
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+use core::slice;
+use zephyr_sys::raw::{net_buf as ZNetBuf, net_pkt as ZNetPkt};
+
+const CONTEXT: PktWrapperContext = PktWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Reference to a `net_pkt`, released with `net_pkt_unref` when dropped.
+///
+/// A [NetPkt] gives mutable access to the data and the cursor, so it is not [Clone]: further
+/// references are taken with the `unsafe` [NetPkt::clone_ref].
+pub struct NetPkt(NonNull<ZNetPkt>);
+
+// packets are reference counted and passed between the threads of the network stack
+unsafe impl Send for NetPkt {}
+
+impl NetPkt {
+    /// Takes over the reference of the caller to `raw`, returns `None` if `raw` is null.
+    ///
+    /// `raw` MUST be a packet allocated by the network stack, e.g. passed to a receive callback.
+    /// If the packet has other references, they MUST NOT access it while this one modifies it,
+    /// like for [NetPkt::clone_ref].
+    pub unsafe fn from_raw(raw: *mut ZNetPkt) -> Option<Self> {
+        NonNull::new(raw).map(NetPkt)
+    }
+
+    /// Wrapper to the `net_pkt_ref` function, takes another reference to the packet.
+    ///
+    /// The data and the cursor are shared by both references, so while one of them modifies the
+    /// packet, e.g. reads advancing the cursor, the other MUST NOT access it, from this or any
+    /// other thread.
+    pub unsafe fn clone_ref(&self) -> Self {
+        let raw = zephyr_sys::raw::net_pkt_ref(self.as_raw());
+        NetPkt(NonNull::new_unchecked(raw))
+    }
+
+    /// Releases the ownership of the reference, which the caller has to release with
+    /// `net_pkt_unref` or pass on to the network stack.
+    pub fn into_raw(self) -> *mut ZNetPkt {
+        let raw = self.0.as_ptr();
+        core::mem::forget(self);
+        raw
+    }
+
+    /// Pointer to the packet for the Zephyr API.
+    pub fn as_raw(&self) -> *mut ZNetPkt {
+        self.0.as_ptr()
+    }
+
+    fn raw(&self) -> &ZNetPkt {
+        // the packet is alive as long as this reference
+        unsafe { self.0.as_ref() }
+    }
+
+    /// Wrapper to the `net_pkt_alloc_with_buffer` function, allocates a packet for `iface` with
+    /// `size` bytes of data for frames of the link layer. Waits up to `timeout` for free
+    /// buffers.
+    ///
+    /// Fails with [ErrorNumber::NoSpace] if no packet could be allocated in time.
+    pub fn alloc(iface: NetworkInterface, size: usize, timeout: Timeout) -> ZephyrResult<Self> {
+        let raw = unsafe {
+            zephyr_sys::raw::net_pkt_alloc_with_buffer(
+                iface.as_raw(),
+                size,
+                zephyr_sys::raw::AF_UNSPEC as _,
+                0,
+                timeout,
+            )
+        };
+        NonNull::new(raw)
+            .map(NetPkt)
+            .ok_or_else(|| ZephyrError::new_with_context(ErrorNumber::NoSpace, &CONTEXT))
+    }
+
+    /// Wrapper to the `net_send_data` function, passes the packet to its interface for sending.
+    ///
+    /// The packet is released if it could not be queued.
+    pub fn send(self) -> ZephyrResult<()> {
+        let errno = unsafe { zephyr_sys::raw::net_send_data(self.as_raw()) };
+        if errno < 0 {
+            // the stack only takes over the packet on success, dropping releases it
+            return Err(ZephyrError::from_errno_with_context(errno, &CONTEXT));
+        }
+        core::mem::forget(self);
+        Ok(())
+    }
+
+    /// Interface the packet was received on or is sent from, the equivalent of the inlined
+    /// `net_pkt_iface`.
+    pub fn interface(&self) -> Option<NetworkInterface> {
+        unsafe { NetworkInterface::from_raw(self.raw().iface) }
+    }
+
+    /// Priority of the packet (0 to 7), the equivalent of the inlined `net_pkt_priority`.
+    pub fn priority(&self) -> u8 {
+        self.raw().priority
+    }
+
+    /// Time the packet was received or sent by the driver, the equivalent of the inlined
+    /// `net_pkt_timestamp`. Requires `CONFIG_NET_PKT_TIMESTAMP`.
+    #[cfg(feature = "net-pkt-timestamp")]
+    pub fn timestamp(&self) -> core::time::Duration {
+        let timestamp = unsafe { &self.raw().__bindgen_anon_2.timestamp };
+        core::time::Duration::new(timestamp.second, timestamp.nanosecond)
+    }
+
+    fn first_fragment(&self) -> *mut ZNetBuf {
+        unsafe { self.raw().__bindgen_anon_1.frags }
+    }
+
+    /// Length of the data in all fragments, the equivalent of the inlined `net_pkt_get_len`.
+    pub fn len(&self) -> usize {
+        unsafe { zephyr_sys::raw::net_buf_frags_len(self.first_fragment()) }
+    }
+
+    /// Whether the packet contains no data.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The data of the fragments of the packet, without copying it.
+    pub fn fragments(&self) -> Fragments<'_> {
+        Fragments {
+            fragment: self.first_fragment(),
+            _pkt: PhantomData,
+        }
+    }
+
+    /// Wrapper to the `net_pkt_cursor_init` function, moves the cursor to the start of the data.
+    pub fn rewind(&mut self) {
+        unsafe { zephyr_sys::raw::net_pkt_cursor_init(self.as_raw()) };
+    }
+
+    /// Wrapper to the `net_pkt_remaining_data` function, the number of bytes after the cursor.
+    pub fn remaining(&self) -> usize {
+        unsafe { zephyr_sys::raw::net_pkt_remaining_data(self.as_raw()) }
+    }
+
+    /// Set whether writes overwrite the data after the cursor instead of appending new data,
+    /// the equivalent of the inlined `net_pkt_set_overwrite`.
+    pub fn set_overwrite(&mut self, overwrite: bool) {
+        unsafe { self.0.as_mut().set_overwrite(overwrite as u8) };
+    }
+
+    /// The data from the cursor up to the end of its fragment, without copying it. Advance the
+    /// cursor with [NetPkt::skip] after processing it.
+    pub fn contiguous(&self) -> &[u8] {
+        let len = unsafe { zephyr_sys::raw::net_pkt_get_contiguous_len(self.as_raw()) };
+        let pos = self.raw().cursor.pos;
+        if pos.is_null() || len == 0 {
+            return &[];
+        }
+        unsafe { slice::from_raw_parts(pos as *const u8, len) }
+    }
+
+    /// Wrapper to the `net_pkt_skip` function, advances the cursor by `len` bytes.
+    ///
+    /// For packets in overwrite mode, fails with [ErrorNumber::NoBuffers] if less data remains.
+    pub fn skip(&mut self, len: usize) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::net_pkt_skip(self.as_raw(), len) })
+    }
+
+    /// Wrapper to the `net_pkt_read` function, copies the data at the cursor into `buf` and
+    /// advances the cursor.
+    ///
+    /// Fails with [ErrorNumber::NoBuffers] if less data than the length of `buf` remains.
+    pub fn read(&mut self, buf: &mut [u8]) -> ZephyrResult<()> {
+        let data = buf.as_mut_ptr() as *mut c_void;
+        result(unsafe { zephyr_sys::raw::net_pkt_read(self.as_raw(), data, buf.len()) })
+    }
+
+    /// Reads a byte, the equivalent of the inlined `net_pkt_read_u8`.
+    pub fn read_u8(&mut self) -> ZephyrResult<u8> {
+        let mut value = [0u8; 1];
+        self.read(&mut value)?;
+        Ok(value[0])
+    }
+
+    /// Reads a big endian `u16`, the equivalent of `net_pkt_read_be16`.
+    pub fn read_be16(&mut self) -> ZephyrResult<u16> {
+        let mut value = [0u8; 2];
+        self.read(&mut value)?;
+        Ok(u16::from_be_bytes(value))
+    }
+
+    /// Reads a little endian `u16`, the equivalent of `net_pkt_read_le16`.
+    pub fn read_le16(&mut self) -> ZephyrResult<u16> {
+        let mut value = [0u8; 2];
+        self.read(&mut value)?;
+        Ok(u16::from_le_bytes(value))
+    }
+
+    /// Reads a big endian `u32`, the equivalent of `net_pkt_read_be32`.
+    pub fn read_be32(&mut self) -> ZephyrResult<u32> {
+        let mut value = [0u8; 4];
+        self.read(&mut value)?;
+        Ok(u32::from_be_bytes(value))
+    }
+
+    /// Reads a little endian `u32`, the equivalent of `net_pkt_read_le32`.
+    pub fn read_le32(&mut self) -> ZephyrResult<u32> {
+        let mut value = [0u8; 4];
+        self.read(&mut value)?;
+        Ok(u32::from_le_bytes(value))
+    }
+
+    /// Wrapper to the `net_pkt_write` function, writes `data` at the cursor and advances it.
+    ///
+    /// Fails with [ErrorNumber::NoBuffers] if the buffers of the packet are too small.
+    pub fn write(&mut self, data: &[u8]) -> ZephyrResult<()> {
+        let len = data.len();
+        let data = data.as_ptr() as *const c_void;
+        result(unsafe { zephyr_sys::raw::net_pkt_write(self.as_raw(), data, len) })
+    }
+
+    /// Writes a byte, the equivalent of the inlined `net_pkt_write_u8`.
+    pub fn write_u8(&mut self, value: u8) -> ZephyrResult<()> {
+        self.write(&[value])
+    }
+
+    /// Writes a big endian `u16`, the equivalent of the inlined `net_pkt_write_be16`.
+    pub fn write_be16(&mut self, value: u16) -> ZephyrResult<()> {
+        self.write(&value.to_be_bytes())
+    }
+
+    /// Writes a big endian `u32`, the equivalent of the inlined `net_pkt_write_be32`.
+    pub fn write_be32(&mut self, value: u32) -> ZephyrResult<()> {
+        self.write(&value.to_be_bytes())
+    }
+
+    /// Writes a little endian `u16`, the equivalent of the inlined `net_pkt_write_le16`.
+    pub fn write_le16(&mut self, value: u16) -> ZephyrResult<()> {
+        self.write(&value.to_le_bytes())
+    }
+
+    /// Writes a little endian `u32`, the equivalent of the inlined `net_pkt_write_le32`.
+    pub fn write_le32(&mut self, value: u32) -> ZephyrResult<()> {
+        self.write(&value.to_le_bytes())
+    }
+}
+
+impl Drop for NetPkt {
+    fn drop(&mut self) {
+        unsafe { zephyr_sys::raw::net_pkt_unref(self.as_raw()) }
+    }
+}
+
+/// Iterator over the data of the fragments of a packet, returned by [NetPkt::fragments].
+pub struct Fragments<'a> {
+    fragment: *mut ZNetBuf,
+    _pkt: PhantomData<&'a NetPkt>,
+}
+
+impl<'a> Iterator for Fragments<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // fragments live as long as the packet referencing them
+        let fragment = unsafe { self.fragment.as_ref() }?;
+        self.fragment = fragment.frags;
+        // the data fields of a net_buf overlay a net_buf_simple
+        let simple = unsafe { &fragment.__bindgen_anon_1.b };
+        if simple.len == 0 {
+            return Some(&[]);
+        }
+        Some(unsafe { slice::from_raw_parts(simple.data, simple.len as usize) })
+    }
+}
+
+#[derive(Debug)]
+struct PktWrapperContext {}
+
+impl Context for PktWrapperContext {
+    fn name(&self) -> &'static str {
+        "net_pkt wrapper"
+    }
+}
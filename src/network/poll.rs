@@ -0,0 +1,229 @@
+//! Wrapper for `zsock_poll`, which waits for events on several sockets at once.
+//!
+//! A [PollSet] collects the file descriptors and the events of interest, and after
+//! [PollSet::poll] returns the events that occurred for each of them:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::poll::{PollEvents, PollSet};
+//!# use zephyr_rust_wrappers::network::socket::{TcpListener, UdpSocket};
+//!# let listener = TcpListener::bind(([0, 0, 0, 0], 80).into()).unwrap();
+//!# let socket = UdpSocket::bind(([0, 0, 0, 0], 53).into()).unwrap();
+//! let mut set = PollSet::new()
+//!     .with(&listener, PollEvents::IN)
+//!     .with(&socket, PollEvents::IN);
+//! set.poll(None).unwrap();
+//! if set.revents(0).contains(PollEvents::IN) {
+//!     let (stream, _) = listener.accept().unwrap();
+//! }
+//! ```
+//!
+//! An [EventFd] (with the `eventfd` feature and `CONFIG_EVENTFD`) can be added to the set to wake
+//! up the polling thread from other threads.
+
+use super::socket::AsRawFd;
+use crate::{Context, ZephyrError, ZephyrResult};
+use alloc::vec::Vec;
+use bitflags::bitflags;
+use core::ffi::{c_int, c_short};
+use core::time::Duration;
+use zephyr_sys::raw::zsock_pollfd as ZPollFd;
+
+const CONTEXT: PollWrapperContext = PollWrapperContext {};
+
+/// The functions return `-1` and set `errno` on errors.
+fn result(ret: c_int) -> ZephyrResult<c_int> {
+    if ret < 0 {
+        let errno = unsafe { *zephyr_sys::syscalls::any::z_errno() };
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    } else {
+        Ok(ret)
+    }
+}
+
+bitflags! {
+    /// Events of a [PollFd].
+    pub struct PollEvents: c_short {
+        /// Data can be read, or a connection can be accepted.
+        const IN = zephyr_sys::raw::ZSOCK_POLLIN as c_short;
+        /// Urgent data can be read.
+        const PRI = zephyr_sys::raw::ZSOCK_POLLPRI as c_short;
+        /// Data can be written without blocking.
+        const OUT = zephyr_sys::raw::ZSOCK_POLLOUT as c_short;
+        /// An error occurred, only reported in the returned events.
+        const ERR = zephyr_sys::raw::ZSOCK_POLLERR as c_short;
+        /// The peer closed the connection, only reported in the returned events.
+        const HUP = zephyr_sys::raw::ZSOCK_POLLHUP as c_short;
+        /// The file descriptor is not open, only reported in the returned events.
+        const NVAL = zephyr_sys::raw::ZSOCK_POLLNVAL as c_short;
+    }
+}
+
+/// File descriptor and events of interest, the layout matches `struct zsock_pollfd`.
+#[repr(transparent)]
+#[derive(Copy, Clone)]
+pub struct PollFd(ZPollFd);
+
+impl PollFd {
+    /// Creates a new entry waiting for `events` on `fd`.
+    pub fn new(fd: c_int, events: PollEvents) -> Self {
+        PollFd(ZPollFd {
+            fd,
+            events: events.bits(),
+            revents: 0,
+        })
+    }
+
+    /// File descriptor of the entry.
+    pub fn fd(&self) -> c_int {
+        self.0.fd
+    }
+
+    /// Events of interest.
+    pub fn events(&self) -> PollEvents {
+        PollEvents::from_bits_truncate(self.0.events)
+    }
+
+    /// Events that occurred in the last [poll].
+    pub fn revents(&self) -> PollEvents {
+        PollEvents::from_bits_truncate(self.0.revents)
+    }
+}
+
+/// Wrapper to the `zsock_poll` syscall.
+///
+/// Waits up to `timeout` (forever if `None`) for the events of `fds`. Returns the number of entries
+/// with events, `0` if the timeout expired.
+pub fn poll(fds: &mut [PollFd], timeout: Option<Duration>) -> ZephyrResult<usize> {
+    let timeout = timeout.map_or(-1, |timeout| timeout.as_millis() as c_int);
+    let count = result(unsafe {
+        zephyr_sys::syscalls::any::zsock_poll(
+            fds.as_mut_ptr() as *mut ZPollFd,
+            fds.len() as c_int,
+            timeout,
+        )
+    })?;
+    Ok(count as usize)
+}
+
+/// Set of file descriptors to [poll], entries are identified by the order they were added in.
+#[derive(Clone, Default)]
+pub struct PollSet {
+    fds: Vec<PollFd>,
+}
+
+impl PollSet {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `source` waiting for `events`, returns the index of the entry.
+    ///
+    /// The file descriptor is only valid as long as `source`, which must not be dropped while the
+    /// set is in use.
+    pub fn add<S: AsRawFd>(&mut self, source: &S, events: PollEvents) -> usize {
+        self.fds.push(PollFd::new(source.as_raw_fd(), events));
+        self.fds.len() - 1
+    }
+
+    /// Builder variant of [PollSet::add].
+    pub fn with<S: AsRawFd>(mut self, source: &S, events: PollEvents) -> Self {
+        self.add(source, events);
+        self
+    }
+
+    /// Changes the events of interest of the entry `index`.
+    pub fn set_events(&mut self, index: usize, events: PollEvents) {
+        self.fds[index].0.events = events.bits();
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.fds.clear();
+    }
+
+    /// Waits up to `timeout` (forever if `None`) for the events of the entries, returns the number
+    /// of entries with events.
+    pub fn poll(&mut self, timeout: Option<Duration>) -> ZephyrResult<usize> {
+        poll(&mut self.fds, timeout)
+    }
+
+    /// Events of the entry `index` that occurred in the last [PollSet::poll].
+    pub fn revents(&self, index: usize) -> PollEvents {
+        self.fds[index].revents()
+    }
+
+    /// Iterator over the indices and events of the entries with events in the last
+    /// [PollSet::poll].
+    pub fn ready(&self) -> impl Iterator<Item = (usize, PollEvents)> + '_ {
+        self.fds
+            .iter()
+            .map(PollFd::revents)
+            .enumerate()
+            .filter(|(_, revents)| !revents.is_empty())
+    }
+
+    /// The entries of the set.
+    pub fn fds(&self) -> &[PollFd] {
+        &self.fds
+    }
+}
+
+/// Counter that can be polled, used to wake up a thread blocked in [poll].
+///
+/// The counter is readable while it is not zero, [EventFd::notify] increments it and
+/// [EventFd::reset] reads and clears it.
+#[cfg(feature = "eventfd")]
+#[derive(Debug)]
+pub struct EventFd(c_int);
+
+#[cfg(feature = "eventfd")]
+impl EventFd {
+    /// Wrapper to the `eventfd` function, creates a non-blocking counter starting at zero.
+    pub fn new() -> ZephyrResult<Self> {
+        let flags = zephyr_sys::raw::EFD_NONBLOCK as c_int;
+        let fd = result(unsafe { zephyr_sys::raw::eventfd(0, flags) })?;
+        Ok(EventFd(fd))
+    }
+
+    /// Wrapper to the `eventfd_write` function, adds `1` to the counter.
+    pub fn notify(&self) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::eventfd_write(self.0, 1) })?;
+        Ok(())
+    }
+
+    /// Wrapper to the `eventfd_read` function, returns the counter and resets it to zero.
+    ///
+    /// Returns `0` if the counter was already zero.
+    pub fn reset(&self) -> ZephyrResult<u64> {
+        let mut value: zephyr_sys::raw::eventfd_t = 0;
+        match result(unsafe { zephyr_sys::raw::eventfd_read(self.0, &mut value) }) {
+            Ok(_) => Ok(value as u64),
+            Err(e) if e.is_would_block() => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(feature = "eventfd")]
+impl AsRawFd for EventFd {
+    fn as_raw_fd(&self) -> c_int {
+        self.0
+    }
+}
+
+#[cfg(feature = "eventfd")]
+impl Drop for EventFd {
+    fn drop(&mut self) {
+        unsafe { zephyr_sys::syscalls::any::zsock_close(self.0) };
+    }
+}
+
+#[derive(Debug)]
+struct PollWrapperContext {}
+
+impl Context for PollWrapperContext {
+    fn name(&self) -> &'static str {
+        "poll wrapper"
+    }
+}
@@ -0,0 +1,105 @@
+//! Wrappers for the SNTP client of Zephyr (`CONFIG_SNTP`).
+//!
+//! [query] returns the time of a server, [sync] also sets the wall clock of [crate::time] to it
+//! and [run_sync] keeps the wall clock synchronized from a dedicated thread:
+//!
+//! ```no_run
+//!# use std::time::Duration;
+//! zephyr_rust_wrappers::network::sntp::sync("pool.ntp.org", Duration::from_secs(5)).unwrap();
+//! println!("{:?}", zephyr_rust_wrappers::time::wall_clock());
+//! ```
+
+use super::addr::RawSocketAddr;
+use crate::time::timeout;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::ffi::CString;
+use core::ffi::c_int;
+use core::net::SocketAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zephyr_sys::raw::{sntp_ctx as ZSntpCtx, sntp_time as ZSntpTime};
+
+const CONTEXT: SntpWrapperContext = SntpWrapperContext {};
+
+/// The SNTP functions return a negative error number on errors.
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno >= 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+fn timeout_ms(timeout: Duration) -> u32 {
+    timeout.as_millis().min(u32::MAX as u128) as u32
+}
+
+/// Converts the seconds since the Unix epoch and the binary fraction of a second of a
+/// `sntp_time`.
+fn to_system_time(time: &ZSntpTime) -> SystemTime {
+    let nanos = (time.fraction as u64 * 1_000_000_000) >> 32;
+    UNIX_EPOCH + Duration::new(time.seconds, nanos as u32)
+}
+
+/// Wrapper to the `sntp_simple` function, resolves `server` and returns its time. Waits up to
+/// `timeout` for the response.
+pub fn query(server: &str, timeout: Duration) -> ZephyrResult<SystemTime> {
+    let server = CString::new(server)
+        .map_err(|_| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))?;
+    // zeroed as the time is written by the function
+    let mut time: ZSntpTime = unsafe { core::mem::zeroed() };
+    result(unsafe {
+        zephyr_sys::raw::sntp_simple(server.as_ptr(), timeout_ms(timeout), &mut time)
+    })?;
+    Ok(to_system_time(&time))
+}
+
+/// Wrapper to the `sntp_init`, `sntp_query` and `sntp_close` functions, returns the time of the
+/// server at `addr` (usually port 123). Waits up to `timeout` for the response.
+pub fn query_addr(addr: SocketAddr, timeout: Duration) -> ZephyrResult<SystemTime> {
+    let mut raw = RawSocketAddr::from(&addr);
+    // zeroed as the context is initialized below
+    let mut context: ZSntpCtx = unsafe { core::mem::zeroed() };
+    result(unsafe {
+        zephyr_sys::raw::sntp_init(&mut context, raw.as_mut_ptr(), raw.socklen())
+    })?;
+
+    // zeroed as the time is written by the function
+    let mut time: ZSntpTime = unsafe { core::mem::zeroed() };
+    let queried = result(unsafe {
+        zephyr_sys::raw::sntp_query(&mut context, timeout_ms(timeout), &mut time)
+    });
+    unsafe { zephyr_sys::raw::sntp_close(&mut context) };
+    queried?;
+    Ok(to_system_time(&time))
+}
+
+/// Queries `server` like [query] and sets the wall clock of [crate::time::set_wall_clock] to its
+/// time.
+pub fn sync(server: &str, timeout: Duration) -> ZephyrResult<SystemTime> {
+    let now = query(server, timeout)?;
+    crate::time::set_wall_clock(now);
+    Ok(now)
+}
+
+/// Synchronizes the wall clock with `server` every `period`, blocks the calling thread forever.
+///
+/// Failed queries are retried after `query_timeout`, the server is resolved on each query so
+/// changes of its addresses are picked up.
+pub fn run_sync(server: &str, period: Duration, query_timeout: Duration) -> ! {
+    loop {
+        let delay = match sync(server, query_timeout) {
+            Ok(_) => period,
+            Err(_) => query_timeout,
+        };
+        unsafe { zephyr_sys::syscalls::any::k_sleep(timeout(delay)) };
+    }
+}
+
+#[derive(Debug)]
+struct SntpWrapperContext {}
+
+impl Context for SntpWrapperContext {
+    fn name(&self) -> &'static str {
+        "sntp wrapper"
+    }
+}
@@ -0,0 +1,460 @@
+//! Futures for the sockets, the sockets are set to `O_NONBLOCK` and an operation that would block
+//! waits for the socket to become ready.
+//!
+//! Readiness is checked with `zsock_poll` without a timeout each time the future is polled. A
+//! pending future registers its socket with a waiter thread, which waits with `zsock_poll` for the
+//! sockets of all pending futures and wakes their tasks. The thread is started by the first
+//! pending future, it requires the `eventfd` feature (`CONFIG_EVENTFD`) to be woken up for new
+//! sockets and `CONFIG_DYNAMIC_THREAD` for its stack. Without them, or if the thread cannot be
+//! started, a pending future sleeps for a tick and wakes its task right away. The futures run on
+//! any executor, e.g. on [crate::executor::Executor].
+
+use super::{would_block, AsRawFd, Shutdown, Socket, TcpListener, TcpStream, UdpSocket, DONTWAIT};
+use crate::network::poll::{poll, PollEvents, PollFd};
+use crate::time::Timeout;
+use crate::{ErrorNumber, ZephyrResult};
+#[cfg(all(feature = "eventfd", zephyr_config_dynamic_thread))]
+use alloc::boxed::Box;
+use core::ffi::c_int;
+use core::future::Future;
+use core::net::SocketAddr;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use core::time::Duration;
+
+/// Thread waiting for the sockets of the pending futures.
+#[cfg(all(feature = "eventfd", zephyr_config_dynamic_thread))]
+mod waiter {
+    use crate::handlers::Handlers;
+    use crate::network::poll::{poll, EventFd, PollEvents, PollFd};
+    use crate::network::socket::AsRawFd;
+    use crate::time::Timeout;
+    use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+    use alloc::boxed::Box;
+    use alloc::vec::Vec;
+    use core::cell::UnsafeCell;
+    use core::ffi::{c_int, c_void};
+    use core::task::Waker;
+    use zephyr_sys::raw::{k_spinlock, k_thread};
+
+    const CONTEXT: WaiterContext = WaiterContext {};
+
+    const STACK_SIZE: usize = 1024;
+    /// Highest preemptible priority, the thread only wakes tasks.
+    const PRIORITY: c_int = 0;
+
+    /// Socket a pending future waits for.
+    pub(super) struct Waiting {
+        pub(super) fd: c_int,
+        pub(super) events: PollEvents,
+        pub(super) waker: Waker,
+        /// Set once the task has been woken, until the future registers again.
+        pub(super) woken: bool,
+    }
+
+    /// Pending futures by the file descriptor of their socket.
+    pub(super) static WAITING: Handlers<c_int, Waiting> = Handlers::new();
+
+    #[derive(Copy, Clone)]
+    enum State {
+        Stopped,
+        Starting,
+        Running(&'static EventFd),
+        Failed,
+    }
+
+    struct Thread {
+        lock: UnsafeCell<k_spinlock>,
+        state: UnsafeCell<State>,
+    }
+
+    // the state is only accessed with the lock held
+    unsafe impl Sync for Thread {}
+
+    static THREAD: Thread = Thread {
+        // zeroed as an unlocked spinlock is all zeros, like K_SPINLOCK_DEFINE
+        lock: UnsafeCell::new(unsafe { core::mem::zeroed() }),
+        state: UnsafeCell::new(State::Stopped),
+    };
+
+    impl Thread {
+        fn locked<R>(&self, f: impl FnOnce(&mut State) -> R) -> R {
+            let key = unsafe { crate::sys::k_spin_lock(self.lock.get()) };
+            // the lock is held, so nothing else accesses the state
+            let result = f(unsafe { &mut *self.state.get() });
+            unsafe { crate::sys::k_spin_unlock(self.lock.get(), key) };
+            result
+        }
+    }
+
+    /// Starts the thread if it is not running, then wakes it up to wait for the registered
+    /// sockets. Returns `false` if the thread is not running.
+    pub(super) fn notify() -> bool {
+        let state = THREAD.locked(|state| {
+            let current = *state;
+            if let State::Stopped = current {
+                *state = State::Starting;
+            }
+            current
+        });
+        match state {
+            State::Running(eventfd) => eventfd.notify().is_ok(),
+            State::Stopped => {
+                let started = start();
+                // the thread waits for the sockets registered until now when it starts
+                THREAD.locked(|state| {
+                    *state = match started {
+                        Ok(eventfd) => State::Running(eventfd),
+                        Err(_) => State::Failed,
+                    }
+                });
+                started.is_ok()
+            }
+            // futures registered while starting are woken by the thread once it runs
+            State::Starting => true,
+            State::Failed => false,
+        }
+    }
+
+    fn start() -> ZephyrResult<&'static EventFd> {
+        let eventfd = EventFd::new()?;
+        let stack = unsafe { zephyr_sys::syscalls::any::k_thread_stack_alloc(STACK_SIZE, 0) };
+        if stack.is_null() {
+            return Err(ZephyrError::new_with_context(
+                ErrorNumber::NoMemory,
+                &CONTEXT,
+            ));
+        }
+        // the thread and its wake up counter live for the rest of the runtime
+        let eventfd: &'static EventFd = Box::leak(Box::new(eventfd));
+        let thread: &'static mut k_thread = Box::leak(Box::new(unsafe { core::mem::zeroed() }));
+        unsafe {
+            zephyr_sys::syscalls::any::k_thread_create(
+                thread as *mut k_thread,
+                stack,
+                STACK_SIZE,
+                Some(run),
+                eventfd as *const EventFd as *mut c_void,
+                core::ptr::null_mut(),
+                core::ptr::null_mut(),
+                PRIORITY,
+                0,
+                crate::time::NO_WAIT,
+            )
+        };
+        Ok(eventfd)
+    }
+
+    unsafe extern "C" fn run(eventfd: *mut c_void, _: *mut c_void, _: *mut c_void) {
+        let eventfd = &*(eventfd as *const EventFd);
+        let mut fds = Vec::new();
+        loop {
+            fds.clear();
+            fds.push(PollFd::new(eventfd.as_raw_fd(), PollEvents::IN));
+            WAITING.call_all(
+                |_| true,
+                |waiting| {
+                    if !waiting.woken {
+                        fds.push(PollFd::new(waiting.fd, waiting.events));
+                    }
+                },
+            );
+            if poll(&mut fds, None).is_err() {
+                // e.g. out of memory for the poll events, the sockets are polled again later
+                zephyr_sys::syscalls::any::k_sleep(Timeout { ticks: 1 });
+                continue;
+            }
+            if !fds[0].revents().is_empty() {
+                let _ = eventfd.reset();
+            }
+            for fd in fds[1..].iter().filter(|fd| !fd.revents().is_empty()) {
+                WAITING.call_all(
+                    |key| *key == fd.fd(),
+                    |waiting| {
+                        // errors and hang ups are reported for any events
+                        if !waiting.woken {
+                            waiting.woken = true;
+                            waiting.waker.wake_by_ref();
+                        }
+                    },
+                );
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct WaiterContext {}
+
+    impl Context for WaiterContext {
+        fn name(&self) -> &'static str {
+            "socket waiter"
+        }
+    }
+}
+
+/// Future that completes once one of `events` occurred on `fd`.
+struct Readiness {
+    fd: c_int,
+    events: PollEvents,
+    /// Entry of the future in the table of the waiter thread.
+    #[cfg(all(feature = "eventfd", zephyr_config_dynamic_thread))]
+    id: Option<usize>,
+}
+
+impl Readiness {
+    fn new(fd: c_int, events: PollEvents) -> Self {
+        Readiness {
+            fd,
+            events,
+            #[cfg(all(feature = "eventfd", zephyr_config_dynamic_thread))]
+            id: None,
+        }
+    }
+
+    /// Registers the waker of the task with the waiter thread.
+    #[cfg(all(feature = "eventfd", zephyr_config_dynamic_thread))]
+    fn wait(&mut self, waker: &Waker) {
+        let waiting = Box::new(waiter::Waiting {
+            fd: self.fd,
+            events: self.events,
+            waker: waker.clone(),
+            woken: false,
+        });
+        match self.id {
+            Some(id) => drop(waiter::WAITING.restore(id, Some(waiting))),
+            None => self.id = Some(waiter::WAITING.push(self.fd, waiting).id),
+        }
+        if !waiter::notify() {
+            sleep_and_wake(waker);
+        }
+    }
+
+    #[cfg(not(all(feature = "eventfd", zephyr_config_dynamic_thread)))]
+    fn wait(&mut self, waker: &Waker) {
+        sleep_and_wake(waker);
+    }
+}
+
+/// Fallback without the waiter thread, the socket is polled again after a tick.
+fn sleep_and_wake(waker: &Waker) {
+    unsafe { zephyr_sys::syscalls::any::k_sleep(Timeout { ticks: 1 }) };
+    waker.wake_by_ref();
+}
+
+impl Future for Readiness {
+    type Output = ZephyrResult<PollEvents>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut fds = [PollFd::new(self.fd, self.events)];
+        match poll(&mut fds, Some(Duration::from_millis(0))) {
+            Ok(0) => {
+                self.wait(cx.waker());
+                Poll::Pending
+            }
+            Ok(_) => Poll::Ready(Ok(fds[0].revents())),
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+}
+
+#[cfg(all(feature = "eventfd", zephyr_config_dynamic_thread))]
+impl Drop for Readiness {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            waiter::WAITING.remove_id(id);
+        }
+    }
+}
+
+/// Runs `f` until it does not block, waiting for `events` on `fd` in between.
+async fn retry<T, F>(fd: c_int, events: PollEvents, mut f: F) -> ZephyrResult<T>
+where
+    F: FnMut() -> ZephyrResult<Option<T>>,
+{
+    loop {
+        if let Some(value) = f()? {
+            return Ok(value);
+        }
+        // errors and hang ups are reported by the next try
+        Readiness::new(fd, events).await?;
+    }
+}
+
+/// Asynchronous variant of [TcpStream].
+#[derive(Debug)]
+pub struct AsyncTcpStream {
+    stream: TcpStream,
+}
+
+impl AsyncTcpStream {
+    /// Opens a TCP connection to `addr`.
+    pub async fn connect(addr: SocketAddr) -> ZephyrResult<AsyncTcpStream> {
+        let socket = Socket::stream(&addr)?;
+        socket.set_nonblocking(true)?;
+        match socket.connect(&addr) {
+            Ok(()) => {}
+            Err(e) if e.number() == ErrorNumber::InProgress => {
+                Readiness::new(socket.0, PollEvents::OUT).await?;
+                if let Some(e) = socket.take_error()? {
+                    return Err(e);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+
+        Ok(AsyncTcpStream {
+            stream: TcpStream { socket },
+        })
+    }
+
+    /// Converts a connected [TcpStream], which is set to `O_NONBLOCK`.
+    pub fn from_std(stream: TcpStream) -> ZephyrResult<AsyncTcpStream> {
+        stream.set_nonblocking(true)?;
+        Ok(AsyncTcpStream { stream })
+    }
+
+    /// Returns the blocking [TcpStream], which is still set to `O_NONBLOCK`.
+    pub fn into_std(self) -> TcpStream {
+        self.stream
+    }
+
+    /// Receives data into `buf`, returns `0` if the peer closed the connection.
+    pub async fn read(&mut self, buf: &mut [u8]) -> ZephyrResult<usize> {
+        let stream = &self.stream;
+        retry(stream.as_raw_fd(), PollEvents::IN, || stream.try_recv(buf)).await
+    }
+
+    /// Sends data of `buf`, returns how many bytes were sent.
+    pub async fn write(&mut self, buf: &[u8]) -> ZephyrResult<usize> {
+        let stream = &self.stream;
+        retry(stream.as_raw_fd(), PollEvents::OUT, || stream.try_send(buf)).await
+    }
+
+    /// Sends all data of `buf`.
+    pub async fn write_all(&mut self, mut buf: &[u8]) -> ZephyrResult<()> {
+        while !buf.is_empty() {
+            let count = self.write(buf).await?;
+            buf = &buf[count..];
+        }
+        Ok(())
+    }
+
+    /// Address of the remote peer.
+    pub fn peer_addr(&self) -> ZephyrResult<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    /// Local address of the connection.
+    pub fn local_addr(&self) -> ZephyrResult<SocketAddr> {
+        self.stream.local_addr()
+    }
+
+    /// Shuts down the read, write or both halves of the connection.
+    pub fn shutdown(&self, how: Shutdown) -> ZephyrResult<()> {
+        self.stream.shutdown(how)
+    }
+}
+
+impl AsRawFd for AsyncTcpStream {
+    fn as_raw_fd(&self) -> c_int {
+        self.stream.as_raw_fd()
+    }
+}
+
+/// Asynchronous variant of [TcpListener].
+#[derive(Debug)]
+pub struct AsyncTcpListener {
+    listener: TcpListener,
+}
+
+impl AsyncTcpListener {
+    /// Creates a listener bound to `addr`, port `0` lets the stack choose a port.
+    pub fn bind(addr: SocketAddr) -> ZephyrResult<AsyncTcpListener> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(AsyncTcpListener { listener })
+    }
+
+    /// Waits for a connection, returns the connection and the address of the peer.
+    pub async fn accept(&self) -> ZephyrResult<(AsyncTcpStream, SocketAddr)> {
+        let listener = &self.listener;
+        let (stream, addr) = retry(listener.as_raw_fd(), PollEvents::IN, || {
+            would_block(listener.accept())
+        })
+        .await?;
+        Ok((AsyncTcpStream::from_std(stream)?, addr))
+    }
+
+    /// Local address the listener is bound to.
+    pub fn local_addr(&self) -> ZephyrResult<SocketAddr> {
+        self.listener.local_addr()
+    }
+}
+
+impl AsRawFd for AsyncTcpListener {
+    fn as_raw_fd(&self) -> c_int {
+        self.listener.as_raw_fd()
+    }
+}
+
+/// Asynchronous variant of [UdpSocket].
+#[derive(Debug)]
+pub struct AsyncUdpSocket {
+    socket: UdpSocket,
+}
+
+impl AsyncUdpSocket {
+    /// Creates a socket bound to `addr`, port `0` lets the stack choose a port.
+    pub fn bind(addr: SocketAddr) -> ZephyrResult<AsyncUdpSocket> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(AsyncUdpSocket { socket })
+    }
+
+    /// Sets the default destination of [AsyncUdpSocket::send] and only receives datagrams of
+    /// `addr`.
+    pub fn connect(&self, addr: SocketAddr) -> ZephyrResult<()> {
+        self.socket.connect(addr)
+    }
+
+    /// Sends the datagram `buf` to `addr`.
+    pub async fn send_to(&self, buf: &[u8], addr: SocketAddr) -> ZephyrResult<usize> {
+        let socket = &self.socket;
+        retry(socket.as_raw_fd(), PollEvents::OUT, || socket.try_send_to(buf, addr)).await
+    }
+
+    /// Receives a datagram into `buf`, returns its length and sender.
+    pub async fn recv_from(&self, buf: &mut [u8]) -> ZephyrResult<(usize, SocketAddr)> {
+        let socket = &self.socket;
+        retry(socket.as_raw_fd(), PollEvents::IN, || socket.try_recv_from(buf)).await
+    }
+
+    /// Sends the datagram `buf` to the address of [AsyncUdpSocket::connect].
+    pub async fn send(&self, buf: &[u8]) -> ZephyrResult<usize> {
+        let socket = &self.socket.socket;
+        retry(socket.0, PollEvents::OUT, || {
+            would_block(socket.send_to(buf, None, DONTWAIT))
+        })
+        .await
+    }
+
+    /// Receives a datagram of the address of [AsyncUdpSocket::connect] into `buf`.
+    pub async fn recv(&self, buf: &mut [u8]) -> ZephyrResult<usize> {
+        let socket = &self.socket.socket;
+        retry(socket.0, PollEvents::IN, || {
+            would_block(socket.recv_from(buf, DONTWAIT).map(|(count, _)| count))
+        })
+        .await
+    }
+
+    /// Local address the socket is bound to.
+    pub fn local_addr(&self) -> ZephyrResult<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
+
+impl AsRawFd for AsyncUdpSocket {
+    fn as_raw_fd(&self) -> c_int {
+        self.socket.as_raw_fd()
+    }
+}
@@ -0,0 +1,231 @@
+//! Raw CAN sockets (`CONFIG_NET_SOCKETS_CAN`), the SocketCAN API of Zephyr on top of the network
+//! interfaces of the CAN controllers.
+//!
+//! Frames are the [CanFrame] of the driver API, received frames can be limited to those matching
+//! a list of [CanFilter]s:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::can::{CanFilter, CanFrame, CanId, Device};
+//!# use zephyr_rust_wrappers::network::socket::can::{self, CanSocket};
+//!# fn example(device: &'static Device) {
+//! let socket = CanSocket::bind(can::interface(device).unwrap()).unwrap();
+//! socket.add_filters(&[CanFilter::exact(CanId::Standard(0x123))]).unwrap();
+//! socket.send(&CanFrame::new(CanId::Standard(0x321), &[1, 2, 3]).unwrap()).unwrap();
+//! let frame = socket.recv().unwrap();
+//! println!("{:?}: {:?}", frame.id(), frame.data());
+//!# }
+//! ```
+
+use super::{check, check_len, would_block, AsRawFd, Socket, CONTEXT, DONTWAIT};
+use crate::can::{CanFilter, CanFrame, CanId, Device};
+use crate::network::interface::NetworkInterface;
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+use core::convert::TryFrom;
+use core::ffi::{c_int, c_void};
+use core::mem::size_of;
+use core::time::Duration;
+use zephyr_sys::raw::{
+    sockaddr as ZSockAddr, sockaddr_can as ZSockAddrCan, socketcan_filter as ZSocketCanFilter,
+    socketcan_frame as ZSocketCanFrame, socklen_t,
+};
+
+/// Interface of the CAN controller `device`.
+pub fn interface(device: &'static Device) -> Option<NetworkInterface> {
+    unsafe {
+        NetworkInterface::from_raw(zephyr_sys::raw::net_if_lookup_by_dev(
+            device as *const Device,
+        ))
+    }
+}
+
+fn to_raw_id(id: CanId) -> u32 {
+    match id {
+        CanId::Standard(id) => id as u32,
+        CanId::Extended(id) => id | zephyr_sys::raw::CAN_EFF_FLAG,
+    }
+}
+
+impl From<&CanFrame> for ZSocketCanFrame {
+    fn from(other: &CanFrame) -> Self {
+        // zeroed as the reserved bytes and the rest of the payload must be zero
+        let mut frame: ZSocketCanFrame = unsafe { core::mem::zeroed() };
+        frame.can_id = to_raw_id(other.id());
+        if other.is_remote() {
+            frame.can_id |= zephyr_sys::raw::CAN_RTR_FLAG;
+            frame.len = other.dlc();
+        } else {
+            // the payload is 8 or 64 bytes long depending on CONFIG_CAN_FD_MODE
+            let data = other.data();
+            let len = data.len().min(frame.data.len());
+            frame.data[..len].copy_from_slice(&data[..len]);
+            frame.len = len as u8;
+        }
+        if other.is_fd() {
+            frame.flags |= zephyr_sys::raw::CANFD_FDF as u8;
+        }
+        if other.is_bitrate_switch() {
+            frame.flags |= zephyr_sys::raw::CANFD_BRS as u8;
+        }
+        frame
+    }
+}
+
+impl TryFrom<&ZSocketCanFrame> for CanFrame {
+    type Error = ZephyrError;
+
+    fn try_from(other: &ZSocketCanFrame) -> Result<Self, Self::Error> {
+        let id = if other.can_id & zephyr_sys::raw::CAN_EFF_FLAG != 0 {
+            CanId::Extended(other.can_id & zephyr_sys::raw::CAN_EFF_MASK)
+        } else {
+            CanId::Standard((other.can_id & zephyr_sys::raw::CAN_SFF_MASK) as u16)
+        };
+        if other.can_id & zephyr_sys::raw::CAN_RTR_FLAG != 0 {
+            return CanFrame::remote(id, other.len);
+        }
+
+        let len = (other.len as usize).min(other.data.len());
+        let data = &other.data[..len];
+        if other.flags as u32 & zephyr_sys::raw::CANFD_FDF != 0 {
+            let bitrate_switch = other.flags as u32 & zephyr_sys::raw::CANFD_BRS != 0;
+            CanFrame::new_fd(id, data, bitrate_switch)
+        } else {
+            CanFrame::new(id, data)
+        }
+    }
+}
+
+impl From<&CanFilter> for ZSocketCanFilter {
+    fn from(other: &CanFilter) -> Self {
+        // zeroed as the flags of the filter are optional
+        let mut filter: ZSocketCanFilter = unsafe { core::mem::zeroed() };
+        filter.can_id = to_raw_id(other.id);
+        filter.can_mask = other.mask;
+        filter
+    }
+}
+
+/// Raw CAN socket (`CAN_RAW`) bound to the interface of a CAN controller.
+#[derive(Debug)]
+pub struct CanSocket {
+    socket: Socket,
+    iface: NetworkInterface,
+}
+
+impl CanSocket {
+    /// Creates a `SOCK_RAW` socket of the `AF_CAN` family bound to `iface`, which receives all
+    /// frames until filters are added.
+    pub fn bind(iface: NetworkInterface) -> ZephyrResult<CanSocket> {
+        let fd = check(unsafe {
+            zephyr_sys::syscalls::any::zsock_socket(
+                zephyr_sys::raw::AF_CAN as c_int,
+                zephyr_sys::raw::net_sock_type_SOCK_RAW as c_int,
+                zephyr_sys::raw::CAN_RAW as c_int,
+            )
+        })?;
+        let socket = Socket(fd);
+
+        // zeroed as the addresses of transport protocols are unused
+        let mut address: ZSockAddrCan = unsafe { core::mem::zeroed() };
+        address.can_family = zephyr_sys::raw::AF_CAN as _;
+        address.can_ifindex = iface.index();
+        check(unsafe {
+            zephyr_sys::syscalls::any::zsock_bind(
+                socket.0,
+                &address as *const ZSockAddrCan as *const ZSockAddr,
+                size_of::<ZSockAddrCan>() as socklen_t,
+            )
+        })?;
+        Ok(CanSocket { socket, iface })
+    }
+
+    fn send_flags(&self, frame: &CanFrame, flags: c_int) -> ZephyrResult<()> {
+        let raw = ZSocketCanFrame::from(frame);
+        check_len(unsafe {
+            zephyr_sys::syscalls::any::zsock_sendto(
+                self.socket.0,
+                &raw as *const ZSocketCanFrame as *const c_void,
+                size_of::<ZSocketCanFrame>(),
+                flags,
+                core::ptr::null(),
+                0,
+            )
+        })?;
+        Ok(())
+    }
+
+    fn recv_flags(&self, flags: c_int) -> ZephyrResult<CanFrame> {
+        // zeroed as the frame is written by the receive
+        let mut raw: ZSocketCanFrame = unsafe { core::mem::zeroed() };
+        let len = check_len(unsafe {
+            zephyr_sys::syscalls::any::zsock_recvfrom(
+                self.socket.0,
+                &mut raw as *mut ZSocketCanFrame as *mut c_void,
+                size_of::<ZSocketCanFrame>(),
+                flags,
+                core::ptr::null_mut(),
+                core::ptr::null_mut(),
+            )
+        })?;
+        if len != size_of::<ZSocketCanFrame>() {
+            return Err(ZephyrError::new_with_context(ErrorNumber::Io, &CONTEXT));
+        }
+        CanFrame::try_from(&raw)
+    }
+
+    /// Sends `frame`, blocks while the transmit queue of the controller is full.
+    pub fn send(&self, frame: &CanFrame) -> ZephyrResult<()> {
+        self.send_flags(frame, 0)
+    }
+
+    /// Receives the next frame matching the filters.
+    pub fn recv(&self) -> ZephyrResult<CanFrame> {
+        self.recv_flags(0)
+    }
+
+    /// Sends `frame` without blocking (`MSG_DONTWAIT`), returns `None` if the queue is full.
+    pub fn try_send(&self, frame: &CanFrame) -> ZephyrResult<Option<()>> {
+        would_block(self.send_flags(frame, DONTWAIT))
+    }
+
+    /// Receives a frame without blocking (`MSG_DONTWAIT`), returns `None` if no frame has been
+    /// received.
+    pub fn try_recv(&self) -> ZephyrResult<Option<CanFrame>> {
+        would_block(self.recv_flags(DONTWAIT))
+    }
+
+    /// Add filters of received frames (`CAN_RAW_FILTER`), a frame is received if it matches any
+    /// of the filters added. Zephyr takes one filter per `setsockopt`.
+    pub fn add_filters(&self, filters: &[CanFilter]) -> ZephyrResult<()> {
+        for filter in filters {
+            let raw = ZSocketCanFilter::from(filter);
+            self.socket.set_option(
+                zephyr_sys::raw::SOL_CAN_RAW,
+                zephyr_sys::raw::CAN_RAW_FILTER,
+                &raw,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Interface the socket is bound to.
+    pub fn interface(&self) -> NetworkInterface {
+        self.iface
+    }
+
+    /// Set the timeout of receives, `None` blocks forever.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> ZephyrResult<()> {
+        self.socket.set_timeout(zephyr_sys::raw::SO_RCVTIMEO, timeout)
+    }
+
+    /// Set `O_NONBLOCK`, all operations fail with [crate::ErrorNumber::TryAgain] instead of
+    /// blocking.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> ZephyrResult<()> {
+        self.socket.set_nonblocking(nonblocking)
+    }
+}
+
+impl AsRawFd for CanSocket {
+    fn as_raw_fd(&self) -> c_int {
+        self.socket.0
+    }
+}
@@ -0,0 +1,682 @@
+//! Wrappers for the BSD socket layer of Zephyr (`CONFIG_NET_SOCKETS`).
+//!
+//! [TcpStream], [TcpListener] and [UdpSocket] mirror the types of `std::net` and use the address
+//! types of `std::net` (which are the types of `core::net`), so code written against the standard
+//! library mostly works unchanged:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::socket::TcpStream;
+//!# use std::io::Write;
+//! let mut stream = TcpStream::connect(([192, 0, 2, 1], 4242).into()).unwrap();
+//! stream.write_all(b"hello").unwrap();
+//! ```
+//!
+//! [TcpStream::connect_tls] opens connections secured by the TLS sockets of Zephyr. With the
+//! `executor` feature, [AsyncTcpStream], [AsyncTcpListener] and [AsyncUdpSocket] offer
+//! the same operations as futures. [PacketSocket] sends and receives raw frames of the link layer,
+//! [CanSocket] the frames of CAN controllers.
+//!
+//! The sockets are closed when they are dropped. The functions of the socket layer report errors
+//! in `errno`, which is wrapped in a [ZephyrError].
+
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use super::addr::{family, to_in6_addr, to_in_addr, RawSocketAddr, ZSockAddr};
+use alloc::ffi::CString;
+use core::ffi::{c_int, c_void};
+use core::mem::size_of;
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::io;
+use zephyr_sys::raw::{ip_mreq as ZIpMreq, ipv6_mreq as ZIpv6Mreq, socklen_t};
+
+#[cfg(feature = "executor")]
+mod asynchronous;
+pub mod can;
+pub mod packet;
+
+#[cfg(feature = "executor")]
+pub use asynchronous::{AsyncTcpListener, AsyncTcpStream, AsyncUdpSocket};
+pub use can::CanSocket;
+pub use packet::PacketSocket;
+#[cfg(feature = "std")]
+pub use std::net::Shutdown;
+
+/// Halves of a connection to shut down, the equivalent of `std::net::Shutdown` without the `std`
+/// feature.
+#[cfg(not(feature = "std"))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Shutdown {
+    Read,
+    Write,
+    Both,
+}
+
+const CONTEXT: SocketWrapperContext = SocketWrapperContext {};
+
+/// Returns the `errno` of the calling thread.
+fn errno() -> c_int {
+    unsafe { *zephyr_sys::syscalls::any::z_errno() }
+}
+
+/// The socket functions return `-1` and set `errno` on errors.
+fn check(ret: c_int) -> ZephyrResult<c_int> {
+    if ret < 0 {
+        Err(ZephyrError::from_errno_with_context(errno(), &CONTEXT))
+    } else {
+        Ok(ret)
+    }
+}
+
+fn check_len(ret: isize) -> ZephyrResult<usize> {
+    if ret < 0 {
+        Err(ZephyrError::from_errno_with_context(errno(), &CONTEXT))
+    } else {
+        Ok(ret as usize)
+    }
+}
+
+#[cfg(feature = "std")]
+fn io_error(error: ZephyrError) -> io::Error {
+    io::Error::from_raw_os_error(error.number().into())
+}
+
+/// Flag of a single send or receive that returns instead of blocking.
+const DONTWAIT: c_int = zephyr_sys::raw::ZSOCK_MSG_DONTWAIT as c_int;
+
+/// Maps the error of operations that would block to `None`.
+fn would_block<T>(result: ZephyrResult<T>) -> ZephyrResult<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.is_would_block() => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn invalid_address() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT)
+}
+
+/// Types owning a file descriptor, like the sockets of this module.
+pub trait AsRawFd {
+    /// File descriptor, only valid as long as its owner.
+    fn as_raw_fd(&self) -> c_int;
+}
+
+/// File descriptor of a socket, closed on drop.
+#[derive(Debug)]
+struct Socket(c_int);
+
+impl Socket {
+    fn new(addr: &SocketAddr, socket_type: u32, protocol: u32) -> ZephyrResult<Self> {
+        let fd = check(unsafe {
+            zephyr_sys::syscalls::any::zsock_socket(
+                family(&addr.ip()) as c_int,
+                socket_type as c_int,
+                protocol as c_int,
+            )
+        })?;
+        Ok(Socket(fd))
+    }
+
+    fn stream(addr: &SocketAddr) -> ZephyrResult<Self> {
+        Self::new(
+            addr,
+            zephyr_sys::raw::net_sock_type_SOCK_STREAM,
+            zephyr_sys::raw::net_ip_protocol_IPPROTO_TCP,
+        )
+    }
+
+    fn datagram(addr: &SocketAddr) -> ZephyrResult<Self> {
+        Self::new(
+            addr,
+            zephyr_sys::raw::net_sock_type_SOCK_DGRAM,
+            zephyr_sys::raw::net_ip_protocol_IPPROTO_UDP,
+        )
+    }
+
+    fn connect(&self, addr: &SocketAddr) -> ZephyrResult<()> {
+        let raw = RawSocketAddr::from(addr);
+        check(unsafe {
+            zephyr_sys::syscalls::any::zsock_connect(self.0, raw.as_ptr(), raw.socklen())
+        })?;
+        Ok(())
+    }
+
+    fn bind(&self, addr: &SocketAddr) -> ZephyrResult<()> {
+        let raw = RawSocketAddr::from(addr);
+        check(unsafe {
+            zephyr_sys::syscalls::any::zsock_bind(self.0, raw.as_ptr(), raw.socklen())
+        })?;
+        Ok(())
+    }
+
+    fn accept(&self) -> ZephyrResult<(Socket, SocketAddr)> {
+        let mut raw = RawSocketAddr::new();
+        let fd = check(unsafe {
+            zephyr_sys::syscalls::any::zsock_accept(self.0, raw.as_mut_ptr(), raw.socklen_mut())
+        })?;
+        let socket = Socket(fd);
+        let addr = raw.to_socket_addr().ok_or_else(invalid_address)?;
+        Ok((socket, addr))
+    }
+
+    fn address(
+        &self,
+        f: unsafe fn(c_int, *mut ZSockAddr, *mut socklen_t) -> c_int,
+    ) -> ZephyrResult<SocketAddr> {
+        let mut raw = RawSocketAddr::new();
+        check(unsafe { f(self.0, raw.as_mut_ptr(), raw.socklen_mut()) })?;
+        raw.to_socket_addr().ok_or_else(invalid_address)
+    }
+
+    fn local_addr(&self) -> ZephyrResult<SocketAddr> {
+        self.address(zephyr_sys::syscalls::any::zsock_getsockname)
+    }
+
+    fn peer_addr(&self) -> ZephyrResult<SocketAddr> {
+        self.address(zephyr_sys::syscalls::any::zsock_getpeername)
+    }
+
+    fn send_to(&self, buf: &[u8], addr: Option<&SocketAddr>, flags: c_int) -> ZephyrResult<usize> {
+        let raw = addr.map(RawSocketAddr::from);
+        let (addr_ptr, len) = match &raw {
+            Some(raw) => (raw.as_ptr(), raw.socklen()),
+            None => (core::ptr::null(), 0),
+        };
+        check_len(unsafe {
+            zephyr_sys::syscalls::any::zsock_sendto(
+                self.0,
+                buf.as_ptr() as *const c_void,
+                buf.len(),
+                flags,
+                addr_ptr,
+                len,
+            )
+        })
+    }
+
+    fn recv_from(&self, buf: &mut [u8], flags: c_int) -> ZephyrResult<(usize, Option<SocketAddr>)> {
+        let mut raw = RawSocketAddr::new();
+        let count = check_len(unsafe {
+            zephyr_sys::syscalls::any::zsock_recvfrom(
+                self.0,
+                buf.as_mut_ptr() as *mut c_void,
+                buf.len(),
+                flags,
+                raw.as_mut_ptr(),
+                raw.socklen_mut(),
+            )
+        })?;
+        Ok((count, raw.to_socket_addr()))
+    }
+
+    fn shutdown(&self, how: Shutdown) -> ZephyrResult<()> {
+        let how = match how {
+            Shutdown::Read => zephyr_sys::raw::ZSOCK_SHUT_RD,
+            Shutdown::Write => zephyr_sys::raw::ZSOCK_SHUT_WR,
+            Shutdown::Both => zephyr_sys::raw::ZSOCK_SHUT_RDWR,
+        };
+        check(unsafe { zephyr_sys::syscalls::any::zsock_shutdown(self.0, how as c_int) })?;
+        Ok(())
+    }
+
+    fn set_option<T>(&self, level: u32, name: u32, value: &T) -> ZephyrResult<()> {
+        self.set_option_bytes(level, name, value as *const T as *const c_void, size_of::<T>())
+    }
+
+    fn set_option_bytes(
+        &self,
+        level: u32,
+        name: u32,
+        value: *const c_void,
+        len: usize,
+    ) -> ZephyrResult<()> {
+        check(unsafe {
+            zephyr_sys::syscalls::any::zsock_setsockopt(
+                self.0,
+                level as c_int,
+                name as c_int,
+                value,
+                len as socklen_t,
+            )
+        })?;
+        Ok(())
+    }
+
+    fn set_timeout(&self, name: u32, timeout: Option<Duration>) -> ZephyrResult<()> {
+        // a zero timeout blocks forever
+        let timeout = timeout.unwrap_or_default();
+        let value = zephyr_sys::raw::zsock_timeval {
+            tv_sec: timeout.as_secs() as _,
+            tv_usec: timeout.subsec_micros() as _,
+        };
+        self.set_option(zephyr_sys::raw::SOL_SOCKET, name, &value)
+    }
+
+    fn set_flag(&self, level: u32, name: u32, enable: bool) -> ZephyrResult<()> {
+        let value = enable as c_int;
+        self.set_option(level, name, &value)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> ZephyrResult<()> {
+        let fcntl = zephyr_sys::syscalls::any::zsock_fcntl_impl;
+        let flags = check(unsafe { fcntl(self.0, zephyr_sys::raw::F_GETFL as c_int, 0) })?;
+        let flags = if nonblocking {
+            flags | zephyr_sys::raw::O_NONBLOCK as c_int
+        } else {
+            flags & !(zephyr_sys::raw::O_NONBLOCK as c_int)
+        };
+        check(unsafe { fcntl(self.0, zephyr_sys::raw::F_SETFL as c_int, flags) })?;
+        Ok(())
+    }
+
+    fn take_error(&self) -> ZephyrResult<Option<ZephyrError>> {
+        let mut value: c_int = 0;
+        let mut len = size_of::<c_int>() as socklen_t;
+        check(unsafe {
+            zephyr_sys::syscalls::any::zsock_getsockopt(
+                self.0,
+                zephyr_sys::raw::SOL_SOCKET as c_int,
+                zephyr_sys::raw::SO_ERROR as c_int,
+                &mut value as *mut c_int as *mut c_void,
+                &mut len,
+            )
+        })?;
+
+        if value == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(ZephyrError::from_errno_with_context(value, &CONTEXT)))
+        }
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        unsafe { zephyr_sys::syscalls::any::zsock_close(self.0) };
+    }
+}
+
+/// TCP connection between a local and a remote socket, mirrors [std::net::TcpStream].
+#[derive(Debug)]
+pub struct TcpStream {
+    socket: Socket,
+}
+
+impl TcpStream {
+    /// Opens a TCP connection to `addr`.
+    pub fn connect(addr: SocketAddr) -> ZephyrResult<TcpStream> {
+        let socket = Socket::stream(&addr)?;
+        socket.connect(&addr)?;
+        Ok(TcpStream { socket })
+    }
+
+    /// Opens a TLS 1.2 connection to `addr`, requires `CONFIG_NET_SOCKETS_SOCKOPT_TLS`.
+    ///
+    /// The credentials are the security tags registered with the TLS credentials API, `hostname`
+    /// is verified against the certificate of the server.
+    pub fn connect_tls(
+        addr: SocketAddr,
+        hostname: &str,
+        sec_tags: &[zephyr_sys::raw::sec_tag_t],
+    ) -> ZephyrResult<TcpStream> {
+        let socket = Socket::new(
+            &addr,
+            zephyr_sys::raw::net_sock_type_SOCK_STREAM,
+            zephyr_sys::raw::net_ip_protocol_secure_IPPROTO_TLS_1_2,
+        )?;
+        socket.set_option_bytes(
+            zephyr_sys::raw::SOL_TLS,
+            zephyr_sys::raw::TLS_SEC_TAG_LIST,
+            sec_tags.as_ptr() as *const c_void,
+            size_of::<zephyr_sys::raw::sec_tag_t>() * sec_tags.len(),
+        )?;
+        // the hostname is passed on to mbed TLS as C string
+        let hostname = CString::new(hostname).map_err(|_| invalid_address())?;
+        let hostname = hostname.as_bytes_with_nul();
+        socket.set_option_bytes(
+            zephyr_sys::raw::SOL_TLS,
+            zephyr_sys::raw::TLS_HOSTNAME,
+            hostname.as_ptr() as *const c_void,
+            hostname.len(),
+        )?;
+        socket.connect(&addr)?;
+        Ok(TcpStream { socket })
+    }
+
+    /// Releases the file descriptor without closing it, e.g. to hand the connection to an API
+    /// that closes it.
+    pub fn into_raw_fd(self) -> c_int {
+        let fd = self.socket.0;
+        core::mem::forget(self);
+        fd
+    }
+
+    /// Address of the remote peer.
+    pub fn peer_addr(&self) -> ZephyrResult<SocketAddr> {
+        self.socket.peer_addr()
+    }
+
+    /// Local address of the connection.
+    pub fn local_addr(&self) -> ZephyrResult<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Shuts down the read, write or both halves of the connection.
+    pub fn shutdown(&self, how: Shutdown) -> ZephyrResult<()> {
+        self.socket.shutdown(how)
+    }
+
+    /// Enable or disable the Nagle algorithm.
+    pub fn set_nodelay(&self, nodelay: bool) -> ZephyrResult<()> {
+        self.socket.set_flag(
+            zephyr_sys::raw::net_ip_protocol_IPPROTO_TCP,
+            zephyr_sys::raw::TCP_NODELAY,
+            nodelay,
+        )
+    }
+
+    /// Set the timeout of reads, `None` blocks forever.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> ZephyrResult<()> {
+        self.socket.set_timeout(zephyr_sys::raw::SO_RCVTIMEO, timeout)
+    }
+
+    /// Set the timeout of writes, `None` blocks forever.
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> ZephyrResult<()> {
+        self.socket.set_timeout(zephyr_sys::raw::SO_SNDTIMEO, timeout)
+    }
+
+    /// Receives data into `buf` without removing it from the queue.
+    pub fn peek(&self, buf: &mut [u8]) -> ZephyrResult<usize> {
+        let flags = zephyr_sys::raw::ZSOCK_MSG_PEEK as c_int;
+        Ok(self.socket.recv_from(buf, flags)?.0)
+    }
+
+    /// Receives data into `buf`, returns `0` if the peer closed the connection.
+    pub fn recv(&self, buf: &mut [u8]) -> ZephyrResult<usize> {
+        Ok(self.socket.recv_from(buf, 0)?.0)
+    }
+
+    /// Sends data of `buf`, returns how many bytes were sent.
+    pub fn send(&self, buf: &[u8]) -> ZephyrResult<usize> {
+        self.socket.send_to(buf, None, 0)
+    }
+
+    /// Receives data into `buf` without blocking (`MSG_DONTWAIT`), returns `None` if no data has
+    /// been received.
+    pub fn try_recv(&self, buf: &mut [u8]) -> ZephyrResult<Option<usize>> {
+        would_block(self.socket.recv_from(buf, DONTWAIT).map(|(count, _)| count))
+    }
+
+    /// Sends data of `buf` without blocking (`MSG_DONTWAIT`), returns `None` if the send buffer is
+    /// full.
+    pub fn try_send(&self, buf: &[u8]) -> ZephyrResult<Option<usize>> {
+        would_block(self.socket.send_to(buf, None, DONTWAIT))
+    }
+
+    /// Set `O_NONBLOCK`, all operations fail with [ErrorNumber::TryAgain] instead of blocking.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> ZephyrResult<()> {
+        self.socket.set_nonblocking(nonblocking)
+    }
+
+    /// Returns and clears the pending error of the socket (`SO_ERROR`), e.g. of a connect.
+    pub fn take_error(&self) -> ZephyrResult<Option<ZephyrError>> {
+        self.socket.take_error()
+    }
+}
+
+impl AsRawFd for TcpStream {
+    fn as_raw_fd(&self) -> c_int {
+        self.socket.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl io::Read for TcpStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.recv(buf).map_err(io_error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl io::Write for TcpStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.send(buf).map_err(io_error)
+    }
+
+    /// Sent data is queued in the network stack, so there is nothing to flush.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// TCP socket listening for connections, mirrors [std::net::TcpListener].
+#[derive(Debug)]
+pub struct TcpListener {
+    socket: Socket,
+}
+
+impl TcpListener {
+    /// Number of pending connections queued by the socket layer.
+    const BACKLOG: c_int = 4;
+
+    /// Creates a listener bound to `addr`, port `0` lets the stack choose a port.
+    pub fn bind(addr: SocketAddr) -> ZephyrResult<TcpListener> {
+        let socket = Socket::stream(&addr)?;
+        socket.set_flag(
+            zephyr_sys::raw::SOL_SOCKET,
+            zephyr_sys::raw::SO_REUSEADDR,
+            true,
+        )?;
+        socket.bind(&addr)?;
+        check(unsafe { zephyr_sys::syscalls::any::zsock_listen(socket.0, Self::BACKLOG) })?;
+        Ok(TcpListener { socket })
+    }
+
+    /// Local address the listener is bound to.
+    pub fn local_addr(&self) -> ZephyrResult<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Blocks until a connection has been established, returns the connection and the address of
+    /// the peer.
+    pub fn accept(&self) -> ZephyrResult<(TcpStream, SocketAddr)> {
+        let (socket, addr) = self.socket.accept()?;
+        Ok((TcpStream { socket }, addr))
+    }
+
+    /// Set `O_NONBLOCK`, [TcpListener::accept] fails with [ErrorNumber::TryAgain] instead of
+    /// blocking if no connection is pending.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> ZephyrResult<()> {
+        self.socket.set_nonblocking(nonblocking)
+    }
+
+    /// Iterator accepting connections, never returns `None`.
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming { listener: self }
+    }
+}
+
+impl AsRawFd for TcpListener {
+    fn as_raw_fd(&self) -> c_int {
+        self.socket.0
+    }
+}
+
+/// Iterator over the connections of a [TcpListener], returned by [TcpListener::incoming].
+#[derive(Debug)]
+pub struct Incoming<'l> {
+    listener: &'l TcpListener,
+}
+
+impl Iterator for Incoming<'_> {
+    type Item = ZephyrResult<TcpStream>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.listener.accept().map(|(stream, _)| stream))
+    }
+}
+
+/// UDP socket, mirrors [std::net::UdpSocket].
+#[derive(Debug)]
+pub struct UdpSocket {
+    socket: Socket,
+}
+
+impl UdpSocket {
+    /// Creates a socket bound to `addr`, port `0` lets the stack choose a port.
+    pub fn bind(addr: SocketAddr) -> ZephyrResult<UdpSocket> {
+        let socket = Socket::datagram(&addr)?;
+        socket.bind(&addr)?;
+        Ok(UdpSocket { socket })
+    }
+
+    /// Sets the default destination of [UdpSocket::send] and only receives datagrams of `addr`.
+    pub fn connect(&self, addr: SocketAddr) -> ZephyrResult<()> {
+        self.socket.connect(&addr)
+    }
+
+    /// Sends the datagram `buf` to `addr`.
+    pub fn send_to(&self, buf: &[u8], addr: SocketAddr) -> ZephyrResult<usize> {
+        self.socket.send_to(buf, Some(&addr), 0)
+    }
+
+    /// Receives a datagram into `buf`, returns its length and sender.
+    ///
+    /// The rest of datagrams larger than `buf` is discarded.
+    pub fn recv_from(&self, buf: &mut [u8]) -> ZephyrResult<(usize, SocketAddr)> {
+        let (count, addr) = self.socket.recv_from(buf, 0)?;
+        Ok((count, addr.ok_or_else(invalid_address)?))
+    }
+
+    /// Sends the datagram `buf` to `addr` without blocking (`MSG_DONTWAIT`), returns `None` if the
+    /// send buffer is full.
+    pub fn try_send_to(&self, buf: &[u8], addr: SocketAddr) -> ZephyrResult<Option<usize>> {
+        would_block(self.socket.send_to(buf, Some(&addr), DONTWAIT))
+    }
+
+    /// Receives a datagram into `buf` without blocking (`MSG_DONTWAIT`), returns `None` if no
+    /// datagram has been received.
+    pub fn try_recv_from(&self, buf: &mut [u8]) -> ZephyrResult<Option<(usize, SocketAddr)>> {
+        match would_block(self.socket.recv_from(buf, DONTWAIT))? {
+            Some((count, addr)) => Ok(Some((count, addr.ok_or_else(invalid_address)?))),
+            None => Ok(None),
+        }
+    }
+
+    /// Sends the datagram `buf` to the address of [UdpSocket::connect].
+    pub fn send(&self, buf: &[u8]) -> ZephyrResult<usize> {
+        self.socket.send_to(buf, None, 0)
+    }
+
+    /// Receives a datagram of the address of [UdpSocket::connect] into `buf`.
+    pub fn recv(&self, buf: &mut [u8]) -> ZephyrResult<usize> {
+        Ok(self.socket.recv_from(buf, 0)?.0)
+    }
+
+    /// Address of [UdpSocket::connect].
+    pub fn peer_addr(&self) -> ZephyrResult<SocketAddr> {
+        self.socket.peer_addr()
+    }
+
+    /// Local address the socket is bound to.
+    pub fn local_addr(&self) -> ZephyrResult<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Enable or disable sending to broadcast addresses.
+    pub fn set_broadcast(&self, broadcast: bool) -> ZephyrResult<()> {
+        self.socket.set_flag(
+            zephyr_sys::raw::SOL_SOCKET,
+            zephyr_sys::raw::SO_BROADCAST,
+            broadcast,
+        )
+    }
+
+    fn membership_v4(
+        &self,
+        name: u32,
+        multiaddr: &Ipv4Addr,
+        interface: &Ipv4Addr,
+    ) -> ZephyrResult<()> {
+        let value = ZIpMreq {
+            imr_multiaddr: to_in_addr(multiaddr),
+            imr_interface: to_in_addr(interface),
+        };
+        self.socket.set_option(zephyr_sys::raw::net_ip_protocol_IPPROTO_IP, name, &value)
+    }
+
+    fn membership_v6(&self, name: u32, multiaddr: &Ipv6Addr, interface: u32) -> ZephyrResult<()> {
+        let value = ZIpv6Mreq {
+            ipv6mr_multiaddr: to_in6_addr(multiaddr),
+            ipv6mr_ifindex: interface as _,
+        };
+        self.socket.set_option(zephyr_sys::raw::net_ip_protocol_IPPROTO_IPV6, name, &value)
+    }
+
+    /// Joins the IPv4 multicast group `multiaddr` on the interface with the address `interface`,
+    /// or the default interface for [Ipv4Addr::UNSPECIFIED] (`IP_ADD_MEMBERSHIP`).
+    pub fn join_multicast_v4(
+        &self,
+        multiaddr: &Ipv4Addr,
+        interface: &Ipv4Addr,
+    ) -> ZephyrResult<()> {
+        self.membership_v4(zephyr_sys::raw::IP_ADD_MEMBERSHIP, multiaddr, interface)
+    }
+
+    /// Leaves the IPv4 multicast group `multiaddr` (`IP_DROP_MEMBERSHIP`).
+    pub fn leave_multicast_v4(
+        &self,
+        multiaddr: &Ipv4Addr,
+        interface: &Ipv4Addr,
+    ) -> ZephyrResult<()> {
+        self.membership_v4(zephyr_sys::raw::IP_DROP_MEMBERSHIP, multiaddr, interface)
+    }
+
+    /// Joins the IPv6 multicast group `multiaddr` on the interface with the index `interface`, or
+    /// the default interface for `0` (`IPV6_ADD_MEMBERSHIP`).
+    pub fn join_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> ZephyrResult<()> {
+        self.membership_v6(zephyr_sys::raw::IPV6_ADD_MEMBERSHIP, multiaddr, interface)
+    }
+
+    /// Leaves the IPv6 multicast group `multiaddr` (`IPV6_DROP_MEMBERSHIP`).
+    pub fn leave_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> ZephyrResult<()> {
+        self.membership_v6(zephyr_sys::raw::IPV6_DROP_MEMBERSHIP, multiaddr, interface)
+    }
+
+    /// Set the timeout of receives, `None` blocks forever.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> ZephyrResult<()> {
+        self.socket.set_timeout(zephyr_sys::raw::SO_RCVTIMEO, timeout)
+    }
+
+    /// Set the timeout of sends, `None` blocks forever.
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> ZephyrResult<()> {
+        self.socket.set_timeout(zephyr_sys::raw::SO_SNDTIMEO, timeout)
+    }
+
+    /// Set `O_NONBLOCK`, all operations fail with [ErrorNumber::TryAgain] instead of blocking.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> ZephyrResult<()> {
+        self.socket.set_nonblocking(nonblocking)
+    }
+
+    /// Returns and clears the pending error of the socket (`SO_ERROR`).
+    pub fn take_error(&self) -> ZephyrResult<Option<ZephyrError>> {
+        self.socket.take_error()
+    }
+}
+
+impl AsRawFd for UdpSocket {
+    fn as_raw_fd(&self) -> c_int {
+        self.socket.0
+    }
+}
+
+#[derive(Debug)]
+struct SocketWrapperContext {}
+
+impl Context for SocketWrapperContext {
+    fn name(&self) -> &'static str {
+        "socket wrapper"
+    }
+}
@@ -0,0 +1,127 @@
+//! Packet sockets of the link layer (`CONFIG_NET_SOCKETS_PACKET`), e.g. for protocols without IP
+//! or capturing the traffic of an interface.
+
+use super::{check, check_len, would_block, AsRawFd, Socket, DONTWAIT};
+use crate::network::interface::NetworkInterface;
+use crate::ZephyrResult;
+use core::ffi::{c_int, c_void};
+use core::mem::size_of;
+use core::time::Duration;
+use zephyr_sys::raw::{sockaddr as ZSockAddr, sockaddr_ll as ZSockAddrLl, socklen_t};
+
+/// Protocols of [PacketSocket::bind], the `ETH_P_*` constants of Zephyr.
+pub mod protocol {
+    /// Frames of all protocols.
+    pub const ALL: u16 = zephyr_sys::raw::ETH_P_ALL as u16;
+    /// IEEE 802.15.4 frames.
+    pub const IEEE802154: u16 = zephyr_sys::raw::ETH_P_IEEE802154 as u16;
+}
+
+/// Raw socket of the link layer of an interface.
+///
+/// Frames are sent and received with their link layer header, bypassing the IP stack.
+#[derive(Debug)]
+pub struct PacketSocket {
+    socket: Socket,
+    address: ZSockAddrLl,
+}
+
+impl PacketSocket {
+    /// Creates a `SOCK_RAW` socket of the `AF_PACKET` family bound to `iface`, receiving the
+    /// frames of `protocol`.
+    pub fn bind(iface: NetworkInterface, protocol: u16) -> ZephyrResult<PacketSocket> {
+        let fd = check(unsafe {
+            zephyr_sys::syscalls::any::zsock_socket(
+                zephyr_sys::raw::AF_PACKET as c_int,
+                zephyr_sys::raw::net_sock_type_SOCK_RAW as c_int,
+                protocol.to_be() as c_int,
+            )
+        })?;
+        let socket = Socket(fd);
+
+        // zeroed as the hardware address is unused
+        let mut address: ZSockAddrLl = unsafe { core::mem::zeroed() };
+        address.sll_family = zephyr_sys::raw::AF_PACKET as _;
+        address.sll_protocol = protocol.to_be();
+        address.sll_ifindex = iface.index();
+        check(unsafe {
+            zephyr_sys::syscalls::any::zsock_bind(
+                socket.0,
+                &address as *const ZSockAddrLl as *const ZSockAddr,
+                size_of::<ZSockAddrLl>() as socklen_t,
+            )
+        })?;
+        Ok(PacketSocket { socket, address })
+    }
+
+    fn send_flags(&self, frame: &[u8], flags: c_int) -> ZephyrResult<usize> {
+        check_len(unsafe {
+            zephyr_sys::syscalls::any::zsock_sendto(
+                self.socket.0,
+                frame.as_ptr() as *const c_void,
+                frame.len(),
+                flags,
+                &self.address as *const ZSockAddrLl as *const ZSockAddr,
+                size_of::<ZSockAddrLl>() as socklen_t,
+            )
+        })
+    }
+
+    fn recv_flags(&self, buf: &mut [u8], flags: c_int) -> ZephyrResult<usize> {
+        check_len(unsafe {
+            zephyr_sys::syscalls::any::zsock_recvfrom(
+                self.socket.0,
+                buf.as_mut_ptr() as *mut c_void,
+                buf.len(),
+                flags,
+                core::ptr::null_mut(),
+                core::ptr::null_mut(),
+            )
+        })
+    }
+
+    /// Sends `frame`, which starts with the link layer header, on the interface.
+    pub fn send(&self, frame: &[u8]) -> ZephyrResult<usize> {
+        self.send_flags(frame, 0)
+    }
+
+    /// Receives a frame into `buf`, returns its length.
+    ///
+    /// The rest of frames larger than `buf` is discarded.
+    pub fn recv(&self, buf: &mut [u8]) -> ZephyrResult<usize> {
+        self.recv_flags(buf, 0)
+    }
+
+    /// Sends `frame` without blocking (`MSG_DONTWAIT`), returns `None` if the send buffer is full.
+    pub fn try_send(&self, frame: &[u8]) -> ZephyrResult<Option<usize>> {
+        would_block(self.send_flags(frame, DONTWAIT))
+    }
+
+    /// Receives a frame without blocking (`MSG_DONTWAIT`), returns `None` if no frame has been
+    /// received.
+    pub fn try_recv(&self, buf: &mut [u8]) -> ZephyrResult<Option<usize>> {
+        would_block(self.recv_flags(buf, DONTWAIT))
+    }
+
+    /// Interface the socket is bound to.
+    pub fn interface(&self) -> Option<NetworkInterface> {
+        NetworkInterface::by_index(self.address.sll_ifindex)
+    }
+
+    /// Set the timeout of receives, `None` blocks forever.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> ZephyrResult<()> {
+        self.socket.set_timeout(zephyr_sys::raw::SO_RCVTIMEO, timeout)
+    }
+
+    /// Set `O_NONBLOCK`, all operations fail with [crate::ErrorNumber::TryAgain] instead of
+    /// blocking.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> ZephyrResult<()> {
+        self.socket.set_nonblocking(nonblocking)
+    }
+}
+
+impl AsRawFd for PacketSocket {
+    fn as_raw_fd(&self) -> c_int {
+        self.socket.0
+    }
+}
@@ -0,0 +1,294 @@
+//! Wrappers for the statistics of the network stack (`CONFIG_NET_STATISTICS_USER_API`).
+//!
+//! The counters are read with the `NET_REQUEST_STATS_*` requests of the network management API,
+//! either of a single interface (`CONFIG_NET_STATISTICS_PER_INTERFACE`) or summed up over all
+//! interfaces for `None`. The functions of the protocols are only compiled in with their
+//! `CONFIG_NET_STATISTICS_*` option, requests failing at runtime are skipped by [snapshot]:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::stats;
+//! let stats = stats::snapshot(None);
+//! if let Some(tcp) = stats.tcp {
+//!     println!("{} retransmissions, {} bytes sent", tcp.retransmissions, tcp.bytes.sent);
+//! }
+//! ```
+
+use super::interface::{NetworkInterface, RequestHandler};
+use crate::{Context, ZephyrError, ZephyrResult};
+use core::ffi::{c_int, c_void};
+use zephyr_sys::raw::{
+    net_stats_bytes as ZBytes, net_stats_icmp as ZIcmp, net_stats_ip as ZIp,
+    net_stats_t as ZCounter, net_stats_tcp as ZTcp, net_stats_udp as ZUdp,
+};
+
+const CONTEXT: StatsWrapperContext = StatsWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Sends the request `code` for `iface`, or for all interfaces with a null interface.
+fn request<T>(
+    iface: Option<NetworkInterface>,
+    handler: RequestHandler,
+    code: u32,
+) -> ZephyrResult<T> {
+    // zeroed as the statistics are written by the request
+    let mut stats: T = unsafe { core::mem::zeroed() };
+    let iface = iface.map_or(core::ptr::null_mut(), |iface| iface.as_raw());
+    let data = &mut stats as *mut T as *mut c_void;
+    result(unsafe { handler(code, iface, data, core::mem::size_of::<T>()) })?;
+    Ok(stats)
+}
+
+/// Bytes sent and received.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ByteStats {
+    pub sent: u64,
+    pub received: u64,
+}
+
+impl From<&ZBytes> for ByteStats {
+    fn from(raw: &ZBytes) -> Self {
+        ByteStats {
+            sent: raw.sent as u64,
+            received: raw.received as u64,
+        }
+    }
+}
+
+/// Packets of IPv4 or IPv6.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct IpStats {
+    pub received: u64,
+    pub sent: u64,
+    pub forwarded: u64,
+    pub dropped: u64,
+}
+
+impl From<&ZIp> for IpStats {
+    fn from(raw: &ZIp) -> Self {
+        IpStats {
+            received: raw.recv as u64,
+            sent: raw.sent as u64,
+            forwarded: raw.forwarded as u64,
+            dropped: raw.drop as u64,
+        }
+    }
+}
+
+/// Messages of ICMPv4 and ICMPv6.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct IcmpStats {
+    pub received: u64,
+    pub sent: u64,
+    pub dropped: u64,
+    /// Messages of an unsupported type.
+    pub type_errors: u64,
+    pub checksum_errors: u64,
+}
+
+impl From<&ZIcmp> for IcmpStats {
+    fn from(raw: &ZIcmp) -> Self {
+        IcmpStats {
+            received: raw.recv as u64,
+            sent: raw.sent as u64,
+            dropped: raw.drop as u64,
+            type_errors: raw.typeerr as u64,
+            checksum_errors: raw.chkerr as u64,
+        }
+    }
+}
+
+/// Segments and connections of TCP.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TcpStats {
+    /// Bytes of the payload of the segments.
+    pub bytes: ByteStats,
+    /// Bytes sent again.
+    pub resent: u64,
+    pub received: u64,
+    pub sent: u64,
+    /// Packets dropped by the TCP layer.
+    pub dropped: u64,
+    pub segments_dropped: u64,
+    pub checksum_errors: u64,
+    /// Segments with an invalid acknowledgment number.
+    pub ack_errors: u64,
+    /// Invalid reset segments.
+    pub rst_errors: u64,
+    /// Reset segments received.
+    pub rst: u64,
+    pub retransmissions: u64,
+    pub connections_dropped: u64,
+    pub connections_reset: u64,
+}
+
+impl From<&ZTcp> for TcpStats {
+    fn from(raw: &ZTcp) -> Self {
+        TcpStats {
+            bytes: (&raw.bytes).into(),
+            resent: raw.resent as u64,
+            received: raw.recv as u64,
+            sent: raw.sent as u64,
+            dropped: raw.drop as u64,
+            segments_dropped: raw.seg_drop as u64,
+            checksum_errors: raw.chkerr as u64,
+            ack_errors: raw.ackerr as u64,
+            rst_errors: raw.rsterr as u64,
+            rst: raw.rst as u64,
+            retransmissions: raw.rexmit as u64,
+            connections_dropped: raw.conndrop as u64,
+            connections_reset: raw.connrst as u64,
+        }
+    }
+}
+
+/// Datagrams of UDP.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct UdpStats {
+    pub received: u64,
+    pub sent: u64,
+    pub dropped: u64,
+    pub checksum_errors: u64,
+}
+
+impl From<&ZUdp> for UdpStats {
+    fn from(raw: &ZUdp) -> Self {
+        UdpStats {
+            received: raw.recv as u64,
+            sent: raw.sent as u64,
+            dropped: raw.drop as u64,
+            checksum_errors: raw.chkerr as u64,
+        }
+    }
+}
+
+/// Sends `NET_REQUEST_STATS_GET_BYTES`, the bytes of all protocols.
+pub fn bytes(iface: Option<NetworkInterface>) -> ZephyrResult<ByteStats> {
+    let raw: ZBytes = request(
+        iface,
+        zephyr_sys::raw::net_mgmt_NET_REQUEST_STATS_GET_BYTES,
+        zephyr_sys::raw::NET_REQUEST_STATS_GET_BYTES,
+    )?;
+    Ok((&raw).into())
+}
+
+/// Sends `NET_REQUEST_STATS_GET_PROCESSING_ERROR`, the packets the stack failed to process.
+pub fn processing_errors(iface: Option<NetworkInterface>) -> ZephyrResult<u64> {
+    let raw: ZCounter = request(
+        iface,
+        zephyr_sys::raw::net_mgmt_NET_REQUEST_STATS_GET_PROCESSING_ERROR,
+        zephyr_sys::raw::NET_REQUEST_STATS_GET_PROCESSING_ERROR,
+    )?;
+    Ok(raw as u64)
+}
+
+/// Sends `NET_REQUEST_STATS_GET_IPV4`, requires `CONFIG_NET_STATISTICS_IPV4`.
+#[cfg(zephyr_config_net_statistics_ipv4)]
+pub fn ipv4(iface: Option<NetworkInterface>) -> ZephyrResult<IpStats> {
+    let raw: ZIp = request(
+        iface,
+        zephyr_sys::raw::net_mgmt_NET_REQUEST_STATS_GET_IPV4,
+        zephyr_sys::raw::NET_REQUEST_STATS_GET_IPV4,
+    )?;
+    Ok((&raw).into())
+}
+
+/// Sends `NET_REQUEST_STATS_GET_IPV6`, requires `CONFIG_NET_STATISTICS_IPV6`.
+#[cfg(zephyr_config_net_statistics_ipv6)]
+pub fn ipv6(iface: Option<NetworkInterface>) -> ZephyrResult<IpStats> {
+    let raw: ZIp = request(
+        iface,
+        zephyr_sys::raw::net_mgmt_NET_REQUEST_STATS_GET_IPV6,
+        zephyr_sys::raw::NET_REQUEST_STATS_GET_IPV6,
+    )?;
+    Ok((&raw).into())
+}
+
+/// Sends `NET_REQUEST_STATS_GET_ICMP`, requires `CONFIG_NET_STATISTICS_ICMP`.
+#[cfg(zephyr_config_net_statistics_icmp)]
+pub fn icmp(iface: Option<NetworkInterface>) -> ZephyrResult<IcmpStats> {
+    let raw: ZIcmp = request(
+        iface,
+        zephyr_sys::raw::net_mgmt_NET_REQUEST_STATS_GET_ICMP,
+        zephyr_sys::raw::NET_REQUEST_STATS_GET_ICMP,
+    )?;
+    Ok((&raw).into())
+}
+
+/// Sends `NET_REQUEST_STATS_GET_TCP`, requires `CONFIG_NET_STATISTICS_TCP`.
+#[cfg(zephyr_config_net_statistics_tcp)]
+pub fn tcp(iface: Option<NetworkInterface>) -> ZephyrResult<TcpStats> {
+    let raw: ZTcp = request(
+        iface,
+        zephyr_sys::raw::net_mgmt_NET_REQUEST_STATS_GET_TCP,
+        zephyr_sys::raw::NET_REQUEST_STATS_GET_TCP,
+    )?;
+    Ok((&raw).into())
+}
+
+/// Sends `NET_REQUEST_STATS_GET_UDP`, requires `CONFIG_NET_STATISTICS_UDP`.
+#[cfg(zephyr_config_net_statistics_udp)]
+pub fn udp(iface: Option<NetworkInterface>) -> ZephyrResult<UdpStats> {
+    let raw: ZUdp = request(
+        iface,
+        zephyr_sys::raw::net_mgmt_NET_REQUEST_STATS_GET_UDP,
+        zephyr_sys::raw::NET_REQUEST_STATS_GET_UDP,
+    )?;
+    Ok((&raw).into())
+}
+
+/// Statistics of all protocols, `None` for protocols without statistics.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct NetStats {
+    pub bytes: Option<ByteStats>,
+    pub processing_errors: Option<u64>,
+    pub ipv4: Option<IpStats>,
+    pub ipv6: Option<IpStats>,
+    pub icmp: Option<IcmpStats>,
+    pub tcp: Option<TcpStats>,
+    pub udp: Option<UdpStats>,
+}
+
+/// Reads the statistics of all protocols of `iface`, or of all interfaces for `None`. Protocols
+/// whose statistics are disabled in the configuration are `None`.
+pub fn snapshot(iface: Option<NetworkInterface>) -> NetStats {
+    NetStats {
+        bytes: bytes(iface).ok(),
+        processing_errors: processing_errors(iface).ok(),
+        #[cfg(zephyr_config_net_statistics_ipv4)]
+        ipv4: ipv4(iface).ok(),
+        #[cfg(not(zephyr_config_net_statistics_ipv4))]
+        ipv4: None,
+        #[cfg(zephyr_config_net_statistics_ipv6)]
+        ipv6: ipv6(iface).ok(),
+        #[cfg(not(zephyr_config_net_statistics_ipv6))]
+        ipv6: None,
+        #[cfg(zephyr_config_net_statistics_icmp)]
+        icmp: icmp(iface).ok(),
+        #[cfg(not(zephyr_config_net_statistics_icmp))]
+        icmp: None,
+        #[cfg(zephyr_config_net_statistics_tcp)]
+        tcp: tcp(iface).ok(),
+        #[cfg(not(zephyr_config_net_statistics_tcp))]
+        tcp: None,
+        #[cfg(zephyr_config_net_statistics_udp)]
+        udp: udp(iface).ok(),
+        #[cfg(not(zephyr_config_net_statistics_udp))]
+        udp: None,
+    }
+}
+
+#[derive(Debug)]
+struct StatsWrapperContext {}
+
+impl Context for StatsWrapperContext {
+    fn name(&self) -> &'static str {
+        "net_stats wrapper"
+    }
+}
@@ -0,0 +1,229 @@
+//! Wrappers for the WebSocket client of Zephyr (`CONFIG_WEBSOCKET_CLIENT`).
+//!
+//! The handshake is an HTTP upgrade request sent by the HTTP client of Zephyr over an established
+//! [TcpStream], which may be secured with [TcpStream::connect_tls]:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::socket::TcpStream;
+//!# use zephyr_rust_wrappers::network::websocket::{Message, WebSocket};
+//! let stream = TcpStream::connect(([192, 0, 2, 1], 80).into()).unwrap();
+//! let mut socket = WebSocket::connect(stream, "example.com", "/events", None).unwrap();
+//! socket.send(&Message::Text("hello".into())).unwrap();
+//! if let Message::Text(text) = socket.recv(None).unwrap() {
+//!     println!("{}", text);
+//! }
+//! ```
+//!
+//! Fragmented messages are reassembled and pings are answered while receiving.
+
+use crate::network::socket::TcpStream;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::ffi::CString;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ffi::c_int;
+use core::time::Duration;
+use zephyr_sys::raw::websocket_request as ZWebSocketRequest;
+
+const CONTEXT: WebSocketWrapperContext = WebSocketWrapperContext {};
+
+/// The WebSocket functions return a negative error number on errors.
+fn result(ret: c_int) -> ZephyrResult<c_int> {
+    if ret < 0 {
+        Err(ZephyrError::from_errno_with_context(ret, &CONTEXT))
+    } else {
+        Ok(ret)
+    }
+}
+
+/// Timeout in milliseconds, `SYS_FOREVER_MS` for `None`.
+fn timeout_ms(timeout: Option<Duration>) -> i32 {
+    match timeout {
+        Some(timeout) => timeout.as_millis().min(i32::MAX as u128) as i32,
+        None => zephyr_sys::raw::SYS_FOREVER_MS,
+    }
+}
+
+/// Size of the buffer used for the handshake and the headers of received frames.
+const BUFFER_SIZE: usize = 512;
+
+/// Message received from or sent to the server.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong(Vec<u8>),
+    /// The server closed the connection.
+    Close,
+}
+
+/// Client side of a WebSocket connection, disconnected on drop.
+pub struct WebSocket {
+    fd: c_int,
+    // the connection keeps receiving through the buffer of the request
+    _buffer: Vec<u8>,
+}
+
+impl WebSocket {
+    /// Wrapper to the `websocket_connect` function, upgrades `stream` to a WebSocket connection
+    /// for `url` (e.g. `"/events"`) of `host`. Waits up to `timeout` or forever for `None`.
+    ///
+    /// The connection takes over `stream`, which is closed together with the connection.
+    pub fn connect(
+        stream: TcpStream,
+        host: &str,
+        url: &str,
+        timeout: Option<Duration>,
+    ) -> ZephyrResult<Self> {
+        let invalid = |_| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT);
+        let host = CString::new(host).map_err(invalid)?;
+        let url = CString::new(url).map_err(invalid)?;
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+
+        // zeroed as the optional headers and callbacks are unused
+        let mut request: ZWebSocketRequest = unsafe { core::mem::zeroed() };
+        request.host = host.as_ptr();
+        request.url = url.as_ptr();
+        request.tmp_buf = buffer.as_mut_ptr();
+        request.tmp_buf_len = buffer.len();
+
+        let fd = stream.into_raw_fd();
+        let ws = unsafe {
+            zephyr_sys::raw::websocket_connect(
+                fd,
+                &mut request,
+                timeout_ms(timeout),
+                core::ptr::null_mut(),
+            )
+        };
+        match result(ws) {
+            Ok(ws) => Ok(WebSocket {
+                fd: ws,
+                _buffer: buffer,
+            }),
+            Err(e) => {
+                // the connection does not own the socket if the handshake failed
+                unsafe { zephyr_sys::syscalls::any::zsock_close(fd) };
+                Err(e)
+            }
+        }
+    }
+
+    fn send_frame(&self, opcode: u32, payload: &[u8]) -> ZephyrResult<()> {
+        result(unsafe {
+            zephyr_sys::raw::websocket_send_msg(
+                self.fd,
+                payload.as_ptr(),
+                payload.len(),
+                opcode,
+                // frames of clients are always masked
+                true,
+                true,
+                zephyr_sys::raw::SYS_FOREVER_MS,
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Wrapper to the `websocket_send_msg` function, sends `message` in a single frame.
+    ///
+    /// [Message::Close] sends a close frame, the connection is closed when it is dropped.
+    pub fn send(&self, message: &Message) -> ZephyrResult<()> {
+        let (opcode, payload) = match message {
+            Message::Text(text) => (
+                zephyr_sys::raw::websocket_opcode_WEBSOCKET_OPCODE_DATA_TEXT,
+                text.as_bytes(),
+            ),
+            Message::Binary(data) => (
+                zephyr_sys::raw::websocket_opcode_WEBSOCKET_OPCODE_DATA_BINARY,
+                data.as_slice(),
+            ),
+            Message::Ping(data) => (
+                zephyr_sys::raw::websocket_opcode_WEBSOCKET_OPCODE_PING,
+                data.as_slice(),
+            ),
+            Message::Pong(data) => (
+                zephyr_sys::raw::websocket_opcode_WEBSOCKET_OPCODE_PONG,
+                data.as_slice(),
+            ),
+            Message::Close => (zephyr_sys::raw::websocket_opcode_WEBSOCKET_OPCODE_CLOSE, &[][..]),
+        };
+        self.send_frame(opcode, payload)
+    }
+
+    /// Receives the next message, waits up to `timeout` or forever for `None`.
+    ///
+    /// Pings are answered with a pong carrying the same payload and are not returned. Fails with
+    /// [ErrorNumber::TryAgain] if no complete message was received in time.
+    pub fn recv(&mut self, timeout: Option<Duration>) -> ZephyrResult<Message> {
+        let mut payload = Vec::new();
+        let mut flags: u32 = 0;
+        let mut chunk = [0u8; BUFFER_SIZE];
+
+        loop {
+            let mut message_type: u32 = 0;
+            let mut remaining: u64 = 0;
+            let count = result(unsafe {
+                zephyr_sys::raw::websocket_recv_msg(
+                    self.fd,
+                    chunk.as_mut_ptr(),
+                    chunk.len(),
+                    &mut message_type,
+                    &mut remaining,
+                    timeout_ms(timeout),
+                )
+            })?;
+            if count == 0 && message_type == 0 {
+                // the server closed the connection without a close frame
+                return Ok(Message::Close);
+            }
+            payload.extend_from_slice(&chunk[..count as usize]);
+            // continuation frames do not repeat the type of the message
+            if flags & !zephyr_sys::raw::WEBSOCKET_FLAG_FINAL == 0 {
+                flags |= message_type;
+            }
+            if remaining > 0 || message_type & zephyr_sys::raw::WEBSOCKET_FLAG_FINAL == 0 {
+                continue;
+            }
+
+            if flags & zephyr_sys::raw::WEBSOCKET_FLAG_PING != 0 {
+                self.send(&Message::Pong(core::mem::take(&mut payload)))?;
+                flags = 0;
+                continue;
+            }
+            let message = if flags & zephyr_sys::raw::WEBSOCKET_FLAG_TEXT != 0 {
+                Message::Text(String::from_utf8_lossy(&payload).into_owned())
+            } else if flags & zephyr_sys::raw::WEBSOCKET_FLAG_BINARY != 0 {
+                Message::Binary(payload)
+            } else if flags & zephyr_sys::raw::WEBSOCKET_FLAG_PONG != 0 {
+                Message::Pong(payload)
+            } else {
+                Message::Close
+            };
+            return Ok(message);
+        }
+    }
+
+    /// Sends a ping with `payload`, the pong is returned by [WebSocket::recv].
+    pub fn ping(&self, payload: &[u8]) -> ZephyrResult<()> {
+        self.send(&Message::Ping(payload.to_vec()))
+    }
+}
+
+impl Drop for WebSocket {
+    fn drop(&mut self) {
+        // also closes the underlying socket
+        unsafe { zephyr_sys::raw::websocket_disconnect(self.fd) };
+    }
+}
+
+#[derive(Debug)]
+struct WebSocketWrapperContext {}
+
+impl Context for WebSocketWrapperContext {
+    fn name(&self) -> &'static str {
+        "websocket wrapper"
+    }
+}
@@ -0,0 +1,489 @@
+//! Wrappers for the Wi-Fi management API of Zephyr (`CONFIG_WIFI`).
+//!
+//! The `NET_REQUEST_WIFI_*` requests of the network management API are sent to the interface of a
+//! Wi-Fi driver, their results are reported as `NET_EVENT_WIFI_*` events. Scan results are
+//! reported in the info of the events, which requires `CONFIG_NET_MGMT_EVENT_INFO`:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::network::interface::NetworkInterface;
+//!# use zephyr_rust_wrappers::network::wifi::{ConnectParams, Credentials, Wifi};
+//!# use zephyr_rust_wrappers::time::FOREVER;
+//! let wifi = Wifi::new(NetworkInterface::get_default().unwrap());
+//! for network in wifi.scan(FOREVER).unwrap() {
+//!     println!("{} {} dBm {:?}", network.ssid, network.rssi, network.security);
+//! }
+//! wifi.connect(&ConnectParams::new("home", Credentials::Psk("secret".into()))).unwrap();
+//! ```
+
+use super::events::{subscribe_raw, unsubscribe, SubscriptionId};
+use super::interface::{NetworkInterface, RequestHandler};
+use crate::time::Timeout;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ffi::{c_int, c_void};
+use core::mem::size_of;
+use core::time::Duration;
+use zephyr_sys::raw::{
+    k_sem, wifi_connect_req_params as ZConnectParams,
+    wifi_iface_status as ZIfaceStatus, wifi_scan_result as ZScanResult, wifi_status as ZStatus,
+};
+
+const CONTEXT: WifiWrapperContext = WifiWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Sends the request `code` handled by `handler` with `data` to `iface`.
+fn request<T>(
+    handler: RequestHandler,
+    code: u32,
+    iface: NetworkInterface,
+    data: Option<&mut T>,
+) -> ZephyrResult<()> {
+    result(iface.net_mgmt(handler, code, data))
+}
+
+/// Security of a network, the equivalent of `enum wifi_security_type`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Security {
+    Open,
+    /// WPA2-PSK.
+    Psk,
+    /// WPA2-PSK with SHA-256.
+    PskSha256,
+    /// WPA3-SAE.
+    Sae,
+    Wapi,
+    /// WPA2-Enterprise.
+    Eap,
+    Wep,
+    /// WPA-PSK.
+    WpaPsk,
+    Other(u32),
+}
+
+impl From<u32> for Security {
+    fn from(raw: u32) -> Self {
+        match raw {
+            zephyr_sys::raw::wifi_security_type_WIFI_SECURITY_TYPE_NONE => Security::Open,
+            zephyr_sys::raw::wifi_security_type_WIFI_SECURITY_TYPE_PSK => Security::Psk,
+            zephyr_sys::raw::wifi_security_type_WIFI_SECURITY_TYPE_PSK_SHA256 => {
+                Security::PskSha256
+            }
+            zephyr_sys::raw::wifi_security_type_WIFI_SECURITY_TYPE_SAE => Security::Sae,
+            zephyr_sys::raw::wifi_security_type_WIFI_SECURITY_TYPE_WAPI => Security::Wapi,
+            zephyr_sys::raw::wifi_security_type_WIFI_SECURITY_TYPE_EAP => Security::Eap,
+            zephyr_sys::raw::wifi_security_type_WIFI_SECURITY_TYPE_WEP => Security::Wep,
+            zephyr_sys::raw::wifi_security_type_WIFI_SECURITY_TYPE_WPA_PSK => Security::WpaPsk,
+            raw => Security::Other(raw),
+        }
+    }
+}
+
+/// Frequency band, the equivalent of `enum wifi_frequency_bands`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Band {
+    Band2_4GHz,
+    Band5GHz,
+    Band6GHz,
+    Other(u32),
+}
+
+impl Band {
+    fn to_raw(self) -> u32 {
+        match self {
+            Band::Band2_4GHz => zephyr_sys::raw::wifi_frequency_bands_WIFI_FREQ_BAND_2_4_GHZ,
+            Band::Band5GHz => zephyr_sys::raw::wifi_frequency_bands_WIFI_FREQ_BAND_5_GHZ,
+            Band::Band6GHz => zephyr_sys::raw::wifi_frequency_bands_WIFI_FREQ_BAND_6_GHZ,
+            Band::Other(raw) => raw,
+        }
+    }
+}
+
+impl From<u32> for Band {
+    fn from(raw: u32) -> Self {
+        match raw {
+            zephyr_sys::raw::wifi_frequency_bands_WIFI_FREQ_BAND_2_4_GHZ => Band::Band2_4GHz,
+            zephyr_sys::raw::wifi_frequency_bands_WIFI_FREQ_BAND_5_GHZ => Band::Band5GHz,
+            zephyr_sys::raw::wifi_frequency_bands_WIFI_FREQ_BAND_6_GHZ => Band::Band6GHz,
+            raw => Band::Other(raw),
+        }
+    }
+}
+
+/// Network found by [Wifi::scan].
+#[derive(Clone, Debug)]
+pub struct ScanResult {
+    pub ssid: String,
+    pub bssid: [u8; 6],
+    pub band: Band,
+    pub channel: u8,
+    pub security: Security,
+    /// Signal strength in dBm.
+    pub rssi: i8,
+}
+
+impl From<&ZScanResult> for ScanResult {
+    fn from(raw: &ZScanResult) -> Self {
+        let ssid = &raw.ssid[..(raw.ssid_length as usize).min(raw.ssid.len())];
+        ScanResult {
+            ssid: String::from_utf8_lossy(ssid).into_owned(),
+            bssid: raw.mac,
+            band: (raw.band as u32).into(),
+            channel: raw.channel as u8,
+            security: (raw.security as u32).into(),
+            rssi: raw.rssi,
+        }
+    }
+}
+
+/// Credentials of the network to connect to.
+#[derive(Clone, Debug)]
+pub enum Credentials {
+    Open,
+    /// WPA2-PSK with the passphrase.
+    Psk(String),
+    /// WPA3-SAE with the password.
+    Sae(String),
+    /// WPA2-Enterprise with PEAP/MSCHAPv2, requires
+    /// `CONFIG_WIFI_NM_WPA_SUPPLICANT_CRYPTO_ENTERPRISE`.
+    Enterprise { identity: String, password: String },
+}
+
+/// Parameters of [Wifi::connect].
+#[derive(Clone, Debug)]
+pub struct ConnectParams {
+    pub ssid: String,
+    pub credentials: Credentials,
+    /// Band of the network, any band if `None`.
+    pub band: Option<Band>,
+    /// Channel of the network, any channel if `None`.
+    pub channel: Option<u8>,
+    /// Timeout of the connection attempt, which is reported as event, forever if `None`.
+    pub timeout: Option<Duration>,
+}
+
+impl ConnectParams {
+    /// Parameters to connect to `ssid` on any band and channel.
+    pub fn new(ssid: &str, credentials: Credentials) -> Self {
+        ConnectParams {
+            ssid: ssid.to_string(),
+            credentials,
+            band: None,
+            channel: None,
+            timeout: None,
+        }
+    }
+}
+
+/// State of the interface, the equivalent of `enum wifi_iface_state`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum State {
+    Disconnected,
+    Disabled,
+    Inactive,
+    Scanning,
+    Authenticating,
+    Associating,
+    Associated,
+    Handshake,
+    /// Connected to the network.
+    Completed,
+    Other(u32),
+}
+
+impl From<u32> for State {
+    fn from(raw: u32) -> Self {
+        match raw {
+            zephyr_sys::raw::wifi_iface_state_WIFI_STATE_DISCONNECTED => State::Disconnected,
+            zephyr_sys::raw::wifi_iface_state_WIFI_STATE_INTERFACE_DISABLED => State::Disabled,
+            zephyr_sys::raw::wifi_iface_state_WIFI_STATE_INACTIVE => State::Inactive,
+            zephyr_sys::raw::wifi_iface_state_WIFI_STATE_SCANNING => State::Scanning,
+            zephyr_sys::raw::wifi_iface_state_WIFI_STATE_AUTHENTICATING => State::Authenticating,
+            zephyr_sys::raw::wifi_iface_state_WIFI_STATE_ASSOCIATING => State::Associating,
+            zephyr_sys::raw::wifi_iface_state_WIFI_STATE_ASSOCIATED => State::Associated,
+            zephyr_sys::raw::wifi_iface_state_WIFI_STATE_4WAY_HANDSHAKE
+            | zephyr_sys::raw::wifi_iface_state_WIFI_STATE_GROUP_HANDSHAKE => State::Handshake,
+            zephyr_sys::raw::wifi_iface_state_WIFI_STATE_COMPLETED => State::Completed,
+            raw => State::Other(raw),
+        }
+    }
+}
+
+/// Status of the interface returned by [Wifi::status].
+#[derive(Clone, Debug)]
+pub struct WifiStatus {
+    pub state: State,
+    /// Network of the interface, only valid while connected.
+    pub ssid: String,
+    pub bssid: [u8; 6],
+    pub band: Band,
+    pub channel: u32,
+    pub security: Security,
+    /// Signal strength in dBm.
+    pub rssi: i32,
+}
+
+impl WifiStatus {
+    /// Returns `true` if the interface is connected to a network.
+    pub fn is_connected(&self) -> bool {
+        self.state == State::Completed
+    }
+}
+
+impl From<&ZIfaceStatus> for WifiStatus {
+    fn from(raw: &ZIfaceStatus) -> Self {
+        let ssid = &raw.ssid[..(raw.ssid_len as usize).min(raw.ssid.len())];
+        WifiStatus {
+            state: (raw.state as u32).into(),
+            ssid: String::from_utf8_lossy(ssid).into_owned(),
+            bssid: raw.bssid,
+            band: (raw.band as u32).into(),
+            channel: raw.channel as u32,
+            security: (raw.security as u32).into(),
+            rssi: raw.rssi as i32,
+        }
+    }
+}
+
+/// Connection event of an interface.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum WifiEvent {
+    Connected,
+    /// The connection attempt failed with the status of the driver.
+    ConnectFailed(i32),
+    /// The connection was closed, with the reason code of the driver.
+    Disconnected(i32),
+}
+
+/// Status of `wifi_status` in the info of an event.
+fn event_status(info: Option<&[u8]>) -> i32 {
+    info.filter(|info| info.len() >= size_of::<ZStatus>())
+        // the info of the connection events is a struct wifi_status
+        .map_or(0, |info| unsafe { (*(info.as_ptr() as *const ZStatus)).status })
+}
+
+/// State of a [Wifi::scan], shared with the event handler.
+struct ScanState {
+    results: Vec<ScanResult>,
+    done: k_sem,
+}
+
+/// Pointer to the state of a scan, which outlives the subscription of the scan.
+struct ScanStatePtr(*mut ScanState);
+
+unsafe impl Send for ScanStatePtr {}
+
+/// Wi-Fi functions of a network interface.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Wifi {
+    iface: NetworkInterface,
+}
+
+impl Wifi {
+    /// Wi-Fi functions of `iface`, which MUST be the interface of a Wi-Fi driver for the
+    /// requests to succeed.
+    pub fn new(iface: NetworkInterface) -> Self {
+        Wifi { iface }
+    }
+
+    /// The network interface.
+    pub fn interface(&self) -> NetworkInterface {
+        self.iface
+    }
+
+    /// Sends `NET_REQUEST_WIFI_SCAN` and returns the networks found before the scan is done or
+    /// `timeout` expired.
+    pub fn scan(&self, timeout: Timeout) -> ZephyrResult<Vec<ScanResult>> {
+        let mut state = Box::new(ScanState {
+            results: Vec::new(),
+            // zeroed as the semaphore is initialized below
+            done: unsafe { core::mem::zeroed() },
+        });
+        result(unsafe { zephyr_sys::syscalls::any::k_sem_init(&mut state.done, 0, 1) })?;
+
+        let this = self.iface;
+        let pointer = ScanStatePtr(state.as_mut());
+        let mask =
+            zephyr_sys::raw::NET_EVENT_WIFI_SCAN_RESULT | zephyr_sys::raw::NET_EVENT_WIFI_SCAN_DONE;
+        let subscription = subscribe_raw(mask, move |event, iface, info| {
+            if iface != Some(this) {
+                return;
+            }
+            // the state is only dropped after the subscription was removed
+            let state = unsafe { &mut *pointer.0 };
+            match event {
+                zephyr_sys::raw::NET_EVENT_WIFI_SCAN_RESULT => {
+                    let info = info.filter(|info| info.len() >= size_of::<ZScanResult>());
+                    if let Some(info) = info {
+                        // the info of the event is a struct wifi_scan_result
+                        let raw = unsafe { &*(info.as_ptr() as *const ZScanResult) };
+                        state.results.push(raw.into());
+                    }
+                }
+                zephyr_sys::raw::NET_EVENT_WIFI_SCAN_DONE => unsafe {
+                    zephyr_sys::syscalls::any::k_sem_give(&mut state.done)
+                },
+                _ => {}
+            }
+        });
+
+        let requested = request::<()>(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_WIFI_SCAN,
+            zephyr_sys::raw::NET_REQUEST_WIFI_SCAN,
+            self.iface,
+            None,
+        );
+        if requested.is_ok() {
+            unsafe { zephyr_sys::syscalls::any::k_sem_take(&mut state.done, timeout) };
+        }
+        unsubscribe(subscription);
+        requested?;
+        Ok(core::mem::take(&mut state.results))
+    }
+
+    /// Sends `NET_REQUEST_WIFI_CONNECT`, the result of the connection attempt is reported as
+    /// [WifiEvent], see [Wifi::on_event] and [Wifi::wait_connected].
+    pub fn connect(&self, params: &ConnectParams) -> ZephyrResult<()> {
+        if params.ssid.len() > zephyr_sys::raw::WIFI_SSID_MAX_LEN as usize {
+            return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT));
+        }
+        // zeroed as unused credentials are empty
+        let mut raw: ZConnectParams = unsafe { core::mem::zeroed() };
+        raw.ssid = params.ssid.as_ptr();
+        raw.ssid_length = params.ssid.len() as _;
+        raw.band = params
+            .band
+            .map_or(zephyr_sys::raw::wifi_frequency_bands_WIFI_FREQ_BAND_UNKNOWN, Band::to_raw)
+            as _;
+        raw.channel = params.channel.map_or(zephyr_sys::raw::WIFI_CHANNEL_ANY, |c| c as u32) as _;
+        raw.mfp = zephyr_sys::raw::wifi_mfp_options_WIFI_MFP_OPTIONAL;
+        raw.timeout = params.timeout.map_or(zephyr_sys::raw::SYS_FOREVER_MS, |timeout| {
+            timeout.as_millis().min(i32::MAX as u128) as i32
+        });
+        raw.security = match &params.credentials {
+            Credentials::Open => zephyr_sys::raw::wifi_security_type_WIFI_SECURITY_TYPE_NONE,
+            Credentials::Psk(passphrase) => {
+                raw.psk = passphrase.as_ptr();
+                raw.psk_length = passphrase.len() as _;
+                zephyr_sys::raw::wifi_security_type_WIFI_SECURITY_TYPE_PSK
+            }
+            Credentials::Sae(password) => {
+                raw.sae_password = password.as_ptr();
+                raw.sae_password_length = password.len() as _;
+                zephyr_sys::raw::wifi_security_type_WIFI_SECURITY_TYPE_SAE
+            }
+            Credentials::Enterprise { identity, password } => {
+                raw.eap_identity = identity.as_ptr();
+                raw.eap_id_length = identity.len() as _;
+                raw.eap_password = password.as_ptr();
+                raw.eap_passwd_length = password.len() as _;
+                zephyr_sys::raw::wifi_security_type_WIFI_SECURITY_TYPE_EAP_PEAP_MSCHAPV2
+            }
+        };
+        request(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_WIFI_CONNECT,
+            zephyr_sys::raw::NET_REQUEST_WIFI_CONNECT,
+            self.iface,
+            Some(&mut raw),
+        )
+    }
+
+    /// Sends `NET_REQUEST_WIFI_DISCONNECT`, fails with [ErrorNumber::Already] if the
+    /// interface is not connected.
+    pub fn disconnect(&self) -> ZephyrResult<()> {
+        request::<()>(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_WIFI_DISCONNECT,
+            zephyr_sys::raw::NET_REQUEST_WIFI_DISCONNECT,
+            self.iface,
+            None,
+        )
+    }
+
+    /// Sends `NET_REQUEST_WIFI_IFACE_STATUS` and returns the status of the interface.
+    pub fn status(&self) -> ZephyrResult<WifiStatus> {
+        // zeroed as the status is written by the request
+        let mut raw: ZIfaceStatus = unsafe { core::mem::zeroed() };
+        request(
+            zephyr_sys::raw::net_mgmt_NET_REQUEST_WIFI_IFACE_STATUS,
+            zephyr_sys::raw::NET_REQUEST_WIFI_IFACE_STATUS,
+            self.iface,
+            Some(&mut raw),
+        )?;
+        Ok((&raw).into())
+    }
+
+    /// Calls `f` for the connection events of this interface, returns the id to unsubscribe with
+    /// [crate::network::events::unsubscribe].
+    pub fn on_event<F>(&self, mut f: F) -> SubscriptionId
+    where
+        F: FnMut(WifiEvent) + Send + 'static,
+    {
+        let this = self.iface;
+        let mask = zephyr_sys::raw::NET_EVENT_WIFI_CONNECT_RESULT
+            | zephyr_sys::raw::NET_EVENT_WIFI_DISCONNECT_RESULT;
+        subscribe_raw(mask, move |event, iface, info| {
+            if iface != Some(this) {
+                return;
+            }
+            let status = event_status(info);
+            match event {
+                zephyr_sys::raw::NET_EVENT_WIFI_CONNECT_RESULT if status == 0 => {
+                    f(WifiEvent::Connected)
+                }
+                zephyr_sys::raw::NET_EVENT_WIFI_CONNECT_RESULT => {
+                    f(WifiEvent::ConnectFailed(status))
+                }
+                zephyr_sys::raw::NET_EVENT_WIFI_DISCONNECT_RESULT => {
+                    f(WifiEvent::Disconnected(status))
+                }
+                _ => {}
+            }
+        })
+    }
+
+    /// Wrapper to the `net_mgmt_event_wait_on_iface` function, blocks until the result of a
+    /// connection attempt is reported or `timeout` expired.
+    ///
+    /// Fails with [ErrorNumber::Io] if the connection attempt failed.
+    pub fn wait_connected(&self, timeout: Timeout) -> ZephyrResult<()> {
+        let mut info: *const c_void = core::ptr::null();
+        let mut info_length: usize = 0;
+        result(unsafe {
+            zephyr_sys::raw::net_mgmt_event_wait_on_iface(
+                self.iface.as_raw(),
+                zephyr_sys::raw::NET_EVENT_WIFI_CONNECT_RESULT,
+                core::ptr::null_mut(),
+                &mut info,
+                &mut info_length,
+                timeout,
+            )
+        })?;
+        let info = if info.is_null() {
+            None
+        } else {
+            // the info stays valid until the next event
+            Some(unsafe { core::slice::from_raw_parts(info as *const u8, info_length) })
+        };
+        match event_status(info) {
+            0 => Ok(()),
+            _ => Err(ZephyrError::new_with_context(ErrorNumber::Io, &CONTEXT)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct WifiWrapperContext {}
+
+impl Context for WifiWrapperContext {
+    fn name(&self) -> &'static str {
+        "wifi wrapper"
+    }
+}
@@ -0,0 +1,111 @@
+//! Reboot and power off of the system.
+//!
+//! [reboot] and [power_off] first call the hooks registered with [register_hook] in the order of
+//! registration, e.g. to flush the logs or to persist state, then restart or power off the system:
+//!
+//! ```no_run
+//! use zephyr_rust_wrappers::power::{self, RebootType, Shutdown};
+//!
+//! fn persist(shutdown: Shutdown) {
+//!     // write state that survives the shutdown
+//! }
+//!
+//! power::register_hook(persist).expect("registering shutdown hook");
+//! power::reboot(RebootType::Warm);
+//! ```
+//!
+//! The hooks are called from the thread that shuts the system down, they MUST NOT block for long.
+
+use crate::sys::SpinLocked;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+
+const CONTEXT: PowerWrapperContext = PowerWrapperContext {};
+
+/// Maximum number of hooks registered with [register_hook].
+pub const MAX_HOOKS: usize = 8;
+
+/// The registered hooks, locked as not every target has compare and swap atomics.
+static HOOKS: SpinLocked<[Option<Hook>; MAX_HOOKS]> = SpinLocked::new([None; MAX_HOOKS]);
+
+/// Kind of a reboot.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RebootType {
+    /// Restarts the CPU only, `SYS_REBOOT_WARM`. Retained memory keeps its content.
+    Warm,
+    /// Resets the whole SoC, `SYS_REBOOT_COLD`.
+    Cold,
+}
+
+impl From<RebootType> for i32 {
+    fn from(other: RebootType) -> Self {
+        match other {
+            RebootType::Warm => zephyr_sys::raw::SYS_REBOOT_WARM as i32,
+            RebootType::Cold => zephyr_sys::raw::SYS_REBOOT_COLD as i32,
+        }
+    }
+}
+
+/// Shutdown passed to the hooks.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Shutdown {
+    Reboot(RebootType),
+    PowerOff,
+}
+
+/// Hook called before the system shuts down.
+pub type Hook = fn(Shutdown);
+
+/// Registers `hook` to be called by [reboot] and [power_off].
+///
+/// This function fails with [ErrorNumber::NoMemory] if [MAX_HOOKS] hooks are registered already.
+pub fn register_hook(hook: Hook) -> ZephyrResult<()> {
+    let registered = HOOKS.locked(|hooks| match hooks.iter_mut().find(|slot| slot.is_none()) {
+        Some(slot) => {
+            *slot = Some(hook);
+            true
+        }
+        None => false,
+    });
+
+    if registered {
+        Ok(())
+    } else {
+        Err(ZephyrError::new_with_context(
+            ErrorNumber::NoMemory,
+            &CONTEXT,
+        ))
+    }
+}
+
+fn run_hooks(shutdown: Shutdown) {
+    // the hooks are called without the lock held
+    for hook in HOOKS.get().iter().flatten() {
+        hook(shutdown);
+    }
+}
+
+/// Calls the hooks, then reboots the system. Wrapper to the `sys_reboot` function, requires
+/// `CONFIG_REBOOT`.
+pub fn reboot(reboot_type: RebootType) -> ! {
+    run_hooks(Shutdown::Reboot(reboot_type));
+    unsafe { zephyr_sys::raw::sys_reboot(reboot_type.into()) }
+}
+
+/// Calls the hooks, then powers the system off. Wrapper to the `sys_poweroff` function, requires
+/// `CONFIG_POWEROFF`.
+///
+/// The system only starts again from a wakeup source of the SoC, e.g. a GPIO configured as
+/// wakeup, or a reset.
+pub fn power_off() -> ! {
+    run_hooks(Shutdown::PowerOff);
+    unsafe { zephyr_sys::raw::sys_poweroff() }
+}
+
+#[derive(Debug)]
+struct PowerWrapperContext {}
+
+impl Context for PowerWrapperContext {
+    fn name(&self) -> &'static str {
+        "power wrapper"
+    }
+}
@@ -0,0 +1,319 @@
+//! Syscalls and high level wrappers for the Zephyr PWM API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API. Capturing is only available with the `pwm-capture` feature, which
+//! requires `CONFIG_PWM_CAPTURE` to be enabled.
+
+use crate::device::{class, TypedDevice};
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use core::time::Duration;
+pub use zephyr::device::Device;
+pub use zephyr_sys::raw::pwm_flags_t as PwmFlags;
+
+const CONTEXT: PwmWrapperContext = PwmWrapperContext {};
+
+const NSEC_PER_SEC: u64 = 1_000_000_000;
+
+/// Polarity of the PWM signal.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Polarity {
+    /// The pulse is the high part of the period.
+    Normal,
+    /// The pulse is the low part of the period.
+    Inverted,
+}
+
+impl From<Polarity> for PwmFlags {
+    fn from(other: Polarity) -> Self {
+        match other {
+            Polarity::Normal => zephyr_sys::raw::PWM_POLARITY_NORMAL as PwmFlags,
+            Polarity::Inverted => zephyr_sys::raw::PWM_POLARITY_INVERTED as PwmFlags,
+        }
+    }
+}
+
+/// Wrapper to the `pwm_set_cycles` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn set_cycles(
+    device: &Device,
+    channel: u32,
+    period: u32,
+    pulse: u32,
+    flags: PwmFlags,
+) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::pwm_set_cycles(
+        device as *const Device,
+        channel,
+        period,
+        pulse,
+        flags,
+    );
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `pwm_get_cycles_per_sec` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn get_cycles_per_sec(device: &Device, channel: u32) -> ZephyrResult<u64> {
+    let mut cycles = 0_u64;
+    let errno = zephyr_sys::syscalls::any::pwm_get_cycles_per_sec(
+        device as *const Device,
+        channel,
+        &mut cycles as *mut u64,
+    );
+
+    if errno == 0 {
+        Ok(cycles)
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Convert `nanoseconds` to cycles of a clock running at `cycles_per_sec`.
+///
+/// Fails with [ErrorNumber::NotSupported] if the result does not fit the 32 bit cycle counter, like
+/// `pwm_set` does.
+fn nsec_to_cycles(nanoseconds: u64, cycles_per_sec: u64) -> ZephyrResult<u32> {
+    let cycles = (nanoseconds as u128 * cycles_per_sec as u128) / NSEC_PER_SEC as u128;
+    if cycles > u32::MAX as u128 {
+        Err(ZephyrError::new_with_context(ErrorNumber::NotSupported, &CONTEXT))
+    } else {
+        Ok(cycles as u32)
+    }
+}
+
+/// Equivalent of the `pwm_set` API function, taking the period and pulse in nanoseconds.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+pub unsafe fn set(
+    device: &Device,
+    channel: u32,
+    period: u64,
+    pulse: u64,
+    flags: PwmFlags,
+) -> ZephyrResult<()> {
+    let cycles_per_sec = get_cycles_per_sec(device, channel)?;
+    set_cycles(
+        device,
+        channel,
+        nsec_to_cycles(period, cycles_per_sec)?,
+        nsec_to_cycles(pulse, cycles_per_sec)?,
+        flags,
+    )
+}
+
+/// High level wrapper for a single channel of a PWM controller.
+///
+/// The pin keeps track of the last period and pulse, so the frequency and the duty cycle can be
+/// changed independently of each other.
+pub struct PwmPin {
+    device: &'static Device,
+    channel: u32,
+    flags: PwmFlags,
+    period: u64,
+    pulse: u64,
+}
+
+impl PwmPin {
+    /// Creates a new [PwmPin] on the current interface. The output stays unchanged until a period is
+    /// set.
+    ///
+    /// `device` MUST be a PWM device. If `device` is not a PWM device the behaviour when calling any
+    /// method is undefined!
+    pub unsafe fn new(device: &'static Device, channel: u32, polarity: Polarity) -> Self {
+        PwmPin {
+            device,
+            channel,
+            flags: polarity.into(),
+            period: 0,
+            pulse: 0,
+        }
+    }
+
+    /// Creates a new [PwmPin] from a device checked to be a PWM device.
+    pub fn from_device(device: TypedDevice<class::Pwm>, channel: u32, polarity: Polarity) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { PwmPin::new(device.device(), channel, polarity) }
+    }
+
+    /// Clock frequency the period and pulse are counted in.
+    pub fn cycles_per_sec(&self) -> ZephyrResult<u64> {
+        // device is required to be a PWM device in constructor
+        unsafe { get_cycles_per_sec(self.device, self.channel) }
+    }
+
+    /// [PwmPin::cycles_per_sec] for conversions of cycles, failing with
+    /// [ErrorNumber::InvalidArgument] if the driver reports a clock of 0 Hertz.
+    fn nonzero_cycles_per_sec(&self) -> ZephyrResult<u64> {
+        match self.cycles_per_sec()? {
+            0 => Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &CONTEXT,
+            )),
+            cycles_per_sec => Ok(cycles_per_sec),
+        }
+    }
+
+    /// Set period and pulse in cycles of the PWM clock, see [PwmPin::cycles_per_sec].
+    ///
+    /// This method fails with [ErrorNumber::InvalidArgument] if the clock frequency is 0.
+    pub fn set_cycles(&mut self, period: u32, pulse: u32) -> ZephyrResult<()> {
+        let cycles_per_sec = self.nonzero_cycles_per_sec()?;
+        // device is required to be a PWM device in constructor
+        unsafe { set_cycles(self.device, self.channel, period, pulse, self.flags) }?;
+        self.period = period as u64 * NSEC_PER_SEC / cycles_per_sec;
+        self.pulse = pulse as u64 * NSEC_PER_SEC / cycles_per_sec;
+        Ok(())
+    }
+
+    /// Set period and pulse of the signal.
+    pub fn set(&mut self, period: Duration, pulse: Duration) -> ZephyrResult<()> {
+        self.set_nsec(period.as_nanos() as u64, pulse.as_nanos() as u64)
+    }
+
+    fn set_nsec(&mut self, period: u64, pulse: u64) -> ZephyrResult<()> {
+        // device is required to be a PWM device in constructor
+        unsafe { set(self.device, self.channel, period, pulse, self.flags) }?;
+        self.period = period;
+        self.pulse = pulse;
+        Ok(())
+    }
+
+    /// Set the frequency of the signal in Hertz, keeping the current duty cycle.
+    ///
+    /// A frequency of `0` or above 1 GHz (a period below 1 ns) fails with
+    /// [ErrorNumber::InvalidArgument].
+    pub fn set_frequency(&mut self, hertz: u32) -> ZephyrResult<()> {
+        if hertz == 0 || hertz as u64 > NSEC_PER_SEC {
+            return Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &CONTEXT,
+            ));
+        }
+
+        let period = NSEC_PER_SEC / hertz as u64;
+        let pulse = match self.period {
+            0 => 0,
+            old_period => (self.pulse as u128 * period as u128 / old_period as u128) as u64,
+        };
+        self.set_nsec(period, pulse)
+    }
+
+    /// Set the duty cycle as a fraction between `0.0` and `1.0` of the current period.
+    ///
+    /// Values outside of that range are clamped.
+    pub fn set_duty_cycle(&mut self, duty_cycle: f32) -> ZephyrResult<()> {
+        let duty_cycle = duty_cycle.max(0.0).min(1.0) as f64;
+        let pulse = (self.period as f64 * duty_cycle) as u64;
+        self.set_nsec(self.period, pulse)
+    }
+
+    /// Current period of the signal.
+    pub fn period(&self) -> Duration {
+        Duration::from_nanos(self.period)
+    }
+
+    /// Current pulse width of the signal.
+    pub fn pulse(&self) -> Duration {
+        Duration::from_nanos(self.pulse)
+    }
+
+    /// Current duty cycle of the signal as fraction of the period.
+    pub fn duty_cycle(&self) -> f32 {
+        match self.period {
+            0 => 0.0,
+            period => (self.pulse as f64 / period as f64) as f32,
+        }
+    }
+
+    /// Stop the output by setting a pulse width of zero.
+    pub fn off(&mut self) -> ZephyrResult<()> {
+        self.set_nsec(self.period, 0)
+    }
+}
+
+/// A captured PWM signal.
+#[cfg(feature = "pwm-capture")]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Capture {
+    pub period: Duration,
+    pub pulse: Duration,
+}
+
+/// Wrapper to the `pwm_capture_cycles` syscall, capturing a single period and pulse.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// Returns the period and pulse in cycles.
+#[cfg(feature = "pwm-capture")]
+pub unsafe fn capture_cycles(
+    device: &Device,
+    channel: u32,
+    flags: PwmFlags,
+    timeout: crate::time::Timeout,
+) -> ZephyrResult<(u32, u32)> {
+    let mut period = 0_u32;
+    let mut pulse = 0_u32;
+    let errno = zephyr_sys::syscalls::any::pwm_capture_cycles(
+        device as *const Device,
+        channel,
+        flags,
+        &mut period as *mut u32,
+        &mut pulse as *mut u32,
+        timeout,
+    );
+
+    if errno == 0 {
+        Ok((period, pulse))
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+#[cfg(feature = "pwm-capture")]
+impl PwmPin {
+    /// Capture a single period and pulse of the signal on the channel, waiting at most `timeout`
+    /// or forever if it is `None`.
+    ///
+    /// The polarity of the pin determines whether the high or the low part is captured as pulse.
+    pub fn capture(&mut self, timeout: Option<Duration>) -> ZephyrResult<Capture> {
+        let flags = self.flags
+            | (zephyr_sys::raw::PWM_CAPTURE_TYPE_BOTH | zephyr_sys::raw::PWM_CAPTURE_MODE_SINGLE)
+                as PwmFlags;
+        // device is required to be a PWM device in constructor
+        let (period, pulse) = unsafe {
+            capture_cycles(
+                self.device,
+                self.channel,
+                flags,
+                crate::time::timeout_or_forever(timeout),
+            )
+        }?;
+
+        let cycles_per_sec = self.nonzero_cycles_per_sec()?;
+        let to_duration =
+            |cycles: u32| Duration::from_nanos(cycles as u64 * NSEC_PER_SEC / cycles_per_sec);
+        Ok(Capture {
+            period: to_duration(period),
+            pulse: to_duration(pulse),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct PwmWrapperContext {}
+
+impl Context for PwmWrapperContext {
+    fn name(&self) -> &'static str {
+        "pwm wrapper"
+    }
+}
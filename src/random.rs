@@ -0,0 +1,55 @@
+//! Random numbers of the kernel, `sys_rand_get` and the cryptographically secure
+//! `sys_csrand_get` (`CONFIG_CSPRNG_ENABLED`).
+//!
+//! [rand_get] is fast but not suited for keys or nonces, [csrand_get] is. With the `getrandom`
+//! feature, [csrand_get] is the custom backend of the `getrandom` crate, so crates relying on it
+//! like `uuid` (`Uuid::new_v4`) and `rand` (`OsRng`, `thread_rng`) work on Zephyr. The backend is
+//! only used for targets without an operating system known to `getrandom`, e.g. the bare metal
+//! targets of the boards, not for `native_sim`.
+
+pub use crate::entropy::csrand_get;
+
+/// Wrapper to the `sys_rand_get` syscall, filling `buffer` with random bytes that are not
+/// cryptographically secure.
+pub fn rand_get(buffer: &mut [u8]) {
+    unsafe {
+        zephyr_sys::syscalls::any::sys_rand_get(
+            buffer.as_mut_ptr() as *mut core::ffi::c_void,
+            buffer.len(),
+        )
+    };
+}
+
+/// Random `u32` that is not cryptographically secure, the equivalent of `sys_rand32_get`.
+pub fn rand_u32() -> u32 {
+    let mut bytes = [0_u8; 4];
+    rand_get(&mut bytes);
+    u32::from_ne_bytes(bytes)
+}
+
+/// Random `u64` that is not cryptographically secure, the equivalent of `sys_rand64_get`.
+pub fn rand_u64() -> u64 {
+    let mut bytes = [0_u8; 8];
+    rand_get(&mut bytes);
+    u64::from_ne_bytes(bytes)
+}
+
+#[cfg(feature = "getrandom")]
+mod backend {
+    use super::csrand_get;
+    use core::num::NonZeroU32;
+    use getrandom::Error;
+
+    /// Fills `dest` with [csrand_get], errors are custom error codes of [getrandom] holding the
+    /// error number.
+    fn zephyr_getrandom(dest: &mut [u8]) -> Result<(), Error> {
+        csrand_get(dest).map_err(|error| {
+            let code = Error::CUSTOM_START + i32::from(error.number()) as u32;
+            NonZeroU32::new(code)
+                .expect("custom error codes are not zero")
+                .into()
+        })
+    }
+
+    getrandom::register_custom_getrandom!(zephyr_getrandom);
+}
@@ -0,0 +1,253 @@
+//! Wrappers for the Zephyr regulator API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! Regulators are reference counted by Zephyr: a rail stays powered until every user that enabled
+//! it disabled it again. A [RegulatorGuard] keeps a rail powered while it is alive, which makes
+//! switching off the supply of an external sensor between measurements hard to forget.
+
+use crate::device::{class, TypedDevice};
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::vec::Vec;
+use core::ffi::c_int;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::regulator_driver_api as RegulatorDriverApi;
+
+const CONTEXT: RegulatorWrapperContext = RegulatorWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+fn not_implemented() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::NotImplemented, &CONTEXT)
+}
+
+/// Wrapper to the `regulator_enable` function.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the function and wraps the error number in a safe error type.
+pub unsafe fn enable(device: &Device) -> ZephyrResult<()> {
+    result(zephyr_sys::raw::regulator_enable(device as *const Device))
+}
+
+/// Wrapper to the `regulator_disable` function.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the function and wraps the error number in a safe error type.
+pub unsafe fn disable(device: &Device) -> ZephyrResult<()> {
+    result(zephyr_sys::raw::regulator_disable(device as *const Device))
+}
+
+/// Wrapper to the `regulator_is_enabled` function.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+pub unsafe fn is_enabled(device: &Device) -> bool {
+    zephyr_sys::raw::regulator_is_enabled(device as *const Device)
+}
+
+/// Wrapper to the `regulator_set_voltage` function, with the voltages in microvolts.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the function and wraps the error number in a safe error type.
+pub unsafe fn set_voltage(device: &Device, min_uv: i32, max_uv: i32) -> ZephyrResult<()> {
+    result(zephyr_sys::raw::regulator_set_voltage(
+        device as *const Device,
+        min_uv,
+        max_uv,
+    ))
+}
+
+/// Wrapper to the `regulator_set_current_limit` function, with the currents in microamperes.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the function and wraps the error number in a safe error type.
+pub unsafe fn set_current_limit(device: &Device, min_ua: i32, max_ua: i32) -> ZephyrResult<()> {
+    result(zephyr_sys::raw::regulator_set_current_limit(
+        device as *const Device,
+        min_ua,
+        max_ua,
+    ))
+}
+
+/// Wrapper to the `regulator_set_mode` function.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the function and wraps the error number in a safe error type.
+pub unsafe fn set_mode(device: &Device, mode: RegulatorMode) -> ZephyrResult<()> {
+    result(zephyr_sys::raw::regulator_set_mode(
+        device as *const Device,
+        mode.0,
+    ))
+}
+
+/// Operating mode of a regulator.
+///
+/// The meaning of the modes is specific to the regulator, the values are the ones of the
+/// `regulator-allowed-modes` devicetree property, e.g. the `NPM1300_BUCK_MODE_*` constants.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct RegulatorMode(pub u8);
+
+/// High level wrapper for a regulator.
+pub struct Regulator {
+    device: &'static Device,
+}
+
+impl Regulator {
+    /// Creates a new [Regulator].
+    ///
+    /// `device` MUST be a regulator device. If `device` is not a regulator device the behaviour
+    /// when calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Regulator { device }
+    }
+
+    /// Creates a new [Regulator] from a device checked to be a regulator device.
+    pub fn from_device(device: TypedDevice<class::Regulator>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Regulator::new(device.device()) }
+    }
+
+    fn api(&self) -> Option<&RegulatorDriverApi> {
+        // device is required to be a regulator device in constructor
+        unsafe { (self.device.api as *const RegulatorDriverApi).as_ref() }
+    }
+
+    /// Enable the regulator, it stays enabled until [Regulator::disable] is called as often as
+    /// it was enabled.
+    pub fn enable(&self) -> ZephyrResult<()> {
+        // device is required to be a regulator device in constructor
+        unsafe { enable(self.device) }
+    }
+
+    /// Release one reference of the regulator, it is turned off when no references are left.
+    pub fn disable(&self) -> ZephyrResult<()> {
+        // device is required to be a regulator device in constructor
+        unsafe { disable(self.device) }
+    }
+
+    /// Enable the regulator until the returned guard is dropped.
+    pub fn enable_guard(&self) -> ZephyrResult<RegulatorGuard<'_>> {
+        self.enable()?;
+        Ok(RegulatorGuard { regulator: self })
+    }
+
+    /// Returns `true` if the regulator is enabled.
+    pub fn is_enabled(&self) -> bool {
+        // device is required to be a regulator device in constructor
+        unsafe { is_enabled(self.device) }
+    }
+
+    /// Set the output voltage to a value between `min_uv` and `max_uv` microvolts.
+    pub fn set_voltage(&self, min_uv: i32, max_uv: i32) -> ZephyrResult<()> {
+        // device is required to be a regulator device in constructor
+        unsafe { set_voltage(self.device, min_uv, max_uv) }
+    }
+
+    /// Output voltage in microvolts. This is the equivalent of `regulator_get_voltage`.
+    pub fn voltage(&self) -> ZephyrResult<i32> {
+        let get_voltage = self
+            .api()
+            .and_then(|api| api.get_voltage)
+            .ok_or_else(not_implemented)?;
+        let mut voltage = 0;
+        result(unsafe { (get_voltage)(self.device as *const Device, &mut voltage as *mut i32) })?;
+        Ok(voltage)
+    }
+
+    /// Voltages in microvolts the regulator can output. This is the equivalent of calling
+    /// `regulator_list_voltage` for every index up to `regulator_count_voltages`.
+    pub fn supported_voltages(&self) -> Vec<i32> {
+        let api = self.api();
+        let (count, list) = match (
+            api.and_then(|api| api.count_voltages),
+            api.and_then(|api| api.list_voltage),
+        ) {
+            (Some(count), Some(list)) => (count, list),
+            _ => return Vec::new(),
+        };
+
+        let count = unsafe { (count)(self.device as *const Device) };
+        (0..count)
+            .filter_map(|index| {
+                let mut voltage = 0;
+                let errno =
+                    unsafe { (list)(self.device as *const Device, index, &mut voltage as *mut _) };
+                if errno == 0 {
+                    Some(voltage)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Set the current limit to a value between `min_ua` and `max_ua` microamperes.
+    pub fn set_current_limit(&self, min_ua: i32, max_ua: i32) -> ZephyrResult<()> {
+        // device is required to be a regulator device in constructor
+        unsafe { set_current_limit(self.device, min_ua, max_ua) }
+    }
+
+    /// Current limit in microamperes. This is the equivalent of `regulator_get_current_limit`.
+    pub fn current_limit(&self) -> ZephyrResult<i32> {
+        let get_current_limit = self
+            .api()
+            .and_then(|api| api.get_current_limit)
+            .ok_or_else(not_implemented)?;
+        let mut current = 0;
+        result(unsafe {
+            (get_current_limit)(self.device as *const Device, &mut current as *mut i32)
+        })?;
+        Ok(current)
+    }
+
+    /// Select the operating mode of the regulator.
+    pub fn set_mode(&self, mode: RegulatorMode) -> ZephyrResult<()> {
+        // device is required to be a regulator device in constructor
+        unsafe { set_mode(self.device, mode) }
+    }
+
+    /// Current operating mode. This is the equivalent of `regulator_get_mode`.
+    pub fn mode(&self) -> ZephyrResult<RegulatorMode> {
+        let get_mode = self
+            .api()
+            .and_then(|api| api.get_mode)
+            .ok_or_else(not_implemented)?;
+        let mut mode = 0;
+        result(unsafe { (get_mode)(self.device as *const Device, &mut mode as *mut _) })?;
+        Ok(RegulatorMode(mode))
+    }
+}
+
+/// Keeps a [Regulator] enabled while it is alive, see [Regulator::enable_guard].
+pub struct RegulatorGuard<'a> {
+    regulator: &'a Regulator,
+}
+
+impl<'a> RegulatorGuard<'a> {
+    /// The guarded regulator.
+    pub fn regulator(&self) -> &Regulator {
+        self.regulator
+    }
+}
+
+impl<'a> Drop for RegulatorGuard<'a> {
+    fn drop(&mut self) {
+        // the reference taken in `enable_guard` is released, errors cannot be reported here
+        let _ = self.regulator.disable();
+    }
+}
+
+#[derive(Debug)]
+struct RegulatorWrapperContext {}
+
+impl Context for RegulatorWrapperContext {
+    fn name(&self) -> &'static str {
+        "regulator wrapper"
+    }
+}
@@ -0,0 +1,505 @@
+//! Syscalls and high level wrappers for the Zephyr RTC API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API. Alarms require `CONFIG_RTC_ALARM` and the `rtc-alarm` feature.
+//!
+//! The calendar time is represented by [DateTime], which converts to the types of the `chrono` and
+//! `time` crates if the features of the same name are enabled.
+
+use crate::device::{class, TypedDevice};
+#[cfg(feature = "rtc-alarm")]
+use crate::handlers::Handlers;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+#[cfg(feature = "rtc-alarm")]
+use alloc::boxed::Box;
+use core::convert::TryFrom;
+use core::fmt::{Display, Formatter};
+use core::ops::BitOr;
+use core::time::Duration;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::rtc_time as ZRtcTime;
+
+const CONTEXT: RtcWrapperContext = RtcWrapperContext {};
+
+fn result(errno: core::ffi::c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year } as i64;
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Date in the proleptic Gregorian calendar of the day `days` after 1970-01-01.
+fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let days = days + 719_468;
+    let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u8;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u8;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year as i32, month, day)
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Civil date and time without a time zone.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct DateTime {
+    pub year: i32,
+    /// Month between 1 and 12.
+    pub month: u8,
+    /// Day of the month starting at 1.
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+}
+
+impl DateTime {
+    /// Creates a date time, fails with [ErrorNumber::InvalidArgument] if a field is out of range.
+    pub fn new(
+        year: i32,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    ) -> ZephyrResult<Self> {
+        let date_time = DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            nanosecond: 0,
+        };
+
+        if date_time.is_valid() {
+            Ok(date_time)
+        } else {
+            Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &CONTEXT,
+            ))
+        }
+    }
+
+    /// Returns `true` if all fields are in range.
+    pub fn is_valid(&self) -> bool {
+        (1..=12).contains(&self.month)
+            && self.day >= 1
+            && self.day <= days_in_month(self.year, self.month)
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60
+            && self.nanosecond < 1_000_000_000
+    }
+
+    /// Date time of the `seconds` since the Unix epoch.
+    pub fn from_unix_timestamp(seconds: i64) -> Self {
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let second_of_day = seconds.rem_euclid(86_400);
+        DateTime {
+            year,
+            month,
+            day,
+            hour: (second_of_day / 3600) as u8,
+            minute: (second_of_day / 60 % 60) as u8,
+            second: (second_of_day % 60) as u8,
+            nanosecond: 0,
+        }
+    }
+
+    /// Seconds since the Unix epoch.
+    pub fn unix_timestamp(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) * 86_400
+            + self.hour as i64 * 3600
+            + self.minute as i64 * 60
+            + self.second as i64
+    }
+
+    /// Day of the week, where `0` is Sunday.
+    pub fn weekday(&self) -> u8 {
+        // 1970-01-01 was a Thursday
+        (days_from_civil(self.year, self.month, self.day) + 4).rem_euclid(7) as u8
+    }
+
+    /// Day of the year, where `0` is the first of January.
+    pub fn yearday(&self) -> u16 {
+        (days_from_civil(self.year, self.month, self.day) - days_from_civil(self.year, 1, 1)) as u16
+    }
+}
+
+impl Display for DateTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+impl From<&DateTime> for ZRtcTime {
+    fn from(other: &DateTime) -> Self {
+        ZRtcTime {
+            tm_sec: other.second as _,
+            tm_min: other.minute as _,
+            tm_hour: other.hour as _,
+            tm_mday: other.day as _,
+            tm_mon: other.month as i32 - 1,
+            tm_year: other.year - 1900,
+            tm_wday: other.weekday() as _,
+            tm_yday: other.yearday() as _,
+            tm_isdst: -1,
+            tm_nsec: other.nanosecond as _,
+        }
+    }
+}
+
+impl TryFrom<&ZRtcTime> for DateTime {
+    type Error = ZephyrError;
+
+    fn try_from(other: &ZRtcTime) -> Result<Self, Self::Error> {
+        let date_time = DateTime {
+            year: other.tm_year + 1900,
+            month: (other.tm_mon + 1) as u8,
+            day: other.tm_mday as u8,
+            hour: other.tm_hour as u8,
+            minute: other.tm_min as u8,
+            second: other.tm_sec as u8,
+            nanosecond: other.tm_nsec as u32,
+        };
+
+        if date_time.is_valid() {
+            Ok(date_time)
+        } else {
+            Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &CONTEXT,
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<DateTime> for chrono::NaiveDateTime {
+    fn from(other: DateTime) -> Self {
+        use chrono::{NaiveDate, NaiveTime};
+        // a valid DateTime is always a valid chrono date and time
+        NaiveDate::from_ymd_opt(other.year, other.month as u32, other.day as u32)
+            .and_then(|date| {
+                NaiveTime::from_hms_nano_opt(
+                    other.hour as u32,
+                    other.minute as u32,
+                    other.second as u32,
+                    other.nanosecond,
+                )
+                .map(|time| date.and_time(time))
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDateTime> for DateTime {
+    fn from(other: chrono::NaiveDateTime) -> Self {
+        use chrono::{Datelike, Timelike};
+        DateTime {
+            year: other.year(),
+            month: other.month() as u8,
+            day: other.day() as u8,
+            hour: other.hour() as u8,
+            minute: other.minute() as u8,
+            second: other.second() as u8,
+            // leap seconds are represented as nanoseconds above one second by chrono
+            nanosecond: other.nanosecond().min(999_999_999),
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<DateTime> for ::time::PrimitiveDateTime {
+    type Error = ::time::error::ComponentRange;
+
+    fn try_from(other: DateTime) -> Result<Self, Self::Error> {
+        let month = ::time::Month::try_from(other.month)?;
+        let date = ::time::Date::from_calendar_date(other.year, month, other.day)?;
+        let time = ::time::Time::from_hms_nano(
+            other.hour,
+            other.minute,
+            other.second,
+            other.nanosecond,
+        )?;
+        Ok(::time::PrimitiveDateTime::new(date, time))
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<::time::PrimitiveDateTime> for DateTime {
+    fn from(other: ::time::PrimitiveDateTime) -> Self {
+        DateTime {
+            year: other.year(),
+            month: other.month() as u8,
+            day: other.day(),
+            hour: other.hour(),
+            minute: other.minute(),
+            second: other.second(),
+            nanosecond: other.nanosecond(),
+        }
+    }
+}
+
+/// Wrapper to the `rtc_set_time` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn set_time(device: &Device, time: &ZRtcTime) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::rtc_set_time(
+        device as *const Device,
+        time as *const ZRtcTime,
+    ))
+}
+
+/// Wrapper to the `rtc_get_time` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn get_time(device: &Device) -> ZephyrResult<ZRtcTime> {
+    let mut time: ZRtcTime = core::mem::zeroed();
+    result(zephyr_sys::syscalls::any::rtc_get_time(
+        device as *const Device,
+        &mut time as *mut ZRtcTime,
+    ))?;
+    Ok(time)
+}
+
+/// High level wrapper for a real time clock.
+pub struct Rtc {
+    device: &'static Device,
+}
+
+impl Rtc {
+    /// Creates a new [Rtc] on the current interface.
+    ///
+    /// `device` MUST be a RTC device. If `device` is not a RTC device the behaviour when calling
+    /// any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Rtc { device }
+    }
+
+    /// Creates a new [Rtc] from a device checked to be a RTC device.
+    pub fn from_device(device: TypedDevice<class::Rtc>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Rtc::new(device.device()) }
+    }
+
+    /// Set the calendar time of the clock.
+    pub fn set_time(&mut self, time: &DateTime) -> ZephyrResult<()> {
+        // device is required to be a RTC device in constructor
+        unsafe { set_time(self.device, &time.into()) }
+    }
+
+    /// Sets the clock to the wall clock of [crate::time::wall_clock_since_epoch]. Fails with
+    /// [ErrorNumber::NoData] if the wall clock has not been set.
+    pub fn set_time_from_wall_clock(&mut self) -> ZephyrResult<()> {
+        let since_epoch = crate::time::wall_clock_since_epoch()
+            .ok_or_else(|| ZephyrError::new_with_context(ErrorNumber::NoData, &CONTEXT))?;
+        let mut time = DateTime::from_unix_timestamp(since_epoch.as_secs() as i64);
+        time.nanosecond = since_epoch.subsec_nanos();
+        self.set_time(&time)
+    }
+
+    /// Sets the wall clock of [crate::time::set_wall_clock_since_epoch] to the time of the clock,
+    /// e.g. at boot before the network is available.
+    pub fn update_wall_clock(&self) -> ZephyrResult<()> {
+        let time = self.time()?;
+        let since_epoch = Duration::new(time.unix_timestamp().max(0) as u64, time.nanosecond);
+        crate::time::set_wall_clock_since_epoch(since_epoch);
+        Ok(())
+    }
+
+    /// Current calendar time of the clock. Fails with [ErrorNumber::NoData] if the time
+    /// has not been set since the clock lost power.
+    pub fn time(&self) -> ZephyrResult<DateTime> {
+        // device is required to be a RTC device in constructor
+        let time = unsafe { get_time(self.device) }?;
+        DateTime::try_from(&time)
+    }
+}
+
+/// Fields of a [DateTime] that are compared to trigger an alarm.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct AlarmFields(pub u16);
+
+impl AlarmFields {
+    pub const SECOND: AlarmFields = AlarmFields(zephyr_sys::raw::RTC_ALARM_TIME_MASK_SECOND as u16);
+    pub const MINUTE: AlarmFields = AlarmFields(zephyr_sys::raw::RTC_ALARM_TIME_MASK_MINUTE as u16);
+    pub const HOUR: AlarmFields = AlarmFields(zephyr_sys::raw::RTC_ALARM_TIME_MASK_HOUR as u16);
+    pub const DAY: AlarmFields = AlarmFields(zephyr_sys::raw::RTC_ALARM_TIME_MASK_MONTHDAY as u16);
+    pub const MONTH: AlarmFields = AlarmFields(zephyr_sys::raw::RTC_ALARM_TIME_MASK_MONTH as u16);
+    pub const YEAR: AlarmFields = AlarmFields(zephyr_sys::raw::RTC_ALARM_TIME_MASK_YEAR as u16);
+    pub const WEEKDAY: AlarmFields =
+        AlarmFields(zephyr_sys::raw::RTC_ALARM_TIME_MASK_WEEKDAY as u16);
+
+    /// Returns `true` if all fields of `other` are contained.
+    pub fn contains(&self, other: AlarmFields) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for AlarmFields {
+    type Output = AlarmFields;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        AlarmFields(self.0 | rhs.0)
+    }
+}
+
+#[cfg(feature = "rtc-alarm")]
+impl Rtc {
+    /// Fields the alarm with `id` can compare.
+    pub fn alarm_supported_fields(&self, id: u16) -> ZephyrResult<AlarmFields> {
+        let mut mask = 0_u16;
+        // device is required to be a RTC device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::rtc_alarm_get_supported_fields(
+                self.device as *const Device,
+                id,
+                &mut mask as *mut u16,
+            )
+        })?;
+        Ok(AlarmFields(mask))
+    }
+
+    /// Set the alarm with `id` to trigger when the `fields` of the clock match `time`.
+    ///
+    /// Fields that are not compared are ignored, so `time` may contain any valid value there.
+    pub fn set_alarm(&mut self, id: u16, fields: AlarmFields, time: &DateTime) -> ZephyrResult<()> {
+        let time: ZRtcTime = time.into();
+        // device is required to be a RTC device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::rtc_alarm_set_time(
+                self.device as *const Device,
+                id,
+                fields.0,
+                &time as *const ZRtcTime,
+            )
+        })
+    }
+
+    /// Disable the alarm with `id`.
+    pub fn clear_alarm(&mut self, id: u16) -> ZephyrResult<()> {
+        // device is required to be a RTC device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::rtc_alarm_set_time(
+                self.device as *const Device,
+                id,
+                0,
+                core::ptr::null(),
+            )
+        })
+    }
+
+    /// Returns `true` if the alarm with `id` triggered since the last call, clearing the flag.
+    pub fn is_alarm_pending(&mut self, id: u16) -> ZephyrResult<bool> {
+        // device is required to be a RTC device in constructor
+        let pending = unsafe {
+            zephyr_sys::syscalls::any::rtc_alarm_is_pending(self.device as *const Device, id)
+        };
+
+        if pending >= 0 {
+            Ok(pending == 1)
+        } else {
+            Err(ZephyrError::from_errno_with_context(pending, &CONTEXT))
+        }
+    }
+
+    /// Call `f` from the interrupt of the clock every time the alarm with `id` triggers.
+    pub fn set_alarm_callback<F>(&mut self, id: u16, f: F) -> ZephyrResult<()>
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let key = AlarmKey {
+            device: self.device as *const Device as usize,
+            id,
+        };
+        let pushed = ALARM_HANDLERS.push(key, Box::new(f));
+        // device is required to be a RTC device in constructor
+        let set = result(unsafe {
+            zephyr_sys::syscalls::any::rtc_alarm_set_callback(
+                self.device as *const Device,
+                id,
+                Some(alarm_trampoline),
+                pushed.id as *mut core::ffi::c_void,
+            )
+        });
+
+        if set.is_ok() {
+            // the previous closure of the alarm is no longer used by the driver
+            ALARM_HANDLERS.remove_others(pushed.id, |other| *other == key);
+        } else {
+            ALARM_HANDLERS.remove_id(pushed.id);
+        }
+        set
+    }
+}
+
+/// Alarm of a device a closure is installed for with [Rtc::set_alarm_callback].
+#[cfg(feature = "rtc-alarm")]
+#[derive(Copy, Clone, PartialEq)]
+struct AlarmKey {
+    device: usize,
+    id: u16,
+}
+
+#[cfg(feature = "rtc-alarm")]
+static ALARM_HANDLERS: Handlers<AlarmKey, dyn FnMut() + Send> = Handlers::new();
+
+/// C callback that dispatches an alarm to the closure whose id is passed as user data.
+#[cfg(feature = "rtc-alarm")]
+extern "C" fn alarm_trampoline(_dev: *const Device, _id: u16, user_data: *mut core::ffi::c_void) {
+    ALARM_HANDLERS.call_id(user_data as usize, |handler| handler());
+}
+
+#[derive(Debug)]
+struct RtcWrapperContext {}
+
+impl Context for RtcWrapperContext {
+    fn name(&self) -> &'static str {
+        "rtc wrapper"
+    }
+}
@@ -0,0 +1,769 @@
+//! Wrappers for the decoder based Zephyr Sensor API.
+//!
+//! Instead of fetching and reading single channels, [read] reads a whole sample (or the content of
+//! a FIFO) of a sensor into a buffer. The buffer uses a driver specific encoding and can be decoded
+//! into frames with the [Decoder] of the sensor.
+//!
+//! The RTIO I/O device and the RTIO context cannot be created from Rust. They need to be defined in
+//! C using the `SENSOR_DT_READ_IODEV` and `RTIO_DEFINE` macros.
+
+use super::{Device, Sensor, SensorChannel, SensorValue, Vector3, CONTEXT};
+#[cfg(feature = "executor")]
+use crate::sys::SpinLocked;
+use crate::time::{FOREVER, NO_WAIT};
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+#[cfg(feature = "executor")]
+use alloc::vec::Vec;
+use core::ffi::c_void;
+#[cfg(feature = "executor")]
+use core::future::Future;
+#[cfg(feature = "executor")]
+use core::mem::replace;
+#[cfg(feature = "executor")]
+use core::pin::Pin;
+#[cfg(feature = "executor")]
+use core::task::{Context, Poll, Waker};
+#[cfg(feature = "executor")]
+use zephyr_sys::raw::{k_poll_event, k_work, k_work_poll};
+pub use zephyr_sys::raw::{
+    rtio as Rtio, rtio_iodev as RtioIodev, sensor_decoder_api as SensorDecoderApi,
+};
+pub use zephyr_sys::raw::{
+    sensor_data_header as DataHeader, sensor_q31_sample_data as Q31SampleData,
+    sensor_three_axis_sample_data as ThreeAxisSampleData,
+};
+use zephyr_sys::raw::{
+    rtio_cqe, rtio_sqe, sensor_chan_spec, sensor_q31_data, sensor_three_axis_data,
+};
+
+/// Wrapper to the `sensor_read` function which is inlined in the Zephyr API.
+///
+/// `iodev` MUST be a sensor read I/O device defined with `SENSOR_DT_READ_IODEV` and `ctx` an RTIO
+/// context defined with `RTIO_DEFINE`. The submission is copied into the context with syscalls,
+/// so this function also works from user mode threads. The call blocks until the read completed,
+/// completions of other submissions that are still in `ctx` are consumed and dropped.
+///
+/// This method will fail if `buffer` is too small for the encoded sample of the sensor.
+pub unsafe fn read(iodev: &RtioIodev, ctx: &mut Rtio, buffer: &mut [u8]) -> ZephyrResult<()> {
+    let sqe = prep_read(iodev, buffer);
+    submit(ctx, &[sqe])?;
+
+    loop {
+        let mut cqe: rtio_cqe = core::mem::zeroed();
+        let count = zephyr_sys::syscalls::any::rtio_cqe_copy_out(
+            ctx as *mut Rtio,
+            &mut cqe as *mut _,
+            1,
+            FOREVER,
+        );
+        if count < 0 {
+            return Err(ZephyrError::from_errno_with_context(count, &CONTEXT));
+        } else if count == 0 {
+            return Err(ZephyrError::new_with_context(
+                ErrorNumber::NoMessage,
+                &CONTEXT,
+            ));
+        }
+
+        // the context may still hold completions of earlier submissions, which are dropped
+        if cqe.userdata == sqe.userdata {
+            return if cqe.result == 0 {
+                Ok(())
+            } else {
+                Err(ZephyrError::from_errno_with_context(cqe.result, &CONTEXT))
+            };
+        }
+    }
+}
+
+/// Copies `sqes` into `ctx` and submits them without waiting for completions.
+unsafe fn submit(ctx: &mut Rtio, sqes: &[rtio_sqe]) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::rtio_sqe_copy_in_get_handles(
+        ctx as *mut Rtio,
+        sqes.as_ptr(),
+        core::ptr::null_mut(),
+        sqes.len(),
+    );
+    if errno < 0 {
+        return Err(ZephyrError::from_errno_with_context(errno, &CONTEXT));
+    }
+
+    let errno = zephyr_sys::syscalls::any::rtio_submit(ctx as *mut Rtio, 0);
+    if errno < 0 {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    } else {
+        Ok(())
+    }
+}
+
+/// Equivalent of the `rtio_sqe_prep_read` function which is inlined in the Zephyr API.
+unsafe fn prep_read(iodev: &RtioIodev, buffer: &mut [u8]) -> rtio_sqe {
+    let mut sqe: rtio_sqe = core::mem::zeroed();
+    sqe.op = zephyr_sys::raw::RTIO_OP_RX as u8;
+    sqe.prio = zephyr_sys::raw::RTIO_PRIO_NORM as u8;
+    sqe.iodev = iodev as *const RtioIodev;
+    sqe.userdata = buffer.as_mut_ptr() as *mut c_void;
+    sqe.__bindgen_anon_1.rx.buf_len = buffer.len() as u32;
+    sqe.__bindgen_anon_1.rx.buf = buffer.as_mut_ptr();
+    sqe
+}
+
+/// C callback that is called by RTIO once a read submitted with [submit_read] completed.
+pub type ReadCompletedCallback =
+    unsafe extern "C" fn(ctx: *mut Rtio, sqe: *const rtio_sqe, arg: *mut c_void);
+
+/// Submits a read of `iodev` into `buffer` without waiting for it to complete.
+///
+/// `callback` is called with `arg` once the read completed successfully. The completion of the
+/// read still has to be consumed from `ctx` (e.g. with [consume_completion]), the callback itself
+/// does not generate a completion. If the read fails, RTIO cancels the chained callback, so the
+/// callback is never called and only the completion of the read tells the read has ended.
+///
+/// `iodev` and `ctx` have the same requirements as for [read]. `buffer` MUST stay valid until the
+/// read completed and `arg` until the callback has been called or the read failed. Callbacks are
+/// not supported for user mode threads.
+pub unsafe fn submit_read(
+    iodev: &RtioIodev,
+    ctx: &mut Rtio,
+    buffer: &mut [u8],
+    callback: ReadCompletedCallback,
+    arg: *mut c_void,
+) -> ZephyrResult<()> {
+    let mut read_sqe = prep_read(iodev, buffer);
+    read_sqe.flags |= zephyr_sys::raw::RTIO_SQE_CHAINED as u16;
+
+    // equivalent of `rtio_sqe_prep_callback_no_cqe`
+    let mut callback_sqe: rtio_sqe = core::mem::zeroed();
+    callback_sqe.op = zephyr_sys::raw::RTIO_OP_CALLBACK as u8;
+    callback_sqe.prio = zephyr_sys::raw::RTIO_PRIO_NORM as u8;
+    callback_sqe.flags = zephyr_sys::raw::RTIO_SQE_NO_RESPONSE as u16;
+    callback_sqe.__bindgen_anon_1.callback.callback = Some(callback);
+    callback_sqe.__bindgen_anon_1.callback.arg0 = arg;
+
+    submit(ctx, &[read_sqe, callback_sqe])
+}
+
+/// Consumes the completion of a read submitted with [submit_read] from `ctx` and returns its
+/// result. Returns `None` if there is no completion in `ctx`.
+pub unsafe fn consume_completion(ctx: &mut Rtio) -> Option<ZephyrResult<()>> {
+    let mut cqe: rtio_cqe = core::mem::zeroed();
+    let count = zephyr_sys::syscalls::any::rtio_cqe_copy_out(
+        ctx as *mut Rtio,
+        &mut cqe as *mut _,
+        1,
+        NO_WAIT,
+    );
+
+    if count == 0 {
+        None
+    } else if cqe.result == 0 {
+        Some(Ok(()))
+    } else {
+        Some(Err(ZephyrError::from_errno_with_context(cqe.result, &CONTEXT)))
+    }
+}
+
+/// Wrapper to the `sensor_get_decoder` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// This method will fail if the sensor driver does not implement the decoder API.
+pub unsafe fn get_decoder(device: &Device) -> ZephyrResult<Decoder> {
+    let mut decoder: *const SensorDecoderApi = core::ptr::null();
+    let errno = zephyr_sys::syscalls::any::sensor_get_decoder(
+        device as *const Device,
+        &mut decoder as *mut *const SensorDecoderApi,
+    );
+
+    if errno != 0 {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    } else if let Some(decoder) = decoder.as_ref() {
+        Ok(Decoder(decoder))
+    } else {
+        Err(ZephyrError::new_with_context(
+            ErrorNumber::NotImplemented,
+            &CONTEXT,
+        ))
+    }
+}
+
+/// Channel and index of the channel (for sensors with multiple channels of the same type) that
+/// should be decoded.
+#[derive(Copy, Clone)]
+pub struct ChannelSpec {
+    pub channel: SensorChannel,
+    pub index: u16,
+}
+
+impl ChannelSpec {
+    /// Creates a channel specification for the first channel of type `channel`.
+    pub const fn new(channel: SensorChannel) -> Self {
+        Self { channel, index: 0 }
+    }
+}
+
+impl From<ChannelSpec> for sensor_chan_spec {
+    fn from(other: ChannelSpec) -> Self {
+        sensor_chan_spec {
+            chan_type: other.channel as u16,
+            chan_idx: other.index,
+        }
+    }
+}
+
+/// Single decoded reading of a channel.
+#[derive(Copy, Clone, Debug)]
+pub struct Sample<T> {
+    pub timestamp_ns: u64,
+    pub value: T,
+}
+
+/// Decoder of a sensor driver, used to decode buffers read with [read].
+#[derive(Copy, Clone)]
+pub struct Decoder(&'static SensorDecoderApi);
+
+impl Decoder {
+    /// Returns the number of frames of `channel` that are encoded in `buffer`.
+    pub fn frame_count(&self, buffer: &[u8], channel: ChannelSpec) -> ZephyrResult<u16> {
+        let get_frame_count = self.0.get_frame_count.ok_or(ZephyrError::new_with_context(
+            ErrorNumber::NotImplemented,
+            &CONTEXT,
+        ))?;
+
+        let mut frame_count = 0_u16;
+        let errno = unsafe {
+            (get_frame_count)(buffer.as_ptr(), channel.into(), &mut frame_count as *mut u16)
+        };
+
+        if errno == 0 {
+            Ok(frame_count)
+        } else {
+            Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+        }
+    }
+
+    /// Returns an iterator over the frames of a scalar channel (e.g. a temperature) in `buffer`.
+    pub fn frames<'buf>(&self, buffer: &'buf [u8], channel: ChannelSpec) -> Frames<'buf> {
+        Frames(FrameIterator::new(*self, buffer, channel))
+    }
+
+    /// Returns an iterator over the frames of an XYZ channel (e.g. [SensorChannel::AccelXYZ]) in
+    /// `buffer`.
+    pub fn three_axis_frames<'buf>(
+        &self,
+        buffer: &'buf [u8],
+        channel: ChannelSpec,
+    ) -> ThreeAxisFrames<'buf> {
+        ThreeAxisFrames(FrameIterator::new(*self, buffer, channel))
+    }
+
+    /// Decodes up to `N` frames of a scalar channel at once into `data`, without converting the
+    /// values. Returns the number of decoded frames, `0` once all frames have been decoded.
+    ///
+    /// `fit` is the frame iterator of the decoder and MUST be `0` for the first call on `buffer`.
+    pub fn decode_q31<const N: usize>(
+        &self,
+        buffer: &[u8],
+        channel: ChannelSpec,
+        fit: &mut u32,
+        data: &mut Q31Data<N>,
+    ) -> ZephyrResult<usize> {
+        unsafe { self.decode_raw(buffer, channel, fit, N, data as *mut _ as *mut c_void) }
+    }
+
+    /// Decodes up to `N` frames of an XYZ channel at once into `data`, without converting the
+    /// values. See [Decoder::decode_q31].
+    pub fn decode_three_axis<const N: usize>(
+        &self,
+        buffer: &[u8],
+        channel: ChannelSpec,
+        fit: &mut u32,
+        data: &mut ThreeAxisData<N>,
+    ) -> ZephyrResult<usize> {
+        unsafe { self.decode_raw(buffer, channel, fit, N, data as *mut _ as *mut c_void) }
+    }
+
+    /// `data_out` MUST point to the decoded data type of the channel with space for `max_count`
+    /// readings.
+    unsafe fn decode_raw(
+        &self,
+        buffer: &[u8],
+        channel: ChannelSpec,
+        fit: &mut u32,
+        max_count: usize,
+        data_out: *mut c_void,
+    ) -> ZephyrResult<usize> {
+        let decode = self.0.decode.ok_or(ZephyrError::new_with_context(
+            ErrorNumber::NotImplemented,
+            &CONTEXT,
+        ))?;
+
+        let decoded = (decode)(
+            buffer.as_ptr(),
+            channel.into(),
+            fit as *mut u32,
+            max_count.min(u16::MAX as usize) as u16,
+            data_out,
+        );
+
+        if decoded >= 0 {
+            Ok(decoded as usize)
+        } else {
+            Err(ZephyrError::from_errno_with_context(decoded, &CONTEXT))
+        }
+    }
+}
+
+/// Batch of up to `N` decoded Q31 readings of a scalar channel, layout compatible with
+/// `struct sensor_q31_data` of the Zephyr API.
+///
+/// Use this instead of [Decoder::frames] for high-rate sensors where converting every reading to a
+/// [SensorValue] on its own is too slow.
+#[repr(C)]
+pub struct Q31Data<const N: usize> {
+    pub header: DataHeader,
+    pub shift: i8,
+    pub readings: [Q31SampleData; N],
+}
+
+impl<const N: usize> Q31Data<N> {
+    /// Creates an empty batch.
+    pub fn new() -> Self {
+        unsafe { core::mem::zeroed() }
+    }
+
+    /// Returns the decoded readings. Only the first `header.reading_count` readings are valid.
+    pub fn readings(&self) -> &[Q31SampleData] {
+        &self.readings[..(self.header.reading_count as usize).min(N)]
+    }
+
+    /// Returns the raw Q31 value of the `index`-th reading.
+    pub fn value(&self, index: usize) -> i32 {
+        unsafe { self.readings()[index].__bindgen_anon_2.value }
+    }
+
+    /// Returns the timestamp of the `index`-th reading in nanoseconds.
+    pub fn timestamp_ns(&self, index: usize) -> u64 {
+        self.header.base_timestamp_ns
+            + unsafe { self.readings()[index].__bindgen_anon_1.timestamp_delta } as u64
+    }
+
+    /// Converts the valid readings to floating point values and writes them to `out`. Returns the
+    /// number of converted values.
+    pub fn to_f32(&self, out: &mut [f32]) -> usize {
+        let scale = q31_scale(self.shift);
+        self.readings()
+            .iter()
+            .zip(out.iter_mut())
+            .map(|(reading, out)| *out = unsafe { reading.__bindgen_anon_2.value } as f32 * scale)
+            .count()
+    }
+
+    /// Converts the valid readings to [SensorValue]s and writes them to `out`. Returns the number
+    /// of converted values.
+    pub fn to_sensor_values(&self, out: &mut [SensorValue]) -> usize {
+        let shift = self.shift;
+        self.readings()
+            .iter()
+            .zip(out.iter_mut())
+            .map(|(reading, out)| {
+                *out = SensorValue::from_q31(unsafe { reading.__bindgen_anon_2.value }, shift)
+            })
+            .count()
+    }
+}
+
+impl<const N: usize> Default for Q31Data<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Batch of up to `N` decoded Q31 readings of an XYZ channel, layout compatible with
+/// `struct sensor_three_axis_data` of the Zephyr API. See [Q31Data].
+#[repr(C)]
+pub struct ThreeAxisData<const N: usize> {
+    pub header: DataHeader,
+    pub shift: i8,
+    pub readings: [ThreeAxisSampleData; N],
+}
+
+impl<const N: usize> ThreeAxisData<N> {
+    /// Creates an empty batch.
+    pub fn new() -> Self {
+        unsafe { core::mem::zeroed() }
+    }
+
+    /// Returns the decoded readings. Only the first `header.reading_count` readings are valid.
+    pub fn readings(&self) -> &[ThreeAxisSampleData] {
+        &self.readings[..(self.header.reading_count as usize).min(N)]
+    }
+
+    /// Returns the raw Q31 values of the `index`-th reading.
+    pub fn values(&self, index: usize) -> [i32; 3] {
+        unsafe { self.readings()[index].__bindgen_anon_2.values }
+    }
+
+    /// Returns the timestamp of the `index`-th reading in nanoseconds.
+    pub fn timestamp_ns(&self, index: usize) -> u64 {
+        self.header.base_timestamp_ns
+            + unsafe { self.readings()[index].__bindgen_anon_1.timestamp_delta } as u64
+    }
+
+    /// Converts the valid readings to floating point vectors and writes them to `out`. Returns the
+    /// number of converted vectors.
+    pub fn to_f32(&self, out: &mut [Vector3<f32>]) -> usize {
+        let scale = q31_scale(self.shift);
+        self.readings()
+            .iter()
+            .zip(out.iter_mut())
+            .map(|(reading, out)| {
+                let values = unsafe { reading.__bindgen_anon_2.values };
+                *out = Vector3 {
+                    x: values[0] as f32 * scale,
+                    y: values[1] as f32 * scale,
+                    z: values[2] as f32 * scale,
+                }
+            })
+            .count()
+    }
+}
+
+impl<const N: usize> Default for ThreeAxisData<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Factor that converts a Q31 value with `shift` to a floating point value. The power of two is
+/// built from its bits, `f32::powi` is not available in core.
+fn q31_scale(shift: i8) -> f32 {
+    // between -159 and 96, the larger ones are normal numbers
+    let exponent = shift as i32 - 31;
+    if exponent >= -126 {
+        f32::from_bits(((exponent + 127) as u32) << 23)
+    } else if exponent >= -149 {
+        f32::from_bits(1 << (exponent + 149))
+    } else {
+        0.0
+    }
+}
+
+/// Shared state of the frame iterators. The frame iterator (`fit`) is managed by the decoder.
+struct FrameIterator<'buf> {
+    decoder: Decoder,
+    buffer: &'buf [u8],
+    channel: ChannelSpec,
+    fit: u32,
+    done: bool,
+}
+
+impl<'buf> FrameIterator<'buf> {
+    fn new(decoder: Decoder, buffer: &'buf [u8], channel: ChannelSpec) -> Self {
+        Self {
+            decoder,
+            buffer,
+            channel,
+            fit: 0,
+            done: false,
+        }
+    }
+
+    /// Decodes the next frame into `data_out`, which MUST be the decoded data type of the channel.
+    fn decode_next<T>(&mut self, data_out: &mut T) -> Option<ZephyrResult<()>> {
+        if self.done {
+            return None;
+        }
+
+        let decode = match self.decoder.0.decode {
+            Some(decode) => decode,
+            None => {
+                self.done = true;
+                return Some(Err(ZephyrError::new_with_context(
+                    ErrorNumber::NotImplemented,
+                    &CONTEXT,
+                )));
+            }
+        };
+
+        let decoded = unsafe {
+            (decode)(
+                self.buffer.as_ptr(),
+                self.channel.into(),
+                &mut self.fit as *mut u32,
+                1,
+                data_out as *mut T as *mut c_void,
+            )
+        };
+
+        if decoded > 0 {
+            Some(Ok(()))
+        } else if decoded == 0 {
+            self.done = true;
+            None
+        } else {
+            self.done = true;
+            Some(Err(ZephyrError::from_errno_with_context(decoded, &CONTEXT)))
+        }
+    }
+}
+
+/// Iterator over the frames of a scalar channel, see [Decoder::frames].
+pub struct Frames<'buf>(FrameIterator<'buf>);
+
+impl Iterator for Frames<'_> {
+    type Item = ZephyrResult<Sample<SensorValue>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut data: sensor_q31_data = unsafe { core::mem::zeroed() };
+        self.0.decode_next(&mut data).map(|result| {
+            result.map(|_| {
+                let reading = &data.readings[0];
+                let value = unsafe { reading.__bindgen_anon_2.value };
+                Sample {
+                    timestamp_ns: data.header.base_timestamp_ns
+                        + unsafe { reading.__bindgen_anon_1.timestamp_delta } as u64,
+                    value: SensorValue::from_q31(value, data.shift),
+                }
+            })
+        })
+    }
+}
+
+/// Iterator over the frames of an XYZ channel, see [Decoder::three_axis_frames].
+pub struct ThreeAxisFrames<'buf>(FrameIterator<'buf>);
+
+impl Iterator for ThreeAxisFrames<'_> {
+    type Item = ZephyrResult<Sample<Vector3<SensorValue>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut data: sensor_three_axis_data = unsafe { core::mem::zeroed() };
+        self.0.decode_next(&mut data).map(|result| {
+            result.map(|_| {
+                let reading = &data.readings[0];
+                let values = unsafe { reading.__bindgen_anon_2.values };
+                Sample {
+                    timestamp_ns: data.header.base_timestamp_ns
+                        + unsafe { reading.__bindgen_anon_1.timestamp_delta } as u64,
+                    value: Vector3 {
+                        x: SensorValue::from_q31(values[0], data.shift),
+                        y: SensorValue::from_q31(values[1], data.shift),
+                        z: SensorValue::from_q31(values[2], data.shift),
+                    },
+                }
+            })
+        })
+    }
+}
+
+/// Equivalent of `K_POLL_TYPE_SEM_AVAILABLE`, the macro is not available in the bindings.
+#[cfg(feature = "executor")]
+const K_POLL_TYPE_SEM_AVAILABLE: u32 =
+    1 << (zephyr_sys::raw::_poll_types_bits__POLL_TYPE_SEM_AVAILABLE - 1);
+
+/// Future of a read submitted with [Sensor::read_async], completes with the buffer.
+///
+/// The future is woken when a completion is queued in the RTIO context, which is signaled by the
+/// semaphore of the context (`CONFIG_RTIO_CONSUME_SEM`). Completions of other submissions found in
+/// the context are dropped. Dropping the future before the read completed leaks the buffer, as
+/// RTIO may still write into it.
+#[cfg(feature = "executor")]
+pub struct ReadFuture<'r> {
+    ctx: &'r mut Rtio,
+    buffer: Option<Vec<u8>>,
+    state: Option<Box<ReadState>>,
+    submitted: ZephyrResult<()>,
+}
+
+/// Triggered work waking a [ReadFuture] once a completion is available.
+#[cfg(feature = "executor")]
+#[repr(C)]
+struct ReadState {
+    // first field, so the handler gets the state from the pointer to the work
+    work: k_work_poll,
+    event: k_poll_event,
+    /// Locked as not every target has compare and swap atomics.
+    waker: SpinLocked<Option<Waker>>,
+}
+
+#[cfg(feature = "executor")]
+unsafe extern "C" fn read_work_handler(work: *mut k_work) {
+    let state = &*(work as *const ReadState);
+    if let Some(waker) = state.waker.locked(Option::take) {
+        waker.wake();
+    }
+}
+
+#[cfg(feature = "executor")]
+impl ReadFuture<'_> {
+    /// Consumes the completions in the context until the one of the read, returns its result.
+    fn completion(&mut self) -> Option<ZephyrResult<()>> {
+        let userdata = self.buffer.as_mut()?.as_mut_ptr() as *mut c_void;
+        loop {
+            let mut cqe: rtio_cqe = unsafe { core::mem::zeroed() };
+            let count = unsafe {
+                zephyr_sys::syscalls::any::rtio_cqe_copy_out(
+                    self.ctx as *mut Rtio,
+                    &mut cqe as *mut _,
+                    1,
+                    NO_WAIT,
+                )
+            };
+            if count <= 0 {
+                return None;
+            }
+            if cqe.userdata == userdata {
+                return Some(if cqe.result == 0 {
+                    Ok(())
+                } else {
+                    Err(ZephyrError::from_errno_with_context(cqe.result, &CONTEXT))
+                });
+            }
+        }
+    }
+
+    /// Submits the work of the state, which runs once the semaphore of the context is available.
+    fn watch(&mut self) -> ZephyrResult<()> {
+        let state = self.state.as_mut().expect("state of a pending read");
+        let errno = unsafe {
+            zephyr_sys::raw::k_work_poll_submit(
+                &mut state.work as *mut k_work_poll,
+                &mut state.event as *mut k_poll_event,
+                1,
+                FOREVER,
+            )
+        };
+        if errno == 0 {
+            Ok(())
+        } else {
+            Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+        }
+    }
+}
+
+#[cfg(feature = "executor")]
+impl Future for ReadFuture<'_> {
+    type Output = ZephyrResult<Vec<u8>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if let Err(e) = replace(&mut this.submitted, Ok(())) {
+            // the read has not been submitted, nothing writes into the buffer
+            this.buffer = None;
+            return Poll::Ready(Err(e));
+        }
+
+        let mut result = this.completion();
+        if result.is_none() {
+            let state = this.state.as_ref().expect("state of a pending read");
+            let waker = cx.waker().clone();
+            // the previous waker is dropped without the lock held
+            drop(state.waker.locked(|previous| previous.replace(waker)));
+            if let Err(e) = this.watch() {
+                return Poll::Ready(Err(e));
+            }
+            // the completion might have been queued before the work was submitted
+            result = this.completion();
+        }
+
+        match result {
+            Some(result) => {
+                let buffer = this.buffer.take().expect("buffer of a pending read");
+                Poll::Ready(result.map(|_| buffer))
+            }
+            None => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(feature = "executor")]
+impl Drop for ReadFuture<'_> {
+    fn drop(&mut self) {
+        let mut state = match self.state.take() {
+            Some(state) => state,
+            None => return,
+        };
+        // -EINVAL if the work is not waiting for the semaphore, it may be queued or running then
+        let cancelled = unsafe { zephyr_sys::raw::k_work_poll_cancel(&mut state.work) };
+        let busy = unsafe { zephyr_sys::raw::k_work_busy_get(&state.work.work) };
+        if cancelled == 0 || busy == 0 {
+            drop(state.waker.locked(Option::take));
+        } else {
+            // the work queue still accesses the state, which is leaked instead of waiting for it
+            core::mem::forget(state);
+        }
+
+        if self.buffer.is_some() && self.submitted.is_ok() && self.completion().is_none() {
+            // RTIO may still write into the buffer, which is leaked instead of waiting for it
+            core::mem::forget(self.buffer.take());
+        }
+    }
+}
+
+impl Sensor {
+    /// Read a sample of the sensor into `buffer` without blocking the current thread.
+    ///
+    /// `iodev` MUST be the sensor read I/O device of this sensor and `ctx` an RTIO context, see
+    /// [read], which signals its completions with a semaphore (`CONFIG_RTIO_CONSUME_SEM`). The
+    /// returned future completes with the buffer once the read finished, the buffer can then be
+    /// decoded with [Sensor::decoder]. Multiple sensors can be read concurrently by using one RTIO
+    /// context per sensor.
+    #[cfg(feature = "executor")]
+    pub fn read_async<'r>(
+        &self,
+        iodev: &'static RtioIodev,
+        ctx: &'r mut Rtio,
+        mut buffer: Vec<u8>,
+    ) -> ReadFuture<'r> {
+        #[cfg(zephyr_config_rtio_consume_sem)]
+        let consume_sem = ctx.consume_sem;
+        #[cfg(not(zephyr_config_rtio_consume_sem))]
+        let consume_sem: *mut zephyr_sys::raw::k_sem = core::ptr::null_mut();
+        if consume_sem.is_null() {
+            return ReadFuture {
+                ctx,
+                buffer: Some(buffer),
+                state: None,
+                submitted: Err(ZephyrError::new_with_context(
+                    ErrorNumber::NotSupported,
+                    &CONTEXT,
+                )),
+            };
+        }
+
+        // zeroed as the work and the event are initialized below
+        let mut state = Box::new(ReadState {
+            work: unsafe { core::mem::zeroed() },
+            event: unsafe { core::mem::zeroed() },
+            waker: SpinLocked::new(None),
+        });
+        unsafe {
+            zephyr_sys::raw::k_work_poll_init(&mut state.work, Some(read_work_handler));
+            zephyr_sys::raw::k_poll_event_init(
+                &mut state.event,
+                K_POLL_TYPE_SEM_AVAILABLE,
+                zephyr_sys::raw::k_poll_modes_K_POLL_MODE_NOTIFY_ONLY as _,
+                consume_sem as *mut c_void,
+            );
+        }
+        let sqe = unsafe { prep_read(iodev, &mut buffer) };
+        let submitted = unsafe { submit(ctx, &[sqe]) };
+
+        ReadFuture {
+            ctx,
+            buffer: Some(buffer),
+            state: Some(state),
+            submitted,
+        }
+    }
+
+    /// Returns the [Decoder] of the sensor driver.
+    ///
+    /// This method will fail if the sensor driver does not implement the decoder API.
+    pub fn decoder(&self) -> ZephyrResult<Decoder> {
+        // device is required to be a sensor device in constructor
+        unsafe { get_decoder(self.device) }
+    }
+}
@@ -0,0 +1,49 @@
+//! Convenience functions for the die temperature sensor of the SoC.
+//!
+//! ```no_run
+//! use zephyr_rust_wrappers::sensor::die_temp;
+//!
+//! let temperature = die_temp::read().expect("reading die temperature");
+//! println!("die temperature: {}", temperature);
+//! ```
+
+use super::units::Celsius;
+use super::{Device, Sensor, SensorChannel, CONTEXT};
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::ffi::CString;
+
+/// Names of the die temperature devices of common SoC families, used by [find].
+pub const DEFAULT_BINDINGS: &[&str] = &["die_temp", "dietemp", "temp", "TEMP_0"];
+
+/// Looks up the sensor device `name` (see `device_get_binding`) and checks that it is a sensor.
+///
+/// This method fails with [ErrorNumber::NoDevice] if there is no device with this name.
+pub fn from_binding(name: &str) -> ZephyrResult<Sensor> {
+    let name = CString::new(name)
+        .map_err(|_| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))?;
+    let device: Option<&'static Device> =
+        unsafe { zephyr_sys::syscalls::any::device_get_binding(name.as_ptr()).as_ref() };
+
+    match device {
+        Some(device) => Sensor::try_new(device),
+        None => Err(ZephyrError::new_with_context(ErrorNumber::NoDevice, &CONTEXT)),
+    }
+}
+
+/// Returns the first ready sensor device of [DEFAULT_BINDINGS].
+pub fn find() -> ZephyrResult<Sensor> {
+    DEFAULT_BINDINGS
+        .iter()
+        .find_map(|name| from_binding(name).ok())
+        .ok_or(ZephyrError::new_with_context(ErrorNumber::NoDevice, &CONTEXT))
+}
+
+/// Samples [SensorChannel::DieTemperature] of `sensor`.
+pub fn read_from(sensor: &mut Sensor) -> ZephyrResult<Celsius> {
+    sensor.sample(SensorChannel::DieTemperature).map(Celsius)
+}
+
+/// Looks up the die temperature sensor with [find] and samples the temperature.
+pub fn read() -> ZephyrResult<Celsius> {
+    read_from(&mut find()?)
+}
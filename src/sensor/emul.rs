@@ -0,0 +1,151 @@
+//! Controls for emulated sensors, used to unit test application logic based on [Sensor].
+//!
+//! The emulated sensors are provided by the Zephyr emulator drivers (`CONFIG_EMUL`), e.g. under
+//! `native_sim`. The value that an emulated sensor returns can be set from the test so the
+//! readings are deterministic.
+//!
+//! ```no_run
+//! use zephyr_rust_wrappers::sensor::decoder::ChannelSpec;
+//! use zephyr_rust_wrappers::sensor::emul::Emulator;
+//! use zephyr_rust_wrappers::sensor::SensorChannel;
+//!
+//! let emulator = Emulator::from_binding("bmi160").expect("emulator of bmi160");
+//! emulator
+//!     .set_value(ChannelSpec::new(SensorChannel::AccelX), 1.5_f32.into())
+//!     .expect("setting emulated acceleration");
+//! ```
+
+use super::decoder::ChannelSpec;
+use super::{SensorAttribute, SensorValue, CONTEXT};
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::ffi::CString;
+use zephyr_sys::raw::{emul, emul_sensor_driver_api as EmulSensorDriverApi};
+
+/// Range of the values an emulated sensor can return for a channel.
+#[derive(Copy, Clone, Debug)]
+pub struct SampleRange {
+    pub lower: SensorValue,
+    pub upper: SensorValue,
+    /// Maximum difference between a value that has been set and the value being read.
+    pub epsilon: SensorValue,
+}
+
+/// Handle of the sensor backend of an emulator.
+#[derive(Copy, Clone)]
+pub struct Emulator(&'static emul);
+
+impl Emulator {
+    /// Wrapper to the `emul_get_binding` function.
+    ///
+    /// This method fails with [ErrorNumber::NoDevice] if there is no emulator for the device
+    /// `name` and with [ErrorNumber::NotSupported] if the emulator has no sensor backend.
+    pub fn from_binding(name: &str) -> ZephyrResult<Self> {
+        let name = CString::new(name)
+            .map_err(|_| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))?;
+        let emulator = unsafe { zephyr_sys::raw::emul_get_binding(name.as_ptr()).as_ref() };
+
+        match emulator {
+            Some(emulator) if !emulator.backend_api.is_null() => Ok(Emulator(emulator)),
+            Some(_) => Err(ZephyrError::new_with_context(
+                ErrorNumber::NotSupported,
+                &CONTEXT,
+            )),
+            None => Err(ZephyrError::new_with_context(ErrorNumber::NoDevice, &CONTEXT)),
+        }
+    }
+
+    fn api(&self) -> &'static EmulSensorDriverApi {
+        // checked to be non-null in constructor
+        unsafe { &*(self.0.backend_api as *const EmulSensorDriverApi) }
+    }
+
+    /// Set the value the emulated sensor returns for `channel`.
+    ///
+    /// This is the equivalent of the `emul_sensor_backend_set_channel` function which is inlined
+    /// in the Zephyr API.
+    pub fn set_value(&self, channel: ChannelSpec, value: SensorValue) -> ZephyrResult<()> {
+        let set_channel = self.api().set_channel.ok_or(ZephyrError::new_with_context(
+            ErrorNumber::NotImplemented,
+            &CONTEXT,
+        ))?;
+
+        let shift = value.q31_shift();
+        let q31 = value.to_q31(shift);
+        let errno = unsafe {
+            (set_channel)(self.0 as *const emul, channel.into(), &q31 as *const i32, shift)
+        };
+
+        if errno == 0 {
+            Ok(())
+        } else {
+            Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+        }
+    }
+
+    /// Returns the range of values the emulated sensor supports for `channel`.
+    ///
+    /// This is the equivalent of the `emul_sensor_backend_get_sample_range` function which is
+    /// inlined in the Zephyr API.
+    pub fn sample_range(&self, channel: ChannelSpec) -> ZephyrResult<SampleRange> {
+        let get_sample_range = self.api().get_sample_range.ok_or(ZephyrError::new_with_context(
+            ErrorNumber::NotImplemented,
+            &CONTEXT,
+        ))?;
+
+        let (mut lower, mut upper, mut epsilon, mut shift) = (0_i32, 0_i32, 0_i32, 0_i8);
+        let errno = unsafe {
+            (get_sample_range)(
+                self.0 as *const emul,
+                channel.into(),
+                &mut lower as *mut i32,
+                &mut upper as *mut i32,
+                &mut epsilon as *mut i32,
+                &mut shift as *mut i8,
+            )
+        };
+
+        if errno == 0 {
+            Ok(SampleRange {
+                lower: SensorValue::from_q31(lower, shift),
+                upper: SensorValue::from_q31(upper, shift),
+                epsilon: SensorValue::from_q31(epsilon, shift),
+            })
+        } else {
+            Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+        }
+    }
+
+    /// Set `attribute` of `channel` in the emulator backend, e.g. to emulate an attribute value
+    /// that cannot be set through the driver.
+    ///
+    /// This is the equivalent of the `emul_sensor_backend_set_attribute` function which is inlined
+    /// in the Zephyr API.
+    pub fn set_attribute(
+        &self,
+        channel: ChannelSpec,
+        attribute: SensorAttribute,
+        value: SensorValue,
+    ) -> ZephyrResult<()> {
+        let set_attribute = self.api().set_attribute.ok_or(ZephyrError::new_with_context(
+            ErrorNumber::NotImplemented,
+            &CONTEXT,
+        ))?;
+
+        let shift = value.q31_shift();
+        let q31 = value.to_q31(shift);
+        let errno = unsafe {
+            (set_attribute)(
+                self.0 as *const emul,
+                channel.into(),
+                attribute as u32,
+                &q31 as *const i32 as *const core::ffi::c_void,
+            )
+        };
+
+        if errno == 0 {
+            Ok(())
+        } else {
+            Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Sampling of multiple sensors without one thread per sensor.
+//!
+//! A [SensorHub] owns several sensors and publishes their readings, tagged with the sensor they
+//! originate from, onto one [MessageQueue]. The sensors are either polled periodically by the hub
+//! or sampled from their trigger handlers.
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::sensor::{Sensor, SensorChannel};
+//!# use zephyr_rust_wrappers::sensor::hub::{Reading, SensorHub};
+//!# use zephyr_rust_wrappers::message_queue::MessageQueue;
+//!# use std::time::Duration;
+//!# fn setup(queue: &'static MessageQueue<Reading>, climate: Sensor, pressure: Sensor) {
+//! let mut hub = SensorHub::new(queue);
+//! hub.add(0, climate, &[SensorChannel::AmbientTemperature, SensorChannel::Humidity]);
+//! hub.add(1, pressure, &[SensorChannel::Pressure]);
+//! hub.run(Duration::from_secs(10));
+//!# }
+//! ```
+
+use super::{Sensor, SensorChannel, SensorValue, TriggerType};
+use crate::message_queue::MessageQueue;
+use crate::time::{timeout, uptime, NO_WAIT};
+use crate::{ErrorNumber, ZephyrResult};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+/// Tag that identifies the sensor of a [Reading].
+pub type SensorTag = u16;
+
+/// Reading of a channel that is published by a [SensorHub].
+#[derive(Copy, Clone, Debug)]
+pub struct Reading {
+    pub tag: SensorTag,
+    pub channel: SensorChannel,
+    /// Uptime of the system when the sample was fetched.
+    pub timestamp: Duration,
+    pub value: Result<SensorValue, ErrorNumber>,
+}
+
+struct HubEntry {
+    tag: SensorTag,
+    sensor: Sensor,
+    channels: Vec<SensorChannel>,
+}
+
+/// Fetch a sample of all channels of `sensor` and publish a reading of each of `channels`.
+///
+/// Failed fetches and reads are published as readings with an error. Fails if the queue is full.
+fn publish(
+    queue: &MessageQueue<Reading>,
+    tag: SensorTag,
+    sensor: &mut Sensor,
+    channels: &[SensorChannel],
+) -> ZephyrResult<()> {
+    let timestamp = uptime();
+    let fetched = sensor.fetch();
+
+    for channel in channels {
+        let value = match &fetched {
+            Ok(_) => sensor.get(*channel).map_err(|e| e.number()),
+            Err(e) => Err(e.number()),
+        };
+        let reading = Reading {
+            tag,
+            channel: *channel,
+            timestamp,
+            value,
+        };
+        queue.put(&reading, NO_WAIT)?;
+    }
+    Ok(())
+}
+
+/// Owner of several sensors that publishes their readings onto one [MessageQueue].
+pub struct SensorHub<'q> {
+    queue: &'q MessageQueue<Reading>,
+    sensors: Vec<HubEntry>,
+}
+
+impl<'q> SensorHub<'q> {
+    /// Creates a new hub without sensors that publishes onto `queue`.
+    pub fn new(queue: &'q MessageQueue<Reading>) -> Self {
+        Self {
+            queue,
+            sensors: vec![],
+        }
+    }
+
+    /// Adds `sensor` with the tag `tag`. Each poll publishes readings of `channels`.
+    pub fn add(&mut self, tag: SensorTag, sensor: Sensor, channels: &[SensorChannel]) {
+        self.sensors.push(HubEntry {
+            tag,
+            sensor,
+            channels: channels.to_vec(),
+        });
+    }
+
+    /// Samples all sensors that have been added with [SensorHub::add] once.
+    ///
+    /// This method fails if the queue is full, the remaining readings are dropped.
+    pub fn poll(&mut self) -> ZephyrResult<()> {
+        let queue = self.queue;
+        self.sensors.iter_mut().try_for_each(|entry| {
+            publish(queue, entry.tag, &mut entry.sensor, &entry.channels)
+        })
+    }
+
+    /// Polls all sensors every `period`. Readings that do not fit into the queue are dropped.
+    pub fn run(&mut self, period: Duration) -> ! {
+        loop {
+            let _ = self.poll();
+            unsafe { zephyr_sys::syscalls::any::k_sleep(timeout(period)) };
+        }
+    }
+}
+
+impl SensorHub<'static> {
+    /// Publishes readings of `channels` of `sensor` each time the trigger `trigger_type` on
+    /// `trigger_channel` occurs, see [Sensor::enable_trigger_with].
+    ///
+    /// The sensor is sampled in the trigger handler, so it is not polled by the hub. Readings that
+    /// do not fit into the queue are dropped.
+    pub fn add_triggered(
+        &mut self,
+        tag: SensorTag,
+        sensor: &mut Sensor,
+        trigger_type: TriggerType,
+        trigger_channel: SensorChannel,
+        channels: &[SensorChannel],
+    ) -> ZephyrResult<()> {
+        let queue: &'static MessageQueue<Reading> = self.queue;
+        let channels = channels.to_vec();
+        sensor.enable_trigger_with(trigger_type, trigger_channel, move |sensor, _| {
+            let _ = publish(queue, tag, sensor, &channels);
+        })
+    }
+}
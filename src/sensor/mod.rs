@@ -0,0 +1,863 @@
+//! Syscalls and high level wrappers for the Zephyr Sensor API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! ```should_panic
+//!# use zephyr::context::Kernel as Context;
+//! use zephyr_rust_wrappers::sensor::{Sensor, SensorChannel};
+//!
+//! let mut sensor = if let Some(sensor_device) = Context::device_get_binding("binding-label") {
+//!     // we must make sure manually that we are binding to a sensor device
+//!     unsafe {
+//!         Sensor::new(sensor_device)
+//!     }
+//! } else {
+//!     panic!("could not resolve binding for sensor")
+//! };
+//!
+//! let value = sensor
+//!                   .sample(SensorChannel::AmbientTemperature)
+//!                   .expect("sampling value from sensor");
+//!
+//! println!("sensor measures {} °C", value as f32)
+//! ```
+
+use alloc::boxed::Box;
+pub use zephyr::device::Device;
+
+use core::cmp::Ordering;
+use core::fmt::{Display, Formatter};
+use core::ops::{Add, Div, Mul, Sub};
+
+use crate::device::{class, DeviceClass, TypedDevice};
+use crate::handlers::Handlers;
+use crate::{Context, ErrorNumber, ZephyrError};
+pub use zephyr_sys::raw::sensor_trigger as SensorTrigger;
+use zephyr_sys::raw::sensor_value as ZSensorValue;
+
+pub mod decoder;
+pub mod die_temp;
+#[cfg(feature = "test-emul")]
+pub mod emul;
+pub mod hub;
+pub mod stream;
+pub mod units;
+
+const CONTEXT: SensorWrapperContext = SensorWrapperContext {};
+
+/// Type that uses the internal Zephyr representation of a sensor value.
+/// This type is needed to add additional functionality to a sensor value (e.g. [Into<f32>]).
+///
+/// As per the Zephyr documentation, the value can be converted to a floating point using the formula
+/// <code>value = val1 + val2 * 10<sup>-6</sup></code>.
+///
+/// We define a [SensorValue] to be normalized if <code>|val2 * 10<sup>-6</sup>| < 1</code> and `val2`
+/// has the same sign as `val1` (e.g. -1.5 is represented as `val1 = -1` and `val2 = -500000`), which
+/// is the representation used by Zephyr. All conversions that are defined for our implementation
+/// will always yield a normalized sensor value.
+///
+/// This type implements conversions to and from [i32], [f32], [f64] and micro units (see
+/// [SensorValue::from_micro]) as well as the basic arithmetic operations and comparisons.
+///
+/// ```rust
+///# use zephyr_rust_wrappers::sensor::SensorValue;
+/// let sensor_float: SensorValue = 1.5.into();
+/// let sensor_int: SensorValue = 1.into();
+///
+/// assert_eq!(1.5_f32, sensor_float.into());
+/// assert_eq!(1_i32, sensor_float.into());
+///
+/// let negative: SensorValue = (-1.5).into();
+/// assert_eq!(-1_500_000, negative.to_micro());
+/// assert_eq!("-1.500000", negative.to_string());
+/// assert!(negative < sensor_int);
+/// assert_eq!(SensorValue::from(2.5), sensor_float + sensor_int);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct SensorValue {
+    val1: i32,
+    val2: i32,
+}
+
+impl SensorValue {
+    /// Smallest value in micro units.
+    const MIN_MICRO: i64 = i32::MIN as i64 * 1_000_000 - 999_999;
+    /// Largest value in micro units.
+    const MAX_MICRO: i64 = i32::MAX as i64 * 1_000_000 + 999_999;
+
+    /// Create a normalized [SensorValue] from a value in micro units (<code>10<sup>-6</sup></code>).
+    ///
+    /// Values beyond the range of a [SensorValue] (the integer part is an [i32]) saturate. So do the
+    /// conversions from floats and the results of the arithmetic operations.
+    pub const fn from_micro(micro: i64) -> Self {
+        let micro = if micro < Self::MIN_MICRO {
+            Self::MIN_MICRO
+        } else if micro > Self::MAX_MICRO {
+            Self::MAX_MICRO
+        } else {
+            micro
+        };
+        // division and remainder round towards zero, so both parts get the same sign
+        Self {
+            val1: (micro / 1_000_000) as i32,
+            val2: (micro % 1_000_000) as i32,
+        }
+    }
+
+    /// [SensorValue::from_micro] for the results of the calculations in [i128].
+    fn from_wide_micro(micro: i128) -> Self {
+        Self::from_micro(micro.max(i64::MIN as i128).min(i64::MAX as i128) as i64)
+    }
+
+    /// Returns the value in micro units (<code>10<sup>-6</sup></code>).
+    pub const fn to_micro(&self) -> i64 {
+        self.val1 as i64 * 1_000_000 + self.val2 as i64
+    }
+
+    /// Convert a Q31 fixed point value with the given `shift` to a normalized [SensorValue].
+    ///
+    /// The represented value is <code>value * 2<sup>shift - 31</sup></code>. This is the format used
+    /// by the decoders of the decoder based sensor API (see [decoder]).
+    pub fn from_q31(value: i32, shift: i8) -> Self {
+        let shift = Self::clamp_q31_shift(shift);
+        let micro = if shift >= 0 {
+            ((value as i128 * 1_000_000) << shift) >> 31
+        } else {
+            (value as i128 * 1_000_000) >> (31 - shift)
+        };
+        Self::from_wide_micro(micro)
+    }
+
+    /// Convert the value to a Q31 fixed point value with the given `shift`, the inverse of
+    /// [SensorValue::from_q31]. The value saturates if it cannot be represented with `shift`.
+    pub fn to_q31(&self, shift: i8) -> i32 {
+        let shift = Self::clamp_q31_shift(shift);
+        let micro = self.to_micro() as i128;
+        let q31 = if shift >= 0 {
+            (micro << 31) / (1_000_000 << shift)
+        } else {
+            (micro << (31 - shift)) / 1_000_000
+        };
+        q31.max(i32::MIN as i128).min(i32::MAX as i128) as i32
+    }
+
+    /// Limits `shift` to the range in which the Q31 conversions fit into [i128]. Beyond it the
+    /// results do not change anymore: every value saturates or rounds to zero.
+    fn clamp_q31_shift(shift: i8) -> i32 {
+        (shift as i32).max(-32).min(64)
+    }
+
+    /// Returns the smallest shift that can represent the value as Q31 fixed point value.
+    pub fn q31_shift(&self) -> i8 {
+        let integer = (self.to_micro().abs() / 1_000_000) as u64;
+        // number of bits needed for the integer part
+        (64 - integer.leading_zeros()) as i8
+    }
+}
+
+impl From<ZSensorValue> for SensorValue {
+    fn from(other: ZSensorValue) -> Self {
+        Self {
+            val1: other.val1,
+            val2: other.val2,
+        }
+    }
+}
+
+impl From<SensorValue> for ZSensorValue {
+    fn from(other: SensorValue) -> Self {
+        ZSensorValue {
+            val1: other.val1,
+            val2: other.val2,
+        }
+    }
+}
+
+impl From<i32> for SensorValue {
+    fn from(other: i32) -> Self {
+        SensorValue {
+            val1: other,
+            val2: 0,
+        }
+    }
+}
+
+impl From<SensorValue> for i32 {
+    fn from(other: SensorValue) -> Self {
+        other.val1
+    }
+}
+
+impl From<SensorValue> for f32 {
+    fn from(other: SensorValue) -> Self {
+        (other.val1 as f32) + (other.val2 as f32 * 1e-6_f32)
+    }
+}
+
+impl From<f32> for SensorValue {
+    fn from(other: f32) -> Self {
+        // rounded as a whole, so the fraction cannot round up to a full unit
+        Self::from_micro(round(other as f64 * 1e6_f64))
+    }
+}
+
+impl From<SensorValue> for f64 {
+    fn from(other: SensorValue) -> Self {
+        (other.val1 as f64) + (other.val2 as f64 * 1e-6_f64)
+    }
+}
+
+impl From<f64> for SensorValue {
+    fn from(other: f64) -> Self {
+        Self::from_micro(round(other * 1e6_f64))
+    }
+}
+
+/// Rounds half away from zero, `f64::round` is not available in core. The cast truncates towards
+/// zero and saturates.
+fn round(value: f64) -> i64 {
+    if value < 0.0 {
+        (value - 0.5) as i64
+    } else {
+        (value + 0.5) as i64
+    }
+}
+
+impl PartialEq for SensorValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_micro() == other.to_micro()
+    }
+}
+
+impl Eq for SensorValue {}
+
+impl PartialOrd for SensorValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SensorValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.to_micro().cmp(&other.to_micro())
+    }
+}
+
+impl Add for SensorValue {
+    type Output = SensorValue;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::from_micro(self.to_micro() + rhs.to_micro())
+    }
+}
+
+impl Sub for SensorValue {
+    type Output = SensorValue;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::from_micro(self.to_micro() - rhs.to_micro())
+    }
+}
+
+impl Mul for SensorValue {
+    type Output = SensorValue;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let micro = (self.to_micro() as i128 * rhs.to_micro() as i128) / 1_000_000;
+        Self::from_wide_micro(micro)
+    }
+}
+
+impl Div for SensorValue {
+    type Output = SensorValue;
+
+    /// Divides two sensor values. Panics if `rhs` is zero.
+    fn div(self, rhs: Self) -> Self::Output {
+        let micro = (self.to_micro() as i128 * 1_000_000) / rhs.to_micro() as i128;
+        Self::from_wide_micro(micro)
+    }
+}
+
+impl Display for SensorValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let micro = self.to_micro();
+        let sign = if micro < 0 { "-" } else { "" };
+        let micro = micro.abs();
+        write!(f, "{}{}.{:06}", sign, micro / 1_000_000, micro % 1_000_000)
+    }
+}
+
+/// Non-exhaustive list of sensor channels. The list uses the values from Zephyr header files and
+/// might fail to compile if two or more sensor channels use the same representation.
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+pub enum SensorChannel {
+    All = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_ALL,
+    AmbientTemperature = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_AMBIENT_TEMP,
+    DieTemperature = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_DIE_TEMP,
+    Pressure = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_PRESS,
+    AccelX = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_ACCEL_X,
+    AccelY = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_ACCEL_Y,
+    AccelZ = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_ACCEL_Z,
+    AccelXYZ = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_ACCEL_XYZ,
+    GyroX = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_GYRO_X,
+    GyroY = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_GYRO_Y,
+    GyroZ = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_GYRO_Z,
+    GyroXYZ = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_GYRO_XYZ,
+    MagnX = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_MAGN_X,
+    MagnY = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_MAGN_Y,
+    MagnZ = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_MAGN_Z,
+    MagnXYZ = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_MAGN_XYZ,
+    Humidity = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_HUMIDITY,
+    Light = zephyr_sys::raw::sensor_channel_SENSOR_CHAN_LIGHT,
+}
+
+impl SensorChannel {
+    /// Returns `true` if the channel yields three values (x, y and z) when being read.
+    pub fn is_xyz(&self) -> bool {
+        matches!(
+            self,
+            SensorChannel::AccelXYZ | SensorChannel::GyroXYZ | SensorChannel::MagnXYZ
+        )
+    }
+}
+
+/// Three-dimensional vector as returned by the XYZ sensor channels (e.g. [SensorChannel::AccelXYZ]).
+#[derive(Copy, Clone, Debug)]
+pub struct Vector3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// List of the generic sensor attributes. The list uses the values from Zephyr header files and
+/// might fail to compile if two or more sensor channels use the same representation.
+#[repr(u32)]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub enum SensorAttribute {
+    SamplingFrequency = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_SAMPLING_FREQUENCY,
+    LowerThreshold = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_LOWER_THRESH,
+    UpperThreshold = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_UPPER_THRESH,
+    SlopeThreshold = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_SLOPE_TH,
+    SlopeDuration = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_SLOPE_DUR,
+    Hysteresis = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_HYSTERESIS,
+    Oversampling = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_OVERSAMPLING,
+    FullScale = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_FULL_SCALE,
+    Offset = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_OFFSET,
+    CalibrationTarget = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_CALIB_TARGET,
+    Configuration = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_CONFIGURATION,
+    Calibration = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_CALIBRATION,
+    FeatureMask = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_FEATURE_MASK,
+    Alert = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_ALERT,
+    FreefallDuration = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_FF_DUR,
+    BatchDuration = zephyr_sys::raw::sensor_attribute_SENSOR_ATTR_BATCH_DURATION,
+}
+
+/// Non-exhaustive list of sensor trigger types. The list uses the values from Zephyr header files and
+/// might fail to compile if two or more sensor channels use the same representation.
+#[repr(u32)]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub enum TriggerType {
+    Timer = zephyr_sys::raw::sensor_trigger_type_SENSOR_TRIG_TIMER,
+    DataReady = zephyr_sys::raw::sensor_trigger_type_SENSOR_TRIG_DATA_READY,
+    Delta = zephyr_sys::raw::sensor_trigger_type_SENSOR_TRIG_DELTA,
+    NearFar = zephyr_sys::raw::sensor_trigger_type_SENSOR_TRIG_NEAR_FAR,
+    Threshold = zephyr_sys::raw::sensor_trigger_type_SENSOR_TRIG_THRESHOLD,
+    Tap = zephyr_sys::raw::sensor_trigger_type_SENSOR_TRIG_TAP,
+    DoubleTap = zephyr_sys::raw::sensor_trigger_type_SENSOR_TRIG_DOUBLE_TAP,
+    Freefall = zephyr_sys::raw::sensor_trigger_type_SENSOR_TRIG_FREEFALL,
+    Motion = zephyr_sys::raw::sensor_trigger_type_SENSOR_TRIG_MOTION,
+    Stationary = zephyr_sys::raw::sensor_trigger_type_SENSOR_TRIG_STATIONARY,
+    FifoWatermark = zephyr_sys::raw::sensor_trigger_type_SENSOR_TRIG_FIFO_WATERMARK,
+    FifoFull = zephyr_sys::raw::sensor_trigger_type_SENSOR_TRIG_FIFO_FULL,
+}
+
+/// Wrapper to the `sensor_sample_fetch_chan` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// This method will fail if the sensor does not support `sensor_channel`.
+pub unsafe fn sample_fetch_channel(
+    device: &Device,
+    sensor_channel: SensorChannel,
+) -> Result<(), ZephyrError> {
+    let errno = unsafe {
+        crate::sys::sensor_sample_fetch_chan(device as *const Device, sensor_channel as u32)
+    };
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `sensor_sample_fetch` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// In contrast to [sample_fetch_channel] this fetches a sample of all channels at once. Some drivers
+/// (e.g. most IMUs) only support this kind of fetch.
+pub unsafe fn sample_fetch(device: &Device) -> Result<(), ZephyrError> {
+    let errno = unsafe { crate::sys::sensor_sample_fetch(device as *const Device) };
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `sensor_channel_get` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// This method will fail if the sensor does not support `sensor_channel`.
+pub unsafe fn channel_get(
+    device: &Device,
+    sensor_channel: SensorChannel,
+) -> Result<SensorValue, ZephyrError> {
+    let mut z_sensor_value = ZSensorValue { val1: 0, val2: 0 };
+    let errno = unsafe {
+        crate::sys::sensor_channel_get(
+            device as *const Device,
+            sensor_channel as u32,
+            (&mut z_sensor_value) as *mut ZSensorValue,
+        )
+    };
+
+    if errno == 0 {
+        Ok(z_sensor_value.into())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `sensor_channel_get` syscall for channels that yield three values.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// This method will fail with [ErrorNumber::InvalidArgument] if `sensor_channel` is not an XYZ
+/// channel (see [SensorChannel::is_xyz]) or if the sensor does not support `sensor_channel`.
+pub unsafe fn channel_get_xyz(
+    device: &Device,
+    sensor_channel: SensorChannel,
+) -> Result<Vector3<SensorValue>, ZephyrError> {
+    if !sensor_channel.is_xyz() {
+        return Err(ZephyrError::new_with_context(
+            ErrorNumber::InvalidArgument,
+            &CONTEXT,
+        ));
+    }
+
+    // the driver writes one value per axis for XYZ channels
+    let mut z_sensor_values = [ZSensorValue { val1: 0, val2: 0 }; 3];
+    let errno = unsafe {
+        crate::sys::sensor_channel_get(
+            device as *const Device,
+            sensor_channel as u32,
+            z_sensor_values.as_mut_ptr(),
+        )
+    };
+
+    if errno == 0 {
+        Ok(Vector3 {
+            x: z_sensor_values[0].into(),
+            y: z_sensor_values[1].into(),
+            z: z_sensor_values[2].into(),
+        })
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `sensor_channel_get` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// This method might fail if the sensor driver does not expose this driver call or if the sensor
+/// does not support the channel or the channel does not support the attribute.
+pub unsafe fn attr_set(
+    device: &Device,
+    sensor_channel: SensorChannel,
+    sensor_attribute: SensorAttribute,
+    value: SensorValue,
+) -> Result<(), ZephyrError> {
+    let z_sensor_value: ZSensorValue = value.into();
+    let errno = unsafe {
+        crate::sys::sensor_attr_set(
+            device as *const Device,
+            sensor_channel as u32,
+            sensor_attribute as u32,
+            (&z_sensor_value) as *const ZSensorValue,
+        )
+    };
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Implementation of the high level function `sensor_trigger_set` which is inlined in the Zephyr API.
+/// The method essentially does the same as the original inline function but also checks if the driver
+/// API is present in `device`.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// This method might fail if the sensor driver does not expose this driver call or if the sensor
+/// does not support the channel or the channel does not support the attribute.
+pub unsafe fn trigger_set(
+    device: &Device,
+    sensor_trigger: &SensorTrigger,
+    f: extern "C" fn(dev: &'static Device, trigger: &SensorTrigger),
+) -> Result<(), ZephyrError> {
+    use zephyr_sys::raw::sensor_driver_api as SensorDriverApi;
+    // convert void pointer from C API to a sensor driver API Rust struct
+    let api: Option<&SensorDriverApi> = core::mem::transmute(device.api);
+
+    if let Some(api) = api {
+        if let Some(trigger_set) = api.trigger_set {
+            // convert safe Rust function pointer to pointer for binding. This can be done because
+            // we use C calling convention for both functions (extern "C") and as per the Rustonomicon
+            // a typed reference is effectively a (slim-)pointer. Using Option is not necessary because
+            // the references are guaranteed to be non-null by Zephyr.
+            let callback: extern "C" fn(dev: *const Device, trigger: *const SensorTrigger) =
+                core::mem::transmute(f);
+            // function pointers need to be called like this
+            let errno = (trigger_set)(
+                device as *const Device,
+                sensor_trigger as *const SensorTrigger,
+                Some(callback),
+            );
+
+            if errno == 0 {
+                Ok(())
+            } else {
+                Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+            }
+        } else {
+            Err(ZephyrError::new_with_context(
+                ErrorNumber::NotImplemented,
+                &CONTEXT,
+            ))
+        }
+    } else {
+        Err(ZephyrError::new_with_context(
+            ErrorNumber::NotImplemented,
+            &CONTEXT,
+        ))
+    }
+}
+
+/// Removes the trigger handler of `sensor_trigger` by passing a `NULL` handler to the driver call
+/// `trigger_set` which is inlined in the Zephyr API.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the driver API and wraps the error number in a safe error type.
+///
+/// This method might fail if the sensor driver does not expose this driver call or if the sensor
+/// does not support the trigger.
+pub unsafe fn trigger_unset(
+    device: &Device,
+    sensor_trigger: &SensorTrigger,
+) -> Result<(), ZephyrError> {
+    use zephyr_sys::raw::sensor_driver_api as SensorDriverApi;
+    // convert void pointer from C API to a sensor driver API Rust struct
+    let api: Option<&SensorDriverApi> = core::mem::transmute(device.api);
+
+    match api.and_then(|api| api.trigger_set) {
+        Some(trigger_set) => {
+            let errno = (trigger_set)(
+                device as *const Device,
+                sensor_trigger as *const SensorTrigger,
+                None,
+            );
+
+            if errno == 0 {
+                Ok(())
+            } else {
+                Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+            }
+        }
+        None => Err(ZephyrError::new_with_context(
+            ErrorNumber::NotImplemented,
+            &CONTEXT,
+        )),
+    }
+}
+
+/// Wrapper to the `device_is_ready` syscall.
+pub fn is_ready(device: &'static Device) -> bool {
+    crate::device::is_ready(device)
+}
+
+/// Checks if the driver API of `device` is a sensor driver API. This is the equivalent of the
+/// `DEVICE_API_IS(sensor, device)` macro of the Zephyr API.
+pub fn is_sensor_api(device: &'static Device) -> bool {
+    class::Sensor::is_api(device)
+}
+
+/// High level wrapper for a sensor.
+///
+/// This essentially wraps the static reference of the underlying device and offers methods that use
+/// the safe syscall wrappers for an object oriented programming interface.
+pub struct Sensor {
+    device: &'static Device,
+}
+
+impl Sensor {
+    /// Creates a new [Sensor] on the current interface.
+    ///
+    /// `device` MUST be a sensor representing a device. If `device` is not a sensor the behaviour
+    /// when calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Sensor { device }
+    }
+
+    /// Creates a new [Sensor] after checking that `device` is a sensor.
+    ///
+    /// This method fails with [ErrorNumber::NoDevice] if the device is not ready (see
+    /// `device_is_ready`) and with [ErrorNumber::NotSupported] if the driver API of the device is
+    /// not a sensor driver API.
+    pub fn try_new(device: &'static Device) -> Result<Self, ZephyrError> {
+        if !is_ready(device) {
+            return Err(ZephyrError::new_with_context(ErrorNumber::NoDevice, &CONTEXT));
+        }
+
+        if !is_sensor_api(device) {
+            return Err(ZephyrError::new_with_context(
+                ErrorNumber::NotSupported,
+                &CONTEXT,
+            ));
+        }
+
+        Ok(Sensor { device })
+    }
+
+    /// Creates a new [Sensor] from a device checked to be a sensor.
+    pub fn from_device(device: TypedDevice<class::Sensor>) -> Self {
+        Sensor {
+            device: device.device(),
+        }
+    }
+
+    /// Fetch `channel` and then read the value from the internal buffer.
+    ///
+    /// This method might fail if the sensor does not support the requested channel.
+    pub fn sample(&mut self, channel: SensorChannel) -> Result<SensorValue, ZephyrError> {
+        // device is required to be a sensor device in constructor
+        unsafe {
+            sample_fetch_channel(self.device, channel)?;
+            channel_get(self.device, channel)
+        }
+    }
+
+    /// Fetch a sample of all channels into the internal buffer of the driver.
+    ///
+    /// The values can be read afterwards with [Sensor::get]. Use this instead of [Sensor::sample]
+    /// for drivers that do not support fetching single channels.
+    pub fn fetch(&mut self) -> Result<(), ZephyrError> {
+        // device is required to be a sensor device in constructor
+        unsafe { sample_fetch(self.device) }
+    }
+
+    /// Read the value of `channel` from the internal buffer without fetching a new sample.
+    ///
+    /// This method might fail if the sensor does not support the requested channel.
+    pub fn get(&mut self, channel: SensorChannel) -> Result<SensorValue, ZephyrError> {
+        // device is required to be a sensor device in constructor
+        unsafe { channel_get(self.device, channel) }
+    }
+
+    /// Fetch a sample of all channels and read the three values of the XYZ channel `channel`.
+    ///
+    /// All three axes are taken from the same sample. This method fails with
+    /// [ErrorNumber::InvalidArgument] if `channel` is not an XYZ channel.
+    pub fn sample_xyz(
+        &mut self,
+        channel: SensorChannel,
+    ) -> Result<Vector3<SensorValue>, ZephyrError> {
+        // device is required to be a sensor device in constructor
+        unsafe {
+            sample_fetch(self.device)?;
+            channel_get_xyz(self.device, channel)
+        }
+    }
+
+    /// Set the attribute of the channel to the given sensor value.
+    ///
+    /// This method might fail if the sensor does not expose the driver API, the sensor channel or
+    /// the channel does not support the sensor attribute.
+    pub fn set_attr(
+        &mut self,
+        channel: SensorChannel,
+        attribute: SensorAttribute,
+        value: SensorValue,
+    ) -> Result<(), ZephyrError> {
+        // device is required to be a sensor device in constructor
+        unsafe { attr_set(self.device, channel, attribute, value) }
+    }
+
+    /// Set the [SensorAttribute::LowerThreshold] attribute of `channel`. See [Sensor::set_attr] for details.
+    pub fn set_lower_threshold(
+        &mut self,
+        channel: SensorChannel,
+        value: SensorValue,
+    ) -> Result<(), ZephyrError> {
+        self.set_attr(channel, SensorAttribute::LowerThreshold, value)
+    }
+
+    /// Set the [SensorAttribute::UpperThreshold] attribute of `channel`. See [Sensor::set_attr] for details.
+    pub fn set_upper_threshold(
+        &mut self,
+        channel: SensorChannel,
+        value: SensorValue,
+    ) -> Result<(), ZephyrError> {
+        self.set_attr(channel, SensorAttribute::UpperThreshold, value)
+    }
+
+    /// Install a trigger of type `trigger_type` on `channel`.
+    ///
+    /// `f` is the callback function. The callback function will be called with the device the trigger
+    /// has occurred on and the configuration that has been passed with the trigger.
+    ///
+    /// The callback uses a few tricks described in [The Nomicon](https://doc.rust-lang.org/nomicon/ffi.html#the-nullable-pointer-optimization)
+    /// to allow the usage of Rust types in the callback. Also note that the callback [MUST NOT panic](https://doc.rust-lang.org/nomicon/ffi.html#ffi-and-panics).
+    pub fn enable_trigger(
+        &mut self,
+        trigger_type: TriggerType,
+        channel: SensorChannel,
+        f: extern "C" fn(dev: &'static Device, trigger: &SensorTrigger),
+    ) -> Result<(), ZephyrError> {
+        let sensor_trigger = SensorTrigger {
+            type_: trigger_type as u32,
+            chan: channel as u32,
+        };
+        /// device is required to be a sensor device in constructor
+        unsafe {
+            trigger_set(self.device, &sensor_trigger, f)
+        }
+    }
+
+    /// Install a trigger of type `trigger_type` on `channel` that calls the closure `f`.
+    ///
+    /// In contrast to [Sensor::enable_trigger] the closure may capture application state. The
+    /// closure is called with a [Sensor] handle of the device the trigger has occurred on and the
+    /// configuration that has been passed with the trigger. Installing a trigger for the same type
+    /// and channel again replaces the previously installed closure, if installing fails the
+    /// previous closure stays installed.
+    ///
+    /// The closure is called in the context the driver reports the trigger in, e.g. its own
+    /// thread, the system work queue or an interrupt. The closure MUST NOT panic.
+    pub fn enable_trigger_with<F>(
+        &mut self,
+        trigger_type: TriggerType,
+        channel: SensorChannel,
+        f: F,
+    ) -> Result<(), ZephyrError>
+    where
+        F: FnMut(&mut Sensor, &SensorTrigger) + Send + 'static,
+    {
+        let key = TriggerKey {
+            device: self.device as *const Device as usize,
+            trigger: SensorTrigger {
+                type_: trigger_type as u32,
+                chan: channel as u32,
+            },
+        };
+        let inserted = TRIGGER_HANDLERS.insert(key, Box::new(f));
+
+        // some drivers keep the trigger pointer, so pass the one that lives in the entry
+        let trigger: &SensorTrigger = unsafe { &(*inserted.key).trigger };
+        // device is required to be a sensor device in constructor
+        let result = unsafe { trigger_set(self.device, trigger, trigger_trampoline) };
+        if result.is_err() {
+            if inserted.added {
+                TRIGGER_HANDLERS.remove_id(inserted.id);
+            } else {
+                // the driver still holds the trigger of the entry from the previous installation
+                TRIGGER_HANDLERS.restore(inserted.id, inserted.replaced);
+            }
+        }
+        result
+    }
+
+    /// Remove the trigger of type `trigger_type` on `channel`.
+    ///
+    /// This removes triggers installed with [Sensor::enable_trigger] as well as
+    /// [Sensor::enable_trigger_with]. A closure installed for the trigger is dropped.
+    pub fn disable_trigger(
+        &mut self,
+        trigger_type: TriggerType,
+        channel: SensorChannel,
+    ) -> Result<(), ZephyrError> {
+        let key = TriggerKey {
+            device: self.device as *const Device as usize,
+            trigger: SensorTrigger {
+                type_: trigger_type as u32,
+                chan: channel as u32,
+            },
+        };
+        // device is required to be a sensor device in constructor
+        unsafe { trigger_unset(self.device, &key.trigger)? };
+
+        TRIGGER_HANDLERS.remove(&key);
+        Ok(())
+    }
+}
+
+/// Device and trigger a closure installed with [Sensor::enable_trigger_with] is called for.
+struct TriggerKey {
+    device: usize,
+    trigger: SensorTrigger,
+}
+
+impl TriggerKey {
+    fn matches(&self, device: &Device, trigger: &SensorTrigger) -> bool {
+        self.device == device as *const Device as usize
+            && self.trigger.type_ == trigger.type_
+            && self.trigger.chan == trigger.chan
+    }
+}
+
+impl PartialEq for TriggerKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.device == other.device
+            && self.trigger.type_ == other.trigger.type_
+            && self.trigger.chan == other.trigger.chan
+    }
+}
+
+type TriggerHandler = dyn FnMut(&mut Sensor, &SensorTrigger) + Send;
+
+static TRIGGER_HANDLERS: Handlers<TriggerKey, TriggerHandler> = Handlers::new();
+
+/// C callback that dispatches a trigger to the closure registered for the device and trigger.
+extern "C" fn trigger_trampoline(dev: &'static Device, trigger: &SensorTrigger) {
+    TRIGGER_HANDLERS.call(
+        |key| key.matches(dev, trigger),
+        |handler| {
+            // the trigger could only be installed on a sensor device
+            let mut sensor = Sensor { device: dev };
+            handler(&mut sensor, trigger)
+        },
+    );
+}
+
+#[derive(Debug)]
+struct SensorWrapperContext {}
+
+impl Context for SensorWrapperContext {
+    fn name(&self) -> &'static str {
+        "sensor wrapper"
+    }
+}
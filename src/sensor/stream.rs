@@ -0,0 +1,91 @@
+//! Periodic sampling of sensor channels.
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::sensor::{Sensor, SensorChannel};
+//!# use std::time::Duration;
+//!# fn log(sensor: &mut Sensor) {
+//! let stream = sensor
+//!     .stream(SensorChannel::AmbientTemperature, Duration::from_secs(1))
+//!     .expect("period is not zero");
+//! for value in stream.take(10) {
+//!     println!("{}", value.expect("sampling value from sensor"));
+//! }
+//!# }
+//! ```
+
+use super::{Sensor, SensorChannel, SensorValue, CONTEXT};
+use crate::time::timeout;
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use core::time::Duration;
+use zephyr_sys::raw::k_timer;
+
+/// Iterator that samples a channel of a sensor once per period, see [Sensor::stream].
+///
+/// Each call to `next` blocks until the next period of the internal kernel timer expired. Periods
+/// that expired while the iterator was not polled are skipped, so the iterator never yields a burst
+/// of samples.
+pub struct SensorStream<'s> {
+    sensor: &'s mut Sensor,
+    channel: SensorChannel,
+    // boxed as the kernel keeps a pointer to the timer while it is running
+    timer: Box<k_timer>,
+}
+
+impl<'s> SensorStream<'s> {
+    fn new(sensor: &'s mut Sensor, channel: SensorChannel, period: Duration) -> Self {
+        let mut timer: Box<k_timer> = Box::new(unsafe { core::mem::zeroed() });
+        unsafe {
+            zephyr_sys::raw::k_timer_init(&mut *timer as *mut k_timer, None, None);
+            zephyr_sys::syscalls::any::k_timer_start(
+                &mut *timer as *mut k_timer,
+                timeout(period),
+                timeout(period),
+            );
+        }
+
+        Self {
+            sensor,
+            channel,
+            timer,
+        }
+    }
+}
+
+impl Iterator for SensorStream<'_> {
+    type Item = ZephyrResult<SensorValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe { zephyr_sys::syscalls::any::k_timer_status_sync(&mut *self.timer as *mut k_timer) };
+        Some(self.sensor.sample(self.channel))
+    }
+}
+
+impl Drop for SensorStream<'_> {
+    fn drop(&mut self) {
+        unsafe { zephyr_sys::syscalls::any::k_timer_stop(&mut *self.timer as *mut k_timer) };
+    }
+}
+
+impl Sensor {
+    /// Returns an iterator that samples `channel` once every `period`.
+    ///
+    /// The first value is sampled after the first period expired. See [Sensor::sample] for the
+    /// errors the iterator might yield.
+    ///
+    /// This method fails with [ErrorNumber::InvalidArgument] if `period` is zero, as the kernel
+    /// timer would only expire once.
+    pub fn stream(
+        &mut self,
+        channel: SensorChannel,
+        period: Duration,
+    ) -> ZephyrResult<SensorStream<'_>> {
+        if period.is_zero() {
+            return Err(ZephyrError::new_with_context(
+                ErrorNumber::InvalidArgument,
+                &CONTEXT,
+            ));
+        }
+        Ok(SensorStream::new(self, channel, period))
+    }
+}
@@ -0,0 +1,248 @@
+//! Typed units for sensor readings.
+//!
+//! Each unit wraps a [SensorValue] in the unit given by the type. The channel specific accessors
+//! of [Sensor] (e.g. [Sensor::temperature]) return these types, so values of different channels
+//! cannot be mixed up accidentally.
+//!
+//! ```rust
+//!# use zephyr_rust_wrappers::sensor::units::Celsius;
+//! let temperature = Celsius::from(21.5_f32);
+//!
+//! assert_eq!(21.5_f32, temperature.into());
+//! assert_eq!("21.500000 °C", temperature.to_string());
+//! ```
+
+use super::{Sensor, SensorAttribute, SensorChannel, SensorValue, Vector3};
+use crate::ZephyrResult;
+use core::fmt::{Display, Formatter};
+
+macro_rules! sensor_unit {
+    ($(#[$meta: meta])* $name: ident, $symbol: expr) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+        pub struct $name(pub SensorValue);
+
+        impl $name {
+            /// Returns the value in this unit.
+            pub fn value(&self) -> SensorValue {
+                self.0
+            }
+        }
+
+        impl From<SensorValue> for $name {
+            fn from(other: SensorValue) -> Self {
+                Self(other)
+            }
+        }
+
+        impl From<$name> for SensorValue {
+            fn from(other: $name) -> Self {
+                other.0
+            }
+        }
+
+        impl From<f32> for $name {
+            fn from(other: f32) -> Self {
+                Self(other.into())
+            }
+        }
+
+        impl From<$name> for f32 {
+            fn from(other: $name) -> Self {
+                other.0.into()
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                write!(f, "{} {}", self.0, $symbol)
+            }
+        }
+    };
+}
+
+sensor_unit!(
+    /// Temperature in degrees Celsius.
+    Celsius,
+    "°C"
+);
+sensor_unit!(
+    /// Pressure in Pascal. Note that Zephyr reports pressure in kilopascal.
+    Pascal,
+    "Pa"
+);
+sensor_unit!(
+    /// Acceleration in metres per second squared.
+    MetresPerSecondSquared,
+    "m/s²"
+);
+sensor_unit!(
+    /// Angular velocity in radians per second.
+    RadiansPerSecond,
+    "rad/s"
+);
+sensor_unit!(
+    /// Magnetic field in Gauss.
+    Gauss,
+    "Gs"
+);
+sensor_unit!(
+    /// Relative humidity in percent.
+    Percent,
+    "%"
+);
+sensor_unit!(
+    /// Illuminance in Lux.
+    Lux,
+    "lx"
+);
+sensor_unit!(
+    /// Frequency in Hertz.
+    Hertz,
+    "Hz"
+);
+
+/// Standard gravity in metres per second squared, used to convert `g` to [MetresPerSecondSquared].
+pub const STANDARD_GRAVITY: SensorValue = SensorValue::from_micro(9_806_650);
+
+/// Conversion of a unit to the value that Zephyr uses for the channels of this unit.
+///
+/// This is used by the attribute helpers of [Sensor] (e.g. [Sensor::set_thresholds]) to convert
+/// values to the representation expected by the drivers.
+pub trait ChannelValue: Copy {
+    fn channel_value(self) -> SensorValue;
+}
+
+impl ChannelValue for SensorValue {
+    fn channel_value(self) -> SensorValue {
+        self
+    }
+}
+
+macro_rules! channel_value {
+    ($($name: ident),*) => {
+        $(
+            impl ChannelValue for $name {
+                fn channel_value(self) -> SensorValue {
+                    self.0
+                }
+            }
+        )*
+    };
+}
+
+channel_value!(Celsius, MetresPerSecondSquared, RadiansPerSecond, Gauss, Percent, Lux, Hertz);
+
+impl ChannelValue for Pascal {
+    fn channel_value(self) -> SensorValue {
+        // Zephyr uses kilopascal for pressure channels
+        SensorValue::from_micro(self.0.to_micro() / 1000)
+    }
+}
+
+impl Pascal {
+    /// Convert a value in kilopascal, as reported by Zephyr, to Pascal.
+    pub fn from_kilopascal(kilopascal: SensorValue) -> Self {
+        Self(SensorValue::from_micro(kilopascal.to_micro() * 1000))
+    }
+}
+
+fn vector_into<U: From<SensorValue>>(vector: Vector3<SensorValue>) -> Vector3<U> {
+    Vector3 {
+        x: vector.x.into(),
+        y: vector.y.into(),
+        z: vector.z.into(),
+    }
+}
+
+impl Sensor {
+    /// Sample [SensorChannel::AmbientTemperature]. See [Sensor::sample] for details.
+    pub fn temperature(&mut self) -> ZephyrResult<Celsius> {
+        self.sample(SensorChannel::AmbientTemperature).map(Celsius)
+    }
+
+    /// Sample [SensorChannel::Pressure]. See [Sensor::sample] for details.
+    pub fn pressure(&mut self) -> ZephyrResult<Pascal> {
+        self.sample(SensorChannel::Pressure).map(Pascal::from_kilopascal)
+    }
+
+    /// Sample [SensorChannel::Humidity]. See [Sensor::sample] for details.
+    pub fn humidity(&mut self) -> ZephyrResult<Percent> {
+        self.sample(SensorChannel::Humidity).map(Percent)
+    }
+
+    /// Sample [SensorChannel::Light]. See [Sensor::sample] for details.
+    pub fn light(&mut self) -> ZephyrResult<Lux> {
+        self.sample(SensorChannel::Light).map(Lux)
+    }
+
+    /// Sample [SensorChannel::AccelXYZ]. See [Sensor::sample_xyz] for details.
+    pub fn acceleration(&mut self) -> ZephyrResult<Vector3<MetresPerSecondSquared>> {
+        self.sample_xyz(SensorChannel::AccelXYZ).map(vector_into)
+    }
+
+    /// Sample [SensorChannel::GyroXYZ]. See [Sensor::sample_xyz] for details.
+    pub fn angular_velocity(&mut self) -> ZephyrResult<Vector3<RadiansPerSecond>> {
+        self.sample_xyz(SensorChannel::GyroXYZ).map(vector_into)
+    }
+
+    /// Sample [SensorChannel::MagnXYZ]. See [Sensor::sample_xyz] for details.
+    pub fn magnetic_field(&mut self) -> ZephyrResult<Vector3<Gauss>> {
+        self.sample_xyz(SensorChannel::MagnXYZ).map(vector_into)
+    }
+
+    /// Set the [SensorAttribute::SamplingFrequency] of `channel` to `frequency` Hertz.
+    pub fn set_sampling_frequency_hz(
+        &mut self,
+        channel: SensorChannel,
+        frequency: f32,
+    ) -> ZephyrResult<()> {
+        self.set_attr(
+            channel,
+            SensorAttribute::SamplingFrequency,
+            Hertz::from(frequency).channel_value(),
+        )
+    }
+
+    /// Set the [SensorAttribute::FullScale] of the acceleration channel `channel` to ±`g` times
+    /// the standard gravity.
+    pub fn set_full_scale_g(&mut self, channel: SensorChannel, g: u8) -> ZephyrResult<()> {
+        let full_scale = MetresPerSecondSquared(STANDARD_GRAVITY * SensorValue::from(g as i32));
+        self.set_attr(channel, SensorAttribute::FullScale, full_scale.channel_value())
+    }
+
+    /// Set the [SensorAttribute::Oversampling] of `channel` to `ratio`.
+    pub fn set_oversampling(&mut self, channel: SensorChannel, ratio: u16) -> ZephyrResult<()> {
+        self.set_attr(
+            channel,
+            SensorAttribute::Oversampling,
+            SensorValue::from(ratio as i32),
+        )
+    }
+
+    /// Set the [SensorAttribute::LowerThreshold] and [SensorAttribute::UpperThreshold] of
+    /// `channel`. The values are converted to the unit Zephyr uses for `channel`, e.g.
+    ///
+    /// ```no_run
+    ///# use zephyr_rust_wrappers::sensor::{Sensor, SensorChannel};
+    ///# use zephyr_rust_wrappers::sensor::units::Celsius;
+    ///# fn configure(sensor: &mut Sensor) {
+    /// sensor
+    ///     .set_thresholds(
+    ///         SensorChannel::AmbientTemperature,
+    ///         Celsius::from(18.0),
+    ///         Celsius::from(25.5),
+    ///     )
+    ///     .expect("setting thresholds");
+    ///# }
+    /// ```
+    pub fn set_thresholds<U: ChannelValue>(
+        &mut self,
+        channel: SensorChannel,
+        lower: U,
+        upper: U,
+    ) -> ZephyrResult<()> {
+        self.set_lower_threshold(channel, lower.channel_value())?;
+        self.set_upper_threshold(channel, upper.channel_value())
+    }
+}
@@ -0,0 +1,294 @@
+//! Wrappers for the Zephyr settings subsystem.
+//!
+//! Values are stored as byte strings under `/` separated names like `app/volume`. Rust handlers for
+//! a subtree implement [SettingsHandler] and are registered with [register]. The handler callbacks
+//! of the C API carry no user data, so every handler type gets its own C callbacks and at most one
+//! handler is registered per type.
+
+use crate::handlers::Handlers;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::cell::UnsafeCell;
+use core::ffi::{c_char, c_int, c_void, CStr};
+use zephyr_sys::raw::{settings_handler as ZSettingsHandler, settings_read_cb};
+
+const CONTEXT: SettingsWrapperContext = SettingsWrapperContext {};
+
+fn c_string(name: &str) -> ZephyrResult<CString> {
+    CString::new(name)
+        .map_err(|_| ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT))
+}
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to `settings_subsys_init`, initializes the storage backend.
+pub fn init() -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::settings_subsys_init() })
+}
+
+/// Wrapper to `settings_load`, passes all stored values to the registered handlers.
+pub fn load() -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::settings_load() })
+}
+
+/// Wrapper to `settings_load_subtree`, passes the stored values of `subtree` to its handlers.
+pub fn load_subtree(subtree: &str) -> ZephyrResult<()> {
+    let subtree = c_string(subtree)?;
+    result(unsafe { zephyr_sys::raw::settings_load_subtree(subtree.as_ptr()) })
+}
+
+/// Wrapper to `settings_save`, stores the values exported by all registered handlers.
+pub fn save() -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::settings_save() })
+}
+
+/// Wrapper to `settings_save_one`, stores `value` under `name`.
+pub fn save_one(name: &str, value: &[u8]) -> ZephyrResult<()> {
+    let name = c_string(name)?;
+    result(unsafe {
+        zephyr_sys::raw::settings_save_one(
+            name.as_ptr(),
+            value.as_ptr() as *const c_void,
+            value.len(),
+        )
+    })
+}
+
+/// Wrapper to `settings_delete`, removes the value stored under `name`.
+pub fn delete(name: &str) -> ZephyrResult<()> {
+    let name = c_string(name)?;
+    result(unsafe { zephyr_sys::raw::settings_delete(name.as_ptr()) })
+}
+
+/// A value passed to [SettingsHandler::set] that is read from the storage on demand.
+pub struct SettingsValue {
+    len: usize,
+    read_cb: settings_read_cb,
+    cb_arg: *mut c_void,
+}
+
+impl SettingsValue {
+    /// Length of the stored value, `0` if the value has been deleted.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the value has been deleted.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Read the value into `buffer`, returns the number of bytes read.
+    pub fn read(&mut self, buffer: &mut [u8]) -> ZephyrResult<usize> {
+        let read_cb = self
+            .read_cb
+            .ok_or_else(|| ZephyrError::new_with_context(ErrorNumber::Io, &CONTEXT))?;
+        let read =
+            unsafe { (read_cb)(self.cb_arg, buffer.as_mut_ptr() as *mut c_void, buffer.len()) };
+
+        if read >= 0 {
+            Ok(read as usize)
+        } else {
+            Err(ZephyrError::from_errno_with_context(read as i32, &CONTEXT))
+        }
+    }
+
+    /// Read the whole value.
+    pub fn read_to_vec(&mut self) -> ZephyrResult<Vec<u8>> {
+        let mut buffer = vec![0; self.len];
+        let read = self.read(&mut buffer)?;
+        buffer.truncate(read);
+        Ok(buffer)
+    }
+}
+
+/// Sink for the values of a handler passed to [SettingsHandler::export].
+pub struct Exporter {
+    export_func: Option<
+        unsafe extern "C" fn(name: *const c_char, value: *const c_void, len: usize) -> c_int,
+    >,
+}
+
+impl Exporter {
+    /// Export `value` under the full `name`, including the subtree of the handler.
+    pub fn export(&mut self, name: &str, value: &[u8]) -> ZephyrResult<()> {
+        let export_func = self
+            .export_func
+            .ok_or_else(|| ZephyrError::new_with_context(ErrorNumber::Io, &CONTEXT))?;
+        let name = c_string(name)?;
+        result(unsafe {
+            (export_func)(name.as_ptr(), value.as_ptr() as *const c_void, value.len())
+        })
+    }
+}
+
+/// Handler for the values of a subtree of the settings.
+///
+/// Keys passed to the handler are relative to the subtree it is registered for.
+pub trait SettingsHandler: Send + 'static {
+    /// Called for every stored value of the subtree while loading.
+    fn set(&mut self, key: &str, value: &mut SettingsValue) -> ZephyrResult<()>;
+
+    /// Copy the current value of `key` into `buffer` and return its length, `None` if unknown.
+    fn get(&mut self, _key: &str, _buffer: &mut [u8]) -> Option<usize> {
+        None
+    }
+
+    /// Called after all values have been loaded.
+    fn commit(&mut self) -> ZephyrResult<()> {
+        Ok(())
+    }
+
+    /// Export all current values for [save].
+    fn export(&mut self, _exporter: &mut Exporter) -> ZephyrResult<()> {
+        Ok(())
+    }
+}
+
+/// Type of a handler registered with [register] together with its C handler.
+struct HandlerKey {
+    type_id: TypeId,
+    _name: CString,
+    raw: UnsafeCell<ZSettingsHandler>,
+}
+
+// the C handler only points to the name in the key and to the trampolines
+unsafe impl Send for HandlerKey {}
+
+/// Only one handler is registered per type.
+impl PartialEq for HandlerKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_id == other.type_id
+    }
+}
+
+static HANDLERS: Handlers<HandlerKey, dyn SettingsHandler> = Handlers::new();
+
+/// Calls `f` with the handler of type `H`, `None` if it is not registered or called already.
+fn with_handler<H, R>(f: impl FnOnce(&mut (dyn SettingsHandler + 'static)) -> R) -> Option<R>
+where
+    H: SettingsHandler,
+{
+    HANDLERS.call(|key| key.type_id == TypeId::of::<H>(), f)
+}
+
+/// Convert the result of a handler to the return value of a C callback.
+fn errno(result: ZephyrResult<()>) -> c_int {
+    match result {
+        Ok(()) => 0,
+        Err(error) => -i32::from(error.number()),
+    }
+}
+
+unsafe fn key<'a>(key: *const c_char) -> &'a str {
+    key.as_ref()
+        .map(|key| CStr::from_ptr(key).to_str().unwrap_or(""))
+        .unwrap_or("")
+}
+
+extern "C" fn set_trampoline<H: SettingsHandler>(
+    key_ptr: *const c_char,
+    len: usize,
+    read_cb: settings_read_cb,
+    cb_arg: *mut c_void,
+) -> c_int {
+    let mut value = SettingsValue {
+        len,
+        read_cb,
+        cb_arg,
+    };
+    match with_handler::<H, _>(|handler| handler.set(unsafe { key(key_ptr) }, &mut value)) {
+        Some(result) => errno(result),
+        None => -i32::from(ErrorNumber::NoEntry),
+    }
+}
+
+extern "C" fn get_trampoline<H: SettingsHandler>(
+    key_ptr: *const c_char,
+    value: *mut c_char,
+    value_len_max: c_int,
+) -> c_int {
+    let buffer =
+        unsafe { core::slice::from_raw_parts_mut(value as *mut u8, value_len_max as usize) };
+    match with_handler::<H, _>(|handler| handler.get(unsafe { key(key_ptr) }, buffer)).flatten() {
+        Some(len) => len as c_int,
+        None => -i32::from(ErrorNumber::NoEntry),
+    }
+}
+
+extern "C" fn commit_trampoline<H: SettingsHandler>() -> c_int {
+    with_handler::<H, _>(|handler| errno(handler.commit())).unwrap_or(0)
+}
+
+extern "C" fn export_trampoline<H: SettingsHandler>(
+    export_func: Option<
+        unsafe extern "C" fn(name: *const c_char, value: *const c_void, len: usize) -> c_int,
+    >,
+) -> c_int {
+    let mut exporter = Exporter { export_func };
+    with_handler::<H, _>(|handler| errno(handler.export(&mut exporter))).unwrap_or(0)
+}
+
+/// Register `handler` for the values below `subtree`. This is a wrapper to `settings_register`.
+///
+/// Handlers cannot be unregistered. Fails with [ErrorNumber::InvalidArgument] if a handler of the
+/// same type is already registered.
+pub fn register<H: SettingsHandler>(subtree: &str, handler: H) -> ZephyrResult<()> {
+    let name = c_string(subtree)?;
+    // the handler contains optional fields depending on the Kconfig, start from all zeros
+    let mut raw: ZSettingsHandler = unsafe { core::mem::zeroed() };
+    // the name is not moved with the CString
+    raw.name = name.as_ptr() as *mut c_char;
+    raw.h_set = Some(set_trampoline::<H>);
+    raw.h_get = Some(get_trampoline::<H>);
+    raw.h_commit = Some(commit_trampoline::<H>);
+    raw.h_export = Some(export_trampoline::<H>);
+    let key = HandlerKey {
+        type_id: TypeId::of::<H>(),
+        _name: name,
+        raw: UnsafeCell::new(raw),
+    };
+
+    let already_registered = || {
+        Err(ZephyrError::new_with_context(
+            ErrorNumber::InvalidArgument,
+            &CONTEXT,
+        ))
+    };
+    if HANDLERS.id(&key).is_some() {
+        return already_registered();
+    }
+    let inserted = HANDLERS.insert(key, Box::new(handler));
+    if !inserted.added {
+        // registered by another thread meanwhile
+        HANDLERS.restore(inserted.id, inserted.replaced);
+        return already_registered();
+    }
+
+    // the C handler is linked into a list, so pass the one that lives in the entry
+    let raw = unsafe { (*inserted.key).raw.get() };
+    let errno = unsafe { zephyr_sys::raw::settings_register(raw) };
+    if errno != 0 {
+        HANDLERS.remove_id(inserted.id);
+    }
+    result(errno)
+}
+
+#[derive(Debug)]
+struct SettingsWrapperContext {}
+
+impl Context for SettingsWrapperContext {
+    fn name(&self) -> &'static str {
+        "settings wrapper"
+    }
+}
@@ -0,0 +1,386 @@
+//! Syscalls and high level wrappers for the Zephyr SMBus API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! SMBus runs on the same wires as I2C, but defines the transactions on the bus, packet error
+//! checking (PEC) and an alert line through which peripherals request attention. Peripherals like
+//! smart batteries rely on these semantics, so they are accessed with an [Smbus] instead of an
+//! [crate::i2c::I2c]. Addresses are 7 bit addresses.
+
+use crate::device::{class, TypedDevice};
+use crate::handlers::Handlers;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use core::ffi::c_int;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::smbus_callback as ZSmbusCallback;
+
+const CONTEXT: SmbusWrapperContext = SmbusWrapperContext {};
+
+/// Maximum number of bytes of a block transfer.
+pub const BLOCK_BYTES_MAX: usize = zephyr_sys::raw::SMBUS_BLOCK_BYTES_MAX as usize;
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Configuration of an SMBus controller.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct SmbusConfig {
+    /// Append and check a packet error code on every transfer.
+    pub pec: bool,
+    /// Enable the host notify protocol.
+    pub host_notify: bool,
+    /// Enable the alert line, see [Smbus::set_alert_callback].
+    pub alert: bool,
+}
+
+impl From<SmbusConfig> for u32 {
+    fn from(other: SmbusConfig) -> Self {
+        let mut config = zephyr_sys::raw::SMBUS_MODE_CONTROLLER;
+        if other.pec {
+            config |= zephyr_sys::raw::SMBUS_MODE_PEC;
+        }
+        if other.host_notify {
+            config |= zephyr_sys::raw::SMBUS_MODE_HOST_NOTIFY;
+        }
+        if other.alert {
+            config |= zephyr_sys::raw::SMBUS_MODE_SMBALERT;
+        }
+        config
+    }
+}
+
+impl From<u32> for SmbusConfig {
+    fn from(other: u32) -> Self {
+        SmbusConfig {
+            pec: other & zephyr_sys::raw::SMBUS_MODE_PEC != 0,
+            host_notify: other & zephyr_sys::raw::SMBUS_MODE_HOST_NOTIFY != 0,
+            alert: other & zephyr_sys::raw::SMBUS_MODE_SMBALERT != 0,
+        }
+    }
+}
+
+/// Wrapper to the `smbus_configure` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn configure(device: &Device, config: SmbusConfig) -> ZephyrResult<()> {
+    result(zephyr_sys::syscalls::any::smbus_configure(
+        device as *const Device,
+        config.into(),
+    ))
+}
+
+/// Wrapper to the `smbus_get_config` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn get_config(device: &Device) -> ZephyrResult<SmbusConfig> {
+    let mut config = 0u32;
+    result(zephyr_sys::syscalls::any::smbus_get_config(
+        device as *const Device,
+        &mut config as *mut u32,
+    ))?;
+    Ok(config.into())
+}
+
+/// High level wrapper for an SMBus controller.
+pub struct Smbus {
+    device: &'static Device,
+}
+
+impl Smbus {
+    /// Creates a new [Smbus].
+    ///
+    /// `device` MUST be an SMBus device. If `device` is not an SMBus device the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Smbus { device }
+    }
+
+    /// Creates a new [Smbus] from a device checked to be an SMBus device.
+    pub fn from_device(device: TypedDevice<class::Smbus>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Smbus::new(device.device()) }
+    }
+
+    fn device_ptr(&self) -> *const Device {
+        self.device as *const Device
+    }
+
+    /// Set the configuration of the controller.
+    pub fn configure(&mut self, config: SmbusConfig) -> ZephyrResult<()> {
+        // device is required to be an SMBus device in constructor
+        unsafe { configure(self.device, config) }
+    }
+
+    /// Returns the current configuration of the controller.
+    pub fn config(&self) -> ZephyrResult<SmbusConfig> {
+        // device is required to be an SMBus device in constructor
+        unsafe { get_config(self.device) }
+    }
+
+    /// Quick command, signals a single bit through the direction of the transfer. This is the
+    /// equivalent of `smbus_quick`.
+    pub fn quick(&mut self, address: u16, read: bool) -> ZephyrResult<()> {
+        let direction = if read {
+            zephyr_sys::raw::smbus_direction_SMBUS_MSG_READ
+        } else {
+            zephyr_sys::raw::smbus_direction_SMBUS_MSG_WRITE
+        };
+        // device is required to be an SMBus device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::smbus_quick(self.device_ptr(), address, direction)
+        })
+    }
+
+    /// Send a byte without a command. This is the equivalent of `smbus_byte_write`.
+    pub fn write_byte(&mut self, address: u16, byte: u8) -> ZephyrResult<()> {
+        // device is required to be an SMBus device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::smbus_byte_write(self.device_ptr(), address, byte)
+        })
+    }
+
+    /// Receive a byte without a command. This is the equivalent of `smbus_byte_read`.
+    pub fn read_byte(&mut self, address: u16) -> ZephyrResult<u8> {
+        let mut byte = 0u8;
+        // device is required to be an SMBus device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::smbus_byte_read(self.device_ptr(), address, &mut byte)
+        })?;
+        Ok(byte)
+    }
+
+    /// Write a byte to `command`. This is the equivalent of `smbus_byte_data_write`.
+    pub fn write_byte_data(&mut self, address: u16, command: u8, byte: u8) -> ZephyrResult<()> {
+        // device is required to be an SMBus device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::smbus_byte_data_write(
+                self.device_ptr(),
+                address,
+                command,
+                byte,
+            )
+        })
+    }
+
+    /// Read a byte from `command`. This is the equivalent of `smbus_byte_data_read`.
+    pub fn read_byte_data(&mut self, address: u16, command: u8) -> ZephyrResult<u8> {
+        let mut byte = 0u8;
+        // device is required to be an SMBus device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::smbus_byte_data_read(
+                self.device_ptr(),
+                address,
+                command,
+                &mut byte,
+            )
+        })?;
+        Ok(byte)
+    }
+
+    /// Write a word to `command`. This is the equivalent of `smbus_word_data_write`.
+    pub fn write_word_data(&mut self, address: u16, command: u8, word: u16) -> ZephyrResult<()> {
+        // device is required to be an SMBus device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::smbus_word_data_write(
+                self.device_ptr(),
+                address,
+                command,
+                word,
+            )
+        })
+    }
+
+    /// Read a word from `command`. This is the equivalent of `smbus_word_data_read`.
+    pub fn read_word_data(&mut self, address: u16, command: u8) -> ZephyrResult<u16> {
+        let mut word = 0u16;
+        // device is required to be an SMBus device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::smbus_word_data_read(
+                self.device_ptr(),
+                address,
+                command,
+                &mut word,
+            )
+        })?;
+        Ok(word)
+    }
+
+    /// Write a word to `command` and read the word of the response. This is the equivalent of
+    /// `smbus_pcall`.
+    pub fn process_call(&mut self, address: u16, command: u8, word: u16) -> ZephyrResult<u16> {
+        let mut response = 0u16;
+        // device is required to be an SMBus device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::smbus_pcall(
+                self.device_ptr(),
+                address,
+                command,
+                word,
+                &mut response,
+            )
+        })?;
+        Ok(response)
+    }
+
+    /// Write up to [BLOCK_BYTES_MAX] bytes to `command`. This is the equivalent of
+    /// `smbus_block_write`.
+    pub fn write_block(&mut self, address: u16, command: u8, data: &[u8]) -> ZephyrResult<()> {
+        if data.len() > BLOCK_BYTES_MAX {
+            return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT));
+        }
+        // device is required to be an SMBus device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::smbus_block_write(
+                self.device_ptr(),
+                address,
+                command,
+                data.len() as u8,
+                data.as_ptr() as *mut u8,
+            )
+        })
+    }
+
+    /// Read a block from `command`, the peripheral decides about its length. This is the
+    /// equivalent of `smbus_block_read`.
+    pub fn read_block(&mut self, address: u16, command: u8) -> ZephyrResult<Vec<u8>> {
+        let mut buffer = vec![0u8; BLOCK_BYTES_MAX];
+        let mut count = 0u8;
+        // device is required to be an SMBus device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::smbus_block_read(
+                self.device_ptr(),
+                address,
+                command,
+                &mut count,
+                buffer.as_mut_ptr(),
+            )
+        })?;
+        buffer.truncate(count as usize);
+        Ok(buffer)
+    }
+
+    /// Write a block to `command` and read the block of the response. This is the equivalent of
+    /// `smbus_block_pcall`.
+    pub fn block_process_call(
+        &mut self,
+        address: u16,
+        command: u8,
+        data: &[u8],
+    ) -> ZephyrResult<Vec<u8>> {
+        if data.len() > BLOCK_BYTES_MAX {
+            return Err(ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT));
+        }
+        let mut buffer = vec![0u8; BLOCK_BYTES_MAX];
+        let mut count = 0u8;
+        // device is required to be an SMBus device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::smbus_block_pcall(
+                self.device_ptr(),
+                address,
+                command,
+                data.len() as u8,
+                data.as_ptr() as *mut u8,
+                &mut count,
+                buffer.as_mut_ptr(),
+            )
+        })?;
+        buffer.truncate(count as usize);
+        Ok(buffer)
+    }
+
+    /// Call `f` with the address of the peripheral that raised an alert. This is the equivalent
+    /// of `smbus_smbalert_set_cb`, the alert line must be enabled with [SmbusConfig::alert].
+    ///
+    /// `f` is called from the work queue of the controller driver. Callbacks stay registered
+    /// until they are removed with [Smbus::remove_alert_callback].
+    pub fn set_alert_callback<F>(&mut self, f: F) -> ZephyrResult<AlertCallbackId>
+    where
+        F: FnMut(u8) + Send + 'static,
+    {
+        // zeroed as the list node is set by the driver
+        let mut raw: ZSmbusCallback = unsafe { core::mem::zeroed() };
+        raw.handler = Some(alert_trampoline);
+        let pushed = ALERT_HANDLERS.push(
+            AlertKey {
+                callback: UnsafeCell::new(raw),
+            },
+            Box::new(f),
+        );
+        // the key and the callback in it stay in the table until the callback is removed
+        let callback = unsafe { (*pushed.key).callback.get() };
+
+        // device is required to be an SMBus device in constructor
+        let errno = unsafe {
+            zephyr_sys::syscalls::any::smbus_smbalert_set_cb(self.device_ptr(), callback)
+        };
+        if errno != 0 {
+            ALERT_HANDLERS.remove_id(pushed.id);
+            return Err(ZephyrError::from_errno_with_context(errno, &CONTEXT));
+        }
+        Ok(AlertCallbackId(callback))
+    }
+
+    /// Remove a callback registered with [Smbus::set_alert_callback]. This is the equivalent of
+    /// `smbus_smbalert_remove_cb`.
+    pub fn remove_alert_callback(&mut self, id: AlertCallbackId) -> ZephyrResult<()> {
+        // device is required to be an SMBus device in constructor
+        result(unsafe {
+            zephyr_sys::syscalls::any::smbus_smbalert_remove_cb(self.device_ptr(), id.0)
+        })?;
+        ALERT_HANDLERS.remove_where(|key| key.callback.get() == id.0);
+        Ok(())
+    }
+}
+
+/// Identifies a callback registered with [Smbus::set_alert_callback].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AlertCallbackId(*mut ZSmbusCallback);
+
+// the pointer is only compared and passed back to the driver
+unsafe impl Send for AlertCallbackId {}
+
+/// C callback registered with [Smbus::set_alert_callback], which the driver links into its list.
+struct AlertKey {
+    callback: UnsafeCell<ZSmbusCallback>,
+}
+
+// the C callback only points to the trampoline and to the other callbacks of the driver
+unsafe impl Send for AlertKey {}
+
+/// Every registered callback is a key of its own.
+impl PartialEq for AlertKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.callback.get() == other.callback.get()
+    }
+}
+
+static ALERT_HANDLERS: Handlers<AlertKey, dyn FnMut(u8) + Send> = Handlers::new();
+
+/// C callback that dispatches an alert to the closure registered with `callback`.
+extern "C" fn alert_trampoline(_dev: *const Device, callback: *mut ZSmbusCallback, address: u8) {
+    ALERT_HANDLERS.call(
+        |key| key.callback.get() == callback,
+        |handler| handler(address),
+    );
+}
+
+#[derive(Debug)]
+struct SmbusWrapperContext {}
+
+impl Context for SmbusWrapperContext {
+    fn name(&self) -> &'static str {
+        "smbus wrapper"
+    }
+}
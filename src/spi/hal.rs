@@ -0,0 +1,75 @@
+//! Implementation of the [embedded_hal] SPI traits, so device driver crates of the embedded
+//! ecosystem can use the SPI controllers of Zephyr.
+//!
+//! [SpiBus] implements the bus trait for drivers that drive the chip select themselves and [Spi]
+//! the device trait using the chip select of the [super::SpiConfig].
+
+use super::{release, Spi, SpiBus};
+use crate::ZephyrError;
+use embedded_hal::spi::{Error, ErrorKind, ErrorType, Operation, SpiBus as HalSpiBus, SpiDevice};
+
+impl Error for ZephyrError {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+impl ErrorType for SpiBus {
+    type Error = ZephyrError;
+}
+
+impl HalSpiBus for SpiBus {
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        SpiBus::read(self, words)
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        SpiBus::write(self, words)
+    }
+
+    /// The drivers of Zephyr handle buffers of different length like required by [HalSpiBus].
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        self.transceive(&[write], &mut [read])
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.transceive_in_place(words)
+    }
+
+    /// The transfers of Zephyr are synchronous, so there is nothing to flush.
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl ErrorType for Spi {
+    type Error = ZephyrError;
+}
+
+impl SpiDevice for Spi {
+    /// Keeps the chip select asserted between the operations by setting `SPI_HOLD_ON_CS` and
+    /// `SPI_LOCK_ON` for the transaction.
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        let operation = self.config.operation;
+        self.config.operation |=
+            (zephyr_sys::raw::SPI_HOLD_ON_CS | zephyr_sys::raw::SPI_LOCK_ON) as _;
+
+        let result = operations
+            .iter_mut()
+            .try_for_each(|operation| match operation {
+                Operation::Read(words) => self.read(words),
+                Operation::Write(words) => self.write(words),
+                Operation::Transfer(read, write) => self.transceive(&[*write], &mut [&mut **read]),
+                Operation::TransferInPlace(words) => self.transceive_in_place(words),
+                Operation::DelayNs(ns) => {
+                    unsafe { zephyr_sys::syscalls::any::k_busy_wait(ns.div_ceil(1000)) };
+                    Ok(())
+                }
+            });
+
+        // device is required to be a SPI controller in constructor
+        let released = unsafe { release(self.device, &self.config) };
+        self.config.operation = operation;
+        result.and(released)
+    }
+}
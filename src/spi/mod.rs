@@ -0,0 +1,365 @@
+//! Syscalls and high level wrappers for the Zephyr SPI controller API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API. Implementations of the [embedded_hal](https://docs.rs/embedded-hal)
+//! SPI traits are available with the `embedded-hal` feature.
+
+use crate::device::{class, TypedDevice};
+use crate::gpio::GpioPinNumber;
+use crate::{Context, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ffi::c_void;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::{
+    gpio_dt_spec, spi_buf as ZSpiBuffer, spi_buf_set as ZSpiBufferSet, spi_config as ZSpiConfig,
+    spi_cs_control,
+};
+
+#[cfg(feature = "embedded-hal")]
+pub mod hal;
+
+const CONTEXT: SpiWrapperContext = SpiWrapperContext {};
+
+/// Clock polarity and phase of the SPI bus.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SpiMode {
+    /// CPOL = 0, CPHA = 0
+    Mode0,
+    /// CPOL = 0, CPHA = 1
+    Mode1,
+    /// CPOL = 1, CPHA = 0
+    Mode2,
+    /// CPOL = 1, CPHA = 1
+    Mode3,
+}
+
+impl SpiMode {
+    fn operation(&self) -> u32 {
+        match self {
+            SpiMode::Mode0 => 0,
+            SpiMode::Mode1 => zephyr_sys::raw::SPI_MODE_CPHA,
+            SpiMode::Mode2 => zephyr_sys::raw::SPI_MODE_CPOL,
+            SpiMode::Mode3 => zephyr_sys::raw::SPI_MODE_CPOL | zephyr_sys::raw::SPI_MODE_CPHA,
+        }
+    }
+}
+
+/// Chip select GPIO that is driven by the SPI driver.
+#[derive(Copy, Clone)]
+pub struct ChipSelect {
+    pub port: &'static Device,
+    pub pin: GpioPinNumber,
+    /// Devicetree flags of the GPIO, e.g. `GPIO_ACTIVE_LOW`.
+    pub flags: zephyr_sys::raw::gpio_dt_flags_t,
+    /// Delay between asserting the chip select and the transfer in microseconds.
+    pub delay_us: u32,
+}
+
+/// Configuration of a SPI transfer.
+#[derive(Copy, Clone)]
+pub struct SpiConfig {
+    /// Bus frequency in Hertz.
+    pub frequency: u32,
+    pub mode: SpiMode,
+    /// Word size in bits.
+    pub word_size: u8,
+    pub lsb_first: bool,
+    /// Chip select GPIO, `None` if the controller handles the chip select (see `slave`).
+    pub chip_select: Option<ChipSelect>,
+    /// Number of the target for controllers that handle the chip select themselves.
+    pub slave: u16,
+}
+
+impl SpiConfig {
+    /// Creates a configuration for 8 bit words in MSB first order.
+    pub const fn new(frequency: u32, mode: SpiMode, chip_select: Option<ChipSelect>) -> Self {
+        Self {
+            frequency,
+            mode,
+            word_size: 8,
+            lsb_first: false,
+            chip_select,
+            slave: 0,
+        }
+    }
+}
+
+impl From<&SpiConfig> for ZSpiConfig {
+    fn from(other: &SpiConfig) -> Self {
+        let mut operation = zephyr_sys::raw::SPI_OP_MODE_MASTER
+            | other.mode.operation()
+            | ((other.word_size as u32) << zephyr_sys::raw::SPI_WORD_SIZE_SHIFT);
+        if other.lsb_first {
+            operation |= zephyr_sys::raw::SPI_TRANSFER_LSB;
+        }
+
+        let cs = match &other.chip_select {
+            Some(chip_select) => spi_cs_control {
+                gpio: gpio_dt_spec {
+                    port: chip_select.port as *const Device,
+                    pin: chip_select.pin,
+                    dt_flags: chip_select.flags,
+                },
+                delay: chip_select.delay_us,
+            },
+            None => spi_cs_control {
+                gpio: gpio_dt_spec {
+                    port: core::ptr::null(),
+                    pin: 0,
+                    dt_flags: 0,
+                },
+                delay: 0,
+            },
+        };
+
+        ZSpiConfig {
+            frequency: other.frequency,
+            operation: operation as _,
+            slave: other.slave,
+            cs,
+        }
+    }
+}
+
+/// Wrapper to the `spi_transceive` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// Each of the buffer sets is optional, buffers of `tx` are transmitted one after another while the
+/// received data is written to the buffers of `rx`.
+pub unsafe fn transceive(
+    device: &Device,
+    config: &ZSpiConfig,
+    tx: Option<&[&[u8]]>,
+    rx: Option<&mut [&mut [u8]]>,
+) -> ZephyrResult<()> {
+    let tx_buffers: Option<Vec<ZSpiBuffer>> = tx.map(|buffers| {
+        buffers
+            .iter()
+            .map(|buffer| ZSpiBuffer {
+                // transmitted buffers are never modified by the driver
+                buf: buffer.as_ptr() as *mut c_void,
+                len: buffer.len(),
+            })
+            .collect()
+    });
+    let rx_buffers: Option<Vec<ZSpiBuffer>> = rx.map(|buffers| {
+        buffers
+            .iter_mut()
+            .map(|buffer| ZSpiBuffer {
+                buf: buffer.as_mut_ptr() as *mut c_void,
+                len: buffer.len(),
+            })
+            .collect()
+    });
+    let tx_set = tx_buffers.as_ref().map(|buffers| ZSpiBufferSet {
+        buffers: buffers.as_ptr(),
+        count: buffers.len(),
+    });
+    let rx_set = rx_buffers.as_ref().map(|buffers| ZSpiBufferSet {
+        buffers: buffers.as_ptr(),
+        count: buffers.len(),
+    });
+
+    let errno = zephyr_sys::syscalls::any::spi_transceive(
+        device as *const Device,
+        config as *const ZSpiConfig,
+        tx_set
+            .as_ref()
+            .map_or(core::ptr::null(), |set| set as *const ZSpiBufferSet),
+        rx_set
+            .as_ref()
+            .map_or(core::ptr::null(), |set| set as *const ZSpiBufferSet),
+    );
+
+    if errno >= 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `spi_transceive` syscall transmitting `buffer` and receiving into it at the same
+/// time.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API. The
+/// same buffer is passed in both buffer sets, so no copy of the transmitted data is needed.
+pub unsafe fn transceive_in_place(
+    device: &Device,
+    config: &ZSpiConfig,
+    buffer: &mut [u8],
+) -> ZephyrResult<()> {
+    let buffers = [ZSpiBuffer {
+        buf: buffer.as_mut_ptr() as *mut c_void,
+        len: buffer.len(),
+    }];
+    let set = ZSpiBufferSet {
+        buffers: buffers.as_ptr(),
+        count: buffers.len(),
+    };
+
+    let errno = zephyr_sys::syscalls::any::spi_transceive(
+        device as *const Device,
+        config as *const ZSpiConfig,
+        &set as *const ZSpiBufferSet,
+        &set as *const ZSpiBufferSet,
+    );
+
+    if errno >= 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `spi_release` syscall.
+///
+/// Releases the chip select and the lock of the controller if `SPI_HOLD_ON_CS` or `SPI_LOCK_ON`
+/// have been set in the operation of `config`.
+pub unsafe fn release(device: &Device, config: &ZSpiConfig) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::spi_release(
+        device as *const Device,
+        config as *const ZSpiConfig,
+    );
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// High level wrapper for a SPI target on a SPI controller.
+///
+/// This wraps the static reference of the underlying device and the configuration of the target.
+/// The configuration is boxed as the drivers identify the currently configured target by the
+/// address of its configuration. Each transfer drives the chip select of the configuration, see
+/// [SpiBus] for transfers without a chip select.
+pub struct Spi {
+    device: &'static Device,
+    config: Box<ZSpiConfig>,
+}
+
+impl Spi {
+    /// Creates a new [Spi] on the current interface.
+    ///
+    /// `device` MUST be a SPI controller. If `device` is not a SPI controller the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device, config: &SpiConfig) -> Self {
+        Spi {
+            device,
+            config: Box::new(config.into()),
+        }
+    }
+
+    /// Creates a new [Spi] on a device checked to be a SPI controller.
+    pub fn from_device(device: TypedDevice<class::Spi>, config: &SpiConfig) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Spi::new(device.device(), config) }
+    }
+
+    /// Replace the configuration used for the transfers.
+    pub fn set_config(&mut self, config: &SpiConfig) {
+        *self.config = config.into();
+    }
+
+    /// Transmit the buffers of `tx` and receive into the buffers of `rx` at the same time.
+    pub fn transceive(&mut self, tx: &[&[u8]], rx: &mut [&mut [u8]]) -> ZephyrResult<()> {
+        // device is required to be a SPI controller in constructor
+        unsafe { transceive(self.device, &self.config, Some(tx), Some(rx)) }
+    }
+
+    /// Transmit `buffer` and receive into it at the same time, see [transceive_in_place].
+    pub fn transceive_in_place(&mut self, buffer: &mut [u8]) -> ZephyrResult<()> {
+        // device is required to be a SPI controller in constructor
+        unsafe { transceive_in_place(self.device, &self.config, buffer) }
+    }
+
+    /// Receive into `buffer`. This is the equivalent of `spi_read`.
+    pub fn read(&mut self, buffer: &mut [u8]) -> ZephyrResult<()> {
+        // device is required to be a SPI controller in constructor
+        unsafe { transceive(self.device, &self.config, None, Some(&mut [buffer])) }
+    }
+
+    /// Transmit `data`. This is the equivalent of `spi_write`.
+    pub fn write(&mut self, data: &[u8]) -> ZephyrResult<()> {
+        // device is required to be a SPI controller in constructor
+        unsafe { transceive(self.device, &self.config, Some(&[data]), None) }
+    }
+
+    /// Release the chip select and the lock of the controller, see [release].
+    pub fn release(&mut self) -> ZephyrResult<()> {
+        // device is required to be a SPI controller in constructor
+        unsafe { release(self.device, &self.config) }
+    }
+}
+
+/// High level wrapper for a SPI controller used as a bus without a chip select.
+///
+/// In contrast to [Spi], the transfers do not drive the chip select GPIO of a target, the chip
+/// selects of the targets on the bus are driven by the user. Controllers that drive the chip select
+/// of the target number `slave` themselves still do so.
+pub struct SpiBus {
+    spi: Spi,
+}
+
+impl SpiBus {
+    /// Creates a new [SpiBus] on the current interface, the chip select of `config` is ignored.
+    ///
+    /// `device` MUST be a SPI controller. If `device` is not a SPI controller the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device, config: &SpiConfig) -> Self {
+        SpiBus {
+            spi: Spi::new(device, &SpiBus::without_chip_select(config)),
+        }
+    }
+
+    /// Creates a new [SpiBus] on a device checked to be a SPI controller.
+    pub fn from_device(device: TypedDevice<class::Spi>, config: &SpiConfig) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { SpiBus::new(device.device(), config) }
+    }
+
+    fn without_chip_select(config: &SpiConfig) -> SpiConfig {
+        SpiConfig {
+            chip_select: None,
+            ..*config
+        }
+    }
+
+    /// Replace the configuration used for the transfers, the chip select of `config` is ignored.
+    pub fn set_config(&mut self, config: &SpiConfig) {
+        self.spi.set_config(&SpiBus::without_chip_select(config));
+    }
+
+    /// Transmit the buffers of `tx` and receive into the buffers of `rx` at the same time.
+    pub fn transceive(&mut self, tx: &[&[u8]], rx: &mut [&mut [u8]]) -> ZephyrResult<()> {
+        self.spi.transceive(tx, rx)
+    }
+
+    /// Transmit `buffer` and receive into it at the same time, see [transceive_in_place].
+    pub fn transceive_in_place(&mut self, buffer: &mut [u8]) -> ZephyrResult<()> {
+        self.spi.transceive_in_place(buffer)
+    }
+
+    /// Receive into `buffer`. This is the equivalent of `spi_read`.
+    pub fn read(&mut self, buffer: &mut [u8]) -> ZephyrResult<()> {
+        self.spi.read(buffer)
+    }
+
+    /// Transmit `data`. This is the equivalent of `spi_write`.
+    pub fn write(&mut self, data: &[u8]) -> ZephyrResult<()> {
+        self.spi.write(data)
+    }
+}
+
+#[derive(Debug)]
+struct SpiWrapperContext {}
+
+impl Context for SpiWrapperContext {
+    fn name(&self) -> &'static str {
+        "spi wrapper"
+    }
+}
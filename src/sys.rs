@@ -0,0 +1,73 @@
+//! Syscalls used by the wrappers, selected at build time.
+//!
+//! The syscalls are those of `zephyr_sys`, or with the `mock-syscalls` feature those of
+//! [crate::mock], which answer from the expectations of a unit test on the host. The spinlock of
+//! the handler tables is selected the same way, so the wrappers installing closures run on the
+//! host too.
+//!
+//! [SpinLocked] guards a value with such a spinlock, in place of atomics which are not available
+//! on every target, e.g. 64 bit atomics on 32 bit targets and compare and swap on ARMv6-M.
+
+use core::cell::UnsafeCell;
+use zephyr_sys::raw::k_spinlock;
+
+#[cfg(not(feature = "mock-syscalls"))]
+pub(crate) use zephyr_sys::syscalls::any::{
+    device_is_ready, gpio_pin_configure, gpio_port_clear_bits_raw, gpio_port_set_bits_raw,
+    sensor_attr_set, sensor_channel_get, sensor_sample_fetch, sensor_sample_fetch_chan,
+};
+
+#[cfg(feature = "mock-syscalls")]
+pub(crate) use crate::mock::syscalls::{
+    device_is_ready, gpio_pin_configure, gpio_port_clear_bits_raw, gpio_port_set_bits_raw,
+    sensor_attr_set, sensor_channel_get, sensor_sample_fetch, sensor_sample_fetch_chan,
+};
+
+#[cfg(not(feature = "mock-syscalls"))]
+pub(crate) use zephyr_sys::raw::{k_spin_lock, k_spin_unlock};
+
+#[cfg(feature = "mock-syscalls")]
+pub(crate) use crate::mock::syscalls::{k_spin_lock, k_spin_unlock};
+
+/// Value guarded by a `k_spinlock`, which may be accessed from interrupts.
+pub(crate) struct SpinLocked<T> {
+    lock: UnsafeCell<k_spinlock>,
+    value: UnsafeCell<T>,
+}
+
+// the value is only accessed with the lock held
+unsafe impl<T: Send> Sync for SpinLocked<T> {}
+
+impl<T> SpinLocked<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        SpinLocked {
+            // zeroed as an unlocked spinlock is all zeros, like K_SPINLOCK_DEFINE
+            lock: UnsafeCell::new(unsafe { core::mem::zeroed() }),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Calls `f` with the value and the lock held, `f` must not allocate or block.
+    pub(crate) fn locked<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let key = unsafe { k_spin_lock(self.lock.get()) };
+        // the lock is held, so nothing else accesses the value
+        let result = f(unsafe { &mut *self.value.get() });
+        unsafe { k_spin_unlock(self.lock.get(), key) };
+        result
+    }
+}
+
+impl<T: Copy> SpinLocked<T> {
+    pub(crate) fn get(&self) -> T {
+        self.locked(|value| *value)
+    }
+
+    pub(crate) fn set(&self, value: T) {
+        self.locked(|current| *current = value)
+    }
+
+    /// Sets the value to `value`, returns the previous value.
+    pub(crate) fn replace(&self, value: T) -> T {
+        self.locked(|current| core::mem::replace(current, value))
+    }
+}
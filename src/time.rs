@@ -0,0 +1,81 @@
+//! Conversions between Rust time types and the timeouts of the Zephyr kernel API.
+//!
+//! The kernel only counts the time since boot. [set_wall_clock_since_epoch] anchors the uptime to
+//! the calendar time, e.g. after a query of `network::sntp` or from an RTC, so
+//! [wall_clock_since_epoch] returns the current time. With the `std` feature, `set_wall_clock` and
+//! `wall_clock` do the same with `SystemTime`.
+
+use crate::sys::SpinLocked;
+use core::convert::TryInto;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+pub use zephyr_sys::raw::k_timeout_t as Timeout;
+
+/// Equivalent of `K_NO_WAIT`.
+pub const NO_WAIT: Timeout = Timeout { ticks: 0 };
+
+/// Equivalent of `K_FOREVER`.
+pub const FOREVER: Timeout = Timeout { ticks: -1 };
+
+/// Convert `duration` to a timeout, rounding up to the next kernel tick.
+///
+/// Durations beyond the ticks the kernel can count (with `CONFIG_TIMEOUT_64BIT` disabled about 49
+/// days at 1000 ticks per second) become [FOREVER].
+pub fn timeout(duration: Duration) -> Timeout {
+    let ticks_per_second = zephyr_sys::raw::CONFIG_SYS_CLOCK_TICKS_PER_SEC as u128;
+    let ticks = (duration.as_micros() * ticks_per_second + 999_999) / 1_000_000;
+    match ticks.try_into() {
+        Ok(ticks) if ticks != FOREVER.ticks => Timeout { ticks },
+        _ => FOREVER,
+    }
+}
+
+/// Convert an optional `duration` to a timeout, where `None` means to wait forever.
+pub fn timeout_or_forever(duration: Option<Duration>) -> Timeout {
+    duration.map(timeout).unwrap_or(FOREVER)
+}
+
+/// Returns the time since the system booted. This is the equivalent of `k_uptime_get`.
+pub fn uptime() -> Duration {
+    let ticks = unsafe { zephyr_sys::syscalls::any::k_uptime_ticks() } as u64;
+    let ticks_per_second = zephyr_sys::raw::CONFIG_SYS_CLOCK_TICKS_PER_SEC as u64;
+    Duration::from_micros(ticks * 1_000_000 / ticks_per_second)
+}
+
+/// Microseconds between the Unix epoch and the boot of the system, `0` while not set. Locked as
+/// 32 bit targets have no 64 bit atomics.
+static BOOT_TIME: SpinLocked<u64> = SpinLocked::new(0);
+
+/// Sets the wall clock to `now`, the current calendar time.
+#[cfg(feature = "std")]
+pub fn set_wall_clock(now: SystemTime) {
+    set_wall_clock_since_epoch(now.duration_since(UNIX_EPOCH).unwrap_or_default());
+}
+
+/// Sets the wall clock to `since_epoch`, the time between the Unix epoch and now.
+pub fn set_wall_clock_since_epoch(since_epoch: Duration) {
+    let boot_time = since_epoch.checked_sub(uptime()).unwrap_or_default();
+    // at least 1 so a clock set to the epoch is still set
+    BOOT_TIME.set((boot_time.as_micros() as u64).max(1));
+}
+
+/// Wall clock offset, the time between the Unix epoch and the boot of the system. `None` if the
+/// wall clock has not been set.
+pub fn wall_clock_offset() -> Option<Duration> {
+    match BOOT_TIME.get() {
+        0 => None,
+        boot_time => Some(Duration::from_micros(boot_time)),
+    }
+}
+
+/// Current calendar time of the wall clock, `None` if it has not been set with [set_wall_clock].
+#[cfg(feature = "std")]
+pub fn wall_clock() -> Option<SystemTime> {
+    wall_clock_since_epoch().map(|since_epoch| UNIX_EPOCH + since_epoch)
+}
+
+/// Time between the Unix epoch and now, `None` if the wall clock has not been set.
+pub fn wall_clock_since_epoch() -> Option<Duration> {
+    wall_clock_offset().map(|offset| offset + uptime())
+}
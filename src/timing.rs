@@ -0,0 +1,77 @@
+//! Wrappers for the Zephyr timing functions (`CONFIG_TIMING_FUNCTIONS`).
+//!
+//! The timing functions read a cycle counter of the SoC, which makes them suitable to measure
+//! short code paths with a resolution well below the kernel tick:
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::timing;
+//!# fn parse() {}
+//! timing::init();
+//! timing::start();
+//! let elapsed = timing::measure(|| parse());
+//! timing::stop();
+//! ```
+
+use core::time::Duration;
+
+/// Value of the cycle counter, as returned by [counter].
+pub type Timestamp = zephyr_sys::raw::timing_t;
+
+/// Wrapper to the `timing_init` function, must be called once before [start].
+pub fn init() {
+    unsafe { zephyr_sys::raw::timing_init() }
+}
+
+/// Wrapper to the `timing_start` function, starts the cycle counter.
+pub fn start() {
+    unsafe { zephyr_sys::raw::timing_start() }
+}
+
+/// Wrapper to the `timing_stop` function, stops the cycle counter.
+pub fn stop() {
+    unsafe { zephyr_sys::raw::timing_stop() }
+}
+
+/// Wrapper to the `timing_counter_get` function.
+pub fn counter() -> Timestamp {
+    unsafe { zephyr_sys::raw::timing_counter_get() }
+}
+
+/// Wrapper to the `timing_cycles_get` function, returns the cycles between `start` and `end`.
+pub fn cycles_between(start: Timestamp, end: Timestamp) -> u64 {
+    unsafe {
+        zephyr_sys::raw::timing_cycles_get(
+            &start as *const Timestamp as *mut Timestamp,
+            &end as *const Timestamp as *mut Timestamp,
+        )
+    }
+}
+
+/// Wrapper to the `timing_cycles_to_ns` function.
+pub fn cycles_to_ns(cycles: u64) -> u64 {
+    unsafe { zephyr_sys::raw::timing_cycles_to_ns(cycles) }
+}
+
+/// Wrapper to the `timing_freq_get_mhz` function, returns the frequency of the cycle counter.
+pub fn frequency_mhz() -> u32 {
+    unsafe { zephyr_sys::raw::timing_freq_get_mhz() }
+}
+
+/// Time between `start` and `end`.
+pub fn elapsed(start: Timestamp, end: Timestamp) -> Duration {
+    Duration::from_nanos(cycles_to_ns(cycles_between(start, end)))
+}
+
+/// Runs `f` and returns the time it took, the cycle counter must be started with [start].
+pub fn measure<F: FnOnce()>(f: F) -> Duration {
+    measure_with(f).1
+}
+
+/// Runs `f` and returns its result together with the time it took, the cycle counter must be
+/// started with [start].
+pub fn measure_with<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
+    let start = counter();
+    let value = f();
+    let end = counter();
+    (value, elapsed(start, end))
+}
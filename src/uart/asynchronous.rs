@@ -0,0 +1,334 @@
+//! Syscalls and high level wrappers for the asynchronous Zephyr UART API.
+//!
+//! The asynchronous API transfers whole buffers (usually using DMA) and reports the progress of
+//! the transfers as [UartEvent]s to a callback. Buffers passed to the driver need to be `'static`
+//! as the driver accesses them after the call returned.
+//!
+//! ```no_run
+//!# use zephyr_rust_wrappers::uart::Uart;
+//!# use zephyr_rust_wrappers::uart::asynchronous::UartEvent;
+//!# fn setup(uart: &mut Uart, first: &'static mut [u8], second: &'static mut [u8]) {
+//! let mut spare = Some(second);
+//! uart.set_callback(move |uart, event| match event {
+//!     UartEvent::RxReady(data) => println!("received {} bytes", data.len()),
+//!     UartEvent::RxBufferRequest => {
+//!         if let Some(buffer) = spare.take() {
+//!             let _ = uart.rx_buffer_response(buffer);
+//!         }
+//!     }
+//!     _ => {}
+//! })
+//! .expect("setting uart callback");
+//! uart.rx_enable(first, None).expect("enabling uart receiver");
+//!# }
+//! ```
+
+use super::{Device, Uart, CONTEXT};
+use crate::handlers::Handlers;
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use core::ffi::c_void;
+use core::slice;
+use core::time::Duration;
+use zephyr_sys::raw::{uart_driver_api as UartDriverApi, uart_event as ZUartEvent};
+
+/// Equivalent of `SYS_FOREVER_US`.
+const FOREVER_US: i32 = -1;
+
+/// Reason why the receiver has been stopped.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RxStopReason {
+    Overrun,
+    Parity,
+    Framing,
+    Break,
+    Collision,
+    Noise,
+    Other(u32),
+}
+
+impl From<u32> for RxStopReason {
+    fn from(reason: u32) -> Self {
+        match reason {
+            zephyr_sys::raw::uart_rx_stop_reason_UART_ERROR_OVERRUN => RxStopReason::Overrun,
+            zephyr_sys::raw::uart_rx_stop_reason_UART_ERROR_PARITY => RxStopReason::Parity,
+            zephyr_sys::raw::uart_rx_stop_reason_UART_ERROR_FRAMING => RxStopReason::Framing,
+            zephyr_sys::raw::uart_rx_stop_reason_UART_BREAK => RxStopReason::Break,
+            zephyr_sys::raw::uart_rx_stop_reason_UART_ERROR_COLLISION => RxStopReason::Collision,
+            zephyr_sys::raw::uart_rx_stop_reason_UART_ERROR_NOISE => RxStopReason::Noise,
+            other => RxStopReason::Other(other),
+        }
+    }
+}
+
+/// Event reported by the asynchronous UART API.
+#[derive(Debug)]
+pub enum UartEvent<'a> {
+    /// The whole buffer passed to [Uart::tx] has been transmitted.
+    TxDone(&'a [u8]),
+    /// The transmission has been aborted, the slice contains the transmitted data.
+    TxAborted(&'a [u8]),
+    /// Data has been received into the current receive buffer.
+    RxReady(&'a [u8]),
+    /// The driver requests the next receive buffer, see [Uart::rx_buffer_response].
+    RxBufferRequest,
+    /// The driver does not use the receive buffer starting at the pointer anymore.
+    RxBufferReleased(*mut u8),
+    /// The receiver has been disabled and all buffers have been released.
+    RxDisabled,
+    /// The receiver has been stopped because of an error. The slice contains the data that has been
+    /// received before the error.
+    RxStopped {
+        reason: RxStopReason,
+        data: &'a [u8],
+    },
+    /// Event not known to this wrapper.
+    Unknown(u32),
+}
+
+impl<'a> UartEvent<'a> {
+    /// Convert the event of the C API. `event` MUST be an event reported by a UART driver.
+    unsafe fn from_raw(event: &'a ZUartEvent) -> Self {
+        match event.type_ {
+            zephyr_sys::raw::uart_event_type_UART_TX_DONE => {
+                let tx = &event.data.tx;
+                UartEvent::TxDone(slice::from_raw_parts(tx.buf, tx.len))
+            }
+            zephyr_sys::raw::uart_event_type_UART_TX_ABORTED => {
+                let tx = &event.data.tx;
+                UartEvent::TxAborted(slice::from_raw_parts(tx.buf, tx.len))
+            }
+            zephyr_sys::raw::uart_event_type_UART_RX_RDY => {
+                let rx = &event.data.rx;
+                UartEvent::RxReady(slice::from_raw_parts(rx.buf.add(rx.offset), rx.len))
+            }
+            zephyr_sys::raw::uart_event_type_UART_RX_BUF_REQUEST => UartEvent::RxBufferRequest,
+            zephyr_sys::raw::uart_event_type_UART_RX_BUF_RELEASED => {
+                UartEvent::RxBufferReleased(event.data.rx_buf.buf)
+            }
+            zephyr_sys::raw::uart_event_type_UART_RX_DISABLED => UartEvent::RxDisabled,
+            zephyr_sys::raw::uart_event_type_UART_RX_STOPPED => {
+                let rx_stop = &event.data.rx_stop;
+                let rx = &rx_stop.data;
+                UartEvent::RxStopped {
+                    reason: RxStopReason::from(rx_stop.reason),
+                    data: if rx.buf.is_null() {
+                        &[]
+                    } else {
+                        slice::from_raw_parts(rx.buf.add(rx.offset), rx.len)
+                    },
+                }
+            }
+            other => UartEvent::Unknown(other),
+        }
+    }
+}
+
+/// C callback of the asynchronous UART API.
+pub type UartCallback =
+    unsafe extern "C" fn(dev: *const Device, evt: *mut ZUartEvent, user_data: *mut c_void);
+
+fn duration_to_us(timeout: Option<Duration>) -> i32 {
+    timeout
+        .map(|timeout| timeout.as_micros().min(i32::MAX as u128) as i32)
+        .unwrap_or(FOREVER_US)
+}
+
+pub(super) fn api(device: &Device) -> Option<&UartDriverApi> {
+    // convert void pointer from C API to a UART driver API Rust struct
+    unsafe { (device.api as *const UartDriverApi).as_ref() }
+}
+
+/// Implementation of the function `uart_callback_set` which is inlined in the Zephyr API.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+///
+/// This method fails with [ErrorNumber::NotImplemented] if the driver does not support the
+/// asynchronous API.
+pub unsafe fn callback_set(
+    device: &Device,
+    callback: Option<UartCallback>,
+    user_data: *mut c_void,
+) -> ZephyrResult<()> {
+    match api(device).and_then(|api| api.callback_set) {
+        Some(callback_set) => {
+            let errno = (callback_set)(device as *const Device, callback, user_data);
+
+            if errno == 0 {
+                Ok(())
+            } else {
+                Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+            }
+        }
+        None => Err(ZephyrError::new_with_context(
+            ErrorNumber::NotImplemented,
+            &CONTEXT,
+        )),
+    }
+}
+
+/// Wrapper to the `uart_tx` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API and
+/// `data` MUST stay valid until [UartEvent::TxDone] or [UartEvent::TxAborted] has been reported.
+pub unsafe fn tx(device: &Device, data: &[u8], timeout: Option<Duration>) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::uart_tx(
+        device as *const Device,
+        data.as_ptr(),
+        data.len(),
+        duration_to_us(timeout),
+    );
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `uart_tx_abort` syscall.
+pub unsafe fn tx_abort(device: &Device) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::uart_tx_abort(device as *const Device);
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `uart_rx_enable` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API and
+/// `buffer` MUST stay valid until [UartEvent::RxBufferReleased] has been reported for it.
+pub unsafe fn rx_enable(
+    device: &Device,
+    buffer: &mut [u8],
+    timeout: Option<Duration>,
+) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::uart_rx_enable(
+        device as *const Device,
+        buffer.as_mut_ptr(),
+        buffer.len(),
+        duration_to_us(timeout),
+    );
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Implementation of the function `uart_rx_buf_rsp` which is inlined in the Zephyr API.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API and
+/// `buffer` MUST stay valid until [UartEvent::RxBufferReleased] has been reported for it.
+pub unsafe fn rx_buf_rsp(device: &Device, buffer: &mut [u8]) -> ZephyrResult<()> {
+    match api(device).and_then(|api| api.rx_buf_rsp) {
+        Some(rx_buf_rsp) => {
+            let errno = (rx_buf_rsp)(device as *const Device, buffer.as_mut_ptr(), buffer.len());
+
+            if errno == 0 {
+                Ok(())
+            } else {
+                Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+            }
+        }
+        None => Err(ZephyrError::new_with_context(
+            ErrorNumber::NotImplemented,
+            &CONTEXT,
+        )),
+    }
+}
+
+/// Wrapper to the `uart_rx_disable` syscall.
+pub unsafe fn rx_disable(device: &Device) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::uart_rx_disable(device as *const Device);
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+type EventHandler = dyn FnMut(&mut Uart, UartEvent<'_>) + Send;
+
+/// Closures installed with [Uart::set_callback], keyed by the address of the device.
+static CALLBACKS: Handlers<usize, EventHandler> = Handlers::new();
+
+/// C callback that dispatches an event to the closure whose id is passed as user data.
+unsafe extern "C" fn callback_trampoline(
+    dev: *const Device,
+    evt: *mut ZUartEvent,
+    user_data: *mut c_void,
+) {
+    CALLBACKS.call_id(user_data as usize, |handler| {
+        // the callback could only be installed on a UART device
+        let mut uart = Uart::new(&*dev);
+        handler(&mut uart, UartEvent::from_raw(&*evt));
+    });
+}
+
+impl Uart {
+    /// Install `f` as the callback of the asynchronous API, replacing a previously installed
+    /// callback. If installing fails, the previous callback stays installed.
+    ///
+    /// The closure is called from the interrupt of the driver in most cases. The closure MUST NOT
+    /// panic.
+    pub fn set_callback<F>(&mut self, f: F) -> ZephyrResult<()>
+    where
+        F: FnMut(&mut Uart, UartEvent<'_>) + Send + 'static,
+    {
+        let device = self.device as *const Device as usize;
+        let inserted = CALLBACKS.insert(device, Box::new(f));
+        let user_data = inserted.id as *mut c_void;
+        // device is required to be a UART device in constructor
+        let result = unsafe { callback_set(self.device, Some(callback_trampoline), user_data) };
+        if result.is_err() {
+            if inserted.added {
+                CALLBACKS.remove_id(inserted.id);
+            } else {
+                CALLBACKS.restore(inserted.id, inserted.replaced);
+            }
+        }
+        result
+    }
+
+    /// Start transmitting `data`. The end of the transmission is reported as
+    /// [UartEvent::TxDone] or, if `timeout` expired, as [UartEvent::TxAborted].
+    pub fn tx(&mut self, data: &'static [u8], timeout: Option<Duration>) -> ZephyrResult<()> {
+        // device is required to be a UART device in constructor
+        unsafe { tx(self.device, data, timeout) }
+    }
+
+    /// Abort the current transmission.
+    pub fn tx_abort(&mut self) -> ZephyrResult<()> {
+        // device is required to be a UART device in constructor
+        unsafe { tx_abort(self.device) }
+    }
+
+    /// Start receiving into `buffer`. Received data is reported as [UartEvent::RxReady] once
+    /// `timeout` passed without new data or the buffer is full.
+    pub fn rx_enable(
+        &mut self,
+        buffer: &'static mut [u8],
+        timeout: Option<Duration>,
+    ) -> ZephyrResult<()> {
+        // device is required to be a UART device in constructor
+        unsafe { rx_enable(self.device, buffer, timeout) }
+    }
+
+    /// Provide the next receive buffer as response to [UartEvent::RxBufferRequest].
+    pub fn rx_buffer_response(&mut self, buffer: &'static mut [u8]) -> ZephyrResult<()> {
+        // device is required to be a UART device in constructor
+        unsafe { rx_buf_rsp(self.device, buffer) }
+    }
+
+    /// Stop receiving. This is reported as [UartEvent::RxDisabled].
+    pub fn rx_disable(&mut self) -> ZephyrResult<()> {
+        // device is required to be a UART device in constructor
+        unsafe { rx_disable(self.device) }
+    }
+}
@@ -0,0 +1,294 @@
+//! Implementations of the [embedded_io] traits for [Uart], so protocol crates of the embedded
+//! ecosystem can use the UARTs of Zephyr.
+//!
+//! The traits are implemented on top of the polling API. While no character can be received or
+//! transmitted, the blocking traits wait for the interrupts of the driver
+//! (`CONFIG_UART_INTERRUPT_DRIVEN`): the first blocking read or write of a [Uart] installs the
+//! interrupt callback of the device, replacing a callback installed otherwise, until the [Uart] is
+//! dropped. Drivers without the interrupt driven API are polled once per kernel tick instead. The
+//! asynchronous variants of the traits (enabled with the `async-io` feature) return to the executor
+//! while no data has been received, the receive interrupt wakes the reading task.
+
+use super::asynchronous::api;
+use super::{Device, Uart, CONTEXT};
+use crate::time::{Timeout, FOREVER};
+use crate::{ZephyrError, ZephyrResult};
+use ::embedded_io::{ErrorType, Read, ReadReady, Write, WriteReady};
+use alloc::boxed::Box;
+use core::ffi::c_void;
+#[cfg(feature = "async-io")]
+use core::task::Waker;
+use zephyr_sys::raw::k_sem;
+#[cfg(feature = "async-io")]
+use zephyr_sys::raw::k_spinlock;
+
+/// Semaphores given from the interrupt of the driver once the UART received a character or can
+/// transmit one. The receive interrupt also wakes the task of an asynchronous read.
+pub(super) struct IrqEvents {
+    rx: k_sem,
+    tx: k_sem,
+    #[cfg(feature = "async-io")]
+    lock: k_spinlock,
+    #[cfg(feature = "async-io")]
+    rx_waker: Option<Waker>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Direction {
+    Rx,
+    Tx,
+}
+
+fn result(errno: i32) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// C callback of the interrupt driven API, which is passed the events as user data.
+unsafe extern "C" fn irq_callback(dev: *const Device, user_data: *mut c_void) {
+    let events = &mut *(user_data as *mut IrqEvents);
+    // the callback could only be installed if the driver implements the API
+    let api = match api(&*dev) {
+        Some(api) => api,
+        None => return,
+    };
+    zephyr_sys::syscalls::any::uart_irq_update(dev);
+    // the interrupts stay disabled until the next wait, the data is transferred by the waiter
+    if api.irq_rx_ready.map_or(false, |ready| ready(dev) != 0) {
+        zephyr_sys::syscalls::any::uart_irq_rx_disable(dev);
+        zephyr_sys::syscalls::any::k_sem_give(&mut events.rx);
+        #[cfg(feature = "async-io")]
+        if let Some(waker) = events.take_rx_waker() {
+            waker.wake();
+        }
+    }
+    if api.irq_tx_ready.map_or(false, |ready| ready(dev) != 0) {
+        zephyr_sys::syscalls::any::uart_irq_tx_disable(dev);
+        zephyr_sys::syscalls::any::k_sem_give(&mut events.tx);
+    }
+}
+
+#[cfg(feature = "async-io")]
+impl IrqEvents {
+    fn take_rx_waker(&mut self) -> Option<Waker> {
+        let key = unsafe { crate::sys::k_spin_lock(&mut self.lock) };
+        let waker = self.rx_waker.take();
+        unsafe { crate::sys::k_spin_unlock(&mut self.lock, key) };
+        // woken and dropped without the lock held
+        waker
+    }
+
+    fn set_rx_waker(&mut self, waker: &Waker) {
+        let key = unsafe { crate::sys::k_spin_lock(&mut self.lock) };
+        let replaced = self.rx_waker.replace(waker.clone());
+        unsafe { crate::sys::k_spin_unlock(&mut self.lock, key) };
+        drop(replaced);
+    }
+}
+
+impl Uart {
+    /// Reads all characters that have been received into `buf` without blocking. Returns the number
+    /// of characters read.
+    fn read_available(&mut self, buf: &mut [u8]) -> Result<usize, ZephyrError> {
+        let mut count = 0;
+        while count < buf.len() {
+            match self.read_byte()? {
+                Some(character) => {
+                    buf[count] = character;
+                    count += 1;
+                }
+                None => break,
+            }
+        }
+        Ok(count)
+    }
+
+    /// Events of the interrupt driven API, installing its callback on the first call. `None` if
+    /// the driver does not implement the API.
+    fn irq_events(&mut self) -> ZephyrResult<Option<&mut IrqEvents>> {
+        if self.events.is_none() {
+            let irq_callback_set = match api(self.device).and_then(|api| api.irq_callback_set) {
+                Some(irq_callback_set) => irq_callback_set,
+                None => return Ok(None),
+            };
+
+            // zeroed as the semaphores are initialized below and an unlocked spinlock is all zeros
+            let mut events = Box::new(IrqEvents {
+                rx: unsafe { core::mem::zeroed() },
+                tx: unsafe { core::mem::zeroed() },
+                #[cfg(feature = "async-io")]
+                lock: unsafe { core::mem::zeroed() },
+                #[cfg(feature = "async-io")]
+                rx_waker: None,
+            });
+            result(unsafe { zephyr_sys::syscalls::any::k_sem_init(&mut events.rx, 0, 1) })?;
+            result(unsafe { zephyr_sys::syscalls::any::k_sem_init(&mut events.tx, 0, 1) })?;
+            let user_data = &mut *events as *mut IrqEvents as *mut c_void;
+            // device is required to be a UART device in constructor
+            unsafe { (irq_callback_set)(self.device, Some(irq_callback), user_data) };
+            self.events = Some(events);
+        }
+        Ok(self.events.as_deref_mut())
+    }
+
+    /// Blocks until the UART received a character or can transmit one, the semaphores may also be
+    /// given before that, so the caller checks again.
+    fn wait(&mut self, direction: Direction) -> ZephyrResult<()> {
+        let device = self.device as *const Device;
+        match self.irq_events()? {
+            Some(events) => unsafe {
+                // enabling the interrupt raises it right away if the UART is ready already
+                let semaphore = if direction == Direction::Rx {
+                    zephyr_sys::syscalls::any::uart_irq_rx_enable(device);
+                    &mut events.rx
+                } else {
+                    zephyr_sys::syscalls::any::uart_irq_tx_enable(device);
+                    &mut events.tx
+                };
+                result(zephyr_sys::syscalls::any::k_sem_take(semaphore, FOREVER))
+            },
+            None => {
+                unsafe { zephyr_sys::syscalls::any::k_sleep(Timeout { ticks: 1 }) };
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Removes the interrupt callback installed by the blocking traits, before the events are freed.
+impl Drop for Uart {
+    fn drop(&mut self) {
+        if self.events.is_none() {
+            return;
+        }
+        let device = self.device as *const Device;
+        unsafe {
+            zephyr_sys::syscalls::any::uart_irq_rx_disable(device);
+            zephyr_sys::syscalls::any::uart_irq_tx_disable(device);
+            if let Some(irq_callback_set) = api(self.device).and_then(|api| api.irq_callback_set) {
+                (irq_callback_set)(device, None, core::ptr::null_mut());
+            }
+        }
+    }
+}
+
+impl ErrorType for Uart {
+    type Error = ZephyrError;
+}
+
+impl Read for Uart {
+    /// Blocks until at least one character has been received.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let count = self.read_available(buf)?;
+            if count > 0 {
+                return Ok(count);
+            }
+            self.wait(Direction::Rx)?;
+        }
+    }
+}
+
+impl ReadReady for Uart {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        self.is_read_ready()
+    }
+}
+
+impl Write for Uart {
+    /// Blocks until the UART can transmit, then transmits all of `buf`.
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.wait(Direction::Tx)?;
+        Uart::write(self, buf);
+        Ok(buf.len())
+    }
+
+    /// Polled characters are transmitted before the write returns, so there is nothing to flush.
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl WriteReady for Uart {
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "async-io")]
+mod asynchronous {
+    use super::{Device, Uart};
+    use crate::time::{Timeout, NO_WAIT};
+    use crate::ZephyrResult;
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll};
+
+    /// Future that completes once the receive interrupt of the UART has been raised.
+    struct RxReady<'u> {
+        uart: &'u mut Uart,
+    }
+
+    impl Future for RxReady<'_> {
+        type Output = ZephyrResult<()>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let device = self.uart.device as *const Device;
+            let events = match self.uart.irq_events() {
+                Ok(Some(events)) => events,
+                Ok(None) => {
+                    // without the interrupt driven API the UART is polled once per tick
+                    unsafe { zephyr_sys::syscalls::any::k_sleep(Timeout { ticks: 1 }) };
+                    return Poll::Ready(Ok(()));
+                }
+                Err(e) => return Poll::Ready(Err(e)),
+            };
+            if unsafe { zephyr_sys::syscalls::any::k_sem_take(&mut events.rx, NO_WAIT) } == 0 {
+                return Poll::Ready(Ok(()));
+            }
+            // the waker is in place before the interrupt, which is raised right away if the UART
+            // received a character meanwhile
+            events.set_rx_waker(cx.waker());
+            unsafe { zephyr_sys::syscalls::any::uart_irq_rx_enable(device) };
+            Poll::Pending
+        }
+    }
+
+    impl embedded_io_async::Read for Uart {
+        /// Waits for the receive interrupt while no character has been received.
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+
+            loop {
+                let count = self.read_available(buf)?;
+                if count > 0 {
+                    return Ok(count);
+                }
+                RxReady { uart: self }.await?;
+            }
+        }
+    }
+
+    impl embedded_io_async::Write for Uart {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            Uart::write(self, buf);
+            Ok(buf.len())
+        }
+
+        async fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+}
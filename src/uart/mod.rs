@@ -0,0 +1,384 @@
+//! Syscalls and high level wrappers for the Zephyr UART API.
+//!
+//! This module wraps the polling API of the UART drivers, the asynchronous (DMA based) API is
+//! wrapped in [asynchronous]. Some functions in the high level API still are marked as `unsafe` as
+//! the required checks cannot be performed to offer a safe API.
+
+use crate::device::{class, TypedDevice};
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use core::convert::TryFrom;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::uart_config as ZUartConfig;
+
+pub mod asynchronous;
+#[cfg(feature = "embedded-io")]
+pub mod io;
+
+const CONTEXT: UartWrapperContext = UartWrapperContext {};
+
+/// Parity of a UART frame. The list uses the values from Zephyr header files.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Parity {
+    None = zephyr_sys::raw::uart_config_parity_UART_CFG_PARITY_NONE as u8,
+    Odd = zephyr_sys::raw::uart_config_parity_UART_CFG_PARITY_ODD as u8,
+    Even = zephyr_sys::raw::uart_config_parity_UART_CFG_PARITY_EVEN as u8,
+    Mark = zephyr_sys::raw::uart_config_parity_UART_CFG_PARITY_MARK as u8,
+    Space = zephyr_sys::raw::uart_config_parity_UART_CFG_PARITY_SPACE as u8,
+}
+
+/// Number of stop bits of a UART frame. The list uses the values from Zephyr header files.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum StopBits {
+    Half = zephyr_sys::raw::uart_config_stop_bits_UART_CFG_STOP_BITS_0_5 as u8,
+    One = zephyr_sys::raw::uart_config_stop_bits_UART_CFG_STOP_BITS_1 as u8,
+    OneAndHalf = zephyr_sys::raw::uart_config_stop_bits_UART_CFG_STOP_BITS_1_5 as u8,
+    Two = zephyr_sys::raw::uart_config_stop_bits_UART_CFG_STOP_BITS_2 as u8,
+}
+
+/// Number of data bits of a UART frame. The list uses the values from Zephyr header files.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DataBits {
+    Five = zephyr_sys::raw::uart_config_data_bits_UART_CFG_DATA_BITS_5 as u8,
+    Six = zephyr_sys::raw::uart_config_data_bits_UART_CFG_DATA_BITS_6 as u8,
+    Seven = zephyr_sys::raw::uart_config_data_bits_UART_CFG_DATA_BITS_7 as u8,
+    Eight = zephyr_sys::raw::uart_config_data_bits_UART_CFG_DATA_BITS_8 as u8,
+    Nine = zephyr_sys::raw::uart_config_data_bits_UART_CFG_DATA_BITS_9 as u8,
+}
+
+/// Hardware flow control of a UART. The list uses the values from Zephyr header files.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FlowControl {
+    None = zephyr_sys::raw::uart_config_flow_control_UART_CFG_FLOW_CTRL_NONE as u8,
+    RtsCts = zephyr_sys::raw::uart_config_flow_control_UART_CFG_FLOW_CTRL_RTS_CTS as u8,
+    DtrDsr = zephyr_sys::raw::uart_config_flow_control_UART_CFG_FLOW_CTRL_DTR_DSR as u8,
+    Rs485 = zephyr_sys::raw::uart_config_flow_control_UART_CFG_FLOW_CTRL_RS485 as u8,
+}
+
+/// Configuration of a UART.
+///
+/// ```rust
+///# use zephyr_rust_wrappers::uart::{UartConfig, Parity};
+/// let config = UartConfig {
+///     parity: Parity::Even,
+///     ..UartConfig::new(9600)
+/// };
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct UartConfig {
+    pub baud_rate: u32,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub data_bits: DataBits,
+    pub flow_control: FlowControl,
+}
+
+impl UartConfig {
+    /// Creates the common 8N1 configuration without flow control with the given baud rate.
+    pub const fn new(baud_rate: u32) -> Self {
+        Self {
+            baud_rate,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            data_bits: DataBits::Eight,
+            flow_control: FlowControl::None,
+        }
+    }
+}
+
+impl From<&UartConfig> for ZUartConfig {
+    fn from(other: &UartConfig) -> Self {
+        ZUartConfig {
+            baudrate: other.baud_rate,
+            parity: other.parity as u8,
+            stop_bits: other.stop_bits as u8,
+            data_bits: other.data_bits as u8,
+            flow_ctrl: other.flow_control as u8,
+        }
+    }
+}
+
+impl TryFrom<ZUartConfig> for UartConfig {
+    type Error = ZephyrError;
+
+    fn try_from(other: ZUartConfig) -> Result<Self, Self::Error> {
+        let invalid = || ZephyrError::new_with_context(ErrorNumber::InvalidArgument, &CONTEXT);
+
+        let parity = [Parity::None, Parity::Odd, Parity::Even, Parity::Mark, Parity::Space]
+            .iter()
+            .find(|parity| **parity as u8 == other.parity)
+            .ok_or_else(invalid)?;
+        let stop_bits = [
+            StopBits::Half,
+            StopBits::One,
+            StopBits::OneAndHalf,
+            StopBits::Two,
+        ]
+        .iter()
+        .find(|stop_bits| **stop_bits as u8 == other.stop_bits)
+        .ok_or_else(invalid)?;
+        let data_bits = [
+            DataBits::Five,
+            DataBits::Six,
+            DataBits::Seven,
+            DataBits::Eight,
+            DataBits::Nine,
+        ]
+        .iter()
+        .find(|data_bits| **data_bits as u8 == other.data_bits)
+        .ok_or_else(invalid)?;
+        let flow_control = [
+            FlowControl::None,
+            FlowControl::RtsCts,
+            FlowControl::DtrDsr,
+            FlowControl::Rs485,
+        ]
+        .iter()
+        .find(|flow_control| **flow_control as u8 == other.flow_ctrl)
+        .ok_or_else(invalid)?;
+
+        Ok(UartConfig {
+            baud_rate: other.baudrate,
+            parity: *parity,
+            stop_bits: *stop_bits,
+            data_bits: *data_bits,
+            flow_control: *flow_control,
+        })
+    }
+}
+
+/// Wrapper to the `uart_poll_in` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// Returns `None` if no character has been received.
+pub unsafe fn poll_in(device: &Device) -> ZephyrResult<Option<u8>> {
+    let mut character = 0_u8;
+    let errno = zephyr_sys::syscalls::any::uart_poll_in(
+        device as *const Device,
+        &mut character as *mut u8,
+    );
+
+    match errno {
+        0 => Ok(Some(character)),
+        -1 => Ok(None),
+        errno => Err(ZephyrError::from_errno_with_context(errno, &CONTEXT)),
+    }
+}
+
+/// Wrapper to the `uart_poll_out` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This call blocks until the character has been transmitted.
+pub unsafe fn poll_out(device: &Device, character: u8) {
+    zephyr_sys::syscalls::any::uart_poll_out(device as *const Device, character)
+}
+
+/// Wrapper to the `uart_configure` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// This method might fail if the driver does not support run-time configuration or a parameter
+/// of `config`.
+pub unsafe fn configure(device: &Device, config: &UartConfig) -> ZephyrResult<()> {
+    let z_config: ZUartConfig = config.into();
+    let errno = zephyr_sys::syscalls::any::uart_configure(
+        device as *const Device,
+        &z_config as *const ZUartConfig,
+    );
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `uart_config_get` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// This method might fail if the driver does not support run-time configuration.
+pub unsafe fn config_get(device: &Device) -> ZephyrResult<UartConfig> {
+    let mut z_config: ZUartConfig = core::mem::zeroed();
+    let errno = zephyr_sys::syscalls::any::uart_config_get(
+        device as *const Device,
+        &mut z_config as *mut ZUartConfig,
+    );
+
+    if errno == 0 {
+        UartConfig::try_from(z_config)
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Line control signals of a UART, mostly used by the USB CDC-ACM UART.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LineControl {
+    BaudRate,
+    Rts,
+    Dtr,
+    Dcd,
+    Dsr,
+}
+
+impl From<LineControl> for u32 {
+    fn from(other: LineControl) -> Self {
+        match other {
+            LineControl::BaudRate => zephyr_sys::raw::uart_line_ctrl_UART_LINE_CTRL_BAUD_RATE,
+            LineControl::Rts => zephyr_sys::raw::uart_line_ctrl_UART_LINE_CTRL_RTS,
+            LineControl::Dtr => zephyr_sys::raw::uart_line_ctrl_UART_LINE_CTRL_DTR,
+            LineControl::Dcd => zephyr_sys::raw::uart_line_ctrl_UART_LINE_CTRL_DCD,
+            LineControl::Dsr => zephyr_sys::raw::uart_line_ctrl_UART_LINE_CTRL_DSR,
+        }
+    }
+}
+
+/// Wrapper to the `uart_line_ctrl_set` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// This method fails if Zephyr is built without `CONFIG_UART_LINE_CTRL`.
+pub unsafe fn line_ctrl_set(device: &Device, control: LineControl, value: u32) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::uart_line_ctrl_set(
+        device as *const Device,
+        control.into(),
+        value,
+    );
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `uart_line_ctrl_get` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+///
+/// This method fails if Zephyr is built without `CONFIG_UART_LINE_CTRL`.
+pub unsafe fn line_ctrl_get(device: &Device, control: LineControl) -> ZephyrResult<u32> {
+    let mut value = 0u32;
+    let errno = zephyr_sys::syscalls::any::uart_line_ctrl_get(
+        device as *const Device,
+        control.into(),
+        &mut value as *mut u32,
+    );
+
+    if errno == 0 {
+        Ok(value)
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// High level wrapper for a UART.
+///
+/// This essentially wraps the static reference of the underlying device and offers methods that use
+/// the safe syscall wrappers for an object oriented programming interface.
+pub struct Uart {
+    device: &'static Device,
+    // character that has been read by [Uart::is_read_ready] but not been returned yet
+    pending: Option<u8>,
+    // semaphores of the interrupt driven API the blocking traits of [io] wait for
+    #[cfg(feature = "embedded-io")]
+    events: Option<alloc::boxed::Box<io::IrqEvents>>,
+}
+
+impl Uart {
+    /// Creates a new [Uart] on the current interface.
+    ///
+    /// `device` MUST be a UART device. If `device` is not a UART device the behaviour when calling
+    /// any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Uart {
+            device,
+            pending: None,
+            #[cfg(feature = "embedded-io")]
+            events: None,
+        }
+    }
+
+    /// Creates a new [Uart] from a device checked to be a UART device.
+    pub fn from_device(device: TypedDevice<class::Uart>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Uart::new(device.device()) }
+    }
+
+    /// Read a received character without blocking. Returns `None` if no character has been
+    /// received.
+    pub fn read_byte(&mut self) -> ZephyrResult<Option<u8>> {
+        if let Some(character) = self.pending.take() {
+            return Ok(Some(character));
+        }
+        // device is required to be a UART device in constructor
+        unsafe { poll_in(self.device) }
+    }
+
+    /// Returns `true` if a character has been received, so [Uart::read_byte] will return it.
+    pub fn is_read_ready(&mut self) -> ZephyrResult<bool> {
+        if self.pending.is_none() {
+            self.pending = self.read_byte()?;
+        }
+        Ok(self.pending.is_some())
+    }
+
+    /// Write a single character, blocking until it has been transmitted.
+    pub fn write_byte(&mut self, character: u8) {
+        // device is required to be a UART device in constructor
+        unsafe { poll_out(self.device, character) }
+    }
+
+    /// Write all bytes of `data`, blocking until they have been transmitted.
+    pub fn write(&mut self, data: &[u8]) {
+        for character in data {
+            self.write_byte(*character);
+        }
+    }
+
+    /// Set the configuration of the UART.
+    ///
+    /// This method might fail if the driver does not support run-time configuration or a parameter
+    /// of `config`.
+    pub fn configure(&mut self, config: &UartConfig) -> ZephyrResult<()> {
+        // device is required to be a UART device in constructor
+        unsafe { configure(self.device, config) }
+    }
+
+    /// Returns the current configuration of the UART.
+    pub fn config(&self) -> ZephyrResult<UartConfig> {
+        // device is required to be a UART device in constructor
+        unsafe { config_get(self.device) }
+    }
+
+    /// Set a line control signal of the UART, e.g. [LineControl::Dcd] of a CDC-ACM UART.
+    pub fn set_line_control(&mut self, control: LineControl, value: u32) -> ZephyrResult<()> {
+        // device is required to be a UART device in constructor
+        unsafe { line_ctrl_set(self.device, control, value) }
+    }
+
+    /// Returns the value of a line control signal of the UART.
+    pub fn line_control(&self, control: LineControl) -> ZephyrResult<u32> {
+        // device is required to be a UART device in constructor
+        unsafe { line_ctrl_get(self.device, control) }
+    }
+}
+
+#[derive(Debug)]
+struct UartWrapperContext {}
+
+impl Context for UartWrapperContext {
+    fn name(&self) -> &'static str {
+        "uart wrapper"
+    }
+}
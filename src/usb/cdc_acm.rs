@@ -0,0 +1,133 @@
+//! Serial ports of the USB CDC-ACM class.
+//!
+//! A CDC-ACM port is a UART device (`zephyr,cdc-acm-uart` in the devicetree), so data is
+//! transferred with the [Uart] wrappers. The host signals an open terminal by setting DTR,
+//! devices usually wait for it before writing, as written data is dropped while no terminal is
+//! open.
+
+use super::CONTEXT;
+#[cfg(feature = "cdc-acm-dte-rate")]
+use crate::handlers::Handlers;
+use crate::uart::{LineControl, Uart};
+use crate::{ErrorNumber, ZephyrError, ZephyrResult};
+#[cfg(feature = "cdc-acm-dte-rate")]
+use alloc::boxed::Box;
+use core::time::Duration;
+pub use zephyr::device::Device;
+
+/// High level wrapper for a CDC-ACM port.
+pub struct CdcAcm {
+    device: &'static Device,
+    uart: Uart,
+}
+
+impl CdcAcm {
+    /// Creates a new [CdcAcm] port.
+    ///
+    /// `device` MUST be a CDC-ACM UART device. If `device` is not a CDC-ACM UART device the
+    /// behaviour when calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        CdcAcm {
+            device,
+            uart: Uart::new(device),
+        }
+    }
+
+    /// The UART used to transfer data over the port.
+    pub fn uart(&mut self) -> &mut Uart {
+        &mut self.uart
+    }
+
+    /// Consumes the port and returns its UART.
+    pub fn into_uart(self) -> Uart {
+        self.uart
+    }
+
+    /// Returns `true` if the host set DTR, i.e. a terminal is connected to the port.
+    pub fn is_dtr_set(&self) -> ZephyrResult<bool> {
+        Ok(self.uart.line_control(LineControl::Dtr)? != 0)
+    }
+
+    /// Returns `true` if the host set RTS.
+    pub fn is_rts_set(&self) -> ZephyrResult<bool> {
+        Ok(self.uart.line_control(LineControl::Rts)? != 0)
+    }
+
+    /// Baud rate the host configured with the line coding. The rate has no effect on the transfer
+    /// speed, but is used by some protocols to signal a mode, e.g. 1200 baud to enter a bootloader.
+    pub fn baud_rate(&self) -> ZephyrResult<u32> {
+        self.uart.line_control(LineControl::BaudRate)
+    }
+
+    /// Signal DCD (a carrier is detected) to the host.
+    pub fn set_dcd(&mut self, set: bool) -> ZephyrResult<()> {
+        self.uart.set_line_control(LineControl::Dcd, set as u32)
+    }
+
+    /// Signal DSR (the device is ready) to the host.
+    pub fn set_dsr(&mut self, set: bool) -> ZephyrResult<()> {
+        self.uart.set_line_control(LineControl::Dsr, set as u32)
+    }
+
+    /// Block until the host sets DTR, checking it every `interval`.
+    ///
+    /// Fails with [ErrorNumber::TryAgain] if DTR is not set within `timeout`.
+    pub fn wait_for_dtr(&self, interval: Duration, timeout: Option<Duration>) -> ZephyrResult<()> {
+        let start = crate::time::uptime();
+        while !self.is_dtr_set()? {
+            if let Some(timeout) = timeout {
+                if crate::time::uptime() - start >= timeout {
+                    return Err(ZephyrError::new_with_context(ErrorNumber::TryAgain, &CONTEXT));
+                }
+            }
+            unsafe { zephyr_sys::syscalls::any::k_sleep(crate::time::timeout(interval)) };
+        }
+        Ok(())
+    }
+
+    /// Call `f` with the new baud rate whenever the host changes the line coding.
+    ///
+    /// This is the equivalent of `cdc_acm_dte_rate_callback_set` and requires
+    /// `CONFIG_CDC_ACM_DTE_RATE_CALLBACK_SUPPORT`. `f` is called from the context of the USB
+    /// driver and replaces a previously set callback of the port.
+    #[cfg(feature = "cdc-acm-dte-rate")]
+    pub fn set_line_coding_callback<F>(&mut self, f: F) -> ZephyrResult<()>
+    where
+        F: FnMut(u32) + Send + 'static,
+    {
+        let device = self.device as *const Device;
+        let inserted = RATE_HANDLERS.insert(device as usize, Box::new(f));
+
+        // device is required to be a CDC-ACM UART device in constructor
+        let errno = unsafe {
+            zephyr_sys::raw::cdc_acm_dte_rate_callback_set(device, Some(rate_trampoline))
+        };
+        if errno != 0 {
+            if inserted.added {
+                RATE_HANDLERS.remove_id(inserted.id);
+            } else {
+                RATE_HANDLERS.restore(inserted.id, inserted.replaced);
+            }
+            return Err(ZephyrError::from_errno_with_context(errno, &CONTEXT));
+        }
+        Ok(())
+    }
+
+    /// The device of the port.
+    pub fn device(&self) -> &'static Device {
+        self.device
+    }
+}
+
+#[cfg(feature = "cdc-acm-dte-rate")]
+type RateHandler = dyn FnMut(u32) + Send;
+
+#[cfg(feature = "cdc-acm-dte-rate")]
+static RATE_HANDLERS: Handlers<usize, RateHandler> = Handlers::new();
+
+/// C callback that dispatches a changed baud rate to the closure registered for the port.
+#[cfg(feature = "cdc-acm-dte-rate")]
+extern "C" fn rate_trampoline(dev: *const Device, rate: u32) {
+    let device = dev as usize;
+    RATE_HANDLERS.call(|key| *key == device, |handler| handler(rate));
+}
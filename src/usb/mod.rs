@@ -0,0 +1,124 @@
+//! Wrappers for the Zephyr USB device stack.
+//!
+//! The device stack is enabled with [enable], which reports the state of the bus to an optional
+//! callback. Serial ports of the CDC-ACM class are UART devices, [cdc_acm::CdcAcm] wraps them in a
+//! [crate::uart::Uart] and adds the line state of the host. With the `usbd` feature the
+//! [usbd::UsbDevice] wraps a context of the new device stack instead.
+
+use crate::handlers::Handlers;
+use crate::{Context, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use core::ffi::c_int;
+
+pub mod cdc_acm;
+#[cfg(feature = "usbd")]
+pub mod usbd;
+
+const CONTEXT: UsbWrapperContext = UsbWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// State changes of the USB device controller reported to the callback of [enable_with].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum UsbStatus {
+    Error,
+    Reset,
+    Connected,
+    /// The host selected the configuration with the given value.
+    Configured(u8),
+    Disconnected,
+    Suspend,
+    Resume,
+    /// The host selected an alternate setting of an interface.
+    Interface,
+    SetHalt,
+    ClearHalt,
+    StartOfFrame,
+    Unknown,
+}
+
+impl UsbStatus {
+    /// # Safety
+    ///
+    /// `param` must be the parameter passed with `status` to the status callback.
+    unsafe fn from_raw(status: zephyr_sys::raw::usb_dc_status_code, param: *const u8) -> Self {
+        match status {
+            zephyr_sys::raw::usb_dc_status_code_USB_DC_ERROR => UsbStatus::Error,
+            zephyr_sys::raw::usb_dc_status_code_USB_DC_RESET => UsbStatus::Reset,
+            zephyr_sys::raw::usb_dc_status_code_USB_DC_CONNECTED => UsbStatus::Connected,
+            zephyr_sys::raw::usb_dc_status_code_USB_DC_CONFIGURED => {
+                // the parameter points to the configuration value
+                UsbStatus::Configured(param.as_ref().copied().unwrap_or(0))
+            }
+            zephyr_sys::raw::usb_dc_status_code_USB_DC_DISCONNECTED => UsbStatus::Disconnected,
+            zephyr_sys::raw::usb_dc_status_code_USB_DC_SUSPEND => UsbStatus::Suspend,
+            zephyr_sys::raw::usb_dc_status_code_USB_DC_RESUME => UsbStatus::Resume,
+            zephyr_sys::raw::usb_dc_status_code_USB_DC_INTERFACE => UsbStatus::Interface,
+            zephyr_sys::raw::usb_dc_status_code_USB_DC_SET_HALT => UsbStatus::SetHalt,
+            zephyr_sys::raw::usb_dc_status_code_USB_DC_CLEAR_HALT => UsbStatus::ClearHalt,
+            zephyr_sys::raw::usb_dc_status_code_USB_DC_SOF => UsbStatus::StartOfFrame,
+            _ => UsbStatus::Unknown,
+        }
+    }
+}
+
+/// Wrapper to the `usb_enable` function.
+///
+/// Enables the USB device stack without a status callback. This fails if the stack is already
+/// enabled, e.g. because `CONFIG_USB_DEVICE_INITIALIZE_AT_BOOT` is set.
+pub fn enable() -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::usb_enable(None) })
+}
+
+/// Enables the USB device stack and calls `f` on every state change of the device controller.
+///
+/// `f` is called from the context of the USB driver, which might be an interrupt. The callback
+/// stays registered until the stack is enabled again.
+pub fn enable_with<F>(f: F) -> ZephyrResult<()>
+where
+    F: FnMut(UsbStatus) + Send + 'static,
+{
+    let inserted = STATUS_HANDLER.insert((), Box::new(f));
+    let errno = unsafe { zephyr_sys::raw::usb_enable(Some(status_trampoline)) };
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        if inserted.added {
+            STATUS_HANDLER.remove_id(inserted.id);
+        } else {
+            STATUS_HANDLER.restore(inserted.id, inserted.replaced);
+        }
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `usb_disable` function.
+pub fn disable() -> ZephyrResult<()> {
+    result(unsafe { zephyr_sys::raw::usb_disable() })
+}
+
+/// The closure registered in [enable_with], the stack has a single status callback.
+static STATUS_HANDLER: Handlers<(), dyn FnMut(UsbStatus) + Send> = Handlers::new();
+
+/// C callback that dispatches a state change to the closure registered in [enable_with].
+extern "C" fn status_trampoline(status: zephyr_sys::raw::usb_dc_status_code, param: *const u8) {
+    // the parameter is passed by the driver together with the status
+    let status = unsafe { UsbStatus::from_raw(status, param) };
+    STATUS_HANDLER.call(|_| true, |handler| handler(status));
+}
+
+#[derive(Debug)]
+struct UsbWrapperContext {}
+
+impl Context for UsbWrapperContext {
+    fn name(&self) -> &'static str {
+        "usb wrapper"
+    }
+}
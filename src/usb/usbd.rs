@@ -0,0 +1,153 @@
+//! Wrappers for the new USB device stack (`CONFIG_USB_DEVICE_STACK_NEXT`).
+//!
+//! The context of the stack, including its descriptors and configurations, is defined in C with
+//! `USBD_DEVICE_DEFINE` and handed to [UsbDevice::new]. Notifications of the stack and its classes,
+//! e.g. a changed DTR of a CDC-ACM port, are delivered to [UsbDevice::set_message_callback].
+
+use super::{result, CONTEXT};
+use crate::handlers::Handlers;
+use crate::{ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::{usbd_context as ZUsbdContext, usbd_msg as ZUsbdMessage};
+
+/// Notifications of the USB device stack.
+#[derive(Copy, Clone, Debug)]
+pub enum UsbdMessage {
+    VbusReady,
+    VbusRemoved,
+    Resume,
+    Suspend,
+    Reset,
+    /// The host selected the configuration with the given value.
+    Configuration(i32),
+    /// The device controller reported an error.
+    UdcError(i32),
+    /// The stack failed to handle an event.
+    StackError(i32),
+    /// The host changed the line coding of the CDC-ACM port, see
+    /// [super::cdc_acm::CdcAcm::baud_rate].
+    CdcAcmLineCoding(&'static Device),
+    /// The host changed DTR or RTS of the CDC-ACM port, see
+    /// [super::cdc_acm::CdcAcm::is_dtr_set].
+    CdcAcmControlLineState(&'static Device),
+    Other(u32),
+}
+
+impl UsbdMessage {
+    /// # Safety
+    ///
+    /// `msg` must be a message passed to a message callback by the stack.
+    unsafe fn from_raw(msg: &ZUsbdMessage) -> Self {
+        let status = msg.__bindgen_anon_1.status;
+        match msg.type_ {
+            zephyr_sys::raw::usbd_msg_type_USBD_MSG_VBUS_READY => UsbdMessage::VbusReady,
+            zephyr_sys::raw::usbd_msg_type_USBD_MSG_VBUS_REMOVED => UsbdMessage::VbusRemoved,
+            zephyr_sys::raw::usbd_msg_type_USBD_MSG_RESUME => UsbdMessage::Resume,
+            zephyr_sys::raw::usbd_msg_type_USBD_MSG_SUSPEND => UsbdMessage::Suspend,
+            zephyr_sys::raw::usbd_msg_type_USBD_MSG_RESET => UsbdMessage::Reset,
+            zephyr_sys::raw::usbd_msg_type_USBD_MSG_CONFIGURATION => {
+                UsbdMessage::Configuration(status)
+            }
+            zephyr_sys::raw::usbd_msg_type_USBD_MSG_UDC_ERROR => UsbdMessage::UdcError(status),
+            zephyr_sys::raw::usbd_msg_type_USBD_MSG_STACK_ERROR => UsbdMessage::StackError(status),
+            // class messages carry the device of the class instance, which is static
+            zephyr_sys::raw::usbd_msg_type_USBD_MSG_CDC_ACM_LINE_CODING => {
+                match msg.__bindgen_anon_1.dev.as_ref() {
+                    Some(device) => UsbdMessage::CdcAcmLineCoding(&*(device as *const Device)),
+                    None => UsbdMessage::Other(msg.type_),
+                }
+            }
+            zephyr_sys::raw::usbd_msg_type_USBD_MSG_CDC_ACM_CONTROL_LINE_STATE => {
+                match msg.__bindgen_anon_1.dev.as_ref() {
+                    Some(device) => {
+                        UsbdMessage::CdcAcmControlLineState(&*(device as *const Device))
+                    }
+                    None => UsbdMessage::Other(msg.type_),
+                }
+            }
+            other => UsbdMessage::Other(other),
+        }
+    }
+}
+
+/// High level wrapper for a context of the USB device stack.
+pub struct UsbDevice {
+    context: &'static mut ZUsbdContext,
+}
+
+impl UsbDevice {
+    /// Creates a new [UsbDevice].
+    ///
+    /// `context` MUST be defined with `USBD_DEVICE_DEFINE` and have its descriptors and
+    /// configurations added. Only one [UsbDevice] may exist per context.
+    pub unsafe fn new(context: &'static mut ZUsbdContext) -> Self {
+        UsbDevice { context }
+    }
+
+    fn context_ptr(&mut self) -> *mut ZUsbdContext {
+        &mut *self.context as *mut ZUsbdContext
+    }
+
+    /// Wrapper to the `usbd_init` function. Initializes the device controller and the classes.
+    pub fn init(&mut self) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::usbd_init(self.context_ptr()) })
+    }
+
+    /// Wrapper to the `usbd_enable` function. Attaches the device to the bus.
+    pub fn enable(&mut self) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::usbd_enable(self.context_ptr()) })
+    }
+
+    /// Wrapper to the `usbd_disable` function. Detaches the device from the bus.
+    pub fn disable(&mut self) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::usbd_disable(self.context_ptr()) })
+    }
+
+    /// Wrapper to the `usbd_shutdown` function. Deinitializes the device controller.
+    pub fn shutdown(&mut self) -> ZephyrResult<()> {
+        result(unsafe { zephyr_sys::raw::usbd_shutdown(self.context_ptr()) })
+    }
+
+    /// Call `f` for every notification of the stack.
+    ///
+    /// This is the equivalent of `usbd_msg_register_cb`, which must be called before [init]. `f`
+    /// is called from the thread of the USB device stack.
+    ///
+    /// [init]: UsbDevice::init
+    pub fn set_message_callback<F>(&mut self, f: F) -> ZephyrResult<()>
+    where
+        F: FnMut(UsbdMessage) + Send + 'static,
+    {
+        let context = self.context_ptr();
+        let inserted = MESSAGE_HANDLERS.insert(context as usize, Box::new(f));
+
+        let errno =
+            unsafe { zephyr_sys::raw::usbd_msg_register_cb(context, Some(message_trampoline)) };
+        if errno != 0 {
+            if inserted.added {
+                MESSAGE_HANDLERS.remove_id(inserted.id);
+            } else {
+                MESSAGE_HANDLERS.restore(inserted.id, inserted.replaced);
+            }
+            return Err(ZephyrError::from_errno_with_context(errno, &CONTEXT));
+        }
+        Ok(())
+    }
+}
+
+type MessageHandler = dyn FnMut(UsbdMessage) + Send;
+
+static MESSAGE_HANDLERS: Handlers<usize, MessageHandler> = Handlers::new();
+
+/// C callback that dispatches a message to the closure registered for the context.
+extern "C" fn message_trampoline(context: *mut ZUsbdContext, msg: *const ZUsbdMessage) {
+    // the message is valid for the duration of the callback
+    let msg = match unsafe { msg.as_ref() } {
+        Some(msg) => msg,
+        None => return,
+    };
+    let message = unsafe { UsbdMessage::from_raw(msg) };
+    let context = context as usize;
+    MESSAGE_HANDLERS.call(|key| *key == context, |handler| handler(message));
+}
@@ -0,0 +1,66 @@
+//! Versions of the firmware, to report its identity e.g. in the Device Information Service of
+//! Bluetooth or over MQTT.
+//!
+//! The version of the running kernel is read with `sys_kernel_version_get`. The other constants are
+//! generated from the build directory `ZEPHYR_BINARY_DIR`: the version of the application from
+//! `app_version.h`, which Zephyr generates from the `VERSION` file of the application, and the
+//! build versions (usually from `git describe`) from `app_version.h` and `version.h`. They are
+//! `None` if the headers or their defines are missing. [BUILD_TIMESTAMP] is the time this crate was
+//! built, or `SOURCE_DATE_EPOCH` if set for reproducible builds.
+//!
+//! ```no_run
+//! use zephyr_rust_wrappers::version;
+//!
+//! let firmware = version::APP_VERSION_STRING.unwrap_or("unknown");
+//! println!("firmware {} on Zephyr {}", firmware, version::kernel_version());
+//! ```
+
+use core::fmt::{Display, Formatter};
+
+/// Version of the kernel, as in `KERNEL_VERSION_STRING`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct KernelVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+}
+
+impl Display for KernelVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Version of the application from its `VERSION` file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct AppVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+    pub tweak: u8,
+}
+
+/// Formats the version like `APP_VERSION_STRING`, with the tweak like
+/// `APP_VERSION_EXTENDED_STRING` if it is not 0.
+impl Display for AppVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if self.tweak != 0 {
+            write!(f, "+{}", self.tweak)?;
+        }
+        Ok(())
+    }
+}
+
+/// Wrapper to the `sys_kernel_version_get` function.
+pub fn kernel_version() -> KernelVersion {
+    // the version is encoded as 0xMMmmpp00
+    let version = unsafe { zephyr_sys::raw::sys_kernel_version_get() };
+    KernelVersion {
+        major: (version >> 24) as u8,
+        minor: (version >> 16) as u8,
+        patch: (version >> 8) as u8,
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/version.rs"));
@@ -0,0 +1,404 @@
+//! Wrappers for the Zephyr video driver API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! The video API is inlined in the Zephyr headers, so this module calls the driver API directly.
+//! Frames are captured into buffers allocated from a [MemorySlab]: buffers are added to the
+//! driver with [Video::add_buffers], filled buffers are returned by [Video::dequeue] as [Frame]
+//! and handed back to the driver with [Video::enqueue] once they have been processed.
+
+use crate::device::{class, TypedDevice};
+use crate::memory_slab::{MemorySlab, SlabBlock};
+use crate::time::Timeout;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ffi::c_int;
+use core::fmt::{Display, Formatter};
+use core::ops::Deref;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::{
+    video_buffer as ZVideoBuffer, video_caps as ZCapabilities, video_driver_api as VideoDriverApi,
+    video_format as ZFormat,
+};
+
+const CONTEXT: VideoWrapperContext = VideoWrapperContext {};
+
+fn result(errno: c_int) -> ZephyrResult<()> {
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+fn not_implemented() -> ZephyrError {
+    ZephyrError::new_with_context(ErrorNumber::NotImplemented, &CONTEXT)
+}
+
+/// Four character code of a pixel format, this is the equivalent of `video_fourcc`.
+pub const fn fourcc(code: &[u8; 4]) -> u32 {
+    (code[0] as u32) | ((code[1] as u32) << 8) | ((code[2] as u32) << 16) | ((code[3] as u32) << 24)
+}
+
+/// Pixel format of a video stream, identified by its four character code.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PixelFormat(pub u32);
+
+impl PixelFormat {
+    /// 8 bit Bayer pattern, blue green / green red.
+    pub const BGGR8: PixelFormat = PixelFormat(fourcc(b"BA81"));
+    /// 8 bit Bayer pattern, red green / green blue.
+    pub const RGGB8: PixelFormat = PixelFormat(fourcc(b"RGGB"));
+    pub const RGB565: PixelFormat = PixelFormat(fourcc(b"RGBP"));
+    pub const XRGB32: PixelFormat = PixelFormat(fourcc(b"BX24"));
+    pub const YUYV: PixelFormat = PixelFormat(fourcc(b"YUYV"));
+    /// 8 bit greyscale.
+    pub const GREY: PixelFormat = PixelFormat(fourcc(b"GREY"));
+    pub const JPEG: PixelFormat = PixelFormat(fourcc(b"JPEG"));
+}
+
+impl Display for PixelFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        for byte in self.0.to_le_bytes().iter() {
+            write!(f, "{}", *byte as char)?;
+        }
+        Ok(())
+    }
+}
+
+/// Endpoint of a video device, devices with an input and an output (e.g. an image signal
+/// processor) are configured per endpoint.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Endpoint {
+    Any,
+    In,
+    Out,
+    All,
+}
+
+impl From<Endpoint> for zephyr_sys::raw::video_endpoint_id {
+    fn from(other: Endpoint) -> Self {
+        match other {
+            Endpoint::Any => zephyr_sys::raw::video_endpoint_id_VIDEO_EP_ANY,
+            Endpoint::In => zephyr_sys::raw::video_endpoint_id_VIDEO_EP_IN,
+            Endpoint::Out => zephyr_sys::raw::video_endpoint_id_VIDEO_EP_OUT,
+            Endpoint::All => zephyr_sys::raw::video_endpoint_id_VIDEO_EP_ALL,
+        }
+    }
+}
+
+/// Format of a video stream.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Format {
+    pub pixel_format: PixelFormat,
+    pub width: u32,
+    pub height: u32,
+    /// Bytes per line, including padding.
+    pub pitch: u32,
+}
+
+impl Format {
+    /// Size of a frame of this format in bytes, for uncompressed formats.
+    pub fn frame_size(&self) -> usize {
+        self.pitch as usize * self.height as usize
+    }
+}
+
+impl From<&ZFormat> for Format {
+    fn from(other: &ZFormat) -> Self {
+        Format {
+            pixel_format: PixelFormat(other.pixelformat),
+            width: other.width,
+            height: other.height,
+            pitch: other.pitch,
+        }
+    }
+}
+
+impl From<&Format> for ZFormat {
+    fn from(other: &Format) -> Self {
+        // zeroed as newer Zephyr versions extend the struct
+        let mut format: ZFormat = unsafe { core::mem::zeroed() };
+        format.pixelformat = other.pixel_format.0;
+        format.width = other.width;
+        format.height = other.height;
+        format.pitch = other.pitch;
+        format
+    }
+}
+
+/// Range of frame sizes a device supports for a pixel format.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FormatCapability {
+    pub pixel_format: PixelFormat,
+    pub width_min: u32,
+    pub width_max: u32,
+    pub height_min: u32,
+    pub height_max: u32,
+    pub width_step: u16,
+    pub height_step: u16,
+}
+
+/// Capabilities of a video device, as returned by [Video::capabilities].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Capabilities {
+    pub formats: Vec<FormatCapability>,
+    /// Number of buffers the driver needs to be queued before the stream starts.
+    pub min_buffers: u8,
+}
+
+// the video buffer is the first field, so the driver's pointer to it points to the whole entry
+#[repr(C)]
+struct FrameEntry {
+    raw: ZVideoBuffer,
+    block: SlabBlock<'static>,
+}
+
+/// Captured frame, its buffer is freed when dropped unless it is handed back to the driver with
+/// [Video::enqueue].
+///
+/// The frame dereferences to the bytes written by the driver.
+pub struct Frame {
+    entry: Box<FrameEntry>,
+}
+
+impl Frame {
+    fn new(block: SlabBlock<'static>) -> Self {
+        // zeroed as newer Zephyr versions extend the struct
+        let mut raw: ZVideoBuffer = unsafe { core::mem::zeroed() };
+        raw.size = block.len() as u32;
+        let mut entry = Box::new(FrameEntry { raw, block });
+        entry.raw.buffer = entry.block.as_mut_ptr();
+        Frame { entry }
+    }
+
+    fn into_raw(self) -> *mut ZVideoBuffer {
+        Box::into_raw(self.entry) as *mut ZVideoBuffer
+    }
+
+    /// # Safety
+    ///
+    /// `raw` MUST be returned by [Frame::into_raw] and not be owned by the driver anymore.
+    unsafe fn from_raw(raw: *mut ZVideoBuffer) -> Self {
+        Frame {
+            entry: Box::from_raw(raw as *mut FrameEntry),
+        }
+    }
+
+    /// Number of bytes written by the driver.
+    pub fn bytes_used(&self) -> usize {
+        self.entry.raw.bytesused as usize
+    }
+
+    /// Capacity of the buffer in bytes.
+    pub fn capacity(&self) -> usize {
+        self.entry.block.len()
+    }
+
+    /// Uptime in milliseconds when the frame was captured.
+    pub fn timestamp(&self) -> u32 {
+        self.entry.raw.timestamp
+    }
+}
+
+impl Deref for Frame {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.entry.block[..self.bytes_used().min(self.capacity())]
+    }
+}
+
+/// High level wrapper for a video device.
+pub struct Video {
+    device: &'static Device,
+    endpoint: Endpoint,
+}
+
+impl Video {
+    /// Creates a new [Video] capturing from `endpoint`, usually [Endpoint::Out] for cameras.
+    ///
+    /// `device` MUST be a video device. If `device` is not a video device the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device, endpoint: Endpoint) -> Self {
+        Video { device, endpoint }
+    }
+
+    /// Creates a new [Video] from a device checked to be a video device.
+    pub fn from_device(device: TypedDevice<class::Video>, endpoint: Endpoint) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Video::new(device.device(), endpoint) }
+    }
+
+    fn api(&self) -> Option<&VideoDriverApi> {
+        // device is required to be a video device in constructor
+        unsafe { (self.device.api as *const VideoDriverApi).as_ref() }
+    }
+
+    /// Capabilities of the endpoint. This is the equivalent of `video_get_caps`.
+    pub fn capabilities(&self) -> ZephyrResult<Capabilities> {
+        let get_caps = self
+            .api()
+            .and_then(|api| api.get_caps)
+            .ok_or_else(not_implemented)?;
+        let mut caps: ZCapabilities = unsafe { core::mem::zeroed() };
+        result(unsafe {
+            (get_caps)(
+                self.device as *const Device,
+                self.endpoint.into(),
+                &mut caps as *mut ZCapabilities,
+            )
+        })?;
+
+        let mut formats = Vec::new();
+        let mut cap = caps.format_caps;
+        // the list is terminated by an entry with pixel format 0
+        while let Some(format) = unsafe { cap.as_ref() } {
+            if format.pixelformat == 0 {
+                break;
+            }
+            formats.push(FormatCapability {
+                pixel_format: PixelFormat(format.pixelformat),
+                width_min: format.width_min,
+                width_max: format.width_max,
+                height_min: format.height_min,
+                height_max: format.height_max,
+                width_step: format.width_step,
+                height_step: format.height_step,
+            });
+            cap = unsafe { cap.add(1) };
+        }
+        Ok(Capabilities {
+            formats,
+            min_buffers: caps.min_vbuf_count,
+        })
+    }
+
+    /// Set the format of the endpoint. This is the equivalent of `video_set_format`.
+    pub fn set_format(&mut self, format: &Format) -> ZephyrResult<()> {
+        let set_format = self
+            .api()
+            .and_then(|api| api.set_format)
+            .ok_or_else(not_implemented)?;
+        let mut raw: ZFormat = format.into();
+        result(unsafe {
+            (set_format)(
+                self.device as *const Device,
+                self.endpoint.into(),
+                &mut raw as *mut ZFormat,
+            )
+        })
+    }
+
+    /// Current format of the endpoint. This is the equivalent of `video_get_format`.
+    pub fn format(&self) -> ZephyrResult<Format> {
+        let get_format = self
+            .api()
+            .and_then(|api| api.get_format)
+            .ok_or_else(not_implemented)?;
+        let mut raw: ZFormat = unsafe { core::mem::zeroed() };
+        result(unsafe {
+            (get_format)(
+                self.device as *const Device,
+                self.endpoint.into(),
+                &mut raw as *mut ZFormat,
+            )
+        })?;
+        Ok((&raw).into())
+    }
+
+    /// Allocate `count` buffers from `slab` and queue them. The block size of `slab` should be at
+    /// least the frame size of the format.
+    pub fn add_buffers(&mut self, slab: &'static MemorySlab, count: usize) -> ZephyrResult<()> {
+        for _ in 0..count {
+            let block = slab.alloc(crate::time::NO_WAIT)?;
+            self.enqueue(Frame::new(block))?;
+        }
+        Ok(())
+    }
+
+    /// Hand the buffer of `frame` back to the driver to be filled again. This is the equivalent
+    /// of `video_enqueue`.
+    pub fn enqueue(&mut self, frame: Frame) -> ZephyrResult<()> {
+        let enqueue = self
+            .api()
+            .and_then(|api| api.enqueue)
+            .ok_or_else(not_implemented)?;
+        let raw = frame.into_raw();
+        let errno = unsafe { (enqueue)(self.device as *const Device, self.endpoint.into(), raw) };
+        if errno != 0 {
+            // the driver did not take the buffer, so it is still owned here
+            drop(unsafe { Frame::from_raw(raw) });
+            return Err(ZephyrError::from_errno_with_context(errno, &CONTEXT));
+        }
+        Ok(())
+    }
+
+    /// Wait up to `timeout` for a captured frame. This is the equivalent of `video_dequeue`.
+    pub fn dequeue(&mut self, timeout: Timeout) -> ZephyrResult<Frame> {
+        let dequeue = self
+            .api()
+            .and_then(|api| api.dequeue)
+            .ok_or_else(not_implemented)?;
+        let mut raw: *mut ZVideoBuffer = core::ptr::null_mut();
+        result(unsafe {
+            (dequeue)(
+                self.device as *const Device,
+                self.endpoint.into(),
+                &mut raw as *mut *mut ZVideoBuffer,
+                timeout,
+            )
+        })?;
+        // all buffers of the driver have been queued by `enqueue`
+        Ok(unsafe { Frame::from_raw(raw) })
+    }
+
+    /// Start capturing. This is the equivalent of `video_stream_start`.
+    pub fn start(&mut self) -> ZephyrResult<()> {
+        let stream_start = self
+            .api()
+            .and_then(|api| api.stream_start)
+            .ok_or_else(not_implemented)?;
+        result(unsafe { (stream_start)(self.device as *const Device) })
+    }
+
+    /// Stop capturing. This is the equivalent of `video_stream_stop`.
+    pub fn stop(&mut self) -> ZephyrResult<()> {
+        let stream_stop = self
+            .api()
+            .and_then(|api| api.stream_stop)
+            .ok_or_else(not_implemented)?;
+        result(unsafe { (stream_stop)(self.device as *const Device) })
+    }
+
+    /// Return all queued buffers, so they can be dequeued. This is the equivalent of
+    /// `video_flush` with `cancel` set.
+    fn cancel(&mut self) -> ZephyrResult<()> {
+        let flush = self
+            .api()
+            .and_then(|api| api.flush)
+            .ok_or_else(not_implemented)?;
+        result(unsafe { (flush)(self.device as *const Device, self.endpoint.into(), true) })
+    }
+}
+
+impl Drop for Video {
+    fn drop(&mut self) {
+        // buffers still owned by the driver are dequeued and freed, errors cannot be reported here
+        let _ = self.stop();
+        if self.cancel().is_ok() {
+            while self.dequeue(crate::time::NO_WAIT).is_ok() {}
+        }
+    }
+}
+
+#[derive(Debug)]
+struct VideoWrapperContext {}
+
+impl Context for VideoWrapperContext {
+    fn name(&self) -> &'static str {
+        "video wrapper"
+    }
+}
@@ -0,0 +1,288 @@
+//! Syscalls and high level wrappers for the Zephyr watchdog API.
+//!
+//! Some functions in the high level API still are marked as `unsafe` as the required checks cannot be
+//! performed to offer a safe API.
+//!
+//! Timeouts are installed with [Watchdog::install_timeout] before the watchdog is started with
+//! [Watchdog::setup]. Each timeout is fed through its [WatchdogChannel], which can be moved to the
+//! thread that checks the health of the application.
+
+use crate::device::{class, TypedDevice};
+use crate::handlers::Handlers;
+use crate::{Context, ErrorNumber, ZephyrError, ZephyrResult};
+use alloc::boxed::Box;
+use core::time::Duration;
+pub use zephyr::device::Device;
+use zephyr_sys::raw::{wdt_driver_api as WdtDriverApi, wdt_timeout_cfg as ZTimeoutConfig};
+
+const CONTEXT: WatchdogWrapperContext = WatchdogWrapperContext {};
+
+/// What is reset when a timeout expires.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ResetBehavior {
+    /// Nothing is reset, only the callback is called.
+    None,
+    /// Only the CPU core is reset.
+    CpuCore,
+    /// The whole SoC is reset.
+    Soc,
+}
+
+impl From<ResetBehavior> for u8 {
+    fn from(other: ResetBehavior) -> Self {
+        match other {
+            ResetBehavior::None => zephyr_sys::raw::WDT_FLAG_RESET_NONE as u8,
+            ResetBehavior::CpuCore => zephyr_sys::raw::WDT_FLAG_RESET_CPU_CORE as u8,
+            ResetBehavior::Soc => zephyr_sys::raw::WDT_FLAG_RESET_SOC as u8,
+        }
+    }
+}
+
+/// Behavior of the watchdog while the CPU does not run the application.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct WatchdogOptions {
+    /// Pause the watchdog while the CPU is sleeping.
+    pub pause_in_sleep: bool,
+    /// Pause the watchdog while the CPU is halted by the debugger.
+    pub pause_halted_by_debug: bool,
+}
+
+impl From<WatchdogOptions> for u8 {
+    fn from(other: WatchdogOptions) -> Self {
+        let mut options = 0;
+        if other.pause_in_sleep {
+            options |= zephyr_sys::raw::WDT_OPT_PAUSE_IN_SLEEP as u8;
+        }
+        if other.pause_halted_by_debug {
+            options |= zephyr_sys::raw::WDT_OPT_PAUSE_HALTED_BY_DBG as u8;
+        }
+        options
+    }
+}
+
+/// Configuration of a watchdog timeout.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TimeoutConfig {
+    /// Feeding the channel before this time is treated like an expired timeout.
+    pub window_min: Duration,
+    /// Time after which the timeout expires if the channel is not fed.
+    pub window_max: Duration,
+    pub reset: ResetBehavior,
+}
+
+impl TimeoutConfig {
+    /// Creates a timeout resetting the SoC after `timeout` without a lower window limit.
+    pub const fn new(timeout: Duration) -> Self {
+        TimeoutConfig {
+            window_min: Duration::from_millis(0),
+            window_max: timeout,
+            reset: ResetBehavior::Soc,
+        }
+    }
+
+    fn raw(&self, callback: zephyr_sys::raw::wdt_callback_t) -> ZTimeoutConfig {
+        // the configuration contains optional fields depending on the Kconfig, start from all zeros
+        let mut config: ZTimeoutConfig = unsafe { core::mem::zeroed() };
+        config.window.min = self.window_min.as_millis() as u32;
+        config.window.max = self.window_max.as_millis() as u32;
+        config.callback = callback;
+        config.flags = self.reset.into();
+        config
+    }
+}
+
+/// Equivalent of the `wdt_install_timeout` API function, returns the id of the installed channel.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the API function of the driver and wraps the error number in a safe error
+/// type.
+///
+/// This method fails with [ErrorNumber::NotImplemented] if the driver does not expose this call.
+pub unsafe fn install_timeout(device: &Device, config: &ZTimeoutConfig) -> ZephyrResult<i32> {
+    // convert void pointer from C API to a watchdog driver API Rust struct
+    let api = (device.api as *const WdtDriverApi).as_ref();
+
+    match api.and_then(|api| api.install_timeout) {
+        Some(install_timeout) => {
+            let id = (install_timeout)(device as *const Device, config as *const ZTimeoutConfig);
+
+            if id >= 0 {
+                Ok(id)
+            } else {
+                Err(ZephyrError::from_errno_with_context(id, &CONTEXT))
+            }
+        }
+        None => Err(ZephyrError::new_with_context(
+            ErrorNumber::NotImplemented,
+            &CONTEXT,
+        )),
+    }
+}
+
+/// Wrapper to the `wdt_setup` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn setup(device: &Device, options: u8) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::wdt_setup(device as *const Device, options);
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `wdt_disable` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn disable(device: &Device) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::wdt_disable(device as *const Device);
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// Wrapper to the `wdt_feed` syscall.
+///
+/// `device` MUST be a `'static` reference to a device descriptor obtained from the Zephyr API.
+/// This wrapper calls the syscall and wraps the error number in a safe error type.
+pub unsafe fn feed(device: &Device, channel_id: i32) -> ZephyrResult<()> {
+    let errno = zephyr_sys::syscalls::any::wdt_feed(device as *const Device, channel_id);
+
+    if errno == 0 {
+        Ok(())
+    } else {
+        Err(ZephyrError::from_errno_with_context(errno, &CONTEXT))
+    }
+}
+
+/// High level wrapper for a watchdog device.
+pub struct Watchdog {
+    device: &'static Device,
+}
+
+impl Watchdog {
+    /// Creates a new [Watchdog] on the current interface.
+    ///
+    /// `device` MUST be a watchdog device. If `device` is not a watchdog device the behaviour when
+    /// calling any method is undefined!
+    pub unsafe fn new(device: &'static Device) -> Self {
+        Watchdog { device }
+    }
+
+    /// Creates a new [Watchdog] from a device checked to be a watchdog device.
+    pub fn from_device(device: TypedDevice<class::Watchdog>) -> Self {
+        // the class of the device is checked by the handle
+        unsafe { Watchdog::new(device.device()) }
+    }
+
+    /// Install a timeout, must be called before [Watchdog::setup].
+    pub fn install_timeout(&mut self, config: &TimeoutConfig) -> ZephyrResult<WatchdogChannel> {
+        // device is required to be a watchdog device in constructor
+        let id = unsafe { install_timeout(self.device, &config.raw(None)) }?;
+        Ok(WatchdogChannel {
+            device: self.device,
+            id,
+        })
+    }
+
+    /// Install a timeout calling `f` with the channel id when it expires.
+    ///
+    /// The closure is called from the interrupt of the watchdog, shortly before the reset if the
+    /// timeout resets the system.
+    pub fn install_timeout_with<F>(
+        &mut self,
+        config: &TimeoutConfig,
+        f: F,
+    ) -> ZephyrResult<WatchdogChannel>
+    where
+        F: FnMut(i32) + Send + 'static,
+    {
+        // device is required to be a watchdog device in constructor
+        let id =
+            unsafe { install_timeout(self.device, &config.raw(Some(timeout_trampoline))) }?;
+
+        let key = TimeoutKey {
+            device: self.device as *const Device as usize,
+            channel_id: id,
+        };
+        TIMEOUT_HANDLERS.insert(key, Box::new(f));
+
+        Ok(WatchdogChannel {
+            device: self.device,
+            id,
+        })
+    }
+
+    /// Start the watchdog with all installed timeouts.
+    pub fn setup(&mut self, options: WatchdogOptions) -> ZephyrResult<()> {
+        // device is required to be a watchdog device in constructor
+        unsafe { setup(self.device, options.into()) }
+    }
+
+    /// Stop the watchdog and remove all timeouts. Not all watchdogs can be stopped once started.
+    pub fn disable(&mut self) -> ZephyrResult<()> {
+        // device is required to be a watchdog device in constructor
+        unsafe { disable(self.device) }?;
+        let device = self.device as *const Device as usize;
+        TIMEOUT_HANDLERS.remove_where(|key| key.device == device);
+        Ok(())
+    }
+}
+
+/// Handle to feed a timeout installed on a [Watchdog].
+#[derive(Copy, Clone)]
+pub struct WatchdogChannel {
+    device: &'static Device,
+    id: i32,
+}
+
+// feeding is a syscall which may be called from any thread
+unsafe impl Send for WatchdogChannel {}
+
+impl WatchdogChannel {
+    /// Id of the channel assigned by the driver.
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Feed the channel, restarting its timeout.
+    pub fn feed(&self) -> ZephyrResult<()> {
+        // device is a watchdog device as the channel is created by a Watchdog
+        unsafe { feed(self.device, self.id) }
+    }
+}
+
+/// Channel of a device a closure is installed for with [Watchdog::install_timeout_with].
+#[derive(PartialEq)]
+struct TimeoutKey {
+    device: usize,
+    channel_id: i32,
+}
+
+type TimeoutHandler = dyn FnMut(i32) + Send;
+
+static TIMEOUT_HANDLERS: Handlers<TimeoutKey, TimeoutHandler> = Handlers::new();
+
+/// C callback that dispatches an expired timeout to the closure registered for the channel.
+extern "C" fn timeout_trampoline(dev: *const Device, channel_id: core::ffi::c_int) {
+    let device = dev as usize;
+    TIMEOUT_HANDLERS.call(
+        |key| key.device == device && key.channel_id == channel_id,
+        |handler| handler(channel_id),
+    );
+}
+
+#[derive(Debug)]
+struct WatchdogWrapperContext {}
+
+impl Context for WatchdogWrapperContext {
+    fn name(&self) -> &'static str {
+        "watchdog wrapper"
+    }
+}
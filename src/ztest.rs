@@ -0,0 +1,281 @@
+//! Test suites of Rust functions run by ztest, the test framework of Zephyr, requires
+//! `CONFIG_ZTEST` and the `ztest` feature.
+//!
+//! [crate::ztest_suite] registers a suite like `ZTEST_SUITE` and its tests like `ZTEST`, so the
+//! tests run on the board or `native_sim` with the `main` of ztest and are reported to twister like
+//! tests written in C. The suite becomes a module holding the tests:
+//!
+//! ```no_run
+//! use zephyr_rust_wrappers::{zassert, zassert_eq, ztest_suite};
+//!
+//! fn power_on() {}
+//!
+//! ztest_suite! {
+//!     pub sensor_tests, before = power_on, {
+//!         #[zephyr_test]
+//!         fn reads_in_range() {
+//!             let value = 21;
+//!             zassert!(value < 85, "{} out of range", value);
+//!         }
+//!
+//!         #[zephyr_test]
+//!         fn adds() {
+//!             zassert_eq!(1 + 1, 2);
+//!         }
+//!     }
+//! }
+//!# fn main() {}
+//! ```
+//!
+//! The hooks `setup` and `teardown` run once before and after the suite, `before` and `after`
+//! around every test. They are optional, but given in this order. Failed assertions are reported
+//! through ztest with their location and message and end the test, like `zassert` in C.
+
+use alloc::string::String;
+use core::cell::UnsafeCell;
+use core::ffi::{c_char, c_void};
+use core::fmt::{Arguments, Write};
+use zephyr_sys::raw::{
+    ztest_suite_node as ZSuiteNode, ztest_suite_stats as ZSuiteStats, ztest_unit_test as ZUnitTest,
+    ztest_unit_test_stats as ZUnitTestStats,
+};
+
+/// Setup hook of a suite, the fixture is not used by Rust suites.
+#[doc(hidden)]
+pub type SetupHook = Option<unsafe extern "C" fn() -> *mut c_void>;
+/// Before, after or teardown hook of a suite.
+#[doc(hidden)]
+pub type Hook = Option<unsafe extern "C" fn(fixture: *mut c_void)>;
+
+/// Statistics of a suite or test, written by ztest.
+#[doc(hidden)]
+#[repr(transparent)]
+pub struct Stats<T>(UnsafeCell<T>);
+
+// the statistics are only accessed by ztest
+unsafe impl<T> Sync for Stats<T> {}
+
+impl<T> Stats<T> {
+    #[doc(hidden)]
+    pub const fn new() -> Self {
+        // zeroed like the statistics of C suites, which are in .bss
+        Stats(UnsafeCell::new(unsafe { core::mem::zeroed() }))
+    }
+
+    const fn get(&'static self) -> *mut T {
+        self.0.get()
+    }
+}
+
+/// Statistics of a [TestSuite].
+#[doc(hidden)]
+pub type SuiteStats = Stats<ZSuiteStats>;
+/// Statistics of a [UnitTest].
+#[doc(hidden)]
+pub type TestStats = Stats<ZUnitTestStats>;
+
+/// Test suite defined with [crate::ztest_suite].
+///
+/// The layout matches `struct ztest_suite_node`, the suite is read by ztest.
+#[repr(transparent)]
+pub struct TestSuite(ZSuiteNode);
+
+// the suite is immutable, only the statistics are written through a pointer
+unsafe impl Sync for TestSuite {}
+
+impl TestSuite {
+    /// Used by [crate::ztest_suite], the name MUST be NUL terminated.
+    #[doc(hidden)]
+    pub const fn new(
+        name: &'static str,
+        setup: SetupHook,
+        before: Hook,
+        after: Hook,
+        teardown: Hook,
+        stats: &'static SuiteStats,
+    ) -> Self {
+        TestSuite(ZSuiteNode {
+            name: name.as_ptr() as *const c_char,
+            setup,
+            before,
+            after,
+            teardown,
+            predicate: None,
+            stats: stats.get(),
+        })
+    }
+}
+
+/// Test of a [TestSuite] defined with [crate::ztest_suite].
+///
+/// The layout matches `struct ztest_unit_test`, the test is read by ztest.
+#[repr(transparent)]
+pub struct UnitTest(ZUnitTest);
+
+// the test is immutable, only the statistics are written through a pointer
+unsafe impl Sync for UnitTest {}
+
+impl UnitTest {
+    /// Used by [crate::ztest_suite], the names MUST be NUL terminated.
+    #[doc(hidden)]
+    pub const fn new(
+        suite: &'static str,
+        name: &'static str,
+        test: unsafe extern "C" fn(fixture: *mut c_void),
+        stats: &'static TestStats,
+    ) -> Self {
+        UnitTest(ZUnitTest {
+            test_suite_name: suite.as_ptr() as *const c_char,
+            name: name.as_ptr() as *const c_char,
+            test: Some(test),
+            thread_options: 0,
+            stats: stats.get(),
+        })
+    }
+}
+
+/// Reports a failed assertion at `file:line` with `message` and fails the running test. Wrapper to
+/// the `ztest_test_fail` function.
+///
+/// The test thread is aborted, so the function only returns without `CONFIG_MULTITHREADING`, the
+/// test MUST return then. [crate::zassert] does both.
+pub fn fail(file: &str, line: u32, message: Arguments<'_>) {
+    let mut text = String::new();
+    // formatting into a string never fails
+    let _ = write!(
+        text,
+        "\n    Assertion failed at {}:{}: {}\n\0",
+        file, line, message
+    );
+    unsafe {
+        zephyr_sys::raw::printk(b"%s\0".as_ptr() as *const c_char, text.as_ptr());
+        zephyr_sys::raw::ztest_test_fail();
+    }
+}
+
+/// Skips the running test, e.g. if the hardware it needs is missing. Wrapper to the
+/// `ztest_test_skip` function.
+///
+/// Like [fail], the function only returns without `CONFIG_MULTITHREADING`.
+pub fn skip() {
+    unsafe { zephyr_sys::raw::ztest_test_skip() };
+}
+
+/// Registers a test suite with ztest, the equivalent of `ZTEST_SUITE` and `ZTEST`.
+///
+/// Parameters are the visibility and name of the module created for the suite, optionally the
+/// hooks `setup`, `before`, `after` and `teardown` as `name = function` in this order and the
+/// tests, functions without parameters annotated with `#[zephyr_test]`.
+#[macro_export]
+macro_rules! ztest_suite {
+    ($vis:vis $suite:ident,
+        $(setup = $setup:path,)?
+        $(before = $before:path,)?
+        $(after = $after:path,)?
+        $(teardown = $teardown:path,)?
+        { $(#[zephyr_test] $(#[$attr:meta])* fn $test:ident() $body:block)* }) => {
+        $vis mod $suite {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $(
+                $(#[$attr])*
+                pub fn $test() $body
+
+                const _: () = {
+                    unsafe extern "C" fn wrapper(_fixture: *mut ::core::ffi::c_void) {
+                        $test()
+                    }
+                    static STATS: $crate::ztest::TestStats = $crate::ztest::TestStats::new();
+                    #[used]
+                    #[link_section = "._ztest_unit_test.static.zephyr_rust_wrappers"]
+                    static TEST: $crate::ztest::UnitTest = $crate::ztest::UnitTest::new(
+                        concat!(stringify!($suite), "\0"),
+                        concat!(stringify!($test), "\0"),
+                        wrapper,
+                        &STATS,
+                    );
+                };
+            )*
+
+            const _: () = {
+                static STATS: $crate::ztest::SuiteStats = $crate::ztest::SuiteStats::new();
+                #[used]
+                #[link_section = "._ztest_suite_node.static.zephyr_rust_wrappers"]
+                static SUITE: $crate::ztest::TestSuite = $crate::ztest::TestSuite::new(
+                    concat!(stringify!($suite), "\0"),
+                    $crate::__ztest_setup!($($setup)?),
+                    $crate::__ztest_hook!($($before)?),
+                    $crate::__ztest_hook!($($after)?),
+                    $crate::__ztest_hook!($($teardown)?),
+                    &STATS,
+                );
+            };
+        }
+    };
+}
+
+/// Expands to the setup hook of [crate::ztest_suite] calling `$setup`, if any.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ztest_setup {
+    () => {
+        None
+    };
+    ($setup:path) => {{
+        unsafe extern "C" fn setup() -> *mut ::core::ffi::c_void {
+            $setup();
+            ::core::ptr::null_mut()
+        }
+        Some(setup)
+    }};
+}
+
+/// Expands to a before, after or teardown hook of [crate::ztest_suite] calling `$hook`, if any.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ztest_hook {
+    () => {
+        None
+    };
+    ($hook:path) => {{
+        unsafe extern "C" fn hook(_fixture: *mut ::core::ffi::c_void) {
+            $hook();
+        }
+        Some(hook)
+    }};
+}
+
+/// Asserts that `cond` is true in a test of [crate::ztest_suite], the equivalent of `zassert`.
+///
+/// On failure, the test fails with the optional message formatted like `format!`.
+#[macro_export]
+macro_rules! zassert {
+    ($cond:expr $(,)?) => {
+        $crate::zassert!($cond, "{}", stringify!($cond))
+    };
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            $crate::ztest::fail(file!(), line!(), format_args!($($arg)+));
+            return;
+        }
+    };
+}
+
+/// Asserts that `left` equals `right` in a test of [crate::ztest_suite], the equivalent of
+/// `zassert_equal`.
+#[macro_export]
+macro_rules! zassert_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => $crate::zassert!(
+                *left == *right,
+                "{} == {}: {:?} != {:?}",
+                stringify!($left),
+                stringify!($right),
+                left,
+                right
+            ),
+        }
+    };
+}
@@ -0,0 +1,145 @@
+//! Host tests of the GPIO and sensor wrappers against the mocked syscalls.
+#![cfg(feature = "mock-syscalls")]
+
+use zephyr_rust_wrappers::device::{class, TypedDevice};
+use zephyr_rust_wrappers::gpio::GpioPin;
+use zephyr_rust_wrappers::mock::{self, Mock};
+use zephyr_rust_wrappers::sensor::{Sensor, SensorChannel, SensorValue};
+use zephyr_rust_wrappers::ErrorNumber;
+
+fn gpio_pin(mock: &Mock, pin: u8) -> GpioPin {
+    let port = TypedDevice::<class::Gpio>::new(mock::device("gpio0")).unwrap();
+    let led = GpioPin::from_device(port, pin, 0).unwrap();
+    mock.clear_calls();
+    led
+}
+
+fn sensor(mock: &Mock) -> Sensor {
+    let device = TypedDevice::<class::Sensor>::new(mock::device("sensor0")).unwrap();
+    mock.clear_calls();
+    Sensor::from_device(device)
+}
+
+#[test]
+fn gpio_pin_is_configured_on_creation() {
+    let mock = Mock::install();
+    let port = TypedDevice::<class::Gpio>::new(mock::device("gpio0")).unwrap();
+    GpioPin::from_device(port, 3, 0x200).unwrap();
+
+    let calls = mock.calls();
+    assert_eq!(calls.last().unwrap().name, "gpio_pin_configure");
+    assert_eq!(calls.last().unwrap().args, [3, 0x200]);
+}
+
+#[test]
+fn gpio_set_value_sets_and_clears_the_bit_of_the_pin() {
+    let mock = Mock::install();
+    let mut led = gpio_pin(&mock, 3);
+
+    led.set_value(true).unwrap();
+    led.set_value(false).unwrap();
+
+    let calls = mock.calls();
+    assert_eq!(
+        mock.call_names(),
+        ["gpio_port_set_bits_raw", "gpio_port_clear_bits_raw"]
+    );
+    assert_eq!(calls[0].args, [1 << 3]);
+    assert_eq!(calls[1].args, [1 << 3]);
+}
+
+#[test]
+fn gpio_set_value_returns_the_error_of_the_syscall() {
+    let mock = Mock::install();
+    let mut led = gpio_pin(&mock, 0);
+
+    mock.returns("gpio_port_set_bits_raw", -5);
+    let error = led.set_value(true).unwrap_err();
+    assert_eq!(error.number(), ErrorNumber::Io);
+}
+
+#[test]
+fn gpio_device_that_is_not_ready_is_rejected() {
+    let mock = Mock::install();
+
+    mock.returns("device_is_ready", 0);
+    let error = TypedDevice::<class::Gpio>::new(mock::device("gpio0")).unwrap_err();
+    assert_eq!(error.number(), ErrorNumber::NoDevice);
+    assert_eq!(mock.call_names(), ["device_is_ready"]);
+}
+
+#[test]
+fn sensor_sample_fetches_and_reads_the_channel() {
+    let mock = Mock::install();
+    let mut sensor = sensor(&mock);
+
+    let temperature = SensorValue::from_micro(21_500_000);
+    mock.set_channel_values(SensorChannel::AmbientTemperature, &[temperature]);
+    let value = sensor.sample(SensorChannel::AmbientTemperature).unwrap();
+
+    assert_eq!(value.to_micro(), 21_500_000);
+    assert_eq!(
+        mock.call_names(),
+        ["sensor_sample_fetch_chan", "sensor_channel_get"]
+    );
+    let channel = SensorChannel::AmbientTemperature as i64;
+    assert!(mock.calls().iter().all(|call| call.args == [channel]));
+}
+
+#[test]
+fn sensor_sample_xyz_reads_all_three_axes() {
+    let mock = Mock::install();
+    let mut sensor = sensor(&mock);
+
+    let values = [
+        SensorValue::from_micro(1_000_000),
+        SensorValue::from_micro(-2_000_000),
+        SensorValue::from_micro(9_810_000),
+    ];
+    mock.set_channel_values(SensorChannel::AccelXYZ, &values);
+    let vector = sensor.sample_xyz(SensorChannel::AccelXYZ).unwrap();
+
+    assert_eq!(vector.x.to_micro(), 1_000_000);
+    assert_eq!(vector.y.to_micro(), -2_000_000);
+    assert_eq!(vector.z.to_micro(), 9_810_000);
+    assert_eq!(
+        mock.call_names(),
+        ["sensor_sample_fetch", "sensor_channel_get"]
+    );
+}
+
+#[test]
+fn sensor_sample_xyz_rejects_other_channels() {
+    let mock = Mock::install();
+    let mut sensor = sensor(&mock);
+
+    let error = sensor.sample_xyz(SensorChannel::AccelX).unwrap_err();
+    assert_eq!(error.number(), ErrorNumber::InvalidArgument);
+}
+
+#[test]
+fn sensor_get_reads_one_value_of_other_channels() {
+    let mock = Mock::install();
+    let mut sensor = sensor(&mock);
+
+    // only the first value fits into the room the wrapper passes for the channel
+    let values = [
+        SensorValue::from_micro(5_000_000),
+        SensorValue::from_micro(6_000_000),
+        SensorValue::from_micro(7_000_000),
+    ];
+    mock.set_channel_values(SensorChannel::Humidity, &values);
+    let value = sensor.get(SensorChannel::Humidity).unwrap();
+
+    assert_eq!(value.to_micro(), 5_000_000);
+}
+
+#[test]
+fn sensor_get_returns_the_error_of_the_syscall() {
+    let mock = Mock::install();
+    let mut sensor = sensor(&mock);
+
+    mock.returns("sensor_channel_get", -134);
+    let error = sensor.get(SensorChannel::Light).unwrap_err();
+    assert_eq!(error.number(), ErrorNumber::NotSupported);
+}
@@ -0,0 +1,63 @@
+//! Host tests of the conversions of [SensorValue].
+#![cfg(feature = "mock-syscalls")]
+
+use zephyr_rust_wrappers::sensor::SensorValue;
+
+const MAX_MICRO: i64 = i32::MAX as i64 * 1_000_000 + 999_999;
+const MIN_MICRO: i64 = i32::MIN as i64 * 1_000_000 - 999_999;
+
+#[test]
+fn from_q31_scales_by_the_shift() {
+    assert_eq!(SensorValue::from_q31(1 << 30, 0).to_micro(), 500_000);
+    assert_eq!(SensorValue::from_q31(1 << 30, 1).to_micro(), 1_000_000);
+    assert_eq!(SensorValue::from_q31(-(1 << 30), 1).to_micro(), -1_000_000);
+}
+
+#[test]
+fn from_q31_saturates_and_rounds_to_zero_at_the_shift_limits() {
+    assert_eq!(
+        SensorValue::from_q31(i32::MAX, i8::MAX).to_micro(),
+        MAX_MICRO
+    );
+    assert_eq!(
+        SensorValue::from_q31(i32::MIN, i8::MAX).to_micro(),
+        MIN_MICRO
+    );
+    assert_eq!(SensorValue::from_q31(i32::MAX, i8::MIN).to_micro(), 0);
+    assert_eq!(SensorValue::from_q31(0, i8::MAX).to_micro(), 0);
+}
+
+#[test]
+fn to_q31_saturates_and_rounds_to_zero_at_the_shift_limits() {
+    assert_eq!(SensorValue::from(1).to_q31(i8::MAX), 0);
+    assert_eq!(SensorValue::from(1).to_q31(i8::MIN), i32::MAX);
+    assert_eq!(SensorValue::from(-1).to_q31(i8::MIN), i32::MIN);
+    assert_eq!(SensorValue::from(0).to_q31(i8::MIN), 0);
+}
+
+#[test]
+fn q31_conversions_accept_every_shift() {
+    let values = [
+        SensorValue::from_micro(MAX_MICRO),
+        SensorValue::from_micro(MIN_MICRO),
+        SensorValue::from_micro(1),
+        SensorValue::from_micro(0),
+    ];
+    for shift in i8::MIN..=i8::MAX {
+        for value in values.iter() {
+            SensorValue::from_q31(value.to_q31(shift), shift);
+        }
+        SensorValue::from_q31(i32::MAX, shift);
+        SensorValue::from_q31(i32::MIN, shift);
+    }
+}
+
+#[test]
+fn q31_conversions_round_trip_with_the_smallest_shift() {
+    let value = SensorValue::from_micro(1_500_000);
+    let shift = value.q31_shift();
+
+    assert_eq!(shift, 1);
+    assert_eq!(value.to_q31(shift), 0x6000_0000);
+    assert_eq!(SensorValue::from_q31(value.to_q31(shift), shift), value);
+}